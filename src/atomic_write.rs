@@ -0,0 +1,407 @@
+//! Atomic Write Module for Space Pklr
+//!
+//! Outputs are written to a temporary sibling file and renamed into place, so readers
+//! only ever see the old file or the complete new one. Temporary files are tracked
+//! while they're in flight; when a run is interrupted (Ctrl-C) they're removed before
+//! exiting with [`EXIT_INTERRUPTED`], so an interrupted run never leaves partial outputs.
+//!
+//! Large outputs can be streamed with [`write_atomic_with`] (and
+//! [`StagedDir::write_with`]), which hand a buffered writer on the temporary file to a
+//! closure instead of taking the whole content, so peak memory doesn't grow with the
+//! size of what is written.
+//!
+//! Multi-file outputs go through a [`StagedDir`]: every artifact is written to a staging
+//! directory and moved into the target only once all of them succeeded. That's
+//! all-or-nothing with rollback on error, not atomic to observers: each file is replaced
+//! atomically, but a reader during the commit can see some files updated and others not.
+
+use std::collections::HashSet;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+use crate::types::CliError;
+
+/// Exit code for runs cancelled by Ctrl-C (128 + SIGINT, as shells report it)
+pub const EXIT_INTERRUPTED: i32 = 130;
+
+/// Temporary files that haven't been renamed into place yet
+static IN_FLIGHT: Mutex<Option<HashSet<PathBuf>>> = Mutex::new(None);
+
+/// Set once the run is interrupted; no new writes start after that
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Numbers the temporary paths of this process, so concurrent writes never share one
+static TEMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+fn track(path: &Path) {
+    if let Ok(mut in_flight) = IN_FLIGHT.lock() {
+        in_flight.get_or_insert_with(HashSet::new).insert(path.to_path_buf());
+    }
+}
+
+fn untrack(path: &Path) {
+    if let Ok(mut in_flight) = IN_FLIGHT.lock() {
+        if let Some(set) = in_flight.as_mut() {
+            set.remove(path);
+        }
+    }
+}
+
+/// Temporary sibling path for `path` (same directory, so the rename stays on one filesystem)
+fn temp_path(path: &Path) -> PathBuf {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!(".{}.spklr-tmp-{}-{}", name, std::process::id(), next_temp_id()))
+}
+
+fn next_temp_id() -> u64 {
+    TEMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Atomically replace `path` with `contents`
+pub async fn write_atomic(path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> Result<(), CliError> {
+    let path = path.as_ref().to_path_buf();
+    let contents = contents.as_ref().to_vec();
+    tokio::task::spawn_blocking(move || write_atomic_sync(&path, &contents))
+        .await
+        .map_err(|e| CliError::Generic(format!("Write task failed: {}", e)))?
+}
+
+/// Atomically replace `path` with `contents` (blocking)
+pub fn write_atomic_sync(path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> Result<(), CliError> {
+    let path = path.as_ref();
+    write_atomic_with(path, |out| {
+        out.write_all(contents.as_ref()).map_err(|e| CliError::IoError {
+            context: format!("Writing file: {}", path.display()),
+            source: e,
+        })
+    })
+}
+
+/// Atomically replace `path` with whatever `write` streams into it
+pub async fn write_atomic_streamed<F>(path: impl AsRef<Path>, write: F) -> Result<(), CliError>
+where
+    F: FnOnce(&mut dyn Write) -> Result<(), CliError> + Send + 'static,
+{
+    let path = path.as_ref().to_path_buf();
+    tokio::task::spawn_blocking(move || write_atomic_with(&path, write))
+        .await
+        .map_err(|e| CliError::Generic(format!("Write task failed: {}", e)))?
+}
+
+/// Atomically replace `path` with whatever `write` streams into it (blocking)
+///
+/// The writer is buffered; the file is only renamed into place if `write` succeeds.
+pub fn write_atomic_with(
+    path: impl AsRef<Path>,
+    write: impl FnOnce(&mut dyn Write) -> Result<(), CliError>,
+) -> Result<(), CliError> {
+    let path = path.as_ref();
+    crate::read_only::ensure_allowed(format!("write {}", path.display()))?;
+    if INTERRUPTED.load(Ordering::SeqCst) {
+        return Err(CliError::Generic(format!("Interrupted before writing {}", path.display())));
+    }
+
+    let temp = temp_path(path);
+    track(&temp);
+
+    let io_err = |e| CliError::IoError {
+        context: format!("Writing file: {}", path.display()),
+        source: e,
+    };
+    let result = (|| {
+        let mut out = BufWriter::new(std::fs::File::create(&temp).map_err(io_err)?);
+        write(&mut out)?;
+        let file = out.into_inner().map_err(|e| io_err(e.into_error()))?;
+        file.sync_all().map_err(io_err)?;
+        std::fs::rename(&temp, path).map_err(io_err)
+    })();
+
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp);
+    }
+    untrack(&temp);
+    result
+}
+
+/// Stop new writes and remove every temporary file still in flight; returns how many were removed
+pub fn cleanup_in_flight() -> usize {
+    INTERRUPTED.store(true, Ordering::SeqCst);
+    let Ok(mut in_flight) = IN_FLIGHT.lock() else {
+        return 0;
+    };
+    let Some(paths) = in_flight.take() else {
+        return 0;
+    };
+    paths
+        .iter()
+        .filter(|path| {
+            if path.is_dir() {
+                std::fs::remove_dir_all(path).is_ok()
+            } else {
+                std::fs::remove_file(path).is_ok()
+            }
+        })
+        .count()
+}
+
+/// Handle Ctrl-C on a dedicated thread: remove in-flight files and exit with [`EXIT_INTERRUPTED`]
+///
+/// Generation and writes run synchronously, so a signal future raced against them on the
+/// same thread would never be polled until they finish. The handler thread has its own
+/// runtime and is registered before this returns, so a Ctrl-C at any later point is seen.
+pub fn install_interrupt_handler() -> Result<(), CliError> {
+    let io_err = |context: &str| {
+        let context = context.to_string();
+        move |e| CliError::IoError { context, source: e }
+    };
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_io()
+        .build()
+        .map_err(io_err("Starting the Ctrl-C handler"))?;
+
+    #[cfg(unix)]
+    let mut interrupts = {
+        let _guard = runtime.enter();
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::interrupt())
+            .map_err(io_err("Registering the Ctrl-C handler"))?
+    };
+
+    std::thread::Builder::new()
+        .name("spklr-ctrl-c".to_string())
+        .spawn(move || {
+            #[cfg(unix)]
+            runtime.block_on(interrupts.recv());
+            #[cfg(not(unix))]
+            let _ = runtime.block_on(tokio::signal::ctrl_c());
+
+            let removed = cleanup_in_flight();
+            eprintln!();
+            eprintln!("🛑 Interrupted; removed {} partially written file(s)", removed);
+            std::process::exit(EXIT_INTERRUPTED);
+        })
+        .map_err(io_err("Starting the Ctrl-C handler"))?;
+    Ok(())
+}
+
+/// Number of temporary files currently in flight
+pub fn in_flight_count() -> usize {
+    IN_FLIGHT
+        .lock()
+        .map(|in_flight| in_flight.as_ref().map_or(0, HashSet::len))
+        .unwrap_or_default()
+}
+
+/// A set of files written together: all of them land in the target directory, or none do
+///
+/// This is all-or-nothing with rollback on error, not an atomic swap: a concurrent reader
+/// can see a mix of old and new files while the commit runs, and a process killed between
+/// renames leaves that mix behind (with the replaced files in the backup directory).
+///
+/// Only the files the run produces are staged, in a hidden directory inside the target so
+/// the final renames stay on one filesystem; whatever else lives in the target is never
+/// read, copied or moved. [`StagedDir::commit`] renames the staged files into place one by
+/// one, moving each file it replaces into a backup directory first and restoring those if a
+/// rename fails. Dropping a `StagedDir` without committing discards the staged files.
+///
+/// Symlinks are never followed: an existing symlink at a destination is replaced by the
+/// generated file (the file it pointed to is left alone), and staged paths that would
+/// write through a symlinked directory or over a directory are refused.
+pub struct StagedDir {
+    target: PathBuf,
+    staging: PathBuf,
+    /// Whether `begin` created the target, so an abandoned run can remove it again
+    created_target: bool,
+    committed: bool,
+}
+
+impl StagedDir {
+    /// Start a transaction for `target`, which need not exist yet
+    pub fn begin(target: impl AsRef<Path>) -> Result<Self, CliError> {
+        crate::read_only::ensure_allowed(format!("write {}", target.as_ref().display()))?;
+        let target = target.as_ref().to_path_buf();
+        let io_err = |context: String| move |e| CliError::IoError { context, source: e };
+
+        let created_target = !target.exists();
+        std::fs::create_dir_all(&target)
+            .map_err(io_err(format!("Creating output directory: {}", target.display())))?;
+
+        let staging = target.join(hidden_name("staging"));
+        if staging.exists() {
+            std::fs::remove_dir_all(&staging)
+                .map_err(io_err(format!("Removing stale staging directory: {}", staging.display())))?;
+        }
+        track(&staging);
+        std::fs::create_dir(&staging)
+            .map_err(io_err(format!("Creating staging directory: {}", staging.display())))?;
+
+        Ok(Self {
+            target,
+            staging,
+            created_target,
+            committed: false,
+        })
+    }
+
+    /// Directory to write staged files into
+    pub fn path(&self) -> &Path {
+        &self.staging
+    }
+
+    /// Stage one file, relative to the target directory
+    pub fn write(&self, name: &str, contents: impl AsRef<[u8]>) -> Result<(), CliError> {
+        self.write_with(name, |out| {
+            out.write_all(contents.as_ref()).map_err(|e| CliError::IoError {
+                context: format!("Writing staged file: {}", self.staging.join(name).display()),
+                source: e,
+            })
+        })
+    }
+
+    /// Stage one file, relative to the target directory, from whatever `write` streams into it
+    pub fn write_with(
+        &self,
+        name: &str,
+        write: impl FnOnce(&mut dyn Write) -> Result<(), CliError>,
+    ) -> Result<(), CliError> {
+        let path = self.staging.join(name);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| CliError::IoError {
+                context: format!("Creating staging directory: {}", parent.display()),
+                source: e,
+            })?;
+        }
+        let io_err = |e| CliError::IoError {
+            context: format!("Writing staged file: {}", path.display()),
+            source: e,
+        };
+        let mut out = BufWriter::new(std::fs::File::create(&path).map_err(io_err)?);
+        write(&mut out)?;
+        out.flush().map_err(io_err)
+    }
+
+    /// Move the staged files into place
+    pub fn commit(mut self) -> Result<(), CliError> {
+        if INTERRUPTED.load(Ordering::SeqCst) {
+            return Err(CliError::Generic(format!(
+                "Interrupted before updating {}",
+                self.target.display()
+            )));
+        }
+
+        let mut files = Vec::new();
+        staged_files(&self.staging, Path::new(""), &mut files).map_err(|e| CliError::IoError {
+            context: format!("Reading staging directory: {}", self.staging.display()),
+            source: e,
+        })?;
+        for name in &files {
+            check_destination(&self.target, name)?;
+        }
+
+        // Backups aren't tracked, so an interrupted commit never loses the previous files
+        let backup = self.target.join(hidden_name("backup"));
+        let mut moved: Vec<(&PathBuf, bool)> = Vec::new();
+        let result = files.iter().try_for_each(|name| {
+            let dest = self.target.join(name);
+            let replaced = dest.symlink_metadata().is_ok();
+            let io_err = |e| CliError::IoError {
+                context: format!("Moving staged file into {}", dest.display()),
+                source: e,
+            };
+            if replaced {
+                let aside = backup.join(name);
+                if let Some(parent) = aside.parent() {
+                    std::fs::create_dir_all(parent).map_err(io_err)?;
+                }
+                std::fs::rename(&dest, &aside).map_err(io_err)?;
+            } else if let Some(parent) = dest.parent() {
+                std::fs::create_dir_all(parent).map_err(io_err)?;
+            }
+            moved.push((name, replaced));
+            std::fs::rename(self.staging.join(name), &dest).map_err(io_err)
+        });
+
+        if let Err(e) = result {
+            // Roll back to the previous contents
+            for (name, replaced) in moved.into_iter().rev() {
+                let dest = self.target.join(name);
+                if !self.staging.join(name).exists() {
+                    let _ = std::fs::remove_file(&dest);
+                }
+                if replaced {
+                    let _ = std::fs::rename(backup.join(name), &dest);
+                }
+            }
+            let _ = std::fs::remove_dir_all(&backup);
+            return Err(e);
+        }
+
+        let _ = std::fs::remove_dir_all(&backup);
+        let _ = std::fs::remove_dir_all(&self.staging);
+        untrack(&self.staging);
+        self.committed = true;
+        Ok(())
+    }
+}
+
+impl Drop for StagedDir {
+    fn drop(&mut self) {
+        if !self.committed {
+            let _ = std::fs::remove_dir_all(&self.staging);
+            untrack(&self.staging);
+            if self.created_target {
+                // Only succeeds if nothing else was put there in the meantime
+                let _ = std::fs::remove_dir(&self.target);
+            }
+        }
+    }
+}
+
+/// Hidden name for a staging or backup directory, unique to this call
+fn hidden_name(purpose: &str) -> String {
+    format!(".spklr-{}-{}-{}", purpose, std::process::id(), next_temp_id())
+}
+
+/// Collect staged files relative to the staging root, without following symlinks
+fn staged_files(dir: &Path, prefix: &Path, files: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = prefix.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            staged_files(&entry.path(), &name, files)?;
+        } else {
+            files.push(name);
+        }
+    }
+    files.sort();
+    Ok(())
+}
+
+/// Refuse destinations that would write through a symlinked directory or over a directory
+fn check_destination(target: &Path, name: &Path) -> Result<(), CliError> {
+    let mut path = target.to_path_buf();
+    let mut components = name.components().peekable();
+    while let Some(component) = components.next() {
+        path.push(component);
+        let Ok(metadata) = path.symlink_metadata() else {
+            // Nothing further down exists yet
+            return Ok(());
+        };
+        let is_last = components.peek().is_none();
+        if !is_last && metadata.file_type().is_symlink() {
+            return Err(CliError::Generic(format!(
+                "Refusing to write {} through the symlinked directory {}",
+                target.join(name).display(),
+                path.display()
+            )));
+        }
+        if is_last && metadata.is_dir() {
+            return Err(CliError::Generic(format!(
+                "Refusing to replace the directory {} with a generated file",
+                path.display()
+            )));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,562 @@
+//! Autofix Module for Space Pklr
+//!
+//! The fixable lint checks, and the edits `spklr lint --fix` makes for them. A policy
+//! opts into each check:
+//!
+//! ```yaml
+//! keyWhitespace: true   # key-whitespace: keys with leading or trailing spaces
+//! requireSchema: true   # missing-schema: YAML and JSON configs without `$schema`
+//! renames:              # deprecated-rename: properties with a 1:1 replacement
+//!   - from: tasks.*.local
+//!     to: preset
+//!     reason: Renamed in moon 1.20
+//! ```
+//!
+//! Fixes edit the file text one line at a time instead of re-rendering the config, so
+//! comments and formatting survive. Keys are found in YAML block mappings and
+//! sequences, JSON, and Pkl object bodies; a key that can't be found (flow-style YAML,
+//! Pkl listing elements) is reported as skipped and left for a manual fix.
+//!
+//! Keys can also be moved to another path (see [`crate::upgrade`]) when their member
+//! fits on one line, in YAML and Pkl. Missing parents of the new path are created under
+//! the nearest one that exists.
+
+use regex::Regex;
+use serde_json::Value;
+use std::path::Path;
+
+use crate::config_path::ConfigPath;
+use crate::config_processor::{pkl_identifier, pkl_string};
+use crate::policy::Policy;
+use crate::types::{MoonConfig, SchemaFormat};
+
+/// Rule id for keys with leading or trailing whitespace
+pub const KEY_WHITESPACE: &str = "key-whitespace";
+/// Rule id for configs without a `$schema`
+pub const MISSING_SCHEMA: &str = "missing-schema";
+/// Rule id for deprecated properties with a 1:1 replacement
+pub const DEPRECATED_RENAME: &str = "deprecated-rename";
+
+/// A problem `spklr lint --fix` can correct
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fix {
+    pub rule: &'static str,
+    /// Path of the offending key; `$schema` for a missing schema
+    pub path: ConfigPath,
+    pub kind: FixKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FixKind {
+    /// Rename the last key of the path
+    RenameKey { to: String },
+    /// Add `$schema` at the top of the file
+    InsertSchema { url: String },
+    /// Move the key, with its one-line value, to another path
+    MoveKey { to: ConfigPath },
+}
+
+impl Fix {
+    /// Lint message for the problem
+    pub fn message(&self) -> String {
+        match &self.kind {
+            FixKind::RenameKey { to } if self.rule == KEY_WHITESPACE => {
+                format!("key {:?} has surrounding whitespace; use {:?}", self.key(), to)
+            }
+            FixKind::RenameKey { to } => format!("`{}` is deprecated; rename it to `{}`", self.key(), to),
+            FixKind::InsertSchema { url } => format!("missing `$schema: {}`", url),
+            FixKind::MoveKey { to } => format!("`{}` has moved to `{}`", self.path, to),
+        }
+    }
+
+    fn key(&self) -> &str {
+        self.path.segments().last().map(String::as_str).unwrap_or_default()
+    }
+}
+
+/// One line changed by a fix
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineEdit {
+    /// 1-based line number in the fixed file
+    pub line: usize,
+    /// The line before the fix; `None` for an inserted line
+    pub before: Option<String>,
+    /// The new line, or lines for a moved key
+    pub after: String,
+}
+
+/// Result of applying fixes to a file
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FixOutcome {
+    pub content: String,
+    pub applied: Vec<(Fix, LineEdit)>,
+    /// Fixes that couldn't be made, with the reason
+    pub skipped: Vec<(Fix, String)>,
+}
+
+impl FixOutcome {
+    pub fn changed(&self) -> bool {
+        !self.applied.is_empty()
+    }
+
+    /// A diff-style preview of the applied fixes
+    pub fn preview(&self, file: &Path) -> String {
+        let mut preview = format!("--- {0}\n+++ {0} (fixed)\n", file.display());
+        for (fix, edit) in &self.applied {
+            preview.push_str(&format!("@@ line {} @@ [{}] {}\n", edit.line, fix.rule, fix.path));
+            if let Some(before) = &edit.before {
+                preview.push_str(&format!("-{}\n", before));
+            }
+            for line in edit.after.lines() {
+                preview.push_str(&format!("+{}\n", line));
+            }
+        }
+        preview
+    }
+}
+
+/// Fixable problems in a config, for the checks the policy enables
+pub fn detect(policy: &Policy, file: &Path, config_type: Option<MoonConfig>, config: &Value) -> Vec<Fix> {
+    let mut fixes = Vec::new();
+
+    if policy.require_schema
+        && let Some(url) = config_type.and_then(schema_url)
+        && matches!(format_of(file), Some(SchemaFormat::Yaml | SchemaFormat::Json | SchemaFormat::Jsonc))
+        && config.get("$schema").is_none()
+    {
+        fixes.push(Fix {
+            rule: MISSING_SCHEMA,
+            path: ConfigPath::new("$schema"),
+            kind: FixKind::InsertSchema { url },
+        });
+    }
+
+    if policy.key_whitespace {
+        untrimmed_keys(config, &ConfigPath::default(), &mut fixes);
+    }
+
+    for rename in &policy.renames {
+        for (path, value) in ConfigPath::new(&rename.from).select(config) {
+            if value.is_null() || path.is_root() {
+                continue;
+            }
+            fixes.push(Fix {
+                rule: DEPRECATED_RENAME,
+                path,
+                kind: FixKind::RenameKey { to: rename.to.clone() },
+            });
+        }
+    }
+    fixes
+}
+
+fn untrimmed_keys(value: &Value, path: &ConfigPath, fixes: &mut Vec<Fix>) {
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map {
+                let child_path = path.child(key.clone());
+                // Fix the children first; the parent's key is still as written until then
+                untrimmed_keys(child, &child_path, fixes);
+                if key.trim() != key && !key.trim().is_empty() {
+                    fixes.push(Fix {
+                        rule: KEY_WHITESPACE,
+                        path: child_path,
+                        kind: FixKind::RenameKey { to: key.trim().to_string() },
+                    });
+                }
+            }
+        }
+        Value::Array(items) => {
+            for (index, item) in items.iter().enumerate() {
+                untrimmed_keys(item, &path.child(index.to_string()), fixes);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// moonrepo's published JSON Schema for a config type
+pub fn schema_url(config_type: MoonConfig) -> Option<String> {
+    let name = match config_type {
+        MoonConfig::Project => "project",
+        MoonConfig::Workspace => "workspace",
+        MoonConfig::Toolchain => "toolchain",
+        MoonConfig::Template => "template",
+        MoonConfig::Task => "tasks",
+        MoonConfig::All => return None,
+    };
+    Some(format!("https://moonrepo.dev/schemas/{}.json", name))
+}
+
+fn format_of(file: &Path) -> Option<SchemaFormat> {
+    crate::config_processor::detect_format_from_path(file).ok()
+}
+
+/// Apply `fixes` to the text of `file`, in order
+pub fn apply(file: &Path, source: &str, fixes: &[Fix]) -> FixOutcome {
+    let format = format_of(file);
+    let mut lines: Vec<String> = source.lines().map(str::to_string).collect();
+    let mut outcome = FixOutcome::default();
+
+    for fix in fixes {
+        let result = match (&fix.kind, &format) {
+            (FixKind::InsertSchema { url }, Some(format)) => insert_schema(&mut lines, format, url),
+            (FixKind::RenameKey { to }, Some(SchemaFormat::Yaml | SchemaFormat::Json | SchemaFormat::Jsonc)) => {
+                rename_key(&mut lines, fix.path.segments(), to, locate_yaml_key, quote_like)
+            }
+            (FixKind::RenameKey { to }, Some(SchemaFormat::Pkl)) => {
+                rename_key(&mut lines, fix.path.segments(), to, locate_pkl_key, pkl_key)
+            }
+            (FixKind::MoveKey { to }, Some(SchemaFormat::Yaml)) => move_key(&mut lines, fix.path.segments(), to, false),
+            (FixKind::MoveKey { to }, Some(SchemaFormat::Pkl)) => move_key(&mut lines, fix.path.segments(), to, true),
+            _ => Err("the file format can't be fixed automatically".to_string()),
+        };
+        match result {
+            Ok(edit) => outcome.applied.push((fix.clone(), edit)),
+            Err(reason) => outcome.skipped.push((fix.clone(), reason)),
+        }
+    }
+
+    outcome.content = lines.join("\n");
+    if source.ends_with('\n') {
+        outcome.content.push('\n');
+    }
+    outcome
+}
+
+/// A key token found on a line: line index and byte range
+type KeyToken = (usize, std::ops::Range<usize>);
+
+fn rename_key(
+    lines: &mut [String],
+    segments: &[String],
+    to: &str,
+    locate: fn(&[String], &[String]) -> Option<KeyToken>,
+    render: fn(&str, &str) -> String,
+) -> Result<LineEdit, String> {
+    let (line, range) = locate(lines, segments).ok_or_else(|| "couldn't find the key in the file".to_string())?;
+    let mut target = segments.to_vec();
+    if let Some(last) = target.last_mut()
+        && last != to
+    {
+        *last = to.to_string();
+        if locate(lines, &target).is_some() {
+            return Err(format!("`{}` is already set", to));
+        }
+    }
+    let before = lines[line].clone();
+    let token = render(&before[range.clone()], to);
+    lines[line].replace_range(range, &token);
+    Ok(LineEdit {
+        line: line + 1,
+        before: Some(before),
+        after: lines[line].clone(),
+    })
+}
+
+/// Move a one-line member to `to`, nesting it under the nearest parent that exists
+///
+/// A member moving to a missing parent of its own parent takes the member's place;
+/// otherwise it goes at the top of the existing parent, or at the end of the file.
+fn move_key(lines: &mut Vec<String>, segments: &[String], to: &ConfigPath, pkl: bool) -> Result<LineEdit, String> {
+    let locate = if pkl { locate_pkl_key } else { locate_yaml_key };
+    let (line, range) = locate(lines, segments).ok_or_else(|| "couldn't find the key in the file".to_string())?;
+    let target = to.segments();
+    if target.is_empty() {
+        return Err("can't move a key to the root".to_string());
+    }
+    if locate(lines, target).is_some() {
+        return Err(format!("`{}` is already set", to));
+    }
+
+    let source = lines[line].clone();
+    let token = &source[range.clone()];
+    let rest = &source[range.end..];
+    let one_line = if pkl {
+        let depths = brace_depths(lines);
+        !rest.trim_end().ends_with('{') && depths.get(line + 1).is_none_or(|&depth| depth == depths[line])
+    } else {
+        let value = rest.trim_start().trim_start_matches(':').trim();
+        let nested = lines[line + 1..]
+            .iter()
+            .find(|next| !next.trim().is_empty() && !next.trim_start().starts_with('#'))
+            .is_some_and(|next| indent_of(next) > indent_of(&source));
+        !value.is_empty() && !value.starts_with(['|', '>']) && !nested
+    };
+    if !one_line {
+        return Err("only members on a single line can be moved".to_string());
+    }
+
+    // The deepest parent of the new path that's already in the file
+    let existing = (0..target.len() - 1)
+        .rev()
+        .find_map(|depth| locate(lines, &target[..=depth]).map(|(parent, _)| (depth + 1, parent)));
+    let (depth, mut at, indent) = match existing {
+        Some((depth, parent)) => {
+            let opener = lines[parent].trim_end();
+            if !(if pkl { opener.ends_with('{') } else { opener.ends_with(':') }) {
+                return Err(format!("`{}` isn't a block the key can move into", target[..depth].join(".")));
+            }
+            (depth, Some(parent + 1), indent_of(&lines[parent]) + 2)
+        }
+        None if segments.len() == 1 => (0, Some(line), indent_of(&source)),
+        None => (0, None, 0),
+    };
+
+    let key = |segment: &str| if pkl { pkl_key(token, segment) } else { quote_like(token, segment) };
+    let missing = &target[depth..];
+    let mut block = Vec::new();
+    for (level, segment) in missing.iter().enumerate() {
+        let pad = " ".repeat(indent + 2 * level);
+        if level + 1 == missing.len() {
+            block.push(format!("{}{}{}", pad, key(segment), rest));
+        } else if pkl {
+            block.push(format!("{}{} {{", pad, key(segment)));
+        } else {
+            block.push(format!("{}{}:", pad, key(segment)));
+        }
+    }
+    if pkl {
+        for level in (0..missing.len() - 1).rev() {
+            block.push(format!("{}}}", " ".repeat(indent + 2 * level)));
+        }
+    }
+
+    lines.remove(line);
+    if let Some(index) = at.as_mut()
+        && *index > line
+    {
+        *index -= 1;
+    }
+    let at = at.unwrap_or(lines.len());
+    lines.splice(at..at, block.iter().cloned());
+    Ok(LineEdit {
+        line: at + 1,
+        before: Some(source),
+        after: block.join("\n"),
+    })
+}
+
+fn insert_schema(lines: &mut Vec<String>, format: &SchemaFormat, url: &str) -> Result<LineEdit, String> {
+    let (line, text) = match format {
+        SchemaFormat::Yaml => {
+            // After any header comments and the document marker
+            let line = lines
+                .iter()
+                .position(|line| {
+                    let line = line.trim();
+                    !(line.is_empty() || line.starts_with('#') || line == "---")
+                })
+                .unwrap_or(lines.len());
+            (line, format!("$schema: '{}'", url))
+        }
+        SchemaFormat::Json | SchemaFormat::Jsonc => {
+            let open = lines
+                .iter()
+                .position(|line| line.trim() == "{")
+                .ok_or_else(|| "the opening `{` isn't on a line of its own".to_string())?;
+            let indent = lines
+                .get(open + 1)
+                .map(|line| &line[..line.len() - line.trim_start().len()])
+                .filter(|indent| !indent.is_empty())
+                .unwrap_or("  ")
+                .to_string();
+            let empty = lines.get(open + 1).is_some_and(|line| line.trim_start().starts_with('}'));
+            let comma = if empty { "" } else { "," };
+            (open + 1, format!("{}\"$schema\": {}{}", indent, Value::String(url.to_string()), comma))
+        }
+        _ => return Err("only YAML and JSON configs have a `$schema`".to_string()),
+    };
+    lines.insert(line, text.clone());
+    Ok(LineEdit {
+        line: line + 1,
+        before: None,
+        after: text,
+    })
+}
+
+/// `to` quoted the way `token` is (YAML and JSON keys)
+fn quote_like(token: &str, to: &str) -> String {
+    if token.starts_with('"') {
+        Value::String(to.to_string()).to_string()
+    } else if token.starts_with('\'') || !is_plain_yaml_key(to) {
+        format!("'{}'", to.replace('\'', "''"))
+    } else {
+        to.to_string()
+    }
+}
+
+fn is_plain_yaml_key(key: &str) -> bool {
+    key.chars().next().is_some_and(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | '$' | '/'))
+}
+
+/// `to` as a Pkl property name or entry key, like `token`
+fn pkl_key(token: &str, to: &str) -> String {
+    if token.starts_with('[') {
+        format!("[{}]", pkl_string(to))
+    } else {
+        pkl_identifier(to)
+    }
+}
+
+fn indent_of(line: &str) -> usize {
+    line.len() - line.trim_start_matches(' ').len()
+}
+
+/// The key at the start of `text` and its length, for a `key:` mapping entry
+fn yaml_key(text: &str) -> Option<(String, usize)> {
+    let (key, len) = match text.chars().next()? {
+        '"' => {
+            let mut escaped = false;
+            let (close, _) = text.char_indices().skip(1).find(|&(_, c)| {
+                let close = c == '"' && !escaped;
+                escaped = c == '\\' && !escaped;
+                close
+            })?;
+            (serde_json::from_str::<String>(&text[..=close]).ok()?, close + 1)
+        }
+        '\'' => {
+            let mut end = 1;
+            loop {
+                end += text[end..].find('\'')?;
+                if text[end + 1..].starts_with('\'') {
+                    end += 2;
+                } else {
+                    break;
+                }
+            }
+            (text[1..end].replace("''", "'"), end + 1)
+        }
+        _ => {
+            let end = text.find(": ").or_else(|| text.strip_suffix(':').map(str::len))?;
+            let key = text[..end].trim_end();
+            (key.to_string(), key.len())
+        }
+    };
+    text[len..].trim_start().starts_with(':').then_some((key, len))
+}
+
+/// Find the key at `segments` in YAML block style (JSON is flow style YAML, and its
+/// keys are found the same way when each member is on a line of its own)
+fn locate_yaml_key(lines: &[String], segments: &[String]) -> Option<KeyToken> {
+    let mut start = 0;
+    // Lines indented at or below this end the current block
+    let mut parent: Option<usize> = None;
+    // Content that starts mid-line, after a `- ` item marker
+    let mut inline: Option<(usize, usize)> = None;
+
+    for (depth, segment) in segments.iter().enumerate() {
+        let mut level = None;
+        let mut item = 0;
+        let mut found = None;
+        for (index, line) in lines.iter().enumerate().skip(start) {
+            let column = match inline {
+                Some((inline_line, column)) if inline_line == index => column,
+                _ => indent_of(line),
+            };
+            let text = line.get(column..).unwrap_or_default();
+            let trimmed = text.trim_start_matches(['{', '[', ' ']);
+            if trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with("//") {
+                continue;
+            }
+            if inline.is_none_or(|(inline_line, _)| inline_line != index) && parent.is_some_and(|parent| column <= parent) {
+                break;
+            }
+            let level = *level.get_or_insert(column);
+            if column != level {
+                continue;
+            }
+            if text == "-" || text.starts_with("- ") {
+                if segment.parse::<usize>().ok() == Some(item) {
+                    found = Some((index, column, None));
+                    break;
+                }
+                item += 1;
+            } else if let Some((key, len)) = yaml_key(text)
+                && key == *segment
+            {
+                found = Some((index, column, Some(len)));
+                break;
+            }
+        }
+        let (index, column, key_len) = found?;
+        match key_len {
+            Some(len) if depth + 1 == segments.len() => return Some((index, column..column + len)),
+            Some(_) => {
+                start = index + 1;
+                parent = Some(column);
+                inline = None;
+            }
+            None => {
+                let content = column + 1 + lines[index][column + 1..].len() - lines[index][column + 1..].trim_start().len();
+                start = index;
+                parent = Some(column);
+                inline = Some((index, content));
+            }
+        }
+    }
+    None
+}
+
+/// Find the member at `segments` in a Pkl module, where each member starts a line
+fn locate_pkl_key(lines: &[String], segments: &[String]) -> Option<KeyToken> {
+    static MEMBER: std::sync::OnceLock<Regex> = std::sync::OnceLock::new();
+    let member = MEMBER.get_or_init(|| {
+        Regex::new(r#"^\s*(?:(?:local|hidden|fixed|const)\s+)*(?P<key>\[\s*"(?:[^"\\]|\\.)*"\s*\]|`[^`]+`|[A-Za-z_$][A-Za-z0-9_$]*)\s*[={:]"#)
+            .unwrap()
+    });
+
+    let depths = brace_depths(lines);
+    let mut start = 0;
+    let mut end = lines.len();
+    for (depth, segment) in segments.iter().enumerate() {
+        let (index, range) = (start..end).find_map(|index| {
+            if depths[index] != depth {
+                return None;
+            }
+            let key = member.captures(&lines[index])?.name("key")?;
+            let name = match key.as_str() {
+                entry if entry.starts_with('[') => {
+                    serde_json::from_str::<String>(entry.trim_start_matches('[').trim_end_matches(']').trim()).ok()?
+                }
+                quoted if quoted.starts_with('`') => quoted.trim_matches('`').to_string(),
+                name => name.to_string(),
+            };
+            (name == *segment).then(|| (index, key.range()))
+        })?;
+        if depth + 1 == segments.len() {
+            return Some((index, range));
+        }
+        start = index + 1;
+        end = (start..lines.len()).find(|&line| depths[line] <= depth).unwrap_or(lines.len());
+    }
+    None
+}
+
+/// Brace depth at the start of each line, ignoring strings and `//` comments
+fn brace_depths(lines: &[String]) -> Vec<usize> {
+    let mut depth: usize = 0;
+    let mut depths = Vec::with_capacity(lines.len());
+    for line in lines {
+        // A closing brace leading the line belongs to the enclosing level
+        let leading_close = line.trim_start().starts_with('}');
+        depths.push(if leading_close { depth.saturating_sub(1) } else { depth });
+        let mut in_string = false;
+        let mut escaped = false;
+        let mut previous = ' ';
+        for c in line.chars() {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' if in_string => escaped = true,
+                '"' => in_string = !in_string,
+                '/' if !in_string && previous == '/' => break,
+                '{' if !in_string => depth += 1,
+                '}' if !in_string => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+            previous = c;
+        }
+    }
+    depths
+}
@@ -0,0 +1,269 @@
+//! Batch Module for Space Pklr
+//!
+//! Converts many configs in one run: `spklr convert 'src/**/moon.yml' --to pkl --out-dir
+//! converted/`. Inputs are files or glob patterns, expanded here so they work even when
+//! the shell doesn't (quoted, or on Windows):
+//!
+//! - `*` matches any run of characters within one path segment, `?` any one character
+//! - `**` matches any number of directories (including none)
+//!
+//! Inputs are loaded the way a single `--input` is: YAML, JSON and TOML are read as text,
+//! and Pkl is evaluated with the Pkl CLI (see
+//! [`load_config_value`](crate::config_processor::load_config_value)). Files in any other
+//! format fail. The loaded files are then converted in parallel (see
+//! [`crate::pkl_schema::parallel`]) with [`convert_str`](crate::convert::convert_str), which
+//! validates each one as its config type (from `--config-type`, or guessed from the file
+//! name). A file that fails doesn't stop the others: every outcome is collected for the
+//! summary, and each output keeps its input's path under the output directory, with the
+//! target format's extension. Files that don't validate aren't written, and existing
+//! outputs are only replaced with `--force`.
+
+use serde::Serialize;
+use std::path::{Component, Path, PathBuf};
+
+use crate::config_processor::{detect_format_from_path, load_config_value};
+use crate::convert::convert_str;
+use crate::policy::{Severity, infer_config_type};
+use crate::types::{CliError, MoonConfig, SchemaFormat, ensure_output_writable};
+
+/// Directories never searched for `**`
+const SKIPPED_DIRS: &[&str] = &[".git", "node_modules", "target"];
+
+/// Result of converting one file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FileOutcome {
+    pub input: PathBuf,
+    /// Where the converted file was written, if it was
+    pub output: Option<PathBuf>,
+    pub config_type: Option<String>,
+    /// Read, parse, validation, conversion, and write errors
+    pub errors: Vec<String>,
+    /// Values that didn't survive conversion unchanged
+    pub warnings: usize,
+}
+
+impl FileOutcome {
+    pub fn passed(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+/// The files named by `inputs`: plain paths as given, and the sorted matches of each
+/// glob pattern, without duplicates
+pub fn expand(inputs: &[String]) -> Result<Vec<PathBuf>, CliError> {
+    let mut files = Vec::new();
+    for input in inputs {
+        if !is_pattern(input) {
+            files.push(PathBuf::from(input));
+            continue;
+        }
+        let segments: Vec<&str> = input.split(['/', '\\']).collect();
+        let literal = segments.iter().take_while(|segment| !is_pattern(segment)).count();
+        let base: PathBuf = if literal == 0 {
+            PathBuf::from(".")
+        } else if segments[0].is_empty() {
+            // An absolute pattern: `/` starts the base
+            PathBuf::from(format!("/{}", segments[1..literal].join("/")))
+        } else {
+            segments[..literal].iter().collect()
+        };
+        let mut matches = Vec::new();
+        walk(&base, &segments[literal..], &mut matches);
+        if matches.is_empty() {
+            return Err(CliError::Generic(format!("No files match {}", input)));
+        }
+        matches.sort();
+        files.extend(matches);
+    }
+    let mut seen = std::collections::BTreeSet::new();
+    files.retain(|file| seen.insert(file.clone()));
+    Ok(files)
+}
+
+fn is_pattern(text: &str) -> bool {
+    text.contains(['*', '?'])
+}
+
+/// Collect the files under `dir` whose path below it matches `pattern`
+fn walk(dir: &Path, pattern: &[&str], matches: &mut Vec<PathBuf>) {
+    let Some((first, rest)) = pattern.split_first() else {
+        return;
+    };
+    if *first == "**" {
+        walk(dir, rest, matches);
+    }
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let path = if dir == Path::new(".") { PathBuf::from(&name) } else { dir.join(&name) };
+        let is_dir = entry.file_type().is_ok_and(|file_type| file_type.is_dir());
+        if *first == "**" {
+            if is_dir && !SKIPPED_DIRS.contains(&name.as_str()) {
+                walk(&path, pattern, matches);
+            }
+        } else if matches_segment(first, &name) {
+            if rest.is_empty() {
+                if !is_dir {
+                    matches.push(path);
+                }
+            } else if is_dir {
+                walk(&path, rest, matches);
+            }
+        }
+    }
+}
+
+/// Whether a file name matches one segment of a pattern, with `*` and `?` wildcards
+pub fn matches_segment(pattern: &str, name: &str) -> bool {
+    fn matches(pattern: &[char], name: &[char]) -> bool {
+        match pattern.split_first() {
+            None => name.is_empty(),
+            Some(('*', rest)) => (0..=name.len()).any(|skip| matches(rest, &name[skip..])),
+            Some(('?', rest)) => !name.is_empty() && matches(rest, &name[1..]),
+            Some((expected, rest)) => name.first() == Some(expected) && matches(rest, &name[1..]),
+        }
+    }
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    matches(&pattern, &name)
+}
+
+/// Where `input` goes under `out_dir`: its relative path, with `to`'s extension
+///
+/// Absolute inputs and `..` segments lose their root and parents, so every output stays
+/// inside `out_dir`.
+pub fn output_path(input: &Path, out_dir: &Path, to: &SchemaFormat) -> PathBuf {
+    let relative: PathBuf = input
+        .components()
+        .filter(|component| matches!(component, Component::Normal(_)))
+        .collect();
+    out_dir.join(relative).with_extension(extension(to))
+}
+
+fn extension(format: &SchemaFormat) -> &'static str {
+    match format {
+        SchemaFormat::Pkl => "pkl",
+        SchemaFormat::Json => "json",
+        SchemaFormat::Jsonc => "jsonc",
+        SchemaFormat::Yaml => "yml",
+        SchemaFormat::Toml => "toml",
+        SchemaFormat::Typescript => "ts",
+    }
+}
+
+/// One input as text to convert: its own content, or for Pkl the evaluated config as JSON
+async fn load_source(input: &Path) -> Result<(String, SchemaFormat), CliError> {
+    let format = detect_format_from_path(input)?;
+    if format == SchemaFormat::Pkl {
+        let value = load_config_value(input, Some(format)).await?;
+        let json = serde_json::to_string(&value)
+            .map_err(|e| CliError::Generic(format!("Serializing {}: {}", input.display(), e)))?;
+        return Ok((json, SchemaFormat::Json));
+    }
+    let content = tokio::fs::read_to_string(input).await.map_err(|e| CliError::IoError {
+        context: format!("Reading {}", input.display()),
+        source: e,
+    })?;
+    Ok((content, format))
+}
+
+/// Convert one loaded file to `to`, writing it under `out_dir` when given and it passed
+///
+/// An existing output is only replaced when `force` is set.
+pub fn convert_file(
+    input: &Path,
+    source: &Result<(String, SchemaFormat), CliError>,
+    config_type: Option<MoonConfig>,
+    to: &SchemaFormat,
+    out_dir: Option<&Path>,
+    force: bool,
+) -> FileOutcome {
+    let config_type = config_type.or_else(|| infer_config_type(input));
+    let mut outcome = FileOutcome {
+        input: input.to_path_buf(),
+        output: None,
+        config_type: config_type.map(|config_type| config_type.to_string()),
+        errors: Vec::new(),
+        warnings: 0,
+    };
+    let Some(config_type) = config_type else {
+        outcome
+            .errors
+            .push("Can't tell the config type from the file name; pass --config-type".to_string());
+        return outcome;
+    };
+
+    let converted = match source {
+        Ok((content, from)) => convert_str(content, from.clone(), to.clone(), config_type).map_err(|e| e.to_string()),
+        Err(error) => Err(error.to_string()),
+    };
+    let converted = match converted {
+        Ok(converted) => converted,
+        Err(error) => {
+            outcome.errors.push(error);
+            return outcome;
+        }
+    };
+    for diagnostic in &converted.diagnostics {
+        match diagnostic.severity {
+            Severity::Error => outcome.errors.push(diagnostic.message.clone()),
+            Severity::Warning => outcome.warnings += 1,
+        }
+    }
+
+    // Only configs that validated are written, so the output directory can be used as is
+    if let Some(out_dir) = out_dir.filter(|_| outcome.passed()) {
+        let output = output_path(input, out_dir, to);
+        match write(&output, &converted.output, force) {
+            Ok(()) => outcome.output = Some(output),
+            Err(error) => outcome.errors.push(error.to_string()),
+        }
+    }
+    outcome
+}
+
+fn write(path: &Path, content: &str, force: bool) -> Result<(), CliError> {
+    ensure_output_writable(&path.to_path_buf(), force)?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| CliError::IoError {
+            context: format!("Creating output directory: {}", parent.display()),
+            source: e,
+        })?;
+    }
+    crate::atomic_write::write_atomic_sync(path, content)
+}
+
+/// Convert every file on up to `concurrency` threads, in input order
+///
+/// All inputs are loaded first, Pkl evaluations running concurrently; with `out_dir`,
+/// it stays locked while the outputs are written.
+pub async fn convert_files(
+    inputs: &[PathBuf],
+    config_type: Option<MoonConfig>,
+    to: &SchemaFormat,
+    out_dir: Option<&Path>,
+    force: bool,
+    concurrency: Option<usize>,
+) -> Result<Vec<FileOutcome>, CliError> {
+    let _lock = out_dir.map(crate::lock::PathLock::acquire).transpose()?;
+
+    let mut loading = tokio::task::JoinSet::new();
+    for (index, input) in inputs.iter().cloned().enumerate() {
+        loading.spawn(async move { (index, load_source(&input).await) });
+    }
+    let mut sources: Vec<Option<Result<(String, SchemaFormat), CliError>>> = inputs.iter().map(|_| None).collect();
+    while let Some(loaded) = loading.join_next().await {
+        let (index, source) = loaded.map_err(|e| CliError::Generic(format!("Loading task failed: {}", e)))?;
+        sources[index] = Some(source);
+    }
+
+    // Every task completed, so every input has its source
+    let sources: Vec<_> = sources.into_iter().flatten().collect();
+    let work: Vec<_> = inputs.iter().zip(&sources).collect();
+    let outcomes = crate::pkl_schema::parallel::map(&work, concurrency, |(input, source)| {
+        convert_file(input, source, config_type, to, out_dir, force)
+    });
+    Ok(outcomes)
+}
@@ -0,0 +1,167 @@
+//! Checksums Module for Space Pklr
+//!
+//! Every generation run records a `SHA256SUMS` manifest (the `sha256sum` format,
+//! `<hex>  <file>` per line) next to what it wrote, and optionally an unsigned in-toto
+//! statement describing the run. `spklr check --verify-checksums` compares committed
+//! files against the manifest, so hand edits to "generated, do not edit" schemas fail CI.
+
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::download::sha256_hex;
+use crate::types::CliError;
+
+/// Manifest file name written alongside generated outputs
+pub const CHECKSUM_FILE: &str = "SHA256SUMS";
+
+/// in-toto statement file name written with `--attestation`
+pub const ATTESTATION_FILE: &str = "spklr.intoto.json";
+
+/// `predicateType` for spklr generation attestations
+const GENERATION_PREDICATE: &str = "https://github.com/knitli/space-pklr/attestation/generation/v1";
+
+/// SHA-256 digests of generated files, keyed by path relative to the manifest
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChecksumManifest {
+    pub entries: BTreeMap<String, String>,
+}
+
+/// Result of comparing files on disk against a manifest
+#[derive(Debug, Clone, Default)]
+pub struct ChecksumReport {
+    pub verified: Vec<String>,
+    /// Files whose contents no longer match their recorded digest
+    pub modified: Vec<String>,
+    /// Files listed in the manifest that no longer exist
+    pub missing: Vec<String>,
+}
+
+impl ChecksumReport {
+    pub fn is_ok(&self) -> bool {
+        self.modified.is_empty() && self.missing.is_empty()
+    }
+
+    pub fn failure_count(&self) -> usize {
+        self.modified.len() + self.missing.len()
+    }
+}
+
+impl ChecksumManifest {
+    /// Parse `sha256sum`-style content; binary-mode markers (`*file`) are accepted
+    pub fn parse(content: &str) -> Self {
+        let entries = content
+            .lines()
+            .filter_map(|line| {
+                let (digest, name) = line.trim_end().split_once(char::is_whitespace)?;
+                let name = name.trim_start().trim_start_matches('*');
+                (!name.is_empty()).then(|| (name.to_string(), digest.to_lowercase()))
+            })
+            .collect();
+        Self { entries }
+    }
+
+    /// Render in `sha256sum` format, sorted by file name
+    pub fn render(&self) -> String {
+        self.entries
+            .iter()
+            .map(|(name, digest)| format!("{}  {}\n", digest, name))
+            .collect()
+    }
+
+    /// Load the manifest in `dir`, or an empty one if there isn't one yet
+    pub fn load(dir: &Path) -> Result<Self, CliError> {
+        let path = dir.join(CHECKSUM_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(&path).map_err(|e| CliError::IoError {
+            context: format!("Reading checksum manifest: {}", path.display()),
+            source: e,
+        })?;
+        Ok(Self::parse(&content))
+    }
+
+    /// Record the current digest of each file (relative to `dir`)
+    pub fn record(&mut self, dir: &Path, names: &[String]) -> Result<(), CliError> {
+        for name in names {
+            let path = dir.join(name);
+            let bytes = std::fs::read(&path).map_err(|e| CliError::IoError {
+                context: format!("Reading generated file for checksum: {}", path.display()),
+                source: e,
+            })?;
+            self.entries.insert(name.clone(), sha256_hex(&bytes));
+        }
+        Ok(())
+    }
+
+    /// Write the manifest to `dir`
+    pub fn write(&self, dir: &Path) -> Result<PathBuf, CliError> {
+        let path = dir.join(CHECKSUM_FILE);
+        crate::atomic_write::write_atomic_sync(&path, self.render())?;
+        Ok(path)
+    }
+
+    /// Compare the files in `dir` against the manifest
+    pub fn verify(&self, dir: &Path) -> ChecksumReport {
+        let mut report = ChecksumReport::default();
+        for (name, expected) in &self.entries {
+            match std::fs::read(dir.join(name)) {
+                Ok(bytes) if sha256_hex(&bytes) == *expected => report.verified.push(name.clone()),
+                Ok(_) => report.modified.push(name.clone()),
+                Err(_) => report.missing.push(name.clone()),
+            }
+        }
+        report
+    }
+
+    /// Unsigned in-toto v1 statement covering the listed files
+    pub fn in_toto_statement(&self, names: &[String], predicate: Value) -> Value {
+        let subject: Vec<Value> = names
+            .iter()
+            .filter_map(|name| {
+                let digest = self.entries.get(name)?;
+                Some(json!({ "name": name, "digest": { "sha256": digest } }))
+            })
+            .collect();
+
+        json!({
+            "_type": "https://in-toto.io/Statement/v1",
+            "subject": subject,
+            "predicateType": GENERATION_PREDICATE,
+            "predicate": predicate,
+        })
+    }
+}
+
+/// Record a generation run: update `SHA256SUMS` in `dir` and optionally write an attestation
+///
+/// `names` are the files the run wrote, relative to `dir`. Existing entries for other files are kept,
+/// so several runs into one directory share a manifest.
+pub fn record_generation(
+    dir: &Path,
+    names: &[String],
+    attestation: bool,
+    invocation: Value,
+) -> Result<(), CliError> {
+    let mut manifest = ChecksumManifest::load(dir)?;
+    manifest.record(dir, names)?;
+    manifest.write(dir)?;
+
+    if attestation {
+        let predicate = json!({
+            "generator": {
+                "name": env!("CARGO_PKG_NAME"),
+                "version": env!("CARGO_PKG_VERSION"),
+            },
+            "invocation": invocation,
+        });
+        let statement = manifest.in_toto_statement(names, predicate);
+        let path = dir.join(ATTESTATION_FILE);
+        let content = serde_json::to_string_pretty(&statement)
+            .map_err(|e| CliError::Generic(format!("Failed to serialize attestation: {}", e)))?;
+        crate::atomic_write::write_atomic_sync(&path, content)?;
+    }
+
+    Ok(())
+}
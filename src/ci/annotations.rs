@@ -0,0 +1,254 @@
+//! CI annotation output for Space Pklr
+//!
+//! Surfaces validation and drift findings natively in CI systems:
+//!
+//! - `github`: workflow commands (`::error file=...::message`) on stdout
+//! - `buildkite`: a Markdown annotation posted with `buildkite-agent annotate`, or
+//!   written to `spklr-annotation.md` for upload as an artifact outside an agent
+//! - `gitlab`: a Code Quality report (`gl-code-quality-report.json`) for the
+//!   `artifacts:reports:codequality` key
+
+use std::fmt::{Display, Write as _};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use serde::Serialize;
+use serde_json::json;
+
+use crate::policy::{PolicyViolation, Severity};
+use crate::types::CliError;
+
+/// Buildkite annotation artifact written when `buildkite-agent` isn't available
+pub const BUILDKITE_ANNOTATION_FILE: &str = "spklr-annotation.md";
+
+/// GitLab Code Quality report file
+pub const GITLAB_CODE_QUALITY_FILE: &str = "gl-code-quality-report.json";
+
+/// How findings are reported
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Human-readable text
+    #[default]
+    Text,
+    /// Structured JSON on stdout
+    Json,
+    /// GitHub Actions workflow commands
+    Github,
+    /// Buildkite annotation
+    Buildkite,
+    /// GitLab Code Quality report
+    Gitlab,
+}
+
+impl FromStr for OutputFormat {
+    type Err = CliError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "text" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            "github" | "gha" => Ok(OutputFormat::Github),
+            "buildkite" | "bk" => Ok(OutputFormat::Buildkite),
+            "gitlab" | "codequality" => Ok(OutputFormat::Gitlab),
+            _ => Err(CliError::UnsupportedFormat {
+                format: s.to_string(),
+                available: vec!["text", "json", "github", "buildkite", "gitlab"],
+            }),
+        }
+    }
+}
+
+impl Display for OutputFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OutputFormat::Text => write!(f, "text"),
+            OutputFormat::Json => write!(f, "json"),
+            OutputFormat::Github => write!(f, "github"),
+            OutputFormat::Buildkite => write!(f, "buildkite"),
+            OutputFormat::Gitlab => write!(f, "gitlab"),
+        }
+    }
+}
+
+/// One problem to report against a file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Finding {
+    pub file: PathBuf,
+    pub severity: Severity,
+    /// Rule or check that produced the finding
+    pub rule: String,
+    pub message: String,
+}
+
+impl From<&PolicyViolation> for Finding {
+    fn from(violation: &PolicyViolation) -> Self {
+        Self {
+            file: violation.file.clone(),
+            severity: violation.severity,
+            rule: violation.rule.clone(),
+            message: format!("{}: {}", violation.path, violation.message),
+        }
+    }
+}
+
+/// Report findings for `command` in a CI-native format (text and JSON are the caller's)
+pub fn emit(format: OutputFormat, command: &str, findings: &[Finding]) -> Result<(), CliError> {
+    match format {
+        OutputFormat::Text | OutputFormat::Json => Ok(()),
+        OutputFormat::Github => {
+            print!("{}", render_github(findings));
+            Ok(())
+        }
+        OutputFormat::Buildkite => annotate_buildkite(command, findings),
+        OutputFormat::Gitlab => {
+            let report = render_gitlab(findings)?;
+            if crate::read_only::is_enabled() {
+                println!("{}", report);
+                return Ok(());
+            }
+            crate::atomic_write::write_atomic_sync(std::path::Path::new(GITLAB_CODE_QUALITY_FILE), report)?;
+            println!("📋 Code Quality report written to {}", GITLAB_CODE_QUALITY_FILE);
+            Ok(())
+        }
+    }
+}
+
+/// GitHub Actions workflow commands
+pub fn render_github(findings: &[Finding]) -> String {
+    let mut out = String::new();
+    for finding in findings {
+        let level = match finding.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        let _ = writeln!(
+            out,
+            "::{} file={},title={}::{}",
+            level,
+            escape_github_property(&finding.file.to_string_lossy()),
+            escape_github_property(&format!("spklr {}", finding.rule)),
+            escape_github_data(&finding.message)
+        );
+    }
+    out
+}
+
+fn escape_github_data(value: &str) -> String {
+    value.replace('%', "%25").replace('\r', "%0D").replace('\n', "%0A")
+}
+
+fn escape_github_property(value: &str) -> String {
+    escape_github_data(value).replace(':', "%3A").replace(',', "%2C")
+}
+
+/// Markdown body of a Buildkite annotation
+pub fn render_buildkite(command: &str, findings: &[Finding]) -> String {
+    let errors = findings.iter().filter(|f| f.severity == Severity::Error).count();
+    let warnings = findings.len() - errors;
+
+    if findings.is_empty() {
+        return format!("**spklr {}**: all checks passed\n", command);
+    }
+
+    format!(
+        "**spklr {}**: {} error(s), {} warning(s)\n\n{}",
+        command,
+        errors,
+        warnings,
+        render_findings_table(findings)
+    )
+}
+
+/// Markdown table of findings, shared by annotations and job summaries
+pub fn render_findings_table(findings: &[Finding]) -> String {
+    let mut out = String::from("| | File | Rule | Message |\n| --- | --- | --- | --- |\n");
+    for finding in findings {
+        let icon = match finding.severity {
+            Severity::Error => "❌",
+            Severity::Warning => "⚠️",
+        };
+        let _ = writeln!(
+            out,
+            "| {} | `{}` | `{}` | {} |",
+            icon,
+            finding.file.display(),
+            finding.rule,
+            finding.message.replace('|', "\\|").replace('\n', " ")
+        );
+    }
+    out
+}
+
+/// Post the annotation through `buildkite-agent`, or leave it as an artifact
+fn annotate_buildkite(command: &str, findings: &[Finding]) -> Result<(), CliError> {
+    use std::io::Write;
+
+    let body = render_buildkite(command, findings);
+    if crate::read_only::is_enabled() {
+        println!("{}", body);
+        return Ok(());
+    }
+
+    let style = if findings.iter().any(|f| f.severity == Severity::Error) {
+        "error"
+    } else if findings.is_empty() {
+        "success"
+    } else {
+        "warning"
+    };
+
+    let agent = which::which("buildkite-agent").ok().filter(|_| std::env::var_os("BUILDKITE").is_some());
+    let Some(agent) = agent else {
+        crate::atomic_write::write_atomic_sync(std::path::Path::new(BUILDKITE_ANNOTATION_FILE), body)?;
+        println!("📋 Buildkite annotation written to {}", BUILDKITE_ANNOTATION_FILE);
+        return Ok(());
+    };
+
+    let mut child = std::process::Command::new(agent)
+        .args(["annotate", "--style", style, "--context", &format!("spklr-{}", command)])
+        .stdin(std::process::Stdio::piped())
+        .spawn()
+        .map_err(|e| CliError::IoError {
+            context: "Running buildkite-agent annotate".to_string(),
+            source: e,
+        })?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(body.as_bytes()).map_err(|e| CliError::IoError {
+            context: "Sending annotation to buildkite-agent".to_string(),
+            source: e,
+        })?;
+    }
+    let status = child.wait().map_err(|e| CliError::IoError {
+        context: "Running buildkite-agent annotate".to_string(),
+        source: e,
+    })?;
+    if !status.success() {
+        tracing::warn!("buildkite-agent annotate exited with {}", status);
+    }
+    Ok(())
+}
+
+/// GitLab Code Quality report (a JSON array of issues)
+pub fn render_gitlab(findings: &[Finding]) -> Result<String, CliError> {
+    let issues: Vec<_> = findings
+        .iter()
+        .map(|finding| {
+            let path = finding.file.to_string_lossy().replace('\\', "/");
+            let fingerprint = crate::download::sha256_hex(
+                format!("{}\0{}\0{}", finding.rule, path, finding.message).as_bytes(),
+            );
+            json!({
+                "description": finding.message,
+                "check_name": finding.rule,
+                "fingerprint": fingerprint,
+                "severity": match finding.severity {
+                    Severity::Error => "major",
+                    Severity::Warning => "minor",
+                },
+                "location": { "path": path, "lines": { "begin": 1 } },
+            })
+        })
+        .collect();
+    serde_json::to_string_pretty(&issues)
+        .map_err(|e| CliError::Generic(format!("Failed to serialize Code Quality report: {}", e)))
+}
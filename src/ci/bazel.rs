@@ -0,0 +1,108 @@
+//! Bazel/Buck rules for Space Pklr
+//!
+//! Renders a `spklr.bzl` with `spklr_schema` and `spklr_convert` rules. Both run spklr
+//! with `--hermetic`, so every input and output is declared on the action and the Pkl
+//! CLI comes from a label via `SPKLR_PKL_PATH` rather than PATH. The rules only use the
+//! Starlark subset shared by Bazel and Buck2.
+
+use crate::hermetic::PKL_PATH_ENV;
+
+pub const DEFAULT_SPKLR_LABEL: &str = "@spklr//:spklr";
+pub const DEFAULT_PKL_LABEL: &str = "@pkl//:pkl";
+
+/// Labels the generated rules point at
+#[derive(Debug, Clone)]
+pub struct BazelRuleOptions {
+    /// Label of the spklr binary
+    pub spklr_label: String,
+    /// Label of the Pkl CLI
+    pub pkl_label: String,
+}
+
+impl Default for BazelRuleOptions {
+    fn default() -> Self {
+        Self {
+            spklr_label: DEFAULT_SPKLR_LABEL.to_string(),
+            pkl_label: DEFAULT_PKL_LABEL.to_string(),
+        }
+    }
+}
+
+/// Render the `spklr.bzl` rules file
+pub fn render_rules(options: &BazelRuleOptions) -> String {
+    format!(
+        r#"# Generated by spklr {version}. Regenerate with `spklr ci bazel`.
+#
+# Usage:
+#   load("//tools:spklr.bzl", "spklr_convert", "spklr_schema")
+#
+#   spklr_schema(name = "project_schema", config_type = "project", format = "json-schema")
+#   spklr_convert(name = "moon_json", src = "moon.yml", config_type = "project", to = "json")
+
+_TOOL_ATTRS = {{
+    "_spklr": attr.label(default = "{spklr}", executable = True, cfg = "exec"),
+    "_pkl": attr.label(default = "{pkl}", executable = True, cfg = "exec", allow_single_file = True),
+}}
+
+_SCHEMA_EXTENSIONS = {{"json-schema": "schema.json", "typescript": "d.ts", "pkl": "pkl"}}
+
+def _run_spklr(ctx, inputs, output, arguments, mnemonic):
+    pkl = ctx.file._pkl
+    ctx.actions.run(
+        executable = ctx.executable._spklr,
+        arguments = ["--hermetic"] + arguments + ["--output", output.path],
+        inputs = inputs,
+        tools = [pkl],
+        outputs = [output],
+        env = {{"{pkl_env}": pkl.path}},
+        mnemonic = mnemonic,
+        progress_message = "spklr %s %s" % (mnemonic, output.short_path),
+    )
+
+def _spklr_schema_impl(ctx):
+    extension = _SCHEMA_EXTENSIONS[ctx.attr.format]
+    out = ctx.actions.declare_file("%s.%s" % (ctx.attr.name, extension))
+    _run_spklr(
+        ctx,
+        inputs = [],
+        output = out,
+        arguments = ["generate", "schema", "--config-type", ctx.attr.config_type, "--format", ctx.attr.format],
+        mnemonic = "SpklrSchema",
+    )
+    return [DefaultInfo(files = depset([out]))]
+
+spklr_schema = rule(
+    implementation = _spklr_schema_impl,
+    attrs = dict(_TOOL_ATTRS, **{{
+        "config_type": attr.string(mandatory = True, values = ["project", "workspace", "template", "toolchain", "task"]),
+        "format": attr.string(default = "json-schema", values = _SCHEMA_EXTENSIONS.keys()),
+    }}),
+)
+
+def _spklr_convert_impl(ctx):
+    extension = "yml" if ctx.attr.to == "yaml" else ctx.attr.to
+    out = ctx.actions.declare_file("%s.%s" % (ctx.attr.name, extension))
+    _run_spklr(
+        ctx,
+        inputs = [ctx.file.src],
+        output = out,
+        arguments = ["convert", "--config-type", ctx.attr.config_type, "--input", ctx.file.src.path, "--to", ctx.attr.to, "--force"],
+        mnemonic = "SpklrConvert",
+    )
+    return [DefaultInfo(files = depset([out]))]
+
+spklr_convert = rule(
+    implementation = _spklr_convert_impl,
+    attrs = dict(_TOOL_ATTRS, **{{
+        "src": attr.label(mandatory = True, allow_single_file = [".yml", ".yaml", ".json", ".pkl"]),
+        "config_type": attr.string(mandatory = True, values = ["project", "workspace", "template", "toolchain", "task"]),
+        "to": attr.string(default = "json", values = ["json", "yaml", "pkl"]),
+    }}),
+)
+"#,
+        version = env!("CARGO_PKG_VERSION"),
+        spklr = options.spklr_label,
+        pkl = options.pkl_label,
+        pkl_env = PKL_PATH_ENV,
+    )
+}
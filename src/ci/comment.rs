@@ -0,0 +1,179 @@
+//! Sticky PR comments for Space Pklr
+//!
+//! Posts run summaries (from `--summary`) as a single pull request comment that later
+//! runs update in place. The comment is found again by a hidden HTML marker, so each
+//! PR carries one spklr comment per marker key rather than one per push. Only comments
+//! posted by the token's own account count, so a reviewer quoting the bot's comment
+//! isn't mistaken for it.
+
+use serde_json::Value;
+
+use crate::network::RetryPolicy;
+use crate::types::CliError;
+
+pub const DEFAULT_API_URL: &str = "https://api.github.com";
+
+/// Hidden marker identifying the sticky comment for `key`
+pub fn sticky_marker(key: &str) -> String {
+    format!("<!-- spklr:sticky-comment:{} -->", key)
+}
+
+/// Comment body: the marker followed by each summary
+pub fn render_comment_body(marker: &str, summaries: &[String]) -> String {
+    let mut body = format!("{}\n", marker);
+    for summary in summaries {
+        body.push_str(summary.trim_end());
+        body.push_str("\n\n");
+    }
+    body.truncate(body.trim_end().len());
+    body.push('\n');
+    body
+}
+
+/// Id of the first comment starting with `marker` that was posted by `author`
+///
+/// `author` is the token's login; `None` (tokens that can't look themselves up, like
+/// Actions' `GITHUB_TOKEN`) matches comments by any bot account.
+pub fn find_sticky_comment(comments: &[Value], marker: &str, author: Option<&str>) -> Option<u64> {
+    comments
+        .iter()
+        .find(|comment| {
+            let user = &comment["user"];
+            let posted_by_author = match author {
+                Some(login) => user["login"].as_str() == Some(login),
+                None => user["type"].as_str() == Some("Bot"),
+            };
+            posted_by_author && comment["body"].as_str().is_some_and(|body| body.starts_with(marker))
+        })
+        .and_then(|comment| comment["id"].as_u64())
+}
+
+/// PR number from a `refs/pull/<n>/merge` (or `/head`) ref
+pub fn parse_pr_number(github_ref: &str) -> Option<u64> {
+    github_ref.strip_prefix("refs/pull/")?.split('/').next()?.parse().ok()
+}
+
+/// PR number from the GitHub Actions environment
+pub fn pr_number_from_env() -> Option<u64> {
+    if let Some(number) = std::env::var("GITHUB_REF").ok().as_deref().and_then(parse_pr_number) {
+        return Some(number);
+    }
+    // pull_request_target and friends run on the base ref; the event payload has the number
+    let event = std::fs::read_to_string(std::env::var_os("GITHUB_EVENT_PATH")?).ok()?;
+    let event: Value = serde_json::from_str(&event).ok()?;
+    event["pull_request"]["number"]
+        .as_u64()
+        .or_else(|| event["number"].as_u64())
+}
+
+/// Whether a comment was created or updated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommentAction {
+    Created(u64),
+    Updated(u64),
+}
+
+/// Create the sticky comment, or update it if an earlier run posted one
+///
+/// Each API request is retried on transient failures per `policy`.
+#[cfg(feature = "network")]
+pub async fn post_sticky_comment(
+    api_url: &str,
+    token: &str,
+    repo: &str,
+    pr: u64,
+    marker: &str,
+    body: &str,
+    policy: &RetryPolicy,
+) -> Result<CommentAction, CliError> {
+    crate::read_only::ensure_allowed(format!("comment on {}#{}", repo, pr))?;
+    let api_url = api_url.trim_end_matches('/');
+    let client = policy.client()?;
+    let request = |method: reqwest::Method, url: String| {
+        client
+            .request(method, url)
+            .bearer_auth(token)
+            .header(reqwest::header::ACCEPT, "application/vnd.github+json")
+    };
+
+    // Installation tokens can't read `/user`; their comments are posted by a bot account
+    let author = match request(reqwest::Method::GET, format!("{}/user", api_url)).send().await {
+        Ok(response) if response.status().is_success() => response
+            .json::<Value>()
+            .await
+            .ok()
+            .and_then(|user| user["login"].as_str().map(str::to_string)),
+        _ => None,
+    };
+
+    let mut existing = None;
+    for page in 1.. {
+        let url = format!("{}/repos/{}/issues/{}/comments?per_page=100&page={}", api_url, repo, pr, page);
+        let comments: Vec<Value> = send(policy, request(reqwest::Method::GET, url)).await?;
+        existing = find_sticky_comment(&comments, marker, author.as_deref());
+        if existing.is_some() || comments.len() < 100 {
+            break;
+        }
+    }
+
+    let payload = serde_json::json!({ "body": body });
+    match existing {
+        Some(id) => {
+            let url = format!("{}/repos/{}/issues/comments/{}", api_url, repo, id);
+            let _: Value = send(policy, request(reqwest::Method::PATCH, url).json(&payload)).await?;
+            Ok(CommentAction::Updated(id))
+        }
+        None => {
+            let url = format!("{}/repos/{}/issues/{}/comments", api_url, repo, pr);
+            let created: Value = send(policy, request(reqwest::Method::POST, url).json(&payload)).await?;
+            Ok(CommentAction::Created(created["id"].as_u64().unwrap_or_default()))
+        }
+    }
+}
+
+#[cfg(not(feature = "network"))]
+pub async fn post_sticky_comment(
+    _api_url: &str,
+    _token: &str,
+    repo: &str,
+    pr: u64,
+    _marker: &str,
+    _body: &str,
+    _policy: &RetryPolicy,
+) -> Result<CommentAction, CliError> {
+    Err(CliError::NetworkError(format!(
+        "spklr was built without the `network` feature; cannot comment on {}#{}",
+        repo, pr
+    )))
+}
+
+#[cfg(feature = "network")]
+async fn send<T: serde::de::DeserializeOwned>(policy: &RetryPolicy, request: reqwest::RequestBuilder) -> Result<T, CliError> {
+    use crate::network::Failure;
+
+    crate::network::retry(policy, "GitHub API request", || async {
+        let request = request
+            .try_clone()
+            .ok_or_else(|| Failure::Fatal("request body can't be resent".to_string()))?;
+        let response = request
+            .send()
+            .await
+            .map_err(|e| policy.request_failure(e))?;
+        match crate::network::status_failure(&response) {
+            None => response.json().await.map_err(|e| Failure::Fatal(e.to_string())),
+            Some(Failure::Fatal(_)) => {
+                let status = response.status();
+                let message = response
+                    .json::<Value>()
+                    .await
+                    .ok()
+                    .and_then(|body| body["message"].as_str().map(str::to_string))
+                    .unwrap_or_default();
+                Err(Failure::Fatal(format!("GitHub API returned {}: {}", status, message)))
+            }
+            Some(retryable) => Err(retryable),
+        }
+    })
+    .await
+    .map_err(CliError::NetworkError)
+}
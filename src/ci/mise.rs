@@ -0,0 +1,129 @@
+//! mise/asdf tool pins for Space Pklr
+//!
+//! Renders the `[tools]` entries for `.mise.toml` and the lines for `.tool-versions`
+//! that pin spklr and its Pkl CLI, and reads those pins back for `spklr doctor`.
+//! mise installs spklr through its cargo backend in `.mise.toml`. There is no asdf plugin
+//! for spklr, so `.tool-versions` pins it with mise's `ubi:` backend (GitHub release
+//! binaries); mise reads those lines, and plain asdf only the `pkl` one.
+
+use std::path::{Path, PathBuf};
+
+use crate::types::CliError;
+
+/// Tool-version files checked for pins, in precedence order
+pub const PIN_FILES: [&str; 3] = [".mise.toml", "mise.toml", ".tool-versions"];
+
+/// mise tool key for spklr
+pub const MISE_SPKLR_TOOL: &str = "cargo:space-pklr";
+
+/// spklr and Pkl versions pinned by a project
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ToolPins {
+    pub spklr: Option<String>,
+    pub pkl: Option<String>,
+    /// File the pins were read from
+    pub source: Option<PathBuf>,
+}
+
+impl ToolPins {
+    /// Read the first tool-version file found in `dir`
+    pub fn discover(dir: &Path) -> Result<Self, CliError> {
+        for name in PIN_FILES {
+            let path = dir.join(name);
+            if !path.is_file() {
+                continue;
+            }
+            let content = std::fs::read_to_string(&path).map_err(|e| CliError::IoError {
+                context: format!("Reading tool versions: {}", path.display()),
+                source: e,
+            })?;
+            let mut pins = if name.ends_with(".toml") {
+                parse_mise_toml(&content).map_err(|e| CliError::ValidationError {
+                    source: Box::new(e),
+                })?
+            } else {
+                parse_tool_versions(&content)
+            };
+            pins.source = Some(path);
+            return Ok(pins);
+        }
+        Ok(Self::default())
+    }
+}
+
+/// Read pins from a `.mise.toml` `[tools]` table
+pub fn parse_mise_toml(content: &str) -> Result<ToolPins, toml::de::Error> {
+    let document: toml::Table = toml::from_str(content)?;
+    let mut pins = ToolPins::default();
+    let Some(tools) = document.get("tools").and_then(|tools| tools.as_table()) else {
+        return Ok(pins);
+    };
+
+    for (tool, spec) in tools {
+        // `tool = "1.0"`, `tool = ["1.0"]` or `tool = { version = "1.0" }`
+        let version = match spec {
+            toml::Value::String(version) => Some(version.clone()),
+            toml::Value::Array(versions) => versions.first().and_then(|v| v.as_str()).map(str::to_string),
+            toml::Value::Table(table) => table.get("version").and_then(|v| v.as_str()).map(str::to_string),
+            _ => None,
+        };
+        assign_pin(&mut pins, tool, version);
+    }
+    Ok(pins)
+}
+
+/// Read pins from an asdf `.tool-versions` file
+pub fn parse_tool_versions(content: &str) -> ToolPins {
+    let mut pins = ToolPins::default();
+    for line in content.lines() {
+        let line = line.split('#').next().unwrap_or_default();
+        let mut fields = line.split_whitespace();
+        if let (Some(tool), Some(version)) = (fields.next(), fields.next()) {
+            assign_pin(&mut pins, tool, Some(version.to_string()));
+        }
+    }
+    pins
+}
+
+fn assign_pin(pins: &mut ToolPins, tool: &str, version: Option<String>) {
+    // Strip mise backends (`cargo:`, `ubi:owner/`, `aqua:owner/`)
+    let name = tool.rsplit([':', '/']).next().unwrap_or(tool);
+    match name {
+        "spklr" | "space-pklr" => pins.spklr = version,
+        "pkl" => pins.pkl = version,
+        _ => {}
+    }
+}
+
+/// `[tools]` entries for `.mise.toml`
+pub fn render_mise_toml(spklr_version: &str, pkl_version: &str) -> String {
+    format!(
+        "# Generated by spklr {spklr}. Regenerate with `spklr ci mise`.\n\
+         [tools]\n\
+         \"{tool}\" = \"{spklr}\"\n\
+         pkl = \"{pkl}\"\n",
+        spklr = spklr_version,
+        tool = MISE_SPKLR_TOOL,
+        pkl = pkl_version,
+    )
+}
+
+/// Lines for a `.tool-versions` file, with spklr under mise's `ubi:` backend
+pub fn render_tool_versions(spklr_version: &str, pkl_version: &str) -> String {
+    format!(
+        "# Generated by spklr {spklr}. Regenerate with `spklr ci mise --format tool-versions`.\n\
+         # spklr is installed by mise from its GitHub releases; asdf has no spklr plugin\n\
+         {tool} {spklr}\n\
+         pkl {pkl}\n",
+        spklr = spklr_version,
+        tool = ubi_spklr_tool(),
+        pkl = pkl_version,
+    )
+}
+
+/// mise `ubi:` backend key for spklr's GitHub releases
+pub fn ubi_spklr_tool() -> String {
+    let repository = env!("CARGO_PKG_REPOSITORY").trim_end_matches('/');
+    let owner_repo = repository.strip_prefix("https://github.com/").unwrap_or(repository);
+    format!("ubi:{}", owner_repo)
+}
@@ -0,0 +1,10 @@
+//! CI Integration Module for Space Pklr
+//!
+//! Generators for the snippets that wire spklr into build systems, tool managers and
+//! CI providers. Submodules render text; the `spklr ci` commands decide where it goes.
+
+pub mod annotations;
+pub mod bazel;
+pub mod comment;
+pub mod mise;
+pub mod nix;
@@ -0,0 +1,135 @@
+//! Nix flake output for Space Pklr
+//!
+//! Renders a flake fragment that pins spklr (as a flake input, locked by `flake.lock`)
+//! and the Pkl CLI release executables for each Nix system by SRI hash, so schemas can be
+//! regenerated with exactly the toolchain that produced the committed ones. Pkl releases
+//! ship the native CLI as a bare executable (`pkl-linux-amd64`, ...), not an archive, so
+//! the derivation installs the download as is.
+
+use std::fmt::Write;
+
+use crate::hermetic::PKL_PATH_ENV;
+
+/// Nix systems and the Pkl release executable published for each
+pub const NIX_SYSTEMS: [(&str, &str); 4] = [
+    ("x86_64-linux", "pkl-linux-amd64"),
+    ("aarch64-linux", "pkl-linux-aarch64"),
+    ("x86_64-darwin", "pkl-macos-amd64"),
+    ("aarch64-darwin", "pkl-macos-aarch64"),
+];
+
+/// A pinned Pkl release executable for one Nix system
+#[derive(Debug, Clone)]
+pub struct PklExecutablePin {
+    pub system: String,
+    pub url: String,
+    /// SRI hash (`sha256-...`); `None` renders `lib.fakeHash` so Nix reports the real one
+    pub hash: Option<String>,
+}
+
+/// Everything the flake fragment pins
+#[derive(Debug, Clone)]
+pub struct NixFlakeOptions {
+    pub spklr_version: String,
+    pub pkl_version: String,
+    pub pkl_executables: Vec<PklExecutablePin>,
+}
+
+/// Render the flake fragment
+pub fn render_flake(options: &NixFlakeOptions) -> String {
+    let mut sources = String::new();
+    for pin in &options.pkl_executables {
+        let hash = pin
+            .hash
+            .as_ref()
+            .map(|hash| format!("\"{}\"", hash))
+            .unwrap_or_else(|| "nixpkgs.lib.fakeHash".to_string());
+        let _ = writeln!(
+            sources,
+            "        {} = {{ url = \"{}\"; hash = {}; }};",
+            pin.system, pin.url, hash
+        );
+    }
+
+    format!(
+        r#"# Generated by spklr {spklr_version}. Regenerate with `spklr ci nix`.
+{{
+  inputs.nixpkgs.url = "github:NixOS/nixpkgs/nixos-unstable";
+  # flake.lock records the exact revision and narHash of this tag
+  inputs.spklr.url = "{spklr_input}";
+
+  outputs = {{ self, nixpkgs, spklr, ... }}:
+    let
+      pklVersion = "{pkl_version}";
+      pklSources = {{
+{sources}      }};
+      forAllSystems = nixpkgs.lib.genAttrs (builtins.attrNames pklSources);
+    in {{
+      packages = forAllSystems (system:
+        let pkgs = nixpkgs.legacyPackages.${{system}}; in {{
+          pkl = pkgs.stdenvNoCC.mkDerivation {{
+            pname = "pkl";
+            version = pklVersion;
+            src = pkgs.fetchurl pklSources.${{system}};
+            dontUnpack = true;
+            installPhase = "install -Dm755 $src $out/bin/pkl";
+          }};
+        }});
+
+      devShells = forAllSystems (system: {{
+        default = nixpkgs.legacyPackages.${{system}}.mkShell {{
+          packages = [ spklr.packages.${{system}}.default self.packages.${{system}}.pkl ];
+          {pkl_env} = "${{self.packages.${{system}}.pkl}}/bin/pkl";
+        }};
+      }});
+    }};
+}}
+"#,
+        spklr_version = options.spklr_version,
+        spklr_input = spklr_flake_input(&options.spklr_version),
+        pkl_version = options.pkl_version,
+        sources = sources,
+        pkl_env = PKL_PATH_ENV,
+    )
+}
+
+/// Flake input URL for a spklr release tag
+pub fn spklr_flake_input(version: &str) -> String {
+    let repository = env!("CARGO_PKG_REPOSITORY").trim_end_matches('/');
+    match repository.strip_prefix("https://github.com/") {
+        Some(owner_repo) => format!("github:{}/v{}", owner_repo, version),
+        None => format!("git+{}?ref=refs/tags/v{}", repository, version),
+    }
+}
+
+/// Convert a hex SHA-256 digest to Nix's SRI form (`sha256-<base64>`)
+pub fn sri_from_hex(hex: &str) -> Option<String> {
+    if hex.len() != 64 {
+        return None;
+    }
+    let bytes = (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect::<Option<Vec<u8>>>()?;
+    Some(format!("sha256-{}", base64(&bytes)))
+}
+
+/// Standard padded base64
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, b)| n | ((*b as u32) << (16 - 8 * i)));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[((n >> (18 - 6 * i)) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
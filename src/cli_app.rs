@@ -15,25 +15,268 @@ use miette::Result;
 pub struct Cli {
     #[command(subcommand)]
     pub command: Commands,
+
+    /// Print a per-phase timing and memory breakdown when the command finishes
+    #[arg(long, global = true, help = "Print per-phase timings and peak memory")]
+    pub timings: bool,
+
+    /// Wait for other spklr processes to release output and cache locks instead of failing
+    #[arg(long, global = true, help = "Wait for locks held by other spklr processes")]
+    pub wait: bool,
+
+    /// Only use explicit inputs and outputs: no discovery, no caches, no side files
+    #[arg(long, global = true, help = "Hermetic mode for Bazel/Buck actions (no discovery, no caches)")]
+    pub hermetic: bool,
+
+    /// Forbid all disk writes and network access; results go to stdout and the exit code
+    #[arg(long, global = true, help = "Never write to disk or the network")]
+    pub read_only: bool,
+
+    /// Write a machine-readable JSON report of the run
+    #[arg(long, global = true, value_name = "PATH", help = "Write a JSON report of the run to PATH")]
+    pub report: Option<std::path::PathBuf>,
+
+    /// Write a Markdown summary of the run (converted files, validation failures, schema changes)
+    #[arg(long, global = true, value_name = "PATH", help = "Write a Markdown summary of the run to PATH")]
+    pub summary: Option<std::path::PathBuf>,
+
+    /// Template variable for headers and footers (repeatable; overrides SPKLR_VAR_<KEY>)
+    #[arg(long = "var", global = true, value_name = "KEY=VALUE", value_parser = crate::templates::parse_var, help = "Set a template variable (repeatable)")]
+    pub vars: Vec<(String, String)>,
+
+    /// Header/footer preset: documented, compact, minimal-comments, annotations-heavy
+    #[arg(long, global = true, value_name = "THEME", help = "Output theme: documented, compact, minimal-comments, annotations-heavy")]
+    pub theme: Option<crate::templates::Theme>,
+
+    /// Directory of module/class/property templates overriding the built-in Pkl rendering
+    /// (defaults to `template_dir` in `.spklr.toml`)
+    #[arg(long, global = true, value_name = "DIR", help = "Load Pkl rendering templates from DIR")]
+    pub template_dir: Option<std::path::PathBuf>,
+
+    /// Pkl CLI version to use for this run (must be installed; see `spklr pkl list`; defaults
+    /// to `pkl_version` in `.spklr.toml`)
+    #[arg(long, global = true, value_name = "VERSION", help = "Use this installed Pkl CLI version")]
+    pub pkl_version: Option<String>,
+
+    /// Jobs to run at once: generation threads, Pkl evaluations, and Tokio workers
+    #[arg(long, short = 'j', global = true, value_name = "N", help = "Run up to N jobs at once (0 detects the core count)")]
+    pub jobs: Option<usize>,
+
+    /// Request timeout for every network operation, e.g. `90s` or `2m` (`0` for none)
+    #[arg(long, global = true, value_name = "DURATION", help = "Network request timeout, e.g. 90s, 2m (0 for none)")]
+    pub timeout: Option<crate::types::HumanDuration>,
+
+    /// Write the resolved template context and per-template render timings to DIR
+    #[arg(long, global = true, value_name = "DIR", help = "Dump template context and render timings to DIR")]
+    pub debug_templates: Option<std::path::PathBuf>,
+
+    /// Print the long help of every command, with examples (answered before parsing; see [`run`])
+    #[arg(long, global = true, help = "Print help for every command, with examples")]
+    pub help_all: bool,
 }
 
 #[derive(Subcommand)]
 pub enum Commands {
+    /// Show or clear the Pkl evaluation cache
+    #[command(subcommand)]
+    Cache(crate::commands::cache::CacheCommands),
+    /// Check generated outputs for drift
+    Check(crate::commands::check::CheckArgs),
+    /// Generate build system and CI integration snippets
+    #[command(subcommand)]
+    Ci(crate::commands::ci::CiCommands),
+    /// Show the CLI defaults from .spklr.toml and ~/.config/spklr/config.toml
+    #[command(subcommand)]
+    Config(crate::commands::config::ConfigCommands),
     /// Convert Moon configuration files between formats
     Convert(crate::commands::convert::ConvertArgs),
+    /// Check the converter against a corpus of real-world Moon repositories
+    #[command(subcommand)]
+    Corpus(crate::commands::corpus::CorpusCommands),
+    /// Compare two generated schema sets by type and property
+    Diff(crate::commands::diff::DiffArgs),
+    /// Generate Markdown reference docs for Moon configuration types
+    #[cfg(feature = "docgen")]
+    Docgen(crate::commands::docgen::DocgenArgs),
+    /// Build an HTML doc site for generated schemas with pkldoc
+    Docs(crate::commands::docs::DocsArgs),
+    /// Check the active tools against the project's mise/asdf pins
+    Doctor(crate::commands::doctor::DoctorArgs),
+    /// Export the effective config Moon sees for a project (workspace, inherited tasks, project)
+    Effective(crate::commands::effective::EffectiveArgs),
     /// Generate schemas or template configurations
     #[command(subcommand)]
     Generate(crate::commands::generate::GenerateCommands),
-    /// Install Pkl CLI tool
+    /// Export dependency graphs built from configuration files
     #[command(subcommand)]
-    PklMe(crate::commands::pklme::InstallCommands),
+    Graph(crate::commands::graph::GraphCommands),
+    /// Set up a Moon repo for Pkl: Pkl CLI, schemas, and starter or converted configs
+    Init(crate::commands::init::InitArgs),
+    /// Check configuration files against organizational policies
+    Lint(crate::commands::lint::LintArgs),
+    /// Render man pages for every command (for packaging)
+    #[command(hide = true)]
+    Manpages(crate::commands::manpages::ManpagesArgs),
+    /// Merge layered configs and print the effective result
+    Merge(crate::commands::merge::MergeArgs),
+    /// List installed Pkl CLI versions and choose the default
+    #[command(subcommand)]
+    Pkl(crate::commands::pkl::PklCommands),
+    /// Install Pkl CLI tool, or report toolchain compatibility with --report
+    #[command(alias = "pklme")]
+    PklMe(crate::commands::pklme::PklMeArgs),
+    /// Manage the lifecycle of generated schemas
+    #[command(subcommand)]
+    Schema(crate::commands::schema::SchemaCommands),
+    /// Check this build against its embedded golden fixtures
+    #[command(name = "self", subcommand)]
+    SelfCheck(crate::commands::self_test::SelfCommands),
+    /// Replace this spklr with a GitHub release
+    SelfUpdate(crate::commands::self_update::SelfUpdateArgs),
+    /// Run as a daemon serving generate/convert/validate/query requests
+    Serve(crate::commands::serve::ServeArgs),
+    /// Apply config migrations between Moon releases
+    UpgradeConfig(crate::commands::upgrade::UpgradeConfigArgs),
+    /// Type-check a config file against a generated Pkl schema
+    Validate(crate::commands::validate::ValidateArgs),
+    /// Run an external `spklr-<name>` command from PATH
+    #[command(external_subcommand)]
+    External(Vec<String>),
+}
+
+impl Commands {
+    /// Command name used in reports
+    pub fn name(&self) -> String {
+        match self {
+            Commands::Cache(_) => "cache".to_string(),
+            Commands::Check(_) => "check".to_string(),
+            Commands::Ci(_) => "ci".to_string(),
+            Commands::Config(_) => "config".to_string(),
+            Commands::Convert(_) => "convert".to_string(),
+            Commands::Corpus(_) => "corpus".to_string(),
+            Commands::Diff(_) => "diff".to_string(),
+            #[cfg(feature = "docgen")]
+            Commands::Docgen(_) => "docgen".to_string(),
+            Commands::Docs(_) => "docs".to_string(),
+            Commands::Doctor(_) => "doctor".to_string(),
+            Commands::Effective(_) => "effective".to_string(),
+            Commands::Generate(_) => "generate".to_string(),
+            Commands::Graph(_) => "graph".to_string(),
+            Commands::Init(_) => "init".to_string(),
+            Commands::Lint(_) => "lint".to_string(),
+            Commands::Manpages(_) => "manpages".to_string(),
+            Commands::Merge(_) => "merge".to_string(),
+            Commands::Pkl(_) => "pkl".to_string(),
+            Commands::PklMe(_) => "pkl-me".to_string(),
+            Commands::Schema(_) => "schema".to_string(),
+            Commands::SelfCheck(_) => "self".to_string(),
+            Commands::SelfUpdate(_) => "self-update".to_string(),
+            Commands::Serve(_) => "serve".to_string(),
+            Commands::UpgradeConfig(_) => "upgrade-config".to_string(),
+            Commands::Validate(_) => "validate".to_string(),
+            Commands::External(args) => args.first().cloned().unwrap_or_default(),
+        }
+    }
+}
+
+/// `--jobs` from the command line, read before the async runtime is built
+///
+/// Invalid arguments yield `None` here; [`run`] reports them.
+pub fn requested_jobs() -> Option<usize> {
+    Cli::try_parse().ok().and_then(|cli| cli.jobs)
 }
 
 /// CLI application with error handling
 pub async fn run() -> Result<()> {
+    // `--help-all` needs no subcommand, which clap would otherwise insist on
+    if std::env::args().skip(1).take_while(|arg| arg != "--").any(|arg| arg == "--help-all") {
+        print!("{}", crate::commands::manpages::help_all());
+        return Ok(());
+    }
     let cli = Cli::parse();
 
-    match cli.command {
+    if cli.timings {
+        crate::timings::enable();
+    }
+    crate::lock::set_wait(cli.wait);
+    if cli.read_only {
+        crate::read_only::enable();
+    }
+    if cli.hermetic {
+        crate::hermetic::enable();
+    }
+    crate::templates::set_cli_variables(cli.vars);
+    crate::templates::set_cli_theme(cli.theme);
+    let defaults = crate::cli_defaults::discover()
+        .map_err(miette::Report::new)?
+        .with_flags(cli.template_dir.clone(), cli.pkl_version.clone());
+    crate::templates::set_cli_template_dir(defaults.defaults.template_dir.clone());
+    crate::pkl_tooling::set_cli_pkl_version(defaults.defaults.pkl_version.clone());
+    crate::cli_defaults::set_active(defaults);
+    crate::concurrency::set_cli_jobs(cli.jobs);
+    crate::network::set_cli_timeout(cli.timeout);
+    if let Some(dir) = cli.debug_templates.clone() {
+        crate::templates::enable_debug(dir);
+    }
+    let started = std::time::Instant::now();
+    let mut report = crate::report::RunReport::new(cli.command.name());
+
+    let result = dispatch(cli.command).await;
+
+    if cli.timings {
+        let timings = crate::timings::TimingsReport::collect(started.elapsed());
+        eprint!("{}", timings.render_table());
+        report.timings = Some(timings);
+    }
+
+    match crate::templates::write_debug_dump() {
+        Ok(Some(dir)) => eprintln!("🪲 Template debug dump written to {}", dir.display()),
+        Ok(None) => {}
+        Err(e) => tracing::warn!("Failed to write template debug dump: {}", e),
+    }
+
+    report.collect_details();
+    if let Err(e) = &result {
+        report.success = false;
+        report.error = Some(crate::interpolation::redact(&e.to_string()));
+    }
+    if let Some(report_path) = &cli.report {
+        if let Err(e) = report.write(report_path) {
+            tracing::warn!("Failed to write run report: {}", e);
+        }
+    }
+    if let Some(summary_path) = &cli.summary {
+        if let Err(e) = report.write_summary(summary_path) {
+            tracing::warn!("Failed to write run summary: {}", e);
+        }
+    }
+
+    result
+}
+
+/// Run the selected command
+async fn dispatch(command: Commands) -> Result<()> {
+    match command {
+        Commands::Cache(commands) => {
+            tracing::info!("Starting cache command");
+            match crate::commands::cache::handle_cache(commands).await {
+                Ok(()) => Ok(()),
+                Err(e) => {
+                    tracing::error!("Cache command failed: {}", e);
+                    Err(miette::Report::new(e))
+                }
+            }
+        }
+        Commands::Check(args) => {
+            tracing::info!("Starting generated output check");
+            match crate::commands::check::handle_check(args).await {
+                Ok(()) => Ok(()),
+                Err(e) => {
+                    tracing::error!("Check failed: {}", e);
+                    Err(miette::Report::new(e))
+                }
+            }
+        }
         Commands::Convert(args) => {
             tracing::info!("Starting configuration conversion");
             match crate::commands::convert::handle_convert(args).await {
@@ -44,6 +287,87 @@ pub async fn run() -> Result<()> {
                 }
             }
         }
+        Commands::Corpus(commands) => {
+            tracing::info!("Starting corpus run");
+            match crate::commands::corpus::handle_corpus(commands).await {
+                Ok(()) => Ok(()),
+                Err(e) => {
+                    tracing::error!("Corpus run failed: {}", e);
+                    Err(miette::Report::new(e))
+                }
+            }
+        }
+        Commands::Diff(args) => {
+            tracing::info!("Starting schema diff");
+            match crate::commands::diff::handle_diff(args).await {
+                Ok(()) => Ok(()),
+                Err(e) => {
+                    tracing::error!("Schema diff failed: {}", e);
+                    Err(miette::Report::new(e))
+                }
+            }
+        }
+        Commands::Ci(commands) => {
+            tracing::info!("Starting CI snippet generation");
+            match crate::commands::ci::handle_ci(commands).await {
+                Ok(()) => Ok(()),
+                Err(e) => {
+                    tracing::error!("CI snippet generation failed: {}", e);
+                    Err(miette::Report::new(e))
+                }
+            }
+        }
+        Commands::Config(commands) => {
+            tracing::info!("Starting config command");
+            match crate::commands::config::handle_config(commands).await {
+                Ok(()) => Ok(()),
+                Err(e) => {
+                    tracing::error!("Config command failed: {}", e);
+                    Err(miette::Report::new(e))
+                }
+            }
+        }
+        #[cfg(feature = "docgen")]
+        Commands::Docgen(args) => {
+            tracing::info!("Starting documentation generation");
+            match crate::commands::docgen::handle_docgen(args).await {
+                Ok(()) => Ok(()),
+                Err(e) => {
+                    tracing::error!("Documentation generation failed: {}", e);
+                    Err(miette::Report::new(e))
+                }
+            }
+        }
+        Commands::Docs(args) => {
+            tracing::info!("Starting pkldoc site generation");
+            match crate::commands::docs::handle_docs(args).await {
+                Ok(()) => Ok(()),
+                Err(e) => {
+                    tracing::error!("Doc site generation failed: {}", e);
+                    Err(e)
+                }
+            }
+        }
+        Commands::Doctor(args) => {
+            tracing::info!("Starting tool pin check");
+            match crate::commands::doctor::handle_doctor(args).await {
+                Ok(()) => Ok(()),
+                Err(e) => {
+                    tracing::error!("Doctor failed: {}", e);
+                    Err(miette::Report::new(e))
+                }
+            }
+        }
+        Commands::Effective(args) => {
+            tracing::info!("Starting effective config export");
+            match crate::commands::effective::handle_effective(args).await {
+                Ok(()) => Ok(()),
+                Err(e) => {
+                    tracing::error!("Effective config export failed: {}", e);
+                    Err(miette::Report::new(e))
+                }
+            }
+        }
         Commands::Generate(commands) => {
             tracing::info!("Starting schema/template generation");
             match crate::commands::generate::handle_generate(commands).await {
@@ -54,9 +378,69 @@ pub async fn run() -> Result<()> {
                 }
             }
         }
-        Commands::PklMe(commands) => {
+        Commands::Graph(commands) => {
+            tracing::info!("Starting graph export");
+            match crate::commands::graph::handle_graph(commands).await {
+                Ok(()) => Ok(()),
+                Err(e) => {
+                    tracing::error!("Graph export failed: {}", e);
+                    Err(e)
+                }
+            }
+        }
+        Commands::Init(args) => {
+            tracing::info!("Starting repo setup");
+            match crate::commands::init::handle_init(args).await {
+                Ok(()) => Ok(()),
+                Err(e) => {
+                    tracing::error!("Init failed: {}", e);
+                    Err(e)
+                }
+            }
+        }
+        Commands::Lint(args) => {
+            tracing::info!("Starting policy lint");
+            match crate::commands::lint::handle_lint(args).await {
+                Ok(()) => Ok(()),
+                Err(e) => {
+                    tracing::error!("Lint failed: {}", e);
+                    Err(miette::Report::new(e))
+                }
+            }
+        }
+        Commands::Manpages(args) => {
+            tracing::info!("Starting man page generation");
+            match crate::commands::manpages::handle_manpages(args).await {
+                Ok(()) => Ok(()),
+                Err(e) => {
+                    tracing::error!("Man page generation failed: {}", e);
+                    Err(miette::Report::new(e))
+                }
+            }
+        }
+        Commands::Merge(args) => {
+            tracing::info!("Starting config merge");
+            match crate::commands::merge::handle_merge(args).await {
+                Ok(()) => Ok(()),
+                Err(e) => {
+                    tracing::error!("Merge failed: {}", e);
+                    Err(miette::Report::new(e))
+                }
+            }
+        }
+        Commands::Pkl(commands) => {
+            tracing::info!("Starting Pkl version management");
+            match crate::commands::pkl::handle_pkl(commands).await {
+                Ok(()) => Ok(()),
+                Err(e) => {
+                    tracing::error!("Pkl version management failed: {}", e);
+                    Err(miette::Report::new(e))
+                }
+            }
+        }
+        Commands::PklMe(args) => {
             tracing::info!("Starting tool installation");
-            match crate::commands::pklme::handle_install(commands).await {
+            match crate::commands::pklme::handle_pkl_me(args).await {
                 Ok(()) => Ok(()),
                 Err(e) => {
                     tracing::error!("Installation failed: {}", e);
@@ -64,5 +448,77 @@ pub async fn run() -> Result<()> {
                 }
             }
         }
+        Commands::Schema(commands) => {
+            tracing::info!("Starting schema lifecycle command");
+            match crate::commands::schema::handle_schema(commands).await {
+                Ok(()) => Ok(()),
+                Err(e) => {
+                    tracing::error!("Schema command failed: {}", e);
+                    Err(miette::Report::new(e))
+                }
+            }
+        }
+        Commands::SelfCheck(commands) => {
+            tracing::info!("Starting self test");
+            match crate::commands::self_test::handle_self(commands).await {
+                Ok(()) => Ok(()),
+                Err(e) => {
+                    tracing::error!("Self test failed: {}", e);
+                    Err(miette::Report::new(e))
+                }
+            }
+        }
+        Commands::SelfUpdate(args) => {
+            tracing::info!("Starting self update");
+            match crate::commands::self_update::handle_self_update(args).await {
+                Ok(()) => Ok(()),
+                Err(e) => {
+                    tracing::error!("Self update failed: {}", e);
+                    Err(miette::Report::new(e))
+                }
+            }
+        }
+        Commands::Serve(args) => {
+            tracing::info!("Starting daemon");
+            match crate::commands::serve::handle_serve(args).await {
+                Ok(()) => Ok(()),
+                Err(e) => {
+                    tracing::error!("Daemon failed: {}", e);
+                    Err(miette::Report::new(e))
+                }
+            }
+        }
+        Commands::UpgradeConfig(args) => {
+            tracing::info!("Starting config upgrade");
+            match crate::commands::upgrade::handle_upgrade_config(args).await {
+                Ok(()) => Ok(()),
+                Err(e) => {
+                    tracing::error!("Config upgrade failed: {}", e);
+                    Err(miette::Report::new(e))
+                }
+            }
+        }
+        Commands::Validate(args) => {
+            tracing::info!("Starting schema validation");
+            match crate::commands::validate::handle_validate(args).await {
+                Ok(()) => Ok(()),
+                Err(e) => {
+                    tracing::error!("Validation failed: {}", e);
+                    Err(miette::Report::new(e))
+                }
+            }
+        }
+        Commands::External(args) => {
+            tracing::info!("Starting external command");
+            match crate::commands::external::handle_external(args).await {
+                Ok(0) => Ok(()),
+                // Pass the plugin's exit code through untouched
+                Ok(code) => std::process::exit(code),
+                Err(e) => {
+                    tracing::error!("External command failed: {}", e);
+                    Err(miette::Report::new(e))
+                }
+            }
+        }
     }
 }
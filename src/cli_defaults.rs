@@ -0,0 +1,166 @@
+//! CLI Defaults Module for Space Pklr
+//!
+//! Default command-line flags for a project, from `.spklr.toml`, and for a user, from
+//! `~/.config/spklr/config.toml` (`$XDG_CONFIG_HOME/spklr/config.toml` when set):
+//!
+//! ```toml
+//! output_dir = "schemas"                 # spklr generate --output
+//! module_name = "Deploy"                 # spklr generate schema --module-name
+//! include_properties = ["tasks.*"]       # spklr generate schema --include-properties
+//! exclude_properties = ["docker"]        # spklr generate schema --exclude-properties
+//! template_dir = "templates"             # spklr --template-dir
+//! pkl_version = "0.28.1"                 # spklr --pkl-version
+//!
+//! [type_mappings]                        # generated type name = name to use instead
+//! TaskConfig = "MoonTask"
+//! ```
+//!
+//! The nearest `.spklr.toml` in the current directory or its ancestors overrides the
+//! user file, and flags override both. `type_mappings` merge by name, and also merge
+//! over `[generator] type_mappings` in `spklr.toml`. Relative paths are resolved
+//! against the file that sets them. `spklr config show` prints the result.
+//!
+//! Hermetic runs read neither file.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::types::CliError;
+
+/// File name of a project's CLI defaults
+pub const PROJECT_DEFAULTS_FILE: &str = ".spklr.toml";
+
+/// Defaults for command-line flags
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CliDefaults {
+    /// Directory `spklr generate` writes to when `--output` isn't given
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_dir: Option<PathBuf>,
+    /// Name of a module generated from a single `--from-json-schema` source
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub module_name: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub include_properties: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub exclude_properties: Vec<String>,
+    /// Generated type names mapped to the names to use instead
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub type_mappings: BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub template_dir: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pkl_version: Option<String>,
+}
+
+impl CliDefaults {
+    /// Load a defaults file
+    pub fn load(path: &Path) -> Result<Self, CliError> {
+        let content = std::fs::read_to_string(path).map_err(|e| CliError::IoError {
+            context: format!("Reading CLI defaults: {}", path.display()),
+            source: e,
+        })?;
+        let mut defaults: CliDefaults = toml::from_str(&content).map_err(|e| CliError::ValidationError {
+            source: format!("{}: {}", path.display(), e).into(),
+        })?;
+
+        let base = path.parent().unwrap_or(Path::new("."));
+        for dir in [defaults.output_dir.as_mut(), defaults.template_dir.as_mut()].into_iter().flatten() {
+            if dir.is_relative() {
+                *dir = base.join(&*dir);
+            }
+        }
+        Ok(defaults)
+    }
+
+    /// These defaults with every setting of `other` laid over them
+    pub fn overlay(mut self, other: CliDefaults) -> Self {
+        self.output_dir = other.output_dir.or(self.output_dir);
+        self.module_name = other.module_name.or(self.module_name);
+        if !other.include_properties.is_empty() {
+            self.include_properties = other.include_properties;
+        }
+        if !other.exclude_properties.is_empty() {
+            self.exclude_properties = other.exclude_properties;
+        }
+        self.type_mappings.extend(other.type_mappings);
+        self.template_dir = other.template_dir.or(self.template_dir);
+        self.pkl_version = other.pkl_version.or(self.pkl_version);
+        self
+    }
+}
+
+/// Defaults and the files they came from, lowest precedence first
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LoadedDefaults {
+    pub defaults: CliDefaults,
+    pub sources: Vec<PathBuf>,
+}
+
+impl LoadedDefaults {
+    /// The defaults with the global `--template-dir` and `--pkl-version` flags applied
+    pub fn with_flags(mut self, template_dir: Option<PathBuf>, pkl_version: Option<String>) -> Self {
+        self.defaults.template_dir = template_dir.or(self.defaults.template_dir);
+        self.defaults.pkl_version = pkl_version.or(self.defaults.pkl_version);
+        self
+    }
+}
+
+/// The nearest `.spklr.toml` in `start` or its ancestors
+pub fn find_project_file(start: &Path) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .map(|dir| dir.join(PROJECT_DEFAULTS_FILE))
+        .find(|path| path.is_file())
+}
+
+/// Path of the user's defaults file
+pub fn user_file() -> Option<PathBuf> {
+    let config_dir = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|home| home.join(".config")))?;
+    Some(config_dir.join("spklr").join("config.toml"))
+}
+
+/// Load the user file, then the project file nearest `start` over it
+pub fn discover_from(start: &Path, user_file: Option<&Path>) -> Result<LoadedDefaults, CliError> {
+    let mut loaded = LoadedDefaults::default();
+    let project_file = find_project_file(start);
+    let files = user_file.filter(|path| path.is_file()).map(Path::to_path_buf).into_iter().chain(project_file);
+    for path in files {
+        loaded.defaults = loaded.defaults.overlay(CliDefaults::load(&path)?);
+        loaded.sources.push(path);
+    }
+    Ok(loaded)
+}
+
+/// Load the defaults for the current directory and user
+///
+/// Hermetic runs never read them.
+pub fn discover() -> Result<LoadedDefaults, CliError> {
+    if crate::hermetic::is_enabled() {
+        return Ok(LoadedDefaults::default());
+    }
+    let cwd = std::env::current_dir().map_err(|e| CliError::IoError {
+        context: "Reading the current directory".to_string(),
+        source: e,
+    })?;
+    discover_from(&cwd, user_file().as_deref())
+}
+
+static ACTIVE: Mutex<Option<LoadedDefaults>> = Mutex::new(None);
+
+/// Set the defaults for the rest of the run
+pub fn set_active(defaults: LoadedDefaults) {
+    if let Ok(mut active) = ACTIVE.lock() {
+        *active = Some(defaults);
+    }
+}
+
+/// The defaults for this run (none unless [`set_active`] was called)
+pub fn active() -> LoadedDefaults {
+    ACTIVE.lock().ok().and_then(|active| active.clone()).unwrap_or_default()
+}
@@ -0,0 +1,56 @@
+//! Cache command implementation for Space Pklr
+//!
+//! Shows and clears the Pkl evaluation cache (see [`crate::eval_cache`]).
+
+use clap::Subcommand;
+
+use crate::eval_cache::{CacheConfig, EvalCache};
+use crate::types::CliError;
+
+/// Cache subcommands
+#[derive(Subcommand)]
+pub enum CacheCommands {
+    /// Show where the evaluation cache is and how much it holds
+    Info,
+    /// Remove every cached evaluation
+    Clear,
+}
+
+/// Handle cache command execution
+pub async fn handle_cache(commands: CacheCommands) -> Result<(), CliError> {
+    let config = crate::tool_config::ToolConfig::discover()?.cache;
+    // Clearing works even when caching is turned off, e.g. to reclaim the space
+    let Some(cache) = EvalCache::from_config(&CacheConfig { enabled: true, ..config.clone() }) else {
+        println!("The evaluation cache isn't used in hermetic runs");
+        return Ok(());
+    };
+    match commands {
+        CacheCommands::Info => {
+            let stats = cache.stats();
+            println!("📁 {}", cache.dir().display());
+            println!(
+                "🗃️  {} cached evaluation{}, {}",
+                stats.entries,
+                if stats.entries == 1 { "" } else { "s" },
+                mebibytes(stats.bytes)
+            );
+            if !config.enabled {
+                println!("⚠️  Caching is disabled by `enabled = false` under [cache]");
+            }
+        }
+        CacheCommands::Clear => {
+            let stats = cache.clear()?;
+            println!(
+                "🧹 Removed {} cached evaluation{} ({})",
+                stats.entries,
+                if stats.entries == 1 { "" } else { "s" },
+                mebibytes(stats.bytes)
+            );
+        }
+    }
+    Ok(())
+}
+
+fn mebibytes(bytes: u64) -> String {
+    format!("{:.1} MiB", bytes as f64 / (1024.0 * 1024.0))
+}
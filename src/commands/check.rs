@@ -0,0 +1,153 @@
+//! Check command implementation for Space Pklr
+//!
+//! This module verifies that committed generated outputs haven't been hand-edited
+//!.
+
+use clap::Args;
+use std::path::PathBuf;
+
+use crate::checksums::{CHECKSUM_FILE, ChecksumManifest};
+use crate::ci::annotations::{Finding, OutputFormat};
+use crate::policy::Severity;
+use crate::types::CliError;
+
+/// Check command arguments.
+#[derive(Args)]
+pub struct CheckArgs {
+    /// Directories containing generated outputs and their SHA256SUMS
+    #[arg(default_value = ".", help = "Directories with generated outputs (defaults to the current directory)")]
+    pub dirs: Vec<PathBuf>,
+
+    /// Verify generated files against their SHA256SUMS manifest
+    #[arg(long, help = "Verify generated files against SHA256SUMS")]
+    pub verify_checksums: bool,
+
+    /// Fail when fewer than PERCENT of generated types and properties are documented
+    #[arg(long, value_name = "PERCENT", value_parser = parse_percent, help = "Minimum documentation coverage of generated schemas (0-100)")]
+    pub doc_coverage: Option<f64>,
+
+    /// Report format; CI formats also print the text report
+    #[arg(long, default_value = "text", value_parser = ["text", "github", "buildkite", "gitlab"], help = "Report format: text (default), github, buildkite, gitlab")]
+    pub output: String,
+}
+
+/// Handle check command execution
+pub async fn handle_check(args: CheckArgs) -> Result<(), CliError> {
+    // With no check selected, run every check that doesn't need a threshold
+    let verify_checksums = args.verify_checksums || args.doc_coverage.is_none();
+    if !args.verify_checksums && args.doc_coverage.is_none() {
+        tracing::debug!("No checks selected; running all checks");
+    }
+
+    let mut failures = 0;
+    let mut findings = Vec::new();
+    let dirs: &[PathBuf] = if verify_checksums { &args.dirs } else { &[] };
+    for dir in dirs {
+        let manifest_path = dir.join(CHECKSUM_FILE);
+        crate::types::ensure_file_exists(&manifest_path)?;
+
+        println!("🔍 Verifying {}", manifest_path.display());
+        let report = ChecksumManifest::load(dir)?.verify(dir);
+
+        for name in &report.modified {
+            println!("  ❌ modified: {}", dir.join(name).display());
+        }
+        for name in &report.missing {
+            println!("  ❌ missing:  {}", dir.join(name).display());
+        }
+        if report.is_ok() {
+            println!("  ✅ {} file(s) match", report.verified.len());
+        }
+        failures += report.failure_count();
+        findings.extend(drift_findings(dir, &report.modified, &report.missing));
+    }
+
+    let mut below_threshold = None;
+    if let Some(threshold) = args.doc_coverage {
+        let coverage_findings = check_doc_coverage(threshold)?;
+        if !coverage_findings.is_empty() {
+            below_threshold = Some(coverage_findings.len());
+        }
+        findings.extend(coverage_findings);
+    }
+
+    crate::report::record_findings(&findings);
+    let output: OutputFormat = args.output.parse()?;
+    crate::ci::annotations::emit(output, "check", &findings)?;
+
+    if failures > 0 {
+        return Err(CliError::ChecksumMismatch { count: failures });
+    }
+    if let (Some(count), Some(threshold)) = (below_threshold, args.doc_coverage) {
+        return Err(CliError::DocCoverageBelowThreshold { count, threshold });
+    }
+
+    Ok(())
+}
+
+/// Check the documentation coverage of every config type's generated schema
+#[cfg(feature = "docgen")]
+fn check_doc_coverage(threshold: f64) -> Result<Vec<Finding>, CliError> {
+    /// Undocumented paths listed per config type before truncating
+    const LISTED: usize = 10;
+
+    println!("📚 Checking documentation coverage (minimum {}%)", threshold);
+    let mut findings = Vec::new();
+    for config_type in crate::types::MoonConfig::all_types() {
+        let coverage = crate::docgen::coverage(&crate::docgen::schema_docs(config_type)?);
+        let percent = coverage.percent();
+        if percent >= threshold {
+            println!("  ✅ {} docs: {:.1}% ({}/{})", config_type, percent, coverage.documented, coverage.total);
+            continue;
+        }
+
+        println!("  ❌ {} docs: {:.1}% ({}/{})", config_type, percent, coverage.documented, coverage.total);
+        let mut undocumented = coverage.undocumented[..coverage.undocumented.len().min(LISTED)].join(", ");
+        if coverage.undocumented.len() > LISTED {
+            undocumented.push_str(&format!(" (+{} more)", coverage.undocumented.len() - LISTED));
+        }
+        findings.push(Finding {
+            file: PathBuf::from(format!("{}_schema.json", config_type)),
+            severity: Severity::Error,
+            rule: "doc-coverage".to_string(),
+            message: format!(
+                "Documentation coverage {:.1}% is below {}%; undocumented: {}",
+                percent, threshold, undocumented
+            ),
+        });
+    }
+    Ok(findings)
+}
+
+#[cfg(not(feature = "docgen"))]
+fn check_doc_coverage(_threshold: f64) -> Result<Vec<Finding>, CliError> {
+    Err(CliError::Generic(
+        "spklr was built without the `docgen` feature; rebuild with `--features docgen` to use `--doc-coverage`".to_string(),
+    ))
+}
+
+fn parse_percent(value: &str) -> Result<f64, String> {
+    match value.trim_end_matches('%').parse::<f64>() {
+        Ok(percent) if (0.0..=100.0).contains(&percent) => Ok(percent),
+        _ => Err(format!("expected a percentage between 0 and 100, got '{}'", value)),
+    }
+}
+
+/// Findings for generated files that drifted from SHA256SUMS
+fn drift_findings(dir: &std::path::Path, modified: &[String], missing: &[String]) -> Vec<Finding> {
+    let finding = |name: &String, rule: &str, message: &str| Finding {
+        file: dir.join(name),
+        severity: Severity::Error,
+        rule: rule.to_string(),
+        message: message.to_string(),
+    };
+    modified
+        .iter()
+        .map(|name| finding(name, "checksum-modified", "Generated file was edited by hand; re-run `spklr generate`"))
+        .chain(
+            missing
+                .iter()
+                .map(|name| finding(name, "checksum-missing", "Generated file listed in SHA256SUMS is missing")),
+        )
+        .collect()
+}
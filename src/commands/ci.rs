@@ -0,0 +1,288 @@
+//! CI command implementation for Space Pklr
+//!
+//! This module generates build system and CI integration snippets
+//!.
+
+use clap::{Args, Subcommand};
+use std::path::PathBuf;
+
+use crate::ci::bazel::{BazelRuleOptions, DEFAULT_PKL_LABEL, DEFAULT_SPKLR_LABEL};
+use crate::ci::nix::{NIX_SYSTEMS, NixFlakeOptions, PklExecutablePin};
+use crate::network::Operation;
+use crate::tool_config::ToolConfig;
+use crate::types::CliError;
+
+/// CI command with subcommands.
+#[derive(Subcommand)]
+pub enum CiCommands {
+    /// Generate Bazel/Buck2 rules that run spklr hermetically
+    Bazel(BazelArgs),
+    /// Post run summaries as a sticky pull request comment
+    Comment(CommentArgs),
+    /// Generate mise/asdf tool pins for spklr and the Pkl CLI
+    Mise(MiseArgs),
+    /// Create or update spklr-versions.json for Renovate/Dependabot
+    Versions(VersionsArgs),
+    /// Generate a Nix flake fragment pinning spklr and the Pkl CLI by hash
+    Nix(NixArgs),
+}
+
+/// Bazel rules arguments
+#[derive(Args)]
+pub struct BazelArgs {
+    /// Label of the spklr binary
+    #[arg(long, default_value = DEFAULT_SPKLR_LABEL, help = "Label of the spklr binary")]
+    pub spklr_label: String,
+
+    /// Label of the Pkl CLI
+    #[arg(long, default_value = DEFAULT_PKL_LABEL, help = "Label of the Pkl CLI")]
+    pub pkl_label: String,
+
+    /// Output file (optional, defaults to stdout)
+    #[arg(short, long, help = "Output file path, e.g. tools/spklr.bzl (defaults to stdout)")]
+    pub output: Option<PathBuf>,
+
+    /// Overwrite existing output file
+    #[arg(short, long, help = "Force overwrite of existing output files")]
+    pub force: bool,
+}
+
+/// PR comment arguments
+#[derive(Args)]
+pub struct CommentArgs {
+    /// Markdown summaries written with `--summary`
+    #[arg(required = true, help = "Summary files written with --summary")]
+    pub summaries: Vec<PathBuf>,
+
+    /// GitHub token (defaults to `github_token` under [ci] in spklr.toml, then $GITHUB_TOKEN)
+    #[arg(long, help = "GitHub token with pull-requests: write (defaults to [ci] github_token, then $GITHUB_TOKEN)")]
+    pub github_token: Option<String>,
+
+    /// Repository as owner/name (defaults to $GITHUB_REPOSITORY)
+    #[arg(long, help = "Repository as owner/name (defaults to $GITHUB_REPOSITORY)")]
+    pub repo: Option<String>,
+
+    /// Pull request number (defaults to the PR that triggered the workflow)
+    #[arg(long, help = "Pull request number (detected in GitHub Actions)")]
+    pub pr: Option<u64>,
+
+    /// Key identifying the sticky comment, for workflows that post more than one
+    #[arg(long, default_value = "spklr", help = "Sticky comment key (defaults to spklr)")]
+    pub marker: String,
+}
+
+/// mise/asdf pin arguments
+#[derive(Args)]
+pub struct MiseArgs {
+    /// Pin file format
+    #[arg(long, default_value = "mise", value_parser = ["mise", "tool-versions"], help = "Format: mise (.mise.toml, default), tool-versions (.tool-versions read by mise)")]
+    pub format: String,
+
+    /// Pkl version to pin (defaults to the resolved Pkl CLI, then the recommended version)
+    #[arg(long, help = "Pkl version to pin (defaults to the Pkl CLI in use)")]
+    pub pkl_version: Option<String>,
+
+    /// Output file (optional, defaults to stdout)
+    #[arg(short, long, help = "Output file path (defaults to stdout)")]
+    pub output: Option<PathBuf>,
+
+    /// Overwrite existing output file
+    #[arg(short, long, help = "Force overwrite of existing output files")]
+    pub force: bool,
+}
+
+/// Version manifest arguments
+#[derive(Args)]
+pub struct VersionsArgs {
+    /// Pkl version to pin (defaults to the existing pin, then the Pkl CLI in use)
+    #[arg(long, help = "Pkl version to pin (defaults to the existing pin)")]
+    pub pkl_version: Option<String>,
+
+    /// Manifest path
+    #[arg(short, long, default_value = crate::versions::VERSIONS_FILE, help = "Manifest path (defaults to spklr-versions.json)")]
+    pub output: PathBuf,
+}
+
+/// Nix flake arguments
+#[derive(Args)]
+pub struct NixArgs {
+    /// Pkl version to pin (defaults to the resolved Pkl CLI, then the recommended version)
+    #[arg(long, help = "Pkl version to pin (defaults to the Pkl CLI in use)")]
+    pub pkl_version: Option<String>,
+
+    /// Download archives that have no published checksum to hash them locally
+    #[arg(long, help = "Download archives without a published checksum to compute their hashes")]
+    pub prefetch: bool,
+
+    /// Output file (optional, defaults to stdout)
+    #[arg(short, long, help = "Output file path, e.g. nix/spklr.nix (defaults to stdout)")]
+    pub output: Option<PathBuf>,
+
+    /// Overwrite existing output file
+    #[arg(short, long, help = "Force overwrite of existing output files")]
+    pub force: bool,
+}
+
+/// Handle ci command execution
+pub async fn handle_ci(commands: CiCommands) -> Result<(), CliError> {
+    match commands {
+        CiCommands::Bazel(args) => {
+            let rules = crate::ci::bazel::render_rules(&BazelRuleOptions {
+                spklr_label: args.spklr_label,
+                pkl_label: args.pkl_label,
+            });
+            write_snippet(args.output.as_ref(), args.force, rules).await
+        }
+        CiCommands::Comment(args) => {
+            use crate::ci::comment::{CommentAction, DEFAULT_API_URL};
+
+            let tool_config = ToolConfig::discover()?;
+            let token = args
+                .github_token
+                .or(tool_config.ci.github_token.clone())
+                .or_else(|| std::env::var("GITHUB_TOKEN").ok())
+                .ok_or_else(|| missing_setting("a GitHub token", "--github-token, [ci] github_token in spklr.toml, or GITHUB_TOKEN"))?;
+            let repo = args
+                .repo
+                .or_else(|| std::env::var("GITHUB_REPOSITORY").ok())
+                .ok_or_else(|| missing_setting("the repository", "--repo or GITHUB_REPOSITORY"))?;
+            let pr = args
+                .pr
+                .or_else(crate::ci::comment::pr_number_from_env)
+                .ok_or_else(|| missing_setting("the pull request number", "--pr"))?;
+            let api_url = std::env::var("GITHUB_API_URL").unwrap_or_else(|_| DEFAULT_API_URL.to_string());
+
+            let mut summaries = Vec::new();
+            for path in &args.summaries {
+                crate::types::ensure_file_exists(path)?;
+                summaries.push(tokio::fs::read_to_string(path).await.map_err(|e| CliError::IoError {
+                    context: format!("Reading summary: {}", path.display()),
+                    source: e,
+                })?);
+            }
+
+            let policy = tool_config.retry_policy(Operation::Github);
+            let marker = crate::ci::comment::sticky_marker(&args.marker);
+            let body = crate::ci::comment::render_comment_body(&marker, &summaries);
+            match crate::ci::comment::post_sticky_comment(&api_url, &token, &repo, pr, &marker, &body, &policy).await? {
+                CommentAction::Created(id) => println!("💬 Posted comment {} on {}#{}", id, repo, pr),
+                CommentAction::Updated(id) => println!("💬 Updated comment {} on {}#{}", id, repo, pr),
+            }
+            Ok(())
+        }
+        CiCommands::Mise(args) => {
+            let pkl_version = match args.pkl_version {
+                Some(version) => version,
+                None => resolved_pkl_version().await,
+            };
+            let spklr_version = env!("CARGO_PKG_VERSION");
+            let pins = match args.format.as_str() {
+                "tool-versions" => crate::ci::mise::render_tool_versions(spklr_version, &pkl_version),
+                _ => crate::ci::mise::render_mise_toml(spklr_version, &pkl_version),
+            };
+            write_snippet(args.output.as_ref(), args.force, pins).await
+        }
+        CiCommands::Versions(args) => {
+            use crate::versions::VersionManifest;
+
+            let existing = if args.output.exists() {
+                Some(VersionManifest::load(&args.output)?)
+            } else {
+                None
+            };
+            let pkl_version = match (args.pkl_version, &existing) {
+                (Some(version), _) => version,
+                (None, Some(manifest)) => manifest.pkl.clone(),
+                (None, None) => resolved_pkl_version().await,
+            };
+
+            let manifest = VersionManifest::current(pkl_version);
+            if existing.as_ref() == Some(&manifest) {
+                println!("✅ {} is up to date", args.output.display());
+                return Ok(());
+            }
+            manifest.write(&args.output)?;
+            println!("📌 Wrote {}", args.output.display());
+            Ok(())
+        }
+        CiCommands::Nix(args) => {
+            let pkl_version = match args.pkl_version {
+                Some(version) => version,
+                None => resolved_pkl_version().await,
+            };
+            eprintln!("📌 Pinning Pkl {}", pkl_version);
+
+            let mut pkl_executables = Vec::new();
+            for (system, executable) in NIX_SYSTEMS {
+                let url = crate::pkl_tooling::pkl_release_url(&pkl_version, executable);
+                let hash = archive_sha256(&url, args.prefetch)
+                    .await?
+                    .and_then(|hex| crate::ci::nix::sri_from_hex(&hex));
+                if hash.is_none() {
+                    eprintln!("⚠️  No checksum for {}; using lib.fakeHash (rerun with --prefetch)", system);
+                }
+                pkl_executables.push(PklExecutablePin {
+                    system: system.to_string(),
+                    url,
+                    hash,
+                });
+            }
+
+            let flake = crate::ci::nix::render_flake(&NixFlakeOptions {
+                spklr_version: env!("CARGO_PKG_VERSION").to_string(),
+                pkl_version,
+                pkl_executables,
+            });
+            write_snippet(args.output.as_ref(), args.force, flake).await
+        }
+    }
+}
+
+fn missing_setting(what: &str, how: &str) -> CliError {
+    CliError::Generic(format!("Could not determine {}; pass {}", what, how))
+}
+
+/// Version of the Pkl CLI this machine would use, falling back to the recommended pin
+async fn resolved_pkl_version() -> String {
+    crate::pkl_tooling::find_pkl_executable()
+        .await
+        .ok()
+        .flatten()
+        .and_then(|pkl| pkl.version)
+        .unwrap_or_else(|| crate::pkl_tooling::get_recommended_pkl_version().to_string())
+}
+
+/// Hex SHA-256 of a release asset: the published checksum, or a local download with `prefetch`
+async fn archive_sha256(url: &str, prefetch: bool) -> Result<Option<String>, CliError> {
+    let urls = [url.to_string()];
+    let tool_config = ToolConfig::discover()?;
+    let checksum_policy = tool_config.retry_policy(Operation::Checksum);
+    if let Some(digest) = crate::download::fetch_published_sha256(&urls, &checksum_policy).await {
+        return Ok(Some(digest));
+    }
+    if !prefetch {
+        return Ok(None);
+    }
+
+    eprintln!("📥 Prefetching {}", url);
+    let dir = tempfile::tempdir().map_err(|e| CliError::IoError {
+        context: "Creating prefetch directory".to_string(),
+        source: e,
+    })?;
+    let archive = dir.path().join("archive");
+    crate::download::download_resumable(&urls, &archive, None, &tool_config.retry_policy(Operation::Download)).await?;
+    crate::download::sha256_file(&archive).await.map(Some)
+}
+
+/// Write a generated snippet to `output`, or stdout if none was given
+async fn write_snippet(output: Option<&PathBuf>, force: bool, content: String) -> Result<(), CliError> {
+    match output {
+        Some(path) => {
+            crate::types::ensure_output_writable(path, force)?;
+            crate::atomic_write::write_atomic(path, content).await?;
+            println!("✅ Generated: {}", path.display());
+        }
+        None => print!("{}", content),
+    }
+    Ok(())
+}
@@ -0,0 +1,56 @@
+//! Config command implementation for Space Pklr
+//!
+//! Shows the CLI defaults in effect (see [`crate::cli_defaults`]).
+
+use clap::{Args, Subcommand};
+
+use crate::types::CliError;
+
+/// Config subcommands
+#[derive(Subcommand)]
+pub enum ConfigCommands {
+    /// Print the merged CLI defaults and the files they came from
+    Show(ConfigShowArgs),
+}
+
+/// `spklr config show` arguments
+#[derive(Args)]
+pub struct ConfigShowArgs {
+    /// Print the defaults as JSON
+    #[arg(long, help = "Print the defaults and their sources as JSON")]
+    pub json: bool,
+}
+
+/// Handle config command execution
+pub async fn handle_config(commands: ConfigCommands) -> Result<(), CliError> {
+    match commands {
+        ConfigCommands::Show(args) => {
+            // Global flags given with this command are already applied
+            let loaded = crate::cli_defaults::active();
+            if args.json {
+                let document = serde_json::json!({
+                    "sources": loaded.sources,
+                    "defaults": loaded.defaults,
+                });
+                let json = serde_json::to_string_pretty(&document)
+                    .map_err(|e| CliError::Generic(format!("Failed to serialize defaults: {}", e)))?;
+                println!("{}", json);
+                return Ok(());
+            }
+
+            if loaded.sources.is_empty() {
+                println!(
+                    "# No {} here or above, and no user defaults",
+                    crate::cli_defaults::PROJECT_DEFAULTS_FILE
+                );
+            }
+            for source in &loaded.sources {
+                println!("# From {}", source.display());
+            }
+            let toml = toml::to_string_pretty(&loaded.defaults)
+                .map_err(|e| CliError::Generic(format!("Failed to serialize defaults: {}", e)))?;
+            print!("{}", toml);
+            Ok(())
+        }
+    }
+}
@@ -1,27 +1,51 @@
 //! Convert command implementation for Space Pklr
 //!
-//! This module handles configuration file conversion between formats
-//!.
+//! This module handles configuration file conversion between formats: one file with
+//! `--input`, or many files and glob patterns at once (see [`crate::batch`]). `-` as the
+//! input reads stdin and as the output writes stdout, for pipes:
+//!
+//! ```sh
+//! cat moon.yml | spklr convert --config-type project --from yaml --to pkl - > moon.pkl
+//! ```
+//!
+//! Whenever the converted config goes to stdout, progress messages go to stderr.
 
 use clap::Args;
 use miette::Result;
-use std::path::PathBuf;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 
-use crate::types::{CliError, SchemaFormat, MoonConfig};
+use crate::batch::FileOutcome;
+use crate::config_processor::{STDIO, is_stdio};
+use crate::env_tokens::EnvTokenPolicy;
+use crate::timings::{self, Phase, Timer};
+use crate::types::{CliError, ConfigHeader, SchemaFormat, MoonConfig};
 
 /// Convert command arguments.
 #[derive(Args)]
 pub struct ConvertArgs {
-    /// Moon configuration type (required for type safety)
-    #[arg(long, help = "Configuration type: project, workspace, template, toolchain, task")]
-    pub config_type: MoonConfig,
+    /// Moon configuration type (optional, inferred from each file name)
+    #[arg(long, help = "Configuration type: project, workspace, template, toolchain, task (inferred if not specified)")]
+    pub config_type: Option<MoonConfig>,
 
     /// Path to the input configuration file
-    #[arg(short, long, help = "Input configuration file path")]
-    pub input: PathBuf,
+    #[arg(short, long, required_unless_present = "inputs", help = "Input configuration file path (- for stdin)")]
+    pub input: Option<PathBuf>,
+
+    /// Files and glob patterns to convert in one run
+    #[arg(
+        value_name = "FILES",
+        conflicts_with = "input",
+        help = "Files or glob patterns (e.g. 'src/**/moon.yml') to convert in one run, or - for stdin"
+    )]
+    pub inputs: Vec<String>,
+
+    /// Directory the files given as FILES are converted into
+    #[arg(long, value_name = "DIR", requires = "inputs", help = "Write converted FILES under DIR, keeping their paths (without it, FILES are only checked)")]
+    pub out_dir: Option<PathBuf>,
 
     /// Path to the output file (optional, defaults to stdout)
-    #[arg(short, long, help = "Output file path (defaults to stdout)")]
+    #[arg(short, long, help = "Output file path (defaults to stdout, also written as -)")]
     pub output: Option<PathBuf>,
 
     /// Input format (optional, auto-detected if not provided)
@@ -35,48 +59,293 @@ pub struct ConvertArgs {
     /// Overwrite existing output file
     #[arg(short, long, help = "Force overwrite of existing output files")]
     pub force: bool,
+
+    /// Rhai script run over the parsed config before rendering (requires the `scripting` feature)
+    #[arg(long, help = "Transform script (.rhai) applied between parse and render")]
+    pub script: Option<PathBuf>,
+
+    /// Decode the input with a codec plugin declared in spklr.toml (requires the `wasm_plugins` feature)
+    #[arg(long, conflicts_with = "from", help = "Codec plugin name for the input format")]
+    pub from_plugin: Option<String>,
+
+    /// Render the output with a renderer plugin declared in spklr.toml (requires the `wasm_plugins` feature)
+    #[arg(long, conflicts_with = "to", help = "Renderer plugin name for the output format")]
+    pub to_plugin: Option<String>,
+
+    /// Convert the output back to the input format and fail if anything was lost or changed
+    #[arg(long, conflicts_with_all = ["from_plugin", "to_plugin"], help = "Verify the conversion is lossless by converting back and diffing")]
+    pub verify_roundtrip: bool,
+
+    /// Start Pkl output with `amends`/`extends` pointing at the generated schema module
+    #[arg(long, value_name = "KIND", help = "Pkl output header: none (default), amends, extends")]
+    pub pkl_header: Option<ConfigHeader>,
+
+    /// Directory of the generated schema modules the header points at
+    #[arg(long, value_name = "DIR", requires = "pkl_header", help = "Generated Pkl schema directory (defaults to the current directory)")]
+    pub schema_dir: Option<PathBuf>,
+
+    /// What Pkl output does with `$NAME`/`${NAME}` environment variable tokens
+    #[arg(
+        long,
+        value_name = "POLICY",
+        conflicts_with = "to_plugin",
+        help = "Env var tokens in Pkl output: preserve (default), read-env (read(\"env:NAME\")), error"
+    )]
+    pub env_policy: Option<EnvTokenPolicy>,
 }
 
 /// Handle convert command execution
 pub async fn handle_convert(args: ConvertArgs) -> Result<(), CliError> {
-    use crate::_rewrite::{load_config, convert_config, ensure_pkl_available};
+    use crate::_rewrite::{load_config, ensure_pkl_available};
+    use crate::convert::ConfigConverter;
+    use crate::types::{LoadedConfig, moon::UnknownConfig};
+
 
+    let input = match (&args.input, args.inputs.as_slice()) {
+        (Some(input), _) => input.clone(),
+        (None, [single]) if single == STDIO => PathBuf::from(STDIO),
+        (None, _) => return convert_batch(&args).await,
+    };
+    let config_type = args
+        .config_type
+        .or_else(|| crate::policy::infer_config_type(&input))
+        .ok_or_else(|| CliError::Generic(format!("Can't tell the config type of {}; pass --config-type", input.display())))?;
 
     // Validate arguments
-    validate_convert_args(&args)?;
+    validate_convert_args(&args, &input)?;
 
-    println!("🔄 Converting {} configuration...", args.config_type);
-    println!("📁 Input: {}", args.input.display());
+    status(&args, format!("🔄 Converting {} configuration...", config_type));
+    status(&args, format!("📁 Input: {}", input.display()));
 
-    // Load the configuration file
-    let (content, detected_input_format) = load_config(&args.input, args.config_type, args.from).await?;
+    if args.from_plugin.is_some() || args.to_plugin.is_some() {
+        let converted_content = convert_with_plugins(&args, &input).await?;
+        let from = match (&args.from_plugin, &args.from) {
+            (Some(plugin), _) => plugin.clone(),
+            (None, Some(format)) => format.to_string(),
+            (None, None) => "auto".to_string(),
+        };
+        let to = match &args.to_plugin {
+            Some(plugin) => plugin.clone(),
+            None => args.to.clone().unwrap_or(SchemaFormat::Yaml).to_string(),
+        };
+        let header = match (&args.to_plugin, &args.to) {
+            (None, Some(SchemaFormat::Pkl)) => schema_header(&args, config_type)?,
+            _ => None,
+        };
+        let write = move |out: &mut dyn Write| {
+            write_header(out, header.as_deref())?;
+            out.write_all(converted_content.as_bytes()).map_err(write_err)
+        };
+        return write_converted(&args, &input, write, from, to).await;
+    }
+
+    // Load the configuration file; stdin can only be read once, so its content is kept
+    let conversion_timer = Timer::start(Phase::Conversion);
+    let (content, detected_input_format) = if is_stdio(&input) {
+        let from = args
+            .from
+            .clone()
+            .ok_or_else(|| CliError::Generic("Reading a config from stdin needs its format (--from)".to_string()))?;
+        (crate::config_processor::read_input(&input).await?, from)
+    } else {
+        load_config(&input, config_type, args.from.clone()).await?
+    };
+    drop(conversion_timer);
 
     // Apply format defaults with Pkl preferences
-    let output_format = apply_format_defaults_with_pkl(Some(detected_input_format.clone()), args.to);
+    let output_format = apply_format_defaults_with_pkl(Some(detected_input_format.clone()), args.to.clone());
 
-    println!("🔧 Converting from {} to {}", detected_input_format, output_format);
+    status(&args, format!("🔧 Converting from {} to {}", detected_input_format, output_format));
 
     // Check if Pkl CLI is needed and available
     if detected_input_format == SchemaFormat::Pkl || output_format == SchemaFormat::Pkl {
         match ensure_pkl_available().await {
             Ok(_) => {
-                println!("✅ Pkl CLI is available");
+                status(&args, "✅ Pkl CLI is available");
             }
             Err(_) => {
-                println!("⚠️  Pkl CLI not found. To use Pkl conversions, install it with:");
-                println!("   spklr install pkl");
+                status(&args, "⚠️  Pkl CLI not found. To use Pkl conversions, install it with:");
+                status(&args, "   spklr install pkl");
 
                 // For now, proceed with placeholder conversion
-                println!("🔄 Proceeding with basic conversion (full Pkl support requires Pkl CLI)");
+                status(&args, "🔄 Proceeding with basic conversion (full Pkl support requires Pkl CLI)");
+            }
+        }
+    }
+
+    // Convert the configuration, running the transform script on the parsed value if given
+    // and renaming properties to the `[generator]` casing
+    let generator = crate::tool_config::ToolConfig::discover()?.generator;
+    let value = {
+        let _timer = Timer::start(Phase::Conversion);
+        let value = load_value(&input, &content, &detected_input_format).await?;
+        let value = match &args.script {
+            Some(script) => {
+                status(&args, format!("📜 Applying transform script: {}", script.display()));
+                crate::scripting::run_transform_script(script, value)?
             }
+            None => value,
+        };
+        if generator.recases_properties() {
+            crate::pkl_schema::casing::recase_value(&value, &config_schema(config_type)?, &generator)
+        } else {
+            value
+        }
+    };
+    // The converted config is rendered as it's written, so it's never held in memory whole
+    let converter = ConfigConverter::new(
+        LoadedConfig::Unknown(UnknownConfig::new(value)),
+        detected_input_format.clone(),
+        output_format.clone(),
+    )
+    .env_policy(args.env_policy.unwrap_or_default());
+
+    if args.verify_roundtrip {
+        // With a script or renamed properties, the value that was rendered is what the
+        // conversion has to preserve
+        status(&args, format!("🔁 Verifying round trip {} → {} → {}", detected_input_format, output_format, detected_input_format));
+        let converted_content = timings::time(Phase::Render, || converter.convert())?;
+        crate::convert::verify_roundtrip(&converter.to_value()?, &converted_content, &detected_input_format, &output_format).await?;
+        status(&args, "✅ Round trip is lossless");
+    }
+
+    let header = if output_format == SchemaFormat::Pkl {
+        schema_header(&args, config_type)?
+    } else {
+        if args.pkl_header.is_some() {
+            status(&args, "⚠️  --pkl-header only applies to Pkl output; ignoring it");
+        }
+        if args.env_policy.is_some() {
+            status(&args, "⚠️  --env-policy only applies to Pkl output; ignoring it");
+        }
+        None
+    };
+    let write = move |out: &mut dyn Write| {
+        write_header(out, header.as_deref())?;
+        converter.write_to(out)
+    };
+    write_converted(&args, &input, write, detected_input_format.to_string(), output_format.to_string()).await
+}
+
+/// The input's config value: a file is loaded from disk, so Pkl resolves its imports next
+/// to it, but stdin only from the content already read
+async fn load_value(input: &Path, content: &str, format: &SchemaFormat) -> Result<serde_json::Value, CliError> {
+    if is_stdio(input) {
+        crate::config_processor::load_config_source(content, format).await
+    } else {
+        crate::config_processor::load_config_value(input, Some(format.clone())).await
+    }
+}
+
+/// Progress messages go to stderr when the converted config goes to stdout
+fn status(args: &ConvertArgs, message: impl std::fmt::Display) {
+    if writes_stdout(args) {
+        eprintln!("{}", message);
+    } else {
+        println!("{}", message);
+    }
+}
+
+fn writes_stdout(args: &ConvertArgs) -> bool {
+    args.output.as_deref().is_none_or(is_stdio)
+}
+
+/// The module generated for `config_type`, with property names as Moon's schema has them
+fn config_schema(config_type: MoonConfig) -> Result<crate::pkl_schema::PklModule, CliError> {
+    let content = crate::moon_schema::generate_schema(config_type, "json-schema")
+        .map_err(|e| CliError::Generic(format!("Failed to generate {} schema: {}", config_type, e)))?;
+    let schema: serde_json::Value = serde_json::from_str(&content).map_err(|e| CliError::ValidationError {
+        source: Box::new(e),
+    })?;
+    crate::pkl_schema::json_schema::to_module(&schema, &config_type.to_string())
+}
+
+/// The `--pkl-header` line for Pkl output, relative to where the output is written
+fn schema_header(args: &ConvertArgs, config_type: MoonConfig) -> Result<Option<String>, CliError> {
+    let header = args.pkl_header.clone().unwrap_or_default();
+    let output_dir = args
+        .output
+        .as_ref()
+        .and_then(|output| output.parent())
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or(std::path::Path::new("."));
+    let schema_dir = args.schema_dir.as_deref().unwrap_or(std::path::Path::new("."));
+    let templates = crate::tool_config::ToolConfig::discover()?.templates;
+    crate::convert::schema_header(&header, config_type, schema_dir, output_dir, &templates)
+}
+
+/// Start the output with the `--pkl-header` line, if there is one
+fn write_header(out: &mut dyn Write, header: Option<&str>) -> Result<(), CliError> {
+    match header {
+        Some(line) => write!(out, "{}\n\n", line).map_err(write_err),
+        None => Ok(()),
+    }
+}
+
+fn write_err(e: std::io::Error) -> CliError {
+    CliError::IoError {
+        context: "Writing converted config".to_string(),
+        source: e,
+    }
+}
+
+/// Convert through the `spklr.toml` codec/renderer plugins, falling back to the built-in formats
+async fn convert_with_plugins(args: &ConvertArgs, input_path: &Path) -> Result<String, CliError> {
+    use crate::config_processor::{load_config_value, render_config_value, write_pkl_with_env_policy};
+    use crate::tool_config::{PluginKind, ToolConfig};
+
+    let tool_config = ToolConfig::discover()?;
+    let find_plugin = |name: &str, kind: PluginKind| {
+        tool_config.plugin(name, kind).ok_or_else(|| CliError::UnsupportedFormat {
+            format: name.to_string(),
+            available: vec!["plugins declared in spklr.toml"],
+        })
+    };
+
+    let conversion_timer = Timer::start(Phase::Conversion);
+    let value = if let Some(name) = &args.from_plugin {
+        let plugin = find_plugin(name, PluginKind::Codec)?;
+        status(args, format!("🧩 Decoding with plugin: {} ({})", name, plugin.path.display()));
+        let input = crate::config_processor::read_input(input_path).await?;
+        crate::wasm_plugins::decode_with_plugin(&plugin.path, &input)?
+    } else {
+        load_config_value(input_path, args.from.clone()).await?
+    };
+
+    let value = match &args.script {
+        Some(script) => {
+            status(args, format!("📜 Applying transform script: {}", script.display()));
+            crate::scripting::run_transform_script(script, value)?
         }
+        None => value,
+    };
+    drop(conversion_timer);
+
+    let _timer = Timer::start(Phase::Render);
+
+    if let Some(name) = &args.to_plugin {
+        let plugin = find_plugin(name, PluginKind::Renderer)?;
+        status(args, format!("🧩 Rendering with plugin: {} ({})", name, plugin.path.display()));
+        crate::wasm_plugins::render_with_plugin(&plugin.path, &value)
+    } else if let (Some(SchemaFormat::Pkl), Some(env_policy)) = (&args.to, args.env_policy) {
+        let mut out = Vec::new();
+        write_pkl_with_env_policy(&value, &std::collections::BTreeMap::new(), env_policy, &mut out)?;
+        String::from_utf8(out).map_err(|e| CliError::Generic(format!("Rendered config is not UTF-8: {}", e)))
+    } else {
+        render_config_value(&value, &args.to.clone().unwrap_or(SchemaFormat::Yaml))
     }
+}
 
-    // Convert the configuration
-    let converted_content = convert_config(&content, detected_input_format, output_format.clone())?;
+/// Stream the converted config from `write` into the output file, or stdout if none (or `-`)
+/// was given
+async fn write_converted<F>(args: &ConvertArgs, input: &Path, write: F, from: String, to: String) -> Result<(), CliError>
+where
+    F: FnOnce(&mut dyn Write) -> Result<(), CliError> + Send + 'static,
+{
+    let _timer = Timer::start(Phase::Write);
+    if let Some(output_path) = args.output.as_ref().filter(|output| !is_stdio(output)) {
+        crate::read_only::ensure_allowed(format!("write {}", output_path.display()))?;
 
-    // Write output
-    if let Some(output_path) = &args.output {
         // Write to file
         if let Some(parent) = output_path.parent() {
             tokio::fs::create_dir_all(parent).await
@@ -86,28 +355,127 @@ pub async fn handle_convert(args: ConvertArgs) -> Result<(), CliError> {
                 })?;
         }
 
-        tokio::fs::write(output_path, converted_content).await
-            .map_err(|e| CliError::IoError {
-                context: format!("Writing output file: {}", output_path.display()),
-                source: e,
-            })?;
+        let _lock = crate::lock::PathLock::acquire(output_path)?;
+        crate::atomic_write::write_atomic_streamed(output_path, write).await?;
 
-        println!("✅ Successfully converted to {}", output_path.display());
+        status(args, format!("✅ Successfully converted to {}", output_path.display()));
     } else {
-        // Write to stdout
-        println!("--- Converted Configuration ---");
-        println!("{}", converted_content);
+        // Write to stdout, with nothing else on it so it can be piped
+        status(args, "--- Converted Configuration ---");
+        let mut stdout = std::io::stdout().lock();
+        write(&mut stdout)?;
+        writeln!(stdout).map_err(write_err)?;
     }
 
+    crate::report::record_conversion(crate::report::ConvertedFile {
+        input: input.to_path_buf(),
+        output: args.output.clone().filter(|output| !is_stdio(output)),
+        from,
+        to,
+    });
     Ok(())
 }
 /// Validate conversion arguments
-fn validate_convert_args(args: &ConvertArgs) -> Result<(), CliError> {
-    crate::types::ensure_file_exists(&args.input)?;
+fn validate_convert_args(args: &ConvertArgs, input: &Path) -> Result<(), CliError> {
+    if !is_stdio(input) {
+        crate::types::ensure_file_exists(&input.to_path_buf())?;
+    }
 
     if let Some(output) = &args.output {
         crate::types::ensure_output_writable(output, args.force)?;
     }
 
+    if args.out_dir.is_some() {
+        return Err(CliError::Generic("--out-dir applies to FILES; write a single file with --output".to_string()));
+    }
+
+    if let Some(script) = &args.script {
+        crate::types::ensure_file_exists(script)?;
+    }
+
+    Ok(())
+}
+
+/// Convert every file named by FILES, then print a summary table
+///
+/// A failing file doesn't stop the run; the command fails at the end if any did.
+async fn convert_batch(args: &ConvertArgs) -> Result<(), CliError> {
+    let single_file_options = [
+        ("--output", args.output.is_some()),
+        ("--from", args.from.is_some()),
+        ("--script", args.script.is_some()),
+        ("--from-plugin", args.from_plugin.is_some()),
+        ("--to-plugin", args.to_plugin.is_some()),
+        ("--verify-roundtrip", args.verify_roundtrip),
+        ("--pkl-header", args.pkl_header.is_some()),
+        ("--env-policy", args.env_policy.is_some()),
+    ];
+    if let Some((option, _)) = single_file_options.iter().find(|(_, set)| *set) {
+        return Err(CliError::Generic(format!("{} only applies to a single --input file; use --out-dir with FILES", option)));
+    }
+    if args.inputs.iter().any(|input| input == STDIO) {
+        return Err(CliError::Generic("stdin (-) can only be converted on its own".to_string()));
+    }
+    let files = crate::batch::expand(&args.inputs)?;
+    let to = args.to.clone().unwrap_or(SchemaFormat::Pkl);
+    if let Some(out_dir) = &args.out_dir {
+        crate::read_only::ensure_allowed(format!("write {}", out_dir.display()))?;
+    }
+
+    println!("🔄 Converting {} file{} to {}...", files.len(), if files.len() == 1 { "" } else { "s" }, to);
+    let concurrency = crate::tool_config::ToolConfig::discover()?.generator.concurrency;
+    let outcomes = {
+        let _timer = Timer::start(Phase::Conversion);
+        crate::batch::convert_files(&files, args.config_type, &to, args.out_dir.as_deref(), args.force, concurrency).await?
+    };
+    for outcome in outcomes.iter().filter(|outcome| outcome.output.is_some()) {
+        crate::report::record_conversion(crate::report::ConvertedFile {
+            input: outcome.input.clone(),
+            output: outcome.output.clone(),
+            from: crate::config_processor::detect_format_from_path(&outcome.input)
+                .map(|format| format.to_string())
+                .unwrap_or_default(),
+            to: to.to_string(),
+        });
+    }
+    print_summary(&outcomes);
+
+    let failed = outcomes.iter().filter(|outcome| !outcome.passed()).count();
+    if failed > 0 {
+        return Err(CliError::BatchConversionFailures {
+            failed,
+            total: outcomes.len(),
+        });
+    }
     Ok(())
 }
+
+/// One row per file: status, config type, warnings, and where it went or why it failed
+fn print_summary(outcomes: &[FileOutcome]) {
+    let width = outcomes
+        .iter()
+        .map(|outcome| outcome.input.display().to_string().len())
+        .max()
+        .unwrap_or(0)
+        .max("FILE".len());
+    println!("\n   {:<width$}  {:<9}  {:>8}  RESULT", "FILE", "TYPE", "WARNINGS", width = width);
+    for outcome in outcomes {
+        let status = if outcome.passed() { "✅" } else { "❌" };
+        let result = match (&outcome.output, outcome.errors.first()) {
+            (_, Some(error)) => error.lines().next().unwrap_or_default().to_string(),
+            (Some(output), None) => format!("→ {}", output.display()),
+            (None, None) => "ok".to_string(),
+        };
+        println!(
+            "{}  {:<width$}  {:<9}  {:>8}  {}",
+            status,
+            outcome.input.display(),
+            outcome.config_type.as_deref().unwrap_or("?"),
+            outcome.warnings,
+            result,
+            width = width
+        );
+    }
+    let passed = outcomes.iter().filter(|outcome| outcome.passed()).count();
+    println!("\n📊 {}/{} files converted", passed, outcomes.len());
+}
@@ -0,0 +1,121 @@
+//! Corpus command implementation for Space Pklr
+//!
+//! Runs the converter over the Moon repositories of a corpus manifest and reports how
+//! many of their configs pass (see [`crate::corpus`]).
+
+use clap::{Args, Subcommand};
+use std::path::PathBuf;
+
+use crate::corpus::{CorpusManifest, RepoReport, run_repo, summarize};
+use crate::types::CliError;
+
+/// Corpus subcommands
+#[derive(Subcommand)]
+pub enum CorpusCommands {
+    /// Clone the manifest's repositories, convert and validate their configs, and report
+    Run(CorpusRunArgs),
+}
+
+/// Arguments for `spklr corpus run`
+#[derive(Args)]
+pub struct CorpusRunArgs {
+    /// Corpus manifest listing the repositories
+    #[arg(long, value_name = "FILE", default_value = "corpus.toml", help = "Corpus manifest listing the repositories to check")]
+    pub manifest: PathBuf,
+
+    /// Where to clone the repositories
+    #[arg(long, value_name = "DIR", help = "Clone repositories into DIR and keep them (default: a temporary directory)")]
+    pub work_dir: Option<PathBuf>,
+
+    /// Print the reports and summary as JSON
+    #[arg(long, help = "Print the reports and summary as JSON")]
+    pub json: bool,
+}
+
+/// Handle corpus command execution
+pub async fn handle_corpus(commands: CorpusCommands) -> Result<(), CliError> {
+    match commands {
+        CorpusCommands::Run(args) => handle_run(args),
+    }
+}
+
+fn handle_run(args: CorpusRunArgs) -> Result<(), CliError> {
+    crate::read_only::ensure_allowed("clone corpus repositories")?;
+    let manifest = CorpusManifest::load(&args.manifest)?;
+    if manifest.repos.is_empty() {
+        return Err(CliError::Generic(format!("{} lists no repositories", args.manifest.display())));
+    }
+
+    // A temporary work directory is removed when it's dropped, after the run
+    let temp_dir;
+    let work_dir = match &args.work_dir {
+        Some(dir) => dir.clone(),
+        None => {
+            temp_dir = tempfile::Builder::new().prefix("spklr-corpus-").tempdir().map_err(|e| CliError::IoError {
+                context: "Creating corpus work directory".to_string(),
+                source: e,
+            })?;
+            temp_dir.path().to_path_buf()
+        }
+    };
+
+    let mut reports = Vec::new();
+    for repo in &manifest.repos {
+        if !args.json {
+            println!("📦 {} ({})", repo.name(), repo.url);
+        }
+        let report = run_repo(repo, &work_dir);
+        if !args.json {
+            print_report(&report);
+        }
+        reports.push(report);
+    }
+    let summary = summarize(&reports);
+
+    if args.json {
+        let json = serde_json::to_string_pretty(&serde_json::json!({ "repos": reports, "summary": summary }))
+            .map_err(|e| CliError::Generic(format!("Failed to serialize corpus report: {}", e)))?;
+        println!("{}", json);
+    } else {
+        println!(
+            "\n📊 {} configs in {} repositories: {} passed, {} failed ({:.1}% pass rate), {} warnings",
+            summary.configs,
+            summary.repos,
+            summary.passed,
+            summary.failed,
+            summary.pass_rate(),
+            summary.warnings
+        );
+        if summary.unavailable > 0 {
+            println!("⚠️  {} repositories couldn't be checked", summary.unavailable);
+        }
+        if !summary.top_errors.is_empty() {
+            println!("\nMost common errors:");
+            for (error, count) in &summary.top_errors {
+                println!("  {:>4} × {}", count, error);
+            }
+        }
+    }
+
+    if summary.failed > 0 || summary.unavailable > 0 {
+        return Err(CliError::CorpusFailures {
+            failed: summary.failed,
+            unavailable: summary.unavailable,
+        });
+    }
+    Ok(())
+}
+
+fn print_report(report: &RepoReport) {
+    if let Some(error) = &report.error {
+        println!("  ❌ {}", error);
+        return;
+    }
+    for config in report.configs.iter().filter(|config| !config.passed()) {
+        println!("  ❌ {} ({})", config.path.display(), config.config_type);
+        for error in &config.errors {
+            println!("     {}", error.lines().next().unwrap_or_default());
+        }
+    }
+    println!("  {}/{} configs passed", report.passed(), report.configs.len());
+}
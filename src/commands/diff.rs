@@ -0,0 +1,73 @@
+//! Diff command implementation for Space Pklr
+//!
+//! Compares two generated schema sets module by module (see [`crate::schema_diff`]).
+
+use clap::Args;
+use std::path::PathBuf;
+
+use crate::schema_diff::{ChangeKind, diff_modules, load_modules};
+use crate::types::CliError;
+
+/// Diff command arguments.
+#[derive(Args)]
+pub struct DiffArgs {
+    /// Schema set to compare from
+    #[arg(value_name = "BEFORE", help = "Schema file or directory to compare from (JSON Schema or *.ir.json)")]
+    pub before: PathBuf,
+
+    /// Schema set to compare to
+    #[arg(value_name = "AFTER", help = "Schema file or directory to compare to (JSON Schema or *.ir.json)")]
+    pub after: PathBuf,
+
+    /// Fail when the sets differ
+    #[arg(long, help = "Exit with an error when there are changes (for CI gating)")]
+    pub exit_code: bool,
+
+    /// Fail on breaking changes only
+    #[arg(long, help = "Mark breaking changes and exit with an error if there are any")]
+    pub check_compat: bool,
+
+    /// Print the changes as JSON
+    #[arg(long, help = "Print the changes as JSON")]
+    pub json: bool,
+}
+
+/// Handle diff command execution
+pub async fn handle_diff(args: DiffArgs) -> Result<(), CliError> {
+    let before = load_modules(&args.before)?;
+    let after = load_modules(&args.after)?;
+    let changes = diff_modules(&before, &after);
+
+    if args.json {
+        let json = serde_json::to_string_pretty(&changes)
+            .map_err(|e| CliError::Generic(format!("Failed to serialize schema changes: {}", e)))?;
+        println!("{}", json);
+    } else if changes.is_empty() {
+        println!("✅ No schema changes");
+    } else {
+        for change in &changes {
+            if args.check_compat && change.breaking {
+                println!("{} (breaking)", change);
+            } else {
+                println!("{}", change);
+            }
+        }
+        let count = |kind: ChangeKind| changes.iter().filter(|change| change.kind == kind).count();
+        println!(
+            "\n{} added, {} removed, {} changed",
+            count(ChangeKind::Added),
+            count(ChangeKind::Removed),
+            count(ChangeKind::Changed)
+        );
+    }
+
+    let breaking = changes.iter().filter(|change| change.breaking).count();
+    if args.check_compat && breaking > 0 {
+        return Err(CliError::BreakingSchemaChanges { count: breaking });
+    }
+
+    if args.exit_code && !changes.is_empty() {
+        return Err(CliError::SchemaChanges { count: changes.len() });
+    }
+    Ok(())
+}
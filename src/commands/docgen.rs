@@ -0,0 +1,156 @@
+//! Docgen command implementation for Space Pklr
+//!
+//! This module generates Markdown reference docs for Moon configuration types
+//!.
+
+use clap::Args;
+use std::path::PathBuf;
+
+use crate::ci::annotations::Finding;
+use crate::docgen::examples::ExampleOverrides;
+use crate::docgen::spellcheck::{self, Dictionary};
+use crate::policy::Severity;
+use crate::types::{CliError, MoonConfig};
+
+/// Docgen command arguments.
+#[derive(Args)]
+pub struct DocgenArgs {
+    /// Moon configuration type (defaults to 'all')
+    #[arg(long, default_value = "all", help = "Configuration type: project, workspace, template, toolchain, task, all (default)")]
+    pub config_type: MoonConfig,
+
+    /// Output directory for multiple pages or file path for a single page (optional, defaults to stdout)
+    #[arg(short, long, help = "Output directory for multiple pages or file path for a single page (defaults to stdout)")]
+    pub output: Option<PathBuf>,
+
+    /// Overwrite existing output files
+    #[arg(short, long, help = "Force overwrite of existing output files")]
+    pub force: bool,
+
+    /// Check documentation strings for misspellings and terminology
+    #[arg(long, help = "Spell-check documentation strings and fail on issues")]
+    pub spellcheck: bool,
+
+    /// Project dictionary (defaults to spklr-dictionary.txt in the current directory)
+    #[arg(long, requires = "spellcheck", help = "Project dictionary of accepted words and preferred terms")]
+    pub dictionary: Option<PathBuf>,
+
+    /// Curated per-property examples (defaults to examples.toml in the current directory)
+    #[arg(long, help = "TOML file mapping property paths to example lists")]
+    pub examples: Option<PathBuf>,
+
+    /// Check links in documentation strings and fail on dead ones
+    #[arg(long, help = "Check links in documentation strings and fail on dead ones")]
+    pub check_links: bool,
+
+    /// Skip the network requests of --check-links
+    #[arg(long, requires = "check_links", help = "Only list links, without checking them")]
+    pub offline: bool,
+}
+
+/// Handle docgen command execution
+pub async fn handle_docgen(args: DocgenArgs) -> Result<(), CliError> {
+    let config_types = match args.config_type {
+        MoonConfig::All => MoonConfig::all_types(),
+        config_type => vec![config_type],
+    };
+    let single_page = config_types.len() == 1;
+
+    let dictionary = match (&args.dictionary, args.spellcheck) {
+        (Some(path), _) => Dictionary::load(path)?,
+        (None, true) => Dictionary::discover()?,
+        (None, false) => Dictionary::default(),
+    };
+
+    let tool_config = crate::tool_config::ToolConfig::discover()?;
+    let template_context = crate::templates::TemplateContext::current();
+    let overrides = match &args.examples {
+        Some(path) => ExampleOverrides::load(path)?,
+        None => ExampleOverrides::discover()?,
+    };
+
+    let mut findings = Vec::new();
+    let mut documented = Vec::new();
+    for config_type in config_types {
+        println!("📚 Documenting {} configuration...", config_type);
+        let mut entries = crate::docgen::schema_docs(config_type)?;
+        overrides.apply(&mut entries);
+        tool_config.redaction.redact_entries(&mut entries);
+        let page = crate::docgen::render_markdown(&format!("{} configuration", config_type), &entries);
+
+        let page_path = match &args.output {
+            Some(output) if single_page => Some(output.clone()),
+            Some(output_dir) => Some(output_dir.join(format!("{}.md", config_type))),
+            None => None,
+        };
+        let report_file = page_path
+            .clone()
+            .unwrap_or_else(|| PathBuf::from(format!("{}.md", config_type)));
+        let page = tool_config
+            .templates
+            .wrap(&template_context, &report_file.to_string_lossy(), &page)?;
+
+        if args.spellcheck {
+            for issue in spellcheck::check(&entries, &dictionary) {
+                findings.push(Finding {
+                    file: report_file.clone(),
+                    severity: Severity::Error,
+                    rule: "spelling".to_string(),
+                    message: format!("{}: \"{}\" should be \"{}\"", issue.path, issue.word, issue.suggestion),
+                });
+            }
+        }
+
+        if args.check_links {
+            let links = crate::docgen::links::extract_links(&entries);
+            if args.offline || crate::read_only::is_enabled() {
+                println!("⏭️  Skipping {} link check(s) (offline)", links.len());
+            } else {
+                println!("🔗 Checking {} link(s)...", links.len());
+                let policy = tool_config.retry_policy(crate::network::Operation::LinkCheck);
+                for dead in crate::docgen::links::check_links(&links, &policy).await {
+                    findings.push(Finding {
+                        file: report_file.clone(),
+                        severity: Severity::Error,
+                        rule: "dead-link".to_string(),
+                        message: format!("{}: {} ({})", dead.path, dead.url, dead.reason),
+                    });
+                }
+            }
+        }
+
+        documented.extend(entries);
+
+        match page_path {
+            Some(page_path) => {
+                crate::types::ensure_output_writable(&page_path, args.force)?;
+                if let Some(parent) = page_path.parent().filter(|p| !p.as_os_str().is_empty()) {
+                    tokio::fs::create_dir_all(parent).await.map_err(|e| CliError::IoError {
+                        context: format!("Creating output directory: {}", parent.display()),
+                        source: e,
+                    })?;
+                }
+                let _lock = crate::lock::PathLock::acquire(&page_path)?;
+                crate::atomic_write::write_atomic(&page_path, page).await?;
+                println!("✅ Docs written to {}", page_path.display());
+            }
+            None => println!("{}", page),
+        }
+    }
+
+    // Overrides for other config types are expected when documenting just one
+    let unmatched = if single_page { Vec::new() } else { overrides.unmatched(&documented) };
+    for path in unmatched {
+        println!("⚠️  No documented property matches example override '{}'", path);
+    }
+
+    crate::report::record_findings(&findings);
+    for finding in &findings {
+        let icon = if finding.rule == "dead-link" { "🔗" } else { "🔤" };
+        println!("{} {} {}", icon, finding.file.display(), finding.message);
+    }
+    match findings.len() {
+        0 => Ok(()),
+        count => Err(CliError::DocIssues { count }),
+    }
+}
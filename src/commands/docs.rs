@@ -0,0 +1,176 @@
+//! Docs command implementation for Space Pklr
+//!
+//! This module builds an HTML doc site for generated schemas with pkldoc. pkldoc
+//! documents a package: every module's name must start with the package name, and a
+//! `doc-package-info.pkl` describes the package. Generated schemas have neither, so the
+//! modules are staged in a temporary directory with both before pkldoc runs.
+
+use clap::Args;
+use std::path::{Path, PathBuf};
+
+use crate::pkl_tooling::{find_pkldoc, install_pkldoc, pkldoc_module_source, pkldoc_package_info, run_pkldoc};
+use crate::types::CliError;
+
+/// File name pkldoc reads package details from
+const PACKAGE_INFO_FILE: &str = "doc-package-info.pkl";
+
+/// Docs command arguments.
+#[derive(Args)]
+pub struct DocsArgs {
+    /// Directory of generated schemas
+    #[arg(default_value = crate::init::DEFAULT_SCHEMA_DIR, help = "Directory of generated .pkl schemas")]
+    pub schemas: PathBuf,
+
+    /// Directory to write the doc site into
+    #[arg(short, long, default_value = "docs/pkl", help = "Directory to write the HTML doc site into")]
+    pub output: PathBuf,
+
+    /// Package name; defaults to the `PklProject` name, else `moon`
+    #[arg(long, help = "Package name (defaults to the PklProject name, else 'moon')")]
+    pub package: Option<String>,
+
+    /// Package version; defaults to the `PklProject` version, else `0.0.0`
+    #[arg(long, help = "Package version (defaults to the PklProject version, else '0.0.0')")]
+    pub package_version: Option<String>,
+
+    /// URI the modules are imported from
+    #[arg(long, help = "Import URI shown for the package (defaults to the PklProject baseUri, else the schema directory)")]
+    pub import_uri: Option<String>,
+
+    /// Base URL of the schemas' source, for "source" links
+    #[arg(long, help = "Base URL of the schema sources, used for source links")]
+    pub source_url: Option<String>,
+
+    /// Fail instead of downloading pkldoc when it isn't installed
+    #[arg(long, help = "Don't download pkldoc if it isn't installed")]
+    pub no_install: bool,
+}
+
+/// Handle docs command execution
+pub async fn handle_docs(args: DocsArgs) -> miette::Result<()> {
+    crate::read_only::ensure_allowed(format!("write docs to {}", args.output.display())).map_err(miette::Report::new)?;
+
+    let modules = schema_files(&args.schemas)?;
+    if modules.is_empty() {
+        return Err(miette::Report::new(CliError::Generic(format!(
+            "No .pkl schemas in {}; generate them with `spklr generate schema` first",
+            args.schemas.display()
+        ))));
+    }
+
+    let project = ProjectInfo::read(&args.schemas.join(crate::composition::PROJECT_FILE));
+    let package = args.package.or(project.name).unwrap_or_else(|| "moon".to_string());
+    let version = args.package_version.or(project.version).unwrap_or_else(|| "0.0.0".to_string());
+    let import_uri = match args.import_uri {
+        Some(import_uri) => import_uri,
+        None => match project.base_uri {
+            Some(base_uri) => format!("{}@{}#/", base_uri, version),
+            None => {
+                let dir = args.schemas.canonicalize().unwrap_or_else(|_| args.schemas.clone());
+                format!("file://{}/", dir.display())
+            }
+        },
+    };
+
+    // Stage the modules, renamed into the package, next to its doc-package-info.pkl
+    let staging = tempfile::tempdir().map_err(|e| {
+        miette::Report::new(CliError::IoError {
+            context: "Creating a staging directory for pkldoc".to_string(),
+            source: e,
+        })
+    })?;
+    let mut staged = Vec::new();
+    for module in &modules {
+        let source = std::fs::read_to_string(module).map_err(|e| {
+            miette::Report::new(CliError::IoError {
+                context: format!("Reading schema: {}", module.display()),
+                source: e,
+            })
+        })?;
+        let stem = module.file_stem().unwrap_or_default().to_string_lossy();
+        let path = staging.path().join(module.file_name().unwrap_or_default());
+        write_staged(&path, &pkldoc_module_source(&source, &package, &stem))?;
+        staged.push(path);
+    }
+    let overview = format!("Pkl schemas for Moon configuration, generated by spklr {}.", env!("CARGO_PKG_VERSION"));
+    let package_info = staging.path().join(PACKAGE_INFO_FILE);
+    write_staged(
+        &package_info,
+        &pkldoc_package_info(&package, &version, &import_uri, args.source_url.as_deref(), Some(&overview)),
+    )?;
+    staged.insert(0, package_info);
+
+    let pkldoc = match find_pkldoc() {
+        Some(pkldoc) => pkldoc,
+        None if args.no_install => {
+            return Err(miette::Report::new(CliError::PklInstallFailed {
+                reason: "pkldoc isn't installed".to_string(),
+                help: Some(format!(
+                    "Run without --no-install to download it, or set {}",
+                    crate::pkl_tooling::PKLDOC_PATH_ENV
+                )),
+            }));
+        }
+        None => install_pkldoc(None).await?,
+    };
+    tracing::info!("Using pkldoc at {}", pkldoc.path.display());
+
+    println!("📚 Documenting {} module(s) as package {} {}", modules.len(), package, version);
+    run_pkldoc(&pkldoc, &staged, &args.output).await?;
+    println!("✅ Wrote docs to {}", args.output.join("index.html").display());
+    Ok(())
+}
+
+/// `.pkl` schemas in `dir`, sorted, leaving out any doc-package-info.pkl
+fn schema_files(dir: &Path) -> miette::Result<Vec<PathBuf>> {
+    let entries = std::fs::read_dir(dir).map_err(|e| {
+        miette::Report::new(CliError::IoError {
+            context: format!("Reading schema directory: {}", dir.display()),
+            source: e,
+        })
+    })?;
+    let mut modules: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "pkl"))
+        .filter(|path| path.file_name().is_some_and(|name| name != PACKAGE_INFO_FILE))
+        .collect();
+    modules.sort();
+    Ok(modules)
+}
+
+fn write_staged(path: &Path, content: &str) -> miette::Result<()> {
+    std::fs::write(path, content).map_err(|e| {
+        miette::Report::new(CliError::IoError {
+            context: format!("Staging module for pkldoc: {}", path.display()),
+            source: e,
+        })
+    })
+}
+
+/// Package fields from a `PklProject`, where there is one
+#[derive(Default)]
+struct ProjectInfo {
+    name: Option<String>,
+    version: Option<String>,
+    base_uri: Option<String>,
+}
+
+impl ProjectInfo {
+    fn read(path: &Path) -> Self {
+        let Ok(content) = std::fs::read_to_string(path) else {
+            return Self::default();
+        };
+        let field = |name: &str| {
+            regex::Regex::new(&format!(r#"(?m)^\s*{}\s*=\s*"([^"]*)""#, name))
+                .ok()?
+                .captures(&content)
+                .map(|captures| captures[1].to_string())
+        };
+        Self {
+            name: field("name"),
+            version: field("version"),
+            base_uri: field("baseUri"),
+        }
+    }
+}
@@ -0,0 +1,76 @@
+//! Doctor command implementation for Space Pklr
+//!
+//! This module checks that the active spklr and Pkl CLI match the project's tool pins
+//!.
+
+use clap::Args;
+use std::path::PathBuf;
+
+use crate::ci::mise::ToolPins;
+use crate::types::CliError;
+
+/// Doctor command arguments.
+#[derive(Args)]
+pub struct DoctorArgs {
+    /// Project directory containing .mise.toml, mise.toml or .tool-versions
+    #[arg(default_value = ".", help = "Project directory with tool pins (defaults to the current directory)")]
+    pub dir: PathBuf,
+}
+
+/// Handle doctor command execution
+pub async fn handle_doctor(args: DoctorArgs) -> Result<(), CliError> {
+    let pins = ToolPins::discover(&args.dir)?;
+    let Some(source) = &pins.source else {
+        println!("ℹ️  No .mise.toml or .tool-versions in {}; nothing to check", args.dir.display());
+        return Ok(());
+    };
+    println!("🩺 Checking tools against {}", source.display());
+
+    let pkl_version = crate::pkl_tooling::find_pkl_executable()
+        .await
+        .ok()
+        .flatten()
+        .and_then(|pkl| pkl.version);
+
+    let mut mismatches = 0;
+    mismatches += check_pin("spklr", pins.spklr.as_deref(), Some(env!("CARGO_PKG_VERSION")));
+    mismatches += check_pin("pkl", pins.pkl.as_deref(), pkl_version.as_deref());
+
+    if mismatches > 0 {
+        return Err(CliError::ToolPinMismatch { count: mismatches });
+    }
+    Ok(())
+}
+
+/// Print one pin check, returning 1 on mismatch
+fn check_pin(tool: &str, pinned: Option<&str>, active: Option<&str>) -> usize {
+    match (pinned, active) {
+        (None, _) => {
+            println!("  ➖ {}: not pinned", tool);
+            0
+        }
+        (Some(pinned), Some(active)) if pin_matches(pinned, active) => {
+            println!("  ✅ {}: {}", tool, active);
+            0
+        }
+        (Some(pinned), Some(active)) => {
+            println!("  ❌ {}: pinned {}, active {}", tool, pinned, active);
+            1
+        }
+        (Some(pinned), None) => {
+            println!("  ❌ {}: pinned {}, not found", tool, pinned);
+            1
+        }
+    }
+}
+
+/// Whether an active version satisfies a pin (`latest`, exact, or a `1.2` prefix)
+pub fn pin_matches(pinned: &str, active: &str) -> bool {
+    let pinned = pinned.trim_start_matches('v');
+    let active = active.trim_start_matches('v');
+    pinned == "latest"
+        || pinned == active
+        || active
+            .strip_prefix(pinned)
+            .is_some_and(|rest| rest.starts_with('.'))
+}
@@ -0,0 +1,93 @@
+//! Effective command implementation for Space Pklr
+//!
+//! This module exports the effective configuration Moon sees for a project
+//!.
+
+use clap::Args;
+use serde_json::{Map, Value};
+use std::path::PathBuf;
+
+use crate::types::{CliError, SchemaFormat};
+
+/// Effective command arguments.
+#[derive(Args)]
+pub struct EffectiveArgs {
+    /// Project directory (containing moon.yml or moon.pkl) or project config file
+    #[arg(help = "Project directory or moon.yml/moon.pkl")]
+    pub project: PathBuf,
+
+    /// Workspace root containing `.moon/` (optional, found by searching upwards)
+    #[arg(long, help = "Workspace root containing .moon/ (searched upwards if not specified)")]
+    pub workspace_root: Option<PathBuf>,
+
+    /// Output format
+    #[arg(long, default_value = "yaml", help = "Output format: yaml (default), json, pkl")]
+    pub to: SchemaFormat,
+
+    /// Output file (optional, defaults to stdout)
+    #[arg(short, long, help = "Output file path (defaults to stdout)")]
+    pub output: Option<PathBuf>,
+
+    /// Overwrite existing output file
+    #[arg(short, long, help = "Force overwrite of existing output files")]
+    pub force: bool,
+}
+
+/// Handle effective command execution
+pub async fn handle_effective(args: EffectiveArgs) -> Result<(), CliError> {
+    use crate::commands::graph::{project_fallback_id, resolve_project_config};
+    use crate::config_processor::{load_config_value, print_config_value, write_config_value};
+    use crate::effective::{compose_project, find_workspace_root, inherited_task_files, moon_config_file};
+
+    if let Some(output) = &args.output {
+        crate::types::ensure_output_writable(output, args.force)?;
+    }
+
+    let project_path = resolve_project_config(&args.project)?;
+    let project = load_config_value(&project_path, None).await?;
+
+    let workspace_root = match &args.workspace_root {
+        Some(root) => Some(root.clone()),
+        // Hermetic runs take the workspace root explicitly
+        None if crate::hermetic::is_enabled() => None,
+        None => project_path.parent().and_then(find_workspace_root),
+    };
+    let Some(workspace_root) = workspace_root else {
+        return Err(CliError::Generic(format!(
+            "No .moon directory found above {}; pass --workspace-root",
+            project_path.display()
+        )));
+    };
+    eprintln!("🏠 Workspace: {}", workspace_root.display());
+
+    let mut effective = Map::new();
+    if let Some(workspace_path) = moon_config_file(&workspace_root, "workspace") {
+        eprintln!("🧩 Workspace config: {}", workspace_path.display());
+        effective.insert("workspace".to_string(), load_config_value(&workspace_path, None).await?);
+    }
+
+    let mut inherited = Vec::new();
+    for task_file in inherited_task_files(&workspace_root, &project) {
+        eprintln!("🧩 Inherited tasks: {}", task_file.display());
+        inherited.push(load_config_value(&task_file, None).await?);
+    }
+    eprintln!("🧩 Project config: {}", project_path.display());
+    effective.insert(
+        "project".to_string(),
+        compose_project(&inherited, &project, &project_fallback_id(&project_path)),
+    );
+
+    let effective = Value::Object(effective);
+    match &args.output {
+        Some(output_path) => {
+            let _lock = crate::lock::PathLock::acquire(output_path)?;
+            let format = args.to.clone();
+            crate::atomic_write::write_atomic_streamed(output_path, move |out| write_config_value(&effective, &format, out))
+                .await?;
+            println!("✅ Effective config written to {}", output_path.display());
+        }
+        None => print_config_value(&effective, &args.to)?,
+    }
+
+    Ok(())
+}
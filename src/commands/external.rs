@@ -0,0 +1,174 @@
+//! External command dispatch for Space Pklr
+//!
+//! Unknown subcommands resolve to `spklr-<name>` executables on PATH, the same way
+//! cargo and git find their plugins. The plugin receives the remaining arguments,
+//! a handful of `SPKLR_*` environment variables, and a JSON context document on
+//! stdin, so it doesn't need to re-discover the Pkl installation itself.
+//!
+//! Context document (`protocol` is bumped on breaking changes):
+//!
+//! ```json
+//! {
+//!   "protocol": 1,
+//!   "spklr_version": "0.1.0",
+//!   "spklr_path": "/usr/local/bin/spklr",
+//!   "command": "audit",
+//!   "args": ["--strict"],
+//!   "cwd": "/work/repo",
+//!   "recommended_pkl_version": "0.28.0",
+//!   "pkl": { "path": "/usr/bin/pkl", "version": "0.28.1", "source": "SystemPath" }
+//! }
+//! ```
+
+use serde_json::json;
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+use std::process::Stdio;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+use crate::types::CliError;
+
+/// Version of the JSON context handed to external commands
+pub const EXTERNAL_PROTOCOL_VERSION: u32 = 1;
+
+/// Prefix for external command executables
+const EXTERNAL_PREFIX: &str = "spklr-";
+
+/// Run an external `spklr-<name>` command, returning its exit code
+///
+/// `args` is what clap collected for the external subcommand: the name followed by its arguments.
+/// The child is awaited without blocking the runtime. A child killed by a signal reports
+/// `128 + signal`, as shells do.
+pub async fn handle_external(args: Vec<String>) -> Result<i32, CliError> {
+    let Some((name, rest)) = args.split_first() else {
+        return Err(CliError::Generic("Missing external command name".to_string()));
+    };
+
+    let executable = which::which(format!("{}{}", EXTERNAL_PREFIX, name)).map_err(|_| {
+        let available = list_external_commands();
+        let help = if available.is_empty() {
+            format!(
+                "Run `spklr --help` for built-in commands, or install a `{}{}` executable on your PATH",
+                EXTERNAL_PREFIX, name
+            )
+        } else {
+            format!("Installed external commands: {}", available.join(", "))
+        };
+        CliError::ExternalCommandNotFound {
+            name: name.clone(),
+            help,
+        }
+    })?;
+
+    tracing::info!("Dispatching to external command: {}", executable.display());
+    // External commands can't be sandboxed, so they're refused outright
+    crate::read_only::ensure_allowed(format!("run external command {}", executable.display()))?;
+
+    let context = build_context(name, rest).await;
+    let context_json = serde_json::to_string(&context)
+        .map_err(|e| CliError::Generic(format!("Failed to serialize command context: {}", e)))?;
+
+    let mut cmd = Command::new(&executable);
+    cmd.args(rest)
+        .stdin(Stdio::piped())
+        .env("SPKLR_VERSION", env!("CARGO_PKG_VERSION"))
+        .env("SPKLR_PROTOCOL", EXTERNAL_PROTOCOL_VERSION.to_string())
+        .env(
+            "SPKLR_RECOMMENDED_PKL_VERSION",
+            crate::pkl_tooling::get_recommended_pkl_version(),
+        );
+    if let Ok(current_exe) = std::env::current_exe() {
+        cmd.env("SPKLR_BIN", current_exe);
+    }
+    if let Some(pkl_path) = context["pkl"]["path"].as_str() {
+        cmd.env("SPKLR_PKL_PATH", pkl_path);
+    }
+
+    let mut child = cmd.spawn().map_err(|e| CliError::IoError {
+        context: format!("Starting external command: {}", executable.display()),
+        source: e,
+    })?;
+
+    // Plugins that don't read stdin close it early; a broken pipe isn't an error
+    if let Some(mut stdin) = child.stdin.take() {
+        let _ = stdin.write_all(context_json.as_bytes()).await;
+    }
+
+    let status = child.wait().await.map_err(|e| CliError::IoError {
+        context: format!("Waiting for external command: {}", executable.display()),
+        source: e,
+    })?;
+
+    Ok(exit_code(status))
+}
+
+#[cfg(unix)]
+fn exit_code(status: std::process::ExitStatus) -> i32 {
+    use std::os::unix::process::ExitStatusExt;
+    status.code().or_else(|| status.signal().map(|signal| 128 + signal)).unwrap_or(1)
+}
+
+#[cfg(not(unix))]
+fn exit_code(status: std::process::ExitStatus) -> i32 {
+    status.code().unwrap_or(1)
+}
+
+/// Build the JSON context passed to external commands on stdin
+async fn build_context(name: &str, args: &[String]) -> serde_json::Value {
+    let pkl = match crate::pkl_tooling::find_pkl_executable().await {
+        Ok(Some(pkl_cli)) => json!({
+            "path": pkl_cli.path,
+            "version": pkl_cli.version,
+            "source": format!("{:?}", pkl_cli.source),
+        }),
+        _ => serde_json::Value::Null,
+    };
+
+    json!({
+        "protocol": EXTERNAL_PROTOCOL_VERSION,
+        "spklr_version": env!("CARGO_PKG_VERSION"),
+        "spklr_path": std::env::current_exe().ok(),
+        "command": name,
+        "args": args,
+        "cwd": std::env::current_dir().ok(),
+        "recommended_pkl_version": crate::pkl_tooling::get_recommended_pkl_version(),
+        "pkl": pkl,
+    })
+}
+
+/// List the names of all `spklr-<name>` executables on PATH
+pub fn list_external_commands() -> Vec<String> {
+    let Some(path_var) = std::env::var_os("PATH") else {
+        return Vec::new();
+    };
+
+    let mut names = BTreeSet::new();
+    for dir in std::env::split_paths(&path_var) {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            let Some(name) = file_name.strip_prefix(EXTERNAL_PREFIX) else {
+                continue;
+            };
+            let name = name.strip_suffix(".exe").unwrap_or(name);
+            if !name.is_empty() && is_executable(&entry.path()) {
+                names.insert(name.to_string());
+            }
+        }
+    }
+    names.into_iter().collect()
+}
+
+#[cfg(unix)]
+fn is_executable(path: &PathBuf) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path).is_ok_and(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &PathBuf) -> bool {
+    path.is_file()
+}
@@ -6,15 +6,19 @@
 use std::str::FromStr;
 use clap::{Args, Subcommand};
 use miette::Result;
+use std::collections::BTreeMap;
+use std::io::Write;
 use std::path::PathBuf;
 
-use crate::types::MoonConfig;
+use crate::report::GeneratedFile;
+use crate::timings::{self, Phase, Timer};
+use crate::types::{CliError, MoonConfig};
 
 /// Generate command with subcommands.
 #[derive(Subcommand)]
 pub enum GenerateCommands {
     /// Generate schema for a Moon configuration type
-    Schema(SchemaArgs),
+    Schema(Box<SchemaArgs>),
     /// Generate template (default) configuration file
     Template(TemplateArgs),
 }
@@ -26,9 +30,18 @@ pub struct GenerateArgs {
     #[arg(long, default_value = "all", help = "Configuration type: project, workspace, template, toolchain, task, all (default)")]
     pub config_type: MoonConfig,
 
-    /// Output directory for multiple files or file path for single output (optional, defaults to stdout)
+    /// Output directory for multiple files or file path for single output (optional, defaults to
+    /// `output_dir` in `.spklr.toml`, then stdout)
     #[arg(short, long, help = "Output directory for multiple files or file path for single output (defaults to stdout)")]
     pub output: Option<PathBuf>,
+
+    /// Set when `output` is `output_dir` from `.spklr.toml`, so single files go inside it
+    #[arg(skip)]
+    pub output_is_dir: bool,
+
+    /// Also write an in-toto attestation for the generated files (requires --output)
+    #[arg(long, requires = "output", help = "Write an in-toto attestation alongside SHA256SUMS")]
+    pub attestation: bool,
 }
 
 /// Schema generation arguments
@@ -37,8 +50,136 @@ pub struct SchemaArgs {
     #[command(flatten)]
     pub common: GenerateArgs,
 
-    #[arg(long, default_value = "all", help = "Schema format: json-schema, typescript, all (default)")]
+    #[arg(long, default_value = "all", help = "Schema format: json-schema, typescript, pkl, all (default); pkl, json-schema, or ir with --from-json-schema")]
     pub format: String,
+
+    /// Also emit `<Name>Partial` types with every property optional, for overlay/patch files
+    #[arg(long, help = "Emit *Partial types alongside the full schemas")]
+    pub partials: bool,
+
+    /// Convert arbitrary JSON Schema documents into Pkl modules instead of a Moon config type
+    #[arg(long, value_name = "PATH", conflicts_with = "partials", help = "Generate a Pkl module from a JSON Schema document (repeatable)")]
+    pub from_json_schema: Vec<PathBuf>,
+
+    /// Prefix for generated class and typealias names (overrides `[generator]` in spklr.toml;
+    /// Pkl modules only)
+    #[arg(long, value_name = "PREFIX", help = "Prefix generated Pkl type names")]
+    pub type_prefix: Option<String>,
+
+    /// Suffix for generated class and typealias names (overrides `[generator]` in spklr.toml;
+    /// Pkl modules only)
+    #[arg(long, value_name = "SUFFIX", help = "Suffix generated Pkl type names")]
+    pub type_suffix: Option<String>,
+
+    /// Name of the generated module instead of the schema's `title` or config type (one
+    /// module only)
+    #[arg(long, value_name = "NAME", help = "Name the module generated from a single source")]
+    pub module_name: Option<String>,
+
+    /// Define types shared by several `--from-json-schema` modules once and import them elsewhere
+    ///
+    /// Composed packages (`--composition`, `--format pkl`) always share their types through
+    /// the package's common module, so this only applies to `--from-json-schema`.
+    #[arg(long, requires = "from_json_schema", help = "Share types across generated modules through imports")]
+    pub split_types: bool,
+
+    /// Only keep these property paths, e.g. `project.*` (adds to `include_properties` in
+    /// spklr.toml; Pkl modules only)
+    #[arg(long, value_name = "PATTERNS", value_delimiter = ',', help = "Keep only matching property paths")]
+    pub include_properties: Vec<String>,
+
+    /// Hide these property paths, e.g. `toolchain.*.version` (adds to `exclude_properties` in
+    /// spklr.toml; Pkl modules only)
+    #[arg(long, value_name = "PATTERNS", value_delimiter = ',', help = "Hide matching property paths")]
+    pub exclude_properties: Vec<String>,
+
+    /// Policy file whose `overrides` make properties required, change defaults, or fix them
+    /// (Pkl modules only)
+    #[arg(long, value_name = "FILE", help = "Apply property overrides from a policy file")]
+    pub policy: Option<PathBuf>,
+
+    /// Compose Moon and JSON Schema sources into one Pkl package, as listed in a `composition.toml`
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["from_json_schema", "partials"], help = "Generate a Pkl package from a composition manifest")]
+    pub composition: Option<PathBuf>,
+
+    /// Write `examples/<Module>.example.pkl` configs amending each generated module
+    #[arg(long, requires = "output", help = "Also write example configs built from schema defaults and examples")]
+    pub with_examples_files: bool,
+
+    /// Render the generated modules as documentation pages instead of schemas
+    #[arg(long, value_name = "FORMAT", value_parser = ["markdown"], conflicts_with_all = ["with_examples_files", "partials"], help = "Generate Markdown docs: one page per module and per type")]
+    pub docs: Option<String>,
+
+    /// Generate only these types and the types they reference (e.g. `ProjectConfig,TaskConfig`)
+    #[arg(long, value_name = "TYPES", value_delimiter = ',', value_parser = crate::selection::parse_type_name, help = "Generate only the named types and their dependencies")]
+    pub types: Vec<String>,
+}
+
+impl GenerateArgs {
+    /// Where a single generated file named `file_name` goes, if not to stdout
+    ///
+    /// Creates the `output_dir` from `.spklr.toml` if it doesn't exist yet.
+    pub fn single_output(&self, file_name: &str) -> Result<Option<PathBuf>> {
+        let Some(output) = &self.output else {
+            return Ok(None);
+        };
+        if !self.output_is_dir {
+            return Ok(Some(output.clone()));
+        }
+        crate::read_only::ensure_allowed(format!("create {}", output.display())).map_err(miette::Report::new)?;
+        std::fs::create_dir_all(output).map_err(|e| miette::miette!("Failed to create {}: {}", output.display(), e))?;
+        Ok(Some(output.join(file_name)))
+    }
+
+    /// Write to `output_dir` from `.spklr.toml` when `--output` isn't given
+    fn apply_defaults(&mut self, defaults: &crate::cli_defaults::CliDefaults) {
+        if self.output.is_none()
+            && let Some(dir) = &defaults.output_dir
+        {
+            self.output = Some(dir.clone());
+            self.output_is_dir = true;
+        }
+    }
+}
+
+impl SchemaArgs {
+    /// Fill in flags that weren't given from `.spklr.toml`
+    fn apply_defaults(&mut self, defaults: &crate::cli_defaults::CliDefaults) {
+        self.common.apply_defaults(defaults);
+        // A default name only fits a single source; an explicit one is checked later
+        if self.module_name.is_none() && self.from_json_schema.len() == 1 {
+            self.module_name = defaults.module_name.clone();
+        }
+        if !self.generates_pkl_modules() {
+            return;
+        }
+        if self.include_properties.is_empty() {
+            self.include_properties = defaults.include_properties.clone();
+        }
+        if self.exclude_properties.is_empty() {
+            self.exclude_properties = defaults.exclude_properties.clone();
+        }
+    }
+
+    /// Whether the output is built from Pkl modules, so `[generator]` settings apply to it
+    fn generates_pkl_modules(&self) -> bool {
+        !self.from_json_schema.is_empty() || self.composition.is_some() || self.docs.is_some() || self.format == "pkl"
+    }
+
+    /// `[generator]` settings from spklr.toml with the flags given on the command line
+    fn generator_config(&self, mut config: crate::pkl_schema::GeneratorConfig) -> crate::pkl_schema::GeneratorConfig {
+        if self.type_prefix.is_some() {
+            config.type_prefix = self.type_prefix.clone();
+        }
+        if self.type_suffix.is_some() {
+            config.type_suffix = self.type_suffix.clone();
+        }
+        config.split_types |= self.split_types;
+        config.include_properties.extend(self.include_properties.iter().cloned());
+        config.exclude_properties.extend(self.exclude_properties.iter().cloned());
+        config.type_mappings.extend(crate::cli_defaults::active().defaults.type_mappings);
+        config
+    }
 }
 
 /// Template generation arguments
@@ -53,11 +194,26 @@ pub struct TemplateArgs {
 }
 
 /// Handle generate command execution
-pub async fn handle_generate(commands: GenerateCommands) -> Result<()> {
+pub async fn handle_generate(mut commands: GenerateCommands) -> Result<()> {
+    let defaults = crate::cli_defaults::active().defaults;
+    match &mut commands {
+        GenerateCommands::Schema(args) => args.apply_defaults(&defaults),
+        GenerateCommands::Template(args) => args.common.apply_defaults(&defaults),
+    }
+    let writes_files = match &commands {
+        GenerateCommands::Schema(args) => args.common.output.is_some(),
+        GenerateCommands::Template(args) => args.common.output.is_some(),
+    };
+
     match commands {
-        GenerateCommands::Schema(args) => handle_schema_generation(args).await,
-        GenerateCommands::Template(args) => handle_template_generation(args).await,
+        GenerateCommands::Schema(args) => handle_schema_generation(*args).await?,
+        GenerateCommands::Template(args) => handle_template_generation(args).await?,
     }
+
+    if writes_files && crate::versions::refresh_existing().map_err(miette::Report::new)? {
+        println!("📌 Updated {}", crate::versions::VERSIONS_FILE);
+    }
+    Ok(())
 }
 
 /// Handle schema generation using schematic's existing capabilities
@@ -65,22 +221,45 @@ pub async fn handle_schema_generation(args: SchemaArgs) -> Result<()> {
     use crate::_rewrite::{generate_schema, generate_all_schemas, generate_all_formats_schema, generate_all_schemas_all_formats};
     use crate::types::MoonConfig;
 
+    if !args.from_json_schema.is_empty() {
+        return handle_json_schema_import(&args.from_json_schema, &args).await;
+    }
+    if let Some(manifest) = &args.composition {
+        return handle_composition(manifest, &args).await;
+    }
+    if args.docs.is_some() || args.format == "pkl" {
+        // Moon's own config types become the package `spklr init` generates, so `[generator]`
+        // settings such as `sort_mode` apply to them as to any other Pkl module
+        let mut manifest = crate::composition::CompositionManifest::moon_package(crate::init::PACKAGE_NAME);
+        if args.common.config_type != MoonConfig::All {
+            let name = crate::templates::module::module_name(args.common.config_type);
+            manifest.sources.retain(|source| source.name.as_deref() == Some(name.as_str()));
+        }
+        return generate_composition(&manifest, &args).await;
+    }
+    if args.with_examples_files {
+        return Err(miette::miette!("--with-examples-files needs Pkl modules; use it with --format pkl, --from-json-schema, or --composition"));
+    }
+    let pkl_flags = [
+        ("--type-prefix", args.type_prefix.is_some()),
+        ("--type-suffix", args.type_suffix.is_some()),
+        ("--include-properties", !args.include_properties.is_empty()),
+        ("--exclude-properties", !args.exclude_properties.is_empty()),
+        ("--policy", args.policy.is_some()),
+    ];
+    if let Some((flag, _)) = pkl_flags.iter().find(|(_, given)| *given) {
+        return Err(miette::miette!("{} shapes Pkl modules; use it with --format pkl, --from-json-schema, or --composition", flag));
+    }
+
     match (&args.common.config_type, args.format.as_str()) {
         (MoonConfig::All, "all") => {
             println!("🔧 Generating schemas for all configuration types in all formats...");
-            let results = generate_all_schemas_all_formats()
+            let results = timings::time(Phase::Introspection, generate_all_schemas_all_formats)
                 .map_err(|e| miette::miette!("Failed to generate schemas: {}", e))?;
+            let results = apply_templates(apply_partials(apply_selection(apply_redaction(results)?, &args.types)?, args.partials)?)?;
 
             if let Some(output_dir) = &args.common.output {
-                tokio::fs::create_dir_all(output_dir).await
-                    .map_err(|e| miette::miette!("Failed to create output directory {}: {}", output_dir.display(), e))?;
-
-                for (filename, content) in results {
-                    let file_path = output_dir.join(&filename);
-                    tokio::fs::write(&file_path, &content).await
-                        .map_err(|e| miette::miette!("Failed to write schema to {}: {}", file_path.display(), e))?;
-                    println!("✅ Generated: {}", file_path.display());
-                }
+                write_generated_set(output_dir, results, &args.common, &args.format, "schema")?;
             } else {
                 for (filename, content) in results {
                     println!("\n=== {} ===", filename);
@@ -90,19 +269,12 @@ pub async fn handle_schema_generation(args: SchemaArgs) -> Result<()> {
         }
         (MoonConfig::All, format) => {
             println!("🔧 Generating schemas for all configuration types in {} format...", format);
-            let results = generate_all_schemas(format)
+            let results = timings::time(Phase::Introspection, || generate_all_schemas(format))
                 .map_err(|e| miette::miette!("Failed to generate schemas: {}", e))?;
+            let results = apply_templates(apply_partials(apply_selection(apply_redaction(results)?, &args.types)?, args.partials)?)?;
 
             if let Some(output_dir) = &args.common.output {
-                tokio::fs::create_dir_all(output_dir).await
-                    .map_err(|e| miette::miette!("Failed to create output directory {}: {}", output_dir.display(), e))?;
-
-                for (filename, content) in results {
-                    let file_path = output_dir.join(&filename);
-                    tokio::fs::write(&file_path, &content).await
-                        .map_err(|e| miette::miette!("Failed to write schema to {}: {}", file_path.display(), e))?;
-                    println!("✅ Generated: {}", file_path.display());
-                }
+                write_generated_set(output_dir, results, &args.common, &args.format, "schema")?;
             } else {
                 for (filename, content) in results {
                     println!("\n=== {} ===", filename);
@@ -112,19 +284,12 @@ pub async fn handle_schema_generation(args: SchemaArgs) -> Result<()> {
         }
         (config_type, "all") => {
             println!("🔧 Generating {} schemas in all formats...", config_type);
-            let results = generate_all_formats_schema(*config_type)
+            let results = timings::time(Phase::Introspection, || generate_all_formats_schema(*config_type))
                 .map_err(|e| miette::miette!("Failed to generate schemas: {}", e))?;
+            let results = apply_templates(apply_partials(apply_selection(apply_redaction(results)?, &args.types)?, args.partials)?)?;
 
             if let Some(output_dir) = &args.common.output {
-                tokio::fs::create_dir_all(output_dir).await
-                    .map_err(|e| miette::miette!("Failed to create output directory {}: {}", output_dir.display(), e))?;
-
-                for (filename, content) in results {
-                    let file_path = output_dir.join(&filename);
-                    tokio::fs::write(&file_path, &content).await
-                        .map_err(|e| miette::miette!("Failed to write schema to {}: {}", file_path.display(), e))?;
-                    println!("✅ Generated: {}", file_path.display());
-                }
+                write_generated_set(output_dir, results, &args.common, &args.format, "schema")?;
             } else {
                 for (filename, content) in results {
                     println!("\n=== {} ===", filename);
@@ -136,17 +301,48 @@ pub async fn handle_schema_generation(args: SchemaArgs) -> Result<()> {
             println!("🔧 Generating {} schema in {} format...", config_type, format);
 
             // Generate schema using schematic's existing renderers
-            let schema_content = generate_schema(*config_type, format)
+            let schema_content = timings::time(Phase::Introspection, || generate_schema(*config_type, format))
                 .map_err(|e| miette::miette!("Failed to generate schema: {}", e))?;
+            let schema_content = if format == "json-schema" {
+                let redaction = crate::tool_config::ToolConfig::discover().map_err(miette::Report::new)?.redaction;
+                redaction.redact_json_schema(&schema_content).map_err(miette::Report::new)?
+            } else {
+                schema_content
+            };
+            let schema_content = if args.types.is_empty() {
+                schema_content
+            } else {
+                let selection = crate::selection::select(&schema_content, format, &args.types)
+                    .map_err(miette::Report::new)?
+                    .ok_or_else(|| miette::miette!("{} doesn't define any of: {}", config_type, args.types.join(", ")))?;
+                report_unknown_types(&args.types, &selection.matched)?;
+                selection.content
+            };
+            let schema_content = if args.partials {
+                crate::partials::add_partials(&schema_content, format).map_err(miette::Report::new)?
+            } else {
+                schema_content
+            };
+            let extension = match format {
+                "typescript" => "ts",
+                "json-schema" => "json",
+                other => other,
+            };
+            let file_name = format!("{}_schema.{}", config_type, extension);
+            let schema_content = wrap_single(&args.common, &file_name, &schema_content)?;
 
             // Output to file or stdout
-            if let Some(output_path) = &args.common.output {
-                tokio::fs::write(output_path, &schema_content)
+            if let Some(output_path) = &args.common.single_output(&file_name)? {
+                let _lock = crate::lock::PathLock::acquire(output_path).map_err(miette::Report::new)?;
+                let previous = std::fs::read_to_string(output_path).ok();
+                crate::atomic_write::write_atomic(output_path, &schema_content)
                     .await
                     .map_err(|e| miette::miette!("Failed to write schema to {}: {}",
                                                output_path.display(), e))?;
 
                 println!("✅ Schema generated successfully: {}", output_path.display());
+                crate::report::record_generated(GeneratedFile::compare(output_path, previous.as_deref(), &schema_content));
+                record_output_checksum(output_path, &args.common, &args.format, "schema")?;
             } else {
                 println!("{}", schema_content);
             }
@@ -156,6 +352,312 @@ pub async fn handle_schema_generation(args: SchemaArgs) -> Result<()> {
     Ok(())
 }
 
+/// Generate Pkl modules from arbitrary JSON Schema documents
+///
+/// With `--format json-schema`, the modules are exported back to JSON Schema instead;
+/// `--format ir` saves each module as versioned JSON (see [`crate::pkl_schema::ir`]).
+/// Several documents are written as a set into the `--output` directory.
+async fn handle_json_schema_import(sources: &[PathBuf], args: &SchemaArgs) -> Result<()> {
+    use crate::pkl_schema::SchemaGenerator;
+
+    let tool_config = crate::tool_config::ToolConfig::discover().map_err(miette::Report::new)?;
+    let overrides = tool_config.templates.overrides().map_err(miette::Report::new)?;
+    let config = args.generator_config(tool_config.generator);
+
+    let generator = SchemaGenerator::new(config.clone());
+    let mut modules = timings::time(Phase::Introspection, || generator.generate_all_from_json_schema(sources))
+        .map_err(miette::Report::new)?;
+    if let Some(name) = &args.module_name {
+        match modules.as_mut_slice() {
+            [module] => module.name = name.clone(),
+            _ => return Err(miette::miette!("--module-name names one module, but {} JSON Schemas were given", sources.len())),
+        }
+    }
+    let mut examples = BTreeMap::new();
+    if args.with_examples_files {
+        for (source, module) in sources.iter().zip(&modules) {
+            let schema = read_json_schema(source)?;
+            examples.insert(module.name.clone(), crate::pkl_schema::examples::schema_examples(&schema));
+        }
+    }
+    if !args.types.is_empty() {
+        modules = select_modules(&modules, &args.types)?;
+    }
+    if let Some(policy_path) = &args.policy {
+        apply_policy(&mut modules, policy_path).await?;
+    }
+    if config.split_types {
+        for name in crate::pkl_schema::imports::split_types(&mut modules) {
+            eprintln!("⚠️  Type '{}' is defined differently by several modules; each keeps its own", name);
+        }
+    }
+    if args.docs.is_some() {
+        return write_docs(&modules, args);
+    }
+
+    let extension = match args.format.as_str() {
+        "json-schema" => "json",
+        "ir" => "ir.json",
+        "pkl" | "all" => "pkl",
+        other => return Err(miette::miette!("Unsupported format '{}' for --from-json-schema; use pkl, json-schema, or ir", other)),
+    };
+    if args.with_examples_files && extension != "pkl" {
+        return Err(miette::miette!("--with-examples-files needs Pkl modules; use --format pkl"));
+    }
+    let context = crate::templates::TemplateContext::current();
+    let rendered = timings::time(Phase::Render, || {
+        crate::pkl_schema::parallel::map(&modules, config.concurrency, |module| match extension {
+            "json" => serde_json::to_string_pretty(&module.to_json_schema())
+                .map_err(|e| miette::miette!("Failed to serialize JSON Schema: {}", e)),
+            "ir.json" => crate::pkl_schema::ir::to_string(std::slice::from_ref(module)).map_err(miette::Report::new),
+            _ => module.render_with(&overrides, &context).map_err(miette::Report::new),
+        })
+        .into_iter()
+        .collect::<Result<Vec<_>>>()
+    })?;
+
+    if let [module] = modules.as_slice() {
+        let file_name = format!("{}.{}", module.name, extension);
+        let content = wrap_single(&args.common, &file_name, &rendered[0])?;
+        if let Some(output_path) = &args.common.single_output(&file_name)? {
+            let _lock = crate::lock::PathLock::acquire(output_path).map_err(miette::Report::new)?;
+            let previous = std::fs::read_to_string(output_path).ok();
+            crate::atomic_write::write_atomic(output_path, &content)
+                .await
+                .map_err(|e| miette::miette!("Failed to write schema to {}: {}", output_path.display(), e))?;
+
+            println!("✅ Pkl module generated successfully: {}", output_path.display());
+            crate::report::record_generated(GeneratedFile::compare(output_path, previous.as_deref(), &content));
+            record_output_checksum(output_path, &args.common, &args.format, "schema")?;
+            ensure_stubs(extension, &[(output_path.clone(), module)])?;
+            if args.with_examples_files {
+                let dir = output_path.parent().unwrap_or(std::path::Path::new("."));
+                let file_name = output_path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+                write_examples(dir, &modules, &examples, |_| file_name.clone()).await?;
+            }
+        } else {
+            println!("{}", content);
+        }
+        return Ok(());
+    }
+
+    // Module files are named after their modules, which is what cross-module imports expect
+    let results: Vec<(String, String)> = modules
+        .iter()
+        .zip(rendered)
+        .map(|(module, content)| (format!("{}.{}", module.name, extension), content))
+        .collect();
+    let results = apply_templates(results)?;
+    if let Some(output_dir) = &args.common.output {
+        write_generated_set(output_dir, results, &args.common, &args.format, "schema")?;
+        let written: Vec<(PathBuf, &crate::pkl_schema::PklModule)> = modules
+            .iter()
+            .map(|module| (output_dir.join(format!("{}.{}", module.name, extension)), module))
+            .collect();
+        ensure_stubs(extension, &written)?;
+        if args.with_examples_files {
+            write_examples(output_dir, &modules, &examples, |module| format!("{}.pkl", module.name)).await?;
+        }
+    } else {
+        for (filename, content) in results {
+            println!("\n=== {} ===", filename);
+            println!("{}", content);
+        }
+    }
+    Ok(())
+}
+
+fn read_json_schema(path: &std::path::Path) -> Result<serde_json::Value> {
+    let content = std::fs::read_to_string(path).map_err(|e| miette::miette!("Failed to read {}: {}", path.display(), e))?;
+    serde_json::from_str(&content).map_err(|e| miette::miette!("Failed to parse JSON Schema {}: {}", path.display(), e))
+}
+
+/// Write an example config amending each module into `<dir>/examples`
+///
+/// `schema_file` names the module's file in `dir`.
+async fn write_examples(
+    dir: &std::path::Path,
+    modules: &[crate::pkl_schema::PklModule],
+    examples: &BTreeMap<String, BTreeMap<String, serde_json::Value>>,
+    schema_file: impl Fn(&crate::pkl_schema::PklModule) -> String,
+) -> Result<()> {
+    use crate::pkl_schema::examples::{example_document, example_file_name};
+
+    let examples_dir = dir.join("examples");
+    std::fs::create_dir_all(&examples_dir)
+        .map_err(|e| miette::miette!("Failed to create {}: {}", examples_dir.display(), e))?;
+    let none = BTreeMap::new();
+    for module in modules {
+        let module_examples = examples.get(&module.name).unwrap_or(&none);
+        let content = example_document(module, modules, module_examples, &format!("../{}", schema_file(module)));
+        let path = examples_dir.join(example_file_name(module));
+        crate::atomic_write::write_atomic(&path, &content).await.map_err(miette::Report::new)?;
+        println!("📝 Example config: {}", path.display());
+    }
+    Ok(())
+}
+
+/// Generate the Pkl package described by a composition manifest file
+async fn handle_composition(manifest_path: &std::path::Path, args: &SchemaArgs) -> Result<()> {
+    let manifest = crate::composition::CompositionManifest::load(manifest_path).map_err(miette::Report::new)?;
+    generate_composition(&manifest, args).await
+}
+
+/// Generate the Pkl package described by a composition manifest
+///
+/// Moon sources are generated as JSON Schema and imported like any other document, so
+/// every module goes through the same `[generator]` settings, generator flags and
+/// templates. The Toolchain
+/// module also gets the `[[toolchain_plugins]]` settings classes.
+pub async fn generate_composition(manifest: &crate::composition::CompositionManifest, args: &SchemaArgs) -> Result<()> {
+    use crate::moon_schema::generate_schema;
+    use crate::composition::{PROJECT_FILE, SourceKind};
+    use crate::pkl_schema::SchemaGenerator;
+
+    if args.partials {
+        return Err(miette::miette!("--partials adds JSON Schema and TypeScript types; use it with --format json-schema or typescript"));
+    }
+    if args.module_name.is_some() && manifest.sources.len() != 1 {
+        return Err(miette::miette!("--module-name names one module, but the package has {} sources", manifest.sources.len()));
+    }
+
+    let tool_config = crate::tool_config::ToolConfig::discover().map_err(miette::Report::new)?;
+    let overrides = tool_config.templates.overrides().map_err(miette::Report::new)?;
+    let config = args.generator_config(tool_config.generator.clone());
+    let generator = SchemaGenerator::new(config.clone());
+
+    println!("📦 Composing Pkl package {}...", manifest.package.name);
+    let mut modules = Vec::new();
+    let mut examples = BTreeMap::new();
+    for source in &manifest.sources {
+        let module = match source.kind().map_err(miette::Report::new)? {
+            SourceKind::Moon(config_type) => {
+                println!("🔧 Generating {} schema...", config_type);
+                let schema_content = timings::time(Phase::Introspection, || generate_schema(config_type, "json-schema"))
+                    .map_err(|e| miette::miette!("Failed to generate schema: {}", e))?;
+                let schema_content = tool_config.redaction.redact_json_schema(&schema_content).map_err(miette::Report::new)?;
+                let schema: serde_json::Value = serde_json::from_str(&schema_content)
+                    .map_err(|e| miette::miette!("Failed to parse {} JSON Schema: {}", config_type, e))?;
+                let mut module = generator
+                    .generate_from_json_value(&schema, &config_type.to_string())
+                    .map_err(miette::Report::new)?;
+                if config_type == MoonConfig::Toolchain && !tool_config.toolchain_plugins.is_empty() {
+                    let policy = tool_config.retry_policy(crate::network::Operation::Download);
+                    crate::pkl_schema::toolchain_plugins::merge_configured(&mut module, &tool_config.toolchain_plugins, &policy)
+                        .await
+                        .map_err(miette::Report::new)?;
+                    // Plugin classes are appended after the `[generator]` settings ran
+                    crate::pkl_schema::ordering::sort_module(&mut module, config.sort_mode);
+                }
+                (module, schema)
+            }
+            SourceKind::JsonSchema(path) => {
+                println!("🔧 Generating Pkl module from JSON Schema {}...", path.display());
+                let module = timings::time(Phase::Introspection, || generator.generate_from_json_schema(&path))
+                    .map_err(miette::Report::new)?;
+                let schema = if args.with_examples_files { read_json_schema(&path)? } else { serde_json::Value::Null };
+                (module, schema)
+            }
+        };
+        let (mut module, schema) = module;
+        if let Some(name) = args.module_name.as_ref().or(source.name.as_ref()) {
+            module.name = name.clone();
+        }
+        if args.with_examples_files {
+            examples.insert(module.name.clone(), crate::pkl_schema::examples::schema_examples(&schema));
+        }
+        modules.push(module);
+    }
+    if !args.types.is_empty() {
+        modules = select_modules(&modules, &args.types)?;
+    }
+    if let Some(policy_path) = &args.policy {
+        apply_policy(&mut modules, policy_path).await?;
+    }
+    crate::composition::compose(&mut modules, &manifest.package.common);
+    if args.docs.is_some() {
+        return write_docs(&modules, args);
+    }
+
+    // Each module is streamed into its file with its fragment and templates around it
+    let context = crate::templates::TemplateContext::current();
+    let project = manifest.pkl_project();
+    let files: Vec<String> = modules
+        .iter()
+        .map(|module| format!("{}.pkl", module.name))
+        .chain(std::iter::once(PROJECT_FILE.to_string()))
+        .collect();
+    let write_file = |index: usize, out: &mut dyn Write| -> Result<(), CliError> {
+        let Some(module) = modules.get(index) else {
+            return out.write_all(project.as_bytes()).map_err(|e| CliError::IoError {
+                context: format!("Writing {}", PROJECT_FILE),
+                source: e,
+            });
+        };
+        let file = &files[index];
+        crate::templates::wrap_generated_with(file, out, |out| {
+            crate::fragments::apply_with(file, out, |out| module.write_with(out, &overrides, &context))
+        })
+    };
+
+    if let Some(output_dir) = &args.common.output {
+        stream_generated_set(output_dir, &files, config.concurrency, write_file, &args.common, "pkl", "schema")?;
+        let written: Vec<(PathBuf, &crate::pkl_schema::PklModule)> = modules
+            .iter()
+            .map(|module| (output_dir.join(format!("{}.pkl", module.name)), module))
+            .collect();
+        ensure_stubs("pkl", &written)?;
+        if args.with_examples_files {
+            write_examples(output_dir, &modules, &examples, |module| format!("{}.pkl", module.name)).await?;
+        }
+    } else {
+        let indices: Vec<usize> = (0..files.len()).collect();
+        let rendered = timings::time(Phase::Render, || {
+            crate::pkl_schema::parallel::map(&indices, config.concurrency, |index| {
+                let mut out = Vec::new();
+                write_file(*index, &mut out).map(|()| out)
+            })
+            .into_iter()
+            .collect::<Result<Vec<_>, CliError>>()
+        })
+        .map_err(miette::Report::new)?;
+        for (filename, content) in files.iter().zip(rendered) {
+            println!("\n=== {} ===", filename);
+            println!("{}", String::from_utf8_lossy(&content));
+        }
+    }
+    Ok(())
+}
+
+/// Write Markdown docs for `modules` into the `--output` directory, or print them
+fn write_docs(modules: &[crate::pkl_schema::PklModule], args: &SchemaArgs) -> Result<()> {
+    println!("📚 Rendering Markdown docs for {} module(s)...", modules.len());
+    let pages = timings::time(Phase::Render, || crate::templates::docs::markdown_pages(modules));
+    let pages = apply_templates(pages)?;
+    if let Some(output_dir) = &args.common.output {
+        write_generated_set(output_dir, pages, &args.common, "markdown", "docs")?;
+    } else {
+        for (filename, content) in pages {
+            println!("\n=== {} ===", filename);
+            println!("{}", content);
+        }
+    }
+    Ok(())
+}
+
+/// Create extension stubs next to generated Pkl modules
+fn ensure_stubs(extension: &str, written: &[(PathBuf, &crate::pkl_schema::PklModule)]) -> Result<()> {
+    if extension != "pkl" {
+        return Ok(());
+    }
+    for (path, module) in written {
+        if let Some(stub) = crate::pkl_schema::extensions::ensure_stub(path, module).map_err(miette::Report::new)? {
+            println!("🧷 Created extension stub: {}", stub.display());
+        }
+    }
+    Ok(())
+}
+
 /// Handle template configuration generation using existing templates and defaults
 pub async fn handle_template_generation(args: TemplateArgs) -> Result<()> {
     use crate::_rewrite::{generate_template, generate_all_templates, generate_all_formats_template, generate_all_templates_all_formats};
@@ -164,19 +666,12 @@ pub async fn handle_template_generation(args: TemplateArgs) -> Result<()> {
     match (&args.common.config_type, args.format.as_str()) {
         (MoonConfig::All, "all") => {
             println!("🔧 Generating template configurations for all types in all formats...");
-            let results = generate_all_templates_all_formats()
+            let results = timings::time(Phase::Introspection, generate_all_templates_all_formats)
                 .map_err(|e| miette::miette!("Failed to generate templates: {}", e))?;
+            let results = apply_templates(results)?;
 
             if let Some(output_dir) = &args.common.output {
-                tokio::fs::create_dir_all(output_dir).await
-                    .map_err(|e| miette::miette!("Failed to create output directory {}: {}", output_dir.display(), e))?;
-
-                for (filename, content) in results {
-                    let file_path = output_dir.join(&filename);
-                    tokio::fs::write(&file_path, &content).await
-                        .map_err(|e| miette::miette!("Failed to write template to {}: {}", file_path.display(), e))?;
-                    println!("✅ Generated: {}", file_path.display());
-                }
+                write_generated_set(output_dir, results, &args.common, &args.format, "template")?;
             } else {
                 for (filename, content) in results {
                     println!("\n=== {} ===", filename);
@@ -189,19 +684,12 @@ pub async fn handle_template_generation(args: TemplateArgs) -> Result<()> {
                 .map_err(|e| miette::miette!("Invalid format '{}': {}", format_str, e))?;
 
             println!("🔧 Generating template configurations for all types in {} format...", format);
-            let results = generate_all_templates(format)
+            let results = timings::time(Phase::Introspection, || generate_all_templates(format))
                 .map_err(|e| miette::miette!("Failed to generate templates: {}", e))?;
+            let results = apply_templates(results)?;
 
             if let Some(output_dir) = &args.common.output {
-                tokio::fs::create_dir_all(output_dir).await
-                    .map_err(|e| miette::miette!("Failed to create output directory {}: {}", output_dir.display(), e))?;
-
-                for (filename, content) in results {
-                    let file_path = output_dir.join(&filename);
-                    tokio::fs::write(&file_path, &content).await
-                        .map_err(|e| miette::miette!("Failed to write template to {}: {}", file_path.display(), e))?;
-                    println!("✅ Generated: {}", file_path.display());
-                }
+                write_generated_set(output_dir, results, &args.common, &args.format, "template")?;
             } else {
                 for (filename, content) in results {
                     println!("\n=== {} ===", filename);
@@ -211,19 +699,12 @@ pub async fn handle_template_generation(args: TemplateArgs) -> Result<()> {
         }
         (config_type, "all") => {
             println!("🔧 Generating {} template configurations in all formats...", config_type);
-            let results = generate_all_formats_template(*config_type)
+            let results = timings::time(Phase::Introspection, || generate_all_formats_template(*config_type))
                 .map_err(|e| miette::miette!("Failed to generate templates: {}", e))?;
+            let results = apply_templates(results)?;
 
             if let Some(output_dir) = &args.common.output {
-                tokio::fs::create_dir_all(output_dir).await
-                    .map_err(|e| miette::miette!("Failed to create output directory {}: {}", output_dir.display(), e))?;
-
-                for (filename, content) in results {
-                    let file_path = output_dir.join(&filename);
-                    tokio::fs::write(&file_path, &content).await
-                        .map_err(|e| miette::miette!("Failed to write template to {}: {}", file_path.display(), e))?;
-                    println!("✅ Generated: {}", file_path.display());
-                }
+                write_generated_set(output_dir, results, &args.common, &args.format, "template")?;
             } else {
                 for (filename, content) in results {
                     println!("\n=== {} ===", filename);
@@ -238,17 +719,23 @@ pub async fn handle_template_generation(args: TemplateArgs) -> Result<()> {
             println!("🔧 Generating {} template configuration in {} format...", config_type, format);
 
             // Generate template using existing templates and defaults
-            let template_content = generate_template(*config_type, format)
+            let template_content = timings::time(Phase::Introspection, || generate_template(*config_type, format.clone()))
                 .map_err(|e| miette::miette!("Failed to generate template: {}", e))?;
+            let file_name = format!("{}.{}", config_type, format);
+            let template_content = wrap_single(&args.common, &file_name, &template_content)?;
 
             // Output to file or stdout
-            if let Some(output_path) = &args.common.output {
-                tokio::fs::write(output_path, &template_content)
+            if let Some(output_path) = &args.common.single_output(&file_name)? {
+                let _lock = crate::lock::PathLock::acquire(output_path).map_err(miette::Report::new)?;
+                let previous = std::fs::read_to_string(output_path).ok();
+                crate::atomic_write::write_atomic(output_path, &template_content)
                     .await
                     .map_err(|e| miette::miette!("Failed to write template to {}: {}",
                                                output_path.display(), e))?;
 
                 println!("✅ Template configuration generated successfully: {}", output_path.display());
+                crate::report::record_generated(GeneratedFile::compare(output_path, previous.as_deref(), &template_content));
+                record_output_checksum(output_path, &args.common, &args.format, "template")?;
             } else {
                 println!("{}", template_content);
             }
@@ -257,3 +744,224 @@ pub async fn handle_template_generation(args: TemplateArgs) -> Result<()> {
 
     Ok(())
 }
+
+/// Write a multi-file generation result as one transaction (see [`stream_generated_set`])
+fn write_generated_set(
+    output_dir: &std::path::Path,
+    results: Vec<(String, String)>,
+    common: &GenerateArgs,
+    format: &str,
+    kind: &str,
+) -> Result<()> {
+    let (files, contents): (Vec<String>, Vec<String>) = results.into_iter().unzip();
+    let write = |index: usize, out: &mut dyn Write| {
+        out.write_all(contents[index].as_bytes()).map_err(|e| CliError::IoError {
+            context: format!("Writing {}", files[index]),
+            source: e,
+        })
+    };
+    stream_generated_set(output_dir, &files, Some(1), write, common, format, kind)
+}
+
+/// Stream a multi-file generation result into `output_dir` as one transaction
+///
+/// `write` streams the file at each index of `files`, on up to `concurrency` threads.
+/// Files and their checksums are written to a staging directory and moved into `output_dir`
+/// only once everything succeeded, and a failed move rolls the others back, so a failed run
+/// never leaves a partially updated schema set. Readers during the moves can still see
+/// one (see [`StagedDir`](crate::atomic_write::StagedDir)).
+fn stream_generated_set(
+    output_dir: &std::path::Path,
+    files: &[String],
+    concurrency: Option<usize>,
+    write: impl Fn(usize, &mut dyn Write) -> Result<(), CliError> + Sync,
+    common: &GenerateArgs,
+    format: &str,
+    kind: &str,
+) -> Result<()> {
+    let _lock = crate::lock::PathLock::acquire(output_dir).map_err(miette::Report::new)?;
+    let _timer = Timer::start(Phase::Write);
+    let staged = crate::atomic_write::StagedDir::begin(output_dir).map_err(miette::Report::new)?;
+
+    let indexed: Vec<(usize, &String)> = files.iter().enumerate().collect();
+    let changes = crate::pkl_schema::parallel::map(&indexed, concurrency, |&(index, filename)| -> Result<GeneratedFile> {
+        let previous = std::fs::read_to_string(output_dir.join(filename)).ok();
+        staged
+            .write_with(filename, |out| write(index, out))
+            .map_err(|e| miette::miette!("Failed to write {} {}: {}", kind, filename, e))?;
+        // Only one file at a time is read back for the change summary
+        let content = std::fs::read_to_string(staged.path().join(filename))
+            .map_err(|e| miette::miette!("Failed to read staged {} {}: {}", kind, filename, e))?;
+        Ok(GeneratedFile::compare(output_dir.join(filename), previous.as_deref(), &content))
+    })
+    .into_iter()
+    .collect::<Result<Vec<_>>>()?;
+    // Start from the existing manifest so entries for files this run doesn't produce are kept
+    let manifest = output_dir.join(crate::checksums::CHECKSUM_FILE);
+    if let Ok(existing) = std::fs::read(&manifest) {
+        staged
+            .write(crate::checksums::CHECKSUM_FILE, existing)
+            .map_err(|e| miette::miette!("Failed to stage {}: {}", manifest.display(), e))?;
+    }
+    record_checksums(staged.path(), files, common, format, kind)?;
+
+    staged.commit().map_err(miette::Report::new)?;
+    for filename in files {
+        println!("✅ Generated: {}", output_dir.join(filename).display());
+    }
+    changes.into_iter().for_each(crate::report::record_generated);
+    if !crate::hermetic::is_enabled() {
+        println!("🔏 Checksums recorded: {}", output_dir.join(crate::checksums::CHECKSUM_FILE).display());
+    }
+    Ok(())
+}
+
+/// Add partial types to each generated schema file when `--partials` is set
+fn apply_partials(results: Vec<(String, String)>, partials: bool) -> Result<Vec<(String, String)>> {
+    if !partials {
+        return Ok(results);
+    }
+    results
+        .into_iter()
+        .map(|(filename, content)| {
+            let format = filename.rsplit('.').next().unwrap_or_default();
+            let content = crate::partials::add_partials(&content, format).map_err(miette::Report::new)?;
+            Ok((filename, content))
+        })
+        .collect()
+}
+
+/// Narrow each generated file to `--types` and their dependencies, dropping files without any
+fn apply_selection(results: Vec<(String, String)>, types: &[String]) -> Result<Vec<(String, String)>> {
+    if types.is_empty() {
+        return Ok(results);
+    }
+    let mut matched = Vec::new();
+    let mut selected = Vec::new();
+    for (filename, content) in results {
+        let format = filename.rsplit('.').next().unwrap_or_default();
+        if let Some(selection) = crate::selection::select(&content, format, types).map_err(miette::Report::new)? {
+            matched.extend(selection.matched);
+            selected.push((filename, selection.content));
+        } else {
+            tracing::debug!("Skipping {}: none of the selected types", filename);
+        }
+    }
+    report_unknown_types(types, &matched)?;
+    Ok(selected)
+}
+
+/// Apply the property overrides of the `--policy` file to `modules`, warning about any
+/// that match nothing
+async fn apply_policy(modules: &mut [crate::pkl_schema::PklModule], policy_path: &std::path::Path) -> Result<()> {
+    let policy = crate::policy::Policy::load(policy_path).await.map_err(miette::Report::new)?;
+    let mut matched = Vec::new();
+    for module in modules.iter_mut() {
+        matched.extend(crate::pkl_schema::overrides::apply_overrides(module, &policy.overrides));
+    }
+    for property_override in policy.overrides.iter().filter(|o| !matched.contains(&o.path.as_str())) {
+        eprintln!("⚠️  Policy override '{}' matched no property", property_override.path);
+    }
+    Ok(())
+}
+
+/// Narrow each module to `--types` and their dependencies, dropping modules without any
+fn select_modules(modules: &[crate::pkl_schema::PklModule], types: &[String]) -> Result<Vec<crate::pkl_schema::PklModule>> {
+    let mut matched = Vec::new();
+    let mut selected = Vec::new();
+    for module in modules {
+        if let Some((module, module_matched)) = crate::selection::select_module(module, types) {
+            matched.extend(module_matched);
+            selected.push(module);
+        }
+    }
+    report_unknown_types(types, &matched)?;
+    Ok(selected)
+}
+
+/// Fail if any `--types` entry wasn't found in the generated output
+fn report_unknown_types(types: &[String], matched: &[String]) -> Result<()> {
+    let unknown: Vec<&str> = types
+        .iter()
+        .filter(|name| !matched.contains(name))
+        .map(String::as_str)
+        .collect();
+    if unknown.is_empty() {
+        return Ok(());
+    }
+    Err(miette::miette!("Unknown type(s) in --types: {}", unknown.join(", ")))
+}
+
+/// Redact configured defaults and examples in each generated JSON Schema file
+fn apply_redaction(results: Vec<(String, String)>) -> Result<Vec<(String, String)>> {
+    let redaction = crate::tool_config::ToolConfig::discover().map_err(miette::Report::new)?.redaction;
+    if redaction.is_empty() {
+        return Ok(results);
+    }
+    results
+        .into_iter()
+        .map(|(filename, content)| {
+            if !filename.ends_with(".json") {
+                return Ok((filename, content));
+            }
+            let content = redaction.redact_json_schema(&content).map_err(miette::Report::new)?;
+            Ok((filename, content))
+        })
+        .collect()
+}
+
+/// Append fragments to each generated file and wrap it in the configured templates
+fn apply_templates(results: Vec<(String, String)>) -> Result<Vec<(String, String)>> {
+    results
+        .into_iter()
+        .map(|(filename, content)| {
+            let content = crate::fragments::apply(&filename, &content).map_err(miette::Report::new)?;
+            let content = crate::templates::wrap_generated(&filename, &content).map_err(miette::Report::new)?;
+            Ok((filename, content))
+        })
+        .collect()
+}
+
+/// Finish a single generated output, named after `--output` when there is one
+fn wrap_single(common: &GenerateArgs, default_name: &str, content: &str) -> Result<String> {
+    let name = common
+        .single_output(default_name)?
+        .as_ref()
+        .and_then(|output| output.file_name())
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| default_name.to_string());
+    let content = crate::fragments::apply(&name, content).map_err(miette::Report::new)?;
+    crate::templates::wrap_generated(&name, &content).map_err(miette::Report::new)
+}
+
+/// Record SHA256SUMS (and optionally an attestation) for files written into `dir`
+fn record_checksums(dir: &std::path::Path, written: &[String], common: &GenerateArgs, format: &str, kind: &str) -> Result<()> {
+    // Hermetic runs produce only the outputs named on the command line
+    if crate::hermetic::is_enabled() {
+        return Ok(());
+    }
+    let invocation = serde_json::json!({
+        "command": format!("generate {}", kind),
+        "configType": common.config_type.to_string(),
+        "format": format,
+    });
+    crate::checksums::record_generation(dir, written, common.attestation, invocation)
+        .map_err(miette::Report::new)
+}
+
+/// Record the checksum of a single generated output file in its directory's manifest
+fn record_output_checksum(output_path: &std::path::Path, common: &GenerateArgs, format: &str, kind: &str) -> Result<()> {
+    let dir = output_path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or(std::path::Path::new("."));
+    let name = output_path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    record_checksums(dir, &[name], common, format, kind)?;
+    if !crate::hermetic::is_enabled() {
+        println!("🔏 Checksums recorded: {}", dir.join(crate::checksums::CHECKSUM_FILE).display());
+    }
+    Ok(())
+}
@@ -0,0 +1,122 @@
+//! Graph command implementation for Space Pklr
+//!
+//! This module exports dependency graphs built from Moon configuration files
+//!.
+
+use clap::{Args, Subcommand};
+use miette::Result;
+use std::path::{Path, PathBuf};
+
+use crate::task_graph::{GraphFormat, TaskGraphBuilder};
+use crate::types::CliError;
+
+/// Graph command with subcommands.
+#[derive(Subcommand)]
+pub enum GraphCommands {
+    /// Export the task dependency graph from project and inherited task configs
+    Tasks(TaskGraphArgs),
+}
+
+/// Task graph arguments
+#[derive(Args)]
+pub struct TaskGraphArgs {
+    /// Project config files or project directories (containing moon.yml or moon.pkl)
+    #[arg(required = true, help = "Project config files or directories containing moon.yml/moon.pkl")]
+    pub projects: Vec<PathBuf>,
+
+    /// Inherited task configs (e.g. .moon/tasks.yml), applied to every project
+    #[arg(long = "tasks", help = "Inherited tasks config files (e.g. .moon/tasks.yml)")]
+    pub tasks: Vec<PathBuf>,
+
+    /// Export format
+    #[arg(long, default_value = "dot", help = "Graph format: dot (default), json")]
+    pub format: GraphFormat,
+
+    /// Output file (optional, defaults to stdout)
+    #[arg(short, long, help = "Output file path (defaults to stdout)")]
+    pub output: Option<PathBuf>,
+
+    /// Overwrite existing output file
+    #[arg(short, long, help = "Force overwrite of existing output files")]
+    pub force: bool,
+}
+
+/// Handle graph command execution
+pub async fn handle_graph(commands: GraphCommands) -> Result<()> {
+    match commands {
+        GraphCommands::Tasks(args) => handle_task_graph(args).await.map_err(miette::Report::new),
+    }
+}
+
+/// Build and export the task graph
+pub async fn handle_task_graph(args: TaskGraphArgs) -> Result<(), CliError> {
+    use crate::config_processor::load_config_value;
+
+    if let Some(output) = &args.output {
+        crate::types::ensure_output_writable(output, args.force)?;
+    }
+
+    let mut builder = TaskGraphBuilder::new();
+
+    for tasks_path in &args.tasks {
+        crate::types::ensure_file_exists(tasks_path)?;
+        let value = load_config_value(tasks_path, None).await?;
+        builder.add_inherited_tasks(&value);
+    }
+
+    for project_path in &args.projects {
+        let config_path = resolve_project_config(project_path)?;
+        let value = load_config_value(&config_path, None).await?;
+        builder.add_project(&project_fallback_id(&config_path), &value);
+    }
+
+    let graph = builder.build();
+    eprintln!(
+        "🔗 Built task graph: {} tasks, {} edges",
+        graph.nodes.len(),
+        graph.edges.len()
+    );
+    for dep in &graph.unresolved {
+        eprintln!("⚠️  {} -> {}: {}", dep.from, dep.dep, dep.reason);
+    }
+
+    let rendered = graph.render(args.format)?;
+
+    if let Some(output_path) = &args.output {
+        crate::atomic_write::write_atomic(output_path, rendered).await?;
+        println!("✅ Task graph written to {}", output_path.display());
+    } else {
+        println!("{}", rendered);
+    }
+
+    Ok(())
+}
+
+/// Resolve a project argument to its config file
+pub(crate) fn resolve_project_config(path: &PathBuf) -> Result<PathBuf, CliError> {
+    if !path.is_dir() {
+        crate::types::ensure_file_exists(path)?;
+        return Ok(path.clone());
+    }
+    // Hermetic runs take explicit config files only
+    if crate::hermetic::is_enabled() {
+        return Err(CliError::FileNotFound { path: path.clone() });
+    }
+
+    ["moon.yml", "moon.yaml", "moon.pkl"]
+        .iter()
+        .map(|name| path.join(name))
+        .find(|candidate| candidate.exists())
+        .ok_or_else(|| CliError::FileNotFound {
+            path: path.join("moon.yml"),
+        })
+}
+
+/// Moon defaults a project's id to its directory name
+pub(crate) fn project_fallback_id(config_path: &Path) -> String {
+    config_path
+        .parent()
+        .and_then(|dir| dir.file_name())
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| "project".to_string())
+}
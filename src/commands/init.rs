@@ -0,0 +1,160 @@
+//! Init command implementation for Space Pklr
+//!
+//! This module bootstraps a Moon repo for Pkl configs
+//!.
+
+use clap::Args;
+use miette::Result;
+use std::path::{Path, PathBuf};
+
+use crate::init::{DEFAULT_SCHEMA_DIR, PACKAGE_NAME, has_project_config, pkl_path, starter_config, starter_path, yaml_configs};
+use crate::types::{ConfigHeader, MoonConfig, SchemaFormat};
+
+/// Init command arguments.
+#[derive(Args)]
+pub struct InitArgs {
+    /// Workspace root to set up
+    #[arg(default_value = ".", help = "Workspace root (defaults to the current directory)")]
+    pub root: PathBuf,
+
+    /// Where to generate the Pkl schemas, relative to the workspace root
+    #[arg(long, value_name = "DIR", default_value = DEFAULT_SCHEMA_DIR, help = "Schema directory, relative to the root")]
+    pub schema_dir: PathBuf,
+
+    /// Convert existing YAML configs to Pkl amending the generated schemas
+    #[arg(long, help = "Convert existing YAML configs to Pkl")]
+    pub convert: bool,
+
+    /// Don't look for or install the Pkl CLI
+    #[arg(long, help = "Skip locating/installing the Pkl CLI")]
+    pub skip_pkl: bool,
+
+    /// Overwrite Pkl files that `--convert` would otherwise skip
+    #[arg(short, long, requires = "convert", help = "Overwrite existing Pkl configs when converting")]
+    pub force: bool,
+}
+
+/// Handle init command execution
+///
+/// - Locate the Pkl CLI, installing it when missing
+/// - Generate every schema into the schema directory
+/// - Write starter configs, or convert the existing YAML ones with `--convert`
+pub async fn handle_init(args: InitArgs) -> Result<()> {
+    if !args.root.is_dir() {
+        return Err(miette::miette!("{} is not a directory", args.root.display()));
+    }
+    let schema_dir = args.root.join(&args.schema_dir);
+    println!("🌱 Setting up Pkl configs in {}", args.root.display());
+
+    if !args.skip_pkl {
+        ensure_pkl().await;
+    }
+
+    let manifest = crate::composition::CompositionManifest::moon_package(PACKAGE_NAME);
+    crate::commands::generate::generate_composition(&manifest, &schema_args(&schema_dir)).await?;
+
+    let tool_config = crate::tool_config::ToolConfig::discover().map_err(miette::Report::new)?;
+    let templates = &tool_config.templates;
+    let header = |config_type: MoonConfig, path: &Path| -> Result<String> {
+        let dir = path.parent().unwrap_or(Path::new("."));
+        let line = crate::convert::schema_header(&ConfigHeader::Amends, config_type, &schema_dir, dir, templates)
+            .map_err(miette::Report::new)?;
+        Ok(line.unwrap_or_default())
+    };
+
+    let mut converted = Vec::new();
+    if args.convert {
+        for (config_type, yaml) in yaml_configs(&args.root) {
+            let pkl = pkl_path(&yaml);
+            if pkl.exists() && !args.force {
+                println!("⏭️  {} already exists (use --force to overwrite)", pkl.display());
+                continue;
+            }
+            let input = std::fs::read_to_string(&yaml)
+                .map_err(|e| miette::miette!("Failed to read {}: {}", yaml.display(), e))?;
+            let output = crate::convert::convert_str(&input, SchemaFormat::Yaml, SchemaFormat::Pkl, config_type)
+                .map_err(miette::Report::new)?;
+            for diagnostic in &output.diagnostics {
+                println!("⚠️  {}: {}", yaml.display(), diagnostic);
+            }
+            let content = format!("{}\n\n{}", header(config_type, &pkl)?, output.output);
+            crate::atomic_write::write_atomic(&pkl, &content).await.map_err(miette::Report::new)?;
+            println!("🔄 {} → {}", yaml.display(), pkl.display());
+            converted.push(yaml);
+        }
+    }
+
+    for config_type in [MoonConfig::Workspace, MoonConfig::Project] {
+        let Some(path) = starter_path(&args.root, config_type) else {
+            continue;
+        };
+        let configured = match config_type {
+            MoonConfig::Workspace => crate::effective::moon_config_file(&args.root, "workspace").is_some(),
+            _ => has_project_config(&args.root),
+        };
+        if configured {
+            continue;
+        }
+        let content = starter_config(config_type, &header(config_type, &path)?);
+        crate::atomic_write::write_atomic(&path, &content).await.map_err(miette::Report::new)?;
+        println!("📝 Starter config: {}", path.display());
+    }
+
+    println!("✅ Pkl schemas are in {}", schema_dir.display());
+    if !converted.is_empty() {
+        // Moon reads YAML before Pkl, so the new files only take effect once these are gone
+        println!("ℹ️  Moon prefers YAML configs over Pkl; once the Pkl versions look right, remove:");
+        for yaml in &converted {
+            println!("   {}", yaml.display());
+        }
+    }
+    Ok(())
+}
+
+/// Find the Pkl CLI, or install the recommended version
+///
+/// Schemas and configs don't need it, so a failed install is only a warning.
+async fn ensure_pkl() {
+    if let Ok(Some(pkl)) = crate::pkl_tooling::find_pkl_executable().await {
+        println!(
+            "✅ Pkl CLI {} found at {}",
+            pkl.version.as_deref().unwrap_or("(unknown version)"),
+            pkl.path.display()
+        );
+        return;
+    }
+    println!("📦 Pkl CLI not found, installing it...");
+    match crate::pkl_tooling::install_pkl(None).await {
+        Ok(pkl) => println!("✅ Installed Pkl CLI at {}", pkl.path.display()),
+        Err(e) => {
+            println!("⚠️  Couldn't install the Pkl CLI: {}", e);
+            println!("   Install it later with: spklr pkl-me pkl");
+        }
+    }
+}
+
+/// `generate schema` arguments writing the Moon schema package into `output`
+fn schema_args(output: &Path) -> crate::commands::generate::SchemaArgs {
+    crate::commands::generate::SchemaArgs {
+        common: crate::commands::generate::GenerateArgs {
+            config_type: MoonConfig::All,
+            output: Some(output.to_path_buf()),
+            output_is_dir: false,
+            attestation: false,
+        },
+        format: "pkl".to_string(),
+        partials: false,
+        from_json_schema: Vec::new(),
+        type_prefix: None,
+        type_suffix: None,
+        module_name: None,
+        split_types: false,
+        include_properties: Vec::new(),
+        exclude_properties: Vec::new(),
+        policy: None,
+        composition: None,
+        with_examples_files: false,
+        docs: None,
+        types: Vec::new(),
+    }
+}
@@ -0,0 +1,188 @@
+//! Lint command implementation for Space Pklr
+//!
+//! This module checks Moon configuration files against organizational policies
+//!.
+
+use clap::Args;
+use std::path::{Path, PathBuf};
+
+use crate::autofix::Fix;
+use crate::ci::annotations::{Finding, OutputFormat};
+use crate::policy::{Policy, PolicyReport, infer_config_type};
+use crate::types::{ByteSize, CliError, MoonConfig};
+
+/// Lint command arguments.
+#[derive(Args)]
+pub struct LintArgs {
+    /// Configuration files to lint
+    #[arg(required = true, help = "Configuration files to lint")]
+    pub files: Vec<PathBuf>,
+
+    /// Policy file declaring the rules to enforce
+    #[arg(long, help = "Policy file (yaml, json, or pkl)")]
+    pub policy: PathBuf,
+
+    /// Configuration type (optional, inferred from each file name)
+    #[arg(long, help = "Configuration type: project, workspace, template, toolchain, task (inferred if not specified)")]
+    pub config_type: Option<MoonConfig>,
+
+    /// Print violations as JSON instead of text (same as `--output json`)
+    #[arg(long, conflicts_with = "output", help = "Print the lint report as JSON")]
+    pub json: bool,
+
+    /// Report format; CI formats also print the text report
+    #[arg(long, default_value = "text", help = "Report format: text (default), json, github, buildkite, gitlab")]
+    pub output: OutputFormat,
+
+    /// Correct fixable problems in place, printing a diff of each change
+    #[arg(long, help = "Fix key-whitespace, missing-schema, and deprecated-rename problems in place")]
+    pub fix: bool,
+
+    /// With --fix, print the diff without writing the files
+    #[arg(long, requires = "fix", help = "Preview fixes without writing them")]
+    pub dry_run: bool,
+
+    /// Rules --fix should leave alone
+    #[arg(long = "no-fix", value_name = "RULE", help = "Don't fix problems found by RULE (repeatable)")]
+    pub no_fix: Vec<String>,
+
+    /// File size budget, overriding `budgets.maxFileSize` in the policy
+    #[arg(long, value_name = "SIZE", help = "Maximum config file size, e.g. 16KiB or 2MB")]
+    pub max_file_size: Option<ByteSize>,
+}
+
+/// Handle lint command execution
+pub async fn handle_lint(args: LintArgs) -> Result<(), CliError> {
+    crate::types::ensure_file_exists(&args.policy)?;
+    let mut policy = Policy::load(&args.policy).await?;
+    if args.max_file_size.is_some() {
+        policy.budgets.get_or_insert_with(Default::default).max_file_size = args.max_file_size;
+    }
+
+    let concurrency = crate::tool_config::ToolConfig::discover()?.generator.concurrency;
+    let loaded = load_files(&args.files, crate::concurrency::jobs(concurrency)).await?;
+
+    let output = if args.json { OutputFormat::Json } else { args.output };
+    let mut report = PolicyReport::default();
+    for (file, (source, value)) in args.files.iter().zip(loaded) {
+        let config_type = args.config_type.or_else(|| infer_config_type(file));
+
+        let mut file_report = policy.evaluate(file, config_type, &value, &source);
+        if args.fix {
+            let fixes: Vec<Fix> = policy
+                .fixes(file, config_type, &value, &source)
+                .into_iter()
+                .filter(|fix| !args.no_fix.iter().any(|rule| rule == fix.rule))
+                .collect();
+            let fixed = fix_file(file, &source, &fixes, args.dry_run, output == OutputFormat::Json).await?;
+            if !args.dry_run {
+                file_report
+                    .violations
+                    .retain(|violation| !fixed.iter().any(|fix| fix.rule == violation.rule && fix.path.to_string() == violation.path));
+            }
+        }
+        report.merge(file_report);
+    }
+
+    let findings: Vec<Finding> = report.violations.iter().map(Finding::from).collect();
+    crate::report::record_findings(&findings);
+
+    if output == OutputFormat::Json {
+        let json = serde_json::to_string_pretty(&report)
+            .map_err(|e| CliError::Generic(format!("Failed to serialize lint report: {}", e)))?;
+        println!("{}", json);
+    } else {
+        print_report(&report, args.files.len());
+        crate::ci::annotations::emit(output, "lint", &findings)?;
+    }
+
+    match report.error_count() {
+        0 => Ok(()),
+        count => Err(CliError::PolicyViolations { count }),
+    }
+}
+
+/// Apply `fixes` to `file`, print the diff, and return the fixes that were made
+///
+/// With a JSON report on stdout, the diff goes to stderr.
+async fn fix_file(file: &Path, source: &str, fixes: &[Fix], dry_run: bool, json: bool) -> Result<Vec<Fix>, CliError> {
+    if fixes.is_empty() {
+        return Ok(Vec::new());
+    }
+    let show = |text: String| if json { eprint!("{}", text) } else { print!("{}", text) };
+    let outcome = crate::autofix::apply(file, source, fixes);
+    for (fix, reason) in &outcome.skipped {
+        show(format!("⏭️  {} [{}] {}: not fixed, {}\n", file.display(), fix.rule, fix.path, reason));
+    }
+    if !outcome.changed() {
+        return Ok(Vec::new());
+    }
+    show(outcome.preview(file));
+    if dry_run {
+        return Ok(Vec::new());
+    }
+    crate::atomic_write::write_atomic(file, &outcome.content).await?;
+    show(format!("🔧 Fixed {} problem(s) in {}\n", outcome.applied.len(), file.display()));
+    Ok(outcome.applied.into_iter().map(|(fix, _)| fix).collect())
+}
+
+/// Read and load every file, `jobs` at a time, keeping their order
+async fn load_files(files: &[PathBuf], jobs: usize) -> Result<Vec<(String, serde_json::Value)>, CliError> {
+    for file in files {
+        crate::types::ensure_file_exists(file)?;
+    }
+
+    let mut loaded = Vec::with_capacity(files.len());
+    for batch in files.chunks(jobs.max(1)) {
+        let tasks: Vec<_> = batch.iter().cloned().map(|file| tokio::spawn(load_file(file))).collect();
+        for task in tasks {
+            let result = task
+                .await
+                .map_err(|e| CliError::Generic(format!("Loading config files failed: {}", e)))?;
+            loaded.push(result?);
+        }
+    }
+    Ok(loaded)
+}
+
+async fn load_file(file: PathBuf) -> Result<(String, serde_json::Value), CliError> {
+    let source = tokio::fs::read_to_string(&file)
+        .await
+        .map_err(|e| CliError::IoError {
+            context: format!("Reading config file: {}", file.display()),
+            source: e,
+        })?;
+    let value = crate::config_processor::load_config_value(&file, None).await?;
+    Ok((source, value))
+}
+
+/// Print a human-readable lint report
+fn print_report(report: &PolicyReport, file_count: usize) {
+    for violation in &report.violations {
+        let icon = match violation.severity {
+            crate::policy::Severity::Error => "❌",
+            crate::policy::Severity::Warning => "⚠️ ",
+        };
+        println!(
+            "{} {} [{}] {}: {}",
+            icon,
+            violation.file.display(),
+            violation.rule,
+            violation.path,
+            violation.message
+        );
+    }
+
+    for exemption in &report.exemptions {
+        println!(
+            "⏭️  {} [{}] exempt: {}",
+            exemption.file.display(),
+            exemption.rule,
+            exemption.reason
+        );
+    }
+
+    if report.violations.is_empty() {
+        println!("✅ {} file(s) passed all policy rules", file_count);
+    }
+}
@@ -0,0 +1,171 @@
+//! Man page and long-form help implementation for Space Pklr
+//!
+//! `spklr manpages <dir>` renders a man page per command with clap_mangen, for Homebrew
+//! and deb packaging; `spklr --help-all` prints every command's long help. Both include
+//! the usage examples in [`EXAMPLES`].
+
+use clap::{Args, CommandFactory};
+use std::path::PathBuf;
+
+use crate::types::CliError;
+
+/// `spklr manpages` arguments
+#[derive(Args)]
+pub struct ManpagesArgs {
+    /// Directory to write the man pages into
+    #[arg(value_name = "DIR", help = "Directory to write spklr.1 and spklr-<command>.1 into")]
+    pub dir: PathBuf,
+}
+
+/// Usage examples by command path (`""` is spklr itself)
+pub const EXAMPLES: &[(&str, &[&str])] = &[
+    ("", &["spklr init --convert", "spklr convert --input moon.yml --to pkl", "spklr --help-all | less"]),
+    ("cache info", &["spklr cache info"]),
+    ("check", &["spklr check .moon/schemas --verify-checksums", "spklr check --doc-coverage 80 --output github"]),
+    ("ci bazel", &["spklr ci bazel --output tools/spklr.bzl"]),
+    ("ci comment", &["spklr convert --input moon.yml --to pkl --summary summary.md && spklr ci comment summary.md"]),
+    ("ci mise", &["spklr ci mise --output .mise.toml", "spklr ci mise --format tool-versions"]),
+    ("ci nix", &["spklr ci nix --output nix/spklr.nix"]),
+    ("ci versions", &["spklr ci versions --pkl-version 0.28.1"]),
+    ("config show", &["spklr config show", "spklr --pkl-version 0.28.1 config show --json"]),
+    (
+        "convert",
+        &[
+            "spklr convert --input moon.yml --to pkl --output moon.pkl",
+            "cat moon.yml | spklr convert --input - --from yaml --to json",
+            "spklr convert 'apps/*/moon.yml' --out-dir converted --to pkl",
+            "spklr convert --input moon.yml --to pkl --pkl-header amends --schema-dir .moon/schemas",
+            "spklr convert --input moon.yml --to pkl --env-policy read-env",
+        ],
+    ),
+    ("corpus run", &["spklr corpus run --manifest corpus.toml --json"]),
+    ("diff", &["spklr diff old-schemas/ new-schemas/ --check-compat"]),
+    ("docgen", &["spklr docgen --config-type project --output docs/project.md"]),
+    ("docs", &["spklr docs .moon/schemas --output docs/pkl"]),
+    ("doctor", &["spklr doctor"]),
+    ("effective", &["spklr effective apps/web --to json"]),
+    (
+        "generate schema",
+        &[
+            "spklr generate schema --format json-schema --output schemas/",
+            "spklr generate schema --config-type project --types Project,TaskConfig",
+            "spklr generate schema --from-json-schema schema.json --format pkl --output Custom.pkl",
+        ],
+    ),
+    ("generate template", &["spklr generate template --config-type workspace --format pkl"]),
+    ("graph tasks", &["spklr graph tasks apps/*/moon.yml --tasks .moon/tasks.yml | dot -Tsvg > tasks.svg"]),
+    ("init", &["spklr init", "spklr init --convert --schema-dir .moon/schemas"]),
+    ("lint", &["spklr lint moon.yml --policy policy.yml", "spklr lint apps/*/moon.yml --policy policy.yml --fix --dry-run"]),
+    ("merge", &["spklr merge base.yml overrides.yml --to pkl"]),
+    ("pkl list", &["spklr pkl list"]),
+    ("pkl use", &["spklr pkl use 0.28.1"]),
+    ("pkl-me", &["spklr pkl-me --report", "spklr pkl-me --report --json --schema-dir .moon/schemas"]),
+    (
+        "pkl-me pkl",
+        &["spklr pkl-me pkl", "spklr pkl-me pkl --version 0.28.1 --force", "spklr pkl-me pkl --pkl-archive pkl-cli-linux-amd64.tar.gz"],
+    ),
+    ("schema deprecations", &["spklr schema deprecations schema.json --previous old-schema.json --plan"]),
+    ("schema extension-points", &["spklr schema extension-points .moon/schemas --json"]),
+    ("self test", &["spklr self test"]),
+    ("self-update", &["spklr self-update --check", "spklr self-update", "spklr self-update --version 0.1.0"]),
+    ("serve", &["spklr serve --socket /tmp/spklr.sock", "spklr serve --http 127.0.0.1:8080"]),
+    ("upgrade-config", &["spklr upgrade-config moon.yml .moon/workspace.yml --to-moon 1.39 --dry-run"]),
+    ("validate", &["spklr validate moon.pkl --schema .moon/schemas/Project.pkl"]),
+];
+
+/// Examples for a command path such as `generate schema`
+pub fn examples(path: &str) -> &'static [&'static str] {
+    EXAMPLES
+        .iter()
+        .find(|(command, _)| *command == path)
+        .map(|(_, examples)| *examples)
+        .unwrap_or_default()
+}
+
+/// The spklr command tree, built so every subcommand knows its full name
+pub fn command() -> clap::Command {
+    let mut command = crate::cli_app::Cli::command().disable_help_subcommand(true);
+    command.build();
+    command
+}
+
+/// A built subcommand's path below spklr, e.g. `generate schema`
+fn command_path(command: &clap::Command) -> String {
+    let bin_name = command.get_bin_name().unwrap_or_else(|| command.get_name());
+    bin_name.strip_prefix("spklr").unwrap_or(bin_name).trim().to_string()
+}
+
+/// Visible commands, depth first, parents before their subcommands
+fn visible_commands(command: &clap::Command) -> Vec<&clap::Command> {
+    let mut commands = vec![command];
+    for subcommand in command.get_subcommands().filter(|subcommand| !subcommand.is_hide_set()) {
+        commands.extend(visible_commands(subcommand));
+    }
+    commands
+}
+
+/// Long help for spklr and every visible subcommand, each followed by its examples
+pub fn help_all() -> String {
+    let root = command();
+    let mut out = String::new();
+    for command in visible_commands(&root) {
+        let title = command.get_bin_name().unwrap_or_else(|| command.get_name()).to_string();
+        out.push_str(&format!("{}\n{}\n\n", title, "=".repeat(title.chars().count())));
+        out.push_str(command.clone().render_long_help().to_string().trim_end());
+        out.push('\n');
+        let examples = examples(&command_path(command));
+        if !examples.is_empty() {
+            out.push_str("\nExamples:\n");
+            for example in examples {
+                out.push_str(&format!("  $ {}\n", example));
+            }
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// An EXAMPLES section in roff
+fn roff_examples(examples: &[&str]) -> String {
+    let mut section = String::from(".SH EXAMPLES\n");
+    for example in examples {
+        let escaped = example.replace('\\', "\\\\").replace('-', "\\-");
+        section.push_str(&format!(".PP\n.nf\n\\&{}\n.fi\n", escaped));
+    }
+    section
+}
+
+/// Render the man pages of spklr and its visible subcommands into `dir`
+pub fn write_manpages(dir: &std::path::Path) -> Result<Vec<PathBuf>, CliError> {
+    crate::read_only::ensure_allowed(format!("write man pages to {}", dir.display()))?;
+    std::fs::create_dir_all(dir).map_err(|e| CliError::IoError {
+        context: format!("Creating {}", dir.display()),
+        source: e,
+    })?;
+
+    let root = command();
+    let mut written = Vec::new();
+    for command in visible_commands(&root) {
+        let man = clap_mangen::Man::new(command.clone());
+        let mut page = Vec::new();
+        man.render(&mut page).map_err(|e| CliError::IoError {
+            context: format!("Rendering the man page of {}", command.get_name()),
+            source: e,
+        })?;
+        let examples = examples(&command_path(command));
+        if !examples.is_empty() {
+            page.extend_from_slice(roff_examples(examples).as_bytes());
+        }
+        let path = dir.join(man.get_filename());
+        crate::atomic_write::write_atomic_sync(&path, page)?;
+        written.push(path);
+    }
+    Ok(written)
+}
+
+/// Handle `spklr manpages`
+pub async fn handle_manpages(args: ManpagesArgs) -> Result<(), CliError> {
+    let written = write_manpages(&args.dir)?;
+    println!("📄 Wrote {} man pages to {}", written.len(), args.dir.display());
+    Ok(())
+}
@@ -0,0 +1,63 @@
+//! Merge command implementation for Space Pklr
+//!
+//! This module merges layered configuration files and emits the effective result
+//!.
+
+use clap::Args;
+use std::path::PathBuf;
+
+use crate::types::{CliError, SchemaFormat};
+
+/// Merge command arguments.
+#[derive(Args)]
+pub struct MergeArgs {
+    /// Base config followed by one or more overlays, applied in order
+    #[arg(required = true, num_args = 2.., help = "Base config followed by overlays (yaml, json, toml, or pkl)")]
+    pub files: Vec<PathBuf>,
+
+    /// Output format
+    #[arg(long, default_value = "yaml", help = "Output format: yaml (default), json, toml, pkl")]
+    pub to: SchemaFormat,
+
+    /// Output file (optional, defaults to stdout)
+    #[arg(short, long, help = "Output file path (defaults to stdout)")]
+    pub output: Option<PathBuf>,
+
+    /// Overwrite existing output file
+    #[arg(short, long, help = "Force overwrite of existing output files")]
+    pub force: bool,
+}
+
+/// Handle merge command execution
+pub async fn handle_merge(args: MergeArgs) -> Result<(), CliError> {
+    use crate::config_processor::{load_config_value, print_config_value, write_config_value};
+
+    if let Some(output) = &args.output {
+        crate::types::ensure_output_writable(output, args.force)?;
+    }
+
+    let mut merged = None;
+    for file in &args.files {
+        crate::types::ensure_file_exists(file)?;
+        let value = load_config_value(file, None).await?;
+        eprintln!("🧩 Layering {}", file.display());
+        merged = Some(match merged {
+            Some(base) => crate::merge::merge_configs(&base, &value),
+            None => value,
+        });
+    }
+    let merged = merged.unwrap_or_default();
+
+    match &args.output {
+        Some(output_path) => {
+            let _lock = crate::lock::PathLock::acquire(output_path)?;
+            let format = args.to.clone();
+            crate::atomic_write::write_atomic_streamed(output_path, move |out| write_config_value(&merged, &format, out))
+                .await?;
+            println!("✅ Merged config written to {}", output_path.display());
+        }
+        None => print_config_value(&merged, &args.to)?,
+    }
+
+    Ok(())
+}
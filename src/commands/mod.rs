@@ -2,9 +2,33 @@
 //!
 //! This module contains all command implementations as specified in
 
+pub mod cache;
+pub mod check;
+pub mod ci;
+pub mod config;
 pub mod convert;
+pub mod corpus;
+pub mod diff;
+#[cfg(feature = "docgen")]
+pub mod docgen;
+pub mod docs;
+pub mod doctor;
+pub mod effective;
+pub mod external;
 pub mod generate;
+pub mod graph;
+pub mod init;
+pub mod lint;
+pub mod manpages;
+pub mod merge;
+pub mod pkl;
 pub mod pklme;
+pub mod schema;
+pub mod self_test;
+pub mod self_update;
+pub mod serve;
+pub mod upgrade;
+pub mod validate;
 
 // Re-export command structures for easier access
 
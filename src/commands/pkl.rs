@@ -0,0 +1,52 @@
+//! Pkl version command implementation for Space Pklr
+//!
+//! Lists the Pkl CLI versions installed side by side and switches the default one.
+
+use clap::{Args, Subcommand};
+
+use crate::types::CliError;
+
+/// Pkl version management subcommands
+#[derive(Subcommand)]
+pub enum PklCommands {
+    /// List installed Pkl CLI versions
+    List,
+    /// Make an installed Pkl CLI version the default
+    Use(PklUseArgs),
+}
+
+/// Arguments for `spklr pkl use`
+#[derive(Args)]
+pub struct PklUseArgs {
+    /// Installed version to use by default (e.g. 0.27.1)
+    #[arg(value_name = "VERSION", help = "Installed Pkl version to make the default")]
+    pub version: String,
+}
+
+/// Handle pkl command execution
+pub async fn handle_pkl(commands: PklCommands) -> Result<(), CliError> {
+    match commands {
+        PklCommands::List => {
+            let versions = crate::pkl_tooling::installed_pkl_versions();
+            if versions.is_empty() {
+                println!("No Pkl CLI versions installed; run 'spklr pkl-me pkl --version <VERSION>'");
+                return Ok(());
+            }
+            let default = crate::pkl_tooling::default_pkl_version();
+            println!("📦 Installed Pkl CLI versions:");
+            for version in versions {
+                if default.as_deref() == Some(version.as_str()) {
+                    println!("  * {} (default)", version);
+                } else {
+                    println!("    {}", version);
+                }
+            }
+            Ok(())
+        }
+        PklCommands::Use(args) => {
+            crate::pkl_tooling::set_default_pkl_version(&args.version)?;
+            println!("✅ Pkl CLI {} is now the default", args.version);
+            Ok(())
+        }
+    }
+}
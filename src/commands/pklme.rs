@@ -1,10 +1,38 @@
 //! Install command implementation for Space Pklr
 //!
-//! This module handles installation of external tools like Pkl CLI
+//! This module handles installation of external tools like Pkl CLI, and `--report`,
+//! the compatibility matrix of the installed toolchain (see [`crate::compatibility`])
 //!.
 
 use clap::{Args, Subcommand};
 use miette::Result;
+use std::path::PathBuf;
+
+/// `spklr pkl-me` arguments: an install subcommand, or `--report`
+#[derive(Args)]
+#[command(args_conflicts_with_subcommands = true, arg_required_else_help = true)]
+pub struct PklMeArgs {
+    #[command(subcommand)]
+    pub command: Option<InstallCommands>,
+
+    /// Print the compatibility matrix of Pkl, moon_config, generated schemas, and platform
+    #[arg(long, help = "Check Pkl, moon_config, generated schema, and platform versions and print how to fix mismatches")]
+    pub report: bool,
+
+    /// Print the report as JSON
+    #[arg(long, requires = "report", help = "Print the report as JSON")]
+    pub json: bool,
+
+    /// Directory of generated schemas to check
+    #[arg(
+        long,
+        value_name = "DIR",
+        default_value = ".",
+        requires = "report",
+        help = "Directory of generated schemas (and spklr-versions.json) to check"
+    )]
+    pub schema_dir: PathBuf,
+}
 
 /// Install command with subcommands.
 #[derive(Subcommand)]
@@ -26,6 +54,48 @@ pub struct PklInstallArgs {
     /// Force reinstallation even if already installed
     #[arg(short, long, help = "Force reinstallation")]
     pub force: bool,
+
+    /// Install from a pre-downloaded release archive instead of the network
+    #[arg(long, value_name = "PATH", help = "Install from a local Pkl release archive (air-gapped installs)")]
+    pub pkl_archive: Option<PathBuf>,
+
+    /// Expected SHA-256 of the archive (defaults to the digest in `<archive>.sha256`)
+    #[arg(long, value_name = "HEX", requires = "pkl_archive", help = "SHA-256 the archive must match")]
+    pub sha256: Option<String>,
+}
+
+/// Handle `spklr pkl-me`
+pub async fn handle_pkl_me(args: PklMeArgs) -> Result<()> {
+    match args.command {
+        Some(commands) => handle_install(commands).await,
+        None => handle_report(&args.schema_dir, args.json).await,
+    }
+}
+
+/// Print the compatibility matrix, failing when any check has an error
+pub async fn handle_report(schema_dir: &std::path::Path, json: bool) -> Result<()> {
+    let pkl_cli = crate::pkl_tooling::find_pkl_executable().await.ok().flatten();
+    let report = crate::pkl_tooling::compatibility_report(pkl_cli.as_ref(), schema_dir).await?;
+
+    if json {
+        let document = serde_json::json!({
+            "compatible": report.failed_checks() == 0,
+            "checks": report.checks,
+        });
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&document)
+                .map_err(|e| crate::types::CliError::Generic(format!("Failed to serialize report: {}", e)))?
+        );
+    } else {
+        println!("🔍 Compatibility report (spklr {})\n", env!("CARGO_PKG_VERSION"));
+        print!("{}", crate::compatibility::render_matrix(&report.checks));
+    }
+
+    match report.failed_checks() {
+        0 => Ok(()),
+        count => Err(miette::Report::new(crate::types::CliError::CompatibilityErrors { count })),
+    }
 }
 
 /// Handle install command execution
@@ -88,7 +158,13 @@ pub async fn handle_pkl_installation(args: PklInstallArgs) -> Result<()> {
 
     // Perform installation
     display_installation_progress(&format!("Installing Pkl CLI version {}...", version));
-    let pkl_cli = crate::pkl_tooling::install_pkl(Some(version.clone())).await?;
+    let pkl_cli = match &args.pkl_archive {
+        Some(archive) => {
+            crate::pkl_tooling::install_pkl_from_archive(archive, Some(version.clone()), args.sha256.as_deref())
+                .await?
+        }
+        None => crate::pkl_tooling::install_pkl(Some(version.clone())).await?,
+    };
 
     // Validate installation
     display_installation_progress("Validating installation...");
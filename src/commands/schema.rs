@@ -0,0 +1,127 @@
+//! Schema lifecycle command implementation for Space Pklr
+//!
+//! Reports the deprecated properties of a generated JSON Schema and plans their removal
+//! against the previous published schema, and lists the extension points of generated
+//! schemas (see [`crate::extension_points`]).
+
+use clap::{Args, Subcommand};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+use crate::deprecations::{Deprecation, collect, plan};
+use crate::types::CliError;
+
+/// Schema lifecycle subcommands
+#[derive(Subcommand)]
+pub enum SchemaCommands {
+    /// List deprecated properties, or plan their removal with --plan
+    Deprecations(DeprecationsArgs),
+    /// List properties that accept arbitrary keys or values, with how to type them
+    ExtensionPoints(ExtensionPointsArgs),
+}
+
+/// Arguments for `spklr schema deprecations`
+#[derive(Args)]
+pub struct DeprecationsArgs {
+    /// Current JSON Schema document
+    #[arg(value_name = "SCHEMA", help = "Current JSON Schema document (e.g. from `spklr generate schema --format json-schema`)")]
+    pub schema: PathBuf,
+
+    /// JSON Schema document of the previous published release
+    #[arg(long, value_name = "SCHEMA", help = "JSON Schema document of the previous published release")]
+    pub previous: Option<PathBuf>,
+
+    /// Compare with --previous and print a removal plan for the next major release
+    #[arg(long, requires = "previous", help = "Print a removal plan for the next major release (needs --previous)")]
+    pub plan: bool,
+
+    /// Current schema version
+    #[arg(long, value_name = "VERSION", help = "Current schema version (defaults to schemaPackage in spklr-versions.json)")]
+    pub version: Option<String>,
+}
+
+/// Arguments for `spklr schema extension-points`
+#[derive(Args)]
+pub struct ExtensionPointsArgs {
+    /// Generated schema file or directory
+    #[arg(value_name = "SCHEMAS", help = "Schema file or directory to scan (JSON Schema or *.ir.json)")]
+    pub schemas: PathBuf,
+
+    /// Print the extension points as JSON
+    #[arg(long, help = "Print the extension points as JSON")]
+    pub json: bool,
+}
+
+/// Handle schema command execution
+pub async fn handle_schema(commands: SchemaCommands) -> Result<(), CliError> {
+    match commands {
+        SchemaCommands::Deprecations(args) => handle_deprecations(args),
+        SchemaCommands::ExtensionPoints(args) => handle_extension_points(args),
+    }
+}
+
+fn handle_deprecations(args: DeprecationsArgs) -> Result<(), CliError> {
+    let schema = load_schema(&args.schema)?;
+    let current = collect(&schema);
+
+    let Some(previous_path) = args.previous.as_deref().filter(|_| args.plan) else {
+        print_deprecations(&current);
+        return Ok(());
+    };
+    let previous = collect(&load_schema(previous_path)?);
+    let version = match args.version {
+        Some(version) => Some(version),
+        None => crate::versions::VersionManifest::discover()?.map(|manifest| manifest.schema_package),
+    };
+    print!("{}", plan(&previous, &current, &schema, version.as_deref()));
+    Ok(())
+}
+
+fn handle_extension_points(args: ExtensionPointsArgs) -> Result<(), CliError> {
+    let modules = crate::schema_diff::load_modules(&args.schemas)?;
+    let points = crate::extension_points::find(&modules);
+
+    if args.json {
+        let json = serde_json::to_string_pretty(&points)
+            .map_err(|e| CliError::Generic(format!("Failed to serialize extension points: {}", e)))?;
+        println!("{}", json);
+        return Ok(());
+    }
+    if points.is_empty() {
+        println!("✅ No extension points: every property is typed");
+        return Ok(());
+    }
+    println!(
+        "🧩 {} extension point{} accepting arbitrary keys or values:",
+        points.len(),
+        if points.len() == 1 { "" } else { "s" }
+    );
+    for point in &points {
+        println!("  - {}", point);
+    }
+    Ok(())
+}
+
+fn print_deprecations(deprecations: &[Deprecation]) {
+    if deprecations.is_empty() {
+        println!("No deprecated properties");
+        return;
+    }
+    println!("⚠️  {} deprecated propert{}:", deprecations.len(), if deprecations.len() == 1 { "y" } else { "ies" });
+    for deprecation in deprecations {
+        match &deprecation.message {
+            Some(message) => println!("  - {}: {}", deprecation.path, message),
+            None => println!("  - {}", deprecation.path),
+        }
+    }
+}
+
+fn load_schema(path: &Path) -> Result<Value, CliError> {
+    let content = std::fs::read_to_string(path).map_err(|e| CliError::IoError {
+        context: format!("Reading JSON Schema: {}", path.display()),
+        source: e,
+    })?;
+    serde_json::from_str(&content).map_err(|e| CliError::ValidationError {
+        source: Box::new(e),
+    })
+}
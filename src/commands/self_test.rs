@@ -0,0 +1,39 @@
+//! Self command implementation for Space Pklr
+//!
+//! Checks this spklr build and environment against the golden fixtures embedded in it.
+
+use clap::Subcommand;
+
+use crate::types::CliError;
+
+/// Self-check subcommands
+#[derive(Subcommand)]
+pub enum SelfCommands {
+    /// Re-render the embedded golden fixtures and compare them byte for byte
+    Test,
+}
+
+/// Handle self command execution
+pub async fn handle_self(commands: SelfCommands) -> Result<(), CliError> {
+    match commands {
+        SelfCommands::Test => {
+            println!("🔬 Checking rendering against embedded fixtures");
+            let outcomes = crate::self_test::run();
+            let mut failures = 0;
+            for outcome in &outcomes {
+                match &outcome.result {
+                    Ok(()) => println!("  ✅ {}", outcome.name),
+                    Err(reason) => {
+                        failures += 1;
+                        println!("  ❌ {}: {}", outcome.name, reason);
+                    }
+                }
+            }
+            if failures > 0 {
+                return Err(CliError::SelfTestFailed { count: failures });
+            }
+            println!("✅ All {} fixtures match", outcomes.len());
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,45 @@
+//! Self update command implementation for Space Pklr
+//!
+//! Installs a spklr GitHub release over the running binary (see [`crate::self_update`]).
+
+use clap::Args;
+
+use crate::self_update::{UpdateOptions, UpdateOutcome};
+use crate::types::CliError;
+
+/// `spklr self-update` arguments
+#[derive(Args)]
+pub struct SelfUpdateArgs {
+    /// Only report whether a newer release is available
+    #[arg(long, help = "Report whether a newer release is available without installing it")]
+    pub check: bool,
+
+    /// Release to install instead of the latest
+    #[arg(long, value_name = "VERSION", help = "Install this release instead of the latest (allows downgrades)")]
+    pub version: Option<String>,
+
+    /// Reinstall the current release, or downgrade
+    #[arg(short, long, help = "Reinstall even if the release isn't newer")]
+    pub force: bool,
+}
+
+/// Handle `spklr self-update`
+pub async fn handle_self_update(args: SelfUpdateArgs) -> Result<(), CliError> {
+    println!("🔍 Checking {} for releases", crate::self_update::release_repo());
+    let options = UpdateOptions {
+        version: args.version,
+        check: args.check,
+        force: args.force,
+    };
+    match crate::self_update::self_update(&options).await? {
+        UpdateOutcome::UpToDate { version } => println!("✅ spklr {} is up to date", version),
+        UpdateOutcome::Available { current, version } => {
+            println!("⬆️  spklr {} is available (installed: {})", version, current);
+            println!("   Run `spklr self-update` to install it");
+        }
+        UpdateOutcome::Updated { from, to, path } => {
+            println!("✅ Updated spklr {} → {} at {}", from, to, path.display());
+        }
+    }
+    Ok(())
+}
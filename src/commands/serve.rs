@@ -0,0 +1,47 @@
+//! Serve command implementation for Space Pklr
+//!
+//! This module runs spklr as a long-lived daemon answering JSON-RPC or HTTP requests
+//!.
+
+use clap::{ArgGroup, Args};
+use std::path::PathBuf;
+
+use crate::types::CliError;
+
+/// Serve command arguments.
+#[derive(Args)]
+#[command(group(ArgGroup::new("listener").required(true).args(["socket", "http"])))]
+pub struct ServeArgs {
+    /// Unix domain socket to listen on
+    #[arg(long, help = "Unix socket path for the JSON-RPC API")]
+    pub socket: Option<PathBuf>,
+
+    /// HTTP listen address (requires the `server` feature)
+    #[arg(long, value_name = "ADDR", help = "HTTP listen address, e.g. :8080 (localhost) or 0.0.0.0:8080")]
+    pub http: Option<String>,
+}
+
+/// Handle serve command execution
+pub async fn handle_serve(args: ServeArgs) -> Result<(), CliError> {
+    crate::read_only::ensure_allowed("open a listening socket")?;
+    match (&args.socket, &args.http) {
+        (Some(socket), Some(http)) => {
+            println!("🛰️  Serving JSON-RPC on {}", socket.display());
+            println!("🌐 Serving HTTP on {}", crate::http_server::normalize_listen_addr(http));
+            tokio::try_join!(
+                crate::daemon::serve_socket(socket),
+                crate::http_server::serve_http(http),
+            )?;
+            Ok(())
+        }
+        (Some(socket), None) => {
+            println!("🛰️  Serving JSON-RPC on {}", socket.display());
+            crate::daemon::serve_socket(socket).await
+        }
+        (None, Some(http)) => {
+            println!("🌐 Serving HTTP on {}", crate::http_server::normalize_listen_addr(http));
+            crate::http_server::serve_http(http).await
+        }
+        (None, None) => unreachable!("clap requires --socket or --http"),
+    }
+}
@@ -0,0 +1,107 @@
+//! Upgrade command implementation for Space Pklr
+//!
+//! Applies the config migrations between Moon releases (see [`crate::upgrade`]).
+
+use clap::Args;
+use std::path::{Path, PathBuf};
+
+use crate::policy::infer_config_type;
+use crate::types::{CliError, MoonConfig};
+use crate::upgrade::{MoonVersion, plan};
+
+/// Upgrade-config command arguments.
+#[derive(Args)]
+pub struct UpgradeConfigArgs {
+    /// Configuration files to upgrade
+    #[arg(required = true, help = "Configuration files to upgrade (yaml or pkl)")]
+    pub files: Vec<PathBuf>,
+
+    /// Moon release to upgrade to
+    #[arg(long, value_name = "VERSION", help = "Moon release to upgrade to, e.g. 1.39 or 1.x")]
+    pub to_moon: MoonVersion,
+
+    /// Configuration type (optional, inferred from each file name)
+    #[arg(long, help = "Configuration type: project, workspace, template, toolchain, task (inferred if not specified)")]
+    pub config_type: Option<MoonConfig>,
+
+    /// Print the plan and the diff without writing the files
+    #[arg(long, help = "Print the upgrade plan without writing it")]
+    pub dry_run: bool,
+}
+
+/// Handle upgrade-config command execution
+///
+/// Upgraded files are staged and written together once every file was planned, so a
+/// failure leaves the project as it was rather than half-migrated.
+pub async fn handle_upgrade_config(args: UpgradeConfigArgs) -> Result<(), CliError> {
+    let mut upgraded = Vec::new();
+    for file in &args.files {
+        crate::types::ensure_file_exists(file)?;
+        let Some(config_type) = args.config_type.or_else(|| infer_config_type(file)) else {
+            println!("⏭️  {}: can't tell the config type; pass --config-type", file.display());
+            continue;
+        };
+
+        let source = tokio::fs::read_to_string(file).await.map_err(|e| CliError::IoError {
+            context: format!("Reading config file: {}", file.display()),
+            source: e,
+        })?;
+        let value = crate::config_processor::load_config_value(file, None).await?;
+        let steps = plan(config_type, &value, &args.to_moon);
+        if steps.is_empty() {
+            println!("✅ {} is up to date for moon {}", file.display(), args.to_moon);
+            continue;
+        }
+
+        println!("📋 {}: {} change(s) for moon {}", file.display(), steps.len(), args.to_moon);
+        for step in &steps {
+            println!("  - {}", step);
+        }
+        let fixes: Vec<_> = steps.into_iter().map(|step| step.fix).collect();
+        let outcome = crate::autofix::apply(file, &source, &fixes);
+        for (fix, reason) in &outcome.skipped {
+            println!("⏭️  {} {}: not changed, {}", file.display(), fix.path, reason);
+        }
+        if !outcome.changed() {
+            continue;
+        }
+        print!("{}", outcome.preview(file));
+        upgraded.push((file, outcome));
+    }
+
+    if args.dry_run || upgraded.is_empty() {
+        return Ok(());
+    }
+    let files: Vec<PathBuf> = upgraded
+        .iter()
+        .map(|(file, _)| {
+            std::fs::canonicalize(file).map_err(|e| CliError::IoError {
+                context: format!("Resolving config file: {}", file.display()),
+                source: e,
+            })
+        })
+        .collect::<Result<_, _>>()?;
+    let root = common_dir(&files);
+    let _lock = crate::lock::PathLock::acquire(&root)?;
+    let staged = crate::atomic_write::StagedDir::begin(&root)?;
+    for (path, (_, outcome)) in files.iter().zip(&upgraded) {
+        let name = path.strip_prefix(&root).unwrap_or(path);
+        staged.write(&name.to_string_lossy(), &outcome.content)?;
+    }
+    staged.commit()?;
+    for (file, outcome) in &upgraded {
+        println!("🔧 Upgraded {} setting(s) in {}", outcome.applied.len(), file.display());
+    }
+    Ok(())
+}
+
+/// The deepest directory containing every one of `files` (absolute paths)
+fn common_dir(files: &[PathBuf]) -> PathBuf {
+    let mut dir = files
+        .first()
+        .and_then(|file| file.parent())
+        .map(Path::to_path_buf)
+        .unwrap_or_default();
+    while !files.iter().all(|file| file.starts_with(&dir)) && dir.pop() {}
+    dir
+}
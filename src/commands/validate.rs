@@ -0,0 +1,218 @@
+//! Validate command implementation for Space Pklr
+//!
+//! This module type-checks a config file against a generated Pkl schema by evaluating
+//! it with `pkl eval`, and points at the offending value when a constraint is violated.
+
+use clap::Args;
+use miette::{NamedSource, SourceSpan};
+use std::path::{Path, PathBuf};
+
+use crate::config_processor::{detect_format_from_path, parse_config_str, render_config_value};
+use crate::types::{CliError, SchemaFormat, SchemaViolation};
+
+/// Header of each error Pkl prints
+const PKL_ERROR_HEADER: &str = "–– Pkl Error ––";
+
+/// Validate command arguments.
+#[derive(Args)]
+pub struct ValidateArgs {
+    /// Config file to check (pkl, yaml, or json)
+    #[arg(help = "Config file to validate")]
+    pub config: PathBuf,
+
+    /// Generated Pkl schema the config should conform to
+    #[arg(long, help = "Generated Pkl schema to validate against")]
+    pub schema: PathBuf,
+
+    /// Config format (optional, detected from the file extension)
+    #[arg(long, help = "Config format: pkl, yaml, json, toml (detected if not specified)")]
+    pub format: Option<SchemaFormat>,
+}
+
+/// A constraint violation reported by `pkl eval`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PklViolation {
+    /// What Pkl reported, e.g. "Type constraint `length > 3` violated."
+    pub message: String,
+    /// Property path of the offending value, e.g. `owners.defaultOwner`
+    pub property: Option<String>,
+    /// Line of the offending value in the evaluated module
+    pub line: Option<usize>,
+}
+
+/// Handle validate command execution
+pub async fn handle_validate(args: ValidateArgs) -> Result<(), CliError> {
+    crate::types::ensure_file_exists(&args.config)?;
+    crate::types::ensure_file_exists(&args.schema)?;
+    let format = match args.format.clone() {
+        Some(format) => format,
+        None => detect_format_from_path(&args.config)?,
+    };
+    let source = tokio::fs::read_to_string(&args.config)
+        .await
+        .map_err(|e| CliError::IoError {
+            context: format!("Reading config file: {}", args.config.display()),
+            source: e,
+        })?;
+    let schema = std::fs::canonicalize(&args.schema).map_err(|e| CliError::IoError {
+        context: format!("Resolving schema: {}", args.schema.display()),
+        source: e,
+    })?;
+
+    println!("🔍 Validating {} against {}", args.config.display(), args.schema.display());
+    let module = amending_module(&source, &format, &schema)?;
+    let file = tempfile::Builder::new()
+        .prefix("spklr-validate-")
+        .suffix(".pkl")
+        .tempfile()
+        .map_err(|e| CliError::IoError {
+            context: "Creating validation Pkl module".to_string(),
+            source: e,
+        })?;
+    std::fs::write(file.path(), &module).map_err(|e| CliError::IoError {
+        context: format!("Writing {}", file.path().display()),
+        source: e,
+    })?;
+
+    let pkl_cli = crate::pkl_tooling::find_pkl_executable()
+        .await
+        .map_err(|e| CliError::Generic(e.to_string()))?
+        .ok_or_else(|| CliError::PklInstallFailed {
+            reason: "Pkl CLI not found".to_string(),
+            help: Some("Install Pkl CLI with: spklr pkl-me pkl".to_string()),
+        })?;
+    let eval_args = vec!["eval".to_string(), file.path().to_string_lossy().to_string()];
+    let error = match crate::pkl_tooling::execute_pkl_command(&pkl_cli, &eval_args).await {
+        Ok(_) => {
+            println!("✅ {} matches its schema", args.config.display());
+            return Ok(());
+        }
+        Err(error) => error,
+    };
+    let stderr = match error.downcast_ref::<CliError>() {
+        Some(CliError::PklExecutionFailed { stderr, .. }) => stderr.clone(),
+        _ => return Err(CliError::Generic(error.to_string())),
+    };
+
+    let module_name = file.path().file_name().map(|name| name.to_string_lossy().to_string());
+    let violation = parse_pkl_error(&stderr, module_name.as_deref().unwrap_or_default());
+    let span = match format {
+        // The amending module's header line pushes the config's lines down by one
+        SchemaFormat::Pkl => violation
+            .line
+            .and_then(|line| line.checked_sub(1))
+            .and_then(|line| line_span(&source, line)),
+        _ => None,
+    }
+    .or_else(|| violation.property.as_deref().and_then(|property| property_span(&source, property)));
+
+    Err(CliError::SchemaViolation(Box::new(SchemaViolation {
+        path: args.config.clone(),
+        message: violation.message,
+        source_code: NamedSource::new(args.config.display().to_string(), source),
+        span,
+    })))
+}
+
+/// Pkl source that amends `schema` with the config's values
+///
+/// Pkl configs keep their own lines after the new `amends` line, with any `amends`/`extends`
+/// header of their own blanked out; YAML, JSON, and TOML configs are rendered as Pkl first.
+pub fn amending_module(source: &str, format: &SchemaFormat, schema: &Path) -> Result<String, CliError> {
+    let body = match format {
+        SchemaFormat::Pkl => source
+            .lines()
+            .map(|line| {
+                let trimmed = line.trim_start();
+                if trimmed.starts_with("amends ") || trimmed.starts_with("extends ") {
+                    ""
+                } else {
+                    line
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ if source.trim().is_empty() => String::new(),
+        _ => render_config_value(&parse_config_str(source, format)?, &SchemaFormat::Pkl)?,
+    };
+    let schema_uri = format!("file://{}", schema.to_string_lossy().replace('\\', "/"));
+    Ok(format!(
+        "amends {}\n{}\n",
+        crate::config_processor::pkl_string(&schema_uri),
+        body
+    ))
+}
+
+/// The first error in `pkl eval` output, located in `module` where possible
+///
+/// Pkl prints the message, then a stack of `at <module>#<property> (<uri>, line N)` frames;
+/// the first frame in `module` is the offending value in the evaluated config.
+pub fn parse_pkl_error(stderr: &str, module: &str) -> PklViolation {
+    let body = stderr
+        .split_once(PKL_ERROR_HEADER)
+        .map(|(_, body)| body)
+        .unwrap_or(stderr);
+    let message = body
+        .trim_start()
+        .split("\n\n")
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_string();
+
+    let frame = body
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("at "))
+        .find(|frame| !module.is_empty() && frame.contains(module));
+    let property = frame
+        .and_then(|frame| frame.split_once('#'))
+        .and_then(|(_, rest)| rest.split([' ', '(']).next())
+        .filter(|property| !property.is_empty())
+        .map(str::to_string);
+    let line = frame
+        .and_then(|frame| frame.rsplit_once("line "))
+        .and_then(|(_, rest)| rest.trim_end_matches(')').trim().parse().ok());
+
+    PklViolation {
+        message: if message.is_empty() { stderr.trim().to_string() } else { message },
+        property,
+        line,
+    }
+}
+
+/// Span of the non-blank text on 1-based `line` of `source`
+pub fn line_span(source: &str, line: usize) -> Option<SourceSpan> {
+    let mut offset = 0;
+    for (index, text) in source.split('\n').enumerate() {
+        if index + 1 == line {
+            let indent = text.len() - text.trim_start().len();
+            let length = text.trim().len();
+            return (length > 0).then(|| SourceSpan::from((offset + indent, length)));
+        }
+        offset += text.len() + 1;
+    }
+    None
+}
+
+/// Span of the line defining the dotted `property` path in a YAML, JSON, or Pkl `source`
+///
+/// Each path segment is looked for after the previous one, so `owners.defaultOwner`
+/// finds the `defaultOwner` key under `owners` rather than an earlier one.
+pub fn property_span(source: &str, property: &str) -> Option<SourceSpan> {
+    let lines: Vec<&str> = source.split('\n').collect();
+    let mut from = 0;
+    let mut found = None;
+    for segment in property.split('.') {
+        let index = (from..lines.len()).find(|&index| defines_key(lines[index], segment))?;
+        found = Some(index);
+        from = index + 1;
+    }
+    found.and_then(|index| line_span(source, index + 1))
+}
+
+fn defines_key(line: &str, key: &str) -> bool {
+    let trimmed = line.trim_start().trim_start_matches("- ");
+    [format!("{}:", key), format!("\"{}\":", key), format!("\"{}\" :", key), format!("{} =", key), format!("{} {{", key)]
+        .iter()
+        .any(|prefix| trimmed.starts_with(prefix.as_str()))
+}
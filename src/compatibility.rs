@@ -0,0 +1,284 @@
+//! Compatibility Module for Space Pklr
+//!
+//! `spklr pkl-me --report` checks every place a version mismatch can hide and prints one
+//! row per component, with what to do about anything that isn't right:
+//!
+//! | Component | Checked against |
+//! |---|---|
+//! | Pkl CLI | Installed; a version tested with this spklr; the `pkl` pin in `spklr-versions.json` |
+//! | Pkl evaluation, amends/extends, packaging | Running the installed CLI on small fixtures |
+//! | moon_config | The release built into spklr vs the one the schemas were generated from |
+//! | Generated schemas | `spklr-versions.json` and `Generated by spklr <version>` headers in the schema directory vs the running spklr |
+//! | Platform | Whether Pkl publishes a native CLI for this OS and architecture |
+//!
+//! `--json` prints the same rows for scripts. Rows whose status is `error` make the
+//! report fail.
+
+use serde::Serialize;
+use std::cmp::Ordering;
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use crate::versions::{MOON_CONFIG_CRATE_VERSION, VersionManifest};
+
+/// Oldest Pkl release spklr works with at all
+pub const MINIMUM_PKL_VERSION: &str = "0.25.0";
+
+/// Outcome of one check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Ok,
+    Warning,
+    Error,
+    /// Nothing to check against
+    Unknown,
+}
+
+impl CheckStatus {
+    pub fn icon(&self) -> &'static str {
+        match self {
+            CheckStatus::Ok => "✅",
+            CheckStatus::Warning => "⚠️ ",
+            CheckStatus::Error => "❌",
+            CheckStatus::Unknown => "➖",
+        }
+    }
+}
+
+/// One row of the compatibility matrix
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CompatibilityCheck {
+    pub component: String,
+    pub found: Option<String>,
+    pub expected: Option<String>,
+    pub status: CheckStatus,
+    /// What to do when the status isn't ok
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remediation: Option<String>,
+}
+
+impl CompatibilityCheck {
+    fn new(component: &str, found: Option<String>, expected: Option<String>, status: CheckStatus) -> Self {
+        Self {
+            component: component.to_string(),
+            found,
+            expected,
+            status,
+            remediation: None,
+        }
+    }
+
+    fn remedy(mut self, remediation: impl Into<String>) -> Self {
+        self.remediation = Some(remediation.into());
+        self
+    }
+}
+
+/// Compare dotted release versions numerically, ignoring a leading `v`; prereleases sort
+/// before their release
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let key = |version: &str| {
+        let version = version.trim().trim_start_matches('v');
+        let (release, pre) = match version.split_once(['-', '+']) {
+            Some((release, pre)) => (release, Some(pre.to_string())),
+            None => (version, None),
+        };
+        let numbers: Vec<u64> = release.split('.').map(|part| part.parse().unwrap_or(0)).collect();
+        (numbers, pre.is_none(), pre)
+    };
+    key(a).cmp(&key(b))
+}
+
+/// Whether `found` satisfies a pin: exact, or a `0.28` prefix of it
+fn pin_matches(pinned: &str, found: &str) -> bool {
+    let pinned = pinned.trim_start_matches('v');
+    let found = found.trim_start_matches('v');
+    found == pinned || found.strip_prefix(pinned).is_some_and(|rest| rest.starts_with('.'))
+}
+
+/// The installed Pkl CLI's version against the tested versions and the project's pin
+pub fn check_pkl_version(found: Option<&str>, pinned: Option<&str>) -> CompatibilityCheck {
+    let tested = crate::pkl_tooling::get_compatible_pkl_versions();
+    let recommended = crate::pkl_tooling::get_recommended_pkl_version();
+    let expected = Some(pinned.unwrap_or(recommended).to_string());
+    let Some(found) = found else {
+        return CompatibilityCheck::new("Pkl CLI", None, expected, CheckStatus::Error)
+            .remedy(format!("Install it: spklr pkl-me pkl --version {}", pinned.unwrap_or(recommended)));
+    };
+    let check = |status| CompatibilityCheck::new("Pkl CLI", Some(found.to_string()), expected.clone(), status);
+
+    if compare_versions(found, MINIMUM_PKL_VERSION) == Ordering::Less {
+        return check(CheckStatus::Error).remedy(format!(
+            "Pkl {} is older than spklr supports ({}+); install a tested version: spklr pkl-me pkl --version {}",
+            found, MINIMUM_PKL_VERSION, recommended
+        ));
+    }
+    if let Some(pinned) = pinned
+        && !pin_matches(pinned, found)
+    {
+        return check(CheckStatus::Warning).remedy(format!(
+            "spklr-versions.json pins Pkl {}; install it with: spklr pkl-me pkl --version {}",
+            pinned, pinned
+        ));
+    }
+    if !tested.iter().any(|version| compare_versions(version, found) == Ordering::Equal) {
+        let direction = if compare_versions(found, recommended) == Ordering::Greater { "newer" } else { "older" };
+        return check(CheckStatus::Warning).remedy(format!(
+            "Pkl {} is {} than the versions this spklr was tested with ({}); install a tested one: spklr pkl-me pkl --version {}",
+            found,
+            direction,
+            tested.join(", "),
+            recommended
+        ));
+    }
+    check(CheckStatus::Ok)
+}
+
+/// One of the functional checks run with the installed Pkl CLI
+pub fn check_functional(component: &str, passed: bool) -> CompatibilityCheck {
+    if passed {
+        return CompatibilityCheck::new(component, Some("passed".to_string()), None, CheckStatus::Ok);
+    }
+    CompatibilityCheck::new(component, Some("failed".to_string()), None, CheckStatus::Error)
+        .remedy("Reinstall the Pkl CLI (spklr pkl-me pkl --force) and rerun with --verbose for the failing command")
+}
+
+/// The moon_config release built into spklr against the one the schemas describe
+pub fn check_moon_config(manifest: Option<&VersionManifest>) -> CompatibilityCheck {
+    let built = Some(MOON_CONFIG_CRATE_VERSION.to_string());
+    let Some(manifest) = manifest else {
+        return CompatibilityCheck::new("moon_config", built, None, CheckStatus::Unknown)
+            .remedy("No spklr-versions.json records which moon_config the schemas describe");
+    };
+    let check = |status| CompatibilityCheck::new("moon_config", built.clone(), Some(manifest.moon_config.clone()), status);
+    match compare_versions(MOON_CONFIG_CRATE_VERSION, &manifest.moon_config) {
+        Ordering::Equal => check(CheckStatus::Ok),
+        Ordering::Greater => check(CheckStatus::Warning).remedy(format!(
+            "The schemas describe moon_config {}, but this spklr generates {}; regenerate them with `spklr generate`",
+            manifest.moon_config, MOON_CONFIG_CRATE_VERSION
+        )),
+        Ordering::Less => check(CheckStatus::Error).remedy(format!(
+            "The schemas describe moon_config {}, newer than this spklr's {}; upgrade spklr before regenerating",
+            manifest.moon_config, MOON_CONFIG_CRATE_VERSION
+        )),
+    }
+}
+
+/// spklr versions named by `Generated by spklr <version>` headers of the Pkl files in `dir`
+pub fn schema_markers(dir: &Path) -> BTreeSet<String> {
+    static MARKER: OnceLock<regex::Regex> = OnceLock::new();
+    let marker = MARKER.get_or_init(|| {
+        regex::Regex::new(r"(?i)generated by spklr v?(\d+\.\d+\.\d+(?:-[0-9A-Za-z.]*[0-9A-Za-z])?)").expect("valid marker regex")
+    });
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return BTreeSet::new();
+    };
+    let mut versions = BTreeSet::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().is_none_or(|extension| extension != "pkl") {
+            continue;
+        }
+        let Ok(content) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        // Headers come first; don't scan whole modules
+        let head: String = content.lines().take(20).collect::<Vec<_>>().join("\n");
+        versions.extend(marker.captures_iter(&head).map(|captures| captures[1].to_string()));
+    }
+    versions
+}
+
+/// The spklr that generated the schemas in `dir` against the running one
+pub fn check_generated_schemas(dir: &Path, manifest: Option<&VersionManifest>) -> CompatibilityCheck {
+    let running = env!("CARGO_PKG_VERSION");
+    let mut versions = schema_markers(dir);
+    versions.extend(manifest.map(|manifest| manifest.spklr.clone()));
+    let expected = Some(running.to_string());
+
+    let Some(newest) = versions.iter().max_by(|a, b| compare_versions(a, b)).cloned() else {
+        return CompatibilityCheck::new("Generated schemas", None, expected, CheckStatus::Unknown)
+            .remedy(format!("No spklr-versions.json or generated headers in {}", dir.display()));
+    };
+    let found = Some(versions.iter().cloned().collect::<Vec<_>>().join(", "));
+    let check = |status| CompatibilityCheck::new("Generated schemas", found.clone(), expected.clone(), status);
+    if compare_versions(&newest, running) == Ordering::Greater {
+        return check(CheckStatus::Error).remedy(format!(
+            "Generated by spklr {}, newer than this one; upgrade spklr before regenerating",
+            newest
+        ));
+    }
+    if versions.len() > 1 {
+        return check(CheckStatus::Warning)
+            .remedy("Schemas from several spklr releases are mixed; regenerate them all with `spklr generate`");
+    }
+    if compare_versions(&newest, running) == Ordering::Less {
+        return check(CheckStatus::Warning).remedy(format!(
+            "Generated by spklr {}; regenerate with `spklr generate` to pick up this release's schemas",
+            newest
+        ));
+    }
+    check(CheckStatus::Ok)
+}
+
+/// Whether Pkl publishes a native CLI for `os` and `arch` (Rust's names for them)
+pub fn check_platform(os: &str, arch: &str) -> CompatibilityCheck {
+    let found = Some(format!("{}/{}", os, arch));
+    match crate::pkl_tooling::pkl_archive_name(os, arch) {
+        Some(archive) => CompatibilityCheck::new("Platform", found, Some(archive), CheckStatus::Ok),
+        None => CompatibilityCheck::new("Platform", found, None, CheckStatus::Warning).remedy(
+            "Pkl publishes no native CLI for this platform; put a `pkl` wrapper around the Java build (pkl-cli-java.jar) on PATH",
+        ),
+    }
+}
+
+/// The matrix as an aligned table, with remediation steps under it
+pub fn render_matrix(checks: &[CompatibilityCheck]) -> String {
+    let rows: Vec<[String; 4]> = checks
+        .iter()
+        .map(|check| {
+            [
+                format!("{} {}", check.status.icon(), check.component),
+                check.found.clone().unwrap_or_else(|| "-".to_string()),
+                check.expected.clone().unwrap_or_else(|| "-".to_string()),
+                format!("{:?}", check.status).to_lowercase(),
+            ]
+        })
+        .collect();
+    let header = ["Component".to_string(), "Found".to_string(), "Expected".to_string(), "Status".to_string()];
+    let widths: Vec<usize> = (0..4)
+        .map(|column| {
+            std::iter::once(&header)
+                .chain(&rows)
+                .map(|row| row[column].chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    let mut out = String::new();
+    for row in std::iter::once(&header).chain(&rows) {
+        let cells: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{}{}", cell, " ".repeat(width - cell.chars().count())))
+            .collect();
+        out.push_str(cells.join("  ").trim_end());
+        out.push('\n');
+    }
+
+    let remediations: Vec<&CompatibilityCheck> = checks
+        .iter()
+        .filter(|check| matches!(check.status, CheckStatus::Warning | CheckStatus::Error))
+        .filter(|check| check.remediation.is_some())
+        .collect();
+    if !remediations.is_empty() {
+        out.push_str("\nTo fix:\n");
+        for check in remediations {
+            out.push_str(&format!("  - {}: {}\n", check.component, check.remediation.as_deref().unwrap_or_default()));
+        }
+    }
+    out
+}
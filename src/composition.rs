@@ -0,0 +1,252 @@
+//! Schema Composition Module for Space Pklr
+//!
+//! A `composition.toml` manifest combines the Moon config schemas with modules
+//! imported from JSON Schema (an organization's own config types) into one Pkl
+//! package. `spklr generate schema --composition composition.toml --output <dir>`
+//! generates every source, moves the types they define identically into a shared
+//! `Common` module that the others import, and writes a `PklProject` describing the
+//! package. All modules go through the same `[templates]` header and footer.
+//!
+//! ```toml
+//! [package]
+//! name = "acme-moon"
+//! version = "1.2.0"
+//! base_uri = "package://pkg.acme.dev/acme-moon"
+//! package_zip_url = "https://pkg.acme.dev/acme-moon@1.2.0.zip"
+//!
+//! [[sources]]
+//! moon = "project"
+//!
+//! [[sources]]
+//! json_schema = "schemas/deploy.schema.json"
+//! name = "Deploy"
+//! ```
+//!
+//! Relative `json_schema` paths are resolved against the manifest's directory.
+
+use serde::Deserialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::pkl_schema::{PklModule, PklType};
+use crate::types::{CliError, MoonConfig};
+
+/// File name of the generated package description
+pub const PROJECT_FILE: &str = "PklProject";
+
+/// A parsed `composition.toml`
+#[derive(Debug, Clone, Deserialize)]
+pub struct CompositionManifest {
+    pub package: PackageInfo,
+    #[serde(default)]
+    pub sources: Vec<CompositionSource>,
+}
+
+/// `[package]`: the published package
+#[derive(Debug, Clone, Deserialize)]
+pub struct PackageInfo {
+    pub name: String,
+    pub version: Option<String>,
+    pub base_uri: Option<String>,
+    pub package_zip_url: Option<String>,
+    /// Module holding the types shared by several sources
+    #[serde(default = "default_common")]
+    pub common: String,
+}
+
+fn default_common() -> String {
+    "Common".to_string()
+}
+
+/// `[[sources]]`: one Moon config type or JSON Schema document
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct CompositionSource {
+    /// Moon config type (`project`, `workspace`, ...)
+    pub moon: Option<String>,
+    /// JSON Schema document to import
+    pub json_schema: Option<PathBuf>,
+    /// Module name, instead of the schema's title
+    pub name: Option<String>,
+}
+
+/// Where a source's module comes from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SourceKind {
+    Moon(MoonConfig),
+    JsonSchema(PathBuf),
+}
+
+impl CompositionSource {
+    pub fn kind(&self) -> Result<SourceKind, CliError> {
+        match (&self.moon, &self.json_schema) {
+            (Some(moon), None) => match MoonConfig::from_str(moon)? {
+                MoonConfig::All => Err(CliError::Generic(
+                    "Composition sources name one Moon config type each, not 'all'".to_string(),
+                )),
+                config => Ok(SourceKind::Moon(config)),
+            },
+            (None, Some(path)) => Ok(SourceKind::JsonSchema(path.clone())),
+            _ => Err(CliError::Generic(
+                "Every composition source needs exactly one of `moon` or `json_schema`".to_string(),
+            )),
+        }
+    }
+}
+
+impl CompositionManifest {
+    /// Load and check a manifest, resolving source paths against its directory
+    pub fn load(path: &Path) -> Result<Self, CliError> {
+        let content = std::fs::read_to_string(path).map_err(|e| CliError::IoError {
+            context: format!("Reading composition manifest: {}", path.display()),
+            source: e,
+        })?;
+        let mut manifest = Self::parse(&content)?;
+        let base = path.parent().unwrap_or(Path::new("."));
+        for source in &mut manifest.sources {
+            if let Some(schema) = source.json_schema.as_mut().filter(|schema| schema.is_relative()) {
+                *schema = base.join(&*schema);
+            }
+        }
+        Ok(manifest)
+    }
+
+    /// A package of every Moon config type, with modules named after the types
+    /// (`Project`, `Workspace`, ...)
+    pub fn moon_package(name: &str) -> Self {
+        Self {
+            package: PackageInfo {
+                name: name.to_string(),
+                version: None,
+                base_uri: None,
+                package_zip_url: None,
+                common: default_common(),
+            },
+            sources: MoonConfig::all_types()
+                .into_iter()
+                .map(|config_type| CompositionSource {
+                    moon: Some(config_type.to_string()),
+                    name: Some(crate::templates::module::module_name(config_type)),
+                    ..Default::default()
+                })
+                .collect(),
+        }
+    }
+
+    /// Parse and check manifest content
+    pub fn parse(content: &str) -> Result<Self, CliError> {
+        let manifest: CompositionManifest = toml::from_str(content).map_err(|e| CliError::ValidationError {
+            source: Box::new(e),
+        })?;
+        if manifest.sources.is_empty() {
+            return Err(CliError::Generic("The composition manifest lists no `[[sources]]`".to_string()));
+        }
+        for source in &manifest.sources {
+            source.kind()?;
+        }
+        Ok(manifest)
+    }
+
+    /// `PklProject` content for the package
+    pub fn pkl_project(&self) -> String {
+        let package = &self.package;
+        let mut fields = vec![format!("name = {}", crate::config_processor::pkl_string(&package.name))];
+        let optional = [
+            ("baseUri", &package.base_uri),
+            ("version", &package.version),
+            ("packageZipUrl", &package.package_zip_url),
+        ];
+        for (field, value) in optional {
+            if let Some(value) = value {
+                fields.push(format!("{} = {}", field, crate::config_processor::pkl_string(value)));
+            }
+        }
+        let body: String = fields.iter().map(|field| format!("  {}\n", field)).collect();
+        format!("amends \"pkl:Project\"\n\npackage {{\n{}}}\n", body)
+    }
+}
+
+/// Move types defined identically by several modules into a `common` module
+///
+/// The common module is added first (when anything is shared), and every module that
+/// used a moved type imports it from there. Types that share a name but differ stay
+/// where they are, as do shared types that depend on one that isn't shared.
+pub fn compose(modules: &mut Vec<PklModule>, common: &str) {
+    let mut definitions: BTreeMap<String, Vec<Definition>> = BTreeMap::new();
+    for module in modules.iter() {
+        for class in &module.classes {
+            definitions.entry(class.name.clone()).or_default().push(Definition::Class(class.clone()));
+        }
+        for typealias in &module.typealiases {
+            definitions
+                .entry(typealias.name.clone())
+                .or_default()
+                .push(Definition::Typealias(typealias.clone()));
+        }
+    }
+
+    let mut shared: BTreeSet<String> = definitions
+        .iter()
+        .filter(|(_, defined)| defined.len() > 1 && defined.iter().all(|definition| *definition == defined[0]))
+        .map(|(name, _)| name.clone())
+        .collect();
+    // The common module can't import from the modules that import it
+    loop {
+        let dependent: Vec<String> = shared
+            .iter()
+            .filter(|name| {
+                definitions[*name][0]
+                    .references()
+                    .iter()
+                    .any(|reference| definitions.contains_key(reference) && !shared.contains(reference))
+            })
+            .cloned()
+            .collect();
+        if dependent.is_empty() {
+            break;
+        }
+        dependent.iter().for_each(|name| {
+            shared.remove(name);
+        });
+    }
+    if shared.is_empty() {
+        return;
+    }
+
+    let mut common_module = PklModule {
+        name: common.to_string(),
+        doc: Some("Types shared by the modules of this package".to_string()),
+        open: modules.first().is_none_or(|module| module.open),
+        ..Default::default()
+    };
+    for name in &shared {
+        match &definitions[name][0] {
+            Definition::Class(class) => common_module.classes.push(class.clone()),
+            Definition::Typealias(typealias) => common_module.typealiases.push(typealias.clone()),
+        }
+    }
+    for module in modules.iter_mut() {
+        module.classes.retain(|class| !shared.contains(&class.name));
+        module.typealiases.retain(|typealias| !shared.contains(&typealias.name));
+    }
+    modules.insert(0, common_module);
+    crate::pkl_schema::imports::resolve_imports(modules);
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Definition {
+    Class(crate::pkl_schema::PklClass),
+    Typealias(crate::pkl_schema::PklTypeAlias),
+}
+
+impl Definition {
+    /// Type names the definition refers to
+    fn references(&self) -> Vec<String> {
+        let types: Vec<&PklType> = match self {
+            Definition::Class(class) => class.properties.iter().map(|property| &property.ty).collect(),
+            Definition::Typealias(typealias) => vec![&typealias.ty],
+        };
+        types.into_iter().flat_map(crate::pkl_schema::filters::class_names).collect()
+    }
+}
@@ -0,0 +1,61 @@
+//! Concurrency Module for Space Pklr
+//!
+//! How much spklr does at once. The job count comes from, in order:
+//!
+//! 1. `--jobs N` on the command line
+//! 2. `concurrency` in the `[generator]` section of `spklr.toml`
+//! 3. the cores available to the process (which honours cgroup CPU quotas on Linux)
+//!
+//! `0` means "detect". The count caps the threads generating and rendering Pkl
+//! modules, the files `lint` loads side by side, and the Pkl CLI evaluations running
+//! at once. `--jobs` also sizes the Tokio worker pool.
+//!
+//! ```toml
+//! [generator]
+//! concurrency = 2
+//! ```
+//!
+//! Memory grows with the job count: every generation thread holds its schema and the
+//! modules rendered from it, and every `pkl eval` is a separate JVM or native process,
+//! typically 100–300 MB each. On constrained CI runners, `--jobs 1` or `--jobs 2` trades
+//! wall-clock time for a much lower peak; `spklr --timings` reports the peak so you
+//! can tune it.
+
+use std::num::NonZeroUsize;
+use std::sync::{Mutex, OnceLock};
+
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+/// `--jobs` from the command line
+static CLI_JOBS: Mutex<Option<usize>> = Mutex::new(None);
+
+/// Limits concurrent Pkl CLI evaluations
+static EVALUATIONS: OnceLock<Semaphore> = OnceLock::new();
+
+/// Set the job count from `--jobs`
+pub fn set_cli_jobs(jobs: Option<usize>) {
+    *CLI_JOBS.lock().unwrap_or_else(|e| e.into_inner()) = jobs;
+}
+
+/// Cores available to this process
+pub fn available() -> usize {
+    std::thread::available_parallelism().map(NonZeroUsize::get).unwrap_or(1)
+}
+
+/// Jobs to run at once: `--jobs`, then `configured`, then [`available`]
+pub fn jobs(configured: Option<usize>) -> usize {
+    let cli = *CLI_JOBS.lock().unwrap_or_else(|e| e.into_inner());
+    match cli.or(configured) {
+        Some(0) | None => available(),
+        Some(jobs) => jobs,
+    }
+}
+
+/// Wait for a free Pkl evaluation slot; hold the permit while the CLI runs
+pub async fn evaluation_permit() -> SemaphorePermit<'static> {
+    EVALUATIONS
+        .get_or_init(|| Semaphore::new(jobs(None)))
+        .acquire()
+        .await
+        .expect("the evaluation semaphore is never closed")
+}
@@ -0,0 +1,250 @@
+//! Config Path Module for Space Pklr
+//!
+//! One dotted path syntax for addressing values in a loaded config, shared by the
+//! `query` daemon request and policy selectors, `[redaction]` globs, and the
+//! property filters and overrides of generated schemas:
+//!
+//! ```
+//! use serde_json::json;
+//! use space_pklr::config_path::ConfigPath;
+//!
+//! let mut config = json!({ "tasks": { "build": { "deps": ["lint"] } } });
+//! let path = ConfigPath::new("tasks.build.options.cache");
+//! path.set(&mut config, json!(true)).unwrap();
+//! assert_eq!(path.get(&config), Some(&json!(true)));
+//! assert_eq!(ConfigPath::new("tasks.build.deps.0").get(&config), Some(&json!("lint")));
+//! ```
+//!
+//! Segments are object keys, or indexes into lists. In patterns, `*` matches any one
+//! segment and `**` any number of them (including none).
+
+use serde_json::Value;
+use std::fmt::Display;
+
+use crate::types::CliError;
+
+/// A dot-separated path into a config value, like `tasks.build.options.cache`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ConfigPath {
+    segments: Vec<String>,
+}
+
+impl ConfigPath {
+    /// Parse a dotted path; empty segments (`a..b`, a trailing `.`) are ignored
+    pub fn new(path: &str) -> Self {
+        Self {
+            segments: path.split('.').filter(|segment| !segment.is_empty()).map(str::to_string).collect(),
+        }
+    }
+
+    pub fn from_segments(segments: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self {
+            segments: segments.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    pub fn segments(&self) -> &[String] {
+        &self.segments
+    }
+
+    /// Whether this is the empty path, addressing the root
+    pub fn is_root(&self) -> bool {
+        self.segments.is_empty()
+    }
+
+    /// Whether the path contains `*` or `**`
+    pub fn is_pattern(&self) -> bool {
+        self.segments.iter().any(|segment| segment == "*" || segment == "**")
+    }
+
+    /// This path with `segment` appended
+    pub fn child(&self, segment: impl Into<String>) -> Self {
+        let mut child = self.clone();
+        child.segments.push(segment.into());
+        child
+    }
+
+    /// The value at this path, if there is one
+    pub fn get<'a>(&self, root: &'a Value) -> Option<&'a Value> {
+        self.segments.iter().try_fold(root, |value, segment| child(value, segment))
+    }
+
+    /// A mutable reference to the value at this path, if there is one
+    pub fn get_mut<'a>(&self, root: &'a mut Value) -> Option<&'a mut Value> {
+        self.segments.iter().try_fold(root, |value, segment| match value {
+            Value::Object(map) => map.get_mut(segment),
+            Value::Array(items) => segment.parse::<usize>().ok().and_then(|index| items.get_mut(index)),
+            _ => None,
+        })
+    }
+
+    /// Set the value at this path, creating missing objects along the way
+    ///
+    /// Returns the value it replaced. List items can be replaced but not appended, and a
+    /// path running through a scalar is an error.
+    pub fn set(&self, root: &mut Value, value: Value) -> Result<Option<Value>, CliError> {
+        if self.is_pattern() {
+            return Err(CliError::Generic(format!("Cannot set a value at pattern '{}'", self)));
+        }
+        let Some((last, parents)) = self.segments.split_last() else {
+            return Ok(Some(std::mem::replace(root, value)));
+        };
+
+        let mut current = root;
+        for (depth, segment) in parents.iter().enumerate() {
+            if current.is_null() {
+                *current = Value::Object(serde_json::Map::new());
+            }
+            let at = || ConfigPath::from_segments(self.segments[..=depth].iter().cloned());
+            current = match current {
+                Value::Object(map) => map.entry(segment.clone()).or_insert(Value::Null),
+                Value::Array(items) => {
+                    let len = items.len();
+                    segment
+                        .parse::<usize>()
+                        .ok()
+                        .and_then(|index| items.get_mut(index))
+                        .ok_or_else(|| CliError::Generic(format!("'{}' is not an item of a {}-item list", at(), len)))?
+                }
+                _ => return Err(CliError::Generic(format!("'{}' is not an object or list", at().parent_display()))),
+            };
+        }
+
+        if current.is_null() {
+            *current = Value::Object(serde_json::Map::new());
+        }
+        match current {
+            Value::Object(map) => Ok(map.insert(last.clone(), value)),
+            Value::Array(items) => {
+                let len = items.len();
+                let slot = last
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|index| items.get_mut(index))
+                    .ok_or_else(|| CliError::Generic(format!("'{}' is not an item of a {}-item list", self, len)))?;
+                Ok(Some(std::mem::replace(slot, value)))
+            }
+            _ => Err(CliError::Generic(format!("'{}' is not an object or list", self.parent_display()))),
+        }
+    }
+
+    /// Whether this pattern matches all of `path`
+    pub fn matches(&self, path: &ConfigPath) -> bool {
+        matches_segments(&self.segments, &path.segments)
+    }
+
+    /// Whether some path below `path` could still match this pattern
+    pub fn matches_prefix(&self, path: &ConfigPath) -> bool {
+        prefix_matches(&self.segments, &path.segments)
+    }
+
+    /// Every value matching this pattern, with its concrete path
+    ///
+    /// A missing final key outside `**` is reported as `null`, so callers can tell
+    /// "selected but unset" from "not selected".
+    pub fn select<'a>(&self, root: &'a Value) -> Vec<(ConfigPath, &'a Value)> {
+        let mut results = Vec::new();
+        select_into(root, &self.segments, ConfigPath::default(), true, &mut results);
+        results
+    }
+
+    fn parent_display(&self) -> String {
+        let parent = &self.segments[..self.segments.len().saturating_sub(1)];
+        if parent.is_empty() { "<root>".to_string() } else { parent.join(".") }
+    }
+}
+
+impl Display for ConfigPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.segments.join("."))
+    }
+}
+
+impl std::str::FromStr for ConfigPath {
+    type Err = CliError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self::new(s))
+    }
+}
+
+impl From<&str> for ConfigPath {
+    fn from(path: &str) -> Self {
+        Self::new(path)
+    }
+}
+
+/// Whether pattern segments match all of `path`
+pub fn matches_segments<S: AsRef<str>, T: AsRef<str>>(pattern: &[S], path: &[T]) -> bool {
+    match (pattern.split_first(), path.split_first()) {
+        (None, None) => true,
+        (Some((first, rest)), _) if first.as_ref() == "**" => {
+            matches_segments(rest, path) || (!path.is_empty() && matches_segments(pattern, &path[1..]))
+        }
+        (Some((expected, rest)), Some((segment, remaining))) => {
+            (expected.as_ref() == "*" || expected.as_ref() == segment.as_ref()) && matches_segments(rest, remaining)
+        }
+        _ => false,
+    }
+}
+
+/// Whether some path below `path` could match the pattern segments
+pub fn prefix_matches<S: AsRef<str>, T: AsRef<str>>(pattern: &[S], path: &[T]) -> bool {
+    match (pattern.split_first(), path.split_first()) {
+        (_, None) => !pattern.is_empty(),
+        (Some((first, _)), Some(_)) if first.as_ref() == "**" => true,
+        (Some((expected, rest)), Some((segment, remaining))) => {
+            (expected.as_ref() == "*" || expected.as_ref() == segment.as_ref()) && prefix_matches(rest, remaining)
+        }
+        _ => false,
+    }
+}
+
+fn child<'a>(value: &'a Value, segment: &str) -> Option<&'a Value> {
+    match value {
+        Value::Object(map) => map.get(segment),
+        Value::Array(items) => segment.parse::<usize>().ok().and_then(|index| items.get(index)),
+        _ => None,
+    }
+}
+
+fn children(value: &Value) -> Vec<(String, &Value)> {
+    match value {
+        Value::Object(map) => map.iter().map(|(key, value)| (key.clone(), value)).collect(),
+        Value::Array(items) => items.iter().enumerate().map(|(index, value)| (index.to_string(), value)).collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn select_into<'a>(
+    value: &'a Value,
+    segments: &[String],
+    path: ConfigPath,
+    report_missing: bool,
+    results: &mut Vec<(ConfigPath, &'a Value)>,
+) {
+    let Some((segment, rest)) = segments.split_first() else {
+        results.push((path, value));
+        return;
+    };
+
+    match segment.as_str() {
+        "**" => {
+            // Under `**` a missing key just means "not at this depth"
+            select_into(value, rest, path.clone(), false, results);
+            for (key, child) in children(value) {
+                select_into(child, segments, path.child(key), false, results);
+            }
+        }
+        "*" => {
+            for (key, child) in children(value) {
+                select_into(child, rest, path.child(key), report_missing, results);
+            }
+        }
+        key => match child(value, key) {
+            Some(child) => select_into(child, rest, path.child(key), report_missing, results),
+            None if rest.is_empty() && report_missing => results.push((path.child(key), &Value::Null)),
+            None => {}
+        },
+    }
+}
@@ -0,0 +1,501 @@
+//! Config Processor Module for Space Pklr
+//!
+//! This module loads Moon configuration files into an untyped `serde_json::Value`
+//! tree. Commands that only need to inspect a config (graphs, linting, queries)
+//! work from this view instead of the strongly typed `moon_config` structs, so
+//! they keep working on partially migrated or slightly invalid configs.
+
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::config_path::ConfigPath;
+use crate::env_tokens::EnvTokenPolicy;
+use crate::types::{CliError, MoonConfig, SchemaFormat};
+
+/// Detect format from file path extension
+pub fn detect_format_from_path(path: &Path) -> Result<SchemaFormat, CliError> {
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .ok_or_else(|| CliError::UnsupportedFormat {
+            format: "unknown".to_string(),
+            available: vec!["yaml", "yml", "json", "jsonc", "json5", "toml", "pkl"],
+        })?;
+
+    SchemaFormat::from_str(extension)
+}
+
+/// Parse configuration content that is already in memory
+///
+/// Pkl content can't be parsed without evaluation; use [`load_config_value`] for Pkl files.
+pub fn parse_config_str(content: &str, format: &SchemaFormat) -> Result<Value, CliError> {
+    match format {
+        SchemaFormat::Yaml => serde_yaml::from_str(content).map_err(|e| CliError::ValidationError {
+            source: Box::new(e),
+        }),
+        SchemaFormat::Json => serde_json::from_str(content).map_err(|e| CliError::ValidationError {
+            source: Box::new(e),
+        }),
+        SchemaFormat::Jsonc => serde_json::from_str(&crate::jsonc::strip(content)).map_err(|e| CliError::ValidationError {
+            source: Box::new(e),
+        }),
+        SchemaFormat::Toml => toml::from_str::<toml::Table>(content)
+            .map(|table| toml_to_value(toml::Value::Table(table)))
+            .map_err(|e| CliError::ValidationError {
+                source: Box::new(e),
+            }),
+        SchemaFormat::Pkl | SchemaFormat::Typescript => Err(CliError::UnsupportedFormat {
+            format: format.to_string(),
+            available: vec!["yaml", "json", "jsonc", "toml"],
+        }),
+    }
+}
+
+/// The path that stands for stdin (as an input) or stdout (as an output)
+pub const STDIO: &str = "-";
+
+/// Whether `path` is `-`, for stdin or stdout
+pub fn is_stdio(path: &Path) -> bool {
+    path == Path::new(STDIO)
+}
+
+/// Read an input file, or all of stdin for `-`
+pub async fn read_input(path: &Path) -> Result<String, CliError> {
+    if is_stdio(path) {
+        let mut content = String::new();
+        tokio::io::AsyncReadExt::read_to_string(&mut tokio::io::stdin(), &mut content)
+            .await
+            .map_err(|e| CliError::IoError {
+                context: "Reading config from stdin".to_string(),
+                source: e,
+            })?;
+        return Ok(content);
+    }
+    tokio::fs::read_to_string(path).await.map_err(|e| CliError::IoError {
+        context: format!("Reading config file: {}", path.display()),
+        source: e,
+    })
+}
+
+/// Load a configuration file into a `serde_json::Value`
+///
+/// YAML, JSON (with or without comments), and TOML are parsed directly. Pkl files are evaluated with the Pkl CLI
+/// (`pkl eval -f json`), so a Pkl installation is required for them, unless spklr was built with
+/// `native_pkl` and the config is literal-only (see [`crate::native_pkl`]). `-` reads the config
+/// from stdin, which has no extension to detect the format from, so `format` is required.
+pub async fn load_config_value(path: &Path, format: Option<SchemaFormat>) -> Result<Value, CliError> {
+    let format = match format {
+        Some(fmt) => fmt,
+        None if is_stdio(path) => {
+            return Err(CliError::Generic("Reading a config from stdin needs its format (--from)".to_string()));
+        }
+        None => detect_format_from_path(path)?,
+    };
+
+    if format == SchemaFormat::Pkl && !is_stdio(path) {
+        return evaluate_pkl_to_value(path).await;
+    }
+    load_config_source(&read_input(path).await?, &format).await
+}
+
+/// Load configuration text that didn't come from a file, such as stdin
+///
+/// Pkl source is evaluated from a temporary file, since the Pkl CLI only reads files.
+pub async fn load_config_source(content: &str, format: &SchemaFormat) -> Result<Value, CliError> {
+    if *format == SchemaFormat::Pkl {
+        let file = tempfile::Builder::new()
+            .suffix(".pkl")
+            .tempfile()
+            .map_err(|e| CliError::IoError {
+                context: "Creating temporary Pkl file".to_string(),
+                source: e,
+            })?;
+        std::fs::write(file.path(), content).map_err(|e| CliError::IoError {
+            context: format!("Writing temporary Pkl file: {}", file.path().display()),
+            source: e,
+        })?;
+        return evaluate_pkl_to_value(file.path()).await;
+    }
+
+    // An empty YAML document is a valid (empty) Moon config
+    if content.trim().is_empty() {
+        return Ok(Value::Object(serde_json::Map::new()));
+    }
+
+    parse_config_str(content, format)
+}
+
+/// Validate a config value against the strongly typed Moon config for `config_type`
+pub fn validate_config_value(value: &Value, config_type: MoonConfig) -> Result<(), CliError> {
+    use moon_config::{ProjectConfig, TaskConfig, TemplateConfig, ToolchainConfig, WorkspaceConfig};
+    use schematic::{Config, ConfigLoader, Format};
+
+    fn validate<T: Config>(code: String) -> Result<(), CliError> {
+        let mut loader = ConfigLoader::<T>::new();
+        loader
+            .code(code, Format::Json)
+            .map_err(|e| CliError::ValidationError { source: Box::new(e) })?;
+        loader
+            .load()
+            .map_err(|e| CliError::ValidationError { source: Box::new(e) })?;
+        Ok(())
+    }
+
+    let code = serde_json::to_string(value).map_err(|e| CliError::ValidationError {
+        source: Box::new(e),
+    })?;
+
+    match config_type {
+        MoonConfig::Project => validate::<ProjectConfig>(code),
+        MoonConfig::Workspace => validate::<WorkspaceConfig>(code),
+        MoonConfig::Template => validate::<TemplateConfig>(code),
+        MoonConfig::Toolchain => validate::<ToolchainConfig>(code),
+        MoonConfig::Task => validate::<TaskConfig>(code),
+        MoonConfig::All => Err(CliError::Generic(
+            "Cannot validate against 'all' - choose a single configuration type".to_string(),
+        )),
+    }
+}
+
+/// Moon config keys whose values are `Mapping`s in the Pkl schemas, so their keys render as entries
+const PKL_MAPPING_KEYS: [&str; 7] = [
+    "aliases", "env", "fileGroups", "metadata", "projects", "tasks", "templates",
+];
+
+/// Serialize a config value into the requested format
+pub fn render_config_value(value: &Value, format: &SchemaFormat) -> Result<String, CliError> {
+    let mut out = Vec::new();
+    write_config_value(value, format, &mut out)?;
+    String::from_utf8(out).map_err(|e| CliError::Generic(format!("Rendered config is not UTF-8: {}", e)))
+}
+
+/// Serialize a config value into the requested format, streaming it into `out`
+///
+/// Pkl output is rendered one top-level member at a time, so only the largest member is
+/// ever held in memory.
+pub fn write_config_value(value: &Value, format: &SchemaFormat, out: &mut dyn std::io::Write) -> Result<(), CliError> {
+    match format {
+        SchemaFormat::Yaml => serde_yaml::to_writer(out, value).map_err(|e| CliError::ValidationError {
+            source: Box::new(e),
+        }),
+        SchemaFormat::Json | SchemaFormat::Jsonc => serde_json::to_writer_pretty(out, value).map_err(|e| CliError::ValidationError {
+            source: Box::new(e),
+        }),
+        SchemaFormat::Toml => {
+            let toml::Value::Table(table) = value_to_toml(value, &ConfigPath::default())?.unwrap_or(toml::Value::Table(toml::Table::new())) else {
+                return Err(CliError::Generic("Only objects can be rendered as a TOML document".to_string()));
+            };
+            let rendered = toml::to_string(&table).map_err(|e| CliError::ValidationError {
+                source: Box::new(e),
+            })?;
+            out.write_all(rendered.as_bytes()).map_err(|e| CliError::IoError {
+                context: "Writing rendered TOML".to_string(),
+                source: e,
+            })
+        }
+        SchemaFormat::Pkl => write_pkl_with_docs(value, &BTreeMap::new(), out),
+        SchemaFormat::Typescript => Err(CliError::UnsupportedFormat {
+            format: format.to_string(),
+            available: vec!["yaml", "json", "toml", "pkl"],
+        }),
+    }
+}
+
+/// Stream a config value as a Pkl module, with `docs` as comments above the members
+///
+/// `docs` is keyed by dotted path, as [`crate::jsonc::comments`] returns it. Properties
+/// get `///` doc comments; mapping entries, listing elements, and the root (the empty
+/// path) get `//` line comments.
+pub fn write_pkl_with_docs(
+    value: &Value,
+    docs: &BTreeMap<String, String>,
+    out: &mut dyn std::io::Write,
+) -> Result<(), CliError> {
+    write_pkl_with_env_policy(value, docs, EnvTokenPolicy::Preserve, out)
+}
+
+/// Like [`write_pkl_with_docs`], with `$NAME`/`${NAME}` tokens in string values handled
+/// as `env_policy` says (see [`crate::env_tokens`])
+pub fn write_pkl_with_env_policy(
+    value: &Value,
+    docs: &BTreeMap<String, String>,
+    env_policy: EnvTokenPolicy,
+    out: &mut dyn std::io::Write,
+) -> Result<(), CliError> {
+    let Value::Object(map) = value else {
+        return Err(CliError::Generic(
+            "Only objects can be rendered as a Pkl module".to_string(),
+        ));
+    };
+    crate::env_tokens::check(value, env_policy)?;
+    let style = PklStyle { docs, env_policy };
+    let root = ConfigPath::default();
+    let mut chunk = String::new();
+    if let Some(doc) = docs.get("") {
+        push_comment(&mut chunk, doc, "", "//");
+        chunk.push('\n');
+    }
+    for (key, child) in map {
+        render_pkl_member(&mut chunk, key, child, 0, false, &style, &root.child(key.clone()));
+        out.write_all(chunk.as_bytes()).map_err(|e| CliError::IoError {
+            context: "Writing rendered Pkl".to_string(),
+            source: e,
+        })?;
+        chunk.clear();
+    }
+    Ok(())
+}
+
+/// A parsed TOML value as a config value; dates and times become strings
+fn toml_to_value(value: toml::Value) -> Value {
+    match value {
+        toml::Value::String(s) => Value::String(s),
+        toml::Value::Integer(i) => Value::from(i),
+        toml::Value::Float(f) => Value::from(f),
+        toml::Value::Boolean(b) => Value::Bool(b),
+        toml::Value::Datetime(datetime) => Value::String(datetime.to_string()),
+        toml::Value::Array(items) => Value::Array(items.into_iter().map(toml_to_value).collect()),
+        toml::Value::Table(table) => Value::Object(table.into_iter().map(|(key, value)| (key, toml_to_value(value))).collect()),
+    }
+}
+
+/// A config value as TOML, which has no `null`: null members are left out, so nested
+/// objects become `[tables]` and lists of objects `[[arrays of tables]]`
+fn value_to_toml(value: &Value, path: &ConfigPath) -> Result<Option<toml::Value>, CliError> {
+    let converted = match value {
+        Value::Null => return Ok(None),
+        Value::Bool(b) => toml::Value::Boolean(*b),
+        Value::Number(n) => match (n.as_i64(), n.as_f64()) {
+            (Some(i), _) => toml::Value::Integer(i),
+            (None, Some(f)) => toml::Value::Float(f),
+            (None, None) => return Err(CliError::Generic(format!("{}: {} doesn't fit a TOML number", path, n))),
+        },
+        Value::String(s) => toml::Value::String(s.clone()),
+        Value::Array(items) => {
+            let mut array = Vec::with_capacity(items.len());
+            for (index, item) in items.iter().enumerate() {
+                let item_path = path.child(index.to_string());
+                let item = value_to_toml(item, &item_path)?
+                    .ok_or_else(|| CliError::Generic(format!("{}: TOML arrays can't contain null", item_path)))?;
+                array.push(item);
+            }
+            toml::Value::Array(array)
+        }
+        Value::Object(map) => {
+            let mut table = toml::Table::new();
+            for (key, child) in map {
+                let child_path = path.child(key.clone());
+                if let Some(child) = value_to_toml(child, &child_path)? {
+                    table.insert(key.clone(), child);
+                }
+            }
+            toml::Value::Table(table)
+        }
+    };
+    Ok(Some(converted))
+}
+
+/// Stream a config value to stdout, followed by a newline
+pub fn print_config_value(value: &Value, format: &SchemaFormat) -> Result<(), CliError> {
+    use std::io::Write;
+
+    let mut stdout = std::io::stdout().lock();
+    write_config_value(value, format, &mut stdout)?;
+    writeln!(stdout).map_err(|e| CliError::IoError {
+        context: "Writing to stdout".to_string(),
+        source: e,
+    })
+}
+
+/// Comments and string handling for rendered Pkl
+struct PklStyle<'a> {
+    docs: &'a BTreeMap<String, String>,
+    env_policy: EnvTokenPolicy,
+}
+
+/// Render one object member as a Pkl property (`key = value`) or entry (`["key"] = value`)
+fn render_pkl_member(
+    out: &mut String,
+    key: &str,
+    value: &Value,
+    depth: usize,
+    as_entry: bool,
+    style: &PklStyle<'_>,
+    path: &ConfigPath,
+) {
+    let indent = "  ".repeat(depth);
+    let name = if as_entry {
+        format!("[{}]", pkl_string(key))
+    } else {
+        pkl_identifier(key)
+    };
+    if !style.docs.is_empty()
+        && let Some(doc) = style.docs.get(&path.to_string())
+    {
+        push_comment(out, doc, &indent, if as_entry { "//" } else { "///" });
+    }
+
+    match value {
+        Value::Object(map) => {
+            out.push_str(&format!("{}{} {{\n", indent, name));
+            let entries = PKL_MAPPING_KEYS.contains(&key) && !as_entry;
+            for (child_key, child) in map {
+                render_pkl_member(out, child_key, child, depth + 1, entries, style, &path.child(child_key.clone()));
+            }
+            out.push_str(&format!("{}}}\n", indent));
+        }
+        Value::Array(items) => {
+            out.push_str(&format!("{}{} {{\n", indent, name));
+            for (index, item) in items.iter().enumerate() {
+                render_pkl_element(out, item, depth + 1, style, &path.child(index.to_string()));
+            }
+            out.push_str(&format!("{}}}\n", indent));
+        }
+        scalar => out.push_str(&format!("{}{} = {}\n", indent, name, pkl_scalar(scalar, style.env_policy))),
+    }
+}
+
+/// Render a listing element
+fn render_pkl_element(out: &mut String, value: &Value, depth: usize, style: &PklStyle<'_>, path: &ConfigPath) {
+    let indent = "  ".repeat(depth);
+    if !style.docs.is_empty()
+        && let Some(doc) = style.docs.get(&path.to_string())
+    {
+        push_comment(out, doc, &indent, "//");
+    }
+    match value {
+        Value::Object(map) => {
+            out.push_str(&format!("{}new {{\n", indent));
+            for (key, child) in map {
+                render_pkl_member(out, key, child, depth + 1, false, style, &path.child(key.clone()));
+            }
+            out.push_str(&format!("{}}}\n", indent));
+        }
+        Value::Array(items) => {
+            out.push_str(&format!("{}new Listing {{\n", indent));
+            for (index, item) in items.iter().enumerate() {
+                render_pkl_element(out, item, depth + 1, style, &path.child(index.to_string()));
+            }
+            out.push_str(&format!("{}}}\n", indent));
+        }
+        scalar => out.push_str(&format!("{}{}\n", indent, pkl_scalar(scalar, style.env_policy))),
+    }
+}
+
+/// Push `text` as comment lines starting with `marker`
+fn push_comment(out: &mut String, text: &str, indent: &str, marker: &str) {
+    for line in text.lines() {
+        if line.is_empty() {
+            out.push_str(&format!("{}{}\n", indent, marker));
+        } else {
+            out.push_str(&format!("{}{} {}\n", indent, marker, line));
+        }
+    }
+}
+
+fn pkl_scalar(value: &Value, env_policy: EnvTokenPolicy) -> String {
+    match value {
+        Value::Null => "null".to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) => n.to_string(),
+        Value::String(s) => crate::env_tokens::pkl_value(s, env_policy),
+        // Containers are handled by the callers
+        Value::Array(_) | Value::Object(_) => String::new(),
+    }
+}
+
+/// Quote a string as a Pkl string literal
+///
+/// Escaping every backslash also neutralizes Pkl's `\(...)` interpolation syntax.
+pub fn pkl_string(value: &str) -> String {
+    format!("\"{}\"", pkl_escape(value))
+}
+
+/// Escape text for the inside of a Pkl string literal
+///
+/// Control characters without a short escape are written as `\u{..}`.
+pub fn pkl_escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{{{:x}}}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Pkl keywords and reserved words, which can only be property names when backtick-quoted
+const PKL_KEYWORDS: [&str; 42] = [
+    "abstract", "amends", "as", "case", "class", "const", "delete", "else", "extends", "external", "false",
+    "fixed", "for", "function", "hidden", "if", "import", "in", "is", "let", "local", "module", "new",
+    "nothing", "null", "open", "out", "outer", "override", "protected", "read", "record", "super", "switch",
+    "this", "throw", "trace", "true", "typealias", "unknown", "vararg", "when",
+];
+
+/// A key as a Pkl property name, backtick-quoted unless it's a plain identifier that
+/// isn't a keyword
+pub fn pkl_identifier(key: &str) -> String {
+    let plain = key
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+        && !PKL_KEYWORDS.contains(&key);
+    if plain {
+        key.to_string()
+    } else {
+        format!("`{}`", key)
+    }
+}
+
+/// Evaluate a Pkl file to JSON with the Pkl CLI and parse the result
+async fn evaluate_pkl_to_value(path: &Path) -> Result<Value, CliError> {
+    let pkl_cli = crate::pkl_tooling::find_pkl_executable()
+        .await
+        .map_err(|e| CliError::Generic(e.to_string()))?;
+    match pkl_cli {
+        Some(pkl_cli) => evaluate_pkl_with(&pkl_cli, path).await,
+        None => read_pkl_without_cli(path),
+    }
+}
+
+/// Without a Pkl CLI, literal-only configs are read by the native parser
+#[cfg(feature = "native_pkl")]
+fn read_pkl_without_cli(path: &Path) -> Result<Value, CliError> {
+    tracing::info!("Pkl CLI not found; reading {} with the native Pkl parser", path.display());
+    crate::native_pkl::load(path)
+}
+
+#[cfg(not(feature = "native_pkl"))]
+fn read_pkl_without_cli(_path: &Path) -> Result<Value, CliError> {
+    Err(CliError::PklInstallFailed {
+        reason: "Pkl CLI not found".to_string(),
+        help: Some("Install Pkl CLI with: spklr pkl-me pkl".to_string()),
+    })
+}
+
+/// Evaluate a Pkl file with an already resolved Pkl CLI
+///
+/// Long-running callers resolve the CLI once and reuse it instead of searching on every call.
+/// At most [`crate::concurrency::jobs`] evaluations run at once. Results of unchanged
+/// modules come from the evaluation cache (see [`crate::eval_cache`]).
+pub async fn evaluate_pkl_with(pkl_cli: &crate::pkl_tooling::PklCli, path: &Path) -> Result<Value, CliError> {
+    let output = crate::eval_cache::cached(pkl_cli, path, || async {
+        let _permit = crate::concurrency::evaluation_permit().await;
+        let mut command = crate::eval_cache::EVAL_ARGS.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+        command.push(path.to_string_lossy().to_string());
+        crate::pkl_tooling::execute_pkl_command(pkl_cli, &command)
+            .await
+            .map_err(|e| CliError::Generic(e.to_string()))
+    })
+    .await?;
+    parse_config_str(&output, &SchemaFormat::Json)
+}
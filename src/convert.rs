@@ -0,0 +1,456 @@
+//! Convert Module for Space Pklr
+//!
+//! Library API for converting Moon configs between formats, for build scripts and
+//! tools that embed conversion instead of shelling out to `spklr convert`.
+//!
+//! ```no_run
+//! use space_pklr::convert::ConfigConverter;
+//! use space_pklr::types::{LoadedConfig, SchemaFormat};
+//!
+//! # fn example(config: LoadedConfig) -> Result<(), space_pklr::types::CliError> {
+//! let pkl = ConfigConverter::new(config, SchemaFormat::Yaml, SchemaFormat::Pkl).convert()?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! With [`ConfigConverter::verify_roundtrip`], the output is converted back to the
+//! source format and compared semantically with the input, so a lossy conversion
+//! fails instead of silently dropping or changing values. Pkl round trips evaluate
+//! the output with the Pkl CLI and need [`ConfigConverter::convert_async`].
+//!
+//! Moon's `$NAME` and `${NAME}` environment variable tokens are kept as written in Pkl
+//! output; [`ConfigConverter::env_policy`] can turn them into `read("env:NAME")` or
+//! reject them instead (see [`crate::env_tokens`]).
+//!
+//! [`convert_str`] converts config text directly, returning the output with any
+//! validation problems or lossy values as diagnostics instead of printing them:
+//!
+//! ```no_run
+//! use space_pklr::convert::convert_str;
+//! use space_pklr::types::{MoonConfig, SchemaFormat};
+//!
+//! # fn example() -> Result<(), space_pklr::types::CliError> {
+//! let converted = convert_str("language: rust\n", SchemaFormat::Yaml, SchemaFormat::Pkl, MoonConfig::Project)?;
+//! for diagnostic in &converted.diagnostics {
+//!     eprintln!("{}", diagnostic);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`schema_header`] builds the `amends`/`extends` line that points a converted Pkl
+//! config at its generated schema module, relative to where the config is written.
+
+use serde_json::{Value, json};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Component, Path, PathBuf};
+
+use crate::config_processor::{parse_config_str, render_config_value, write_config_value, write_pkl_with_env_policy};
+use crate::env_tokens::EnvTokenPolicy;
+use crate::policy::Severity;
+use crate::templates::{TemplateConfig, TemplateContext};
+use crate::types::{CliError, ConfigHeader, LoadedConfig, MoonConfig, SchemaFormat};
+use crate::types::moon::UnknownConfig;
+
+/// Converts a loaded config from its source format to a target format
+#[derive(Debug, Clone)]
+pub struct ConfigConverter {
+    config: LoadedConfig,
+    from: SchemaFormat,
+    to: SchemaFormat,
+    verify_roundtrip: bool,
+    env_policy: EnvTokenPolicy,
+    /// Generated module the config amends, before renaming, with the casing settings
+    property_case: Option<(crate::pkl_schema::PklModule, crate::pkl_schema::GeneratorConfig)>,
+}
+
+impl ConfigConverter {
+    pub fn new(config: LoadedConfig, from: SchemaFormat, to: SchemaFormat) -> Self {
+        Self {
+            config,
+            from,
+            to,
+            verify_roundtrip: false,
+            env_policy: EnvTokenPolicy::Preserve,
+            property_case: None,
+        }
+    }
+
+    /// Convert the output back to the source format and fail if anything was lost or changed
+    pub fn verify_roundtrip(mut self, verify: bool) -> Self {
+        self.verify_roundtrip = verify;
+        self
+    }
+
+    /// How Pkl output handles `$NAME`/`${NAME}` tokens in string values (see
+    /// [`crate::env_tokens`]); they're kept as written by default
+    pub fn env_policy(mut self, policy: EnvTokenPolicy) -> Self {
+        self.env_policy = policy;
+        self
+    }
+
+    /// Rename the config's properties as `config`'s casing renames them in the module
+    /// generated from `schema` (see [`crate::pkl_schema::casing`])
+    ///
+    /// `schema` is the module for the config's type before renaming.
+    pub fn property_case(mut self, schema: crate::pkl_schema::PklModule, config: crate::pkl_schema::GeneratorConfig) -> Self {
+        self.property_case = Some((schema, config));
+        self
+    }
+
+    /// Parse YAML, JSON, JSONC, or TOML `content` as a `config_type` config and convert it
+    ///
+    /// The content is validated against the Moon config type but converted as
+    /// written, so defaults aren't filled in. JSONC comments become Pkl comments.
+    pub fn from_content(
+        content: &str,
+        config_type: MoonConfig,
+        from: SchemaFormat,
+        to: SchemaFormat,
+    ) -> Result<Self, CliError> {
+        let value = if content.trim().is_empty() {
+            Value::Object(serde_json::Map::new())
+        } else {
+            parse_config_str(content, &from)?
+        };
+        crate::config_processor::validate_config_value(&value, config_type)?;
+
+        let mut config = UnknownConfig::new(value);
+        config.original_format = Some(from.clone());
+        config.type_hint = Some(config_type.to_string());
+        if from == SchemaFormat::Jsonc {
+            config.comments = crate::jsonc::comments(content);
+        }
+        Ok(Self::new(LoadedConfig::Unknown(config), from, to))
+    }
+
+    pub fn config(&self) -> &LoadedConfig {
+        &self.config
+    }
+
+    pub fn source_format(&self) -> &SchemaFormat {
+        &self.from
+    }
+
+    pub fn target_format(&self) -> &SchemaFormat {
+        &self.to
+    }
+
+    /// The config as an untyped value
+    pub fn to_value(&self) -> Result<Value, CliError> {
+        self.config.to_value()
+    }
+
+    /// Render the config in the target format
+    pub fn convert(&self) -> Result<String, CliError> {
+        let value = self.output_value()?;
+        let output = self.render(&value)?;
+        if self.verify_roundtrip {
+            let back = render_config_value(&parse_config_str(&output, &self.to)?, &self.from)?;
+            check_roundtrip(&value, &parse_config_str(&back, &self.from)?)?;
+        }
+        Ok(output)
+    }
+
+    /// Like [`convert`](Self::convert), but evaluates Pkl with the Pkl CLI for round trips
+    pub async fn convert_async(&self) -> Result<String, CliError> {
+        let value = self.output_value()?;
+        let output = self.render(&value)?;
+        if self.verify_roundtrip {
+            verify_roundtrip(&value, &output, &self.from, &self.to).await?;
+        }
+        Ok(output)
+    }
+
+    /// Stream the config in the target format into `out`, without a round trip check
+    pub fn write_to(&self, out: &mut dyn std::io::Write) -> Result<(), CliError> {
+        let value = self.output_value()?;
+        write_with_comments(&value, &self.to, self.comments(), self.env_policy, out)
+    }
+
+    /// The config as it's rendered, with any property renaming applied
+    fn output_value(&self) -> Result<Value, CliError> {
+        let value = self.to_value()?;
+        Ok(match &self.property_case {
+            Some((schema, config)) => crate::pkl_schema::casing::recase_value(&value, schema, config),
+            None => value,
+        })
+    }
+
+    fn render(&self, value: &Value) -> Result<String, CliError> {
+        render_with_comments(value, &self.to, self.comments(), self.env_policy)
+    }
+
+    /// Comments kept from JSONC input, by property path
+    fn comments(&self) -> &BTreeMap<String, String> {
+        static NO_COMMENTS: BTreeMap<String, String> = BTreeMap::new();
+        self.config.as_unknown().map_or(&NO_COMMENTS, |config| &config.comments)
+    }
+}
+
+/// Result of [`convert_str`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConvertOutput {
+    /// The converted config
+    pub output: String,
+    pub diagnostics: Vec<ConvertDiagnostic>,
+}
+
+impl ConvertOutput {
+    /// Whether any diagnostic is an error, e.g. the input isn't a valid config of its type
+    pub fn has_errors(&self) -> bool {
+        self.diagnostics.iter().any(|diagnostic| diagnostic.severity == Severity::Error)
+    }
+}
+
+/// A problem found while converting that didn't stop the conversion
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConvertDiagnostic {
+    pub severity: Severity,
+    /// Property path the diagnostic is about, if it's about one
+    pub path: Option<String>,
+    pub message: String,
+}
+
+impl std::fmt::Display for ConvertDiagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.path {
+            Some(path) => write!(f, "{}: {}: {}", self.severity, path, self.message),
+            None => write!(f, "{}: {}", self.severity, self.message),
+        }
+    }
+}
+
+/// Convert `kind` config text from one format to another
+///
+/// Only YAML, JSON, JSONC, and TOML input can be parsed without the Pkl CLI; comments in
+/// JSONC input are kept as comments in Pkl output. Input that doesn't
+/// validate as `kind` is still converted, with an error diagnostic. Values that don't
+/// survive a round trip through the target format are reported as warnings; Pkl
+/// output isn't round-tripped, since that needs the Pkl CLI.
+pub fn convert_str(input: &str, from: SchemaFormat, to: SchemaFormat, kind: MoonConfig) -> Result<ConvertOutput, CliError> {
+    if kind == MoonConfig::All {
+        return Err(CliError::Generic(
+            "Cannot convert as 'all' - choose a single configuration type".to_string(),
+        ));
+    }
+    let value = if input.trim().is_empty() {
+        Value::Object(serde_json::Map::new())
+    } else {
+        parse_config_str(input, &from)?
+    };
+
+    let mut diagnostics = Vec::new();
+    if let Err(error) = crate::config_processor::validate_config_value(&value, kind) {
+        let message = match std::error::Error::source(&error) {
+            Some(source) => source.to_string(),
+            None => error.to_string(),
+        };
+        diagnostics.push(ConvertDiagnostic {
+            severity: Severity::Error,
+            path: None,
+            message,
+        });
+    }
+
+    let comments = if from == SchemaFormat::Jsonc {
+        crate::jsonc::comments(input)
+    } else {
+        BTreeMap::new()
+    };
+    let output = render_with_comments(&value, &to, &comments, EnvTokenPolicy::Preserve)?;
+    if matches!(to, SchemaFormat::Yaml | SchemaFormat::Json | SchemaFormat::Jsonc | SchemaFormat::Toml) {
+        let roundtripped = parse_config_str(&output, &to)?;
+        for difference in semantic_diff(&value, &roundtripped) {
+            let (path, message) = difference.split_once(": ").unwrap_or(("", &difference));
+            diagnostics.push(ConvertDiagnostic {
+                severity: Severity::Warning,
+                path: (!path.is_empty() && path != "<root>").then(|| path.to_string()),
+                message: message.to_string(),
+            });
+        }
+    }
+
+    Ok(ConvertOutput { output, diagnostics })
+}
+
+/// Render `value` as `to`; in Pkl output, `comments` go above the members they describe
+/// and env tokens are handled as `env_policy` says
+fn render_with_comments(
+    value: &Value,
+    to: &SchemaFormat,
+    comments: &BTreeMap<String, String>,
+    env_policy: EnvTokenPolicy,
+) -> Result<String, CliError> {
+    let mut out = Vec::new();
+    write_with_comments(value, to, comments, env_policy, &mut out)?;
+    String::from_utf8(out).map_err(|e| CliError::Generic(format!("Rendered config is not UTF-8: {}", e)))
+}
+
+/// [`render_with_comments`], streamed into `out`
+fn write_with_comments(
+    value: &Value,
+    to: &SchemaFormat,
+    comments: &BTreeMap<String, String>,
+    env_policy: EnvTokenPolicy,
+    out: &mut dyn std::io::Write,
+) -> Result<(), CliError> {
+    if *to != SchemaFormat::Pkl {
+        return write_config_value(value, to, out);
+    }
+    write_pkl_with_env_policy(value, comments, env_policy, out)
+}
+
+/// `amends`/`extends` line pointing a Pkl config written to `output_dir` at the
+/// `config_type` schema module in `schema_dir`
+///
+/// Returns `None` for [`ConfigHeader::None`]. The line comes from `config_header` in
+/// `templates` when that's set.
+pub fn schema_header(
+    header: &ConfigHeader,
+    config_type: MoonConfig,
+    schema_dir: &Path,
+    output_dir: &Path,
+    templates: &TemplateConfig,
+) -> Result<Option<String>, CliError> {
+    let Some(keyword) = header.keyword() else {
+        return Ok(None);
+    };
+    if config_type == MoonConfig::All {
+        return Err(CliError::Generic(
+            "A schema header needs a single config type, not 'all'".to_string(),
+        ));
+    }
+    let module = crate::templates::module::module_name(config_type);
+    let absolute = |path: &Path| {
+        std::path::absolute(path).map_err(|e| CliError::IoError {
+            context: format!("Resolving {}", path.display()),
+            source: e,
+        })
+    };
+    let schema = absolute(&schema_dir.join(format!("{}.pkl", module)))?;
+    let path = relative_path(&absolute(output_dir)?, &schema)
+        .to_string_lossy()
+        .replace('\\', "/");
+
+    let line = match &templates.config_header {
+        Some(template) => {
+            let context = TemplateContext::current().with_value(
+                "schema",
+                json!({ "keyword": keyword, "path": path, "module": module }),
+            );
+            let engine = crate::templates::engine::engine(templates.engine);
+            crate::templates::render_with(engine.as_ref(), "config_header", template, &context, templates.strict)?
+                .trim_end()
+                .to_string()
+        }
+        None => format!("{} {}", keyword, crate::config_processor::pkl_string(&path)),
+    };
+    Ok(Some(line))
+}
+
+/// `to` relative to the directory `from`; both should be absolute
+pub fn relative_path(from: &Path, to: &Path) -> PathBuf {
+    fn normal(path: &Path) -> Vec<Component<'_>> {
+        path.components().fold(Vec::new(), |mut parts, component| {
+            match component {
+                Component::CurDir => {}
+                Component::ParentDir if matches!(parts.last(), Some(Component::Normal(_))) => {
+                    parts.pop();
+                }
+                other => parts.push(other),
+            }
+            parts
+        })
+    }
+    let (from, to) = (normal(from), normal(to));
+    let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+    let mut relative: PathBuf = from[common..].iter().map(|_| Component::ParentDir).collect();
+    relative.extend(&to[common..]);
+    relative
+}
+
+/// Convert `output` (in `to`) back to `from` and compare it with `original`
+pub async fn verify_roundtrip(
+    original: &Value,
+    output: &str,
+    from: &SchemaFormat,
+    to: &SchemaFormat,
+) -> Result<(), CliError> {
+    let back = render_config_value(&parse_output(output, to).await?, from)?;
+    check_roundtrip(original, &parse_output(&back, from).await?)
+}
+
+/// Parse rendered output, evaluating Pkl through a temporary module
+async fn parse_output(output: &str, format: &SchemaFormat) -> Result<Value, CliError> {
+    if *format != SchemaFormat::Pkl {
+        return parse_config_str(output, format);
+    }
+    let module = tempfile::Builder::new()
+        .prefix("spklr-roundtrip-")
+        .suffix(".pkl")
+        .tempfile()
+        .map_err(|e| CliError::IoError {
+            context: "Creating round-trip Pkl module".to_string(),
+            source: e,
+        })?;
+    std::fs::write(module.path(), output).map_err(|e| CliError::IoError {
+        context: format!("Writing {}", module.path().display()),
+        source: e,
+    })?;
+    crate::config_processor::load_config_value(module.path(), Some(SchemaFormat::Pkl)).await
+}
+
+fn check_roundtrip(original: &Value, roundtripped: &Value) -> Result<(), CliError> {
+    let differences = semantic_diff(original, roundtripped);
+    if differences.is_empty() {
+        return Ok(());
+    }
+    Err(CliError::RoundTripMismatch {
+        count: differences.len(),
+        differences: differences.join("\n"),
+    })
+}
+
+/// Differences between two config values, as `path: description` lines
+///
+/// Numbers compare by value (`1` equals `1.0`), and a missing key equals `null`.
+pub fn semantic_diff(before: &Value, after: &Value) -> Vec<String> {
+    let mut differences = Vec::new();
+    diff_into(before, after, String::new(), &mut differences);
+    differences
+}
+
+fn diff_into(before: &Value, after: &Value, path: String, differences: &mut Vec<String>) {
+    let child = |key: &str| if path.is_empty() { key.to_string() } else { format!("{}.{}", path, key) };
+    let shown = || if path.is_empty() { "<root>".to_string() } else { path.clone() };
+
+    match (before, after) {
+        (Value::Object(before), Value::Object(after)) => {
+            let keys: BTreeSet<&String> = before.keys().chain(after.keys()).collect();
+            for key in keys {
+                match (before.get(key), after.get(key)) {
+                    (Some(b), Some(a)) => diff_into(b, a, child(key), differences),
+                    (Some(Value::Null), None) | (None, Some(Value::Null)) => {}
+                    (Some(b), None) => differences.push(format!("{}: lost (was {})", child(key), b)),
+                    (None, Some(a)) => differences.push(format!("{}: added ({})", child(key), a)),
+                    (None, None) => {}
+                }
+            }
+        }
+        (Value::Array(before), Value::Array(after)) if before.len() == after.len() => {
+            for (index, (b, a)) in before.iter().zip(after).enumerate() {
+                diff_into(b, a, child(&index.to_string()), differences);
+            }
+        }
+        (Value::Array(before), Value::Array(after)) => differences.push(format!(
+            "{}: {} item(s) became {}",
+            shown(),
+            before.len(),
+            after.len()
+        )),
+        (Value::Number(b), Value::Number(a)) if b.as_f64() == a.as_f64() => {}
+        (b, a) if b == a => {}
+        (b, a) => differences.push(format!("{}: {} became {}", shown(), b, a)),
+    }
+}
@@ -0,0 +1,258 @@
+//! Corpus Module for Space Pklr
+//!
+//! Real-world Moon repositories as a regression corpus. `spklr corpus run` clones each
+//! repository listed in a manifest, converts every Moon config it finds to Pkl,
+//! validating each one as its config type on the way (see
+//! [`convert_str`](crate::convert::convert_str)), and reports pass/fail counts per
+//! repository and overall. Run it before a release to catch regressions; the most common
+//! errors it lists show which converter fixes would help the most configs.
+//!
+//! ```toml
+//! [[repos]]
+//! url = "https://github.com/moonrepo/moon"
+//! rev = "v1.30.0"
+//!
+//! [[repos]]
+//! name = "examples"
+//! url = "https://github.com/moonrepo/examples"
+//! ```
+//!
+//! Repositories are fetched with `git` at depth 1: the `rev` given (a branch, tag, or
+//! commit), or the default branch. Configs are found as `spklr init` finds them (see
+//! [`crate::init::yaml_configs`]).
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::convert::convert_str;
+use crate::policy::Severity;
+use crate::types::{CliError, SchemaFormat};
+
+/// Errors listed in the summary, most common first
+const TOP_ERRORS: usize = 10;
+
+/// `corpus.toml`: the repositories to check
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct CorpusManifest {
+    #[serde(default)]
+    pub repos: Vec<CorpusRepo>,
+}
+
+impl CorpusManifest {
+    pub fn load(path: &Path) -> Result<Self, CliError> {
+        let content = std::fs::read_to_string(path).map_err(|e| CliError::IoError {
+            context: format!("Reading corpus manifest: {}", path.display()),
+            source: e,
+        })?;
+        Self::from_toml(&content).map_err(|e| CliError::Generic(format!("Invalid corpus manifest {}: {}", path.display(), e)))
+    }
+
+    pub fn from_toml(content: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(content)
+    }
+}
+
+/// A repository in the corpus
+#[derive(Debug, Clone, Deserialize)]
+pub struct CorpusRepo {
+    /// Name in reports; defaults to the last segment of the URL
+    pub name: Option<String>,
+    /// Git URL to clone
+    pub url: String,
+    /// Branch, tag, or commit to check; defaults to the default branch
+    pub rev: Option<String>,
+}
+
+impl CorpusRepo {
+    pub fn name(&self) -> String {
+        self.name.clone().unwrap_or_else(|| {
+            let last = self.url.trim_end_matches('/').rsplit(['/', ':']).next().unwrap_or(&self.url);
+            last.trim_end_matches(".git").to_string()
+        })
+    }
+
+    /// Fetch the repository's `rev` into `dest`, which must not exist yet
+    pub fn clone_into(&self, dest: &Path) -> Result<(), CliError> {
+        std::fs::create_dir_all(dest).map_err(|e| CliError::IoError {
+            context: format!("Creating {}", dest.display()),
+            source: e,
+        })?;
+        let rev = self.rev.as_deref().unwrap_or("HEAD");
+        git(dest, &["init", "--quiet"])?;
+        git(dest, &["remote", "add", "origin", &self.url])?;
+        git(dest, &["fetch", "--quiet", "--depth", "1", "origin", rev])?;
+        git(dest, &["checkout", "--quiet", "FETCH_HEAD"])
+    }
+}
+
+fn git(dir: &Path, args: &[&str]) -> Result<(), CliError> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .map_err(|e| CliError::IoError {
+            context: "Running git (is it installed?)".to_string(),
+            source: e,
+        })?;
+    if output.status.success() {
+        return Ok(());
+    }
+    Err(CliError::Generic(format!(
+        "git {} failed: {}",
+        args.join(" "),
+        String::from_utf8_lossy(&output.stderr).trim()
+    )))
+}
+
+/// Result of converting one config
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ConfigOutcome {
+    /// Path relative to the repository root
+    pub path: PathBuf,
+    pub config_type: String,
+    /// Parse, validation, and conversion errors
+    pub errors: Vec<String>,
+    /// Values that didn't survive conversion unchanged
+    pub warnings: usize,
+}
+
+impl ConfigOutcome {
+    pub fn passed(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+/// Results for one repository
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RepoReport {
+    pub name: String,
+    pub url: String,
+    /// Why the repository couldn't be checked, e.g. a failed clone
+    pub error: Option<String>,
+    pub configs: Vec<ConfigOutcome>,
+}
+
+impl RepoReport {
+    pub fn passed(&self) -> usize {
+        self.configs.iter().filter(|config| config.passed()).count()
+    }
+
+    pub fn failed(&self) -> usize {
+        self.configs.len() - self.passed()
+    }
+}
+
+/// Totals across the corpus
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct CorpusSummary {
+    pub repos: usize,
+    /// Repositories that couldn't be cloned or read
+    pub unavailable: usize,
+    pub configs: usize,
+    pub passed: usize,
+    pub failed: usize,
+    pub warnings: usize,
+    /// The most common errors and how many configs had each
+    pub top_errors: Vec<(String, usize)>,
+}
+
+impl CorpusSummary {
+    /// Percentage of configs that passed; 100 for an empty corpus
+    pub fn pass_rate(&self) -> f64 {
+        if self.configs == 0 {
+            return 100.0;
+        }
+        self.passed as f64 * 100.0 / self.configs as f64
+    }
+}
+
+/// Convert and validate every Moon config in a checkout
+pub fn check_checkout(root: &Path) -> Vec<ConfigOutcome> {
+    crate::init::yaml_configs(root)
+        .into_iter()
+        .map(|(config_type, path)| {
+            let relative = path.strip_prefix(root).unwrap_or(&path).to_path_buf();
+            let result = std::fs::read_to_string(&path)
+                .map_err(|e| format!("Failed to read: {}", e))
+                .and_then(|content| convert_str(&content, SchemaFormat::Yaml, SchemaFormat::Pkl, config_type).map_err(|e| e.to_string()));
+            let (errors, warnings) = match result {
+                Ok(converted) => {
+                    let errors = converted
+                        .diagnostics
+                        .iter()
+                        .filter(|diagnostic| diagnostic.severity == Severity::Error)
+                        .map(|diagnostic| diagnostic.message.clone())
+                        .collect();
+                    let warnings = converted.diagnostics.iter().filter(|diagnostic| diagnostic.severity == Severity::Warning).count();
+                    (errors, warnings)
+                }
+                Err(error) => (vec![error], 0),
+            };
+            ConfigOutcome {
+                path: relative,
+                config_type: config_type.to_string(),
+                errors,
+                warnings,
+            }
+        })
+        .collect()
+}
+
+/// Clone `repo` under `work_dir` and check its configs
+pub fn run_repo(repo: &CorpusRepo, work_dir: &Path) -> RepoReport {
+    let name = repo.name();
+    let dest = work_dir.join(&name);
+    let (error, configs) = match repo.clone_into(&dest) {
+        Ok(()) => (None, check_checkout(&dest)),
+        Err(error) => (Some(error.to_string()), Vec::new()),
+    };
+    RepoReport {
+        name,
+        url: repo.url.clone(),
+        error,
+        configs,
+    }
+}
+
+/// Aggregate the reports of every repository
+pub fn summarize(reports: &[RepoReport]) -> CorpusSummary {
+    let mut summary = CorpusSummary {
+        repos: reports.len(),
+        ..CorpusSummary::default()
+    };
+    let mut errors: BTreeMap<String, usize> = BTreeMap::new();
+    for report in reports {
+        if report.error.is_some() {
+            summary.unavailable += 1;
+        }
+        for config in &report.configs {
+            summary.configs += 1;
+            summary.warnings += config.warnings;
+            if config.passed() {
+                summary.passed += 1;
+            } else {
+                summary.failed += 1;
+            }
+            // Each distinct error counts once per config
+            let mut seen: Vec<String> = config.errors.iter().map(|error| error_kind(error)).collect();
+            seen.sort();
+            seen.dedup();
+            for error in seen {
+                *errors.entry(error).or_default() += 1;
+            }
+        }
+    }
+    let mut top_errors: Vec<(String, usize)> = errors.into_iter().collect();
+    top_errors.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    top_errors.truncate(TOP_ERRORS);
+    summary.top_errors = top_errors;
+    summary
+}
+
+/// The first line of an error, which names the problem without the details of one config
+fn error_kind(error: &str) -> String {
+    error.lines().next().unwrap_or_default().trim().to_string()
+}
@@ -0,0 +1,384 @@
+//! Daemon Module for Space Pklr
+//!
+//! `spklr serve` keeps a process warm so editors, the LSP, and build systems don't
+//! pay CLI cold-start latency on every call. Generated schemas are cached for the
+//! life of the process and the Pkl CLI is resolved once.
+//!
+//! The socket speaks newline-delimited [JSON-RPC 2.0](https://www.jsonrpc.org/specification):
+//! one request object per line in, one response object per line out.
+//!
+//! | Method | Params | Result |
+//! |---|---|---|
+//! | `generate` | `configType`, `format` (`json-schema`, `typescript`) | schema text |
+//! | `convert` | `content` or `path`, `from`?, `to` | converted text |
+//! | `validate` | `content` or `path`, `from`?, `configType` | `{ "valid": true }` |
+//! | `query` | `content` or `path`, `from`?, `selector` | `[{ "path", "value" }]` |
+//! | `ping` | | `"pong"` |
+//! | `shutdown` | | `null`, then the server exits |
+//!
+//! `from` defaults to the extension of `path`, or `yaml` for inline `content`.
+
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::sync::{OnceCell, RwLock};
+
+use crate::config_processor::{parse_config_str, render_config_value};
+use crate::pkl_tooling::{PklEvaluator, PklServer};
+use crate::types::{CliError, MoonConfig, SchemaFormat};
+
+/// JSON-RPC error codes
+const PARSE_ERROR: i64 = -32700;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+/// Server-defined range; the spklr diagnostic code is included in `data`
+const SPKLR_ERROR: i64 = -32000;
+
+/// How long to evaluate with `pkl eval` after `pkl server` fails to start, before trying it again
+const PKL_SERVER_RETRY: Duration = Duration::from_secs(30);
+
+/// Warm state shared across daemon requests
+#[derive(Default)]
+pub struct DaemonState {
+    /// Generated schemas keyed by `<configType>:<format>`
+    schema_cache: RwLock<HashMap<String, String>>,
+    /// Evaluates through one `pkl server` for the daemon's lifetime, once one has started
+    pkl: OnceCell<PklEvaluator>,
+    /// When starting `pkl server` last failed
+    pkl_failed_at: Mutex<Option<Instant>>,
+}
+
+/// A failed daemon call
+#[derive(Debug)]
+pub enum RpcError {
+    MethodNotFound(String),
+    InvalidParams(String),
+    Cli(CliError),
+}
+
+impl From<CliError> for RpcError {
+    fn from(err: CliError) -> Self {
+        RpcError::Cli(err)
+    }
+}
+
+impl RpcError {
+    fn to_json(&self) -> Value {
+        match self {
+            RpcError::MethodNotFound(method) => json!({
+                "code": METHOD_NOT_FOUND,
+                "message": format!("Method not found: {}", method),
+            }),
+            RpcError::InvalidParams(message) => json!({
+                "code": INVALID_PARAMS,
+                "message": message,
+            }),
+            RpcError::Cli(err) => {
+                use miette::Diagnostic;
+                json!({
+                    "code": SPKLR_ERROR,
+                    "message": err.to_string(),
+                    "data": {
+                        "diagnostic": err.code().map(|c| c.to_string()),
+                        "help": err.help().map(|h| h.to_string()),
+                    },
+                })
+            }
+        }
+    }
+}
+
+impl DaemonState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Dispatch one call by method name
+    pub async fn call(&self, method: &str, params: &Value) -> Result<Value, RpcError> {
+        match method {
+            "generate" => self.generate(params).await,
+            "convert" => self.convert(params).await,
+            "validate" => self.validate(params).await,
+            "query" => self.query(params).await,
+            "ping" => Ok(json!("pong")),
+            _ => Err(RpcError::MethodNotFound(method.to_string())),
+        }
+    }
+
+    async fn generate(&self, params: &Value) -> Result<Value, RpcError> {
+        let config_type = config_type_param(params)?;
+        let format = str_param(params, "format")?.to_string();
+        let key = format!("{}:{}", config_type, format);
+
+        if let Some(schema) = self.schema_cache.read().await.get(&key) {
+            tracing::debug!("Schema cache hit: {}", key);
+            return Ok(Value::String(schema.clone()));
+        }
+
+        let schema = crate::moon_schema::generate_schema(config_type, &format)?;
+        self.schema_cache.write().await.insert(key, schema.clone());
+        Ok(Value::String(schema))
+    }
+
+    async fn convert(&self, params: &Value) -> Result<Value, RpcError> {
+        let value = self.load_input(params).await?;
+        let to = SchemaFormat::from_str(str_param(params, "to")?)?;
+        Ok(Value::String(render_config_value(&value, &to)?))
+    }
+
+    async fn validate(&self, params: &Value) -> Result<Value, RpcError> {
+        let config_type = config_type_param(params)?;
+        let value = self.load_input(params).await?;
+        crate::config_processor::validate_config_value(&value, config_type)?;
+        Ok(json!({ "valid": true }))
+    }
+
+    async fn query(&self, params: &Value) -> Result<Value, RpcError> {
+        let selector = str_param(params, "selector")?;
+        let value = self.load_input(params).await?;
+        let matches: Vec<Value> = crate::policy::select_values(&value, selector)
+            .into_iter()
+            .map(|(path, value)| json!({ "path": path, "value": value }))
+            .collect();
+        Ok(Value::Array(matches))
+    }
+
+    /// Load the config a request refers to, from inline `content` or a `path`
+    async fn load_input(&self, params: &Value) -> Result<Value, RpcError> {
+        let from = params
+            .get("from")
+            .and_then(Value::as_str)
+            .map(SchemaFormat::from_str)
+            .transpose()?;
+
+        if let Some(content) = params.get("content").and_then(Value::as_str) {
+            let format = from.unwrap_or(SchemaFormat::Yaml);
+            if format == SchemaFormat::Pkl {
+                return self.evaluate_pkl_content(content).await;
+            }
+            if content.trim().is_empty() {
+                return Ok(json!({}));
+            }
+            return Ok(parse_config_str(content, &format)?);
+        }
+
+        let path = PathBuf::from(str_param(params, "path")?);
+        crate::types::ensure_file_exists(&path)?;
+        let format = match from {
+            Some(format) => format,
+            None => crate::config_processor::detect_format_from_path(&path)?,
+        };
+
+        if format == SchemaFormat::Pkl {
+            return Ok(self.evaluate_pkl(&path).await?);
+        }
+
+        Ok(crate::config_processor::load_config_value(&path, Some(format)).await?)
+    }
+
+    async fn evaluate_pkl_content(&self, content: &str) -> Result<Value, RpcError> {
+        let file = tempfile::Builder::new()
+            .suffix(".pkl")
+            .tempfile()
+            .map_err(|e| CliError::IoError {
+                context: "Creating temporary Pkl file".to_string(),
+                source: e,
+            })?;
+        std::fs::write(file.path(), content).map_err(|e| CliError::IoError {
+            context: format!("Writing temporary Pkl file: {}", file.path().display()),
+            source: e,
+        })?;
+        Ok(self.evaluate_pkl(file.path()).await?)
+    }
+
+    /// Evaluate a Pkl file through the daemon's `pkl server`, started on first use
+    ///
+    /// Only a running server is kept. A missing Pkl CLI is looked up again on the next
+    /// call, and a server that fails to start is retried after [`PKL_SERVER_RETRY`], with
+    /// `pkl eval` used in between.
+    async fn evaluate_pkl(&self, path: &Path) -> Result<Value, CliError> {
+        if let Some(evaluator) = self.pkl.get() {
+            return evaluator.evaluate(path).await;
+        }
+        let pkl_cli = crate::pkl_tooling::find_pkl_executable()
+            .await
+            .ok()
+            .flatten()
+            .ok_or_else(|| CliError::PklInstallFailed {
+                reason: "Pkl CLI not found".to_string(),
+                help: Some("Install Pkl CLI with: spklr pkl-me pkl".to_string()),
+            })?;
+
+        let retry = self
+            .pkl_failed_at
+            .lock()
+            .unwrap()
+            .is_none_or(|failed_at| failed_at.elapsed() >= PKL_SERVER_RETRY);
+        if retry {
+            let started = self
+                .pkl
+                .get_or_try_init(|| async {
+                    let server = PklServer::start(&pkl_cli).await?;
+                    Ok::<_, CliError>(PklEvaluator::Server {
+                        server,
+                        pkl_cli: pkl_cli.clone(),
+                    })
+                })
+                .await;
+            match started {
+                Ok(evaluator) => return evaluator.evaluate(path).await,
+                Err(error) => {
+                    tracing::debug!("pkl server unavailable, evaluating with pkl eval: {}", error);
+                    *self.pkl_failed_at.lock().unwrap() = Some(Instant::now());
+                }
+            }
+        }
+        PklEvaluator::Cli(pkl_cli).evaluate(path).await
+    }
+
+    /// Handle one JSON-RPC request line, returning the response line (if the request wasn't a notification)
+    /// and whether the client asked the server to shut down
+    pub async fn handle_line(&self, line: &str) -> (Option<String>, bool) {
+        let request: Value = match serde_json::from_str(line) {
+            Ok(request) => request,
+            Err(e) => {
+                let response = json!({
+                    "jsonrpc": "2.0",
+                    "id": null,
+                    "error": { "code": PARSE_ERROR, "message": e.to_string() },
+                });
+                return (Some(response.to_string()), false);
+            }
+        };
+
+        let id = request.get("id").cloned();
+        let method = request.get("method").and_then(Value::as_str).unwrap_or_default();
+        let params = request.get("params").cloned().unwrap_or(Value::Null);
+
+        let shutdown = method == "shutdown";
+        let result = if shutdown {
+            Ok(Value::Null)
+        } else {
+            self.call(method, &params).await
+        };
+
+        // Requests without an id are notifications and get no response
+        let Some(id) = id else {
+            return (None, shutdown);
+        };
+
+        let response = match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err(err) => {
+                tracing::debug!("Request {} failed: {:?}", method, err);
+                json!({ "jsonrpc": "2.0", "id": id, "error": err.to_json() })
+            }
+        };
+        (Some(response.to_string()), shutdown)
+    }
+}
+
+fn str_param<'a>(params: &'a Value, name: &str) -> Result<&'a str, RpcError> {
+    params
+        .get(name)
+        .and_then(Value::as_str)
+        .ok_or_else(|| RpcError::InvalidParams(format!("Missing string parameter `{}`", name)))
+}
+
+fn config_type_param(params: &Value) -> Result<MoonConfig, RpcError> {
+    Ok(MoonConfig::from_str(str_param(params, "configType")?)?)
+}
+
+/// Serve JSON-RPC on a Unix domain socket until a `shutdown` request arrives
+#[cfg(unix)]
+pub async fn serve_socket(socket: &Path) -> Result<(), CliError> {
+    use std::os::unix::fs::FileTypeExt;
+    use std::sync::Arc;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::UnixListener;
+
+    // A stale socket from a previous run would make bind fail; anything else at the path is
+    // the user's, so it's left alone
+    match std::fs::symlink_metadata(socket) {
+        Ok(metadata) if metadata.file_type().is_socket() => {
+            std::fs::remove_file(socket).map_err(|e| CliError::IoError {
+                context: format!("Removing stale socket: {}", socket.display()),
+                source: e,
+            })?;
+        }
+        Ok(_) => {
+            return Err(CliError::Generic(format!(
+                "{} already exists and is not a socket; choose another --socket path",
+                socket.display()
+            )));
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => {
+            return Err(CliError::IoError {
+                context: format!("Checking socket path: {}", socket.display()),
+                source: e,
+            });
+        }
+    }
+
+    let listener = UnixListener::bind(socket).map_err(|e| CliError::IoError {
+        context: format!("Binding socket: {}", socket.display()),
+        source: e,
+    })?;
+    tracing::info!("Listening on {}", socket.display());
+
+    let state = Arc::new(DaemonState::new());
+    let (shutdown_tx, mut shutdown_rx) = tokio::sync::watch::channel(false);
+
+    loop {
+        let stream = tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok((stream, _)) => stream,
+                Err(e) => {
+                    tracing::warn!("Failed to accept connection: {}", e);
+                    continue;
+                }
+            },
+            _ = shutdown_rx.changed() => break,
+        };
+
+        let state = Arc::clone(&state);
+        let shutdown_tx = shutdown_tx.clone();
+        tokio::spawn(async move {
+            let (reader, mut writer) = stream.into_split();
+            let mut lines = BufReader::new(reader).lines();
+
+            while let Ok(Some(line)) = lines.next_line().await {
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let (response, shutdown) = state.handle_line(&line).await;
+                if let Some(response) = response {
+                    let written = writer.write_all(response.as_bytes()).await;
+                    if written.is_err() || writer.write_all(b"\n").await.is_err() {
+                        break;
+                    }
+                }
+                if shutdown {
+                    let _ = shutdown_tx.send(true);
+                    break;
+                }
+            }
+        });
+    }
+
+    let _ = std::fs::remove_file(socket);
+    tracing::info!("Daemon shut down");
+    Ok(())
+}
+
+/// Serve JSON-RPC on a Unix domain socket until a `shutdown` request arrives
+#[cfg(not(unix))]
+pub async fn serve_socket(_socket: &Path) -> Result<(), CliError> {
+    Err(CliError::Generic(
+        "`spklr serve --socket` requires Unix domain sockets, which this platform doesn't support".to_string(),
+    ))
+}
@@ -0,0 +1,174 @@
+//! Deprecations Module for Space Pklr
+//!
+//! Tracks deprecated properties across schema releases, so they're removed on a
+//! schedule instead of whenever someone notices them. [`collect`] finds the properties a
+//! JSON Schema document marks `"deprecated": true`, and [`plan`] compares them with the
+//! previous published schema:
+//!
+//! - deprecated then and now: removable in the next major release
+//! - deprecated for the first time: removable in the major release after that
+//! - deprecated then and gone now: already removed
+//! - deprecated then but not now: no longer deprecated
+//!
+//! Properties are named by type and path, like `TaskConfig.options.cache`; properties
+//! of the root object have no type prefix.
+
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt::Display;
+
+/// A deprecated property
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Deprecation {
+    /// `Type.property`, or just `property` on the root object
+    pub path: String,
+    /// Deprecation message, if the schema gives one
+    pub message: Option<String>,
+}
+
+/// Deprecated properties of a JSON Schema document, sorted by path
+pub fn collect(schema: &Value) -> Vec<Deprecation> {
+    let mut found = BTreeMap::new();
+    collect_object(schema, "", &mut found);
+    for key in ["definitions", "$defs"] {
+        if let Some(Value::Object(definitions)) = schema.get(key) {
+            for (name, definition) in definitions {
+                collect_object(definition, name, &mut found);
+            }
+        }
+    }
+    found.into_values().collect()
+}
+
+fn collect_object(schema: &Value, path: &str, found: &mut BTreeMap<String, Deprecation>) {
+    let Some(Value::Object(properties)) = schema.get("properties") else {
+        return;
+    };
+    for (name, property) in properties {
+        let property_path = if path.is_empty() { name.clone() } else { format!("{}.{}", path, name) };
+        if property.get("deprecated").and_then(Value::as_bool) == Some(true) {
+            found.insert(
+                property_path.clone(),
+                Deprecation {
+                    path: property_path.clone(),
+                    message: deprecation_message(property),
+                },
+            );
+        }
+        collect_object(property, &property_path, found);
+        for nested in ["items", "additionalProperties"] {
+            if let Some(nested) = property.get(nested).filter(|nested| nested.is_object()) {
+                collect_object(nested, &format!("{}.*", property_path), found);
+            }
+        }
+    }
+}
+
+/// `deprecationMessage` (as editors read it), or else the description
+fn deprecation_message(property: &Value) -> Option<String> {
+    ["deprecationMessage", "description"]
+        .iter()
+        .find_map(|key| property.get(*key).and_then(Value::as_str))
+        .map(str::to_string)
+}
+
+/// What to do with each deprecation before the next major release
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RemovalPlan {
+    /// Release the plan is for, e.g. `2.0.0`; `None` when the current version is unknown
+    pub next_major: Option<String>,
+    /// Deprecated in the previous release and still here: remove in `next_major`
+    pub removable: Vec<Deprecation>,
+    /// Deprecated since the previous release: keep through `next_major`
+    pub newly_deprecated: Vec<Deprecation>,
+    /// Deprecated in the previous release and already gone
+    pub removed: Vec<Deprecation>,
+    /// Deprecated in the previous release but not anymore
+    pub undeprecated: Vec<Deprecation>,
+}
+
+/// Compare the previous release's deprecations with the current schema's
+///
+/// `current_schema` is the whole current document, to tell a removed property from one
+/// that's just no longer deprecated. `version` is the current schema version.
+pub fn plan(previous: &[Deprecation], current: &[Deprecation], current_schema: &Value, version: Option<&str>) -> RemovalPlan {
+    let mut plan = RemovalPlan {
+        next_major: version.and_then(next_major),
+        ..Default::default()
+    };
+    for deprecation in current {
+        if previous.iter().any(|earlier| earlier.path == deprecation.path) {
+            plan.removable.push(deprecation.clone());
+        } else {
+            plan.newly_deprecated.push(deprecation.clone());
+        }
+    }
+    for deprecation in previous {
+        if current.iter().any(|now| now.path == deprecation.path) {
+            continue;
+        }
+        if has_property(current_schema, &deprecation.path) {
+            plan.undeprecated.push(deprecation.clone());
+        } else {
+            plan.removed.push(deprecation.clone());
+        }
+    }
+    plan
+}
+
+/// The first release of the next major version after `version`, e.g. `1.4.2` → `2.0.0`
+pub fn next_major(version: &str) -> Option<String> {
+    let major: u64 = version.trim_start_matches('v').split('.').next()?.parse().ok()?;
+    Some(format!("{}.0.0", major + 1))
+}
+
+/// Whether the schema still defines the property at `path`
+fn has_property(schema: &Value, path: &str) -> bool {
+    let mut segments: Vec<&str> = path.split('.').collect();
+    let mut object = schema;
+    // A leading segment naming a definition starts there
+    for key in ["definitions", "$defs"] {
+        if let Some(definition) = schema.get(key).and_then(|definitions| definitions.get(segments[0])) {
+            object = definition;
+            segments.remove(0);
+            break;
+        }
+    }
+    segments.iter().try_fold(object, |object, segment| match *segment {
+        "*" => object
+            .get("items")
+            .or_else(|| object.get("additionalProperties"))
+            .filter(|nested| nested.is_object()),
+        name => object.get("properties").and_then(|properties| properties.get(name)),
+    })
+    .is_some()
+}
+
+impl Display for RemovalPlan {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let next = self.next_major.as_deref().unwrap_or("the next major release");
+        writeln!(f, "Deprecation removal plan for {}", next)?;
+        let sections = [
+            ("Remove in this release (deprecated in the previous release)", &self.removable),
+            ("Keep until the following major release (newly deprecated)", &self.newly_deprecated),
+            ("Already removed", &self.removed),
+            ("No longer deprecated", &self.undeprecated),
+        ];
+        for (title, deprecations) in sections {
+            if deprecations.is_empty() {
+                continue;
+            }
+            writeln!(f, "\n{} ({}):", title, deprecations.len())?;
+            for deprecation in deprecations {
+                match &deprecation.message {
+                    Some(message) => writeln!(f, "  - {}: {}", deprecation.path, message)?,
+                    None => writeln!(f, "  - {}", deprecation.path)?,
+                }
+            }
+        }
+        if self.removable.is_empty() && self.newly_deprecated.is_empty() && self.removed.is_empty() && self.undeprecated.is_empty() {
+            writeln!(f, "\nNo deprecated properties.")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,102 @@
+//! Property examples for generated documentation
+//!
+//! Examples are guessed from each property's schema (`examples`, `default`, then
+//! `enum` values, then a placeholder for its type). Heuristics can't produce
+//! realistic Moon values, so an `examples.toml` maps property paths to curated
+//! lists that replace them:
+//!
+//! ```toml
+//! "ProjectConfig.language" = ["typescript", "rust"]
+//!
+//! [TaskConfig]
+//! command = ["cargo build --release", "pnpm run build"]
+//! ```
+
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use super::DocEntry;
+pub use crate::pkl_schema::examples::extract_examples;
+use crate::types::CliError;
+
+/// Example overrides file discovered in the current directory
+pub const EXAMPLES_FILE: &str = "examples.toml";
+
+/// Curated examples keyed by `Type.property` path
+#[derive(Debug, Clone, Default)]
+pub struct ExampleOverrides {
+    pub examples: BTreeMap<String, Vec<Value>>,
+}
+
+impl ExampleOverrides {
+    /// Parse `examples.toml` content
+    pub fn parse(content: &str) -> Result<Self, CliError> {
+        let table: toml::Table = toml::from_str(content).map_err(|e| CliError::ValidationError {
+            source: Box::new(e),
+        })?;
+        let mut overrides = Self::default();
+        for (key, value) in table {
+            overrides.collect(key, value)?;
+        }
+        Ok(overrides)
+    }
+
+    /// Load an examples file
+    pub fn load(path: &Path) -> Result<Self, CliError> {
+        let content = std::fs::read_to_string(path).map_err(|e| CliError::IoError {
+            context: format!("Reading examples: {}", path.display()),
+            source: e,
+        })?;
+        Self::parse(&content)
+    }
+
+    /// Load `examples.toml` from the current directory, or no overrides
+    ///
+    /// Hermetic runs never read it.
+    pub fn discover() -> Result<Self, CliError> {
+        let path = PathBuf::from(EXAMPLES_FILE);
+        if crate::hermetic::is_enabled() || !path.exists() {
+            return Ok(Self::default());
+        }
+        Self::load(&path)
+    }
+
+    /// Tables nest path segments; anything else is the example list (or a single example)
+    fn collect(&mut self, path: String, value: toml::Value) -> Result<(), CliError> {
+        match value {
+            toml::Value::Table(table) => {
+                for (key, value) in table {
+                    self.collect(format!("{}.{}", path, key), value)?;
+                }
+            }
+            value => {
+                let examples = match serde_json::to_value(value) {
+                    Ok(Value::Array(examples)) => examples,
+                    Ok(example) => vec![example],
+                    Err(e) => return Err(CliError::ValidationError { source: Box::new(e) }),
+                };
+                self.examples.insert(path, examples);
+            }
+        }
+        Ok(())
+    }
+
+    /// Replace the heuristic examples of every overridden entry
+    pub fn apply(&self, entries: &mut [DocEntry]) {
+        for entry in entries {
+            if let Some(examples) = self.examples.get(&entry.path) {
+                entry.examples = examples.clone();
+            }
+        }
+    }
+
+    /// Override paths that don't match any entry, usually typos or renamed properties
+    pub fn unmatched(&self, entries: &[DocEntry]) -> Vec<String> {
+        self.examples
+            .keys()
+            .filter(|path| !entries.iter().any(|entry| &entry.path == *path))
+            .cloned()
+            .collect()
+    }
+}
@@ -0,0 +1,115 @@
+//! Link validation for generated documentation
+//!
+//! Upstream doc comments link to the moonrepo.dev docs, and those pages move. Links
+//! are extracted from every documentation string (bare URLs and Markdown link
+//! targets, outside code spans), de-duplicated, and checked with a HEAD request, falling back to GET for
+//! servers that don't support HEAD. Offline and read-only runs skip the requests.
+//! Timeouts and retries come from the `link-check` network policy (see [`crate::network`]).
+
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+use super::DocEntry;
+use crate::network::{Failure, RetryPolicy};
+
+/// A link found in a documentation entry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocLink {
+    /// Path of the documented type or property
+    pub path: String,
+    pub url: String,
+}
+
+/// A link that didn't resolve
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeadLink {
+    pub path: String,
+    pub url: String,
+    /// HTTP status or connection error
+    pub reason: String,
+}
+
+/// Extract every http(s) link from the documentation entries
+pub fn extract_links(entries: &[DocEntry]) -> Vec<DocLink> {
+    static URL: OnceLock<Regex> = OnceLock::new();
+    let url = URL.get_or_init(|| Regex::new(r#"https?://[^\s<>()\[\]"'`]+"#).unwrap());
+
+    let mut links = Vec::new();
+    for entry in entries {
+        let Some(text) = &entry.text else { continue };
+        // Links in code spans are examples, not references
+        for found in text.split('`').step_by(2).flat_map(|prose| url.find_iter(prose)) {
+            // Sentence punctuation isn't part of the link
+            let link = found.as_str().trim_end_matches(['.', ',', ';', ':', '!', '?']);
+            links.push(DocLink {
+                path: entry.path.clone(),
+                url: link.to_string(),
+            });
+        }
+    }
+    links
+}
+
+/// Check each distinct URL once, returning the dead links
+///
+/// Connection errors and 5xx/429 responses are retried per `policy` before a link
+/// counts as dead.
+pub async fn check_links(links: &[DocLink], policy: &RetryPolicy) -> Vec<DeadLink> {
+    let client = match policy.client() {
+        Ok(client) => client,
+        Err(e) => {
+            return links
+                .iter()
+                .map(|link| DeadLink {
+                    path: link.path.clone(),
+                    url: link.url.clone(),
+                    reason: e.to_string(),
+                })
+                .collect();
+        }
+    };
+
+    let mut results: BTreeMap<&str, Option<String>> = BTreeMap::new();
+    for link in links {
+        if !results.contains_key(link.url.as_str()) {
+            let failure = crate::network::retry(policy, &link.url, || check_url(&client, policy, &link.url))
+                .await
+                .err();
+            results.insert(link.url.as_str(), failure);
+        }
+    }
+
+    links
+        .iter()
+        .filter_map(|link| {
+            let reason = results.get(link.url.as_str())?.clone()?;
+            Some(DeadLink {
+                path: link.path.clone(),
+                url: link.url.clone(),
+                reason,
+            })
+        })
+        .collect()
+}
+
+async fn check_url(client: &reqwest::Client, policy: &RetryPolicy, url: &str) -> Result<(), Failure> {
+    use reqwest::StatusCode;
+
+    let unreachable = |e: reqwest::Error| policy.request_failure(e);
+    let response = client.head(url).send().await.map_err(unreachable)?;
+    let response = match response.status() {
+        StatusCode::METHOD_NOT_ALLOWED | StatusCode::NOT_IMPLEMENTED | StatusCode::FORBIDDEN => {
+            client.get(url).send().await.map_err(unreachable)?
+        }
+        _ => response,
+    };
+
+    match crate::network::status_failure(&response) {
+        None => Ok(()),
+        Some(Failure::Retryable(_, retry_after)) => {
+            Err(Failure::Retryable(format!("HTTP {}", response.status()), retry_after))
+        }
+        Some(Failure::Fatal(_)) => Err(Failure::Fatal(format!("HTTP {}", response.status()))),
+    }
+}
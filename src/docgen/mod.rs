@@ -0,0 +1,139 @@
+//! Docgen Module for Space Pklr
+//!
+//! Builds Markdown reference docs for the Moon config types from the documentation
+//! carried in their generated JSON Schemas: the root type and every definition, with
+//! each property's description keyed by its `Type.property` path.
+
+pub mod examples;
+pub mod links;
+pub mod spellcheck;
+
+use serde_json::Value;
+
+use crate::types::{CliError, MoonConfig};
+
+/// Whether a documented item is a type or one of its properties
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocKind {
+    Type,
+    Property,
+}
+
+/// Documentation for one type or property
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocEntry {
+    /// `Type` or `Type.property`
+    pub path: String,
+    pub kind: DocKind,
+    /// Description text, if the schema has a non-empty one
+    pub text: Option<String>,
+    /// Example values for properties
+    pub examples: Vec<Value>,
+}
+
+/// Collect documentation for every type and property in a JSON Schema
+pub fn collect_docs(schema: &Value) -> Vec<DocEntry> {
+    let mut entries = Vec::new();
+    if let Some(title) = schema.get("title").and_then(Value::as_str) {
+        if schema.get("properties").is_some() {
+            collect_type(title, schema, &mut entries);
+        }
+    }
+    if let Some(definitions) = schema.get("definitions").and_then(Value::as_object) {
+        for (name, definition) in definitions {
+            collect_type(name, definition, &mut entries);
+        }
+    }
+    entries
+}
+
+fn collect_type(name: &str, schema: &Value, entries: &mut Vec<DocEntry>) {
+    entries.push(DocEntry {
+        path: name.to_string(),
+        kind: DocKind::Type,
+        text: description(schema),
+        examples: Vec::new(),
+    });
+    if let Some(properties) = schema.get("properties").and_then(Value::as_object) {
+        for (property, property_schema) in properties {
+            entries.push(DocEntry {
+                path: format!("{}.{}", name, property),
+                kind: DocKind::Property,
+                text: description(property_schema),
+                examples: examples::extract_examples(property_schema),
+            });
+        }
+    }
+}
+
+fn description(schema: &Value) -> Option<String> {
+    schema
+        .get("description")
+        .or_else(|| schema.get("markdownDescription"))
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|text| !text.is_empty())
+        .map(str::to_string)
+}
+
+/// How much of a schema carries documentation
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DocCoverage {
+    pub documented: usize,
+    pub total: usize,
+    /// Paths of types and properties without documentation
+    pub undocumented: Vec<String>,
+}
+
+impl DocCoverage {
+    /// Coverage percentage; an empty schema counts as fully documented
+    pub fn percent(&self) -> f64 {
+        if self.total == 0 {
+            return 100.0;
+        }
+        self.documented as f64 * 100.0 / self.total as f64
+    }
+}
+
+/// Documentation coverage of a set of entries
+pub fn coverage(entries: &[DocEntry]) -> DocCoverage {
+    let mut coverage = DocCoverage::default();
+    for entry in entries {
+        coverage.total += 1;
+        match entry.text {
+            Some(_) => coverage.documented += 1,
+            None => coverage.undocumented.push(entry.path.clone()),
+        }
+    }
+    coverage
+}
+
+/// Documentation entries for a Moon config type, from its generated JSON Schema
+pub fn schema_docs(config_type: MoonConfig) -> Result<Vec<DocEntry>, CliError> {
+    let content = crate::moon_schema::generate_schema(config_type, "json-schema")?;
+    let schema: Value = serde_json::from_str(&content).map_err(|e| CliError::ValidationError {
+        source: Box::new(e),
+    })?;
+    Ok(collect_docs(&schema))
+}
+
+/// Render documentation entries as a Markdown reference page
+pub fn render_markdown(title: &str, entries: &[DocEntry]) -> String {
+    let mut output = format!("# {}\n", title);
+    for entry in entries {
+        match entry.kind {
+            DocKind::Type => output.push_str(&format!("\n## {}\n", entry.path)),
+            DocKind::Property => output.push_str(&format!("\n### `{}`\n", entry.path)),
+        }
+        if let Some(text) = &entry.text {
+            output.push('\n');
+            output.push_str(text);
+            output.push('\n');
+        }
+        if !entry.examples.is_empty() {
+            let examples: Vec<String> = entry.examples.iter().map(|e| format!("`{}`", e)).collect();
+            output.push_str(&format!("\nExamples: {}\n", examples.join(", ")));
+        }
+    }
+    output
+}
@@ -0,0 +1,186 @@
+//! Spell and terminology checking for generated documentation
+//!
+//! Descriptions come from upstream doc comments, so typos there ship in every
+//! published schema. The checker flags a built-in list of common misspellings and
+//! product-name casing, plus a project dictionary:
+//!
+//! ```text
+//! # Accepted words, one per line
+//! proto
+//! vcs
+//! # Terminology: flagged word => preferred spelling
+//! Moonrepo => moonrepo
+//! ```
+//!
+//! Code spans (`` `like this` ``) and URLs are skipped.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+use super::DocEntry;
+use crate::types::CliError;
+
+/// Project dictionary discovered in the current directory
+pub const DICTIONARY_FILE: &str = "spklr-dictionary.txt";
+
+/// Common English misspellings in technical prose
+const COMMON_MISSPELLINGS: &[(&str, &str)] = &[
+    ("accross", "across"),
+    ("arguement", "argument"),
+    ("avaiable", "available"),
+    ("compatability", "compatibility"),
+    ("configuraiton", "configuration"),
+    ("definately", "definitely"),
+    ("dependancies", "dependencies"),
+    ("dependancy", "dependency"),
+    ("directoy", "directory"),
+    ("enviroment", "environment"),
+    ("existant", "existent"),
+    ("independant", "independent"),
+    ("inheritence", "inheritance"),
+    ("lenght", "length"),
+    ("occured", "occurred"),
+    ("occurence", "occurrence"),
+    ("overriden", "overridden"),
+    ("paramter", "parameter"),
+    ("recieve", "receive"),
+    ("recommand", "recommend"),
+    ("refered", "referred"),
+    ("seperate", "separate"),
+    ("specifiy", "specify"),
+    ("succesful", "successful"),
+    ("teh", "the"),
+    ("untill", "until"),
+    ("wich", "which"),
+];
+
+/// Product and format names with a canonical spelling
+const TERMINOLOGY: &[(&str, &str)] = &[
+    ("Github", "GitHub"),
+    ("Javascript", "JavaScript"),
+    ("Json", "JSON"),
+    ("Nodejs", "Node.js"),
+    ("Typescript", "TypeScript"),
+    ("Yaml", "YAML"),
+];
+
+/// Words accepted and terminology preferred by a project
+#[derive(Debug, Clone, Default)]
+pub struct Dictionary {
+    /// Accepted words, lowercased
+    pub accepted: BTreeSet<String>,
+    /// Flagged word (case-sensitive) to preferred spelling
+    pub terms: BTreeMap<String, String>,
+}
+
+impl Dictionary {
+    /// Parse a dictionary file's content
+    pub fn parse(content: &str) -> Self {
+        let mut dictionary = Self::default();
+        for line in content.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            match line.split_once("=>") {
+                Some((wrong, right)) => {
+                    dictionary
+                        .terms
+                        .insert(wrong.trim().to_string(), right.trim().to_string());
+                }
+                None => {
+                    dictionary.accepted.insert(line.to_lowercase());
+                }
+            }
+        }
+        dictionary
+    }
+
+    /// Load a dictionary file
+    pub fn load(path: &Path) -> Result<Self, CliError> {
+        let content = std::fs::read_to_string(path).map_err(|e| CliError::IoError {
+            context: format!("Reading dictionary: {}", path.display()),
+            source: e,
+        })?;
+        Ok(Self::parse(&content))
+    }
+
+    /// Load `spklr-dictionary.txt` from the current directory, or an empty dictionary
+    ///
+    /// Hermetic runs never read it.
+    pub fn discover() -> Result<Self, CliError> {
+        let path = PathBuf::from(DICTIONARY_FILE);
+        if crate::hermetic::is_enabled() || !path.exists() {
+            return Ok(Self::default());
+        }
+        Self::load(&path)
+    }
+
+    /// Preferred spelling for `word`, if it's flagged
+    fn suggestion(&self, word: &str) -> Option<String> {
+        if self.accepted.contains(&word.to_lowercase()) {
+            return None;
+        }
+        if let Some(preferred) = self.terms.get(word) {
+            return Some(preferred.clone());
+        }
+        if let Some((_, preferred)) = TERMINOLOGY.iter().find(|(wrong, _)| *wrong == word) {
+            return Some(preferred.to_string());
+        }
+        let lower = word.to_lowercase();
+        COMMON_MISSPELLINGS
+            .iter()
+            .find(|(wrong, _)| *wrong == lower)
+            .map(|(_, preferred)| match_capitalization(word, preferred))
+    }
+}
+
+/// A flagged word in a documentation entry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpellingIssue {
+    /// Path of the documented type or property
+    pub path: String,
+    pub word: String,
+    pub suggestion: String,
+}
+
+/// Check every documentation entry against the built-in lists and `dictionary`
+pub fn check(entries: &[DocEntry], dictionary: &Dictionary) -> Vec<SpellingIssue> {
+    let mut issues = Vec::new();
+    for entry in entries {
+        let Some(text) = &entry.text else { continue };
+        for word in prose_words(text) {
+            if let Some(suggestion) = dictionary.suggestion(word) {
+                issues.push(SpellingIssue {
+                    path: entry.path.clone(),
+                    word: word.to_string(),
+                    suggestion,
+                });
+            }
+        }
+    }
+    issues
+}
+
+/// Words in `text` outside code spans and URLs
+fn prose_words(text: &str) -> Vec<&str> {
+    text.split('`')
+        .step_by(2)
+        .flat_map(str::split_whitespace)
+        .filter(|token| !token.contains("://"))
+        .flat_map(|token| token.split(|c: char| !(c.is_alphabetic() || c == '\'')))
+        .map(|word| word.trim_matches('\''))
+        .filter(|word| !word.is_empty())
+        .collect()
+}
+
+fn match_capitalization(original: &str, replacement: &str) -> String {
+    if original.chars().next().is_some_and(char::is_uppercase) {
+        let mut chars = replacement.chars();
+        chars
+            .next()
+            .map(|first| first.to_uppercase().chain(chars).collect())
+            .unwrap_or_default()
+    } else {
+        replacement.to_string()
+    }
+}
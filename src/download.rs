@@ -0,0 +1,295 @@
+//! Download Module for Space Pklr
+//!
+//! Resumable, retrying downloads for large artifacts like the Pkl CLI. Flaky CI
+//! networks shouldn't mean starting a ~100MB download from scratch:
+//!
+//! - bytes land in `<dest>.part`; later attempts (and later runs) resume it with a
+//!   `Range` request guarded by `If-Range`, so a changed upstream file restarts
+//!   cleanly instead of being spliced onto stale bytes
+//! - failures retry with jittered exponential backoff, honoring `Retry-After` on 429/503
+//!   (see [`crate::network`] for the policy and its `spklr.toml` settings)
+//! - each URL is tried in order, so mirrors take over when the primary is down
+//! - when a checksum is known, the finished file is verified before it's moved into
+//!   place, and a corrupt resumed file is discarded and downloaded again once
+//!
+//! Downloading needs the `network` cargo feature; the checksum helpers don't.
+
+use sha2::{Digest, Sha256};
+use std::path::Path;
+#[cfg(feature = "network")]
+use std::path::PathBuf;
+#[cfg(feature = "network")]
+use tokio::io::AsyncWriteExt;
+
+#[cfg(feature = "network")]
+use crate::network::Failure;
+pub use crate::network::RetryPolicy;
+use crate::types::CliError;
+
+/// Download the first URL that works into `dest`, resuming any earlier partial download
+///
+/// `urls` is the primary URL followed by its mirrors. When `expected_sha256` is given the
+/// file is only moved into place once its checksum matches.
+#[cfg(feature = "network")]
+pub async fn download_resumable(
+    urls: &[String],
+    dest: &Path,
+    expected_sha256: Option<&str>,
+    policy: &RetryPolicy,
+) -> Result<(), CliError> {
+    crate::read_only::ensure_allowed(format!("download {}", dest.display()))?;
+    let part = part_path(dest);
+    let client = policy.client()?;
+    let mut last_error = String::from("no download URLs configured");
+    let mut discarded_corrupt = false;
+
+    for url in urls {
+        let mut attempt = 0;
+        while attempt < policy.max_attempts {
+            attempt += 1;
+            match download_attempt(&client, policy, url, &part).await {
+                Ok(()) => {
+                    if let Some(expected) = expected_sha256 {
+                        let actual = sha256_file(&part).await?;
+                        if !actual.eq_ignore_ascii_case(expected) {
+                            let _ = tokio::fs::remove_file(&part).await;
+                            let _ = tokio::fs::remove_file(meta_path(&part)).await;
+                            last_error = format!(
+                                "checksum mismatch for {} (expected {}, got {})",
+                                url, expected, actual
+                            );
+                            // A bad resume is fixed by one clean download; a second mismatch is the artifact itself
+                            if discarded_corrupt {
+                                break;
+                            }
+                            discarded_corrupt = true;
+                            println!("⚠️  Downloaded file failed verification, restarting from scratch");
+                            attempt -= 1;
+                            continue;
+                        }
+                    }
+
+                    tokio::fs::rename(&part, dest).await.map_err(|e| CliError::IoError {
+                        context: format!("Moving download into place: {}", dest.display()),
+                        source: e,
+                    })?;
+                    let _ = tokio::fs::remove_file(meta_path(&part)).await;
+                    return Ok(());
+                }
+                Err(Failure::Fatal(message)) => {
+                    tracing::warn!("Download from {} failed: {}", url, message);
+                    last_error = message;
+                    break;
+                }
+                Err(Failure::Retryable(message, retry_after)) => {
+                    tracing::warn!("Download attempt {} from {} failed: {}", attempt, url, message);
+                    last_error = message;
+                    if attempt < policy.max_attempts {
+                        let delay = policy.delay(attempt, retry_after);
+                        println!("🔁 Retrying in {}...", crate::types::HumanDuration::from(delay));
+                        tokio::time::sleep(delay).await;
+                    }
+                }
+            }
+        }
+
+        if urls.len() > 1 {
+            println!("🔀 Giving up on {}, trying next mirror", crate::interpolation::redact(url));
+        }
+    }
+
+    Err(CliError::NetworkError(format!(
+        "Download failed from all {} source(s). Last error: {}",
+        urls.len(),
+        last_error
+    )))
+}
+
+#[cfg(not(feature = "network"))]
+pub async fn download_resumable(
+    urls: &[String],
+    _dest: &Path,
+    _expected_sha256: Option<&str>,
+    _policy: &RetryPolicy,
+) -> Result<(), CliError> {
+    Err(CliError::NetworkError(format!(
+        "spklr was built without the `network` feature; cannot download {}",
+        urls.first().map(String::as_str).unwrap_or("anything")
+    )))
+}
+
+/// One request against one URL, appending to the partial file when the server supports ranges
+#[cfg(feature = "network")]
+async fn download_attempt(client: &reqwest::Client, policy: &RetryPolicy, url: &str, part: &Path) -> Result<(), Failure> {
+    use reqwest::StatusCode;
+    use reqwest::header::{CONTENT_RANGE, ETAG, IF_RANGE, LAST_MODIFIED, RANGE};
+
+    let io_fatal = |context: &str, e: std::io::Error| Failure::Fatal(format!("{}: {}", context, e));
+
+    let existing = tokio::fs::metadata(part).await.map(|m| m.len()).unwrap_or(0);
+    // Only resume if we know which version of the file the partial bytes came from
+    let validator = tokio::fs::read_to_string(meta_path(part)).await.ok();
+
+    let mut request = client.get(url);
+    if existing > 0 {
+        if let Some(validator) = &validator {
+            println!("⏯️  Resuming download at {} bytes", existing);
+            request = request
+                .header(RANGE, format!("bytes={}-", existing))
+                .header(IF_RANGE, validator.trim());
+        }
+    }
+
+    let mut response = request
+        .send()
+        .await
+        .map_err(|e| policy.request_failure(e))?;
+
+    let status = response.status();
+    let resuming = match status {
+        StatusCode::PARTIAL_CONTENT => {
+            // Make sure the server resumed where we asked it to
+            let starts_at_end = response
+                .headers()
+                .get(CONTENT_RANGE)
+                .and_then(|v| v.to_str().ok())
+                .is_some_and(|range| range.starts_with(&format!("bytes {}-", existing)));
+            if !starts_at_end {
+                let _ = tokio::fs::remove_file(part).await;
+                return Err(Failure::Retryable(
+                    "server returned an unexpected range; restarting".to_string(),
+                    None,
+                ));
+            }
+            true
+        }
+        StatusCode::RANGE_NOT_SATISFIABLE => {
+            // The partial file is already complete (or longer than the upstream file)
+            if existing > 0 && validator.is_some() {
+                return Ok(());
+            }
+            let _ = tokio::fs::remove_file(part).await;
+            return Err(Failure::Retryable("range not satisfiable".to_string(), None));
+        }
+        s if s.is_success() => false,
+        _ => {
+            let failure = crate::network::status_failure(&response)
+                .unwrap_or_else(|| Failure::Fatal(format!("server returned {}", status)));
+            return Err(failure);
+        }
+    };
+
+    // Remember what we're downloading so a later run can resume it safely
+    if !resuming {
+        let validator = response
+            .headers()
+            .get(ETAG)
+            .or_else(|| response.headers().get(LAST_MODIFIED))
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+        match validator {
+            Some(validator) => tokio::fs::write(meta_path(part), validator)
+                .await
+                .map_err(|e| io_fatal("Writing download metadata", e))?,
+            None => {
+                let _ = tokio::fs::remove_file(meta_path(part)).await;
+            }
+        }
+    }
+
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(resuming)
+        .truncate(!resuming)
+        .open(part)
+        .await
+        .map_err(|e| io_fatal("Opening partial download", e))?;
+
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| policy.request_failure(e))?
+    {
+        file.write_all(&chunk)
+            .await
+            .map_err(|e| io_fatal("Writing partial download", e))?;
+    }
+    file.flush()
+        .await
+        .map_err(|e| io_fatal("Flushing partial download", e))?;
+
+    Ok(())
+}
+
+/// Hex-encoded SHA-256 of a file
+pub async fn sha256_file(path: &Path) -> Result<String, CliError> {
+    let bytes = tokio::fs::read(path).await.map_err(|e| CliError::IoError {
+        context: format!("Reading file for checksum: {}", path.display()),
+        source: e,
+    })?;
+    Ok(sha256_hex(&bytes))
+}
+
+/// Hex-encoded SHA-256 of a byte slice
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+/// Parse the digest out of a `.sha256` file (`<hex>` or `<hex>  <filename>`)
+pub fn parse_sha256_file(content: &str) -> Option<String> {
+    let digest = content.split_whitespace().next()?;
+    (digest.len() == 64 && digest.chars().all(|c| c.is_ascii_hexdigit())).then(|| digest.to_lowercase())
+}
+
+/// Fetch a published `.sha256` checksum for an artifact, if any of the URLs has one
+///
+/// Transient failures are retried per `policy`; a URL without a checksum is skipped.
+#[cfg(feature = "network")]
+pub async fn fetch_published_sha256(urls: &[String], policy: &RetryPolicy) -> Option<String> {
+    if crate::read_only::is_enabled() {
+        return None;
+    }
+    let client = policy.client().ok()?;
+    for url in urls {
+        let checksum_url = format!("{}.sha256", url);
+        let fetched = crate::network::retry(policy, &checksum_url, || async {
+            let response = client
+                .get(&checksum_url)
+                .send()
+                .await
+                .map_err(|e| policy.request_failure(e))?;
+            if let Some(failure) = crate::network::status_failure(&response) {
+                return Err(failure);
+            }
+            response
+                .text()
+                .await
+                .map_err(|e| policy.request_failure(e))
+        })
+        .await;
+        if let Some(digest) = fetched.ok().as_deref().and_then(parse_sha256_file) {
+            return Some(digest);
+        }
+    }
+    None
+}
+
+#[cfg(not(feature = "network"))]
+pub async fn fetch_published_sha256(_urls: &[String], _policy: &RetryPolicy) -> Option<String> {
+    None
+}
+
+#[cfg(feature = "network")]
+fn part_path(dest: &Path) -> PathBuf {
+    let mut name = dest.file_name().unwrap_or_default().to_os_string();
+    name.push(".part");
+    dest.with_file_name(name)
+}
+
+#[cfg(feature = "network")]
+fn meta_path(part: &Path) -> PathBuf {
+    let mut name = part.file_name().unwrap_or_default().to_os_string();
+    name.push(".meta");
+    part.with_file_name(name)
+}
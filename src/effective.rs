@@ -0,0 +1,167 @@
+//! Effective Config Module for Space Pklr
+//!
+//! Composes what Moon sees for one project: the workspace config, the inherited task
+//! files that apply to the project (`.moon/tasks.yml` plus the language, stack, type
+//! and tag scoped files under `.moon/tasks/`), and the project's own `moon.yml`.
+//! Inherited tasks are filtered and renamed per `workspace.inheritedTasks`, get
+//! `taskOptions`, `implicitDeps` and `implicitInputs` applied, and are merged with the
+//! project's tasks using Moon's task merge strategies.
+
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
+
+use crate::merge::{MergeStrategy, merge_configs, merge_field, merge_task};
+
+/// Config file extensions Moon reads, in preference order
+const CONFIG_EXTENSIONS: [&str; 3] = ["yml", "yaml", "pkl"];
+
+/// Nearest ancestor of `start` (inclusive) containing a `.moon` directory
+pub fn find_workspace_root(start: &Path) -> Option<PathBuf> {
+    let start = std::fs::canonicalize(start).unwrap_or_else(|_| start.to_path_buf());
+    start
+        .ancestors()
+        .find(|dir| dir.join(".moon").is_dir())
+        .map(Path::to_path_buf)
+}
+
+/// `.moon/<name>.{yml,yaml,pkl}`, whichever exists
+pub fn moon_config_file(workspace_root: &Path, name: &str) -> Option<PathBuf> {
+    CONFIG_EXTENSIONS
+        .iter()
+        .map(|ext| workspace_root.join(".moon").join(format!("{}.{}", name, ext)))
+        .find(|path| path.is_file())
+}
+
+/// Inherited task files that apply to `project`, in Moon's merge order
+pub fn inherited_task_files(workspace_root: &Path, project: &Value) -> Vec<PathBuf> {
+    let field = |key: &str| project.get(key).and_then(Value::as_str).map(str::to_string);
+    let language = field("language");
+    let stack = field("stack");
+    let project_type = field("type");
+
+    let mut scopes = Vec::new();
+    for scope in [&language, &stack].into_iter().flatten() {
+        scopes.push(scope.clone());
+    }
+    if let (Some(language), Some(stack)) = (&language, &stack) {
+        scopes.push(format!("{}-{}", language, stack));
+    }
+    if let Some(project_type) = &project_type {
+        for scope in [&language, &stack].into_iter().flatten() {
+            scopes.push(format!("{}-{}", scope, project_type));
+        }
+    }
+    if let Some(tags) = project.get("tags").and_then(Value::as_array) {
+        scopes.extend(tags.iter().filter_map(Value::as_str).map(|tag| format!("tag-{}", tag)));
+    }
+
+    let mut files: Vec<PathBuf> = moon_config_file(workspace_root, "tasks").into_iter().collect();
+    for scope in scopes {
+        if let Some(path) = moon_config_file(workspace_root, &format!("tasks/{}", scope)) {
+            if !files.contains(&path) {
+                files.push(path);
+            }
+        }
+    }
+    files
+}
+
+/// The effective project config: inherited settings resolved into the project's own
+pub fn compose_project(inherited_files: &[Value], project: &Value, fallback_id: &str) -> Value {
+    let inherited = inherited_files
+        .iter()
+        .fold(Value::Object(Map::new()), |acc, file| merge_configs(&acc, file));
+
+    let mut effective = match project {
+        Value::Object(project) => project.clone(),
+        _ => Map::new(),
+    };
+    effective
+        .entry("id")
+        .or_insert_with(|| Value::String(fallback_id.to_string()));
+
+    // File groups: project groups replace inherited ones with the same name
+    let file_groups = merge_configs(
+        inherited.get("fileGroups").unwrap_or(&Value::Null),
+        project.get("fileGroups").unwrap_or(&Value::Null),
+    );
+    if !file_groups.is_null() {
+        effective.insert("fileGroups".to_string(), file_groups);
+    }
+
+    let tasks = compose_tasks(&inherited, project);
+    if !tasks.is_empty() {
+        effective.insert("tasks".to_string(), Value::Object(tasks));
+    }
+
+    Value::Object(effective)
+}
+
+fn compose_tasks(inherited: &Value, project: &Value) -> Map<String, Value> {
+    let filters = project.pointer("/workspace/inheritedTasks");
+    let names = |key: &str| -> Option<Vec<String>> {
+        filters?
+            .get(key)?
+            .as_array()
+            .map(|names| names.iter().filter_map(Value::as_str).map(str::to_string).collect())
+    };
+    let include = names("include");
+    let exclude = names("exclude").unwrap_or_default();
+    let rename = filters
+        .and_then(|f| f.get("rename"))
+        .and_then(Value::as_object)
+        .cloned()
+        .unwrap_or_default();
+    let task_options = inherited.get("taskOptions").cloned().unwrap_or(Value::Null);
+
+    let mut tasks = Map::new();
+    if let Some(inherited_tasks) = inherited.get("tasks").and_then(Value::as_object) {
+        for (name, task) in inherited_tasks {
+            let included = include.as_ref().is_none_or(|names| names.contains(name));
+            if !included || exclude.contains(name) {
+                continue;
+            }
+            let name = rename.get(name).and_then(Value::as_str).unwrap_or(name);
+
+            // Workspace-wide taskOptions are defaults under the task's own options
+            let mut task = task.clone();
+            if !task_options.is_null() {
+                let options = match task.get("options") {
+                    Some(options) => merge_configs(&task_options, options),
+                    None => task_options.clone(),
+                };
+                if let Value::Object(task) = &mut task {
+                    task.insert("options".to_string(), options);
+                }
+            }
+            tasks.insert(name.to_string(), task);
+        }
+    }
+
+    if let Some(project_tasks) = project.get("tasks").and_then(Value::as_object) {
+        for (name, task) in project_tasks {
+            let merged = match tasks.get(name) {
+                Some(inherited_task) => merge_task(inherited_task, task),
+                None => task.clone(),
+            };
+            tasks.insert(name.clone(), merged);
+        }
+    }
+
+    // Implicit deps and inputs apply to every task in the project
+    for (field, implicit) in [("deps", "implicitDeps"), ("inputs", "implicitInputs")] {
+        let Some(values) = inherited.get(implicit).filter(|v| !v.is_null()) else {
+            continue;
+        };
+        for task in tasks.values_mut() {
+            let Value::Object(task) = task else { continue };
+            let combined = match task.get(field) {
+                Some(existing) => merge_field(existing, values, MergeStrategy::Append),
+                None => values.clone(),
+            };
+            task.insert(field.to_string(), combined);
+        }
+    }
+
+    tasks
+}
@@ -0,0 +1,181 @@
+//! Env Tokens Module for Space Pklr
+//!
+//! Moon substitutes environment variables written as `$NAME` or `${NAME}` into task
+//! commands, args, env values, and other strings when it runs them. Only uppercase
+//! names (`A-Z`, `0-9`, `_`) are variables; `$projectRoot`, `$target`, and other
+//! lowercase names are Moon's own tokens and are never touched.
+//!
+//! Pkl gives `$` no meaning, so converted Pkl configs keep the tokens exactly as
+//! written for Moon to substitute later. [`EnvTokenPolicy`] chooses otherwise:
+//!
+//! - `preserve` (default): `"$HOME/bin"` stays `"$HOME/bin"`
+//! - `read-env`: Pkl reads the variable when the config is evaluated, so `"$HOME/bin"`
+//!   becomes `"\(read("env:HOME"))/bin"` and `"${HOME}"` becomes `read("env:HOME")`.
+//!   Evaluation fails if the variable isn't set.
+//! - `error`: conversion fails on the first token, for configs that mustn't depend on
+//!   the environment
+//!
+//! Only Pkl output is affected, and only values; keys are always kept as written.
+
+use serde_json::Value;
+use std::fmt::Display;
+use std::str::FromStr;
+
+use crate::config_path::ConfigPath;
+use crate::config_processor::{pkl_escape, pkl_string};
+use crate::types::CliError;
+
+/// What Pkl output does with `$NAME` and `${NAME}` tokens in string values
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum EnvTokenPolicy {
+    /// Keep the tokens as written, for Moon to substitute
+    #[default]
+    Preserve,
+    /// Replace each token with `read("env:NAME")`
+    ReadEnv,
+    /// Fail the conversion
+    Error,
+}
+
+impl FromStr for EnvTokenPolicy {
+    type Err = CliError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "preserve" | "keep" => Ok(EnvTokenPolicy::Preserve),
+            "read-env" | "read_env" | "read" => Ok(EnvTokenPolicy::ReadEnv),
+            "error" | "deny" => Ok(EnvTokenPolicy::Error),
+            _ => Err(CliError::UnsupportedFormat {
+                format: s.to_string(),
+                available: vec!["preserve", "read-env", "error"],
+            }),
+        }
+    }
+}
+
+impl Display for EnvTokenPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EnvTokenPolicy::Preserve => write!(f, "preserve"),
+            EnvTokenPolicy::ReadEnv => write!(f, "read-env"),
+            EnvTokenPolicy::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// A piece of a string value: literal text, or an environment variable token
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Segment<'a> {
+    Text(&'a str),
+    /// `token` is the variable as written, e.g. `${HOME}`
+    Var { name: &'a str, token: &'a str },
+}
+
+/// Split a string into literal text and environment variable tokens
+pub fn segments(value: &str) -> Vec<Segment<'_>> {
+    let mut segments = Vec::new();
+    let mut text_start = 0;
+    let mut index = 0;
+    while index < value.len() {
+        if value.as_bytes()[index] == b'$'
+            && let Some((name, len)) = token_at(&value[index + 1..])
+        {
+            if text_start < index {
+                segments.push(Segment::Text(&value[text_start..index]));
+            }
+            let end = index + 1 + len;
+            segments.push(Segment::Var {
+                name,
+                token: &value[index..end],
+            });
+            index = end;
+            text_start = end;
+        } else {
+            index += 1;
+        }
+    }
+    if text_start < value.len() {
+        segments.push(Segment::Text(&value[text_start..]));
+    }
+    segments
+}
+
+/// The variable name after a `$`, and how many bytes its token takes after the `$`
+fn token_at(rest: &str) -> Option<(&str, usize)> {
+    if let Some(braced) = rest.strip_prefix('{') {
+        let name = &braced[..braced.find('}')?];
+        return is_env_name(name).then_some((name, name.len() + 2));
+    }
+    let len = rest
+        .find(|c: char| !(c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_'))
+        .unwrap_or(rest.len());
+    let name = &rest[..len];
+    is_env_name(name).then_some((name, len))
+}
+
+/// Whether `name` is an environment variable name as Moon substitutes them
+fn is_env_name(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_uppercase() || c == '_')
+        && name.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+}
+
+/// Path and token of the first environment variable in any string value of `value`
+pub fn first_token(value: &Value) -> Option<(ConfigPath, String)> {
+    fn find(value: &Value, path: &ConfigPath) -> Option<(ConfigPath, String)> {
+        match value {
+            Value::String(s) => segments(s).into_iter().find_map(|segment| match segment {
+                Segment::Var { token, .. } => Some((path.clone(), token.to_string())),
+                Segment::Text(_) => None,
+            }),
+            Value::Array(items) => items
+                .iter()
+                .enumerate()
+                .find_map(|(index, item)| find(item, &path.child(index.to_string()))),
+            Value::Object(map) => map.iter().find_map(|(key, child)| find(child, &path.child(key.clone()))),
+            _ => None,
+        }
+    }
+    find(value, &ConfigPath::default())
+}
+
+/// Fail with the first environment variable in `value` if `policy` forbids them
+pub fn check(value: &Value, policy: EnvTokenPolicy) -> Result<(), CliError> {
+    if policy != EnvTokenPolicy::Error {
+        return Ok(());
+    }
+    match first_token(value) {
+        Some((path, token)) => Err(CliError::EnvTokenFound {
+            path: path.to_string(),
+            token,
+        }),
+        None => Ok(()),
+    }
+}
+
+/// A string value as a Pkl expression under `policy`
+///
+/// [`EnvTokenPolicy::Error`] renders like `Preserve`; run [`check`] first.
+pub fn pkl_value(value: &str, policy: EnvTokenPolicy) -> String {
+    if policy != EnvTokenPolicy::ReadEnv {
+        return pkl_string(value);
+    }
+    match segments(value).as_slice() {
+        [Segment::Var { name, .. }] => read_env(name),
+        segments => {
+            let mut out = String::from("\"");
+            for segment in segments {
+                match segment {
+                    Segment::Text(text) => out.push_str(&pkl_escape(text)),
+                    Segment::Var { name, .. } => out.push_str(&format!("\\({})", read_env(name))),
+                }
+            }
+            out.push('"');
+            out
+        }
+    }
+}
+
+/// `read("env:NAME")`
+fn read_env(name: &str) -> String {
+    format!("read({})", pkl_string(&format!("env:{}", name)))
+}
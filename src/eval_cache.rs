@@ -0,0 +1,308 @@
+//! Eval Cache Module for Space Pklr
+//!
+//! Evaluating a Pkl module means starting the Pkl CLI, which takes far longer than
+//! anything else in a validation or conversion. Results of `pkl eval` are kept on disk,
+//! keyed by a hash of everything the output depends on:
+//!
+//! - the module's content, and the content of every local module it amends, extends,
+//!   or imports, recursively (package and HTTPS URIs are versioned, so their URI is enough)
+//! - the Pkl CLI version and the evaluation arguments
+//!
+//! Unchanged configs are then answered from the cache. Modules that read resources
+//! (`read("env:...")` and the like) or glob imports can't be keyed by their sources, so
+//! they're always evaluated.
+//!
+//! ```toml
+//! [cache]
+//! max_size = "128MiB"
+//! ```
+//!
+//! Least recently used entries are removed once the cache grows past `max_size`
+//! (256 MiB by default). `spklr cache clear` empties it. Read-only runs use the cache
+//! without adding to it, and hermetic runs don't use it at all.
+
+use regex::Regex;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::SystemTime;
+
+use crate::pkl_tooling::PklCli;
+use crate::types::{ByteSize, CliError};
+
+/// Default `max_size`
+const DEFAULT_MAX_SIZE: u64 = 256 * 1024 * 1024;
+
+/// Arguments of the evaluation cached entries hold the output of; `pkl server` evaluations
+/// produce the same JSON, so they share entries
+pub const EVAL_ARGS: &[&str] = &["eval", "-f", "json"];
+
+/// Changes whenever the key or entry format does, so older entries are never read
+const KEY_VERSION: &str = "spklr-eval-v1";
+
+/// `[cache]` settings in `spklr.toml`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct CacheConfig {
+    /// Cache evaluation results at all
+    pub enabled: bool,
+    /// Size the cache is trimmed to, least recently used entries first
+    pub max_size: Option<ByteSize>,
+    /// Where entries are kept; defaults to `spklr/pkl-eval` in the user cache directory
+    pub dir: Option<PathBuf>,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_size: None,
+            dir: None,
+        }
+    }
+}
+
+/// Size and number of entries in the cache
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub entries: usize,
+    pub bytes: u64,
+}
+
+/// The on-disk evaluation cache
+#[derive(Debug, Clone)]
+pub struct EvalCache {
+    dir: PathBuf,
+    max_size: u64,
+}
+
+impl EvalCache {
+    pub fn new(dir: impl Into<PathBuf>, max_size: u64) -> Self {
+        Self {
+            dir: dir.into(),
+            max_size,
+        }
+    }
+
+    /// The cache `config` describes, or `None` when it's disabled or the run is hermetic
+    pub fn from_config(config: &CacheConfig) -> Option<Self> {
+        if !config.enabled || crate::hermetic::is_enabled() {
+            return None;
+        }
+        let dir = config.dir.clone().unwrap_or_else(default_dir);
+        let max_size = config.max_size.map_or(DEFAULT_MAX_SIZE, |size| size.bytes());
+        Some(Self::new(dir, max_size))
+    }
+
+    /// The cache configured in `spklr.toml`, read once per process
+    pub fn configured() -> Option<&'static Self> {
+        static CACHE: OnceLock<Option<EvalCache>> = OnceLock::new();
+        CACHE
+            .get_or_init(|| {
+                let config = crate::tool_config::ToolConfig::discover().map(|config| config.cache).unwrap_or_default();
+                Self::from_config(&config)
+            })
+            .as_ref()
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// The cached output for `key`, marking it as recently used
+    pub fn get(&self, key: &str) -> Option<String> {
+        let path = self.entry_path(key);
+        let output = std::fs::read_to_string(&path).ok()?;
+        if !crate::read_only::is_enabled()
+            && let Ok(file) = std::fs::File::options().write(true).open(&path)
+        {
+            let _ = file.set_modified(SystemTime::now());
+        }
+        Some(output)
+    }
+
+    /// Store the output for `key`, then trim the cache to its size limit
+    ///
+    /// Failing to write an entry only costs a later evaluation, so it's logged, not returned.
+    pub fn put(&self, key: &str, output: &str) {
+        if crate::read_only::is_enabled() {
+            return;
+        }
+        let stored = std::fs::create_dir_all(&self.dir)
+            .map_err(|e| CliError::IoError {
+                context: format!("Creating eval cache directory: {}", self.dir.display()),
+                source: e,
+            })
+            .and_then(|()| crate::atomic_write::write_atomic_sync(self.entry_path(key), output));
+        if let Err(error) = stored {
+            tracing::warn!("Couldn't cache Pkl evaluation: {}", error);
+            return;
+        }
+        self.prune(Some(key));
+    }
+
+    /// Remove least recently used entries until the cache fits its size limit, keeping
+    /// the entry for `keep` (the one just stored)
+    pub fn prune(&self, keep: Option<&str>) {
+        let keep = keep.map(|key| self.entry_path(key));
+        let mut entries = self.entries();
+        let mut total: u64 = entries.iter().map(|(_, size, _)| size).sum();
+        entries.sort_by_key(|(_, _, used)| *used);
+        for (path, size, _) in entries {
+            if total <= self.max_size {
+                break;
+            }
+            if keep.as_ref() == Some(&path) {
+                continue;
+            }
+            if std::fs::remove_file(&path).is_ok() {
+                total -= size;
+            }
+        }
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        let entries = self.entries();
+        CacheStats {
+            entries: entries.len(),
+            bytes: entries.iter().map(|(_, size, _)| size).sum(),
+        }
+    }
+
+    /// Remove every entry
+    pub fn clear(&self) -> Result<CacheStats, CliError> {
+        crate::read_only::ensure_allowed(format!("clear {}", self.dir.display()))?;
+        let stats = self.stats();
+        if self.dir.exists() {
+            std::fs::remove_dir_all(&self.dir).map_err(|e| CliError::IoError {
+                context: format!("Removing eval cache: {}", self.dir.display()),
+                source: e,
+            })?;
+        }
+        Ok(stats)
+    }
+
+    fn entry_path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", key))
+    }
+
+    /// Every entry with its size and when it was last used
+    fn entries(&self) -> Vec<(PathBuf, u64, SystemTime)> {
+        let Ok(read_dir) = std::fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+        read_dir
+            .flatten()
+            .filter(|entry| entry.path().extension().is_some_and(|extension| extension == "json"))
+            .filter_map(|entry| {
+                let metadata = entry.metadata().ok()?;
+                Some((entry.path(), metadata.len(), metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH)))
+            })
+            .collect()
+    }
+}
+
+fn default_dir() -> PathBuf {
+    dirs::cache_dir().unwrap_or_else(std::env::temp_dir).join("spklr").join("pkl-eval")
+}
+
+/// The JSON output of evaluating `module`: from the configured cache when it holds it,
+/// else from `evaluate`, whose output is then stored
+///
+/// Outputs are only stored once they parse, so a failed evaluation is retried next time.
+pub async fn cached<F, Fut>(pkl_cli: &PklCli, module: &Path, evaluate: F) -> Result<String, CliError>
+where
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Result<String, CliError>>,
+{
+    let args: Vec<String> = EVAL_ARGS.iter().map(|arg| arg.to_string()).collect();
+    let cached = EvalCache::configured().and_then(|cache| {
+        let version = pkl_cli.version.clone().unwrap_or_else(|| pkl_cli.path.display().to_string());
+        Some((cache, cache_key(module, &version, &args)?))
+    });
+    if let Some((cache, key)) = &cached
+        && let Some(output) = cache.get(key)
+    {
+        tracing::debug!("Pkl evaluation of {} served from the cache", module.display());
+        return Ok(output);
+    }
+
+    let output = evaluate().await?;
+    if let Some((cache, key)) = &cached
+        && serde_json::from_str::<serde_json::Value>(&output).is_ok()
+    {
+        cache.put(key, &output);
+    }
+    Ok(output)
+}
+
+/// Cache key for evaluating `module` with `pkl_version` and `args`, or `None` when the
+/// module's output can depend on more than its sources
+pub fn cache_key(module: &Path, pkl_version: &str, args: &[String]) -> Option<String> {
+    let mut hasher = Sha256::new();
+    hasher.update(KEY_VERSION);
+    hasher.update([0]);
+    hasher.update(pkl_version);
+    for arg in args {
+        hasher.update([0]);
+        hasher.update(arg);
+    }
+
+    let mut seen = BTreeSet::new();
+    let mut queue = vec![module.to_path_buf()];
+    while let Some(path) = queue.pop() {
+        let path = path.canonicalize().ok()?;
+        if !seen.insert(path.clone()) {
+            continue;
+        }
+        let content = std::fs::read_to_string(&path).ok()?;
+        if reads_resources(&content) {
+            return None;
+        }
+        hasher.update([0]);
+        hasher.update(&content);
+        let dir = path.parent().unwrap_or(Path::new("."));
+        for uri in dependencies(&content) {
+            match local_path(&uri, dir) {
+                Some(dependency) => queue.push(dependency),
+                None => {
+                    hasher.update([0]);
+                    hasher.update(&uri);
+                }
+            }
+        }
+    }
+    Some(format!("{:x}", hasher.finalize()))
+}
+
+/// URIs the module amends, extends, or imports
+pub fn dependencies(content: &str) -> Vec<String> {
+    static DEPENDENCY: OnceLock<Regex> = OnceLock::new();
+    DEPENDENCY
+        .get_or_init(|| Regex::new(r#"\b(?:amends|extends|import)\s*\(?\s*"([^"]+)""#).expect("valid dependency regex"))
+        .captures_iter(content)
+        .map(|captures| captures[1].to_string())
+        .collect()
+}
+
+/// Whether the module reads resources or glob-imports modules
+fn reads_resources(content: &str) -> bool {
+    static RESOURCE: OnceLock<Regex> = OnceLock::new();
+    RESOURCE
+        .get_or_init(|| Regex::new(r"\b(?:read[?*]?\s*\(|import\*)").expect("valid resource regex"))
+        .is_match(content)
+}
+
+/// The file a module URI names, relative to the importing module's directory
+fn local_path(uri: &str, dir: &Path) -> Option<PathBuf> {
+    if let Some(path) = uri.strip_prefix("file://") {
+        return Some(PathBuf::from(path));
+    }
+    if uri.contains(':') {
+        // package:, https:, pkl:, modulepath:
+        return None;
+    }
+    Some(dir.join(uri))
+}
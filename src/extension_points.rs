@@ -0,0 +1,124 @@
+//! Extension Points Module for Space Pklr
+//!
+//! Finds the places in generated modules where configs can hold anything: properties
+//! typed `Any`, `Listing<Any>`, or `Mapping<String, Any>`. Each one is a typing gap,
+//! since Pkl accepts whatever keys and values a config puts there and can't catch a
+//! typo. [`find`] lists them with guidance on the mechanism that can type them:
+//!
+//! - a toolchain plugin's block: `[[toolchain_plugins]]` in `spklr.toml`
+//!   (see [`crate::pkl_schema::toolchain_plugins`])
+//! - other open mappings and values: a JSON Schema `[[sources]]` entry in a composition
+//!   manifest (see [`crate::composition`]) that defines them
+//! - values that only need limits: `constraints` in a policy file's `overrides` (see
+//!   [`crate::pkl_schema::overrides`])
+//!
+//! Points are named like schema diffs: `Module.property` or `Module.Class.property`.
+
+use serde::Serialize;
+use std::fmt::Display;
+
+use crate::pkl_schema::{PklModule, PklProperty, PklType};
+
+/// How much an extension point lets through
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ExtensionKind {
+    /// `Mapping<String, Any>`: arbitrary keys with arbitrary values
+    OpenMapping,
+    /// `Listing<Any>`: elements of any type
+    UntypedListing,
+    /// `Any`: a value of any type
+    Untyped,
+}
+
+/// A property that accepts arbitrary user keys or values
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ExtensionPoint {
+    /// `Module.property` or `Module.Class.property`
+    pub path: String,
+    pub kind: ExtensionKind,
+    /// The property's Pkl type
+    #[serde(rename = "type")]
+    pub ty: String,
+    /// How to type it
+    pub guidance: String,
+}
+
+impl Display for ExtensionPoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}\n    → {}", self.path, self.ty, self.guidance)
+    }
+}
+
+/// Extension points of every module, sorted by path
+pub fn find(modules: &[PklModule]) -> Vec<ExtensionPoint> {
+    let mut points = Vec::new();
+    for module in modules {
+        for property in &module.properties {
+            points.extend(point(module, None, property));
+        }
+        for class in &module.classes {
+            for property in &class.properties {
+                points.extend(point(module, Some(&class.name), property));
+            }
+        }
+    }
+    points.sort_by(|a, b| a.path.cmp(&b.path));
+    points
+}
+
+fn point(module: &PklModule, class: Option<&str>, property: &PklProperty) -> Option<ExtensionPoint> {
+    let kind = gap(&property.ty)?;
+    let path = match class {
+        Some(class) => format!("{}.{}.{}", module.name, class, property.name),
+        None => format!("{}.{}", module.name, property.name),
+    };
+    Some(ExtensionPoint {
+        guidance: guidance(module, class, property, kind),
+        path,
+        kind,
+        ty: property.ty.render(),
+    })
+}
+
+/// The widest gap in `ty`, if it has one
+pub fn gap(ty: &PklType) -> Option<ExtensionKind> {
+    match ty {
+        PklType::Any => Some(ExtensionKind::Untyped),
+        PklType::Nullable(inner) => gap(inner),
+        PklType::Mapping(_, value) => match value.as_ref() {
+            PklType::Any => Some(ExtensionKind::OpenMapping),
+            PklType::Nullable(inner) if **inner == PklType::Any => Some(ExtensionKind::OpenMapping),
+            other => gap(other),
+        },
+        PklType::Listing(item) => match item.as_ref() {
+            PklType::Any => Some(ExtensionKind::UntypedListing),
+            other => gap(other),
+        },
+        PklType::Union(members) => members.iter().filter_map(gap).min(),
+        _ => None,
+    }
+}
+
+fn guidance(module: &PklModule, class: Option<&str>, property: &PklProperty, kind: ExtensionKind) -> String {
+    if module.name.eq_ignore_ascii_case("toolchain") && class.is_none() && kind != ExtensionKind::UntypedListing {
+        return format!(
+            "If this is a toolchain plugin's block, add [[toolchain_plugins]] with id = \"{}\" and its schema to spklr.toml",
+            property.name
+        );
+    }
+    match kind {
+        ExtensionKind::OpenMapping => format!(
+            "Define the entries in a JSON Schema source of a composition manifest, or limit them with a policy override on `{}`",
+            property.name
+        ),
+        ExtensionKind::UntypedListing => format!(
+            "Define the element type in a JSON Schema source of a composition manifest, or limit elements with a policy override on `{}`",
+            property.name
+        ),
+        ExtensionKind::Untyped => format!(
+            "Add a policy override on `{}` with a constraint such as `this is String`, or define it in a composition JSON Schema source",
+            property.name
+        ),
+    }
+}
@@ -0,0 +1,206 @@
+//! Fragments Module for Space Pklr
+//!
+//! Hand-written Pkl appended to generated modules, so teams can add helper functions
+//! and the like without forking templates or losing them on regeneration. A file named
+//! `fragments/<Module>.append.pkl` is appended verbatim to the generated `<module>.pkl`
+//! (matched case-insensitively, so `Workspace.append.pkl` goes with `workspace.pkl`):
+//!
+//! ```pkl
+//! // fragments/Workspace.append.pkl
+//! function projectPath(name: String): String = "apps/\(name)"
+//! ```
+//!
+//! Fragments are syntax-checked before they're appended: strings, comments, and
+//! brackets must be closed, and declarations that only belong at the top of a module
+//! (`module`, `amends`, `extends`, `import`) are rejected. Hermetic runs don't look for
+//! fragments.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::types::CliError;
+
+/// Directory searched for fragments, relative to the working directory
+pub const FRAGMENTS_DIR: &str = "fragments";
+
+/// File name suffix of fragments
+pub const FRAGMENT_SUFFIX: &str = ".append.pkl";
+
+/// Declarations that can't follow a module's body
+const HEADER_KEYWORDS: [&str; 5] = ["module", "amends", "extends", "import", "import*"];
+
+/// The fragment in `dir` for the generated Pkl output `file`, if there is one
+pub fn find_fragment(dir: &Path, file: &str) -> Option<PathBuf> {
+    let path = Path::new(file);
+    if path.extension().and_then(|ext| ext.to_str()) != Some("pkl") {
+        return None;
+    }
+    let module = path.file_stem()?.to_str()?;
+    std::fs::read_dir(dir).ok()?.flatten().map(|entry| entry.path()).find(|candidate| {
+        candidate
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.strip_suffix(FRAGMENT_SUFFIX))
+            .is_some_and(|name| name.eq_ignore_ascii_case(module))
+    })
+}
+
+/// Append the matching fragment from `dir` to generated `content`, after checking it
+pub fn append_fragment(dir: &Path, file: &str, content: &str) -> Result<String, CliError> {
+    let Some(source) = read_fragment(dir, file)? else {
+        return Ok(content.to_string());
+    };
+    let mut output = content.trim_end().to_string();
+    output.push_str("\n\n");
+    output.push_str(source.trim_end());
+    output.push('\n');
+    Ok(output)
+}
+
+/// Append the fragment for `file` from [`FRAGMENTS_DIR`]; hermetic runs skip this
+pub fn apply(file: &str, content: &str) -> Result<String, CliError> {
+    if crate::hermetic::is_enabled() {
+        return Ok(content.to_string());
+    }
+    append_fragment(Path::new(FRAGMENTS_DIR), file, content)
+}
+
+/// Stream whatever `body` writes into `out`, followed by the fragment for `file` as
+/// [`apply`] appends it
+///
+/// The body is already written when the fragment follows, so trailing blank lines in
+/// it are kept rather than trimmed.
+pub fn apply_with(
+    file: &str,
+    out: &mut dyn Write,
+    body: impl FnOnce(&mut dyn Write) -> Result<(), CliError>,
+) -> Result<(), CliError> {
+    let fragment = if crate::hermetic::is_enabled() {
+        None
+    } else {
+        read_fragment(Path::new(FRAGMENTS_DIR), file)?
+    };
+    let Some(source) = fragment else {
+        return body(out);
+    };
+    let mut out = crate::templates::LineEndWriter::new(out);
+    body(&mut out)?;
+    let separator = if out.ends_with_newline { "\n" } else { "\n\n" };
+    write!(out, "{}{}\n", separator, source.trim_end()).map_err(|e| CliError::IoError {
+        context: format!("Appending fragment to {}", file),
+        source: e,
+    })
+}
+
+/// The checked source of the fragment in `dir` for `file`, if there is one
+fn read_fragment(dir: &Path, file: &str) -> Result<Option<String>, CliError> {
+    let Some(fragment) = find_fragment(dir, file) else {
+        return Ok(None);
+    };
+    let source = std::fs::read_to_string(&fragment).map_err(|e| CliError::IoError {
+        context: format!("Reading fragment: {}", fragment.display()),
+        source: e,
+    })?;
+    check_syntax(&source).map_err(|message| CliError::FragmentError {
+        fragment: fragment.clone(),
+        message,
+    })?;
+    tracing::debug!("Appending fragment {} to {}", fragment.display(), file);
+    Ok(Some(source))
+}
+
+/// Check that a fragment can be appended to a module body
+///
+/// This is a structural check, not a full parse: the Pkl evaluator still has the last
+/// word on the generated module.
+pub fn check_syntax(source: &str) -> Result<(), String> {
+    let mut open: Vec<(char, usize)> = Vec::new();
+    let mut line = 1;
+    let mut line_start = true;
+    let mut chars = source.char_indices().peekable();
+
+    while let Some((index, c)) = chars.next() {
+        if line_start && !c.is_whitespace() {
+            line_start = false;
+            let word = source[index..].split_whitespace().next().unwrap_or_default();
+            if HEADER_KEYWORDS.contains(&word) {
+                return Err(format!("`{}` declarations can't be appended to a module (line {})", word, line));
+            }
+        }
+        match c {
+            '\n' => {
+                line += 1;
+                line_start = true;
+            }
+            '/' if chars.peek().is_some_and(|(_, next)| *next == '/') => {
+                while chars.peek().is_some_and(|(_, next)| *next != '\n') {
+                    chars.next();
+                }
+            }
+            '/' if chars.peek().is_some_and(|(_, next)| *next == '*') => {
+                let start = line;
+                chars.next();
+                let mut previous = ' ';
+                loop {
+                    match chars.next() {
+                        Some((_, '/')) if previous == '*' => break,
+                        Some((_, next)) => {
+                            if next == '\n' {
+                                line += 1;
+                            }
+                            previous = next;
+                        }
+                        None => return Err(format!("unclosed block comment (line {})", start)),
+                    }
+                }
+            }
+            '"' => {
+                let start = line;
+                let multiline = source[index..].starts_with("\"\"\"");
+                if multiline {
+                    chars.next();
+                    chars.next();
+                }
+                loop {
+                    match chars.next() {
+                        Some((_, '\\')) => {
+                            chars.next();
+                        }
+                        Some((at, '"')) if !multiline || source[at..].starts_with("\"\"\"") => {
+                            if multiline {
+                                chars.next();
+                                chars.next();
+                            }
+                            break;
+                        }
+                        Some((_, '\n')) if !multiline => return Err(format!("unclosed string (line {})", start)),
+                        Some((_, '\n')) => line += 1,
+                        Some(_) => {}
+                        None => return Err(format!("unclosed string (line {})", start)),
+                    }
+                }
+            }
+            '{' | '(' | '[' => open.push((c, line)),
+            '}' | ')' | ']' => {
+                let expected = match c {
+                    '}' => '{',
+                    ')' => '(',
+                    _ => '[',
+                };
+                match open.pop() {
+                    Some((opening, _)) if opening == expected => {}
+                    Some((opening, opened)) => {
+                        return Err(format!("`{}` on line {} closes `{}` from line {}", c, line, opening, opened));
+                    }
+                    None => return Err(format!("unmatched `{}` (line {})", c, line)),
+                }
+            }
+            _ => {}
+        }
+    }
+
+    match open.pop() {
+        Some((opening, opened)) => Err(format!("unclosed `{}` (line {})", opening, opened)),
+        None => Ok(()),
+    }
+}
@@ -0,0 +1,34 @@
+//! Hermetic Module for Space Pklr
+//!
+//! `--hermetic` makes a run a pure function of its explicit inputs, so build systems
+//! like Bazel and Buck can wrap spklr as an action:
+//!
+//! - no discovery: `spklr.toml` isn't read, project directories aren't searched, and the
+//!   Pkl CLI must be given explicitly with `SPKLR_PKL_PATH`
+//! - no caches or downloads: Pkl runs with `--no-cache` and without `https:` and package
+//!   modules or resources
+//! - no undeclared outputs: lock files and `SHA256SUMS` manifests aren't written, so the
+//!   only files produced are the ones named on the command line
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static HERMETIC: AtomicBool = AtomicBool::new(false);
+
+/// Environment variable naming the Pkl CLI in hermetic runs
+pub const PKL_PATH_ENV: &str = "SPKLR_PKL_PATH";
+
+pub fn enable() {
+    HERMETIC.store(true, Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+    HERMETIC.load(Ordering::Relaxed)
+}
+
+/// The explicitly configured Pkl CLI path, if any
+pub fn explicit_pkl_path() -> Option<PathBuf> {
+    std::env::var_os(PKL_PATH_ENV)
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from)
+}
@@ -0,0 +1,181 @@
+//! HTTP Server Module for Space Pklr
+//!
+//! `spklr serve --http` exposes the daemon methods over HTTP so a large org can run
+//! one central config service instead of installing spklr everywhere.
+//!
+//! | Endpoint | Request | Response |
+//! |---|---|---|
+//! | `GET /health` | | `ok` |
+//! | `GET /schemas/{configType}?format=json-schema&moon=<version>` | | schema text |
+//! | `POST /validate?configType=project&from=yaml` | config body | `{ "valid": bool, "error"?, "help"? }` |
+//! | `POST /convert?from=yaml&to=pkl` | config body | converted text |
+//!
+//! Schemas are generated from the `moon_config` crate spklr was built with; asking for
+//! any other `moon` version is a 404 rather than a silently mismatched schema.
+//!
+//! There is no authentication, so a bare port (`:8080`) listens on localhost only; pass
+//! `0.0.0.0:8080` to serve other machines. Pkl request bodies are refused (`400`), since
+//! evaluating them would let any client `read()` the server's files and environment.
+//! The server is behind the `server` cargo feature.
+
+use std::str::FromStr;
+
+use crate::daemon::RpcError;
+use crate::types::{CliError, SchemaFormat};
+use crate::versions::MOON_CONFIG_CRATE_VERSION;
+
+/// Normalize a listen address, so `:8080` means port 8080 on localhost
+pub fn normalize_listen_addr(addr: &str) -> String {
+    if addr.starts_with(':') {
+        format!("127.0.0.1{}", addr)
+    } else {
+        addr.to_string()
+    }
+}
+
+/// Refuse request bodies in formats the HTTP API won't read (Pkl)
+pub fn check_input_format(from: Option<&str>) -> Result<(), RpcError> {
+    match from.map(SchemaFormat::from_str).transpose()? {
+        Some(SchemaFormat::Pkl) => Err(RpcError::InvalidParams(
+            "Pkl input isn't accepted over HTTP; convert it to YAML, JSON, or TOML first".to_string(),
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// HTTP status for a failed daemon call
+pub fn error_status(err: &RpcError) -> u16 {
+    match err {
+        RpcError::MethodNotFound(_) => 404,
+        RpcError::InvalidParams(_) => 400,
+        RpcError::Cli(CliError::UnsupportedFormat { .. }) => 400,
+        RpcError::Cli(CliError::ValidationError { .. }) => 422,
+        RpcError::Cli(_) => 500,
+    }
+}
+
+/// Whether a requested moon version (`0.1.5`, or a prefix like `0.1`) matches the
+/// schemas this server generates
+pub fn moon_version_matches(requested: &str) -> bool {
+    let requested = requested.trim_start_matches('v');
+    requested == MOON_CONFIG_CRATE_VERSION || MOON_CONFIG_CRATE_VERSION.starts_with(&format!("{}.", requested))
+}
+
+/// Serve the HTTP API until interrupted
+#[cfg(feature = "server")]
+pub async fn serve_http(addr: &str) -> Result<(), CliError> {
+    use axum::Router;
+    use axum::extract::{Path, Query, State};
+    use axum::http::StatusCode;
+    use axum::response::{IntoResponse, Response};
+    use axum::routing::{get, post};
+    use serde_json::{Value, json};
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    use crate::daemon::DaemonState;
+
+    type Params = Query<HashMap<String, String>>;
+
+    fn error_response(err: RpcError) -> Response {
+        let status = StatusCode::from_u16(error_status(&err)).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        let message = match &err {
+            RpcError::MethodNotFound(method) => format!("Not found: {}", method),
+            RpcError::InvalidParams(message) => message.clone(),
+            RpcError::Cli(e) => e.to_string(),
+        };
+        (status, message).into_response()
+    }
+
+    fn text_result(result: Result<Value, RpcError>) -> Response {
+        match result {
+            Ok(Value::String(text)) => text.into_response(),
+            Ok(other) => axum::Json(other).into_response(),
+            Err(err) => error_response(err),
+        }
+    }
+
+    async fn schema(
+        State(state): State<Arc<DaemonState>>,
+        Path(config_type): Path<String>,
+        Query(query): Params,
+    ) -> Response {
+        if let Some(moon) = query.get("moon")
+            && !moon_version_matches(moon)
+        {
+            return (
+                StatusCode::NOT_FOUND,
+                format!(
+                    "This server generates schemas for moon_config {}, not {}",
+                    MOON_CONFIG_CRATE_VERSION, moon
+                ),
+            )
+                .into_response();
+        }
+
+        let format = query.get("format").map(String::as_str).unwrap_or("json-schema");
+        let params = json!({ "configType": config_type, "format": format });
+        text_result(state.call("generate", &params).await)
+    }
+
+    async fn validate(State(state): State<Arc<DaemonState>>, Query(query): Params, body: String) -> Response {
+        if let Err(err) = check_input_format(query.get("from").map(String::as_str)) {
+            return error_response(err);
+        }
+        let mut params = json!(query);
+        params["content"] = Value::String(body);
+
+        match state.call("validate", &params).await {
+            Ok(result) => axum::Json(result).into_response(),
+            Err(RpcError::Cli(err)) if matches!(err, CliError::ValidationError { .. }) => {
+                use miette::Diagnostic;
+                let body = json!({
+                    "valid": false,
+                    "error": err.to_string(),
+                    "help": err.help().map(|h| h.to_string()),
+                    "cause": std::error::Error::source(&err).map(|s| s.to_string()),
+                });
+                (StatusCode::UNPROCESSABLE_ENTITY, axum::Json(body)).into_response()
+            }
+            Err(err) => error_response(err),
+        }
+    }
+
+    async fn convert(State(state): State<Arc<DaemonState>>, Query(query): Params, body: String) -> Response {
+        if let Err(err) = check_input_format(query.get("from").map(String::as_str)) {
+            return error_response(err);
+        }
+        let mut params = json!(query);
+        params["content"] = Value::String(body);
+        text_result(state.call("convert", &params).await)
+    }
+
+    let app = Router::new()
+        .route("/health", get(|| async { "ok" }))
+        .route("/schemas/:config_type", get(schema))
+        .route("/validate", post(validate))
+        .route("/convert", post(convert))
+        .with_state(Arc::new(DaemonState::new()));
+
+    let addr = normalize_listen_addr(addr);
+    let listener = tokio::net::TcpListener::bind(&addr)
+        .await
+        .map_err(|e| CliError::IoError {
+            context: format!("Binding HTTP listener: {}", addr),
+            source: e,
+        })?;
+    tracing::info!("HTTP server listening on {}", addr);
+
+    axum::serve(listener, app).await.map_err(|e| CliError::IoError {
+        context: "Running HTTP server".to_string(),
+        source: e,
+    })
+}
+
+/// Serve the HTTP API until interrupted
+#[cfg(not(feature = "server"))]
+pub async fn serve_http(_addr: &str) -> Result<(), CliError> {
+    Err(CliError::Generic(
+        "spklr was built without the `server` feature; rebuild with `--features server` to use `serve --http`".to_string(),
+    ))
+}
@@ -0,0 +1,143 @@
+//! Init Module for Space Pklr
+//!
+//! What `spklr init` writes into a Moon repo. Schemas for every config type are
+//! generated as one Pkl package in `.moon/pkl/` (see
+//! [`CompositionManifest::moon_package`](crate::composition::CompositionManifest::moon_package)),
+//! so each module is named after its config type: `Project.pkl`, `Workspace.pkl`, ...
+//!
+//! A repo without a workspace config gets a starter `.moon/workspace.pkl`, and one
+//! without any project config a starter `moon.pkl` at its root; both amend their schema and
+//! only hold commented-out examples, so they evaluate as they are. With `--convert`,
+//! the YAML configs found by [`yaml_configs`] are converted to Pkl next to the
+//! originals instead.
+
+use std::path::{Path, PathBuf};
+
+use crate::types::MoonConfig;
+
+/// Where `spklr init` generates the schemas, relative to the workspace root
+pub const DEFAULT_SCHEMA_DIR: &str = ".moon/pkl";
+
+/// Name of the generated schema package
+pub const PACKAGE_NAME: &str = "moon";
+
+/// Directories never searched for project configs
+const SKIPPED_DIRS: [&str; 3] = ["node_modules", "target", "dist"];
+
+/// Where the starter config for `config_type` goes, for the types that get one
+pub fn starter_path(root: &Path, config_type: MoonConfig) -> Option<PathBuf> {
+    match config_type {
+        MoonConfig::Workspace => Some(root.join(".moon").join("workspace.pkl")),
+        MoonConfig::Project => Some(root.join("moon.pkl")),
+        _ => None,
+    }
+}
+
+/// Starter config for `config_type`, starting with `header` (its `amends` line)
+pub fn starter_config(config_type: MoonConfig, header: &str) -> String {
+    let body = match config_type {
+        MoonConfig::Workspace => {
+            "// Where Moon finds projects: globs, or a mapping of project IDs to paths\n\
+             // projects {\n\
+             //   \"apps/*\"\n\
+             //   \"packages/*\"\n\
+             // }\n\
+             \n\
+             // vcs {\n\
+             //   defaultBranch = \"main\"\n\
+             // }\n"
+        }
+        MoonConfig::Project => {
+            "// language = \"typescript\"\n\
+             // tags { \"app\" }\n\
+             \n\
+             // tasks {\n\
+             //   [\"build\"] {\n\
+             //     command = \"npm run build\"\n\
+             //     outputs { \"dist\" }\n\
+             //   }\n\
+             // }\n"
+        }
+        _ => "",
+    };
+    format!("{}\n\n{}", header, body)
+}
+
+/// Moon's YAML configs under `root`, with their config types, in path order
+///
+/// Covers `.moon/workspace.yml`, `.moon/toolchain.yml`, `.moon/tasks.yml` and the
+/// scoped task files under `.moon/tasks/`, plus every `moon.yml` and `template.yml`
+/// outside hidden directories and `node_modules`/`target`/`dist`.
+pub fn yaml_configs(root: &Path) -> Vec<(MoonConfig, PathBuf)> {
+    let mut configs = Vec::new();
+    let moon_dir = root.join(".moon");
+    for (name, config_type) in [("workspace", MoonConfig::Workspace), ("toolchain", MoonConfig::Toolchain), ("tasks", MoonConfig::Task)] {
+        configs.extend(yaml_file(&moon_dir, name).map(|path| (config_type, path)));
+    }
+    let mut task_files = Vec::new();
+    collect_yaml(&moon_dir.join("tasks"), &mut task_files);
+    configs.extend(task_files.into_iter().map(|path| (MoonConfig::Task, path)));
+    find_configs(root, &["yml", "yaml"], &mut configs);
+    configs.sort_by(|a, b| a.1.cmp(&b.1));
+    configs
+}
+
+/// Whether any project under `root` has a config, in any format
+pub fn has_project_config(root: &Path) -> bool {
+    let mut configs = Vec::new();
+    find_configs(root, &["yml", "yaml", "pkl"], &mut configs);
+    configs.iter().any(|(config_type, _)| *config_type == MoonConfig::Project)
+}
+
+/// Where the Pkl version of a YAML config goes
+pub fn pkl_path(yaml: &Path) -> PathBuf {
+    yaml.with_extension("pkl")
+}
+
+/// `<dir>/<name>.yml` or `.yaml`, whichever exists
+fn yaml_file(dir: &Path, name: &str) -> Option<PathBuf> {
+    config_file(dir, name, &["yml", "yaml"])
+}
+
+/// `<dir>/<name>.<ext>` for the first of `extensions` that exists
+fn config_file(dir: &Path, name: &str, extensions: &[&str]) -> Option<PathBuf> {
+    extensions
+        .iter()
+        .map(|ext| dir.join(format!("{}.{}", name, ext)))
+        .find(|path| path.is_file())
+}
+
+/// Every YAML file under `dir`, recursively
+fn collect_yaml(dir: &Path, files: &mut Vec<PathBuf>) {
+    for path in sorted_entries(dir) {
+        if path.is_dir() {
+            collect_yaml(&path, files);
+        } else if path.extension().is_some_and(|ext| ext == "yml" || ext == "yaml") {
+            files.push(path);
+        }
+    }
+}
+
+/// Project and template configs under `dir` with one of `extensions`
+fn find_configs(dir: &Path, extensions: &[&str], configs: &mut Vec<(MoonConfig, PathBuf)>) {
+    for (name, config_type) in [("moon", MoonConfig::Project), ("template", MoonConfig::Template)] {
+        configs.extend(config_file(dir, name, extensions).map(|path| (config_type, path)));
+    }
+    for path in sorted_entries(dir).into_iter().filter(|path| path.is_dir()) {
+        let name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+        if !name.starts_with('.') && !SKIPPED_DIRS.contains(&name) {
+            find_configs(&path, extensions, configs);
+        }
+    }
+}
+
+fn sorted_entries(dir: &Path) -> Vec<PathBuf> {
+    let mut entries: Vec<PathBuf> = std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .collect();
+    entries.sort();
+    entries
+}
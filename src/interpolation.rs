@@ -0,0 +1,194 @@
+//! Interpolation Module for Space Pklr
+//!
+//! String values in `spklr.toml` can read environment variables, so one committed tool
+//! config serves developer machines and CI alike. Interpolation is off unless the file
+//! opts in with `[env]`:
+//!
+//! ```toml
+//! [env]
+//! interpolate = true
+//! allow = ["PKL_MIRROR", "CI_*"]   # optional; other variables are an error
+//!
+//! [download]
+//! mirrors = ["${env:PKL_MIRROR:-https://artifacts.example.com/pkl}"]
+//!
+//! [ci]
+//! github_token = "${env:CI_BOT_TOKEN}"
+//! ```
+//!
+//! `${env:NAME}` is replaced by the variable's value; a variable that isn't set is an
+//! error unless a fallback follows `:-`. `$${` writes a literal `${`. Without
+//! `interpolate = true`, values are used exactly as written.
+//!
+//! Substituted values may be credentials, so they're masked as `[REDACTED]` in log
+//! output, download messages, crash reports, and run reports (see [`redact`]). Values
+//! shorter than [`MIN_REDACTED_LEN`] are left alone; masking every `1` or `on` would
+//! garble the logs without hiding anything.
+
+use serde::Deserialize;
+use std::sync::Mutex;
+
+/// Replacement for substituted values in logs and reports
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Shortest substituted value that gets masked
+pub const MIN_REDACTED_LEN: usize = 4;
+
+/// Values substituted from the environment during this run
+static SUBSTITUTED: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// `[env]` settings
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct EnvConfig {
+    /// Replace `${env:NAME}` references in string values
+    pub interpolate: bool,
+    /// Variables references may read; a trailing `*` matches a prefix. Empty allows any.
+    pub allow: Vec<String>,
+}
+
+impl EnvConfig {
+    /// Whether `name` may be read
+    pub fn allows(&self, name: &str) -> bool {
+        self.allow.is_empty()
+            || self.allow.iter().any(|pattern| match pattern.strip_suffix('*') {
+                Some(prefix) => name.starts_with(prefix),
+                None => pattern == name,
+            })
+    }
+}
+
+/// Replace `${env:...}` references in every string of `value`, looking variables up with `lookup`
+///
+/// Errors name the key holding the bad reference, e.g. `download.mirrors[0]`.
+pub fn interpolate(
+    value: &mut toml::Value,
+    config: &EnvConfig,
+    lookup: &impl Fn(&str) -> Option<String>,
+) -> Result<(), String> {
+    interpolate_at(value, "", config, lookup)
+}
+
+fn interpolate_at(
+    value: &mut toml::Value,
+    key: &str,
+    config: &EnvConfig,
+    lookup: &impl Fn(&str) -> Option<String>,
+) -> Result<(), String> {
+    match value {
+        toml::Value::String(text) => {
+            *text = interpolate_str(text, config, lookup).map_err(|e| format!("{}: {}", key, e))?;
+        }
+        toml::Value::Array(items) => {
+            for (index, item) in items.iter_mut().enumerate() {
+                interpolate_at(item, &format!("{}[{}]", key, index), config, lookup)?;
+            }
+        }
+        toml::Value::Table(table) => {
+            for (name, item) in table.iter_mut() {
+                let key = if key.is_empty() { name.clone() } else { format!("{}.{}", key, name) };
+                interpolate_at(item, &key, config, lookup)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// `text` with its `${env:...}` references replaced
+pub fn interpolate_str(
+    text: &str,
+    config: &EnvConfig,
+    lookup: &impl Fn(&str) -> Option<String>,
+) -> Result<String, String> {
+    let mut output = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('$') {
+        output.push_str(&rest[..start]);
+        rest = &rest[start..];
+        if let Some(escaped) = rest.strip_prefix("$${") {
+            output.push_str("${");
+            rest = escaped;
+            continue;
+        }
+        let Some(reference) = rest.strip_prefix("${env:") else {
+            output.push('$');
+            rest = &rest[1..];
+            continue;
+        };
+        let end = reference
+            .find('}')
+            .ok_or_else(|| format!("unterminated reference '{}'", rest))?;
+        let (name, fallback) = match reference[..end].split_once(":-") {
+            Some((name, fallback)) => (name, Some(fallback)),
+            None => (&reference[..end], None),
+        };
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(format!("invalid variable name '{}'", name));
+        }
+        if !config.allows(name) {
+            return Err(format!("${{env:{}}} isn't in [env] allow", name));
+        }
+        match lookup(name) {
+            Some(value) => {
+                record_substituted(&value);
+                output.push_str(&value);
+            }
+            None => match fallback {
+                Some(fallback) => output.push_str(fallback),
+                None => return Err(format!("environment variable {} is not set", name)),
+            },
+        }
+        rest = &reference[end + 1..];
+    }
+    output.push_str(rest);
+    Ok(output)
+}
+
+/// Whether any string in `value` has an `${env:...}` reference
+pub fn has_references(value: &toml::Value) -> bool {
+    match value {
+        toml::Value::String(text) => text.contains("${env:"),
+        toml::Value::Array(items) => items.iter().any(has_references),
+        toml::Value::Table(table) => table.values().any(has_references),
+        _ => false,
+    }
+}
+
+fn record_substituted(value: &str) {
+    if value.len() < MIN_REDACTED_LEN {
+        return;
+    }
+    let mut substituted = SUBSTITUTED.lock().unwrap_or_else(|e| e.into_inner());
+    if !substituted.iter().any(|known| known == value) {
+        substituted.push(value.to_string());
+        // Longer values first, so one containing another is masked whole
+        substituted.sort_by_key(|known| std::cmp::Reverse(known.len()));
+    }
+}
+
+/// `text` with every value substituted from the environment masked
+pub fn redact(text: &str) -> String {
+    let substituted = SUBSTITUTED.lock().unwrap_or_else(|e| e.into_inner());
+    substituted
+        .iter()
+        .fold(text.to_string(), |text, value| text.replace(value.as_str(), REDACTED))
+}
+
+/// Writer that masks substituted values, for log output
+pub struct RedactingWriter<W>(pub W);
+
+impl<W: std::io::Write> std::io::Write for RedactingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        // Log lines are formatted whole before being written, so a value is never split
+        match std::str::from_utf8(buf) {
+            Ok(text) => self.0.write_all(redact(text).as_bytes()),
+            Err(_) => self.0.write_all(buf),
+        }?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.0.flush()
+    }
+}
@@ -0,0 +1,260 @@
+//! JSONC Module for Space Pklr
+//!
+//! Moon configs kept as `.jsonc` (or `.json5`) may contain `//` and `/* */` comments
+//! and trailing commas. [`strip`] blanks those out so the rest parses as plain JSON,
+//! keeping every line and column where it was for error messages. JSON5's other
+//! extensions (unquoted keys, single-quoted strings, hex numbers) aren't supported.
+//!
+//! [`comments`] collects the comments by the property they describe, so a conversion
+//! to Pkl can keep them as doc comments:
+//!
+//! ```jsonc
+//! {
+//!   // Build the crate
+//!   "command": "cargo build",
+//!   "deps": ["^:build"], // upstream builds first
+//! }
+//! ```
+//!
+//! A comment on the lines above a key belongs to that key; one after a value on the
+//! same line belongs to the property it ends. Comments above the opening `{` describe
+//! the whole file (the empty path), and comments before a closing bracket describe
+//! nothing and are dropped.
+
+use std::collections::BTreeMap;
+
+use crate::config_path::ConfigPath;
+
+/// `input` with comments and trailing commas replaced by spaces
+pub fn strip(input: &str) -> String {
+    let chars: Vec<char> = input.chars().collect();
+    let mut output = String::with_capacity(input.len());
+    // Index in `output` of a comma that only whitespace or comments have followed so far
+    let mut pending_comma: Option<usize> = None;
+    let mut index = 0;
+
+    while index < chars.len() {
+        match chars[index] {
+            '"' => {
+                pending_comma = None;
+                let end = string_end(&chars, index);
+                output.extend(&chars[index..end]);
+                index = end;
+            }
+            '/' if chars.get(index + 1) == Some(&'/') => {
+                let end = chars[index..].iter().position(|&c| c == '\n').map_or(chars.len(), |offset| index + offset);
+                blank(&mut output, &chars[index..end]);
+                index = end;
+            }
+            '/' if chars.get(index + 1) == Some(&'*') => {
+                let end = block_comment_end(&chars, index);
+                blank(&mut output, &chars[index..end]);
+                index = end;
+            }
+            ',' => {
+                pending_comma = Some(output.len());
+                output.push(',');
+                index += 1;
+            }
+            c @ ('}' | ']') => {
+                if let Some(comma) = pending_comma.take() {
+                    output.replace_range(comma..=comma, " ");
+                }
+                output.push(c);
+                index += 1;
+            }
+            c => {
+                if !c.is_whitespace() {
+                    pending_comma = None;
+                }
+                output.push(c);
+                index += 1;
+            }
+        }
+    }
+    output
+}
+
+/// Comments in `input`, keyed by the dotted path of the property each one describes
+pub fn comments(input: &str) -> BTreeMap<String, String> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut found: BTreeMap<String, String> = BTreeMap::new();
+    let mut frames: Vec<Frame> = Vec::new();
+    let mut pending: Vec<String> = Vec::new();
+    // Property whose value is on the current line, for trailing comments
+    let mut line_owner: Option<String> = None;
+    let mut index = 0;
+
+    while index < chars.len() {
+        match chars[index] {
+            '\n' => {
+                line_owner = None;
+                index += 1;
+            }
+            '/' if matches!(chars.get(index + 1), Some('/') | Some('*')) => {
+                let end = if chars[index + 1] == '/' {
+                    chars[index..].iter().position(|&c| c == '\n').map_or(chars.len(), |offset| index + offset)
+                } else {
+                    block_comment_end(&chars, index)
+                };
+                let text = comment_text(&chars[index..end]);
+                if !text.is_empty() {
+                    match &line_owner {
+                        Some(path) => attach(&mut found, path.clone(), &text),
+                        None => pending.push(text),
+                    }
+                }
+                index = end;
+            }
+            '{' | '[' => {
+                if frames.is_empty() && !pending.is_empty() {
+                    // A header comment describes the whole file
+                    attach(&mut found, String::new(), &pending.join("\n"));
+                    pending.clear();
+                }
+                start_value(&frames, &mut pending, &mut found, &mut line_owner);
+                frames.push(match chars[index] {
+                    '{' => Frame::Object { key: None, expect_key: true },
+                    _ => Frame::Array { index: 0 },
+                });
+                index += 1;
+            }
+            '}' | ']' => {
+                frames.pop();
+                pending.clear();
+                // A comment after the closing bracket is about the value it ends
+                line_owner = (!frames.is_empty()).then(|| path_of(&frames));
+                index += 1;
+            }
+            ',' => {
+                match frames.last_mut() {
+                    Some(Frame::Object { expect_key, .. }) => *expect_key = true,
+                    Some(Frame::Array { index }) => *index += 1,
+                    None => {}
+                }
+                index += 1;
+            }
+            ':' => {
+                if let Some(Frame::Object { expect_key, .. }) = frames.last_mut() {
+                    *expect_key = false;
+                }
+                index += 1;
+            }
+            '"' => {
+                let end = string_end(&chars, index);
+                let is_key = matches!(frames.last(), Some(Frame::Object { expect_key: true, .. }));
+                if is_key {
+                    let key = serde_json::from_str::<String>(&chars[index..end].iter().collect::<String>())
+                        .unwrap_or_else(|_| chars[index + 1..end.saturating_sub(1)].iter().collect());
+                    if let Some(Frame::Object { key: current, .. }) = frames.last_mut() {
+                        *current = Some(key);
+                    }
+                    let path = path_of(&frames);
+                    if !pending.is_empty() {
+                        attach(&mut found, path.clone(), &pending.join("\n"));
+                        pending.clear();
+                    }
+                    line_owner = Some(path);
+                } else {
+                    start_value(&frames, &mut pending, &mut found, &mut line_owner);
+                }
+                index = end;
+            }
+            c if c.is_whitespace() => index += 1,
+            _ => {
+                // A bare scalar: number, `true`, `false`, or `null`
+                start_value(&frames, &mut pending, &mut found, &mut line_owner);
+                index += 1;
+                while index < chars.len() && !matches!(chars[index], ',' | '}' | ']' | '/') && !chars[index].is_whitespace() {
+                    index += 1;
+                }
+            }
+        }
+    }
+    found
+}
+
+#[derive(Debug)]
+enum Frame {
+    Object { key: Option<String>, expect_key: bool },
+    Array { index: usize },
+}
+
+/// Dotted path of the innermost property or element
+fn path_of(frames: &[Frame]) -> String {
+    let segments = frames.iter().filter_map(|frame| match frame {
+        Frame::Object { key, .. } => key.clone(),
+        Frame::Array { index } => Some(index.to_string()),
+    });
+    ConfigPath::from_segments(segments).to_string()
+}
+
+/// A value starting inside an array claims the comments above it
+fn start_value(
+    frames: &[Frame],
+    pending: &mut Vec<String>,
+    found: &mut BTreeMap<String, String>,
+    line_owner: &mut Option<String>,
+) {
+    if !matches!(frames.last(), Some(Frame::Array { .. })) {
+        return;
+    }
+    let path = path_of(frames);
+    if !pending.is_empty() {
+        attach(found, path.clone(), &pending.join("\n"));
+        pending.clear();
+    }
+    *line_owner = Some(path);
+}
+
+/// Add `text` to the comment for `path`
+fn attach(found: &mut BTreeMap<String, String>, path: String, text: &str) {
+    found
+        .entry(path)
+        .and_modify(|existing| {
+            existing.push('\n');
+            existing.push_str(text);
+        })
+        .or_insert_with(|| text.to_string());
+}
+
+/// Index just past the string starting at `start`
+fn string_end(chars: &[char], start: usize) -> usize {
+    let mut index = start + 1;
+    while index < chars.len() {
+        match chars[index] {
+            '\\' => index += 2,
+            '"' => return index + 1,
+            _ => index += 1,
+        }
+    }
+    chars.len()
+}
+
+/// Index just past the `/* */` comment starting at `start`
+fn block_comment_end(chars: &[char], start: usize) -> usize {
+    (start + 2..chars.len().saturating_sub(1))
+        .find(|&index| chars[index] == '*' && chars[index + 1] == '/')
+        .map_or(chars.len(), |index| index + 2)
+}
+
+/// Push spaces in place of `comment`, keeping its line breaks
+fn blank(output: &mut String, comment: &[char]) {
+    output.extend(comment.iter().map(|&c| if c == '\n' { '\n' } else { ' ' }));
+}
+
+/// The text of a comment, without its markers or `*` gutters
+fn comment_text(comment: &[char]) -> String {
+    let comment: String = comment.iter().collect();
+    let body = match comment.strip_prefix("//") {
+        Some(line) => line.to_string(),
+        None => comment.trim_start_matches("/*").trim_end_matches("*/").to_string(),
+    };
+    body.lines()
+        .map(|line| line.trim().trim_start_matches('*').trim())
+        .skip_while(|line| line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim_end()
+        .to_string()
+}
@@ -2,12 +2,97 @@
 //!
 //! This library provides the core functionality for the Space Pklr tool,
 //! including configuration conversion, schema generation, and Pkl tooling integration.
+//! `use space_pklr::prelude::*` brings in the common entry points (see [`prelude`]).
+//!
+//! # Cargo features
+//!
+//! The defaults build the full `spklr` CLI. Embedders that only need the library can
+//! leave out the command line and everything heavy with the `minimal` profile:
+//!
+//! ```toml
+//! space-pklr = { version = "0.1", default-features = false, features = ["minimal"] }
+//! ```
+//!
+//! | Feature | Adds |
+//! | --- | --- |
+//! | `minimal` | Config loading, conversion, validation, schema generation, and running an installed Pkl CLI |
+//! | `network` | HTTP client for Pkl CLI downloads, pull request comments, and docgen link checks |
+//! | `cli` | The `spklr` binary ([`cli_app`] and [`commands`]); implies `minimal` and `network` |
+//! | `docgen` | Markdown reference docs ([`docgen`]) |
+//! | `jinja_templates` | MiniJinja engine for `[templates]` |
+//! | `scripting` | Rhai transform scripts |
+//! | `wasm_plugins` | WASM renderer and codec plugins |
+//! | `server` | `spklr serve --http` |
+//! | `native_pkl` | Reading literal-only Pkl configs without the Pkl CLI ([`native_pkl`]) |
+//!
+//! Without `network`, installing the Pkl CLI needs a local archive
+//! ([`pkl_tooling::install_pkl_from_archive`]). Optional engines that weren't built in
+//! report an error naming the missing feature when they're asked for.
 
+pub mod atomic_write;
+pub mod autofix;
+pub mod batch;
+pub mod checksums;
+pub mod ci;
+pub mod cli_defaults;
+#[cfg(feature = "cli")]
 pub mod cli_app;
+#[cfg(feature = "cli")]
 pub mod commands;
+pub mod compatibility;
+pub mod composition;
+pub mod concurrency;
+pub mod config_path;
+pub mod config_processor;
+pub mod convert;
+pub mod corpus;
+pub mod crash;
+pub mod daemon;
+pub mod deprecations;
+#[cfg(feature = "docgen")]
+pub mod docgen;
+pub mod download;
+pub mod effective;
+pub mod env_tokens;
+pub mod eval_cache;
+pub mod extension_points;
+pub mod fragments;
+pub mod hermetic;
+pub mod http_server;
+pub mod init;
+pub mod interpolation;
+pub mod jsonc;
+pub mod lock;
+pub mod merge;
+pub mod moon_schema;
+pub mod msgpack;
+#[cfg(feature = "native_pkl")]
+pub mod native_pkl;
+pub mod network;
+pub mod partials;
+pub mod pkl_schema;
+pub mod pkl_server;
 pub mod pkl_tooling;
+pub mod policy;
+pub mod prelude;
+pub mod read_only;
+pub mod redaction;
+pub mod report;
+pub mod schema_diff;
+pub mod scripting;
+pub mod selection;
+pub mod self_test;
+pub mod self_update;
+pub mod task_graph;
+pub mod templates;
+pub mod timings;
+pub mod tool_config;
 pub mod types;
+pub mod upgrade;
+pub mod versions;
+pub mod wasm_plugins;
 
 // Re-export commonly used types
-pub use types::{CliError, InternalError, Result, SchemaFormat, LoadedConfig, MoonConfig, TypeMap, EnumTranslation, OpenStructs, ConfigTranslation, OptionalFormat, PropertyDefault, ensure_file_exists, ensure_output_writable, pkl_execution_error};
+pub use types::{CliError, InternalError, Result, SchemaFormat, LoadedConfig, MoonConfig, TypeMap, EnumTranslation, OpenStructs, ConfigTranslation, ConfigHeader, OptionalFormat, PropertyDefault, ensure_file_exists, ensure_output_writable, pkl_execution_error};
 pub use pkl_tooling::{CompatibilityReport, PklCli, PklSource};
+pub use pkl_schema::{GeneratorConfig, PklModule, SchemaGenerator};
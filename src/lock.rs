@@ -0,0 +1,141 @@
+//! Lock Module for Space Pklr
+//!
+//! Advisory locks that keep concurrent spklr runs (parallel CI jobs, a watcher plus a
+//! manual run) from writing the same output directory or cache at once. Lock files live
+//! in spklr's cache directory (`<cache>/spklr/locks`), named by a hash of the locked
+//! path's canonical form, so nothing is left next to users' outputs; each records the
+//! holder's pid for the diagnostic. By default a held lock fails fast; the global
+//! `--wait` flag blocks until it's released instead.
+
+use std::fs::{File, TryLockError};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::types::CliError;
+
+static WAIT_FOR_LOCKS: AtomicBool = AtomicBool::new(false);
+
+/// Block on held locks instead of failing (`--wait`)
+pub fn set_wait(wait: bool) {
+    WAIT_FOR_LOCKS.store(wait, Ordering::Relaxed);
+}
+
+/// An exclusive advisory lock, released when dropped
+#[derive(Debug)]
+pub struct PathLock {
+    /// `None` for the no-op locks handed out in hermetic runs
+    file: Option<File>,
+    path: PathBuf,
+}
+
+impl PathLock {
+    /// Lock `target` (a file or directory, which need not exist yet)
+    pub fn acquire(target: &Path) -> Result<Self, CliError> {
+        Self::acquire_with(target, WAIT_FOR_LOCKS.load(Ordering::Relaxed))
+    }
+
+    /// Lock `target`, waiting for other holders if `wait` is set
+    ///
+    /// Hermetic runs rely on the build system for exclusivity and get a no-op lock.
+    pub fn acquire_with(target: &Path, wait: bool) -> Result<Self, CliError> {
+        crate::read_only::ensure_allowed(format!("lock {}", target.display()))?;
+        let path = lock_path(target);
+        if crate::hermetic::is_enabled() {
+            return Ok(Self { file: None, path });
+        }
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent).map_err(|e| CliError::IoError {
+                context: format!("Creating directory for lock: {}", parent.display()),
+                source: e,
+            })?;
+        }
+
+        let mut file = File::options()
+            .create(true)
+            .truncate(false)
+            .read(true)
+            .write(true)
+            .open(&path)
+            .map_err(|e| CliError::IoError {
+                context: format!("Opening lock file: {}", path.display()),
+                source: e,
+            })?;
+
+        match file.try_lock() {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) if wait => {
+                eprintln!("⏳ Waiting for another spklr process to release {}", target.display());
+                file.lock().map_err(|e| CliError::IoError {
+                    context: format!("Waiting for lock: {}", path.display()),
+                    source: e,
+                })?;
+            }
+            Err(TryLockError::WouldBlock) => {
+                let holder = std::fs::read_to_string(&path)
+                    .ok()
+                    .map(|pid| pid.trim().to_string())
+                    .filter(|pid| !pid.is_empty())
+                    .map(|pid| format!("pid {}", pid))
+                    .unwrap_or_else(|| "unknown pid".to_string());
+                return Err(CliError::LockHeld {
+                    path: target.to_path_buf(),
+                    holder,
+                });
+            }
+            Err(TryLockError::Error(e)) => {
+                return Err(CliError::IoError {
+                    context: format!("Locking: {}", path.display()),
+                    source: e,
+                });
+            }
+        }
+
+        // Record the holder for other processes' diagnostics
+        let _ = file.set_len(0);
+        let _ = write!(file, "{}", std::process::id());
+        let _ = file.flush();
+
+        Ok(Self {
+            file: Some(file),
+            path,
+        })
+    }
+
+    /// Path of the lock file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for PathLock {
+    fn drop(&mut self) {
+        if let Some(file) = &self.file {
+            let _ = file.set_len(0);
+            let _ = file.unlock();
+        }
+    }
+}
+
+/// Lock file for `target` in the cache directory, keyed by its canonical path
+fn lock_path(target: &Path) -> PathBuf {
+    let canonical = canonical_path(target);
+    let digest = crate::download::sha256_hex(canonical.to_string_lossy().as_bytes());
+    lock_dir().join(format!("{}.lock", digest))
+}
+
+fn lock_dir() -> PathBuf {
+    dirs::cache_dir().unwrap_or_else(std::env::temp_dir).join("spklr").join("locks")
+}
+
+/// `target` made absolute with symlinks resolved, as far as it exists yet
+fn canonical_path(target: &Path) -> PathBuf {
+    if let Ok(canonical) = std::fs::canonicalize(target) {
+        return canonical;
+    }
+    let absolute = std::path::absolute(target).unwrap_or_else(|_| target.to_path_buf());
+    match (absolute.parent(), absolute.file_name()) {
+        (Some(parent), Some(name)) => canonical_path(parent).join(name),
+        _ => absolute,
+    }
+}
@@ -2,22 +2,93 @@
 //!
 //! This is the main entry point for the Space Pklr tool.
 
+mod atomic_write;
+mod autofix;
+mod batch;
+mod checksums;
+mod ci;
+mod cli_defaults;
 mod cli_app;
+mod compatibility;
+mod composition;
+mod concurrency;
+mod config_path;
+mod config_processor;
+mod convert;
+mod corpus;
+mod crash;
+mod daemon;
+mod deprecations;
+#[cfg(feature = "docgen")]
+mod docgen;
+mod download;
+mod effective;
+mod env_tokens;
+mod eval_cache;
+mod extension_points;
+mod fragments;
+mod hermetic;
+mod http_server;
+mod init;
+mod interpolation;
+mod jsonc;
+mod lock;
+mod merge;
+mod moon_schema;
+mod msgpack;
+#[cfg(feature = "native_pkl")]
+mod native_pkl;
+mod network;
+mod partials;
+mod pkl_schema;
+mod pkl_server;
 mod pkl_tooling;
+mod policy;
+mod read_only;
+mod redaction;
+mod report;
+mod schema_diff;
+mod scripting;
+mod selection;
+mod self_test;
+mod self_update;
+mod task_graph;
+mod templates;
+mod timings;
+mod tool_config;
 mod types;
+mod upgrade;
+mod versions;
+mod wasm_plugins;
 mod commands;
 
 use miette::Result;
 
-#[tokio::main]
-async fn main() -> Result<()> {
+fn main() -> Result<()> {
+    // Turn panics into crash report files instead of raw traces
+    crash::install_panic_hook();
+
     // Initialize comprehensive logging/tracing
     init_tracing()?;
 
+    // Size the worker pool from --jobs
+    concurrency::set_cli_jobs(cli_app::requested_jobs());
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(concurrency::jobs(None))
+        .enable_all()
+        .build()
+        .map_err(|e| miette::miette!("Failed to start the async runtime: {}", e))?
+        .block_on(run_main())
+}
+
+async fn run_main() -> Result<()> {
+    // Ctrl-C cancels the run from its own thread and cleans up partial writes
+    atomic_write::install_interrupt_handler()?;
+
     // Global error handling with rich context
     if let Err(error) = run_cli().await {
-        // Use miette for rich error reporting
-        eprintln!("{:?}", error);
+        // Use miette for rich error reporting, masking values substituted from the environment
+        eprintln!("{}", interpolation::redact(&format!("{:?}", error)));
         std::process::exit(1);
     }
 
@@ -25,6 +96,8 @@ async fn main() -> Result<()> {
 }
 
 /// Initialize enhanced tracing with structured logging
+///
+/// Logs go to stderr so they never mix with configs or schemas written to stdout.
 fn init_tracing() -> Result<()> {
     use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
 
@@ -41,6 +114,7 @@ fn init_tracing() -> Result<()> {
                 .with_file(true)
                 .with_line_number(true)
                 .with_ansi(true)
+                .with_writer(|| interpolation::RedactingWriter(std::io::stderr()))
         )
         .with(filter)
         .init();
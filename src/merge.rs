@@ -0,0 +1,175 @@
+//! Merge Module for Space Pklr
+//!
+//! Simulates how Moon layers configs (`extends`, inherited task files) so users can
+//! check what a layered setup actually produces:
+//!
+//! - objects merge key by key, the overlay winning on conflicts
+//! - arrays and scalars are replaced by the overlay
+//! - tasks merge by id using the task's own merge strategies (`options.merge`,
+//!   `options.mergeArgs`, `mergeDeps`, `mergeEnv`, `mergeInputs`, `mergeOutputs`),
+//!   which default to `append` as in Moon
+
+use serde_json::{Map, Value};
+use std::fmt::Display;
+use std::str::FromStr;
+
+use crate::types::CliError;
+
+/// How a task field combines an inherited (base) value with an overlay value
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MergeStrategy {
+    /// Base first, then overlay; overlay keys win
+    #[default]
+    Append,
+    /// Overlay first, then base; base keys win
+    Prepend,
+    /// Overlay only
+    Replace,
+    /// Base only
+    Preserve,
+}
+
+impl FromStr for MergeStrategy {
+    type Err = CliError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "append" => Ok(MergeStrategy::Append),
+            "prepend" => Ok(MergeStrategy::Prepend),
+            "replace" => Ok(MergeStrategy::Replace),
+            "preserve" => Ok(MergeStrategy::Preserve),
+            _ => Err(CliError::UnsupportedFormat {
+                format: s.to_string(),
+                available: vec!["append", "prepend", "replace", "preserve"],
+            }),
+        }
+    }
+}
+
+impl Display for MergeStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MergeStrategy::Append => write!(f, "append"),
+            MergeStrategy::Prepend => write!(f, "prepend"),
+            MergeStrategy::Replace => write!(f, "replace"),
+            MergeStrategy::Preserve => write!(f, "preserve"),
+        }
+    }
+}
+
+/// Task fields governed by a merge strategy, and the option that sets it
+const TASK_MERGE_FIELDS: [(&str, &str); 5] = [
+    ("args", "mergeArgs"),
+    ("deps", "mergeDeps"),
+    ("env", "mergeEnv"),
+    ("inputs", "mergeInputs"),
+    ("outputs", "mergeOutputs"),
+];
+
+/// Merge `overlay` over `base`
+pub fn merge_configs(base: &Value, overlay: &Value) -> Value {
+    match (base, overlay) {
+        (Value::Object(base), Value::Object(overlay)) => {
+            let mut merged = base.clone();
+            for (key, value) in overlay {
+                let combined = match (key.as_str(), merged.get(key)) {
+                    ("tasks", Some(Value::Object(base_tasks))) => match value {
+                        Value::Object(overlay_tasks) => Value::Object(merge_tasks(base_tasks, overlay_tasks)),
+                        other => other.clone(),
+                    },
+                    (_, Some(existing)) => merge_configs(existing, value),
+                    (_, None) => value.clone(),
+                };
+                merged.insert(key.clone(), combined);
+            }
+            Value::Object(merged)
+        }
+        (_, overlay) => overlay.clone(),
+    }
+}
+
+fn merge_tasks(base: &Map<String, Value>, overlay: &Map<String, Value>) -> Map<String, Value> {
+    let mut merged = base.clone();
+    for (id, task) in overlay {
+        let combined = match merged.get(id) {
+            Some(existing) => merge_task(existing, task),
+            None => task.clone(),
+        };
+        merged.insert(id.clone(), combined);
+    }
+    merged
+}
+
+/// Merge an overlay task definition over an inherited one
+pub fn merge_task(base: &Value, overlay: &Value) -> Value {
+    let (Value::Object(base), Value::Object(overlay)) = (base, overlay) else {
+        return overlay.clone();
+    };
+
+    // Strategies come from the combined options, so the overlay can change them
+    let options = match (base.get("options"), overlay.get("options")) {
+        (Some(base_options), Some(overlay_options)) => merge_configs(base_options, overlay_options),
+        (Some(options), None) | (None, Some(options)) => options.clone(),
+        (None, None) => Value::Null,
+    };
+    let default_strategy = strategy_option(&options, "merge").unwrap_or_default();
+
+    let mut merged = match merge_configs(&Value::Object(base.clone()), &Value::Object(overlay.clone())) {
+        Value::Object(merged) => merged,
+        _ => unreachable!("merging two objects yields an object"),
+    };
+    if !options.is_null() {
+        merged.insert("options".to_string(), options.clone());
+    }
+
+    for (field, option) in TASK_MERGE_FIELDS {
+        let (Some(base_value), Some(overlay_value)) = (base.get(field), overlay.get(field)) else {
+            continue;
+        };
+        let strategy = strategy_option(&options, option).unwrap_or(default_strategy);
+        merged.insert(field.to_string(), merge_field(base_value, overlay_value, strategy));
+    }
+
+    Value::Object(merged)
+}
+
+fn strategy_option(options: &Value, name: &str) -> Option<MergeStrategy> {
+    options.get(name)?.as_str()?.parse().ok()
+}
+
+/// Combine one task field under `strategy`
+pub fn merge_field(base: &Value, overlay: &Value, strategy: MergeStrategy) -> Value {
+    match strategy {
+        MergeStrategy::Replace => return overlay.clone(),
+        MergeStrategy::Preserve => return base.clone(),
+        MergeStrategy::Append | MergeStrategy::Prepend => {}
+    }
+    let (first, second) = if strategy == MergeStrategy::Append {
+        (base, overlay)
+    } else {
+        (overlay, base)
+    };
+
+    match (first, second) {
+        (Value::Object(first), Value::Object(second)) => {
+            let mut merged = first.clone();
+            merged.extend(second.clone());
+            Value::Object(merged)
+        }
+        _ => {
+            let mut items = as_list(first);
+            items.extend(as_list(second));
+            Value::Array(items)
+        }
+    }
+}
+
+/// Task lists may also be written as a single string (`args: "--fix --cache"`)
+fn as_list(value: &Value) -> Vec<Value> {
+    match value {
+        Value::Array(items) => items.clone(),
+        Value::String(s) => s.split_whitespace().map(|part| Value::String(part.to_string())).collect(),
+        Value::Null => Vec::new(),
+        other => vec![other.clone()],
+    }
+}
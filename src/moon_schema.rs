@@ -0,0 +1,63 @@
+//! Moon Schema Module for Space Pklr
+//!
+//! JSON Schema and TypeScript definitions for Moon's config types, produced by schematic's
+//! renderers from the `moon_config` structs built into spklr. Everything that starts from
+//! Moon's own schemas (the daemon, docgen, module templates, composition) goes through
+//! [`generate_schema`].
+
+use crate::types::{CliError, MoonConfig};
+
+/// Formats [`generate_schema`] can produce
+pub const SCHEMA_FORMATS: [&str; 2] = ["json-schema", "typescript"];
+
+/// Generate the schema for a Moon config type in `format` (`json-schema` or `typescript`)
+pub fn generate_schema(config_type: MoonConfig, format: &str) -> Result<String, CliError> {
+    use schematic::schema::{JsonSchemaRenderer, SchemaGenerator, TypeScriptRenderer};
+
+    let mut generator = SchemaGenerator::default();
+    match config_type {
+        MoonConfig::Project => generator.add::<moon_config::ProjectConfig>(),
+        MoonConfig::Workspace => generator.add::<moon_config::WorkspaceConfig>(),
+        MoonConfig::Toolchain => generator.add::<moon_config::ToolchainConfig>(),
+        MoonConfig::Template => generator.add::<moon_config::TemplateConfig>(),
+        MoonConfig::Task => generator.add::<moon_config::TaskConfig>(),
+        MoonConfig::All => {
+            return Err(CliError::Generic(
+                "Cannot generate schema for 'All' - generate each config type separately".to_string(),
+            ));
+        }
+    }
+
+    // schematic renders into a file; a private temporary keeps concurrent calls apart
+    let suffix = match format {
+        "json-schema" => ".json",
+        "typescript" => ".ts",
+        _ => {
+            return Err(CliError::UnsupportedFormat {
+                format: format.to_string(),
+                available: SCHEMA_FORMATS.to_vec(),
+            });
+        }
+    };
+    let file = tempfile::Builder::new()
+        .prefix("spklr-schema-")
+        .suffix(suffix)
+        .tempfile()
+        .map_err(|e| CliError::IoError {
+            context: "Creating temporary schema file".to_string(),
+            source: e,
+        })?;
+    let rendered = if format == "json-schema" {
+        generator.generate(file.path(), JsonSchemaRenderer::default())
+    } else {
+        generator.generate(file.path(), TypeScriptRenderer::default())
+    };
+    rendered.map_err(|e| CliError::ValidationError {
+        source: Box::new(std::io::Error::other(e.to_string())),
+    })?;
+
+    std::fs::read_to_string(file.path()).map_err(|e| CliError::IoError {
+        context: format!("Reading generated {} schema", config_type),
+        source: e,
+    })
+}
@@ -0,0 +1,267 @@
+//! MessagePack Module for Space Pklr
+//!
+//! The subset of [MessagePack](https://msgpack.org) the Pkl server protocol uses (see
+//! [`crate::pkl_server`]): nil, booleans, integers, floats, strings, binary, arrays,
+//! maps, and extension values, which are kept as raw bytes. Messages arrive as a stream
+//! with no framing, so [`decode`] reports a value that isn't complete yet instead of
+//! failing on it.
+
+use crate::types::CliError;
+
+/// A MessagePack value
+#[derive(Debug, Clone, PartialEq)]
+pub enum MsgValue {
+    Nil,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Str(String),
+    Bin(Vec<u8>),
+    Array(Vec<MsgValue>),
+    Map(Vec<(MsgValue, MsgValue)>),
+    Ext(i8, Vec<u8>),
+}
+
+impl MsgValue {
+    /// A map from string keys
+    pub fn map<'a>(entries: impl IntoIterator<Item = (&'a str, MsgValue)>) -> Self {
+        MsgValue::Map(entries.into_iter().map(|(key, value)| (MsgValue::from(key), value)).collect())
+    }
+
+    /// The value of a map entry
+    pub fn get(&self, key: &str) -> Option<&MsgValue> {
+        match self {
+            MsgValue::Map(entries) => entries
+                .iter()
+                .find(|(entry_key, _)| matches!(entry_key, MsgValue::Str(name) if name == key))
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    pub fn as_int(&self) -> Option<i64> {
+        match self {
+            MsgValue::Int(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            MsgValue::Str(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            MsgValue::Bin(bytes) => Some(bytes),
+            _ => None,
+        }
+    }
+}
+
+impl From<&str> for MsgValue {
+    fn from(value: &str) -> Self {
+        MsgValue::Str(value.to_string())
+    }
+}
+
+impl From<String> for MsgValue {
+    fn from(value: String) -> Self {
+        MsgValue::Str(value)
+    }
+}
+
+impl From<i64> for MsgValue {
+    fn from(value: i64) -> Self {
+        MsgValue::Int(value)
+    }
+}
+
+/// Append the encoding of `value` to `out`
+pub fn encode(value: &MsgValue, out: &mut Vec<u8>) {
+    match value {
+        MsgValue::Nil => out.push(0xc0),
+        MsgValue::Bool(value) => out.push(if *value { 0xc3 } else { 0xc2 }),
+        MsgValue::Int(value) => encode_int(*value, out),
+        MsgValue::Float(value) => {
+            out.push(0xcb);
+            out.extend(value.to_be_bytes());
+        }
+        MsgValue::Str(value) => {
+            let len = value.len();
+            match len {
+                0..=31 => out.push(0xa0 | len as u8),
+                32..=0xff => out.extend([0xd9, len as u8]),
+                0x100..=0xffff => {
+                    out.push(0xda);
+                    out.extend((len as u16).to_be_bytes());
+                }
+                _ => {
+                    out.push(0xdb);
+                    out.extend((len as u32).to_be_bytes());
+                }
+            }
+            out.extend(value.as_bytes());
+        }
+        MsgValue::Bin(bytes) => {
+            encode_len(bytes.len(), None, [0xc4, 0xc5, 0xc6], out);
+            out.extend(bytes);
+        }
+        MsgValue::Array(items) => {
+            encode_len(items.len(), Some(0x90), [0xdc, 0xdc, 0xdd], out);
+            for item in items {
+                encode(item, out);
+            }
+        }
+        MsgValue::Map(entries) => {
+            encode_len(entries.len(), Some(0x80), [0xde, 0xde, 0xdf], out);
+            for (key, value) in entries {
+                encode(key, out);
+                encode(value, out);
+            }
+        }
+        MsgValue::Ext(kind, data) => {
+            encode_len(data.len(), None, [0xc7, 0xc8, 0xc9], out);
+            out.push(*kind as u8);
+            out.extend(data);
+        }
+    }
+}
+
+fn encode_int(value: i64, out: &mut Vec<u8>) {
+    match value {
+        0..=0x7f => out.push(value as u8),
+        -32..=-1 => out.push(value as i8 as u8),
+        0x80..=0xff => out.extend([0xcc, value as u8]),
+        0x100..=0xffff => {
+            out.push(0xcd);
+            out.extend((value as u16).to_be_bytes());
+        }
+        0x1_0000..=0xffff_ffff => {
+            out.push(0xce);
+            out.extend((value as u32).to_be_bytes());
+        }
+        _ => {
+            out.push(0xd3);
+            out.extend(value.to_be_bytes());
+        }
+    }
+}
+
+/// A length header: the fix form for small lengths (when there is one), else the 8-,
+/// 16-, or 32-bit form (arrays and maps have no 8-bit form, so they pass the 16-bit
+/// marker twice)
+fn encode_len(len: usize, fix: Option<u8>, markers: [u8; 3], out: &mut Vec<u8>) {
+    match (fix, len) {
+        (Some(fix), 0..=15) => out.push(fix | len as u8),
+        (None, 0..=0xff) => out.extend([markers[0], len as u8]),
+        (_, 0..=0xffff) => {
+            out.push(markers[1]);
+            out.extend((len as u16).to_be_bytes());
+        }
+        _ => {
+            out.push(markers[2]);
+            out.extend((len as u32).to_be_bytes());
+        }
+    }
+}
+
+/// Decode the first value in `bytes`, with the number of bytes it took, or `None` when
+/// `bytes` ends before the value does
+pub fn decode(bytes: &[u8]) -> Result<Option<(MsgValue, usize)>, CliError> {
+    let mut reader = Reader { bytes, position: 0 };
+    match reader.value() {
+        Ok(value) => Ok(Some((value, reader.position))),
+        Err(DecodeError::NeedMore) => Ok(None),
+        Err(DecodeError::Invalid(marker)) => Err(CliError::Generic(format!("Invalid MessagePack marker 0x{:02x}", marker))),
+    }
+}
+
+enum DecodeError {
+    NeedMore,
+    Invalid(u8),
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], DecodeError> {
+        let bytes = self.bytes;
+        let end = self.position.checked_add(len).filter(|end| *end <= bytes.len()).ok_or(DecodeError::NeedMore)?;
+        let taken = &bytes[self.position..end];
+        self.position = end;
+        Ok(taken)
+    }
+
+    fn uint(&mut self, len: usize) -> Result<u64, DecodeError> {
+        Ok(self.take(len)?.iter().fold(0u64, |value, byte| (value << 8) | u64::from(*byte)))
+    }
+
+    fn string(&mut self, len: usize) -> Result<MsgValue, DecodeError> {
+        Ok(MsgValue::Str(String::from_utf8_lossy(self.take(len)?).to_string()))
+    }
+
+    fn array(&mut self, len: usize) -> Result<MsgValue, DecodeError> {
+        (0..len).map(|_| self.value()).collect::<Result<_, _>>().map(MsgValue::Array)
+    }
+
+    fn map(&mut self, len: usize) -> Result<MsgValue, DecodeError> {
+        (0..len)
+            .map(|_| Ok((self.value()?, self.value()?)))
+            .collect::<Result<_, _>>()
+            .map(MsgValue::Map)
+    }
+
+    fn ext(&mut self, len: usize) -> Result<MsgValue, DecodeError> {
+        let kind = self.take(1)?[0] as i8;
+        Ok(MsgValue::Ext(kind, self.take(len)?.to_vec()))
+    }
+
+    fn value(&mut self) -> Result<MsgValue, DecodeError> {
+        let marker = self.take(1)?[0];
+        match marker {
+            0x00..=0x7f => Ok(MsgValue::Int(i64::from(marker))),
+            0x80..=0x8f => self.map(usize::from(marker & 0x0f)),
+            0x90..=0x9f => self.array(usize::from(marker & 0x0f)),
+            0xa0..=0xbf => self.string(usize::from(marker & 0x1f)),
+            0xc0 => Ok(MsgValue::Nil),
+            0xc2 => Ok(MsgValue::Bool(false)),
+            0xc3 => Ok(MsgValue::Bool(true)),
+            0xc4..=0xc6 => {
+                let len = self.uint(1 << (marker - 0xc4))? as usize;
+                Ok(MsgValue::Bin(self.take(len)?.to_vec()))
+            }
+            0xc7..=0xc9 => {
+                let len = self.uint(1 << (marker - 0xc7))? as usize;
+                self.ext(len)
+            }
+            0xca => Ok(MsgValue::Float(f64::from(f32::from_bits(self.uint(4)? as u32)))),
+            0xcb => Ok(MsgValue::Float(f64::from_bits(self.uint(8)?))),
+            0xcc..=0xcf => Ok(MsgValue::Int(self.uint(1 << (marker - 0xcc))? as i64)),
+            0xd0 => Ok(MsgValue::Int(i64::from(self.uint(1)? as u8 as i8))),
+            0xd1 => Ok(MsgValue::Int(i64::from(self.uint(2)? as u16 as i16))),
+            0xd2 => Ok(MsgValue::Int(i64::from(self.uint(4)? as u32 as i32))),
+            0xd3 => Ok(MsgValue::Int(self.uint(8)? as i64)),
+            0xd4..=0xd8 => self.ext(1 << (marker - 0xd4)),
+            0xd9..=0xdb => {
+                let len = self.uint(1 << (marker - 0xd9))? as usize;
+                self.string(len)
+            }
+            0xdc | 0xdd => {
+                let len = self.uint(if marker == 0xdc { 2 } else { 4 })? as usize;
+                self.array(len)
+            }
+            0xde | 0xdf => {
+                let len = self.uint(if marker == 0xde { 2 } else { 4 })? as usize;
+                self.map(len)
+            }
+            0xe0..=0xff => Ok(MsgValue::Int(i64::from(marker as i8))),
+            _ => Err(DecodeError::Invalid(marker)),
+        }
+    }
+}
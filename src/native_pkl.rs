@@ -0,0 +1,768 @@
+//! Native Pkl Module for Space Pklr
+//!
+//! Some machines can't install the Pkl CLI at all. With the `native_pkl` cargo feature,
+//! configs are read by this parser when no CLI is found, as long as they're plain data:
+//!
+//! ```pkl
+//! amends "package://github.com/moonrepo/moon/releases/download/pkl-v1/moon@1#/Project.pkl"
+//!
+//! language = "typescript"
+//! tags { "frontend" }
+//! tasks {
+//!   ["build"] {
+//!     command = "vite build"
+//!     deps = new Listing { "^:build" }
+//!   }
+//! }
+//! ```
+//!
+//! Properties, object bodies, listings, mappings, `List()`, `Set()`, and `Map()`, strings
+//! (multi-line and `#"custom-delimited"#` too), numbers, booleans, and `null` are read
+//! into the same value tree `pkl eval -f json` produces. The module a config amends isn't
+//! loaded, so the result has exactly the values written in the config, without the
+//! template's defaults.
+//!
+//! An empty body (`tags {}`, `new {}`) is a listing or an object depending on the
+//! property's type. For a config amending one of Moon's modules that type comes from
+//! Moon's JSON schema; otherwise the body needs evaluation.
+//!
+//! Anything that needs evaluation (references, string interpolation, operators,
+//! durations like `5.min`, imports, `for` and `when` generators, spreads, functions,
+//! classes) is an error naming the construct and where it is.
+
+use serde_json::{Map, Number, Value};
+use std::path::Path;
+
+use crate::types::CliError;
+
+/// Read a literal-only Pkl config file
+pub fn load(path: &Path) -> Result<Value, CliError> {
+    let content = std::fs::read_to_string(path).map_err(|e| CliError::IoError {
+        context: format!("Reading config file: {}", path.display()),
+        source: e,
+    })?;
+    parse(&content)
+}
+
+/// Parse a literal-only Pkl module into its values
+pub fn parse(content: &str) -> Result<Value, CliError> {
+    let tokens = Lexer::new(content).tokens()?;
+    Parser { tokens, position: 0, amends: None, path: Vec::new(), schema: None }.module()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Str(String),
+    Int(i64),
+    Float(f64),
+    Symbol(&'static str),
+    Eof,
+}
+
+#[derive(Debug, Clone)]
+struct Spanned {
+    token: Token,
+    line: usize,
+    column: usize,
+}
+
+/// Symbols, longest first so `...` isn't read as three `.`
+const SYMBOLS: &[&str] = &[
+    "...?", "...", "?.", "??", "!!", "|>", "->", "==", "!=", "<=", ">=", "&&", "||", "**", "~/", "{", "}", "[", "]", "(", ")",
+    "=", ",", ";", ".", ":", "?", "|", "<", ">", "!", "+", "-", "*", "/", "%", "@",
+];
+
+/// Binary and postfix operators: a literal followed by one of these is an expression
+const OPERATORS: &[&str] = &[
+    ".", "?.", "??", "!!", "|>", "==", "!=", "<=", ">=", "&&", "||", "**", "~/", "<", ">", "+", "*", "/", "%",
+];
+
+fn invalid(line: usize, column: usize, message: impl std::fmt::Display) -> CliError {
+    CliError::Generic(format!("Invalid Pkl at line {}, column {}: {}", line, column, message))
+}
+
+fn needs_evaluation(line: usize, column: usize, construct: impl Into<String>) -> CliError {
+    CliError::PklNeedsEvaluation {
+        construct: construct.into(),
+        line,
+        column,
+    }
+}
+
+struct Lexer<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    line: usize,
+    column: usize,
+}
+
+impl<'a> Lexer<'a> {
+    fn new(content: &'a str) -> Self {
+        Self {
+            chars: content.chars().peekable(),
+            line: 1,
+            column: 1,
+        }
+    }
+
+    fn next_char(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    fn starts_with(&self, text: &str) -> bool {
+        let mut chars = self.chars.clone();
+        text.chars().all(|expected| chars.next() == Some(expected))
+    }
+
+    fn tokens(mut self) -> Result<Vec<Spanned>, CliError> {
+        let mut tokens = Vec::new();
+        if self.starts_with("#!") {
+            while self.next_char().is_some_and(|c| c != '\n') {}
+        }
+        loop {
+            self.skip_trivia()?;
+            let (line, column) = (self.line, self.column);
+            let Some(&c) = self.chars.peek() else {
+                tokens.push(Spanned { token: Token::Eof, line, column });
+                return Ok(tokens);
+            };
+            let token = if c == '"' || c == '#' {
+                Token::Str(self.string()?)
+            } else if c.is_ascii_digit() {
+                self.number()?
+            } else if c == '`' {
+                self.next_char();
+                let mut name = String::new();
+                loop {
+                    match self.next_char() {
+                        Some('`') => break,
+                        Some('\n') | None => return Err(invalid(line, column, "unterminated quoted identifier")),
+                        Some(c) => name.push(c),
+                    }
+                }
+                Token::Ident(name)
+            } else if c.is_alphabetic() || c == '_' || c == '$' {
+                let mut name = String::new();
+                while let Some(&c) = self.chars.peek().filter(|c| c.is_alphanumeric() || **c == '_' || **c == '$') {
+                    name.push(c);
+                    self.next_char();
+                }
+                Token::Ident(name)
+            } else {
+                let symbol = SYMBOLS
+                    .iter()
+                    .copied()
+                    .find(|symbol| self.starts_with(symbol))
+                    .ok_or_else(|| invalid(line, column, format!("unexpected `{}`", c)))?;
+                for _ in 0..symbol.len() {
+                    self.next_char();
+                }
+                Token::Symbol(symbol)
+            };
+            tokens.push(Spanned { token, line, column });
+        }
+    }
+
+    /// Whitespace and comments
+    fn skip_trivia(&mut self) -> Result<(), CliError> {
+        loop {
+            if self.chars.peek().is_some_and(|c| c.is_whitespace()) {
+                self.next_char();
+            } else if self.starts_with("//") {
+                while self.chars.peek().is_some_and(|c| *c != '\n') {
+                    self.next_char();
+                }
+            } else if self.starts_with("/*") {
+                let (line, column) = (self.line, self.column);
+                self.next_char();
+                self.next_char();
+                while !self.starts_with("*/") {
+                    if self.next_char().is_none() {
+                        return Err(invalid(line, column, "unterminated block comment"));
+                    }
+                }
+                self.next_char();
+                self.next_char();
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn number(&mut self) -> Result<Token, CliError> {
+        let (line, column) = (self.line, self.column);
+        let mut text = String::new();
+        let radix = match (self.starts_with("0x"), self.starts_with("0b"), self.starts_with("0o")) {
+            (true, _, _) => 16,
+            (_, true, _) => 2,
+            (_, _, true) => 8,
+            _ => 10,
+        };
+        if radix != 10 {
+            self.next_char();
+            self.next_char();
+        }
+        let mut float = false;
+        while let Some(&c) = self.chars.peek() {
+            if c == '_' {
+                self.next_char();
+                continue;
+            }
+            let more = if radix == 10 {
+                // `5.min` is a duration: only a digit after the dot makes a float
+                let fraction = c == '.' && !float && {
+                    let mut ahead = self.chars.clone();
+                    ahead.next();
+                    ahead.next().is_some_and(|c| c.is_ascii_digit())
+                };
+                let exponent = (c == 'e' || c == 'E') && !text.contains(['e', 'E']);
+                let sign = (c == '+' || c == '-') && text.ends_with(['e', 'E']);
+                float |= fraction || exponent;
+                c.is_ascii_digit() || fraction || exponent || sign
+            } else {
+                c.is_digit(radix)
+            };
+            if !more {
+                break;
+            }
+            text.push(c);
+            self.next_char();
+        }
+        let number = if float {
+            text.parse().map(Token::Float).ok()
+        } else {
+            i64::from_str_radix(&text, radix).map(Token::Int).ok()
+        };
+        number.ok_or_else(|| invalid(line, column, format!("invalid number `{}`", text)))
+    }
+
+    /// A string literal with escapes applied: `"..."`, `"""` multi-line `"""`, or either
+    /// with `#` custom delimiters
+    fn string(&mut self) -> Result<String, CliError> {
+        let (line, column) = (self.line, self.column);
+        let mut pounds = String::new();
+        while self.chars.peek() == Some(&'#') {
+            pounds.push('#');
+            self.next_char();
+        }
+        if self.chars.peek() != Some(&'"') {
+            return Err(invalid(line, column, "expected a string after `#`"));
+        }
+        let multiline = self.starts_with("\"\"\"");
+        let quotes = if multiline { "\"\"\"" } else { "\"" };
+        for _ in 0..quotes.len() {
+            self.next_char();
+        }
+        let closing = format!("{}{}", quotes, pounds);
+        let escape = format!("\\{}", pounds);
+
+        // The raw text up to the closing delimiter, escapes still in it
+        let mut raw = String::new();
+        loop {
+            if self.starts_with(&closing) {
+                for _ in 0..closing.chars().count() {
+                    self.next_char();
+                }
+                break;
+            }
+            if self.starts_with(&escape) {
+                for _ in 0..escape.len() {
+                    raw.push(self.next_char().unwrap_or_default());
+                }
+            }
+            match self.next_char() {
+                Some('\n') if !multiline => return Err(invalid(line, column, "unterminated string")),
+                Some(c) => raw.push(c),
+                None => return Err(invalid(line, column, "unterminated string")),
+            }
+        }
+        if multiline {
+            raw = strip_indent(&raw).ok_or_else(|| {
+                invalid(line, column, "multi-line strings start with a line break and end on a line of their own")
+            })?;
+        }
+        unescape(&raw, &escape, line, column)
+    }
+}
+
+/// A multi-line string's text: between its first and last line breaks, with the closing
+/// line's indentation removed from every line
+fn strip_indent(raw: &str) -> Option<String> {
+    let raw = raw.replace("\r\n", "\n");
+    let body = raw.strip_prefix('\n')?;
+    let Some((body, indent)) = body.rsplit_once('\n') else {
+        // The closing delimiter is on the line after the opening one
+        return body.trim_matches([' ', '\t']).is_empty().then(String::new);
+    };
+    if !indent.chars().all(|c| c == ' ' || c == '\t') {
+        return None;
+    }
+    let lines: Vec<&str> = body
+        .split('\n')
+        .map(|line| line.strip_prefix(indent).unwrap_or_else(|| line.trim_start_matches([' ', '\t'])))
+        .collect();
+    Some(lines.join("\n"))
+}
+
+fn unescape(raw: &str, escape: &str, line: usize, column: usize) -> Result<String, CliError> {
+    let mut text = String::new();
+    let mut rest = raw;
+    while let Some(start) = rest.find(escape) {
+        text.push_str(&rest[..start]);
+        rest = &rest[start + escape.len()..];
+        let mut chars = rest.chars();
+        let unescaped = match chars.next() {
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some('t') => '\t',
+            Some('"') => '"',
+            Some('\\') => '\\',
+            Some('(') => return Err(needs_evaluation(line, column, "String interpolation")),
+            Some('u') => {
+                let hex = chars.as_str().strip_prefix('{').and_then(|hex| hex.split_once('}')).map(|(hex, _)| hex);
+                let c = hex
+                    .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                    .and_then(char::from_u32)
+                    .ok_or_else(|| invalid(line, column, "invalid unicode escape"))?;
+                rest = &rest[hex.map_or(0, str::len) + 3..];
+                text.push(c);
+                continue;
+            }
+            other => return Err(invalid(line, column, format!("invalid escape `\\{}`", other.unwrap_or(' ')))),
+        };
+        text.push(unescaped);
+        rest = chars.as_str();
+    }
+    text.push_str(rest);
+    Ok(text)
+}
+
+/// What an object body becomes: listings are always arrays, mappings and other objects
+/// arrays when they only have elements
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Object,
+    Listing,
+    /// Typed by the property it's assigned to (`tags {}`, `new {}`)
+    Inferred,
+}
+
+/// Path segment for a listing element, matching the schema's `items`
+const ELEMENT: &str = "[]";
+
+struct Parser {
+    tokens: Vec<Spanned>,
+    position: usize,
+    /// URI of the amended module
+    amends: Option<String>,
+    /// Property names and entry keys down to the value being parsed
+    path: Vec<String>,
+    /// JSON schema of the amended Moon module, loaded for the first empty body
+    schema: Option<Option<Value>>,
+}
+
+impl Parser {
+    fn peek(&self) -> &Spanned {
+        &self.tokens[self.position.min(self.tokens.len() - 1)]
+    }
+
+    fn peek_at(&self, offset: usize) -> &Token {
+        &self.tokens[(self.position + offset).min(self.tokens.len() - 1)].token
+    }
+
+    fn next(&mut self) -> Spanned {
+        let token = self.peek().clone();
+        self.position += 1;
+        token
+    }
+
+    fn is_symbol(&self, symbol: &str) -> bool {
+        matches!(&self.peek().token, Token::Symbol(s) if *s == symbol)
+    }
+
+    fn is_ident(&self, name: &str) -> bool {
+        matches!(&self.peek().token, Token::Ident(n) if n == name)
+    }
+
+    fn expect(&mut self, symbol: &str) -> Result<(), CliError> {
+        if self.is_symbol(symbol) {
+            self.position += 1;
+            return Ok(());
+        }
+        let found = self.peek();
+        Err(invalid(found.line, found.column, format!("expected `{}`, found {}", symbol, describe(&found.token))))
+    }
+
+    fn unsupported(&self, construct: impl Into<String>) -> CliError {
+        let at = self.peek();
+        needs_evaluation(at.line, at.column, construct)
+    }
+
+    fn module(&mut self) -> Result<Value, CliError> {
+        if self.is_symbol("@") {
+            return Err(self.unsupported("An annotation"));
+        }
+        if self.is_ident("open") || self.is_ident("abstract") {
+            return Err(self.unsupported(format!("An `{}` module", describe_ident(&self.peek().token))));
+        }
+        if self.is_ident("module") {
+            self.next();
+            self.qualified_name()?;
+        }
+        if self.is_ident("amends") {
+            // The amended module only adds defaults and types; the values are all here, and
+            // its URI names the schema that types empty bodies
+            self.next();
+            match self.next().token {
+                Token::Str(uri) => self.amends = Some(uri),
+                other => {
+                    let at = &self.tokens[self.position - 1];
+                    return Err(invalid(at.line, at.column, format!("expected a module URI, found {}", describe(&other))));
+                }
+            }
+        }
+        for keyword in ["extends", "import"] {
+            if self.is_ident(keyword) {
+                return Err(self.unsupported(format!("`{}`", keyword)));
+            }
+        }
+        let mut members = Map::new();
+        let mut elements = Vec::new();
+        self.members(&mut members, &mut elements, true)?;
+        Ok(Value::Object(members))
+    }
+
+    fn qualified_name(&mut self) -> Result<(), CliError> {
+        loop {
+            match self.next() {
+                Spanned { token: Token::Ident(_), .. } => {}
+                Spanned { token, line, column } => {
+                    return Err(invalid(line, column, format!("expected a module name, found {}", describe(&token))));
+                }
+            }
+            if !self.is_symbol(".") {
+                return Ok(());
+            }
+            self.next();
+        }
+    }
+
+    /// Members up to the closing `}`, or the end of the module
+    fn members(&mut self, members: &mut Map<String, Value>, elements: &mut Vec<Value>, module: bool) -> Result<(), CliError> {
+        loop {
+            while self.is_symbol(";") {
+                self.next();
+            }
+            let Spanned { token, line, column } = self.peek().clone();
+            match &token {
+                Token::Eof if module => return Ok(()),
+                Token::Symbol("}") if !module => {
+                    self.next();
+                    return Ok(());
+                }
+                Token::Eof => return Err(invalid(line, column, "unclosed `{`")),
+                Token::Symbol("@") => return Err(self.unsupported("An annotation")),
+                Token::Symbol("..." | "...?") => return Err(self.unsupported("A spread (`...`)")),
+                Token::Ident(name)
+                    if matches!(name.as_str(), "for" | "when") && matches!(self.peek_at(1), Token::Symbol("(")) =>
+                {
+                    return Err(self.unsupported(format!("A `{}` generator", name)));
+                }
+                Token::Ident(name)
+                    if matches!(
+                        name.as_str(),
+                        "local" | "hidden" | "fixed" | "const" | "function" | "class" | "typealias" | "abstract" | "open" | "external" | "import"
+                    ) && matches!(self.peek_at(1), Token::Ident(_) | Token::Str(_) | Token::Symbol("*")) =>
+                {
+                    return Err(self.unsupported(format!("`{}`", name)));
+                }
+                Token::Ident(name) if matches!(self.peek_at(1), Token::Symbol("=" | "{" | ":")) => {
+                    let name = name.clone();
+                    self.next();
+                    if self.is_symbol(":") {
+                        return Err(self.unsupported("A typed property declaration"));
+                    }
+                    self.path.push(name.clone());
+                    let value = self.member_value(members.remove(&name))?;
+                    self.path.pop();
+                    members.insert(name, value);
+                }
+                Token::Symbol("[") if !module => {
+                    self.next();
+                    if self.is_symbol("[") {
+                        return Err(self.unsupported("A member predicate (`[[...]]`)"));
+                    }
+                    let key = match self.expr()? {
+                        Value::String(key) => key,
+                        Value::Number(key) => key.to_string(),
+                        _ => return Err(invalid(line, column, "entry keys must be strings or numbers")),
+                    };
+                    self.expect("]")?;
+                    self.path.push(key.clone());
+                    let value = self.member_value(members.remove(&key))?;
+                    self.path.pop();
+                    members.insert(key, value);
+                }
+                _ if module => return Err(invalid(line, column, format!("expected a property, found {}", describe(&token)))),
+                _ => {
+                    self.path.push(ELEMENT.to_string());
+                    let element = self.expr();
+                    self.path.pop();
+                    elements.push(element?);
+                }
+            }
+        }
+    }
+
+    /// `= value`, or `{ ... }` amending `existing`
+    fn member_value(&mut self, existing: Option<Value>) -> Result<Value, CliError> {
+        if self.is_symbol("=") {
+            self.next();
+            return self.expr();
+        }
+        let kind = match existing {
+            Some(Value::Array(_)) => Kind::Listing,
+            Some(_) => Kind::Object,
+            None => Kind::Inferred,
+        };
+        self.body(existing, kind)
+    }
+
+    /// An object body, amending `base`
+    fn body(&mut self, base: Option<Value>, kind: Kind) -> Result<Value, CliError> {
+        let Spanned { line, column, .. } = self.peek().clone();
+        self.expect("{")?;
+        let (mut members, mut elements) = match base {
+            Some(Value::Object(members)) => (members, Vec::new()),
+            Some(Value::Array(elements)) => (Map::new(), elements),
+            _ => (Map::new(), Vec::new()),
+        };
+        self.members(&mut members, &mut elements, false)?;
+        match (members.is_empty(), elements.is_empty()) {
+            (false, false) => Err(invalid(line, column, "an object with both elements and properties has no JSON form")),
+            (true, false) => Ok(Value::Array(elements)),
+            (true, true) if kind == Kind::Listing => Ok(Value::Array(elements)),
+            (true, true) if kind == Kind::Inferred => match self.schema_type() {
+                Some("array") => Ok(Value::Array(elements)),
+                Some("object") => Ok(Value::Object(members)),
+                _ => Err(needs_evaluation(
+                    line,
+                    column,
+                    "An empty body whose type (listing or object) comes from the amended module",
+                )),
+            },
+            _ => Ok(Value::Object(members)),
+        }
+    }
+
+    /// The JSON schema `type` of the value at the current path in the amended Moon module
+    fn schema_type(&mut self) -> Option<&'static str> {
+        if self.schema.is_none() {
+            let config_type = self
+                .amends
+                .as_deref()
+                .and_then(|uri| crate::templates::module::config_type_for_file(uri.rsplit(['/', '#']).next()?));
+            let schema = config_type
+                .and_then(|config_type| crate::moon_schema::generate_schema(config_type, "json-schema").ok())
+                .and_then(|schema| serde_json::from_str(&schema).ok());
+            self.schema = Some(schema);
+        }
+        let root = self.schema.as_ref()?.as_ref()?;
+
+        let mut node = crate::pkl_schema::examples::resolve(root, root, &mut Vec::new())?;
+        for segment in &self.path {
+            let child = if segment == ELEMENT {
+                node.get("items")
+            } else {
+                node.get("properties")
+                    .and_then(|properties| properties.get(segment))
+                    .or_else(|| node.get("additionalProperties").filter(|schema| schema.is_object()))
+            }?;
+            node = crate::pkl_schema::examples::resolve(root, child, &mut Vec::new())?;
+        }
+        match node.get("type").and_then(Value::as_str)? {
+            "array" => Some("array"),
+            "object" => Some("object"),
+            _ => None,
+        }
+    }
+
+    /// A literal value; anything that continues it into a larger expression needs evaluation
+    fn expr(&mut self) -> Result<Value, CliError> {
+        let value = self.primary()?;
+        let end = &self.tokens[self.position - 1];
+        let next = self.peek();
+        let same_line = next.line == end.line;
+        match &next.token {
+            Token::Symbol(".") => Err(self.unsupported("A member access or unit (like `5.min`)")),
+            Token::Symbol(symbol) if OPERATORS.contains(symbol) => Err(self.unsupported(format!("The `{}` operator", symbol))),
+            Token::Symbol(symbol @ ("-" | "(" | "[")) if same_line => {
+                Err(self.unsupported(format!("The `{}` operator", symbol)))
+            }
+            Token::Ident(name) if matches!(name.as_str(), "is" | "as") => Err(self.unsupported(format!("The `{}` operator", name))),
+            _ => Ok(value),
+        }
+    }
+
+    fn primary(&mut self) -> Result<Value, CliError> {
+        let Spanned { token, line, column } = self.peek().clone();
+        match token {
+            Token::Str(text) => {
+                self.next();
+                Ok(Value::String(text))
+            }
+            Token::Int(value) => {
+                self.next();
+                Ok(Value::from(value))
+            }
+            Token::Float(value) => {
+                self.next();
+                float(value, line, column)
+            }
+            Token::Symbol("-") => match self.peek_at(1).clone() {
+                Token::Int(value) => {
+                    self.position += 2;
+                    Ok(Value::from(-value))
+                }
+                Token::Float(value) => {
+                    self.position += 2;
+                    float(-value, line, column)
+                }
+                _ => Err(self.unsupported("The `-` operator")),
+            },
+            Token::Symbol("(") => {
+                self.next();
+                let value = self.expr()?;
+                self.expect(")")?;
+                Ok(value)
+            }
+            Token::Ident(name) => match name.as_str() {
+                "true" | "false" => {
+                    self.next();
+                    Ok(Value::Bool(name == "true"))
+                }
+                "null" => {
+                    self.next();
+                    Ok(Value::Null)
+                }
+                "new" => {
+                    self.next();
+                    let kind = self.new_type()?;
+                    self.body(None, kind)
+                }
+                "List" | "Set" | "Map" if matches!(self.peek_at(1), Token::Symbol("(")) => {
+                    self.next();
+                    self.collection(&name, line, column)
+                }
+                "if" | "let" | "throw" | "trace" | "read" | "import" | "this" | "outer" | "super" | "module" => {
+                    Err(self.unsupported(format!("`{}`", name)))
+                }
+                _ if matches!(self.peek_at(1), Token::Symbol("(")) => Err(self.unsupported(format!("A call to `{}()`", name))),
+                _ => Err(self.unsupported(format!("A reference to `{}`", name))),
+            },
+            other => Err(invalid(line, column, format!("expected a value, found {}", describe(&other)))),
+        }
+    }
+
+    /// The optional type after `new`: `Listing` and `Listing<...>` make arrays
+    fn new_type(&mut self) -> Result<Kind, CliError> {
+        let kind = match &self.peek().token {
+            Token::Ident(name) => {
+                let kind = if name == "Listing" { Kind::Listing } else { Kind::Object };
+                self.qualified_name()?;
+                kind
+            }
+            _ => return Ok(Kind::Inferred),
+        };
+        if self.is_symbol("<") {
+            let mut depth = 0;
+            loop {
+                match self.next().token {
+                    Token::Symbol("<") => depth += 1,
+                    Token::Symbol(">") => {
+                        depth -= 1;
+                        if depth == 0 {
+                            break;
+                        }
+                    }
+                    Token::Eof => {
+                        let at = self.peek();
+                        return Err(invalid(at.line, at.column, "unclosed type argument list"));
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Ok(kind)
+    }
+
+    /// `List(...)`, `Set(...)`, or `Map(key, value, ...)`
+    fn collection(&mut self, name: &str, line: usize, column: usize) -> Result<Value, CliError> {
+        self.expect("(")?;
+        let mut items = Vec::new();
+        while !self.is_symbol(")") {
+            items.push(self.expr()?);
+            if !self.is_symbol(")") {
+                self.expect(",")?;
+            }
+        }
+        self.next();
+        match name {
+            "Map" => {
+                if items.len() % 2 != 0 {
+                    return Err(invalid(line, column, "`Map()` takes keys and values in pairs"));
+                }
+                let mut map = Map::new();
+                for pair in items.chunks(2) {
+                    let key = match &pair[0] {
+                        Value::String(key) => key.clone(),
+                        Value::Number(key) => key.to_string(),
+                        _ => return Err(invalid(line, column, "map keys must be strings or numbers")),
+                    };
+                    map.insert(key, pair[1].clone());
+                }
+                Ok(Value::Object(map))
+            }
+            "Set" => {
+                let mut unique: Vec<Value> = Vec::new();
+                for item in items {
+                    if !unique.contains(&item) {
+                        unique.push(item);
+                    }
+                }
+                Ok(Value::Array(unique))
+            }
+            _ => Ok(Value::Array(items)),
+        }
+    }
+}
+
+fn float(value: f64, line: usize, column: usize) -> Result<Value, CliError> {
+    Number::from_f64(value)
+        .map(Value::Number)
+        .ok_or_else(|| invalid(line, column, "floats must be finite"))
+}
+
+fn describe(token: &Token) -> String {
+    match token {
+        Token::Ident(name) => format!("`{}`", name),
+        Token::Str(_) => "a string".to_string(),
+        Token::Int(_) | Token::Float(_) => "a number".to_string(),
+        Token::Symbol(symbol) => format!("`{}`", symbol),
+        Token::Eof => "the end of the file".to_string(),
+    }
+}
+
+fn describe_ident(token: &Token) -> &str {
+    match token {
+        Token::Ident(name) => name,
+        _ => "",
+    }
+}
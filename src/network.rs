@@ -0,0 +1,320 @@
+//! Network Module for Space Pklr
+//!
+//! One retry, backoff, and timeout policy for every request spklr makes, configured in
+//! the `[network]` table of `spklr.toml`. Top-level keys apply to every operation, and
+//! `[network.operations.<name>]` tables override them for one:
+//!
+//! ```toml
+//! [network]
+//! max_attempts = 4
+//! initial_backoff_ms = 1000   # doubled after each failure
+//! max_backoff_secs = 30       # caps every delay, including Retry-After
+//! timeout_secs = 30           # whole request; 0 for none
+//! connect_timeout_secs = 10
+//! jitter = 0.2                # delays vary by up to ±20%
+//!
+//! [network.operations.download]
+//! max_attempts = 6
+//! ```
+//!
+//! Operations are `download` (Pkl CLI archives, no request timeout by default),
+//! `checksum` (published `.sha256` files), `github` (pull request comments), and
+//! `link-check` (docgen link validation). Each starts from its own built-in defaults
+//! ([`Operation::defaults`]); [`crate::tool_config::ToolConfig::retry_policy`] layers the
+//! config on top. The older `[download]` retry keys still work, below the download table.
+//! `--timeout 90s` on the command line replaces the request timeout of every operation
+//! (`0` for none).
+//!
+//! Network errors, 5xx responses, and 429s are retried; `Retry-After` is honored but
+//! capped. Jitter spreads out retries from CI jobs that failed together.
+
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::types::HumanDuration;
+#[cfg(feature = "network")]
+use crate::types::CliError;
+
+/// `--timeout` from the command line
+static CLI_TIMEOUT: Mutex<Option<HumanDuration>> = Mutex::new(None);
+
+/// Set the request timeout from `--timeout`
+pub fn set_cli_timeout(timeout: Option<HumanDuration>) {
+    *CLI_TIMEOUT.lock().unwrap_or_else(|e| e.into_inner()) = timeout;
+}
+
+/// `--timeout`, if given
+pub fn cli_timeout() -> Option<HumanDuration> {
+    *CLI_TIMEOUT.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// A kind of network request, each with its own policy
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    /// Artifact downloads, like the Pkl CLI
+    Download,
+    /// Published `.sha256` checksums for downloads
+    Checksum,
+    /// GitHub API calls
+    Github,
+    /// Documentation link checks
+    LinkCheck,
+}
+
+impl Operation {
+    /// Name of the operation's `[network.operations]` table
+    pub fn name(&self) -> &'static str {
+        match self {
+            Operation::Download => "download",
+            Operation::Checksum => "checksum",
+            Operation::Github => "github",
+            Operation::LinkCheck => "link-check",
+        }
+    }
+
+    /// Built-in policy, before `spklr.toml`
+    pub fn defaults(&self) -> RetryPolicy {
+        let policy = RetryPolicy::default();
+        match self {
+            // Large transfers on slow links can legitimately take minutes
+            Operation::Download => RetryPolicy { timeout: None, ..policy },
+            // Checksums are best effort; a missing one only skips verification
+            Operation::Checksum => RetryPolicy { max_attempts: 2, ..policy },
+            Operation::Github => policy,
+            Operation::LinkCheck => RetryPolicy {
+                max_attempts: 2,
+                timeout: Some(Duration::from_secs(10)),
+                ..policy
+            },
+        }
+    }
+}
+
+/// Retry, backoff, and timeout behaviour for one kind of request
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    /// Attempts per URL (for downloads, before moving on to the next mirror)
+    pub max_attempts: u32,
+    /// Delay before the first retry; doubled after each failure
+    pub initial_backoff: Duration,
+    /// Upper bound for any single delay, including `Retry-After`
+    pub max_backoff: Duration,
+    /// Limit for a whole request, or `None` for no limit
+    pub timeout: Option<Duration>,
+    /// Limit for establishing a connection
+    pub connect_timeout: Option<Duration>,
+    /// Fraction by which backoff delays vary at random, from 0 to 1
+    pub jitter: f64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(30),
+            timeout: Some(Duration::from_secs(30)),
+            connect_timeout: Some(Duration::from_secs(10)),
+            jitter: 0.2,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Delay before retry number `attempt` (1-based), without jitter
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+
+    /// Delay before retry number `attempt`: the server's `Retry-After` if it sent one,
+    /// or the jittered backoff, capped at `max_backoff`
+    pub fn delay(&self, attempt: u32, retry_after: Option<Duration>) -> Duration {
+        match retry_after {
+            Some(requested) => requested.min(self.max_backoff),
+            None => self.jittered(self.backoff(attempt), random_unit()),
+        }
+    }
+
+    /// `delay` moved by the jitter fraction, where `unit` in `[0, 1)` picks the point
+    /// between `-jitter` and `+jitter`
+    pub fn jittered(&self, delay: Duration, unit: f64) -> Duration {
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        delay.mul_f64(1.0 - jitter + 2.0 * jitter * unit.clamp(0.0, 1.0)).min(self.max_backoff)
+    }
+
+    /// HTTP client with this policy's timeouts
+    ///
+    /// Every request goes through one of these, so `--read-only` runs are refused here.
+    #[cfg(feature = "network")]
+    pub fn client(&self) -> Result<reqwest::Client, CliError> {
+        crate::read_only::ensure_allowed("access the network")?;
+        let mut builder = reqwest::Client::builder().user_agent(concat!("spklr/", env!("CARGO_PKG_VERSION")));
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(connect_timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(connect_timeout);
+        }
+        builder
+            .build()
+            .map_err(|e| CliError::NetworkError(format!("Failed to create HTTP client: {}", e)))
+    }
+
+    /// The failure a request error stands for; timeouts name the limit that was hit
+    #[cfg(feature = "network")]
+    pub fn request_failure(&self, error: reqwest::Error) -> Failure {
+        match self.timeout {
+            Some(timeout) if error.is_timeout() => {
+                Failure::Retryable(format!("request timed out after {}", HumanDuration::from(timeout)), None)
+            }
+            _ => Failure::Retryable(error.to_string(), None),
+        }
+    }
+}
+
+/// A number in `[0, 1)` that differs between calls; good enough for jitter
+fn random_unit() -> f64 {
+    use std::hash::BuildHasher;
+
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|elapsed| elapsed.subsec_nanos())
+        .unwrap_or_default();
+    let bits = std::collections::hash_map::RandomState::new().hash_one(nanos);
+    (bits >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Settings for every operation, or for one
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct NetworkSettings {
+    pub max_attempts: Option<u32>,
+    pub initial_backoff_ms: Option<u64>,
+    pub max_backoff_secs: Option<u64>,
+    /// `0` removes the limit
+    pub timeout_secs: Option<u64>,
+    /// `0` removes the limit
+    pub connect_timeout_secs: Option<u64>,
+    pub jitter: Option<f64>,
+}
+
+impl NetworkSettings {
+    /// `policy` with the settings that are set
+    pub fn apply(&self, mut policy: RetryPolicy) -> RetryPolicy {
+        let limit = |secs: u64| (secs > 0).then(|| Duration::from_secs(secs));
+        if let Some(max_attempts) = self.max_attempts {
+            policy.max_attempts = max_attempts.max(1);
+        }
+        if let Some(initial_backoff_ms) = self.initial_backoff_ms {
+            policy.initial_backoff = Duration::from_millis(initial_backoff_ms);
+        }
+        if let Some(max_backoff_secs) = self.max_backoff_secs {
+            policy.max_backoff = Duration::from_secs(max_backoff_secs);
+        }
+        if let Some(timeout_secs) = self.timeout_secs {
+            policy.timeout = limit(timeout_secs);
+        }
+        if let Some(connect_timeout_secs) = self.connect_timeout_secs {
+            policy.connect_timeout = limit(connect_timeout_secs);
+        }
+        if let Some(jitter) = self.jitter {
+            policy.jitter = jitter.clamp(0.0, 1.0);
+        }
+        policy
+    }
+}
+
+/// `[network]` settings
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct NetworkConfig {
+    /// Settings for every operation
+    #[serde(flatten)]
+    pub defaults: NetworkSettings,
+    /// Settings for one operation, by [`Operation::name`]
+    pub operations: BTreeMap<String, NetworkSettings>,
+}
+
+impl NetworkConfig {
+    /// Operation tables that don't name an operation, to warn about typos
+    pub fn unknown_operations(&self) -> Vec<&str> {
+        const KNOWN: [Operation; 4] = [Operation::Download, Operation::Checksum, Operation::Github, Operation::LinkCheck];
+        self.operations
+            .keys()
+            .map(String::as_str)
+            .filter(|name| !KNOWN.iter().any(|operation| operation.name() == *name))
+            .collect()
+    }
+}
+
+/// Why one attempt failed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Failure {
+    /// Worth retrying (network errors, 5xx, 429), optionally after a server-requested delay
+    Retryable(String, Option<Duration>),
+    /// Retrying won't help (404, 403, ...)
+    Fatal(String),
+}
+
+/// The failure an unsuccessful response status stands for, or `None` for a success
+#[cfg(feature = "network")]
+pub fn status_failure(response: &reqwest::Response) -> Option<Failure> {
+    use reqwest::StatusCode;
+
+    let status = response.status();
+    match status {
+        s if s.is_success() || s.is_redirection() => None,
+        StatusCode::TOO_MANY_REQUESTS | StatusCode::SERVICE_UNAVAILABLE => Some(Failure::Retryable(
+            format!("server returned {}", status),
+            retry_after(response.headers()),
+        )),
+        s if s.is_server_error() => Some(Failure::Retryable(format!("server returned {}", s), None)),
+        s => Some(Failure::Fatal(format!("server returned {}", s))),
+    }
+}
+
+/// The delay a `Retry-After` header asks for, in seconds
+#[cfg(feature = "network")]
+pub fn retry_after(headers: &reqwest::header::HeaderMap) -> Option<Duration> {
+    headers
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<u64>().ok())
+        .map(Duration::from_secs)
+}
+
+/// Run `attempt` until it succeeds, fails fatally, or runs out of attempts
+///
+/// `what` names the request in retry warnings. The error is the last failure's message.
+#[cfg(feature = "network")]
+pub async fn retry<T, F, Fut>(policy: &RetryPolicy, what: &str, mut attempt: F) -> Result<T, String>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = Result<T, Failure>>,
+{
+    let mut attempts = 0;
+    loop {
+        attempts += 1;
+        match attempt().await {
+            Ok(value) => return Ok(value),
+            Err(Failure::Fatal(message)) => return Err(message),
+            Err(Failure::Retryable(message, _)) if attempts >= policy.max_attempts => {
+                return Err(if attempts > 1 {
+                    format!("{} (gave up after {} attempts)", message, attempts)
+                } else {
+                    message
+                });
+            }
+            Err(Failure::Retryable(message, retry_after)) => {
+                let delay = policy.delay(attempts, retry_after);
+                tracing::warn!("{} attempt {} failed: {}; retrying in {}", what, attempts, message, HumanDuration::from(delay));
+                tokio::time::sleep(delay).await;
+            }
+        }
+    }
+}
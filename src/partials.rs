@@ -0,0 +1,141 @@
+//! Partials Module for Space Pklr
+//!
+//! Moon merges partial configs over full ones in several places (schematic's `partial`
+//! flag), so overlay and patch files need types where every property is optional.
+//! With `spklr generate schema --partials`, each generated schema gains a `<Name>Partial`
+//! counterpart alongside the full definitions: JSON Schema definitions without
+//! `required` whose references point at other partials, and TypeScript `DeepPartial`
+//! aliases.
+
+use serde_json::{Map, Value};
+
+use crate::types::CliError;
+
+/// Suffix of generated partial types
+pub const PARTIAL_SUFFIX: &str = "Partial";
+
+const DEFINITIONS_PREFIX: &str = "#/definitions/";
+
+const DEEP_PARTIAL: &str = r#"
+/** Recursively optional version of `T`, for overlay and patch files. */
+export type DeepPartial<T> = T extends (infer U)[]
+  ? DeepPartial<U>[]
+  : T extends object
+    ? { [K in keyof T]?: DeepPartial<T[K]> }
+    : T;
+"#;
+
+/// Add partial variants to generated schema content in `format` (`json-schema` or `typescript`)
+pub fn add_partials(content: &str, format: &str) -> Result<String, CliError> {
+    match format {
+        "json-schema" | "json" => json_schema_partials(content),
+        "typescript" | "ts" => Ok(typescript_partials(content)),
+        _ => Err(CliError::UnsupportedFormat {
+            format: format.to_string(),
+            available: vec!["json-schema", "typescript"],
+        }),
+    }
+}
+
+/// Add a `<Name>Partial` definition for every definition and for the root schema
+pub fn json_schema_partials(content: &str) -> Result<String, CliError> {
+    let mut schema: Value = serde_json::from_str(content).map_err(|e| CliError::ValidationError {
+        source: Box::new(e),
+    })?;
+    let Some(root) = schema.as_object_mut() else {
+        return Ok(content.to_string());
+    };
+
+    let mut partials = Map::new();
+    if let Some(Value::Object(definitions)) = root.get("definitions") {
+        for (name, definition) in definitions {
+            if name.ends_with(PARTIAL_SUFFIX) {
+                continue;
+            }
+            partials.insert(format!("{}{}", name, PARTIAL_SUFFIX), partial_of(definition));
+        }
+    }
+
+    // The root's own properties become a definition named after its title
+    if let Some(title) = root.get("title").and_then(Value::as_str).map(str::to_string) {
+        if root.contains_key("properties") {
+            let mut root_partial = Map::new();
+            for key in ["title", "description", "type", "properties", "additionalProperties"] {
+                if let Some(value) = root.get(key) {
+                    root_partial.insert(key.to_string(), value.clone());
+                }
+            }
+            partials.insert(format!("{}{}", title, PARTIAL_SUFFIX), partial_of(&Value::Object(root_partial)));
+        }
+    }
+
+    if partials.is_empty() {
+        return Ok(content.to_string());
+    }
+    let definitions = root
+        .entry("definitions")
+        .or_insert_with(|| Value::Object(Map::new()));
+    if let Value::Object(definitions) = definitions {
+        definitions.extend(partials);
+    }
+
+    serde_json::to_string_pretty(&schema)
+        .map_err(|e| CliError::Generic(format!("Failed to serialize partial schema: {}", e)))
+}
+
+/// A copy of `schema` with nothing required and references redirected to partials
+fn partial_of(schema: &Value) -> Value {
+    match schema {
+        Value::Object(object) => Value::Object(
+            object
+                .iter()
+                .filter(|(key, _)| key.as_str() != "required")
+                .map(|(key, value)| {
+                    let value = match (key.as_str(), value) {
+                        ("$ref", Value::String(reference)) => Value::String(partial_ref(reference)),
+                        ("title", Value::String(title)) => Value::String(format!("{}{}", title, PARTIAL_SUFFIX)),
+                        _ => partial_of(value),
+                    };
+                    (key.clone(), value)
+                })
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.iter().map(partial_of).collect()),
+        other => other.clone(),
+    }
+}
+
+fn partial_ref(reference: &str) -> String {
+    match reference.strip_prefix(DEFINITIONS_PREFIX) {
+        Some(name) if !name.ends_with(PARTIAL_SUFFIX) => {
+            format!("{}{}{}", DEFINITIONS_PREFIX, name, PARTIAL_SUFFIX)
+        }
+        _ => reference.to_string(),
+    }
+}
+
+/// Append a `DeepPartial` alias for every exported interface
+pub fn typescript_partials(content: &str) -> String {
+    let interfaces: Vec<&str> = content
+        .lines()
+        .filter_map(|line| line.strip_prefix("export interface "))
+        .filter_map(|rest| rest.split(|c: char| !c.is_alphanumeric() && c != '_').next())
+        .filter(|name| !name.is_empty() && !name.ends_with(PARTIAL_SUFFIX))
+        .collect();
+    if interfaces.is_empty() {
+        return content.to_string();
+    }
+
+    let mut out = content.trim_end().to_string();
+    out.push('\n');
+    out.push_str(DEEP_PARTIAL);
+    for name in interfaces {
+        out.push_str(&format!(
+            "\nexport type {name}{suffix} = DeepPartial<{name}>;",
+            name = name,
+            suffix = PARTIAL_SUFFIX
+        ));
+    }
+    out.push('\n');
+    out
+}
@@ -74,6 +74,7 @@
 //!   - Support enum translations as type aliases or literal unions, with full type annotations.
 //!   - Allow for including or excluding (default) deprecated types. Included deprecations use Pkl's `@Deprecated` decorator with reason and `since` version if available from schematic.
 //!   - Correct marking of default values, such as with the `*` operator.
+//!   - Keep `oneOf` union semantics: Pkl's `|` means "any of", so `oneOf` unions whose variants can overlap get an "exactly one matches" constraint and a doc note instead of silently accepting more.
 //!   - Support for `open` classes/modules, enabling Pkl's `extend` and `amend` features.
 //!   - Renders the top-level `Config` struct as a module by default, but can be switched to a class. This allows you to directly use the generated module as a type using `amends`.
 //!   - Customizable options for module/class naming, indentation, and more.
@@ -193,6 +194,10 @@ pub struct PklSchemaRenderer {
     depth: usize,
     /// Track typealiases to avoid duplicates
     typealiases: IndexMap<String, String>,
+    /// Doc comments for generated typealiases, keyed by alias name
+    typealias_docs: IndexMap<String, String>,
+    /// Set while rendering `*Partial` classes, so struct references point at partials too
+    rendering_partial: bool,
     /// Track `Reference`s to prevent the universe from imploding
     references: HashSet<String>,
 }
@@ -252,6 +257,9 @@ pub struct PklSchemaOptions {
 
     /// Whether to default to requiring properties or marking them optional when the schema lacks information on optionality.
     pub property_default: PropertyDefault,
+
+    /// Also emit a `<Name>Partial` class for every struct, with every property optional, for overlay/patch files
+    pub partials: bool,
 }
 
 impl Default for PklSchemaOptions {
@@ -274,6 +282,7 @@ impl Default for PklSchemaOptions {
           config_translation: ConfigTranslation::Module,
           optional_format: OptionalFormat::Optional,
           property_default: PropertyDefault::RequireProperties,
+          partials: false,
         }
     }
 }
@@ -285,6 +294,8 @@ impl PklSchemaRenderer {
             options,
             depth: 0,
             typealiases: IndexMap::default(),
+            typealias_docs: IndexMap::default(),
+            rendering_partial: false,
             references: HashSet::new(),
         }
     }
@@ -766,7 +777,9 @@ impl PklSchemaRenderer {
                 let union_type = types.join("|");
 
                 // If it's a complex union, consider creating a typealias
-                let final_type = if union.variants_types.len() > 3 {
+                let final_type = if let Some(alias_name) = self.one_of_alias(union, &types) {
+                    alias_name
+                } else if union.variants_types.len() > 3 {
                     let alias_name = format!("UnionType{}", self.typealiases.len());
                     self.typealiases
                         .insert(alias_name.clone(), union_type.clone());
@@ -827,7 +840,18 @@ impl PklSchemaRenderer {
               // TODO: Replace with class implementation
                 ("Dynamic".to_string(), false)
             }
-            SchemaType::Reference(reference) => (self.to_pascal_case(&reference.name), false),
+            SchemaType::Reference(reference) => {
+                let name = self.to_pascal_case(&reference.name);
+                let is_struct = self
+                    .schemas
+                    .get(&reference.name)
+                    .is_some_and(|schema| matches!(schema.ty, SchemaType::Struct(_)));
+                if self.rendering_partial && is_struct {
+                    (format!("{}Partial", name), false)
+                } else {
+                    (name, false)
+                }
+            }
             SchemaType::Null => ("nothing".to_string(), false),
             SchemaType::Unknown => ("unknown".to_string(), false),
         };
@@ -1025,6 +1049,70 @@ impl PklSchemaRenderer {
         Ok(output.join("\n"))
     }
 
+    /// Render the `<Name>Partial` counterpart of a struct: every property optional, no defaults
+    ///
+    /// Moon merges partial configs (schematic's `partial` flag) over full ones, so overlay
+    /// and patch files can be typed against these classes.
+    fn render_as_partial_class(
+        &mut self,
+        name: &str,
+        structure: &StructType,
+    ) -> RenderResult<String> {
+        let mut output = Vec::new();
+        let class_name = format!("{}Partial", self.to_pascal_case(name));
+
+        output.push(self.render_docs(Some(&format!(
+            "Partial [{}] for overlay and patch files; every property is optional.",
+            self.to_pascal_case(name)
+        ))));
+        output.push(format!("class {}", self.escape_name(&class_name)));
+        output.push(String::new());
+
+        self.depth += 1;
+        self.rendering_partial = true;
+        for (field_name, field) in &structure.fields {
+            if field.hidden {
+                continue;
+            }
+            let field_type = match self.render_field_type(&field.schema) {
+                Ok(field_type) => field_type,
+                Err(e) => {
+                    self.rendering_partial = false;
+                    self.depth -= 1;
+                    return Err(e);
+                }
+            };
+            let escaped_name = self.escape_name(&self.to_camel_case(field_name));
+            output.push(format!("{}{}: {}?", self.indent(), escaped_name, field_type));
+        }
+        self.rendering_partial = false;
+        self.depth -= 1;
+
+        Ok(output.join("\n"))
+    }
+
+    /// Typealias enforcing `oneOf` for a union whose variants may overlap
+    ///
+    /// Returns `None` for `anyOf`, and when plain `|` is already exact (see
+    /// [`crate::pkl_schema::unions`]).
+    fn one_of_alias(&mut self, union: &UnionType, variants: &[String]) -> Option<String> {
+        if !matches!(union.operator, UnionOperation::OneOf) {
+            return None;
+        }
+        let alias_type = crate::pkl_schema::unions::one_of_type(variants)?;
+
+        // Reuse an identical alias rather than emitting a duplicate
+        if let Some((existing, _)) = self.typealiases.iter().find(|(_, ty)| **ty == alias_type) {
+            return Some(existing.clone());
+        }
+
+        let alias_name = format!("OneOf{}", self.typealiases.len());
+        self.typealiases.insert(alias_name.clone(), alias_type);
+        self.typealias_docs
+            .insert(alias_name.clone(), crate::pkl_schema::unions::ONE_OF_DOC.to_string());
+        Some(alias_name)
+    }
+
     fn render_typealiases(&self) -> String {
         if self.typealiases.is_empty() {
             return String::new();
@@ -1033,6 +1121,9 @@ impl PklSchemaRenderer {
         let mut output = Vec::new();
 
         for (alias_name, alias_type) in &self.typealiases {
+            if let Some(doc) = self.typealias_docs.get(alias_name) {
+                output.push(format!("/// {}", doc));
+            }
             output.push(format!("typealias {} = {}", alias_name, alias_type));
         }
 
@@ -1144,7 +1235,8 @@ impl SchemaRenderer<String> for PklSchemaRenderer {
             .iter()
             .map(|t| self.render_field_type(t))
             .collect();
-        Ok(types?.join("|"))
+        let types = types?;
+        Ok(self.one_of_alias(union, &types).unwrap_or_else(|| types.join("|")))
     }
 
     fn render_unknown(&mut self, _schema: &Schema) -> RenderResult<String> {
@@ -1190,6 +1282,15 @@ impl SchemaRenderer<String> for PklSchemaRenderer {
             }
         }
 
+        // Partial counterparts for every struct, including the root
+        if self.options.partials {
+            for (name, schema) in schemas.iter() {
+                if let SchemaType::Struct(structure) = &schema.ty {
+                    output.push(self.render_as_partial_class(name, structure)?);
+                }
+            }
+        }
+
         // Add typealiases at the beginning (after module but before classes)
         let typealiases = self.render_typealiases();
         if !typealiases.is_empty() {
@@ -1204,3 +1305,4 @@ impl SchemaRenderer<String> for PklSchemaRenderer {
         Ok(output.join("\n"))
     }
 }
+
@@ -0,0 +1,200 @@
+//! Property name casing
+//!
+//! Property names come from the source schema as written, which for Moon's configs is
+//! camelCase (as in YAML) but can be snake_case or kebab-case in other documents.
+//! `property_case` in `[generator]` renames every property to one casing, and
+//! `property_case_overrides` picks another for the properties of particular classes,
+//! keyed by class name (or by module name, for a module's own properties):
+//!
+//! ```toml
+//! [generator]
+//! property_case = "camelCase"
+//!
+//! [generator.property_case_overrides]
+//! EnvConfig = "preserve"
+//! ```
+//!
+//! Converted configs need the same names to amend the generated modules, so
+//! [`recase_value`] renames the properties of a config value to match, walking it
+//! alongside the module so that mapping keys (task ids, project names, ...) are left
+//! alone.
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::{GeneratorConfig, PklClass, PklModule, PklProperty, PklType};
+
+/// Casing for property names
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+pub enum Case {
+    /// Names as the source schema writes them
+    #[default]
+    #[serde(rename = "preserve")]
+    Preserve,
+    #[serde(rename = "camelCase")]
+    Camel,
+    #[serde(rename = "snake_case")]
+    Snake,
+    #[serde(rename = "kebab-case")]
+    Kebab,
+    #[serde(rename = "PascalCase")]
+    Pascal,
+}
+
+impl Case {
+    /// `name` in this casing
+    ///
+    /// Names starting with `$` (like `$schema`) are never renamed.
+    pub fn apply(&self, name: &str) -> String {
+        if *self == Case::Preserve || name.starts_with('$') {
+            return name.to_string();
+        }
+        let words = words(name);
+        if words.is_empty() {
+            return name.to_string();
+        }
+        match self {
+            Case::Preserve => name.to_string(),
+            Case::Snake => words.join("_"),
+            Case::Kebab => words.join("-"),
+            Case::Pascal => words.iter().map(|word| capitalize(word)).collect(),
+            Case::Camel => words
+                .iter()
+                .enumerate()
+                .map(|(index, word)| if index == 0 { word.clone() } else { capitalize(word) })
+                .collect(),
+        }
+    }
+}
+
+/// The lowercase words of a name, split at `_`, `-`, and case changes (`HTTPServer` →
+/// `http`, `server`)
+fn words(name: &str) -> Vec<String> {
+    let chars: Vec<char> = name.chars().collect();
+    let mut words = Vec::new();
+    let mut current = String::new();
+    for (index, &c) in chars.iter().enumerate() {
+        if !c.is_alphanumeric() {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+        let previous = index.checked_sub(1).map(|i| chars[i]);
+        let next = chars.get(index + 1);
+        let boundary = c.is_uppercase()
+            && previous.is_some_and(|p| p.is_lowercase() || p.is_numeric()
+                || (p.is_uppercase() && next.is_some_and(|n| n.is_lowercase())));
+        if boundary && !current.is_empty() {
+            words.push(std::mem::take(&mut current));
+        }
+        current.extend(c.to_lowercase());
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    chars
+        .next()
+        .map(|first| first.to_uppercase().collect::<String>() + chars.as_str())
+        .unwrap_or_default()
+}
+
+/// Rename the properties of the module and its classes per the configured casing
+///
+/// A property whose new name another property of the same class already has keeps its
+/// name, with a warning.
+pub fn recase_module(module: &mut PklModule, config: &GeneratorConfig) {
+    recase_properties(&mut module.properties, config.property_case_for(&module.name), &module.name);
+    for class in &mut module.classes {
+        recase_properties(&mut class.properties, config.property_case_for(&class.name), &class.name);
+    }
+}
+
+fn recase_properties(properties: &mut [PklProperty], case: Case, owner: &str) {
+    if case == Case::Preserve {
+        return;
+    }
+    let mut taken: Vec<String> = properties.iter().map(|property| property.name.clone()).collect();
+    for (index, property) in properties.iter_mut().enumerate() {
+        let renamed = case.apply(&property.name);
+        if renamed == property.name {
+            continue;
+        }
+        if taken.contains(&renamed) {
+            tracing::warn!("{}.{} keeps its name: {} already has a property '{}'", owner, property.name, owner, renamed);
+            continue;
+        }
+        taken[index] = renamed.clone();
+        property.name = renamed;
+    }
+}
+
+/// `value`, a config for `schema`, with its properties renamed as [`recase_module`]
+/// renames them in the generated module
+///
+/// `schema` is the module before renaming. Keys that aren't properties of the schema,
+/// including every mapping key, are kept as written.
+pub fn recase_value(value: &Value, schema: &PklModule, config: &GeneratorConfig) -> Value {
+    recase_object(value, &schema.properties, &schema.name, schema, config)
+}
+
+fn recase_object(value: &Value, properties: &[PklProperty], owner: &str, schema: &PklModule, config: &GeneratorConfig) -> Value {
+    let Value::Object(map) = value else {
+        return value.clone();
+    };
+    let case = config.property_case_for(owner);
+    map.iter()
+        .map(|(key, child)| match properties.iter().find(|property| property.name == *key) {
+            Some(property) => (case.apply(key), recase_typed(child, &property.ty, schema, config)),
+            None => (key.clone(), child.clone()),
+        })
+        .collect()
+}
+
+fn recase_typed(value: &Value, ty: &PklType, schema: &PklModule, config: &GeneratorConfig) -> Value {
+    match (ty, value) {
+        (PklType::Nullable(inner), _) => recase_typed(value, inner, schema, config),
+        (PklType::Named(name), _) => {
+            if let Some(class) = schema.classes.iter().find(|class| class.name == *name) {
+                let class = tagged_member(schema, class, value);
+                recase_object(value, &class.properties, &class.name, schema, config)
+            } else if let Some(typealias) = schema.typealiases.iter().find(|typealias| typealias.name == *name) {
+                recase_typed(value, &typealias.ty, schema, config)
+            } else {
+                value.clone()
+            }
+        }
+        (PklType::Listing(item), Value::Array(items)) => {
+            Value::Array(items.iter().map(|child| recase_typed(child, item, schema, config)).collect())
+        }
+        (PklType::Mapping(_, value_ty), Value::Object(entries)) => entries
+            .iter()
+            .map(|(key, child)| (key.clone(), recase_typed(child, value_ty, schema, config)))
+            .collect(),
+        // An object in a union is renamed as its first class member
+        (PklType::Union(members), Value::Object(_)) => members
+            .iter()
+            .find(|member| matches!(member, PklType::Named(name) if schema.classes.iter().any(|class| class.name == *name)))
+            .map(|member| recase_typed(value, member, schema, config))
+            .unwrap_or_else(|| value.clone()),
+        _ => value.clone(),
+    }
+}
+
+/// The member of a tagged union whose tag `value` has, or `class` when it isn't one's base
+fn tagged_member<'a>(schema: &'a PklModule, class: &'a PklClass, value: &Value) -> &'a PklClass {
+    schema
+        .subclasses(&class.name)
+        .find(|member| {
+            member.properties.iter().any(|property| {
+                property.fixed
+                    && matches!(&property.ty, PklType::StringLiteral(tag) if value.get(&property.name).and_then(Value::as_str) == Some(tag.as_str()))
+            })
+        })
+        .unwrap_or(class)
+}
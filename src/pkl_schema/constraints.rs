@@ -0,0 +1,128 @@
+//! Constraint providers
+//!
+//! A [`ConstraintProvider`] adds type constraints to the properties of every module a
+//! [`SchemaGenerator`](super::SchemaGenerator) builds, for rules the source schema
+//! can't express, like an organization's port range. Library users register their own
+//! with [`SchemaGenerator::with_constraint_provider`](super::SchemaGenerator::with_constraint_provider):
+//!
+//! ```
+//! use space_pklr::pkl_schema::constraints::{ConstraintProvider, Field};
+//! use space_pklr::pkl_schema::{PklConstraint, SchemaGenerator};
+//!
+//! struct UnprivilegedPorts;
+//!
+//! impl ConstraintProvider for UnprivilegedPorts {
+//!     fn constraints(&self, field: &Field<'_>) -> Vec<PklConstraint> {
+//!         if field.property.name != "port" {
+//!             return Vec::new();
+//!         }
+//!         vec![PklConstraint {
+//!             expression: "this > 1024".to_string(),
+//!             provenance: Some("Ports below 1024 need root".to_string()),
+//!         }]
+//!     }
+//! }
+//!
+//! let _generator = SchemaGenerator::default().with_constraint_provider(UnprivilegedPorts);
+//! ```
+//!
+//! CLI users declare the same rules in `spklr.toml`. `property` is a property name, or
+//! `<Class>.<name>` for one class's property (the module's name for its own
+//! properties); either part can be `*`:
+//!
+//! ```toml
+//! [[generator.constraints]]
+//! property = "port"
+//! expression = "this > 1024"
+//! reason = "Ports below 1024 need root"
+//! ```
+
+use serde::Deserialize;
+
+use super::{PklConstraint, PklModule, PklProperty};
+use crate::config_path::{ConfigPath, matches_segments};
+
+/// Adds constraints to generated properties
+pub trait ConstraintProvider: Send + Sync {
+    /// Constraints for `field`; any it already has are skipped
+    fn constraints(&self, field: &Field<'_>) -> Vec<PklConstraint>;
+}
+
+/// A property being generated, as seen by a [`ConstraintProvider`]
+#[derive(Debug, Clone, Copy)]
+pub struct Field<'a> {
+    /// Name of the generated module
+    pub module: &'a str,
+    /// Class the property belongs to, or `None` for a module property
+    pub class: Option<&'a str>,
+    pub property: &'a PklProperty,
+}
+
+/// `[[generator.constraints]]`: a constraint for matching properties
+#[derive(Debug, Clone, Deserialize)]
+pub struct ConstraintRule {
+    /// `<name>` or `<Class>.<name>`, with `*` for any
+    pub property: String,
+    /// Pkl boolean expression checked against the value
+    pub expression: String,
+    /// Why the rule exists, shown above the property
+    pub reason: Option<String>,
+}
+
+impl ConstraintRule {
+    fn matches(&self, field: &Field<'_>) -> bool {
+        let pattern = ConfigPath::new(&self.property).segments().to_vec();
+        let name = field.property.name.clone();
+        match pattern.len() {
+            1 => matches_segments(&pattern, &[name]),
+            2 => matches_segments(&pattern, &[field.class.unwrap_or(field.module).to_string(), name]),
+            _ => false,
+        }
+    }
+}
+
+impl ConstraintProvider for ConstraintRule {
+    fn constraints(&self, field: &Field<'_>) -> Vec<PklConstraint> {
+        if !self.matches(field) {
+            return Vec::new();
+        }
+        let provenance = match &self.reason {
+            Some(reason) => format!("Org rule: {}", reason),
+            None => format!("Org rule: constraint on `{}`", self.property),
+        };
+        vec![PklConstraint {
+            expression: self.expression.clone(),
+            provenance: Some(provenance),
+        }]
+    }
+}
+
+/// Add the constraints of every provider to the module's properties, in order
+pub fn apply_providers(module: &mut PklModule, providers: &[&dyn ConstraintProvider]) {
+    if providers.is_empty() {
+        return;
+    }
+    let module_name = module.name.clone();
+    for property in &mut module.properties {
+        add_constraints(property, &module_name, None, providers);
+    }
+    for class in &mut module.classes {
+        for property in &mut class.properties {
+            add_constraints(property, &module_name, Some(&class.name), providers);
+        }
+    }
+}
+
+fn add_constraints(property: &mut PklProperty, module: &str, class: Option<&str>, providers: &[&dyn ConstraintProvider]) {
+    let field = Field {
+        module,
+        class,
+        property,
+    };
+    let added: Vec<PklConstraint> = providers.iter().flat_map(|provider| provider.constraints(&field)).collect();
+    for constraint in added {
+        if !property.constraints.iter().any(|existing| existing.expression == constraint.expression) {
+            property.constraints.push(constraint);
+        }
+    }
+}
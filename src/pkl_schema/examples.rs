@@ -0,0 +1,442 @@
+//! Example configs for generated modules
+//!
+//! `spklr generate schema --with-examples-files` writes `examples/<Module>.example.pkl`
+//! next to each module: a document that amends the module and sets every property it
+//! can find an example for. Examples come from the source JSON Schema through
+//! [`extract_examples`] and are keyed by property path (see [`schema_examples`]); the
+//! module decides how each one is written, so classes become nested objects, listings
+//! get one element, and mappings one `["example"]` entry.
+//!
+//! ```pkl
+//! amends "../Project.pkl"
+//!
+//! /// The primary language
+//! language = "rust"
+//! options {
+//!   cache = true
+//! }
+//! ```
+//!
+//! `fixed` properties are left out, since an amending module can't set them. Examples
+//! always meet the property's JSON Schema constraints (`minimum`, `pattern`, `enum`, ...),
+//! so an example config never fails to evaluate because of one.
+
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+use super::{PklClass, PklModule, PklProperty, PklType, pkl_literal};
+use crate::config_processor::pkl_identifier;
+
+/// Enum values shown as examples before truncating
+const MAX_ENUM_EXAMPLES: usize = 3;
+
+/// String used when a schema gives no example
+const PLACEHOLDER: &str = "example";
+
+/// Key used for the example entry of a mapping
+const EXAMPLE_KEY: &str = "example";
+
+/// Heuristic examples for a property schema
+///
+/// Every example meets the schema's constraints (see [`violation`]): declared examples,
+/// defaults, and enum values that break them are skipped with a warning, and the
+/// placeholder used when the schema has none is picked to fit them.
+pub fn extract_examples(schema: &Value) -> Vec<Value> {
+    if let Some(examples) = schema.get("examples").and_then(Value::as_array) {
+        let examples: Vec<Value> = examples.iter().filter(|example| fits(example, schema, "example")).cloned().collect();
+        if !examples.is_empty() {
+            return examples;
+        }
+    }
+    if let Some(default) = schema.get("default").filter(|d| !d.is_null())
+        && fits(default, schema, "default")
+    {
+        return vec![default.clone()];
+    }
+    if let Some(values) = schema.get("enum").and_then(Value::as_array) {
+        return values
+            .iter()
+            .filter(|v| !v.is_null() && fits(v, schema, "enum value"))
+            .take(MAX_ENUM_EXAMPLES)
+            .cloned()
+            .collect();
+    }
+    placeholder(schema).into_iter().collect()
+}
+
+/// How `value` breaks the constraints of `schema`, if it does
+///
+/// Checks `enum`, `const`, `minimum`/`maximum` and their exclusive forms, `multipleOf`,
+/// `minLength`/`maxLength`, and `pattern`.
+pub fn violation(value: &Value, schema: &Value) -> Option<String> {
+    if let Some(values) = schema.get("enum").and_then(Value::as_array)
+        && !values.contains(value)
+    {
+        return Some(format!("not one of {}", Value::Array(values.clone())));
+    }
+    if let Some(constant) = schema.get("const")
+        && constant != value
+    {
+        return Some(format!("not {}", constant));
+    }
+    if let Some(number) = value.as_f64() {
+        let (lower, upper) = bounds(schema);
+        if let Some((minimum, exclusive)) = lower
+            && (number < minimum || (exclusive && number == minimum))
+        {
+            return Some(format!("{} the minimum {}", if exclusive { "not above" } else { "below" }, minimum));
+        }
+        if let Some((maximum, exclusive)) = upper
+            && (number > maximum || (exclusive && number == maximum))
+        {
+            return Some(format!("{} the maximum {}", if exclusive { "not below" } else { "above" }, maximum));
+        }
+        if let Some(step) = schema.get("multipleOf").and_then(Value::as_f64).filter(|step| *step > 0.0)
+            && (number / step).fract() != 0.0
+        {
+            return Some(format!("not a multiple of {}", step));
+        }
+    }
+    if let Some(text) = value.as_str() {
+        let length = text.chars().count() as u64;
+        if let Some(minimum) = schema.get("minLength").and_then(Value::as_u64)
+            && length < minimum
+        {
+            return Some(format!("shorter than {} characters", minimum));
+        }
+        if let Some(maximum) = schema.get("maxLength").and_then(Value::as_u64)
+            && length > maximum
+        {
+            return Some(format!("longer than {} characters", maximum));
+        }
+        // A pattern the regex crate can't compile (e.g. with lookaround) isn't checked
+        if let Some(pattern) = schema.get("pattern").and_then(Value::as_str)
+            && regex::Regex::new(pattern).is_ok_and(|regex| !regex.is_match(text))
+        {
+            return Some(format!("doesn't match the pattern {}", pattern));
+        }
+    }
+    None
+}
+
+/// Whether `value` meets the constraints of `schema`, warning when it doesn't
+fn fits(value: &Value, schema: &Value, kind: &str) -> bool {
+    match violation(value, schema) {
+        Some(reason) => {
+            tracing::warn!("Skipping {} {} from the schema: {}", kind, value, reason);
+            false
+        }
+        None => true,
+    }
+}
+
+/// The lower and upper bounds of a number schema, each with whether it's exclusive
+///
+/// Reads both the draft 6+ form (`exclusiveMinimum: 0`) and the draft 4 form
+/// (`minimum: 0, exclusiveMinimum: true`).
+fn bounds(schema: &Value) -> (Option<(f64, bool)>, Option<(f64, bool)>) {
+    let bound = |inclusive: &str, exclusive: &str| {
+        let flagged = schema.get(exclusive).and_then(Value::as_bool) == Some(true);
+        let inclusive = schema.get(inclusive).and_then(Value::as_f64).map(|value| (value, flagged));
+        let exclusive = schema.get(exclusive).and_then(Value::as_f64).map(|value| (value, true));
+        exclusive.or(inclusive)
+    };
+    (bound("minimum", "exclusiveMinimum"), bound("maximum", "exclusiveMaximum"))
+}
+
+/// A value of the schema's type for when it gives no examples, or `None` when no simple
+/// value meets its constraints
+fn placeholder(schema: &Value) -> Option<Value> {
+    let schema_type = match schema.get("type") {
+        Some(Value::Array(types)) => types.iter().filter_map(Value::as_str).find(|t| *t != "null"),
+        Some(Value::String(schema_type)) => Some(schema_type.as_str()),
+        _ => None,
+    };
+    let value = match schema_type? {
+        "string" => {
+            // "example", repeated or cut to fit the length limits
+            let minimum = schema.get("minLength").and_then(Value::as_u64).unwrap_or(0) as usize;
+            let maximum = schema.get("maxLength").and_then(Value::as_u64).map_or(usize::MAX, |maximum| maximum as usize);
+            let length = PLACEHOLDER.len().max(minimum).min(maximum);
+            Value::String(PLACEHOLDER.chars().cycle().take(length).collect())
+        }
+        "boolean" => Value::Bool(true),
+        "integer" => number_placeholder(schema, true)?,
+        "number" => number_placeholder(schema, false)?,
+        _ => return None,
+    };
+    violation(&value, schema).is_none().then_some(value)
+}
+
+/// `0`, moved inside the schema's bounds and onto its `multipleOf` steps
+fn number_placeholder(schema: &Value, integer: bool) -> Option<Value> {
+    let (lower, upper) = bounds(schema);
+    let step = schema.get("multipleOf").and_then(Value::as_f64).filter(|step| *step > 0.0);
+    // Just inside an exclusive bound: the next integer, or halfway to the other bound
+    let inside = |bound: f64, other: Option<(f64, bool)>, direction: f64| match (integer, other) {
+        (true, _) if direction > 0.0 => bound.floor() + 1.0,
+        (true, _) => bound.ceil() - 1.0,
+        (false, Some((other, _))) => (bound + other) / 2.0,
+        (false, None) => bound + direction,
+    };
+
+    let mut number = 0.0_f64;
+    if let Some((minimum, exclusive)) = lower
+        && (number < minimum || (exclusive && number == minimum))
+    {
+        number = if exclusive { inside(minimum, upper, 1.0) } else { minimum };
+    }
+    if integer {
+        number = number.ceil();
+    }
+    if let Some(step) = step {
+        number = (number / step).ceil() * step;
+    }
+    if let Some((maximum, exclusive)) = upper
+        && (number > maximum || (exclusive && number == maximum))
+    {
+        number = if exclusive { inside(maximum, lower, -1.0) } else { maximum };
+        if integer {
+            number = number.floor();
+        }
+        if let Some(step) = step {
+            number = (number / step).floor() * step;
+        }
+    }
+
+    if number.fract() == 0.0 && number.abs() < i64::MAX as f64 {
+        Some(Value::from(number as i64))
+    } else {
+        serde_json::Number::from_f64(number).map(Value::Number)
+    }
+}
+
+/// The first example of every property in a JSON Schema document, by dotted path
+///
+/// Paths start at the root object and follow local `$ref`s; list items and mapping
+/// values are `*`, like `tasks.*.command`.
+pub fn schema_examples(schema: &Value) -> BTreeMap<String, Value> {
+    let mut examples = BTreeMap::new();
+    collect(schema, schema, "", &mut Vec::new(), &mut examples);
+    examples
+}
+
+fn collect<'a>(root: &'a Value, node: &'a Value, path: &str, seen: &mut Vec<&'a str>, examples: &mut BTreeMap<String, Value>) {
+    let Some(node) = resolve(root, node, seen) else {
+        return;
+    };
+    let depth = seen.len();
+    for (name, property) in node.get("properties").and_then(Value::as_object).into_iter().flatten() {
+        let property_path = if path.is_empty() { name.clone() } else { format!("{}.{}", path, name) };
+        collect_value(root, property, &property_path, seen, examples);
+    }
+    seen.truncate(depth);
+}
+
+fn collect_value<'a>(root: &'a Value, schema: &'a Value, path: &str, seen: &mut Vec<&'a str>, examples: &mut BTreeMap<String, Value>) {
+    let depth = seen.len();
+    // The property's own examples first, then its definition's (e.g. an enum)
+    let example = extract_examples(schema).into_iter().next().or_else(|| {
+        let resolved = resolve(root, schema, &mut seen.clone())?;
+        extract_examples(resolved).into_iter().next()
+    });
+    if let Some(example) = example {
+        examples.insert(path.to_string(), example);
+    }
+    if let Some(resolved) = resolve(root, schema, seen) {
+        collect(root, resolved, path, seen, examples);
+        for nested in ["items", "additionalProperties"] {
+            if let Some(nested) = resolved.get(nested).filter(|nested| nested.is_object()) {
+                collect_value(root, nested, &format!("{}.*", path), seen, examples);
+            }
+        }
+    }
+    seen.truncate(depth);
+}
+
+/// Follow local `$ref`s and nullable `anyOf`/`oneOf` wrappers to the schema they name
+///
+/// Returns `None` for a reference already being followed, so recursive types end.
+pub(crate) fn resolve<'a>(root: &'a Value, schema: &'a Value, seen: &mut Vec<&'a str>) -> Option<&'a Value> {
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+        if seen.contains(&reference) {
+            return None;
+        }
+        seen.push(reference);
+        let target = ["#/definitions/", "#/$defs/"].iter().find_map(|prefix| {
+            let name = reference.strip_prefix(prefix)?;
+            root.get(&prefix[2..prefix.len() - 1])?.get(name)
+        })?;
+        return resolve(root, target, seen);
+    }
+    for key in ["anyOf", "oneOf"] {
+        if let Some(members) = schema.get(key).and_then(Value::as_array) {
+            let member = members.iter().find(|member| member.get("type").and_then(Value::as_str) != Some("null"))?;
+            return resolve(root, member, seen);
+        }
+    }
+    Some(schema)
+}
+
+/// Pkl source for an example config amending `module`
+///
+/// `amends` is the path to the module from the example file. `modules` are the modules
+/// generated together, to find classes imported from them.
+pub fn example_document(module: &PklModule, modules: &[PklModule], examples: &BTreeMap<String, Value>, amends: &str) -> String {
+    let writer = ExampleWriter { module, modules, examples };
+    let mut out = format!("amends {}\n\n", crate::config_processor::pkl_string(amends));
+    writer.properties(&mut out, &module.properties, "", 0, &mut Vec::new());
+    out
+}
+
+/// File name of the example config for `module`
+pub fn example_file_name(module: &PklModule) -> String {
+    format!("{}.example.pkl", module.name)
+}
+
+struct ExampleWriter<'a> {
+    module: &'a PklModule,
+    modules: &'a [PklModule],
+    examples: &'a BTreeMap<String, Value>,
+}
+
+impl<'a> ExampleWriter<'a> {
+    /// The class a named type refers to, in this module or one it imports from
+    fn class(&self, name: &str) -> Option<&'a PklClass> {
+        let name = name.rsplit('.').next().unwrap_or(name);
+        std::iter::once(self.module)
+            .chain(self.modules)
+            .flat_map(|module| &module.classes)
+            .find(|class| class.name == name)
+    }
+
+    fn named_class(&self, ty: &PklType) -> Option<&'a PklClass> {
+        match ty {
+            PklType::Named(name) => self.class(name),
+            _ => None,
+        }
+    }
+
+    /// The first member of a tagged union, since its abstract base can't be instantiated
+    fn first_subclass(&self, class: &PklClass) -> Option<&'a PklClass> {
+        std::iter::once(self.module)
+            .chain(self.modules)
+            .flat_map(|module| &module.classes)
+            .find(|subclass| subclass.extends.as_deref().and_then(|parent| parent.rsplit('.').next()) == Some(class.name.as_str()))
+    }
+
+    fn properties(&self, out: &mut String, properties: &[PklProperty], path: &str, depth: usize, classes: &mut Vec<&'a str>) {
+        for property in properties.iter().filter(|property| !property.fixed) {
+            let property_path = if path.is_empty() { property.name.clone() } else { format!("{}.{}", path, property.name) };
+            let mut body = String::new();
+            let member = self.member(&mut body, &property.ty, &property_path, depth, classes);
+            let Some(member) = member else {
+                continue;
+            };
+            let indent = "  ".repeat(depth);
+            if let Some(doc) = property.doc.as_deref().and_then(|doc| doc.lines().next()) {
+                out.push_str(&format!("{}/// {}\n", indent, doc));
+            }
+            out.push_str(&format!("{}{}{}", indent, pkl_identifier(&property.name), member));
+            out.push_str(&body);
+        }
+    }
+
+    /// The rest of a member's first line (` = value` or ` {`), with any body lines
+    /// pushed to `body`; `None` when there's no example for it
+    fn member(&self, body: &mut String, ty: &PklType, path: &str, depth: usize, classes: &mut Vec<&'a str>) -> Option<String> {
+        let ty = match ty {
+            PklType::Nullable(inner) => inner.as_ref(),
+            other => other,
+        };
+        let indent = "  ".repeat(depth);
+        if let Some(class) = self.named_class(ty) {
+            let (class, open) = if class.is_abstract {
+                let member = self.first_subclass(class)?;
+                (member, format!(" = new {} {{\n", member.name))
+            } else {
+                (class, " {\n".to_string())
+            };
+            let mut inner = String::new();
+            self.object(&mut inner, class, path, depth + 1, classes);
+            if inner.is_empty() {
+                return None;
+            }
+            body.push_str(&inner);
+            body.push_str(&format!("{}}}\n", indent));
+            return Some(open);
+        }
+        match ty {
+            PklType::Listing(item) => {
+                // A list example (usually the default) is used whole
+                let listed: Vec<String> = match self.examples.get(path) {
+                    Some(Value::Array(items)) if items.iter().all(|value| !value.is_object() && !value.is_array()) => {
+                        items.iter().map(|value| format!("{}\n", pkl_literal(value, item))).collect()
+                    }
+                    _ => Vec::new(),
+                };
+                let elements = if listed.is_empty() {
+                    vec![self.element(item, &format!("{}.*", path), depth + 1, classes)?]
+                } else {
+                    listed
+                };
+                for element in elements {
+                    body.push_str(&format!("{}  {}", indent, element));
+                }
+                body.push_str(&format!("{}}}\n", indent));
+                Some(" {\n".to_string())
+            }
+            PklType::Mapping(_, value) => {
+                let mut entry = String::new();
+                let rest = self.member(&mut entry, value, &format!("{}.*", path), depth + 1, classes)?;
+                let key = crate::config_processor::pkl_string(EXAMPLE_KEY);
+                body.push_str(&format!("{}  [{}]{}{}", indent, key, rest, entry));
+                body.push_str(&format!("{}}}\n", indent));
+                Some(" {\n".to_string())
+            }
+            _ => {
+                let example = self.examples.get(path).filter(|example| !example.is_object() && !example.is_array())?;
+                Some(format!(" = {}\n", pkl_literal(example, ty)))
+            }
+        }
+    }
+
+    /// One listing element, including its line ending
+    fn element(&self, ty: &PklType, path: &str, depth: usize, classes: &mut Vec<&'a str>) -> Option<String> {
+        let ty = match ty {
+            PklType::Nullable(inner) => inner.as_ref(),
+            other => other,
+        };
+        if let Some(class) = self.named_class(ty) {
+            let (class, new) = if class.is_abstract {
+                let member = self.first_subclass(class)?;
+                (member, format!("new {} {{\n", member.name))
+            } else {
+                (class, "new {\n".to_string())
+            };
+            let mut inner = String::new();
+            self.object(&mut inner, class, path, depth + 1, classes);
+            if inner.is_empty() {
+                return None;
+            }
+            return Some(format!("{}{}{}}}\n", new, inner, "  ".repeat(depth)));
+        }
+        match ty {
+            PklType::Listing(_) | PklType::Mapping(..) => None,
+            _ => {
+                let example = self.examples.get(path).filter(|example| !example.is_object() && !example.is_array())?;
+                Some(format!("{}\n", pkl_literal(example, ty)))
+            }
+        }
+    }
+
+    /// The members of a class-typed object, skipping classes already being written
+    fn object(&self, out: &mut String, class: &'a PklClass, path: &str, depth: usize, classes: &mut Vec<&'a str>) {
+        if classes.contains(&class.name.as_str()) {
+            return;
+        }
+        classes.push(&class.name);
+        self.properties(out, &class.properties, path, depth, classes);
+        classes.pop();
+    }
+}
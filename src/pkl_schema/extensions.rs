@@ -0,0 +1,89 @@
+//! Extension stubs for generated modules
+//!
+//! Next to a generated `service.pkl`, spklr creates `service.ext.pkl`: a module that
+//! `extends` the generated one, with an empty subclass of each generated class. It's the
+//! supported place to add org-specific properties. The stub is created once and never
+//! overwritten, so what users add survives regeneration.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use super::PklModule;
+use crate::types::CliError;
+
+/// Suffix of extension stub modules
+pub const EXT_SUFFIX: &str = ".ext.pkl";
+
+/// Suffix of the subclasses in a stub
+pub const EXT_CLASS_SUFFIX: &str = "Ext";
+
+/// Stub path for a generated module (`service.pkl` → `service.ext.pkl`)
+pub fn ext_path(module_path: &Path) -> PathBuf {
+    let stem = module_path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    module_path.with_file_name(format!("{}{}", stem, EXT_SUFFIX))
+}
+
+/// Pkl source of the stub extending `module`, generated as `module_file`
+pub fn render_stub(module: &PklModule, module_file: &str) -> String {
+    let mut output = format!(
+        "/// Org-specific extensions to {name}.\n\
+         ///\n\
+         /// spklr created this file once and never overwrites it. Add properties here rather\n\
+         /// than in {file}, which is regenerated.\n\
+         open module {name}{suffix}\n\
+         \n\
+         extends \"{file}\"\n",
+        name = module.name,
+        file = module_file,
+        suffix = EXT_CLASS_SUFFIX,
+    );
+    for class in &module.classes {
+        output.push_str(&format!(
+            "\nopen class {name}{suffix} extends {name} {{\n}}\n",
+            name = class.name,
+            suffix = EXT_CLASS_SUFFIX,
+        ));
+    }
+    output
+}
+
+/// Create the stub for `module` written to `module_path`, unless it already exists
+///
+/// Returns the stub's path if it was created. Hermetic runs don't create stubs, since
+/// they only write the outputs named on the command line.
+pub fn ensure_stub(module_path: &Path, module: &PklModule) -> Result<Option<PathBuf>, CliError> {
+    if !module.open || crate::hermetic::is_enabled() {
+        return Ok(None);
+    }
+    let path = ext_path(module_path);
+    if path.exists() {
+        return Ok(None);
+    }
+    crate::read_only::ensure_allowed(format!("write {}", path.display()))?;
+
+    let module_file = module_path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let file = std::fs::OpenOptions::new().write(true).create_new(true).open(&path);
+    let mut file = match file {
+        Ok(file) => file,
+        // Another run created it first; it's still never overwritten
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => return Ok(None),
+        Err(e) => {
+            return Err(CliError::IoError {
+                context: format!("Creating extension stub: {}", path.display()),
+                source: e,
+            });
+        }
+    };
+    file.write_all(render_stub(module, &module_file).as_bytes())
+        .map_err(|e| CliError::IoError {
+            context: format!("Writing extension stub: {}", path.display()),
+            source: e,
+        })?;
+    Ok(Some(path))
+}
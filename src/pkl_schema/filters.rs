@@ -0,0 +1,174 @@
+//! Property filters for generated modules
+//!
+//! `include_properties` and `exclude_properties` in `[generator]` (or
+//! `--include-properties`/`--exclude-properties`) hide properties from the generated
+//! module before it's rendered. Patterns are dotted property paths from the module
+//! root, following class-typed properties into their classes:
+//!
+//! - `*` matches any one property name, `**` any number of them (as in
+//!   [`ConfigPath`](crate::config_path::ConfigPath) patterns)
+//! - an excluded property is removed along with everything under it
+//! - with include patterns, only matching properties, the properties leading to them,
+//!   and everything under them are kept
+//!
+//! Classes are shared, so a property excluded on one path is removed wherever its
+//! class is used. Classes only reachable through removed properties are dropped.
+//!
+//! ```toml
+//! [generator]
+//! exclude_properties = ["toolchain.*.version"]
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::{PklModule, PklProperty, PklType};
+use crate::config_path::{ConfigPath, matches_segments, prefix_matches};
+
+/// Include and exclude patterns for a module's properties
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PropertyFilter {
+    include: Vec<Vec<String>>,
+    exclude: Vec<Vec<String>>,
+}
+
+/// What the filter decided for one property, across every path reaching it
+#[derive(Debug, Default, Clone, Copy)]
+struct Decision {
+    excluded: bool,
+    included: bool,
+}
+
+impl PropertyFilter {
+    pub fn new(include: &[String], exclude: &[String]) -> Self {
+        let split = |patterns: &[String]| {
+            patterns
+                .iter()
+                .map(|pattern| ConfigPath::new(pattern).segments().to_vec())
+                .collect()
+        };
+        Self {
+            include: split(include),
+            exclude: split(exclude),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.include.is_empty() && self.exclude.is_empty()
+    }
+
+    /// Remove filtered properties, and classes only they used, from `module`
+    pub fn apply(&self, module: &mut PklModule) {
+        if self.is_empty() {
+            return;
+        }
+        let reachable_before = reachable_classes(module);
+
+        let mut walk = Walk {
+            filter: self,
+            module,
+            path: Vec::new(),
+            classes: Vec::new(),
+            decisions: BTreeMap::new(),
+        };
+        walk.visit(None, &module.properties, false);
+        let decisions = walk.decisions;
+        // Properties no path reaches are left alone
+        let keep = |owner: Option<&str>, property: &PklProperty| {
+            match decisions.get(&(owner.map(str::to_string), property.name.clone())) {
+                Some(decision) => !decision.excluded && (self.include.is_empty() || decision.included),
+                None => true,
+            }
+        };
+        module.properties.retain(|property| keep(None, property));
+        for class in &mut module.classes {
+            let owner = class.name.clone();
+            class.properties.retain(|property| keep(Some(&owner), property));
+        }
+
+        let reachable_after = reachable_classes(module);
+        module
+            .classes
+            .retain(|class| !reachable_before.contains(&class.name) || reachable_after.contains(&class.name));
+        module.typealiases.retain(|typealias| {
+            !reachable_before.contains(&typealias.name) || reachable_after.contains(&typealias.name)
+        });
+    }
+}
+
+/// A walk over every property path of a module, recording filter decisions
+struct Walk<'a> {
+    filter: &'a PropertyFilter,
+    module: &'a PklModule,
+    path: Vec<String>,
+    /// Classes entered on the current path
+    classes: Vec<String>,
+    decisions: BTreeMap<(Option<String>, String), Decision>,
+}
+
+impl<'a> Walk<'a> {
+    fn visit(&mut self, owner: Option<&str>, properties: &'a [PklProperty], under_include: bool) {
+        for property in properties {
+            self.path.push(property.name.clone());
+            let excluded = self.filter.exclude.iter().any(|pattern| matches_segments(pattern, &self.path));
+            let matched = under_include || self.filter.include.iter().any(|pattern| matches_segments(pattern, &self.path));
+            let leads_to_include = self.filter.include.iter().any(|pattern| prefix_matches(pattern, &self.path));
+
+            let decision = self
+                .decisions
+                .entry((owner.map(str::to_string), property.name.clone()))
+                .or_default();
+            decision.excluded |= excluded;
+            decision.included |= matched || leads_to_include;
+
+            if !excluded {
+                for name in class_names(&property.ty) {
+                    let module = self.module;
+                    let Some(class) = module.classes.iter().find(|class| class.name == name) else {
+                        continue;
+                    };
+                    // The members of a tagged union are on the same paths as its base
+                    for class in std::iter::once(class).chain(module.subclasses(&class.name)) {
+                        // Recursive classes are only followed once per path
+                        if self.classes.contains(&class.name) {
+                            continue;
+                        }
+                        self.classes.push(class.name.clone());
+                        self.visit(Some(&class.name), &class.properties, matched);
+                        self.classes.pop();
+                    }
+                }
+            }
+            self.path.pop();
+        }
+    }
+}
+
+pub(crate) fn class_names(ty: &PklType) -> Vec<String> {
+    match ty {
+        PklType::Named(name) => vec![name.clone()],
+        PklType::Listing(inner) | PklType::Nullable(inner) => class_names(inner),
+        PklType::Mapping(key, value) => class_names(key).into_iter().chain(class_names(value)).collect(),
+        PklType::Union(members) => members.iter().flat_map(class_names).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Classes and typealiases reachable from the module's properties
+fn reachable_classes(module: &PklModule) -> BTreeSet<String> {
+    let mut queue: Vec<String> = module.properties.iter().flat_map(|property| class_names(&property.ty)).collect();
+    let mut seen = BTreeSet::new();
+    while let Some(name) = queue.pop() {
+        if !seen.insert(name.clone()) {
+            continue;
+        }
+        if let Some(class) = module.classes.iter().find(|class| class.name == name) {
+            queue.extend(class.properties.iter().flat_map(|property| class_names(&property.ty)));
+            queue.extend(class.extends.iter().cloned());
+            queue.extend(module.subclasses(&class.name).map(|subclass| subclass.name.clone()));
+        }
+        if let Some(typealias) = module.typealiases.iter().find(|typealias| typealias.name == name) {
+            queue.extend(class_names(&typealias.ty));
+        }
+    }
+    seen
+}
@@ -0,0 +1,197 @@
+//! Cross-module imports for split types
+//!
+//! With `split_types` in `[generator]`, modules generated together share their types
+//! instead of each carrying a copy: a type defined identically by more than one module
+//! is kept in the first module that defines it and dropped from the others. Types that
+//! share a name but differ stay in every module defining them. [`resolve_imports`]
+//! then gives every module an aliased import for each module it borrows types from,
+//! and qualifies the references:
+//!
+//! ```pkl
+//! open module Project
+//!
+//! import "Tasks.pkl" as tasksModule
+//!
+//! tasks: Mapping<String, tasksModule.TaskConfig>
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::{PklClass, PklModule, PklType, PklTypeAlias};
+
+/// An `import "<uri>" as <alias>` declaration
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PklImport {
+    pub uri: String,
+    #[serde(default)]
+    pub alias: Option<String>,
+}
+
+impl PklImport {
+    /// Pkl source for the import
+    pub fn render(&self) -> String {
+        match &self.alias {
+            Some(alias) => format!("import \"{}\" as {}", self.uri, alias),
+            None => format!("import \"{}\"", self.uri),
+        }
+    }
+}
+
+/// File a module is generated as, and imported from
+pub fn module_file(module: &PklModule) -> String {
+    format!("{}.pkl", module.name)
+}
+
+/// Keep each type only in the first module defining it, then resolve imports
+///
+/// Only identical definitions are shared. Returns the names defined differently by
+/// more than one module; each of those modules keeps its own definition.
+pub fn split_types(modules: &mut [PklModule]) -> Vec<String> {
+    let mut classes: BTreeMap<String, PklClass> = BTreeMap::new();
+    let mut typealiases: BTreeMap<String, PklTypeAlias> = BTreeMap::new();
+    let mut conflicts = BTreeSet::new();
+    for module in modules.iter_mut() {
+        module.classes.retain(|class| {
+            if typealiases.contains_key(&class.name) {
+                conflicts.insert(class.name.clone());
+                return true;
+            }
+            match classes.get(&class.name) {
+                None => {
+                    classes.insert(class.name.clone(), class.clone());
+                    true
+                }
+                Some(first) if first == class => false,
+                Some(_) => {
+                    conflicts.insert(class.name.clone());
+                    true
+                }
+            }
+        });
+        module.typealiases.retain(|typealias| {
+            if classes.contains_key(&typealias.name) {
+                conflicts.insert(typealias.name.clone());
+                return true;
+            }
+            match typealiases.get(&typealias.name) {
+                None => {
+                    typealiases.insert(typealias.name.clone(), typealias.clone());
+                    true
+                }
+                Some(first) if first == typealias => false,
+                Some(_) => {
+                    conflicts.insert(typealias.name.clone());
+                    true
+                }
+            }
+        });
+    }
+    resolve_imports(modules);
+    conflicts.into_iter().collect()
+}
+
+/// Import and qualify references to types defined in other modules of `modules`
+///
+/// References that no module defines are left alone.
+pub fn resolve_imports(modules: &mut [PklModule]) {
+    let mut owners: BTreeMap<String, usize> = BTreeMap::new();
+    for (index, module) in modules.iter().enumerate() {
+        let names = module.classes.iter().map(|class| &class.name);
+        for name in names.chain(module.typealiases.iter().map(|typealias| &typealias.name)) {
+            owners.entry(name.clone()).or_insert(index);
+        }
+    }
+    let files: Vec<String> = modules.iter().map(module_file).collect();
+    let names: Vec<String> = modules.iter().map(|module| module.name.clone()).collect();
+
+    for (index, module) in modules.iter_mut().enumerate() {
+        let local: BTreeSet<String> = module
+            .classes
+            .iter()
+            .map(|class| class.name.clone())
+            .chain(module.typealiases.iter().map(|typealias| typealias.name.clone()))
+            .collect();
+        // Aliases also share the module's namespace with its properties
+        let taken: BTreeSet<String> = local
+            .iter()
+            .cloned()
+            .chain(module.properties.iter().map(|property| property.name.clone()))
+            .collect();
+        let mut aliases: BTreeMap<usize, String> = module
+            .imports
+            .iter()
+            .filter_map(|import| {
+                let owner = files.iter().position(|file| *file == import.uri)?;
+                Some((owner, import.alias.clone().unwrap_or_else(|| names[owner].clone())))
+            })
+            .collect();
+
+        let mut qualify = |name: &str| -> Option<String> {
+            if local.contains(name) || name.contains('.') {
+                return None;
+            }
+            let owner = *owners.get(name).filter(|owner| **owner != index)?;
+            let alias = aliases
+                .entry(owner)
+                .or_insert_with(|| import_alias(&names[owner], &taken))
+                .clone();
+            Some(format!("{}.{}", alias, name))
+        };
+        let types = module
+            .properties
+            .iter_mut()
+            .map(|property| &mut property.ty)
+            .chain(module.classes.iter_mut().flat_map(|class| class.properties.iter_mut().map(|property| &mut property.ty)))
+            .chain(module.typealiases.iter_mut().map(|typealias| &mut typealias.ty));
+        for ty in types {
+            qualify_type(ty, &mut qualify);
+        }
+        for parent in module.classes.iter_mut().filter_map(|class| class.extends.as_mut()) {
+            if let Some(qualified) = qualify(parent) {
+                *parent = qualified;
+            }
+        }
+
+        for (owner, alias) in aliases {
+            if !module.imports.iter().any(|import| import.uri == files[owner]) {
+                module.imports.push(PklImport {
+                    uri: files[owner].clone(),
+                    alias: Some(alias),
+                });
+            }
+        }
+    }
+}
+
+/// Alias for importing `module`: its name in camelCase, suffixed with `Module` when that
+/// is already a class, typealias or property name in the importing module
+fn import_alias(module: &str, taken: &BTreeSet<String>) -> String {
+    let mut chars = module.chars();
+    let alias = chars
+        .next()
+        .map(|first| first.to_ascii_lowercase().to_string() + chars.as_str())
+        .unwrap_or_default();
+    if taken.contains(&alias) {
+        format!("{}Module", alias)
+    } else {
+        alias
+    }
+}
+
+fn qualify_type(ty: &mut PklType, qualify: &mut impl FnMut(&str) -> Option<String>) {
+    match ty {
+        PklType::Named(name) => {
+            if let Some(qualified) = qualify(name) {
+                *name = qualified;
+            }
+        }
+        PklType::Listing(inner) | PklType::Nullable(inner) => qualify_type(inner, qualify),
+        PklType::Mapping(key, value) => {
+            qualify_type(key, qualify);
+            qualify_type(value, qualify);
+        }
+        PklType::Union(members) => members.iter_mut().for_each(|member| qualify_type(member, qualify)),
+        _ => {}
+    }
+}
@@ -0,0 +1,98 @@
+//! Versioned serialization of the module model
+//!
+//! [`PklModule`]s can be saved as JSON and read back by a later spklr, for caches,
+//! bundles, and tools that post-process generated schemas. Every document records the
+//! `schema_version` of its shape:
+//!
+//! ```json
+//! {
+//!   "schema_version": 1,
+//!   "modules": [{ "name": "Project", "open": true, "classes": [], ... }]
+//! }
+//! ```
+//!
+//! [`from_json`] upgrades older documents one version at a time before deserializing,
+//! and refuses documents from a newer spklr instead of guessing at their shape. When
+//! the model changes shape, bump [`SCHEMA_VERSION`] and add the upgrade from the
+//! previous version to [`UPGRADES`].
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use super::PklModule;
+use crate::types::CliError;
+
+/// Shape version written by this spklr
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Upgrades from each version to the next: `UPGRADES[n]` turns version `n` into `n + 1`
+const UPGRADES: [fn(Value) -> Result<Value, CliError>; SCHEMA_VERSION as usize] = [upgrade_unversioned];
+
+/// A serialized set of modules
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IrDocument {
+    pub schema_version: u32,
+    pub modules: Vec<PklModule>,
+}
+
+/// `modules` as a document of the current version
+pub fn to_json(modules: &[PklModule]) -> Value {
+    json!({
+        "schema_version": SCHEMA_VERSION,
+        "modules": modules,
+    })
+}
+
+/// `modules` as pretty-printed JSON of the current version
+pub fn to_string(modules: &[PklModule]) -> Result<String, CliError> {
+    serde_json::to_string_pretty(&to_json(modules)).map_err(|e| CliError::ValidationError { source: Box::new(e) })
+}
+
+/// Modules from a document of any version up to [`SCHEMA_VERSION`]
+pub fn from_json(document: Value) -> Result<Vec<PklModule>, CliError> {
+    let version = version_of(&document)?;
+    if version > SCHEMA_VERSION {
+        return Err(CliError::Generic(format!(
+            "Module document has schema_version {}, but this spklr reads up to {}; upgrade spklr to read it",
+            version, SCHEMA_VERSION
+        )));
+    }
+    let document = UPGRADES[version as usize..]
+        .iter()
+        .try_fold(document, |document, upgrade| upgrade(document))?;
+    let document: IrDocument =
+        serde_json::from_value(document).map_err(|e| CliError::ValidationError { source: Box::new(e) })?;
+    Ok(document.modules)
+}
+
+/// Modules from JSON text of any version up to [`SCHEMA_VERSION`]
+pub fn from_str(content: &str) -> Result<Vec<PklModule>, CliError> {
+    let document: Value = serde_json::from_str(content).map_err(|e| CliError::ValidationError { source: Box::new(e) })?;
+    from_json(document)
+}
+
+/// The document's `schema_version`; documents without one are version 0
+fn version_of(document: &Value) -> Result<u32, CliError> {
+    match document.get("schema_version") {
+        None => Ok(0),
+        Some(version) => version
+            .as_u64()
+            .and_then(|version| u32::try_from(version).ok())
+            .ok_or_else(|| CliError::Generic(format!("Invalid module document schema_version: {}", version))),
+    }
+}
+
+/// Version 0 is a bare module, or a list of them, with no envelope
+fn upgrade_unversioned(document: Value) -> Result<Value, CliError> {
+    let modules = match document {
+        Value::Array(modules) => modules,
+        module @ Value::Object(_) => vec![module],
+        other => {
+            return Err(CliError::Generic(format!(
+                "Expected a module or a list of modules, found {}",
+                other
+            )));
+        }
+    };
+    Ok(json!({ "schema_version": 1, "modules": modules }))
+}
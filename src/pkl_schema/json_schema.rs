@@ -0,0 +1,600 @@
+//! JSON Schema import and export
+//!
+//! [`to_module`] converts an arbitrary JSON Schema document into an open [`PklModule`]:
+//!
+//! - root properties become module properties; properties not listed in `required`
+//!   are nullable
+//! - `definitions`/`$defs` with properties become classes, other definitions become
+//!   typealiases
+//! - inline objects with properties become classes named after their parent and
+//!   property (`ProjectConfig.owners` → `ProjectConfigOwners`)
+//! - string `enum`s and `const`s become literal unions, `anyOf`/`oneOf` become unions,
+//!   and a `null` member makes the type nullable
+//! - a string enum whose values are documented, by `oneOf` `const`s with a `description`
+//!   (or `deprecated`) or by `enumDescriptions`/`markdownEnumDescriptions`, keeps each
+//!   value's docs, rendered above its alternative
+//! - `additionalProperties` objects become `Mapping<String, T>`, arrays `Listing<T>`
+//! - an `anyOf`/`oneOf` of objects that all have a string `const` property (a tagged
+//!   union, like serde's internally tagged enums) becomes an abstract class with that
+//!   property, and each member a subclass fixing it:
+//!
+//!   ```pkl
+//!   abstract class TaskDependency {
+//!     type: "project"|"tag"
+//!   }
+//!
+//!   open class TaskDependencyProject extends TaskDependency {
+//!     fixed type: "project"
+//!     project: String
+//!   }
+//!   ```
+//!
+//! Only local references (`#/definitions/<name>`, `#/$defs/<name>`) are supported.
+//!
+//! [`to_json_schema`] goes the other way, so editors without Pkl support can still
+//! validate against a generated module: classes and typealiases become `definitions`,
+//! and properties whose type isn't nullable and that have no default are `required`. References to imported
+//! modules' types point at the `<Module>.json` exported alongside.
+
+use serde_json::{Map, Value, json};
+
+use super::{PklClass, PklModule, PklProperty, PklType, PklTypeAlias, PklVariant};
+use crate::types::CliError;
+
+const REFERENCE_PREFIXES: [&str; 2] = ["#/definitions/", "#/$defs/"];
+
+/// Build a module from a JSON Schema, named after its `title` or `fallback_name`
+pub fn to_module(schema: &Value, fallback_name: &str) -> Result<PklModule, CliError> {
+    let Value::Object(root) = schema else {
+        return Err(CliError::Generic("JSON Schema root must be an object".to_string()));
+    };
+    let title = root.get("title").and_then(Value::as_str).unwrap_or(fallback_name);
+    let definitions = root
+        .get("definitions")
+        .or_else(|| root.get("$defs"))
+        .and_then(Value::as_object);
+    let mut importer = Importer {
+        definitions: definitions.cloned().unwrap_or_default(),
+        ..Importer::default()
+    };
+
+    for (name, definition) in definitions.into_iter().flatten() {
+        let name = pascal_case(name);
+        if definition.get("properties").is_some() {
+            importer.class(&name, definition)?;
+            continue;
+        }
+        let ty = importer.pkl_type(definition, &name)?;
+        // A tagged union definition is its own base class
+        if ty == PklType::Named(name.clone())
+            && let Some(base) = importer.classes.iter_mut().find(|class| class.name == name)
+        {
+            base.doc = description(definition);
+            continue;
+        }
+        importer.typealiases.push(PklTypeAlias {
+            name,
+            doc: description(definition),
+            ty,
+        });
+    }
+
+    let properties = importer.properties(root, &pascal_case(title))?;
+    importer.finish_tagged();
+    Ok(PklModule {
+        name: pascal_case(title),
+        open: true,
+        doc: description(schema),
+        imports: Vec::new(),
+        properties,
+        classes: importer.classes,
+        typealiases: importer.typealiases,
+    })
+}
+
+/// A draft-07 JSON Schema document for `module`
+pub fn to_json_schema(module: &PklModule) -> Value {
+    let mut schema = Map::new();
+    schema.insert("$schema".to_string(), json!("http://json-schema.org/draft-07/schema#"));
+    schema.insert("title".to_string(), json!(module.name));
+    if let Some(doc) = &module.doc {
+        schema.insert("description".to_string(), json!(doc));
+    }
+    schema.extend(object_schema(&module.properties));
+
+    let mut definitions = Map::new();
+    for class in &module.classes {
+        let mut definition = Map::new();
+        if let Some(doc) = &class.doc {
+            definition.insert("description".to_string(), json!(doc));
+        }
+        if class.is_abstract {
+            // A tagged union: one of its members, each of which has its tag
+            let members: Vec<Value> = module
+                .subclasses(&class.name)
+                .map(|member| type_schema(&PklType::Named(member.name.clone())))
+                .collect();
+            definition.insert("oneOf".to_string(), Value::Array(members));
+        } else {
+            definition.extend(object_schema(&class.properties));
+        }
+        definitions.insert(class.name.clone(), Value::Object(definition));
+    }
+    for typealias in &module.typealiases {
+        let mut definition = type_schema(&typealias.ty);
+        if let (Some(doc), Value::Object(definition)) = (&typealias.doc, &mut definition) {
+            definition.insert("description".to_string(), json!(doc));
+        }
+        definitions.insert(typealias.name.clone(), definition);
+    }
+    if !definitions.is_empty() {
+        schema.insert("definitions".to_string(), Value::Object(definitions));
+    }
+    let mut schema = Value::Object(schema);
+    for import in &module.imports {
+        let alias = import.alias.clone().unwrap_or_else(|| import.uri.trim_end_matches(".pkl").to_string());
+        let document = format!("{}.json", import.uri.trim_end_matches(".pkl"));
+        point_to_import(&mut schema, &alias, &document);
+    }
+    schema
+}
+
+/// Point `$ref`s to `<alias>.<Type>` at the `document` generated for the imported module
+fn point_to_import(schema: &mut Value, alias: &str, document: &str) {
+    let local = format!("#/definitions/{}.", alias);
+    match schema {
+        Value::Object(object) => {
+            for (key, value) in object.iter_mut() {
+                match (key.as_str(), value) {
+                    ("$ref", Value::String(reference)) => {
+                        if let Some(name) = reference.strip_prefix(&local) {
+                            *reference = format!("{}#/definitions/{}", document, name);
+                        }
+                    }
+                    (_, value) => point_to_import(value, alias, document),
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(|item| point_to_import(item, alias, document)),
+        _ => {}
+    }
+}
+
+fn object_schema(properties: &[PklProperty]) -> Map<String, Value> {
+    let mut schema = Map::new();
+    schema.insert("type".to_string(), json!("object"));
+    let mut required = Vec::new();
+    let mut property_schemas = Map::new();
+    for property in properties {
+        let mut property_schema = type_schema(&property.ty);
+        if let Value::Object(property_schema) = &mut property_schema {
+            if let Some(doc) = &property.doc {
+                property_schema.insert("description".to_string(), json!(doc));
+            }
+            if let Some(default) = &property.default {
+                property_schema.insert("default".to_string(), default.clone());
+                if property.fixed {
+                    property_schema.insert("const".to_string(), default.clone());
+                }
+            }
+            if !property.examples.is_empty() {
+                property_schema.insert("examples".to_string(), json!(property.examples));
+            }
+            if let Some(message) = &property.deprecated {
+                property_schema.insert("deprecated".to_string(), json!(true));
+                if !message.is_empty() {
+                    property_schema.insert("deprecationMessage".to_string(), json!(message));
+                }
+            }
+            // Pkl constraints have no JSON Schema equivalent; keep them for readers
+            if !property.constraints.is_empty() {
+                let expressions: Vec<&str> = property.constraints.iter().map(|c| c.expression.as_str()).collect();
+                property_schema.insert("$comment".to_string(), json!(format!("Pkl constraints: {}", expressions.join(", "))));
+            }
+        }
+        property_schemas.insert(property.name.clone(), property_schema);
+        // Properties with a default needn't be set
+        if property.default.is_none() && !matches!(property.ty, PklType::Nullable(_) | PklType::Any) {
+            required.push(json!(property.name));
+        }
+    }
+    schema.insert("properties".to_string(), Value::Object(property_schemas));
+    if !required.is_empty() {
+        schema.insert("required".to_string(), Value::Array(required));
+    }
+    schema
+}
+
+/// JSON Schema for a Pkl type
+pub fn type_schema(ty: &PklType) -> Value {
+    match ty {
+        PklType::String => json!({ "type": "string" }),
+        PklType::Int => json!({ "type": "integer" }),
+        PklType::Number => json!({ "type": "number" }),
+        PklType::Boolean => json!({ "type": "boolean" }),
+        PklType::Any => json!({}),
+        PklType::Listing(item) => json!({ "type": "array", "items": type_schema(item) }),
+        PklType::Mapping(_, value) => json!({ "type": "object", "additionalProperties": type_schema(value) }),
+        PklType::Named(name) => json!({ "$ref": format!("#/definitions/{}", name) }),
+        PklType::StringLiteral(value) => json!({ "const": value }),
+        PklType::Union(members) => match string_literals(members) {
+            Some(values) => json!({ "enum": values }),
+            None => json!({ "anyOf": members.iter().map(type_schema).collect::<Vec<_>>() }),
+        },
+        PklType::Variants(variants) => json!({ "oneOf": variants.iter().map(variant_schema).collect::<Vec<_>>() }),
+        PklType::Nullable(inner) => {
+            let mut schema = type_schema(inner);
+            match schema.get_mut("enum").and_then(Value::as_array_mut) {
+                Some(values) => values.push(Value::Null),
+                None => match schema.get("type").and_then(Value::as_str).map(str::to_string) {
+                    Some(primitive) => schema["type"] = json!([primitive, "null"]),
+                    None => schema = json!({ "anyOf": [schema, { "type": "null" }] }),
+                },
+            }
+            schema
+        }
+    }
+}
+
+fn variant_schema(variant: &PklVariant) -> Value {
+    let mut schema = Map::new();
+    schema.insert("const".to_string(), json!(variant.value));
+    if let Some(doc) = &variant.doc {
+        schema.insert("description".to_string(), json!(doc));
+    }
+    if let Some(message) = &variant.deprecated {
+        schema.insert("deprecated".to_string(), json!(true));
+        if !message.is_empty() {
+            schema.insert("deprecationMessage".to_string(), json!(message));
+        }
+    }
+    Value::Object(schema)
+}
+
+fn string_literals(members: &[PklType]) -> Option<Vec<String>> {
+    members
+        .iter()
+        .map(|member| match member {
+            PklType::StringLiteral(value) => Some(value.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[derive(Default)]
+struct Importer {
+    classes: Vec<PklClass>,
+    typealiases: Vec<PklTypeAlias>,
+    /// The document's `definitions`, to look into referenced union members
+    definitions: Map<String, Value>,
+    /// Members of tagged unions, made subclasses of their base once every class exists
+    tagged: Vec<TaggedMember>,
+}
+
+/// A class that is one member of a tagged union
+struct TaggedMember {
+    class: String,
+    base: String,
+    tag: String,
+    value: String,
+}
+
+impl Importer {
+    fn class(&mut self, name: &str, schema: &Value) -> Result<(), CliError> {
+        // Reserve the slot first so nested classes follow their parent
+        let index = self.classes.len();
+        self.classes.push(PklClass::new(name, description(schema), Vec::new()));
+        let empty = Map::new();
+        let properties = self.properties(schema.as_object().unwrap_or(&empty), name)?;
+        self.classes[index].properties = properties;
+        Ok(())
+    }
+
+    fn properties(&mut self, schema: &Map<String, Value>, owner: &str) -> Result<Vec<PklProperty>, CliError> {
+        let required: Vec<&str> = schema
+            .get("required")
+            .and_then(Value::as_array)
+            .map(|names| names.iter().filter_map(Value::as_str).collect())
+            .unwrap_or_default();
+
+        let mut properties = Vec::new();
+        for (name, property) in schema.get("properties").and_then(Value::as_object).into_iter().flatten() {
+            let ty = self.pkl_type(property, &format!("{}{}", owner, pascal_case(name)))?;
+            let ty = if required.contains(&name.as_str()) { ty } else { ty.nullable() };
+            let mut pkl_property = PklProperty::new(name.clone(), description(property), ty);
+            if property.get("deprecated").and_then(Value::as_bool) == Some(true) {
+                let message = property.get("deprecationMessage").and_then(Value::as_str).unwrap_or_default();
+                pkl_property.deprecated = Some(message.to_string());
+            }
+            pkl_property.examples = property.get("examples").and_then(Value::as_array).cloned().unwrap_or_default();
+            properties.push(pkl_property);
+        }
+        Ok(properties)
+    }
+
+    /// An abstract class `name` for `anyOf`/`oneOf` members that are all objects told
+    /// apart by a string `const` property (the tag), with a subclass for each member
+    ///
+    /// Returns `None`, importing nothing, when the members aren't a tagged union.
+    fn tagged_union(&mut self, members: &[Value], name: &str) -> Result<Option<String>, CliError> {
+        // Each member's schema, with its definition's name when it's a reference
+        let mut resolved = Vec::new();
+        for member in members.iter().filter(|member| !is_null_schema(member)) {
+            let reference = member.get("$ref").and_then(Value::as_str);
+            let (definition, schema) = match reference {
+                Some(reference) => {
+                    let Some(target) = REFERENCE_PREFIXES.iter().find_map(|prefix| reference.strip_prefix(prefix)) else {
+                        return Ok(None);
+                    };
+                    let Some(schema) = self.definitions.get(target) else {
+                        return Ok(None);
+                    };
+                    (Some(pascal_case(target)), schema.clone())
+                }
+                None => (None, member.clone()),
+            };
+            if !schema.get("properties").is_some_and(Value::is_object) {
+                return Ok(None);
+            }
+            resolved.push((definition, schema));
+        }
+        if resolved.len() < 2 {
+            return Ok(None);
+        }
+
+        let tag_of = |schema: &Value, tag: &str| schema.get("properties").and_then(|properties| properties.get(tag)).and_then(tag_value);
+        let candidates: Vec<String> = resolved[0].1["properties"].as_object().into_iter().flatten().map(|(tag, _)| tag.clone()).collect();
+        let Some((tag, values)) = candidates.into_iter().find_map(|tag| {
+            let values: Vec<String> = resolved.iter().map(|(_, schema)| tag_of(schema, &tag)).collect::<Option<_>>()?;
+            let distinct = values.iter().enumerate().all(|(index, value)| !values[..index].contains(value));
+            distinct.then_some((tag, values))
+        }) else {
+            return Ok(None);
+        };
+
+        let literals = values.iter().map(|value| PklType::StringLiteral(value.clone())).collect();
+        let mut base = PklClass::new(name, None, vec![PklProperty::new(tag.clone(), None, PklType::Union(literals))]);
+        base.is_abstract = true;
+        self.classes.push(base);
+        for ((definition, schema), value) in resolved.iter().zip(values) {
+            let class = match definition {
+                Some(definition) => definition.clone(),
+                None => {
+                    let class = match schema.get("title").and_then(Value::as_str) {
+                        Some(title) => pascal_case(title),
+                        None => format!("{}{}", name, pascal_case(&value)),
+                    };
+                    self.class(&class, schema)?;
+                    class
+                }
+            };
+            self.tagged.push(TaggedMember {
+                class,
+                base: name.to_string(),
+                tag: tag.clone(),
+                value,
+            });
+        }
+        Ok(Some(name.to_string()))
+    }
+
+    /// Make each tagged union member a subclass of its base, with its tag `fixed`
+    fn finish_tagged(&mut self) {
+        for member in std::mem::take(&mut self.tagged) {
+            let Some(class) = self.classes.iter_mut().find(|class| class.name == member.class) else {
+                continue;
+            };
+            let doc = class
+                .properties
+                .iter()
+                .position(|property| property.name == member.tag)
+                .and_then(|index| class.properties.remove(index).doc);
+            let mut tag = PklProperty::new(member.tag, doc, PklType::StringLiteral(member.value));
+            tag.fixed = true;
+            class.properties.insert(0, tag);
+            class.extends = Some(member.base);
+        }
+    }
+
+    /// The Pkl type for `schema`; `name` is used for any class it needs
+    fn pkl_type(&mut self, schema: &Value, name: &str) -> Result<PklType, CliError> {
+        let Value::Object(object) = schema else {
+            // `true` or `{}` accept anything
+            return Ok(PklType::Any);
+        };
+
+        if let Some(reference) = object.get("$ref").and_then(Value::as_str) {
+            return REFERENCE_PREFIXES
+                .iter()
+                .find_map(|prefix| reference.strip_prefix(prefix))
+                .map(|target| PklType::Named(pascal_case(target)))
+                .ok_or_else(|| CliError::Generic(format!("Unsupported $ref '{}': only local definitions are supported", reference)));
+        }
+        if let Some(value) = object.get("const") {
+            return Ok(literal(value));
+        }
+        if let Some(values) = object.get("enum").and_then(Value::as_array) {
+            if let Some(variants) = described_enum(object, values) {
+                return Ok(variants_type(variants, values.iter().any(Value::is_null)));
+            }
+            let nullable = values.iter().any(Value::is_null);
+            let members: Vec<PklType> = values.iter().filter(|v| !v.is_null()).map(literal).collect();
+            return Ok(union(members, nullable));
+        }
+        for key in ["anyOf", "oneOf"] {
+            if let Some(variants) = object.get(key).and_then(Value::as_array) {
+                let nullable = variants.iter().any(is_null_schema);
+                if let Some(variants) = documented_consts(variants) {
+                    return Ok(variants_type(variants, nullable));
+                }
+                if let Some(base) = self.tagged_union(variants, name)? {
+                    let ty = PklType::Named(base);
+                    return Ok(if nullable { ty.nullable() } else { ty });
+                }
+                let members = variants
+                    .iter()
+                    .filter(|variant| !is_null_schema(variant))
+                    .enumerate()
+                    .map(|(index, variant)| self.pkl_type(variant, &format!("{}{}", name, index + 1)))
+                    .collect::<Result<Vec<_>, _>>()?;
+                return Ok(union(members, nullable));
+            }
+        }
+        if let Some([single]) = object.get("allOf").and_then(Value::as_array).map(Vec::as_slice) {
+            return self.pkl_type(single, name);
+        }
+
+        let types: Vec<&str> = match object.get("type") {
+            Some(Value::String(ty)) => vec![ty.as_str()],
+            Some(Value::Array(types)) => types.iter().filter_map(Value::as_str).collect(),
+            _ if object.contains_key("properties") => vec!["object"],
+            _ => Vec::new(),
+        };
+        let nullable = types.contains(&"null");
+        let mut members = Vec::new();
+        for ty in types.into_iter().filter(|ty| *ty != "null") {
+            members.push(match ty {
+                "string" => PklType::String,
+                "integer" => PklType::Int,
+                "number" => PklType::Number,
+                "boolean" => PklType::Boolean,
+                "array" => {
+                    let item = match object.get("items") {
+                        Some(items) => self.pkl_type(items, &format!("{}Item", name))?,
+                        None => PklType::Any,
+                    };
+                    PklType::Listing(Box::new(item))
+                }
+                "object" if object.contains_key("properties") => {
+                    self.class(name, schema)?;
+                    PklType::Named(name.to_string())
+                }
+                "object" => {
+                    let value = match object.get("additionalProperties") {
+                        Some(Value::Object(_)) => self.pkl_type(&object["additionalProperties"], &format!("{}Value", name))?,
+                        _ => PklType::Any,
+                    };
+                    PklType::Mapping(Box::new(PklType::String), Box::new(value))
+                }
+                _ => PklType::Any,
+            });
+        }
+        if members.is_empty() {
+            return Ok(PklType::Any);
+        }
+        Ok(union(members, nullable))
+    }
+}
+
+fn union(members: Vec<PklType>, nullable: bool) -> PklType {
+    let mut unique: Vec<PklType> = Vec::new();
+    for member in members {
+        if !unique.contains(&member) {
+            unique.push(member);
+        }
+    }
+    let ty = match unique.len() {
+        0 => PklType::Any,
+        1 => unique.remove(0),
+        _ => PklType::Union(unique),
+    };
+    if nullable { ty.nullable() } else { ty }
+}
+
+fn literal(value: &Value) -> PklType {
+    match value {
+        Value::String(value) => PklType::StringLiteral(value.clone()),
+        Value::Bool(_) => PklType::Boolean,
+        Value::Number(number) if number.is_i64() || number.is_u64() => PklType::Int,
+        Value::Number(_) => PklType::Number,
+        _ => PklType::Any,
+    }
+}
+
+/// The values of a string `enum` with `enumDescriptions` or `markdownEnumDescriptions`,
+/// which list a description for each value in order
+fn described_enum(object: &Map<String, Value>, values: &[Value]) -> Option<Vec<PklVariant>> {
+    let descriptions = ["markdownEnumDescriptions", "enumDescriptions"]
+        .iter()
+        .find_map(|key| object.get(*key).and_then(Value::as_array))?;
+    let variants = values
+        .iter()
+        .zip(descriptions.iter().map(Some).chain(std::iter::repeat(None)))
+        .filter(|(value, _)| !value.is_null())
+        .map(|(value, doc)| {
+            let doc = doc.and_then(Value::as_str).map(str::trim).filter(|doc| !doc.is_empty());
+            Some(PklVariant::new(value.as_str()?, doc.map(str::to_string)))
+        })
+        .collect::<Option<Vec<_>>>()?;
+    variants.iter().any(|variant| variant.doc.is_some()).then_some(variants)
+}
+
+/// The values of `anyOf`/`oneOf` members that are all string `const`s, when any of them
+/// has a description or is deprecated
+fn documented_consts(members: &[Value]) -> Option<Vec<PklVariant>> {
+    let variants = members
+        .iter()
+        .filter(|member| !is_null_schema(member))
+        .map(|member| {
+            let mut variant = PklVariant::new(member.get("const")?.as_str()?, description(member));
+            if member.get("deprecated").and_then(Value::as_bool) == Some(true) {
+                let message = member.get("deprecationMessage").and_then(Value::as_str).unwrap_or_default();
+                variant.deprecated = Some(message.to_string());
+            }
+            Some(variant)
+        })
+        .collect::<Option<Vec<_>>>()?;
+    variants
+        .iter()
+        .any(|variant| variant.doc.is_some() || variant.deprecated.is_some())
+        .then_some(variants)
+}
+
+fn variants_type(mut variants: Vec<PklVariant>, nullable: bool) -> PklType {
+    let mut seen = Vec::new();
+    variants.retain(|variant| {
+        let new = !seen.contains(&variant.value);
+        seen.push(variant.value.clone());
+        new
+    });
+    let ty = PklType::Variants(variants);
+    if nullable { ty.nullable() } else { ty }
+}
+
+/// The value a tag property must have: a string `const`, or a string `enum` of one
+fn tag_value(schema: &Value) -> Option<String> {
+    if let Some(value) = schema.get("const") {
+        return value.as_str().map(str::to_string);
+    }
+    match schema.get("enum").and_then(Value::as_array).map(Vec::as_slice) {
+        Some([value]) => value.as_str().map(str::to_string),
+        _ => None,
+    }
+}
+
+fn is_null_schema(schema: &Value) -> bool {
+    schema.get("type").and_then(Value::as_str) == Some("null")
+}
+
+fn description(schema: &Value) -> Option<String> {
+    schema
+        .get("description")
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|text| !text.is_empty())
+        .map(str::to_string)
+}
+
+/// `PascalCase` for a definition or property name (`owners`, `vcs-config`, `vcs_config`)
+pub fn pascal_case(name: &str) -> String {
+    name.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_ascii_uppercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        })
+        .collect()
+}
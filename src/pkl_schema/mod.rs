@@ -0,0 +1,756 @@
+//! Pkl Schema Module for Space Pklr
+//!
+//! A small model of a generated Pkl module: module-level properties, classes, and
+//! typealiases, each with optional doc comments. [`SchemaGenerator`] builds one from a
+//! source schema, [`PklModule::render`] writes it out as Pkl source, and
+//! [`PklModule::to_json_schema`] exports it as a JSON Schema document.
+//!
+//! ```pkl
+//! /// Project settings
+//! open module Project
+//!
+//! language: String
+//! owners: OwnersConfig?
+//!
+//! open class OwnersConfig {
+//!   paths: Listing<String>
+//! }
+//!
+//! typealias Kind = "library"|"application"
+//! ```
+//!
+//! Property names can be renamed to one casing (see [`casing`]), and constraint
+//! providers add rules the source schema can't express (see [`constraints`]).
+//! Open modules get companion `*.ext.pkl` stubs that extend them (see [`extensions`]).
+//! Modules generated together can share types through imports (see [`imports`]).
+//! A policy file can make properties required, change their defaults, fix them, or
+//! constrain them further (see [`overrides`]). [`ir`] saves modules as versioned JSON, and
+//! [`examples`] writes example configs that amend them. Toolchain plugins' settings
+//! schemas are merged into the Toolchain module as typed classes (see [`toolchain_plugins`]).
+//! `oneOf` unions whose variants can overlap get an exactly-one constraint (see [`unions`]).
+//! Types and properties can be sorted into a stable order (see [`ordering`]).
+
+pub mod casing;
+pub mod constraints;
+pub mod examples;
+pub mod extensions;
+pub mod filters;
+pub mod imports;
+pub mod ir;
+pub mod json_schema;
+pub mod ordering;
+pub mod overrides;
+pub mod parallel;
+pub mod toolchain_plugins;
+pub mod unions;
+
+pub use imports::PklImport;
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::templates::{TemplateContext, TemplateOverrides};
+use crate::types::CliError;
+
+/// `[generator]` settings for generated Pkl modules
+///
+/// ```toml
+/// [generator]
+/// type_prefix = "Moon"
+/// split_types = true
+/// property_case = "camelCase"
+/// sort_mode = "dependency"
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct GeneratorConfig {
+    /// Prepended to every class and typealias name
+    pub type_prefix: Option<String>,
+    /// Appended to every class and typealias name
+    pub type_suffix: Option<String>,
+    /// Class and typealias names mapped to the names to use instead, ahead of the prefix
+    /// and suffix
+    pub type_mappings: BTreeMap<String, String>,
+    /// Define types shared by modules generated together once, and import them elsewhere
+    pub split_types: bool,
+    /// Property paths to keep; everything else is hidden (see [`filters`])
+    pub include_properties: Vec<String>,
+    /// Property paths to hide (see [`filters`])
+    pub exclude_properties: Vec<String>,
+    /// Threads for generating and rendering modules; unset or `0` uses every core (see
+    /// [`crate::concurrency`])
+    #[serde(alias = "parallelism")]
+    pub concurrency: Option<usize>,
+    /// Casing for property names (see [`casing`])
+    pub property_case: casing::Case,
+    /// Casing for the properties of particular classes, or of a module's own properties
+    /// by module name
+    pub property_case_overrides: BTreeMap<String, casing::Case>,
+    /// Constraints added to matching properties (see [`constraints`])
+    pub constraints: Vec<constraints::ConstraintRule>,
+    /// Order of types and properties (see [`ordering`])
+    pub sort_mode: ordering::SortMode,
+}
+
+impl GeneratorConfig {
+    /// `name` as mapped, or with the configured prefix and suffix
+    pub fn type_name(&self, name: &str) -> String {
+        if let Some(mapped) = self.type_mappings.get(name) {
+            return mapped.clone();
+        }
+        format!(
+            "{}{}{}",
+            self.type_prefix.as_deref().unwrap_or_default(),
+            name,
+            self.type_suffix.as_deref().unwrap_or_default()
+        )
+    }
+
+    /// Casing for the properties of the class or module named `type_name`
+    pub fn property_case_for(&self, type_name: &str) -> casing::Case {
+        self.property_case_overrides
+            .get(type_name)
+            .copied()
+            .unwrap_or(self.property_case)
+    }
+
+    /// Whether any properties are renamed to another casing
+    pub fn recases_properties(&self) -> bool {
+        self.property_case != casing::Case::Preserve
+            || self.property_case_overrides.values().any(|case| *case != casing::Case::Preserve)
+    }
+
+    /// Apply the casing, property filters, naming, and ordering settings to a generated
+    /// module
+    ///
+    /// Properties are renamed first, so filters and policy overrides name them as the
+    /// generated module does, and sorted last, by their final names.
+    pub fn apply(&self, module: &mut PklModule) {
+        casing::recase_module(module, self);
+        filters::PropertyFilter::new(&self.include_properties, &self.exclude_properties).apply(module);
+        if self.type_prefix.is_some() || self.type_suffix.is_some() || !self.type_mappings.is_empty() {
+            module.rename_types(|name| self.type_name(name));
+        }
+        ordering::sort_module(module, self.sort_mode);
+    }
+}
+
+/// A Pkl type annotation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PklType {
+    String,
+    Int,
+    Number,
+    Boolean,
+    Any,
+    /// `Listing<T>`
+    Listing(Box<PklType>),
+    /// `Mapping<K, V>`
+    Mapping(Box<PklType>, Box<PklType>),
+    /// A class or typealias defined in the module
+    Named(String),
+    /// A string literal type, as used in enum unions
+    StringLiteral(String),
+    /// `A|B|C`
+    Union(Vec<PklType>),
+    /// A union of string literals with per-value docs, from a documented enum
+    Variants(Vec<PklVariant>),
+    /// `T?`
+    Nullable(Box<PklType>),
+}
+
+impl PklType {
+    /// `T?`, unless the type already admits `null`
+    pub fn nullable(self) -> Self {
+        match self {
+            PklType::Nullable(_) | PklType::Any => self,
+            other => PklType::Nullable(Box::new(other)),
+        }
+    }
+
+    /// Pkl source for this type
+    pub fn render(&self) -> String {
+        match self {
+            PklType::String => "String".to_string(),
+            PklType::Int => "Int".to_string(),
+            PklType::Number => "Number".to_string(),
+            PklType::Boolean => "Boolean".to_string(),
+            PklType::Any => "Any".to_string(),
+            PklType::Listing(item) => format!("Listing<{}>", item.render()),
+            PklType::Mapping(key, value) => format!("Mapping<{}, {}>", key.render(), value.render()),
+            PklType::Named(name) => name.clone(),
+            PklType::StringLiteral(value) => string_literal(value),
+            PklType::Union(members) => members.iter().map(PklType::render).collect::<Vec<_>>().join("|"),
+            PklType::Variants(variants) => variants.iter().map(|variant| string_literal(&variant.value)).collect::<Vec<_>>().join("|"),
+            PklType::Nullable(inner) => match inner.as_ref() {
+                PklType::Union(_) | PklType::Variants(_) => format!("({})?", inner.render()),
+                _ => format!("{}?", inner.render()),
+            },
+        }
+    }
+
+    /// Rewrite references to classes and typealiases
+    pub fn rename(&mut self, rename: &impl Fn(&str) -> String) {
+        match self {
+            PklType::Named(name) => *name = rename(name),
+            PklType::Listing(inner) | PklType::Nullable(inner) => inner.rename(rename),
+            PklType::Mapping(key, value) => {
+                key.rename(rename);
+                value.rename(rename);
+            }
+            PklType::Union(members) => members.iter_mut().for_each(|member| member.rename(rename)),
+            _ => {}
+        }
+    }
+
+    /// The members of a union, with a documented union's values as string literals
+    pub fn union_members(&self) -> Option<Vec<PklType>> {
+        match self {
+            PklType::Union(members) => Some(members.clone()),
+            PklType::Variants(variants) => Some(
+                variants
+                    .iter()
+                    .map(|variant| PklType::StringLiteral(variant.value.clone()))
+                    .collect(),
+            ),
+            _ => None,
+        }
+    }
+}
+
+/// `"value"`, escaped for Pkl
+fn string_literal(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// One value of a documented string union
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PklVariant {
+    pub value: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub doc: Option<String>,
+    /// Deprecation message (empty when the schema gives none)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deprecated: Option<String>,
+}
+
+impl PklVariant {
+    pub fn new(value: impl Into<String>, doc: Option<String>) -> Self {
+        Self {
+            value: value.into(),
+            doc,
+            deprecated: None,
+        }
+    }
+
+    /// `//` lines above the variant's alternative: its doc, then any deprecation
+    fn comments(&self, indent: &str) -> String {
+        let deprecation = self.deprecated.as_deref().map(|message| match message.trim() {
+            "" => "Deprecated".to_string(),
+            message => format!("Deprecated: {}", message),
+        });
+        self.doc
+            .as_deref()
+            .into_iter()
+            .flat_map(str::lines)
+            .map(str::to_string)
+            .chain(deprecation)
+            .map(|line| format!("{}{}\n", indent, format!("// {}", line).trim_end()))
+            .collect()
+    }
+}
+
+/// A property of a module or class
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PklProperty {
+    pub name: String,
+    #[serde(default)]
+    pub doc: Option<String>,
+    pub ty: PklType,
+    /// Default value, rendered as a Pkl expression of `ty`
+    #[serde(default)]
+    pub default: Option<serde_json::Value>,
+    /// Render as `fixed`, so amending modules can't change the default
+    #[serde(default)]
+    pub fixed: bool,
+    /// Type constraints, e.g. `startsWith("20.")`, rendered as `String(startsWith("20."))`
+    #[serde(default)]
+    pub constraints: Vec<PklConstraint>,
+    /// Deprecation message (empty when the schema gives none); only shown in docs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deprecated: Option<String>,
+    /// Example values from the schema; only shown in docs
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub examples: Vec<serde_json::Value>,
+}
+
+/// A type constraint on a property
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PklConstraint {
+    /// Pkl boolean expression checked against the value
+    pub expression: String,
+    /// Where the constraint came from, rendered as a comment above the property
+    #[serde(default)]
+    pub provenance: Option<String>,
+}
+
+impl PklProperty {
+    pub fn new(name: impl Into<String>, doc: Option<String>, ty: PklType) -> Self {
+        Self {
+            name: name.into(),
+            doc,
+            ty,
+            default: None,
+            fixed: false,
+            constraints: Vec::new(),
+            deprecated: None,
+            examples: Vec::new(),
+        }
+    }
+
+    /// Pkl source for the property's type, with its constraints
+    pub fn render_type(&self) -> String {
+        constrained(&self.ty, &self.constraints)
+    }
+
+    /// `// <provenance>` lines for the property's constraints
+    fn provenance_comments(&self) -> String {
+        let mut seen = Vec::new();
+        self.constraints
+            .iter()
+            .filter_map(|constraint| constraint.provenance.as_deref())
+            .filter(|provenance| {
+                let new = !seen.contains(provenance);
+                seen.push(*provenance);
+                new
+            })
+            .map(|provenance| format!("// {}\n", provenance))
+            .collect()
+    }
+
+    /// ` = <default>` for the property's declaration, if it has a default
+    fn default_suffix(&self) -> String {
+        self.default
+            .as_ref()
+            .map(|default| format!(" = {}", pkl_literal(default, &self.ty)))
+            .unwrap_or_default()
+    }
+}
+
+/// `ty` with `constraints` applied to its non-null value
+fn constrained(ty: &PklType, constraints: &[PklConstraint]) -> String {
+    if constraints.is_empty() {
+        return ty.render();
+    }
+    let expressions: Vec<&str> = constraints.iter().map(|constraint| constraint.expression.as_str()).collect();
+    match ty {
+        PklType::Nullable(inner) => format!("{}?", constrained(inner, constraints)),
+        PklType::Union(_) | PklType::Variants(_) => format!("({})({})", ty.render(), expressions.join(", ")),
+        _ => format!("{}({})", ty.render(), expressions.join(", ")),
+    }
+}
+
+/// A class definition
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PklClass {
+    pub name: String,
+    #[serde(default)]
+    pub doc: Option<String>,
+    /// Render as `abstract`: the base class of a tagged union
+    #[serde(default, rename = "abstract", skip_serializing_if = "std::ops::Not::not")]
+    pub is_abstract: bool,
+    /// Class this one extends: a tagged union's base, for each of its members
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extends: Option<String>,
+    #[serde(default)]
+    pub properties: Vec<PklProperty>,
+}
+
+impl PklClass {
+    pub fn new(name: impl Into<String>, doc: Option<String>, properties: Vec<PklProperty>) -> Self {
+        Self {
+            name: name.into(),
+            doc,
+            is_abstract: false,
+            extends: None,
+            properties,
+        }
+    }
+}
+
+/// A typealias definition
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PklTypeAlias {
+    pub name: String,
+    #[serde(default)]
+    pub doc: Option<String>,
+    pub ty: PklType,
+}
+
+impl PklTypeAlias {
+    /// Pkl source for the typealias
+    ///
+    /// A documented union is written one alternative per line, each under its comments:
+    ///
+    /// ```pkl
+    /// typealias Kind =
+    ///   // A reusable package
+    ///   "library"
+    ///   // Deprecated: use "library"
+    ///   |"package"
+    /// ```
+    pub fn render(&self) -> String {
+        let doc = doc_comment(self.doc.as_deref());
+        match &self.ty {
+            PklType::Variants(variants) if variants.iter().any(|variant| variant.doc.is_some() || variant.deprecated.is_some()) => {
+                let alternatives: String = variants
+                    .iter()
+                    .enumerate()
+                    .map(|(index, variant)| {
+                        let separator = if index == 0 { "" } else { "|" };
+                        format!("{}  {}{}\n", variant.comments("  "), separator, string_literal(&variant.value))
+                    })
+                    .collect();
+                format!("{}typealias {} =\n{}", doc, self.name, alternatives)
+            }
+            ty => format!("{}typealias {} = {}\n", doc, self.name, ty.render()),
+        }
+    }
+}
+
+/// A generated Pkl module
+///
+/// Serializable for caches and tooling; save and load it through [`ir`], which versions
+/// the shape.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PklModule {
+    pub name: String,
+    /// Render the module and its classes as `open`, so they can be extended
+    pub open: bool,
+    pub doc: Option<String>,
+    pub imports: Vec<PklImport>,
+    pub properties: Vec<PklProperty>,
+    pub classes: Vec<PklClass>,
+    pub typealiases: Vec<PklTypeAlias>,
+}
+
+impl PklModule {
+    /// Pkl source for the module
+    pub fn render(&self) -> String {
+        // The built-in rendering has no templates to fail
+        self.render_with(&TemplateOverrides::default(), &TemplateContext::default())
+            .unwrap_or_default()
+    }
+
+    /// Pkl source for the module, using any override templates
+    pub fn render_with(&self, overrides: &TemplateOverrides, context: &TemplateContext) -> Result<String, CliError> {
+        let mut output = Vec::new();
+        self.write_with(&mut output, overrides, context)?;
+        String::from_utf8(output).map_err(|e| CliError::Generic(format!("Rendered Pkl is not UTF-8: {}", e)))
+    }
+
+    /// Stream the module's Pkl source into `out`, using any override templates
+    ///
+    /// Classes and typealiases are rendered and written one at a time, so even a module
+    /// several megabytes long is never held as a single string. A `module` override
+    /// template needs every section at once, so it's rendered whole.
+    pub fn write_with(
+        &self,
+        out: &mut dyn std::io::Write,
+        overrides: &TemplateOverrides,
+        context: &TemplateContext,
+    ) -> Result<(), CliError> {
+        let mut write = |chunk: &str| {
+            out.write_all(chunk.as_bytes()).map_err(|e| CliError::IoError {
+                context: format!("Writing Pkl module {}", self.name),
+                source: e,
+            })
+        };
+
+        let mut properties = String::new();
+        push_properties(&mut properties, &self.properties, "", overrides, context)?;
+        let imports: String = self.imports.iter().map(|import| format!("{}\n", import.render())).collect();
+
+        if overrides.has("module") {
+            let classes = self
+                .classes
+                .iter()
+                .map(|class| self.render_class(class, overrides, context))
+                .collect::<Result<Vec<_>, _>>()?;
+            let typealiases: Vec<String> = self.typealiases.iter().map(PklTypeAlias::render).collect();
+            let module_context = context.with_value(
+                "module",
+                json!({
+                    "name": self.name,
+                    "open": self.open,
+                    "doc": self.doc,
+                    "doc_comment": doc_comment(self.doc.as_deref()),
+                    "imports": imports,
+                    "properties": properties,
+                    "classes": classes.join("\n"),
+                    "typealiases": typealiases.join("\n"),
+                }),
+            );
+            return write(&overrides.render("module", &module_context, String::new)?);
+        }
+
+        let open = if self.open { "open " } else { "" };
+        write(&format!("{}{}module {}\n", doc_comment(self.doc.as_deref()), open, self.name))?;
+        for section in [imports, properties] {
+            if !section.is_empty() {
+                write("\n")?;
+                write(&section)?;
+            }
+        }
+        for class in &self.classes {
+            write("\n")?;
+            write(&self.render_class(class, overrides, context)?)?;
+        }
+        for typealias in &self.typealiases {
+            write("\n")?;
+            write(&typealias.render())?;
+        }
+        Ok(())
+    }
+
+    /// Pkl source for one of the module's classes
+    fn render_class(&self, class: &PklClass, overrides: &TemplateOverrides, context: &TemplateContext) -> Result<String, CliError> {
+        let modifier = match (class.is_abstract, self.open) {
+            (true, _) => "abstract ",
+            (false, true) => "open ",
+            (false, false) => "",
+        };
+        let extends = class.extends.as_ref().map(|parent| format!(" extends {}", parent)).unwrap_or_default();
+        let mut body = String::new();
+        push_properties(&mut body, &class.properties, "  ", overrides, context)?;
+        let class_context = context.with_value(
+            "class",
+            json!({
+                "name": class.name,
+                "open": self.open,
+                "abstract": class.is_abstract,
+                "extends": class.extends,
+                "doc": class.doc,
+                "doc_comment": doc_comment(class.doc.as_deref()),
+                "properties": body,
+            }),
+        );
+        overrides.render("class", &class_context, || {
+            format!("{}{}class {}{} {{\n{}}}\n", doc_comment(class.doc.as_deref()), modifier, class.name, extends, body)
+        })
+    }
+
+    /// Rename every class and typealias, rewriting references to them
+    pub fn rename_types(&mut self, rename: impl Fn(&str) -> String) {
+        for property in &mut self.properties {
+            property.ty.rename(&rename);
+        }
+        for class in &mut self.classes {
+            class.name = rename(&class.name);
+            if let Some(parent) = &mut class.extends {
+                *parent = rename(parent);
+            }
+            for property in &mut class.properties {
+                property.ty.rename(&rename);
+            }
+        }
+        for typealias in &mut self.typealiases {
+            typealias.name = rename(&typealias.name);
+            typealias.ty.rename(&rename);
+        }
+    }
+
+    /// Classes extending the class named `name`
+    pub fn subclasses<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a PklClass> + 'a {
+        self.classes.iter().filter(move |class| class.extends.as_deref() == Some(name))
+    }
+
+    /// The module as a JSON Schema document, for editors without Pkl support
+    pub fn to_json_schema(&self) -> serde_json::Value {
+        json_schema::to_json_schema(self)
+    }
+}
+
+/// `///` lines for a doc comment, one per line of `doc`
+fn doc_comment(doc: Option<&str>) -> String {
+    doc.into_iter()
+        .flat_map(str::lines)
+        .map(|line| format!("{}\n", format!("/// {}", line).trim_end()))
+        .collect()
+}
+
+fn push_properties(
+    output: &mut String,
+    properties: &[PklProperty],
+    indent: &str,
+    overrides: &TemplateOverrides,
+    context: &TemplateContext,
+) -> Result<(), CliError> {
+    for (index, property) in properties.iter().enumerate() {
+        let comments = property.provenance_comments();
+        if index > 0 && (property.doc.is_some() || !comments.is_empty()) {
+            output.push('\n');
+        }
+        let property_context = context.with_value(
+            "property",
+            json!({
+                "name": property.name,
+                "identifier": identifier(&property.name),
+                "type": property.render_type(),
+                "doc": property.doc,
+                "doc_comment": doc_comment(property.doc.as_deref()),
+                "optional": matches!(property.ty, PklType::Nullable(_)),
+                "default": property.default.as_ref().map(|default| pkl_literal(default, &property.ty)),
+                "fixed": property.fixed,
+                "constraints": property.constraints.iter().map(|constraint| &constraint.expression).collect::<Vec<_>>(),
+            }),
+        );
+        let rendered = overrides.render("property", &property_context, || {
+            format!(
+                "{}{}{}{}: {}{}",
+                comments,
+                doc_comment(property.doc.as_deref()),
+                if property.fixed { "fixed " } else { "" },
+                identifier(&property.name),
+                property.render_type(),
+                property.default_suffix()
+            )
+        })?;
+        for line in rendered.trim_end().lines() {
+            if line.is_empty() {
+                output.push('\n');
+            } else {
+                output.push_str(&format!("{}{}\n", indent, line));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// A JSON value as a Pkl expression for a property of type `ty`
+///
+/// Arrays become `new Listing { ... }`, and objects `new Mapping { ... }` for mapping
+/// types or `new { ... }` for classes.
+pub fn pkl_literal(value: &serde_json::Value, ty: &PklType) -> String {
+    use serde_json::Value;
+
+    let ty = match ty {
+        PklType::Nullable(inner) => inner.as_ref(),
+        other => other,
+    };
+    match value {
+        Value::Null => "null".to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) => n.to_string(),
+        Value::String(s) => crate::config_processor::pkl_string(s),
+        Value::Array(items) => {
+            let item_ty = match ty {
+                PklType::Listing(item) => item.as_ref(),
+                _ => &PklType::Any,
+            };
+            let items: Vec<String> = items.iter().map(|item| pkl_literal(item, item_ty)).collect();
+            format!("new Listing {{ {} }}", items.join("; "))
+        }
+        Value::Object(entries) => {
+            let members: Vec<String> = match ty {
+                PklType::Mapping(_, value_ty) => entries
+                    .iter()
+                    .map(|(key, value)| format!("[{}] = {}", crate::config_processor::pkl_string(key), pkl_literal(value, value_ty)))
+                    .collect(),
+                _ => entries
+                    .iter()
+                    .map(|(key, value)| format!("{} = {}", identifier(key), pkl_literal(value, &PklType::Any)))
+                    .collect(),
+            };
+            let keyword = if matches!(ty, PklType::Mapping(..)) { "new Mapping" } else { "new" };
+            format!("{} {{ {} }}", keyword, members.join("; "))
+        }
+    }
+}
+
+/// A property name as a Pkl identifier, backquoted when it isn't a plain one or is a keyword
+pub fn identifier(name: &str) -> String {
+    crate::config_processor::pkl_identifier(name)
+}
+
+/// Builds [`PklModule`]s from source schemas
+#[derive(Clone, Default)]
+pub struct SchemaGenerator {
+    config: GeneratorConfig,
+    providers: Vec<Arc<dyn constraints::ConstraintProvider>>,
+}
+
+impl std::fmt::Debug for SchemaGenerator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SchemaGenerator")
+            .field("config", &self.config)
+            .field("providers", &self.providers.len())
+            .finish()
+    }
+}
+
+impl SchemaGenerator {
+    pub fn new(config: GeneratorConfig) -> Self {
+        Self {
+            config,
+            providers: Vec::new(),
+        }
+    }
+
+    /// Add constraints from `provider` to every generated property, after those from
+    /// `[generator]` and any providers registered earlier
+    pub fn with_constraint_provider(mut self, provider: impl constraints::ConstraintProvider + 'static) -> Self {
+        self.providers.push(Arc::new(provider));
+        self
+    }
+
+    /// Build a module from a JSON Schema document on disk, with the default settings
+    pub fn from_json_schema(path: &Path) -> Result<PklModule, CliError> {
+        Self::default().generate_from_json_schema(path)
+    }
+
+    /// Build a module from a JSON Schema document on disk
+    ///
+    /// The module is named after the schema's `title`, or the file name without one.
+    pub fn generate_from_json_schema(&self, path: &Path) -> Result<PklModule, CliError> {
+        let content = std::fs::read_to_string(path).map_err(|e| CliError::IoError {
+            context: format!("Reading JSON Schema: {}", path.display()),
+            source: e,
+        })?;
+        let schema: serde_json::Value = serde_json::from_str(&content).map_err(|e| CliError::ValidationError {
+            source: Box::new(e),
+        })?;
+        let fallback = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().trim_end_matches(".schema").to_string())
+            .unwrap_or_default();
+        self.generate_from_json_value(&schema, &fallback)
+    }
+
+    /// Build a module from a parsed JSON Schema document, named `fallback` if it has no `title`
+    pub fn generate_from_json_value(&self, schema: &serde_json::Value, fallback: &str) -> Result<PklModule, CliError> {
+        let mut module = json_schema::to_module(schema, fallback)?;
+        self.config.apply(&mut module);
+        let providers: Vec<&dyn constraints::ConstraintProvider> = self
+            .config
+            .constraints
+            .iter()
+            .map(|rule| rule as &dyn constraints::ConstraintProvider)
+            .chain(self.providers.iter().map(|provider| provider.as_ref()))
+            .collect();
+        constraints::apply_providers(&mut module, &providers);
+        Ok(module)
+    }
+
+    /// Build a module from each JSON Schema document, in parallel, keeping their order
+    pub fn generate_all_from_json_schema(&self, paths: &[PathBuf]) -> Result<Vec<PklModule>, CliError> {
+        parallel::map(paths, self.config.concurrency, |path| self.generate_from_json_schema(path))
+            .into_iter()
+            .collect()
+    }
+}
@@ -0,0 +1,101 @@
+//! Output ordering
+//!
+//! Classes and typealiases come out of a source schema in the order its definitions are
+//! discovered, which shifts when the schema is regenerated and makes diffs of generated
+//! modules noisy. `sort_mode` in `[generator]` reorders them, and the properties of the
+//! module and its classes, into an order that only depends on the names and references:
+//!
+//! ```toml
+//! [generator]
+//! sort_mode = "dependency"
+//! ```
+//!
+//! - `source` (the default) keeps the source schema's order
+//! - `dependency` puts each type after the types it references or extends, breaking
+//!   ties (and cycles) alphabetically, and sorts properties alphabetically
+//! - `alphabetical` sorts types and properties by name
+//!
+//! Every Pkl module spklr generates goes through this pass: Moon's config types
+//! (`spklr generate schema --format pkl`), `--from-json-schema`, and `--composition`.
+
+use serde::Deserialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::PklModule;
+use super::filters::class_names;
+
+/// Order of types and properties in generated modules
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SortMode {
+    /// The source schema's order
+    #[default]
+    Source,
+    /// Referenced types first, then alphabetical
+    Dependency,
+    /// Alphabetical by name
+    Alphabetical,
+}
+
+/// Reorder the module's classes, typealiases, and properties for `mode`
+pub fn sort_module(module: &mut PklModule, mode: SortMode) {
+    match mode {
+        SortMode::Source => {}
+        SortMode::Alphabetical => {
+            sort_properties(module);
+            module.classes.sort_by(|a, b| a.name.cmp(&b.name));
+            module.typealiases.sort_by(|a, b| a.name.cmp(&b.name));
+        }
+        SortMode::Dependency => {
+            sort_properties(module);
+            let rank: BTreeMap<String, usize> =
+                dependency_order(module).into_iter().enumerate().map(|(index, name)| (name, index)).collect();
+            module.classes.sort_by_key(|class| rank.get(&class.name).copied());
+            module.typealiases.sort_by_key(|typealias| rank.get(&typealias.name).copied());
+        }
+    }
+}
+
+fn sort_properties(module: &mut PklModule) {
+    module.properties.sort_by(|a, b| a.name.cmp(&b.name));
+    for class in &mut module.classes {
+        class.properties.sort_by(|a, b| a.name.cmp(&b.name));
+    }
+}
+
+/// Names of the module's types, each after the types it depends on
+///
+/// Of the types whose dependencies are all placed, the alphabetically first goes next.
+/// When a cycle leaves none, the alphabetically first remaining type goes next.
+pub fn dependency_order(module: &PklModule) -> Vec<String> {
+    let mut dependencies: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    for class in &module.classes {
+        let mut names: BTreeSet<String> = class.properties.iter().flat_map(|property| class_names(&property.ty)).collect();
+        names.extend(class.extends.iter().cloned());
+        dependencies.insert(class.name.clone(), names);
+    }
+    for typealias in &module.typealiases {
+        dependencies.insert(typealias.name.clone(), class_names(&typealias.ty).into_iter().collect());
+    }
+    // Only the module's own types order anything; a type doesn't wait for itself
+    let defined: BTreeSet<String> = dependencies.keys().cloned().collect();
+    for (name, names) in &mut dependencies {
+        names.retain(|dependency| dependency != name && defined.contains(dependency));
+    }
+
+    let mut order = Vec::with_capacity(dependencies.len());
+    while !dependencies.is_empty() {
+        let next = dependencies
+            .iter()
+            .find(|(_, names)| names.is_empty())
+            .or_else(|| dependencies.iter().next())
+            .map(|(name, _)| name.clone())
+            .unwrap_or_default();
+        dependencies.remove(&next);
+        for names in dependencies.values_mut() {
+            names.remove(&next);
+        }
+        order.push(next);
+    }
+    order
+}
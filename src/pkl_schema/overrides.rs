@@ -0,0 +1,117 @@
+//! Policy overrides for generated modules
+//!
+//! The `overrides` of a policy file (see [`crate::policy`]) change individual
+//! properties of a generated module: whether they're required, their default, whether
+//! that default is `fixed`, and extra type constraints. Paths are matched like the property filters (see
+//! [`super::filters`]), so an override of a class's property applies wherever the
+//! class is used:
+//!
+//! ```pkl
+//! class NodeConfig {
+//!   // Org policy: Node 20 or 22 only
+//!   version: String(startsWith("20.") || startsWith("22."))
+//! }
+//! ```
+
+use std::collections::BTreeSet;
+
+use super::filters::class_names;
+use crate::config_path::{ConfigPath, matches_segments};
+use super::{PklConstraint, PklModule, PklProperty, PklType};
+use crate::policy::PropertyOverride;
+
+/// Apply the overrides for `module`, returning the paths of those that matched a property
+///
+/// Overrides whose `appliesTo` doesn't name the module are skipped.
+pub fn apply_overrides<'a>(module: &mut PklModule, overrides: &'a [PropertyOverride]) -> Vec<&'a str> {
+    let mut matched = Vec::new();
+    for property_override in overrides {
+        let applies = property_override.applies_to.is_empty()
+            || property_override
+                .applies_to
+                .iter()
+                .any(|name| name.eq_ignore_ascii_case(&module.name));
+        if !applies {
+            continue;
+        }
+
+        let pattern = ConfigPath::new(&property_override.path).segments().to_vec();
+        let mut targets = BTreeSet::new();
+        collect(module, None, &module.properties, &pattern, &mut Vec::new(), &mut Vec::new(), &mut targets);
+        if targets.is_empty() {
+            continue;
+        }
+        matched.push(property_override.path.as_str());
+
+        for property in module.properties.iter_mut().filter(|property| targets.contains(&(None, property.name.clone()))) {
+            apply_override(property, property_override);
+        }
+        for class in &mut module.classes {
+            let owner = Some(class.name.clone());
+            for property in class.properties.iter_mut().filter(|property| targets.contains(&(owner.clone(), property.name.clone()))) {
+                apply_override(property, property_override);
+            }
+        }
+    }
+    matched
+}
+
+fn apply_override(property: &mut PklProperty, property_override: &PropertyOverride) {
+    match property_override.required {
+        Some(true) => {
+            if let PklType::Nullable(inner) = &property.ty {
+                property.ty = inner.as_ref().clone();
+            }
+        }
+        Some(false) => property.ty = property.ty.clone().nullable(),
+        None => {}
+    }
+    if property_override.default.is_some() {
+        property.default = property_override.default.clone();
+    }
+    property.fixed |= property_override.fixed;
+
+    let provenance = match &property_override.reason {
+        Some(reason) => format!("Org policy: {}", reason),
+        None => format!("Org policy: override of `{}`", property_override.path),
+    };
+    for expression in &property_override.constraints {
+        if !property.constraints.iter().any(|constraint| constraint.expression == *expression) {
+            property.constraints.push(PklConstraint {
+                expression: expression.clone(),
+                provenance: Some(provenance.clone()),
+            });
+        }
+    }
+}
+
+/// Record `(owning class, property)` for every property path matching `pattern`
+fn collect(
+    module: &PklModule,
+    owner: Option<&str>,
+    properties: &[PklProperty],
+    pattern: &[String],
+    path: &mut Vec<String>,
+    classes: &mut Vec<String>,
+    targets: &mut BTreeSet<(Option<String>, String)>,
+) {
+    for property in properties {
+        path.push(property.name.clone());
+        if matches_segments(pattern, path) {
+            targets.insert((owner.map(str::to_string), property.name.clone()));
+        }
+        for name in class_names(&property.ty) {
+            let Some(class) = module.classes.iter().find(|class| class.name == name) else {
+                continue;
+            };
+            // Recursive classes are only followed once per path
+            if classes.contains(&class.name) {
+                continue;
+            }
+            classes.push(class.name.clone());
+            collect(module, Some(&class.name), &class.properties, pattern, path, classes, targets);
+            classes.pop();
+        }
+        path.pop();
+    }
+}
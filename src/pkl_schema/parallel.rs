@@ -0,0 +1,37 @@
+//! Parallel generation across modules
+//!
+//! Each JSON Schema document is converted, and each module rendered, independently,
+//! so large sets are spread over scoped threads. The thread count is the job count
+//! from [`crate::concurrency`]: `--jobs`, then `concurrency` in `[generator]`, then
+//! every available core. `1` keeps generation sequential.
+
+/// Threads to use for `concurrency`, never more than there is work for
+pub fn threads(concurrency: Option<usize>, work: usize) -> usize {
+    crate::concurrency::jobs(concurrency).min(work).max(1)
+}
+
+/// `f` applied to every item on up to `concurrency` threads, in input order
+pub fn map<T, R, F>(items: &[T], concurrency: Option<usize>, f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    let threads = threads(concurrency, items.len());
+    if threads <= 1 {
+        return items.iter().map(f).collect();
+    }
+
+    let chunk_size = items.len().div_ceil(threads);
+    let f = &f;
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = items
+            .chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || chunk.iter().map(f).collect::<Vec<R>>()))
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap_or_else(|panic| std::panic::resume_unwind(panic)))
+            .collect()
+    })
+}
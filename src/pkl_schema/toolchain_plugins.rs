@@ -0,0 +1,172 @@
+//! Toolchain plugin schemas
+//!
+//! Moon toolchains can be provided by WASM plugins, each configured by its own block in
+//! `toolchain.yml`. Moon's own schema only knows those blocks as free-form maps, so the
+//! generated `Toolchain` module would type them as `Mapping<String, Any>`. Declaring the
+//! plugins in `spklr.toml` with their JSON Schemas gives them real classes:
+//!
+//! ```toml
+//! [[toolchain_plugins]]
+//! id = "deno"
+//! schema = "plugins/deno.schema.json"
+//!
+//! [[toolchain_plugins]]
+//! id = "zig"
+//! url = "https://plugins.example.com/zig/0.3.0/schema.json"
+//! ```
+//!
+//! A local `schema` is either a JSON Schema document or a plugin manifest holding one
+//! under `schema`; a `url` is fetched from the plugin registry with the `download`
+//! network policy. Each schema is imported like any other (see [`super::json_schema`]),
+//! its root becomes `<Id>PluginConfig`, its other types are prefixed `<Id>Plugin`, and
+//! the plugin's block in the Toolchain module becomes an optional property of that class:
+//!
+//! ```pkl
+//! deno: DenoPluginConfig?
+//!
+//! open class DenoPluginConfig {
+//!   permissions: Listing<DenoPluginPermission>?
+//! }
+//! ```
+
+use serde::Deserialize;
+use serde_json::Value;
+use std::path::PathBuf;
+
+use super::json_schema::{pascal_case, to_module};
+use super::{PklClass, PklModule, PklProperty, PklType};
+use crate::network::RetryPolicy;
+use crate::types::CliError;
+
+/// `[[toolchain_plugins]]`: a toolchain plugin and where its settings schema lives
+#[derive(Debug, Clone, Deserialize)]
+pub struct ToolchainPluginConfig {
+    /// Toolchain id, the key of the plugin's block in `toolchain.yml`
+    pub id: String,
+    /// Local JSON Schema document or plugin manifest
+    pub schema: Option<PathBuf>,
+    /// Registry URL of the JSON Schema document
+    pub url: Option<String>,
+}
+
+impl ToolchainPluginConfig {
+    /// The plugin's settings schema, read from disk or fetched from the registry
+    pub async fn load_schema(&self, policy: &RetryPolicy) -> Result<Value, CliError> {
+        let (origin, content) = match (&self.schema, &self.url) {
+            (Some(path), None) => {
+                let content = std::fs::read_to_string(path).map_err(|e| CliError::IoError {
+                    context: format!("Reading schema of toolchain plugin '{}': {}", self.id, path.display()),
+                    source: e,
+                })?;
+                (path.display().to_string(), content)
+            }
+            (None, Some(url)) => (url.clone(), fetch_schema(url, policy).await?),
+            _ => {
+                return Err(CliError::Generic(format!(
+                    "Toolchain plugin '{}' needs exactly one of `schema` or `url`",
+                    self.id
+                )));
+            }
+        };
+        let document: Value = serde_json::from_str(&content)
+            .map_err(|e| CliError::Generic(format!("Toolchain plugin '{}': {} isn't valid JSON: {}", self.id, origin, e)))?;
+        Ok(manifest_schema(document))
+    }
+}
+
+/// The JSON Schema in a plugin manifest, or the document itself when it is one
+pub fn manifest_schema(document: Value) -> Value {
+    match document {
+        Value::Object(mut manifest) if !manifest.contains_key("properties") && manifest.get("schema").is_some_and(Value::is_object) => {
+            manifest.remove("schema").unwrap_or_default()
+        }
+        other => other,
+    }
+}
+
+/// Type the blocks of every configured plugin in the Toolchain module
+pub async fn merge_configured(toolchain: &mut PklModule, plugins: &[ToolchainPluginConfig], policy: &RetryPolicy) -> Result<(), CliError> {
+    for plugin in plugins {
+        tracing::info!("Adding settings of toolchain plugin '{}'", plugin.id);
+        let schema = plugin.load_schema(policy).await?;
+        merge_plugin(toolchain, &plugin.id, &schema)?;
+    }
+    Ok(())
+}
+
+/// Add a plugin's settings schema to the Toolchain module as `<Id>PluginConfig`
+///
+/// The plugin's block becomes an optional property of that class, replacing the untyped
+/// one when Moon's schema already has it.
+pub fn merge_plugin(toolchain: &mut PklModule, id: &str, schema: &Value) -> Result<(), CliError> {
+    let prefix = format!("{}Plugin", pascal_case(id));
+    let root = format!("{}Config", prefix);
+    // Without a title, inline classes are named after their property alone, then prefixed
+    let mut schema = schema.clone();
+    if let Value::Object(object) = &mut schema {
+        object.remove("title");
+    }
+    let mut plugin = to_module(&schema, "")?;
+    plugin.rename_types(|name| format!("{}{}", prefix, name));
+
+    let defined: Vec<&str> = toolchain
+        .classes
+        .iter()
+        .map(|class| class.name.as_str())
+        .chain(toolchain.typealiases.iter().map(|typealias| typealias.name.as_str()))
+        .collect();
+    let clashes: Vec<String> = std::iter::once(root.clone())
+        .chain(plugin.classes.iter().map(|class| class.name.clone()))
+        .chain(plugin.typealiases.iter().map(|typealias| typealias.name.clone()))
+        .filter(|name| defined.contains(&name.as_str()))
+        .collect();
+    if !clashes.is_empty() {
+        return Err(CliError::Generic(format!(
+            "Toolchain plugin '{}' defines types the {} module already has: {}",
+            id,
+            toolchain.name,
+            clashes.join(", ")
+        )));
+    }
+
+    let doc = plugin
+        .doc
+        .clone()
+        .or_else(|| Some(format!("Settings for the `{}` toolchain plugin", id)));
+    toolchain.classes.push(PklClass::new(root.clone(), plugin.doc, plugin.properties));
+    toolchain.classes.extend(plugin.classes);
+    toolchain.typealiases.extend(plugin.typealiases);
+
+    let ty = PklType::Named(root).nullable();
+    match toolchain.properties.iter_mut().find(|property| property.name == id) {
+        Some(property) => {
+            property.ty = ty;
+            property.doc = property.doc.take().or(doc);
+        }
+        None => toolchain.properties.push(PklProperty::new(id, doc, ty)),
+    }
+    Ok(())
+}
+
+#[cfg(feature = "network")]
+async fn fetch_schema(url: &str, policy: &RetryPolicy) -> Result<String, CliError> {
+    crate::read_only::ensure_allowed(format!("fetch {}", url))?;
+    let client = policy.client()?;
+    crate::network::retry(policy, url, || async {
+        let response = client.get(url).send().await.map_err(|e| policy.request_failure(e))?;
+        if let Some(failure) = crate::network::status_failure(&response) {
+            return Err(failure);
+        }
+        response.text().await.map_err(|e| policy.request_failure(e))
+    })
+    .await
+    .map_err(|e| CliError::NetworkError(format!("Fetching toolchain plugin schema {}: {}", url, e)))
+}
+
+#[cfg(not(feature = "network"))]
+async fn fetch_schema(url: &str, _policy: &RetryPolicy) -> Result<String, CliError> {
+    Err(CliError::NetworkError(format!(
+        "spklr was built without the `network` feature; cannot fetch {}",
+        url
+    )))
+}
@@ -0,0 +1,96 @@
+//! `oneOf` unions
+//!
+//! Pkl's `A|B` accepts a value that matches any variant, which is JSON Schema's `anyOf`.
+//! A `oneOf` whose variants can overlap is written with a constraint counting the
+//! variants that match:
+//!
+//! ```pkl
+//! /// Exactly one of these variants must match (JSON Schema `oneOf`).
+//! typealias OneOf0 = (Project|Tag)((if (this is Project) 1 else 0) + (if (this is Tag) 1 else 0) == 1)
+//! ```
+//!
+//! Variants that are distinct built-in base types can't both match, so their plain union
+//! is already exact. Variants are rendered Pkl types; a variant that is itself a union
+//! (`String|Int`) counts as its members.
+
+/// Doc comment for typealiases that enforce `oneOf`
+pub const ONE_OF_DOC: &str = "Exactly one of these variants must match (JSON Schema `oneOf`).";
+
+/// Built-in types no value can match more than one of
+const DISJOINT_BASES: [&str; 9] = [
+    "String", "Int", "Float", "Boolean", "Null", "Listing", "Mapping", "Duration", "DataSize",
+];
+
+/// The Pkl type enforcing `oneOf` over `variants`, or `None` when their plain union is exact
+pub fn one_of_type(variants: &[String]) -> Option<String> {
+    if variants_are_disjoint(variants) {
+        return None;
+    }
+
+    let matches = variants
+        .iter()
+        .map(|variant| {
+            let variant = variant.trim_start_matches('*');
+            // `this is A|B` would only test `A`
+            let checked = if union_members(variant).len() > 1 {
+                format!("({})", variant)
+            } else {
+                variant.to_string()
+            };
+            format!("(if (this is {}) 1 else 0)", checked)
+        })
+        .collect::<Vec<_>>()
+        .join(" + ");
+    Some(format!("({})({} == 1)", variants.join("|"), matches))
+}
+
+/// Whether no value can match more than one of these rendered union variants
+///
+/// Only distinct built-in base types are known to be disjoint; literals, constrained
+/// variants and classes (which may share a parent) are assumed to overlap.
+pub fn variants_are_disjoint(variants: &[String]) -> bool {
+    let mut seen = Vec::new();
+    variants
+        .iter()
+        .flat_map(|variant| union_members(variant.trim_start_matches('*')))
+        .all(|member| {
+            let member = member.trim_start_matches('*');
+            let base = member.split(['<', '(']).next().unwrap_or(member);
+            let constrained = member[base.len()..].starts_with('(');
+            let disjoint = DISJOINT_BASES.contains(&base) && !constrained && !seen.contains(&base);
+            seen.push(base);
+            disjoint
+        })
+}
+
+/// The members of a rendered type split at its top-level `|`s
+fn union_members(ty: &str) -> Vec<&str> {
+    let mut members = Vec::new();
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut start = 0;
+    for (index, c) in ty.char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '<' | '(' => depth += 1,
+            '>' | ')' => depth = depth.saturating_sub(1),
+            '|' if depth == 0 => {
+                members.push(&ty[start..index]);
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    members.push(&ty[start..]);
+    members
+}
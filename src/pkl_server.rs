@@ -0,0 +1,282 @@
+//! Pkl Server Module for Space Pklr
+//!
+//! Starting the Pkl CLI costs far more than evaluating a config with it, so converting or
+//! validating hundreds of Pkl files one `pkl eval` at a time is slow. [`PklServer`] starts
+//! `pkl server`, Pkl's [message passing mode](https://pkl-lang.org/main/current/bindings-specification/message-passing-api.html),
+//! once and sends every evaluation to it: MessagePack messages over the child's stdin and
+//! stdout (see [`crate::msgpack`]), matched to their responses by request id, so any
+//! number of evaluations can be in flight at once.
+//!
+//! [`PklEvaluator`] is what callers use: the server when it starts, or one-shot CLI runs
+//! when it doesn't (Pkl releases before 0.25 have no server mode). Either way, results of
+//! unchanged modules come from the evaluation cache (see [`crate::eval_cache`]).
+
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::process::{Child, ChildStdin};
+use tokio::sync::oneshot;
+
+use crate::msgpack::{MsgValue, decode, encode};
+use crate::pkl_tooling::{PklCli, allowed_modules, allowed_resources, pkl_command};
+use crate::types::CliError;
+
+/// Message types of the Pkl server protocol
+const CREATE_EVALUATOR_REQUEST: i64 = 0x20;
+const CREATE_EVALUATOR_RESPONSE: i64 = 0x21;
+const CLOSE_EVALUATOR: i64 = 0x22;
+const EVALUATE_REQUEST: i64 = 0x23;
+const EVALUATE_RESPONSE: i64 = 0x24;
+const LOG: i64 = 0x25;
+
+/// Responses waited for, by request id
+type Pending = Arc<Mutex<HashMap<i64, oneshot::Sender<MsgValue>>>>;
+
+/// A running `pkl server` with one evaluator producing JSON
+pub struct PklServer {
+    stdin: tokio::sync::Mutex<ChildStdin>,
+    pending: Pending,
+    next_request: AtomicI64,
+    evaluator_id: i64,
+    _child: Child,
+}
+
+impl PklServer {
+    /// Start `pkl server` and create its evaluator
+    pub async fn start(pkl_cli: &PklCli) -> Result<Self, CliError> {
+        let mut command = tokio::process::Command::from(pkl_command(pkl_cli, &["server".to_string()]));
+        command.stdin(Stdio::piped()).stdout(Stdio::piped()).stderr(Stdio::null()).kill_on_drop(true);
+        let mut child = command.spawn().map_err(|e| CliError::IoError {
+            context: "Starting pkl server".to_string(),
+            source: e,
+        })?;
+        let (Some(stdin), Some(stdout)) = (child.stdin.take(), child.stdout.take()) else {
+            return Err(CliError::Generic("pkl server has no stdin or stdout".to_string()));
+        };
+
+        let pending: Pending = Arc::default();
+        tokio::spawn(read_responses(stdout, pending.clone()));
+        let mut server = Self {
+            stdin: tokio::sync::Mutex::new(stdin),
+            pending,
+            next_request: AtomicI64::new(1),
+            evaluator_id: 0,
+            _child: child,
+        };
+
+        let response = server.request(CREATE_EVALUATOR_REQUEST, evaluator_settings()).await?;
+        if let Some(error) = response.get("error").and_then(MsgValue::as_str) {
+            return Err(CliError::Generic(format!("pkl server couldn't create an evaluator: {}", error)));
+        }
+        server.evaluator_id = response
+            .get("evaluatorId")
+            .and_then(MsgValue::as_int)
+            .ok_or_else(|| CliError::Generic("pkl server sent no evaluator id".to_string()))?;
+        Ok(server)
+    }
+
+    /// Evaluate a Pkl file to JSON text
+    pub async fn evaluate_json(&self, path: &Path) -> Result<String, CliError> {
+        let response = self
+            .request(
+                EVALUATE_REQUEST,
+                vec![
+                    ("evaluatorId", MsgValue::from(self.evaluator_id)),
+                    ("moduleUri", MsgValue::from(file_uri(path)?)),
+                    ("expr", MsgValue::from("output.text")),
+                ],
+            )
+            .await?;
+        if let Some(error) = response.get("error").and_then(MsgValue::as_str) {
+            return Err(crate::types::pkl_execution_error(
+                format!("pkl server: evaluate {}", path.display()),
+                error.to_string(),
+                Some("Check Pkl syntax and file paths".to_string()),
+            ));
+        }
+        // The result is the `output.text` String in Pkl's binary encoding: a MessagePack string
+        let result = response.get("result").and_then(MsgValue::as_bytes).unwrap_or_default();
+        match decode(result)? {
+            Some((MsgValue::Str(text), _)) => Ok(text),
+            _ => Err(CliError::Generic(format!("pkl server sent no output for {}", path.display()))),
+        }
+    }
+
+    /// Close the evaluator; the server exits when this is dropped
+    pub async fn close(&self) -> Result<(), CliError> {
+        self.send(CLOSE_EVALUATOR, vec![("evaluatorId", MsgValue::from(self.evaluator_id))]).await
+    }
+
+    /// Send a request and wait for its response
+    async fn request(&self, code: i64, body: Vec<(&str, MsgValue)>) -> Result<MsgValue, CliError> {
+        let request_id = self.next_request.fetch_add(1, Ordering::Relaxed);
+        let (sender, receiver) = oneshot::channel();
+        lock(&self.pending).insert(request_id, sender);
+
+        let mut body = body;
+        body.insert(0, ("requestId", MsgValue::from(request_id)));
+        if let Err(error) = self.send(code, body).await {
+            lock(&self.pending).remove(&request_id);
+            return Err(error);
+        }
+        receiver
+            .await
+            .map_err(|_| CliError::Generic("pkl server exited before responding".to_string()))
+    }
+
+    async fn send(&self, code: i64, body: Vec<(&str, MsgValue)>) -> Result<(), CliError> {
+        let mut bytes = Vec::new();
+        encode(&MsgValue::Array(vec![MsgValue::from(code), MsgValue::map(body)]), &mut bytes);
+        let mut stdin = self.stdin.lock().await;
+        let written = match stdin.write_all(&bytes).await {
+            Ok(()) => stdin.flush().await,
+            Err(error) => Err(error),
+        };
+        written.map_err(|e| CliError::IoError {
+            context: "Writing to pkl server".to_string(),
+            source: e,
+        })
+    }
+}
+
+/// Route each message from the server to the request waiting for it, until it exits
+///
+/// When it does, the pending senders are dropped, which fails their requests.
+async fn read_responses(mut stdout: tokio::process::ChildStdout, pending: Pending) {
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 8192];
+    loop {
+        match stdout.read(&mut chunk).await {
+            Ok(0) | Err(_) => break,
+            Ok(read) => buffer.extend_from_slice(&chunk[..read]),
+        }
+        loop {
+            let (message, used) = match decode(&buffer) {
+                Ok(Some(decoded)) => decoded,
+                Ok(None) => break,
+                Err(error) => {
+                    tracing::warn!("Unreadable message from pkl server: {}", error);
+                    lock(&pending).clear();
+                    return;
+                }
+            };
+            buffer.drain(..used);
+            dispatch(message, &pending);
+        }
+    }
+    lock(&pending).clear();
+}
+
+fn dispatch(message: MsgValue, pending: &Pending) {
+    let MsgValue::Array(mut parts) = message else {
+        return;
+    };
+    if parts.len() != 2 {
+        return;
+    }
+    let body = parts.pop().unwrap_or(MsgValue::Nil);
+    match parts[0].as_int() {
+        Some(CREATE_EVALUATOR_RESPONSE | EVALUATE_RESPONSE) => {
+            let sender = body.get("requestId").and_then(MsgValue::as_int).and_then(|id| lock(pending).remove(&id));
+            if let Some(sender) = sender {
+                let _ = sender.send(body);
+            }
+        }
+        Some(LOG) => {
+            let message = body.get("message").and_then(MsgValue::as_str).unwrap_or_default();
+            match body.get("level").and_then(MsgValue::as_int) {
+                Some(0) => tracing::debug!("pkl: {}", message),
+                _ => tracing::warn!("pkl: {}", message),
+            }
+        }
+        // Resource and module reader requests only come for readers registered with the
+        // evaluator, and this one registers none
+        _ => {}
+    }
+}
+
+fn lock(pending: &Pending) -> std::sync::MutexGuard<'_, HashMap<i64, oneshot::Sender<MsgValue>>> {
+    pending.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Body of the `CreateEvaluatorRequest`: JSON output, the process environment, and the
+/// URI schemes evaluations may use (without network ones in read-only and hermetic runs)
+pub fn evaluator_settings() -> Vec<(&'static str, MsgValue)> {
+    let env: Vec<(String, String)> = std::env::vars().collect();
+    vec![
+        ("allowedModules", strings(&allowed_modules())),
+        ("allowedResources", strings(&allowed_resources())),
+        ("outputFormat", MsgValue::from("json")),
+        (
+            "env",
+            MsgValue::Map(env.into_iter().map(|(name, value)| (MsgValue::from(name), MsgValue::from(value))).collect()),
+        ),
+    ]
+}
+
+fn strings(values: &[&str]) -> MsgValue {
+    MsgValue::Array(values.iter().map(|value| MsgValue::from(*value)).collect())
+}
+
+/// `file:` URI of a module path
+fn file_uri(path: &Path) -> Result<String, CliError> {
+    let path = path.canonicalize().map_err(|e| CliError::IoError {
+        context: format!("Resolving Pkl module: {}", path.display()),
+        source: e,
+    })?;
+    let path = path.to_string_lossy().replace('\\', "/");
+    let path = path.strip_prefix("//?/").unwrap_or(&path);
+    if path.starts_with('/') {
+        Ok(format!("file://{}", path))
+    } else {
+        Ok(format!("file:///{}", path))
+    }
+}
+
+/// Evaluates Pkl modules to JSON: through a long-lived `pkl server` when it's available,
+/// or one `pkl eval` per module
+pub enum PklEvaluator {
+    Server { server: PklServer, pkl_cli: PklCli },
+    Cli(PklCli),
+}
+
+impl PklEvaluator {
+    /// Start a server for `pkl_cli`, falling back to one-shot runs if it doesn't start
+    pub async fn start(pkl_cli: PklCli) -> Self {
+        match PklServer::start(&pkl_cli).await {
+            Ok(server) => Self::Server { server, pkl_cli },
+            Err(error) => {
+                tracing::debug!("pkl server unavailable, evaluating with one pkl eval per module: {}", error);
+                Self::Cli(pkl_cli)
+            }
+        }
+    }
+
+    pub fn pkl_cli(&self) -> &PklCli {
+        match self {
+            Self::Server { pkl_cli, .. } | Self::Cli(pkl_cli) => pkl_cli,
+        }
+    }
+
+    /// Evaluate a Pkl file to a config value
+    ///
+    /// Errors in the module are returned as they are; if the server itself fails (it
+    /// exited, or sent something unreadable), the module is evaluated with `pkl eval`.
+    pub async fn evaluate(&self, path: &Path) -> Result<Value, CliError> {
+        let Self::Server { server, pkl_cli } = self else {
+            return crate::config_processor::evaluate_pkl_with(self.pkl_cli(), path).await;
+        };
+        match crate::eval_cache::cached(pkl_cli, path, || server.evaluate_json(path)).await {
+            Ok(output) => crate::config_processor::parse_config_str(&output, &crate::types::SchemaFormat::Json),
+            Err(error @ CliError::PklExecutionFailed { .. }) => Err(error),
+            Err(error) => {
+                tracing::debug!("pkl server failed, evaluating {} with pkl eval: {}", path.display(), error);
+                crate::config_processor::evaluate_pkl_with(pkl_cli, path).await
+            }
+        }
+    }
+}
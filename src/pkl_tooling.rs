@@ -2,9 +2,43 @@
 //!
 //! This module manages Pkl CLI installation, detection, and execution through proto
 //! for consistent toolchain management.
+//!
+//! Air-gapped environments can install from a pre-downloaded release archive instead
+//! (`spklr pkl-me pkl --pkl-archive`); its SHA-256 must match the one recorded in
+//! `--sha256` or a `<archive>.sha256` file next to it.
+//!
+//! Downloaded versions are installed side by side under ~/.moon/tools/pkl/<version>/.
+//! `spklr pkl use <version>` records which of them is the default, and the global
+//! `--pkl-version` flag picks one for a single run.
+//!
+//! pkldoc, Pkl's HTML documentation generator, is managed the same way: `SPKLR_PKLDOC_PATH`
+//! or `pkldoc` on PATH when present, else a jar downloaded to ~/.moon/tools/pkldoc/<version>/
+//! for the selected Pkl version. `spklr docs` runs it over the generated schemas.
+//!
+//! Long-running callers evaluate through a [`PklServer`], one `pkl server` process shared
+//! by every evaluation, via [`PklEvaluator`], which falls back to one-shot `pkl eval` runs.
 
 use miette::Result;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+pub use crate::pkl_server::{PklEvaluator, PklServer};
+
+/// File in the Pkl tools directory naming the default installed version
+const DEFAULT_VERSION_FILE: &str = "default";
+
+static CLI_PKL_VERSION: Mutex<Option<String>> = Mutex::new(None);
+
+/// Set the `--pkl-version` for the rest of the run
+pub fn set_cli_pkl_version(version: Option<String>) {
+    if let Ok(mut cli_version) = CLI_PKL_VERSION.lock() {
+        *cli_version = version;
+    }
+}
+
+fn cli_pkl_version() -> Option<String> {
+    CLI_PKL_VERSION.lock().ok().and_then(|version| version.clone())
+}
 
 /// Pkl CLI representation.
 #[derive(Debug, Clone)]
@@ -23,6 +57,8 @@ pub enum PklSource {
     SystemPath,
     /// Manually downloaded and installed
     Manual(PathBuf),
+    /// Installed from a local release archive (the archive's path)
+    LocalArchive(PathBuf),
 }
 
 /// Install Pkl CLI with proto-first approach
@@ -31,7 +67,11 @@ pub enum PklSource {
 pub async fn install_pkl(version: Option<String>) -> Result<PklCli> {
     use crate::types::CliError;
 
-    let target_version = version.unwrap_or_else(|| get_recommended_pkl_version().to_string());
+    crate::read_only::ensure_allowed("install Pkl").map_err(miette::Report::new)?;
+    let target_version = version
+        .or_else(cli_pkl_version)
+        .or_else(crate::versions::pinned_pkl_version)
+        .unwrap_or_else(|| get_recommended_pkl_version().to_string());
 
     // 1. Try proto installation first
     if is_proto_available().await {
@@ -87,12 +127,123 @@ pub async fn install_pkl(version: Option<String>) -> Result<PklCli> {
     }
 }
 
+/// Install Pkl CLI from a pre-downloaded release archive, without touching the network
+///
+/// The archive is checked against `expected_sha256`, or the digest recorded in
+/// `<archive>.sha256`, before it's extracted to ~/.moon/tools/pkl/<version>/.
+pub async fn install_pkl_from_archive(
+    archive: &Path,
+    version: Option<String>,
+    expected_sha256: Option<&str>,
+) -> Result<PklCli> {
+    use crate::types::CliError;
+
+    crate::read_only::ensure_allowed("install Pkl").map_err(miette::Report::new)?;
+    let target_version = version
+        .or_else(cli_pkl_version)
+        .or_else(crate::versions::pinned_pkl_version)
+        .unwrap_or_else(|| get_recommended_pkl_version().to_string());
+
+    println!("📦 Installing Pkl CLI {} from {}...", target_version, archive.display());
+    verify_archive(archive, expected_sha256).await.map_err(miette::Report::new)?;
+    let archive_bytes = tokio::fs::read(archive).await.map_err(|e| {
+        miette::Report::new(CliError::IoError {
+            context: format!("Reading Pkl archive: {}", archive.display()),
+            source: e,
+        })
+    })?;
+
+    let install_dir = get_pkl_install_dir(&target_version)?;
+    let _lock = crate::lock::PathLock::acquire(&install_dir).map_err(miette::Report::new)?;
+    tokio::fs::create_dir_all(&install_dir).await.map_err(|e| {
+        miette::Report::new(CliError::IoError {
+            context: format!("Creating Pkl installation directory: {}", install_dir.display()),
+            source: e,
+        })
+    })?;
+
+    let pkl_executable_path = if archive.extension().is_some_and(|extension| extension == "zip") {
+        extract_zip_archive(&archive_bytes, &install_dir).await?
+    } else {
+        extract_tar_gz_archive(&archive_bytes, &install_dir).await?
+    };
+    make_executable(&pkl_executable_path).await?;
+
+    println!("✅ Installed Pkl CLI from local archive");
+    Ok(PklCli {
+        path: pkl_executable_path,
+        source: PklSource::LocalArchive(archive.to_path_buf()),
+        version: Some(target_version),
+    })
+}
+
+/// Check a local archive's SHA-256 against `expected`, or its `<archive>.sha256` file
+///
+/// Returns the verified digest. An archive without a recorded digest is rejected, since
+/// there's no published checksum to fall back on offline.
+pub async fn verify_archive(archive: &Path, expected: Option<&str>) -> Result<String, crate::types::CliError> {
+    use crate::types::CliError;
+
+    let sidecar = PathBuf::from(format!("{}.sha256", archive.display()));
+    let expected = match expected {
+        Some(expected) => crate::download::parse_sha256_file(expected).ok_or_else(|| CliError::PklInstallFailed {
+            reason: format!("'{}' is not a SHA-256 digest", expected),
+            help: Some("Pass the 64-character hex digest of the archive".to_string()),
+        })?,
+        None => tokio::fs::read_to_string(&sidecar)
+            .await
+            .ok()
+            .as_deref()
+            .and_then(crate::download::parse_sha256_file)
+            .ok_or_else(|| CliError::PklInstallFailed {
+                reason: format!("No recorded SHA-256 for {}", archive.display()),
+                help: Some(format!(
+                    "Pass --sha256 or record the digest in {} (e.g. `sha256sum <archive> > {}`)",
+                    sidecar.display(),
+                    sidecar.display()
+                )),
+            })?,
+    };
+
+    let actual = crate::download::sha256_file(archive).await?;
+    if actual != expected {
+        return Err(CliError::PklInstallFailed {
+            reason: format!("Checksum mismatch for {} (expected {}, got {})", archive.display(), expected, actual),
+            help: Some("Re-copy the archive from a trusted source, or update the recorded digest".to_string()),
+        });
+    }
+    Ok(actual)
+}
+
 /// Find existing Pkl executable
 ///
 /// Searches for Pkl CLI in order of preference: proto -> system PATH -> manual installations
 pub async fn find_pkl_executable() -> Result<Option<PklCli>> {
     use crate::types::CliError;
 
+    // Hermetic runs only use the Pkl CLI they were explicitly given
+    if crate::hermetic::is_enabled() {
+        return Ok(crate::hermetic::explicit_pkl_path().map(|path| PklCli {
+            path,
+            source: PklSource::SystemPath,
+            version: None,
+        }));
+    }
+
+    // A version picked with --pkl-version or `spklr pkl use` comes from the managed installs
+    if let Some(version) = selected_pkl_version() {
+        if let Some(pkl_cli) = installed_pkl(&version).await {
+            return Ok(Some(pkl_cli));
+        }
+        // --pkl-version is a requirement; the default is only a preference
+        if cli_pkl_version().is_some() {
+            return Err(miette::Report::new(CliError::PklInstallFailed {
+                reason: format!("Pkl CLI {} is not installed", version),
+                help: Some(format!("Install it with 'spklr pkl-me pkl --version {}'", version)),
+            }));
+        }
+    }
+
     // 1. Check proto-managed Pkl first
     if is_proto_available().await {
         if let Ok(pkl_cli) = check_proto_pkl().await {
@@ -111,36 +262,100 @@ pub async fn find_pkl_executable() -> Result<Option<PklCli>> {
         }
     }
 
-    // 3. Check manual installation locations
-    if let Ok(home_dir) = dirs::home_dir()
-        .ok_or_else(|| CliError::Generic("Could not find home directory".to_string()))
-    {
-        let pkl_tools_dir = home_dir.join(".moon").join("tools").join("pkl");
-
-        if pkl_tools_dir.exists() {
-            // Look for any version directory
-            if let Ok(entries) = std::fs::read_dir(&pkl_tools_dir) {
-                for entry in entries.flatten() {
-                    if entry.file_type().map_or(false, |ft| ft.is_dir()) {
-                        let pkl_path = entry.path().join("pkl");
-                        if pkl_path.exists() {
-                            if let Ok(version) = get_pkl_version(&pkl_path).await {
-                                return Ok(Some(PklCli {
-                                    path: pkl_path,
-                                    source: PklSource::Manual(entry.path()),
-                                    version: Some(version),
-                                }));
-                            }
-                        }
-                    }
-                }
-            }
+    // 3. Check manual installations, newest first
+    for version in installed_pkl_versions() {
+        if let Some(pkl_cli) = installed_pkl(&version).await {
+            return Ok(Some(pkl_cli));
         }
     }
 
     Ok(None)
 }
 
+/// Version picked for this run: `--pkl-version`, else the default set with `spklr pkl use`
+pub fn selected_pkl_version() -> Option<String> {
+    cli_pkl_version().or_else(default_pkl_version)
+}
+
+/// Versions installed under ~/.moon/tools/pkl/, newest first
+pub fn installed_pkl_versions() -> Vec<String> {
+    let Some(entries) = pkl_tools_dir().and_then(|tools_dir| std::fs::read_dir(tools_dir).ok()) else {
+        return Vec::new();
+    };
+    let mut versions: Vec<String> = entries
+        .flatten()
+        .filter(|entry| entry.path().join(pkl_executable_name()).is_file())
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .collect();
+    sort_versions(&mut versions);
+    versions
+}
+
+/// Sort version strings newest first, comparing dotted numbers numerically
+///
+/// Pre-releases (`0.28.0-rc.1`) sort below their release.
+pub fn sort_versions(versions: &mut [String]) {
+    let key = |version: &String| {
+        let (release, pre) = match version.trim_start_matches('v').split_once('-') {
+            Some((release, pre)) => (release.to_string(), Some(pre.to_string())),
+            None => (version.trim_start_matches('v').to_string(), None),
+        };
+        let numbers: Vec<u64> = release.split('.').map(|part| part.parse().unwrap_or(0)).collect();
+        (numbers, pre.is_none(), pre)
+    };
+    versions.sort_by_key(|version| std::cmp::Reverse(key(version)));
+}
+
+/// The default version recorded with `spklr pkl use`, if it's still installed
+pub fn default_pkl_version() -> Option<String> {
+    let tools_dir = pkl_tools_dir()?;
+    let version = std::fs::read_to_string(tools_dir.join(DEFAULT_VERSION_FILE)).ok()?;
+    let version = version.trim().to_string();
+    tools_dir.join(&version).join(pkl_executable_name()).is_file().then_some(version)
+}
+
+/// Make an installed version the default for later runs
+pub fn set_default_pkl_version(version: &str) -> Result<(), crate::types::CliError> {
+    use crate::types::CliError;
+
+    crate::read_only::ensure_allowed("change the default Pkl version")?;
+    let tools_dir = pkl_tools_dir().ok_or_else(|| CliError::Generic("Could not determine home directory".to_string()))?;
+    if !tools_dir.join(version).join(pkl_executable_name()).is_file() {
+        return Err(CliError::PklInstallFailed {
+            reason: format!("Pkl CLI {} is not installed", version),
+            help: Some(format!("Install it with 'spklr pkl-me pkl --version {}'", version)),
+        });
+    }
+    std::fs::write(tools_dir.join(DEFAULT_VERSION_FILE), format!("{}\n", version)).map_err(|e| CliError::IoError {
+        context: format!("Recording default Pkl version in {}", tools_dir.display()),
+        source: e,
+    })
+}
+
+/// A managed install of `version`, if it's there and runs
+async fn installed_pkl(version: &str) -> Option<PklCli> {
+    let install_dir = pkl_tools_dir()?.join(version);
+    let pkl_path = install_dir.join(pkl_executable_name());
+    if !pkl_path.is_file() {
+        return None;
+    }
+    let version = get_pkl_version(&pkl_path).await.ok()?;
+    Some(PklCli {
+        path: pkl_path,
+        source: PklSource::Manual(install_dir),
+        version: Some(version),
+    })
+}
+
+fn pkl_executable_name() -> &'static str {
+    if cfg!(windows) { "pkl.exe" } else { "pkl" }
+}
+
+/// ~/.moon/tools/pkl/, where downloaded versions are installed
+fn pkl_tools_dir() -> Option<PathBuf> {
+    dirs::home_dir().map(|home_dir| home_dir.join(".moon").join("tools").join("pkl"))
+}
+
 /// Install Pkl via proto
 async fn install_via_proto(version: &str) -> Result<PklCli> {
     use crate::types::CliError;
@@ -345,9 +560,79 @@ async fn extract_tar_gz_archive(_archive_bytes: &[u8], _target_dir: &PathBuf) ->
 /// Executes Pkl CLI with proper handling based on installation source
 pub async fn execute_pkl_command(pkl_cli: &PklCli, args: &[String]) -> Result<String> {
     use crate::types::{CliError, pkl_execution_error};
+
+    let mut cmd = pkl_command(pkl_cli, args);
+    let _timer = crate::timings::Timer::start(crate::timings::Phase::PklEval);
+    let output = cmd.output().map_err(|e| CliError::PklExecutionFailed {
+        command: format!("{:?}", cmd),
+        stderr: e.to_string(),
+        help: Some("Check that Pkl CLI is properly installed and accessible".to_string()),
+    })?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(miette::Report::new(pkl_execution_error(
+            format!("{:?}", cmd),
+            stderr.to_string(),
+            Some("Check Pkl syntax and file paths".to_string()),
+        )))
+    }
+}
+
+/// Module URI schemes evaluations may use, as `pkl eval` allows by default
+const ALLOWED_MODULES: &[&str] = &["pkl:", "repl:", "file:", "modulepath:", "package:", "projectpackage:", "https:"];
+/// Resource URI schemes evaluations may use, as `pkl eval` allows by default
+const ALLOWED_RESOURCES: &[&str] = &["env:", "prop:", "file:", "modulepath:", "package:", "projectpackage:", "https:"];
+/// Schemes that download: `https:` directly, packages from their repositories
+const NETWORK_SCHEMES: &[&str] = &["https:", "package:", "projectpackage:"];
+
+/// Whether Pkl must stay off the network and away from its package cache (~/.pkl)
+fn offline_pkl() -> bool {
+    crate::read_only::is_enabled() || crate::hermetic::is_enabled()
+}
+
+fn allowed(schemes: &[&'static str]) -> Vec<&'static str> {
+    schemes
+        .iter()
+        .copied()
+        .filter(|scheme| !offline_pkl() || !NETWORK_SCHEMES.contains(scheme))
+        .collect()
+}
+
+/// Module URI schemes Pkl evaluations may use; read-only and hermetic runs drop the ones
+/// that reach the network
+pub fn allowed_modules() -> Vec<&'static str> {
+    allowed(ALLOWED_MODULES)
+}
+
+/// Resource URI schemes Pkl evaluations may read; read-only and hermetic runs drop the
+/// ones that reach the network
+pub fn allowed_resources() -> Vec<&'static str> {
+    allowed(ALLOWED_RESOURCES)
+}
+
+/// The command running the Pkl CLI with `args`, however it was installed
+///
+/// Read-only and hermetic runs keep `pkl eval` away from its package cache (~/.pkl) and
+/// the network: only the non-network [`allowed_modules`] and [`allowed_resources`] are
+/// passed on.
+pub fn pkl_command(pkl_cli: &PklCli, args: &[String]) -> std::process::Command {
     use std::process::Command;
 
-    let mut cmd = match &pkl_cli.source {
+    let mut args = args.to_vec();
+    if offline_pkl() && args.first().is_some_and(|sub| sub == "eval") {
+        let offline = [
+            "--no-cache".to_string(),
+            "--allowed-modules".to_string(),
+            allowed_modules().join(","),
+            "--allowed-resources".to_string(),
+            allowed_resources().join(","),
+        ];
+        args = [&args[..1], &offline[..], &args[1..]].concat();
+    }
+    match &pkl_cli.source {
         PklSource::Proto => {
             let mut command = Command::new("proto");
             command.arg("run");
@@ -360,28 +645,11 @@ pub async fn execute_pkl_command(pkl_cli: &PklCli, args: &[String]) -> Result<St
             command.args(args);
             command
         }
-        PklSource::SystemPath | PklSource::Manual(_) => {
+        PklSource::SystemPath | PklSource::Manual(_) | PklSource::LocalArchive(_) => {
             let mut command = Command::new(&pkl_cli.path);
             command.args(args);
             command
         }
-    };
-
-    let output = cmd.output().map_err(|e| CliError::PklExecutionFailed {
-        command: format!("{:?}", cmd),
-        stderr: e.to_string(),
-        help: Some("Check that Pkl CLI is properly installed and accessible".to_string()),
-    })?;
-
-    if output.status.success() {
-        Ok(String::from_utf8_lossy(&output.stdout).to_string())
-    } else {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        Err(miette::Report::new(pkl_execution_error(
-            format!("{:?}", cmd),
-            stderr.to_string(),
-            Some("Check Pkl syntax and file paths".to_string()),
-        )))
     }
 }
 
@@ -393,22 +661,16 @@ async fn download_pkl_binary(version: &str) -> Result<PathBuf> {
     use std::env;
 
     // Platform detection
-    let (os, arch) = match (env::consts::OS, env::consts::ARCH) {
-        ("linux", "x86_64") => ("linux", "amd64"),
-        ("linux", "aarch64") => ("linux", "aarch64"),
-        ("macos", "x86_64") => ("macos", "amd64"),
-        ("macos", "aarch64") => ("macos", "aarch64"),
-        ("windows", "x86_64") => ("windows", "amd64"),
-        (os, arch) => {
-            return Err(miette::Report::new(CliError::PklInstallFailed {
-                reason: format!("Unsupported platform: {}-{}", os, arch),
-                help: Some("Install Pkl CLI manually or use proto".to_string()),
-            }));
-        }
-    };
+    let archive_name = pkl_archive_name(env::consts::OS, env::consts::ARCH).ok_or_else(|| {
+        miette::Report::new(CliError::PklInstallFailed {
+            reason: format!("Unsupported platform: {}-{}", env::consts::OS, env::consts::ARCH),
+            help: Some("Install Pkl CLI manually or use proto".to_string()),
+        })
+    })?;
 
-    // Create installation directory
+    // Create installation directory; the lock keeps concurrent installs out of each other's way
     let install_dir = get_pkl_install_dir(version)?;
+    let _lock = crate::lock::PathLock::acquire(&install_dir).map_err(miette::Report::new)?;
     tokio::fs::create_dir_all(&install_dir).await.map_err(|e| {
         miette::Report::new(CliError::IoError {
             context: format!(
@@ -420,41 +682,50 @@ async fn download_pkl_binary(version: &str) -> Result<PathBuf> {
     })?;
 
     // Construct download URL
-    let file_extension = if env::consts::OS == "windows" {
-        "zip"
-    } else {
-        "tar.gz"
-    };
-    let archive_name = format!("pkl-cli-{}-{}.{}", os, arch, file_extension);
-    let download_url = format!(
-        "https://github.com/apple/pkl/releases/download/{}/{}",
-        version, archive_name
+    let download_url = pkl_release_url(version, &archive_name);
+
+    // Mirrors follow the GitHub release layout: <mirror>/<version>/<archive>
+    let tool_config = crate::tool_config::ToolConfig::discover().map_err(miette::Report::new)?;
+    let mut urls = vec![download_url.clone()];
+    urls.extend(
+        tool_config
+            .download
+            .all_mirrors()
+            .iter()
+            .map(|mirror| format!("{}/{}/{}", mirror.trim_end_matches('/'), version, archive_name)),
     );
 
     println!("📥 Downloading from: {}", download_url);
+    if urls.len() > 1 {
+        println!("🪞 {} mirror(s) configured as fallback", urls.len() - 1);
+    }
 
-    // Download with retry logic
-    let client = reqwest::Client::new();
-    let response = client
-        .get(&download_url)
-        .send()
-        .await
-        .map_err(|e| miette::Report::new(CliError::NetworkError(e.to_string())))?;
-
-    if !response.status().is_success() {
-        return Err(miette::Report::new(CliError::PklInstallFailed {
-            reason: format!("Download failed with status: {}", response.status()),
-            help: Some(format!(
-                "Check if version {} exists at {}",
-                version, download_url
-            )),
-        }));
+    let expected_sha256 = crate::download::fetch_published_sha256(
+        &urls,
+        &tool_config.retry_policy(crate::network::Operation::Checksum),
+    )
+    .await;
+    if expected_sha256.is_none() {
+        tracing::warn!("No published checksum found for {}; skipping verification", archive_name);
     }
 
-    let archive_bytes = response
-        .bytes()
-        .await
-        .map_err(|e| miette::Report::new(CliError::NetworkError(e.to_string())))?;
+    let archive_path = install_dir.join(&archive_name);
+    crate::download::download_resumable(
+        &urls,
+        &archive_path,
+        expected_sha256.as_deref(),
+        &tool_config.retry_policy(crate::network::Operation::Download),
+    )
+    .await
+    .map_err(miette::Report::new)?;
+
+    let archive_bytes = tokio::fs::read(&archive_path).await.map_err(|e| {
+        miette::Report::new(CliError::IoError {
+            context: format!("Reading downloaded archive: {}", archive_path.display()),
+            source: e,
+        })
+    })?;
+    let _ = tokio::fs::remove_file(&archive_path).await;
 
     // Extract archive
     let pkl_executable_path = if env::consts::OS == "windows" {
@@ -463,11 +734,18 @@ async fn download_pkl_binary(version: &str) -> Result<PathBuf> {
         extract_tar_gz_archive(&archive_bytes, &install_dir).await?
     };
 
-    // Set executable permissions on Unix-like systems
+    make_executable(&pkl_executable_path).await?;
+
+    Ok(pkl_executable_path)
+}
+
+/// Set executable permissions on Unix-like systems
+async fn make_executable(path: &Path) -> Result<()> {
     #[cfg(unix)]
     {
+        use crate::types::CliError;
         use std::os::unix::fs::PermissionsExt;
-        let mut perms = tokio::fs::metadata(&pkl_executable_path)
+        let mut perms = tokio::fs::metadata(path)
             .await
             .map_err(|e| {
                 miette::Report::new(CliError::IoError {
@@ -477,7 +755,7 @@ async fn download_pkl_binary(version: &str) -> Result<PathBuf> {
             })?
             .permissions();
         perms.set_mode(0o755);
-        tokio::fs::set_permissions(&pkl_executable_path, perms)
+        tokio::fs::set_permissions(path, perms)
             .await
             .map_err(|e| {
                 miette::Report::new(CliError::IoError {
@@ -486,8 +764,31 @@ async fn download_pkl_binary(version: &str) -> Result<PathBuf> {
                 })
             })?;
     }
+    #[cfg(not(unix))]
+    let _ = path;
+    Ok(())
+}
 
-    Ok(pkl_executable_path)
+/// Release archive name for a Rust `(OS, ARCH)` pair, if Pkl publishes one
+pub fn pkl_archive_name(os: &str, arch: &str) -> Option<String> {
+    let (os, arch) = match (os, arch) {
+        ("linux", "x86_64") => ("linux", "amd64"),
+        ("linux", "aarch64") => ("linux", "aarch64"),
+        ("macos", "x86_64") => ("macos", "amd64"),
+        ("macos", "aarch64") => ("macos", "aarch64"),
+        ("windows", "x86_64") => ("windows", "amd64"),
+        _ => return None,
+    };
+    let file_extension = if os == "windows" { "zip" } else { "tar.gz" };
+    Some(format!("pkl-cli-{}-{}.{}", os, arch, file_extension))
+}
+
+/// GitHub release URL for a Pkl archive
+pub fn pkl_release_url(version: &str, archive_name: &str) -> String {
+    format!(
+        "https://github.com/apple/pkl/releases/download/{}/{}",
+        version, archive_name
+    )
 }
 
 /// Get the target installation directory for Pkl
@@ -496,17 +797,13 @@ async fn download_pkl_binary(version: &str) -> Result<PathBuf> {
 fn get_pkl_install_dir(version: &str) -> Result<PathBuf> {
     use crate::types::CliError;
 
-    let home_dir = dirs::home_dir().ok_or_else(|| {
+    let tools_dir = pkl_tools_dir().ok_or_else(|| {
         miette::Report::new(CliError::Generic(
             "Could not determine home directory".to_string(),
         ))
     })?;
 
-    Ok(home_dir
-        .join(".moon")
-        .join("tools")
-        .join("pkl")
-        .join(version))
+    Ok(tools_dir.join(version))
 }
 
 /// Check if proto is available in the system
@@ -528,6 +825,9 @@ pub fn get_compatible_pkl_versions() -> Vec<&'static str> {
 }
 
 /// Comprehensive compatibility report for Pkl CLI validation
+///
+/// The functional checks are run by [`validate_pkl_compatibility`]; [`compatibility_report`]
+/// adds the version matrix rows in `checks` (see [`crate::compatibility`]).
 #[derive(Debug)]
 pub struct CompatibilityReport {
     pub basic_functionality: bool,
@@ -535,6 +835,7 @@ pub struct CompatibilityReport {
     pub extend_amend_support: bool,
     pub schema_generation: bool,
     pub pkl_version: String,
+    pub checks: Vec<crate::compatibility::CompatibilityCheck>,
 }
 
 impl CompatibilityReport {
@@ -545,6 +846,7 @@ impl CompatibilityReport {
             extend_amend_support: false,
             schema_generation: false,
             pkl_version,
+            checks: Vec::new(),
         }
     }
 
@@ -553,7 +855,48 @@ impl CompatibilityReport {
             && self.moon_config_integration
             && self.extend_amend_support
             && self.schema_generation
+            && self.failed_checks() == 0
+    }
+
+    /// Matrix rows with an error status
+    pub fn failed_checks(&self) -> usize {
+        self.checks
+            .iter()
+            .filter(|check| check.status == crate::compatibility::CheckStatus::Error)
+            .count()
+    }
+}
+
+/// Full compatibility matrix: the Pkl CLI (when found) with its functional checks,
+/// moon_config, the schemas generated into `schema_dir`, and the platform
+pub async fn compatibility_report(pkl_cli: Option<&PklCli>, schema_dir: &Path) -> Result<CompatibilityReport> {
+    use crate::compatibility::{check_functional, check_generated_schemas, check_moon_config, check_pkl_version, check_platform};
+    use crate::versions::{VERSIONS_FILE, VersionManifest};
+
+    let manifest_path = schema_dir.join(VERSIONS_FILE);
+    let manifest = if manifest_path.is_file() {
+        Some(VersionManifest::load(&manifest_path)?)
+    } else {
+        VersionManifest::discover()?
+    };
+
+    let mut report = match pkl_cli {
+        Some(pkl_cli) => validate_pkl_compatibility(pkl_cli).await?,
+        None => CompatibilityReport::new("not found".to_string()),
+    };
+    let found = pkl_cli.and_then(|pkl_cli| pkl_cli.version.as_deref());
+    report.checks.push(check_pkl_version(found, manifest.as_ref().map(|manifest| manifest.pkl.as_str())));
+    if pkl_cli.is_some() {
+        report.checks.extend([
+            check_functional("Pkl evaluation", report.basic_functionality && report.moon_config_integration),
+            check_functional("Pkl amends/extends", report.extend_amend_support),
+            check_functional("Pkl packaging", report.schema_generation),
+        ]);
     }
+    report.checks.push(check_moon_config(manifest.as_ref()));
+    report.checks.push(check_generated_schemas(schema_dir, manifest.as_ref()));
+    report.checks.push(check_platform(std::env::consts::OS, std::env::consts::ARCH));
+    Ok(report)
 }
 
 /// Validate Pkl version compatibility with comprehensive testing
@@ -797,3 +1140,235 @@ pub async fn validate_pkl_installation(pkl_cli: &PklCli) -> Result<bool> {
         Err(_) => Ok(false),
     }
 }
+
+/// Environment variable naming the pkldoc executable or jar to use
+pub const PKLDOC_PATH_ENV: &str = "SPKLR_PKLDOC_PATH";
+
+/// File name of a managed pkldoc install
+const PKLDOC_JAR: &str = "pkldoc.jar";
+
+/// The pkldoc documentation generator
+#[derive(Debug, Clone)]
+pub struct PklDoc {
+    /// A native executable, or an executable jar that runs with `java -jar`
+    pub path: PathBuf,
+    pub source: PklSource,
+    pub version: Option<String>,
+}
+
+impl PklDoc {
+    pub fn is_jar(&self) -> bool {
+        self.path.extension().is_some_and(|ext| ext == "jar")
+    }
+}
+
+/// Find pkldoc
+///
+/// Searches `SPKLR_PKLDOC_PATH`, then `pkldoc` on the system PATH, then the managed
+/// installs under ~/.moon/tools/pkldoc/ (the selected Pkl version first, else the
+/// newest). Hermetic runs only use `SPKLR_PKLDOC_PATH`.
+pub fn find_pkldoc() -> Option<PklDoc> {
+    let explicit = std::env::var_os(PKLDOC_PATH_ENV)
+        .filter(|path| !path.is_empty())
+        .map(|path| PklDoc {
+            path: PathBuf::from(path),
+            source: PklSource::SystemPath,
+            version: None,
+        });
+    if explicit.is_some() || crate::hermetic::is_enabled() {
+        return explicit;
+    }
+
+    if let Ok(path) = which::which("pkldoc") {
+        return Some(PklDoc {
+            path,
+            source: PklSource::SystemPath,
+            version: None,
+        });
+    }
+
+    let installed = installed_pkldoc_versions();
+    let version = selected_pkl_version()
+        .filter(|version| installed.contains(version))
+        .or_else(|| installed.first().cloned())?;
+    let install_dir = pkldoc_tools_dir()?.join(&version);
+    Some(PklDoc {
+        path: install_dir.join(PKLDOC_JAR),
+        source: PklSource::Manual(install_dir),
+        version: Some(version),
+    })
+}
+
+/// pkldoc versions installed under ~/.moon/tools/pkldoc/, newest first
+pub fn installed_pkldoc_versions() -> Vec<String> {
+    let Some(entries) = pkldoc_tools_dir().and_then(|tools_dir| std::fs::read_dir(tools_dir).ok()) else {
+        return Vec::new();
+    };
+    let mut versions: Vec<String> = entries
+        .flatten()
+        .filter(|entry| entry.path().join(PKLDOC_JAR).is_file())
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .collect();
+    sort_versions(&mut versions);
+    versions
+}
+
+/// Download pkldoc into ~/.moon/tools/pkldoc/<version>/
+///
+/// pkldoc is released alongside the Pkl CLI, so the version is picked the same way:
+/// `version`, `--pkl-version`, the pinned version, then the recommended one. It's an
+/// executable jar from Maven Central and needs Java 17 or later to run.
+pub async fn install_pkldoc(version: Option<String>) -> Result<PklDoc> {
+    use crate::types::CliError;
+
+    crate::read_only::ensure_allowed("install pkldoc").map_err(miette::Report::new)?;
+    let target_version = version
+        .or_else(cli_pkl_version)
+        .or_else(crate::versions::pinned_pkl_version)
+        .unwrap_or_else(|| get_recommended_pkl_version().to_string());
+
+    if which::which("java").is_err() {
+        return Err(miette::Report::new(CliError::PklInstallFailed {
+            reason: "pkldoc needs Java 17 or later, which wasn't found on PATH".to_string(),
+            help: Some(format!("Install a JDK, or point {} at a native pkldoc", PKLDOC_PATH_ENV)),
+        }));
+    }
+
+    let install_dir = pkldoc_tools_dir()
+        .ok_or_else(|| miette::Report::new(CliError::Generic("Could not determine home directory".to_string())))?
+        .join(&target_version);
+    let jar = install_dir.join(PKLDOC_JAR);
+    let _lock = crate::lock::PathLock::acquire(&install_dir).map_err(miette::Report::new)?;
+    if !jar.is_file() {
+        tokio::fs::create_dir_all(&install_dir).await.map_err(|e| {
+            miette::Report::new(CliError::IoError {
+                context: format!("Creating pkldoc installation directory: {}", install_dir.display()),
+                source: e,
+            })
+        })?;
+
+        let urls = vec![pkldoc_jar_url(&target_version)];
+        println!("📥 Downloading pkldoc {} from: {}", target_version, urls[0]);
+        let tool_config = crate::tool_config::ToolConfig::discover().map_err(miette::Report::new)?;
+        let expected_sha256 = crate::download::fetch_published_sha256(
+            &urls,
+            &tool_config.retry_policy(crate::network::Operation::Checksum),
+        )
+        .await;
+        if expected_sha256.is_none() {
+            tracing::warn!("No published checksum found for pkldoc {}; skipping verification", target_version);
+        }
+        crate::download::download_resumable(
+            &urls,
+            &jar,
+            expected_sha256.as_deref(),
+            &tool_config.retry_policy(crate::network::Operation::Download),
+        )
+        .await
+        .map_err(miette::Report::new)?;
+        println!("✅ Installed pkldoc at {}", jar.display());
+    }
+
+    Ok(PklDoc {
+        path: jar,
+        source: PklSource::Manual(install_dir),
+        version: Some(target_version),
+    })
+}
+
+/// Maven Central URL of the pkldoc jar for a Pkl version
+pub fn pkldoc_jar_url(version: &str) -> String {
+    format!(
+        "https://repo1.maven.org/maven2/org/pkl-lang/pkl-doc/{}/pkl-doc-{}.jar",
+        version, version
+    )
+}
+
+/// ~/.moon/tools/pkldoc/, where downloaded pkldoc versions are installed
+fn pkldoc_tools_dir() -> Option<PathBuf> {
+    dirs::home_dir().map(|home_dir| home_dir.join(".moon").join("tools").join("pkldoc"))
+}
+
+/// Run pkldoc over `modules`, writing the HTML site into `output_dir`
+///
+/// `modules` must include a `doc-package-info.pkl` (see [`pkldoc_package_info`]), and
+/// every other module's name must start with that package's name.
+pub async fn run_pkldoc(pkldoc: &PklDoc, modules: &[PathBuf], output_dir: &Path) -> Result<()> {
+    use crate::types::{CliError, pkl_execution_error};
+    use std::process::Command;
+
+    let mut cmd = if pkldoc.is_jar() {
+        let mut command = Command::new("java");
+        command.arg("-jar").arg(&pkldoc.path);
+        command
+    } else {
+        Command::new(&pkldoc.path)
+    };
+    cmd.arg("--output-dir").arg(output_dir).args(modules);
+
+    let _timer = crate::timings::Timer::start(crate::timings::Phase::PklEval);
+    let output = cmd.output().map_err(|e| CliError::PklExecutionFailed {
+        command: format!("{:?}", cmd),
+        stderr: e.to_string(),
+        help: Some(format!("Check that pkldoc (and Java, for the jar) is installed, or set {}", PKLDOC_PATH_ENV)),
+    })?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(miette::Report::new(pkl_execution_error(
+            format!("{:?}", cmd),
+            String::from_utf8_lossy(&output.stderr).to_string(),
+            Some("pkldoc couldn't document these modules; check that they evaluate with `pkl eval`".to_string()),
+        )))
+    }
+}
+
+/// `doc-package-info.pkl` for a pkldoc package
+///
+/// `overview` becomes the package's doc comment, shown on its page. Without a source
+/// repository, the source links point at `import_uri`.
+pub fn pkldoc_package_info(name: &str, version: &str, import_uri: &str, source_url: Option<&str>, overview: Option<&str>) -> String {
+    use crate::config_processor::pkl_string;
+
+    let source = source_url.unwrap_or(import_uri).trim_end_matches('/');
+    let mut output = String::new();
+    for line in overview.into_iter().flat_map(str::lines) {
+        output.push_str(format!("/// {}", line).trim_end());
+        output.push('\n');
+    }
+    output.push_str("amends \"pkl:DocPackageInfo\"\n\n");
+    for (field, value) in [
+        ("name", name.to_string()),
+        ("version", version.to_string()),
+        ("importUri", import_uri.to_string()),
+        ("sourceCode", format!("{}/", source)),
+        ("sourceCodeUrlScheme", format!("{}%{{path}}", source)),
+        ("issueTracker", format!("{}/", source)),
+    ] {
+        output.push_str(&format!("{} = {}\n", field, pkl_string(&value)));
+    }
+    output.push_str("authors {}\n");
+    output
+}
+
+/// `source` with its module named `<package>.<name>`, as pkldoc requires
+///
+/// Modules without a `module` clause are named after `fallback_name`.
+pub fn pkldoc_module_source(source: &str, package: &str, fallback_name: &str) -> String {
+    let clause = regex::Regex::new(r"(?m)^((?:open |abstract )?module )([\w.`]+)[ \t]*$").expect("valid regex");
+    if clause.is_match(source) {
+        return clause
+            .replacen(source, 1, |captures: &regex::Captures| format!("{}{}.{}", &captures[1], package, &captures[2]))
+            .to_string();
+    }
+    // The clause goes after the module's doc comment and annotations
+    let lines: Vec<&str> = source.lines().collect();
+    let header = lines
+        .iter()
+        .take_while(|line| line.starts_with("///") || line.starts_with('@'))
+        .count();
+    let mut output: Vec<String> = lines[..header].iter().map(|line| line.to_string()).collect();
+    output.push(format!("module {}.{}", package, fallback_name));
+    output.extend(lines[header..].iter().map(|line| line.to_string()));
+    output.join("\n") + "\n"
+}
@@ -0,0 +1,578 @@
+//! Policy Module for Space Pklr
+//!
+//! Organizations declare rules about their Moon configs in a policy file (YAML,
+//! JSON, or Pkl evaluated to JSON), and `spklr lint --policy` evaluates them.
+//!
+//! ```yaml
+//! rules:
+//!   - id: tasks-cache
+//!     description: All tasks must set options.cache
+//!     appliesTo: [project, task]
+//!     select: tasks.*
+//!     require: options.cache
+//!   - id: no-docker
+//!     description: The docker toolchain is forbidden
+//!     appliesTo: [toolchain]
+//!     forbid: docker
+//!   - id: languages
+//!     select: language
+//!     allowed: [rust, typescript]
+//!     severity: warning
+//! ```
+//!
+//! Budgets cap config size and complexity so oversized configs get factored into
+//! inherited task files. Each budget is reported as a `budget-*` rule:
+//!
+//! ```yaml
+//! budgets:
+//!   maxTasks: 25        # budget-max-tasks
+//!   maxFileSize: 16KiB  # budget-max-file-size (bytes, or a size like 2MB)
+//!   maxDepth: 6         # budget-max-depth
+//! ```
+//!
+//! Overrides tighten the schemas generated from JSON Schema with
+//! `spklr generate schema --policy`: a property can be made required, given a
+//! different default, or locked to it with Pkl's `fixed`. Paths follow class-typed
+//! properties from the module root, like `include_properties`:
+//!
+//! ```yaml
+//! overrides:
+//!   - path: toolchain.node.version
+//!     required: true
+//!     default: "20.11.0"
+//!     fixed: true
+//!   - path: toolchain.node.version
+//!     constraints: ['startsWith("20.") || startsWith("22.")']
+//!     reason: Node 20 or 22 only
+//! ```
+//!
+//! `constraints` are Pkl expressions added to the property's type, with a comment
+//! crediting the org policy (and `reason`, if given).
+//!
+//! `keyWhitespace`, `requireSchema`, and `renames` enable warnings that
+//! `spklr lint --fix` can correct in place (see [`crate::autofix`]).
+//!
+//! Selectors are dot-separated paths where `*` matches any single key or list index
+//! and `**` matches any depth. A config can exempt itself from a rule with a comment
+//! annotation, which must include a reason:
+//!
+//! ```yaml
+//! # spklr:allow(no-docker) legacy image builds until Q3
+//! ```
+//!
+//! An annotation without a reason exempts nothing and is reported as an
+//! [`EXEMPTION_WITHOUT_REASON`] error.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use crate::types::{ByteSize, CliError, MoonConfig};
+
+/// How serious a rule violation is
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Warning,
+    #[default]
+    Error,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// A single organizational rule
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PolicyRule {
+    pub id: String,
+    pub description: Option<String>,
+    pub severity: Severity,
+    /// Config types the rule applies to (`project`, `workspace`, ...); empty means all
+    pub applies_to: Vec<String>,
+    /// Selector for the values the checks run against; defaults to the config root
+    pub select: Option<String>,
+    /// Path (relative to each selected value) that must be set
+    pub require: Option<String>,
+    /// Path (relative to each selected value) that must not be set
+    pub forbid: Option<String>,
+    /// Allowed values for each selected value
+    pub allowed: Option<Vec<Value>>,
+    /// Denied values for each selected value
+    pub denied: Option<Vec<Value>>,
+    /// Regex that selected string values must match
+    pub pattern: Option<String>,
+}
+
+/// Policy-file level exemption, for configs that can't carry annotations (e.g. generated files)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PolicyExemption {
+    pub rule: String,
+    /// Substring matched against the config file path
+    pub file: String,
+    pub reason: String,
+}
+
+/// Rule id for the tasks-per-config budget
+pub const BUDGET_MAX_TASKS: &str = "budget-max-tasks";
+/// Rule id for the file size budget
+pub const BUDGET_MAX_FILE_SIZE: &str = "budget-max-file-size";
+/// Rule id for the nesting depth budget
+pub const BUDGET_MAX_DEPTH: &str = "budget-max-depth";
+/// Rule id for `spklr:allow(...)` annotations that don't give a reason
+pub const EXEMPTION_WITHOUT_REASON: &str = "exemption-without-reason";
+
+/// Size and complexity limits for configs
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Budgets {
+    /// Maximum number of tasks a single config may define
+    pub max_tasks: Option<usize>,
+    /// Maximum config file size
+    pub max_file_size: Option<ByteSize>,
+    /// Maximum nesting depth of objects and lists below the top level
+    pub max_depth: Option<usize>,
+    pub severity: Severity,
+}
+
+/// A change to a property of generated schemas
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PropertyOverride {
+    /// Property path from the module root; `*` and `**` work as in selectors
+    pub path: String,
+    /// Modules the override applies to (`Project`, `workspace`, ...); empty means all
+    pub applies_to: Vec<String>,
+    /// Make the property required (`true`) or optional (`false`)
+    pub required: Option<bool>,
+    /// New default value
+    pub default: Option<Value>,
+    /// Lock the property to its default with `fixed`
+    pub fixed: bool,
+    /// Pkl type constraints added to the property, e.g. `startsWith("20.")`
+    pub constraints: Vec<String>,
+    /// Why the override exists, noted next to its constraints
+    pub reason: Option<String>,
+}
+
+/// A deprecated property with a 1:1 replacement, fixable with `spklr lint --fix`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PropertyRename {
+    /// Selector for the deprecated property
+    pub from: String,
+    /// New name for the property's key
+    pub to: String,
+    pub reason: Option<String>,
+}
+
+/// A parsed policy file
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Policy {
+    pub rules: Vec<PolicyRule>,
+    pub exemptions: Vec<PolicyExemption>,
+    pub budgets: Option<Budgets>,
+    pub overrides: Vec<PropertyOverride>,
+    /// Report keys with leading or trailing whitespace (see [`crate::autofix`])
+    pub key_whitespace: bool,
+    /// Report YAML and JSON configs without a `$schema`
+    pub require_schema: bool,
+    /// Deprecated properties to rename
+    pub renames: Vec<PropertyRename>,
+}
+
+/// A rule violation found in a config file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PolicyViolation {
+    pub rule: String,
+    pub severity: Severity,
+    pub file: PathBuf,
+    /// Dot path to the offending value
+    pub path: String,
+    pub message: String,
+}
+
+/// A rule skipped because of an exemption, kept so reports can show what was waived
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AppliedExemption {
+    pub rule: String,
+    pub file: PathBuf,
+    pub reason: String,
+}
+
+/// Result of evaluating a policy over one or more configs
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PolicyReport {
+    pub violations: Vec<PolicyViolation>,
+    pub exemptions: Vec<AppliedExemption>,
+}
+
+impl PolicyReport {
+    /// Number of violations at error severity
+    pub fn error_count(&self) -> usize {
+        self.violations
+            .iter()
+            .filter(|v| v.severity == Severity::Error)
+            .count()
+    }
+
+    pub fn merge(&mut self, other: PolicyReport) {
+        self.violations.extend(other.violations);
+        self.exemptions.extend(other.exemptions);
+    }
+}
+
+impl Policy {
+    /// Build a policy from an already loaded value
+    pub fn from_value(value: Value) -> Result<Self, CliError> {
+        let policy: Policy = serde_json::from_value(value).map_err(|e| CliError::ValidationError {
+            source: Box::new(e),
+        })?;
+
+        for rule in &policy.rules {
+            if rule.id.is_empty() {
+                return Err(CliError::Generic("Every policy rule needs an `id`".to_string()));
+            }
+            if let Some(pattern) = &rule.pattern {
+                Regex::new(pattern).map_err(|e| {
+                    CliError::Generic(format!("Invalid pattern in policy rule '{}': {}", rule.id, e))
+                })?;
+            }
+        }
+
+        for rename in &policy.renames {
+            if rename.from.is_empty() || rename.to.is_empty() {
+                return Err(CliError::Generic("Every policy rename needs a `from` and a `to`".to_string()));
+            }
+        }
+
+        for property in &policy.overrides {
+            if property.path.is_empty() {
+                return Err(CliError::Generic("Every policy override needs a `path`".to_string()));
+            }
+            if property.fixed && property.default.is_none() {
+                return Err(CliError::Generic(format!(
+                    "Policy override '{}' is fixed, so it needs a `default`",
+                    property.path
+                )));
+            }
+            if property.constraints.iter().any(|constraint| constraint.trim().is_empty()) {
+                return Err(CliError::Generic(format!(
+                    "Policy override '{}' has an empty constraint",
+                    property.path
+                )));
+            }
+        }
+
+        Ok(policy)
+    }
+
+    /// Load a policy file (YAML, JSON, or Pkl)
+    pub async fn load(path: &Path) -> Result<Self, CliError> {
+        let value = crate::config_processor::load_config_value(path, None).await?;
+        Self::from_value(value)
+    }
+
+    /// Evaluate every applicable rule against a config
+    ///
+    /// `source` is the raw file content, scanned for `spklr:allow(rule)` annotations.
+    pub fn evaluate(
+        &self,
+        file: &Path,
+        config_type: Option<MoonConfig>,
+        config: &Value,
+        source: &str,
+    ) -> PolicyReport {
+        let (annotations, unexplained) = parse_exemption_annotations(source);
+        let mut report = PolicyReport::default();
+
+        for rule in unexplained {
+            report.violations.push(PolicyViolation {
+                rule: EXEMPTION_WITHOUT_REASON.to_string(),
+                severity: Severity::Error,
+                file: file.to_path_buf(),
+                path: "<root>".to_string(),
+                message: format!("`spklr:allow({})` needs a reason, so the rule still applies", rule),
+            });
+        }
+
+        for rule in &self.rules {
+            if !rule_applies_to(rule, config_type) {
+                continue;
+            }
+
+            if let Some(reason) = self.exemption_reason(&annotations, &rule.id, file) {
+                report.exemptions.push(AppliedExemption {
+                    rule: rule.id.clone(),
+                    file: file.to_path_buf(),
+                    reason,
+                });
+                continue;
+            }
+
+            let selected = match &rule.select {
+                Some(selector) => select_values(config, selector),
+                None => vec![(String::new(), config)],
+            };
+
+            for (path, value) in selected {
+                for message in check_rule(rule, value) {
+                    report.violations.push(PolicyViolation {
+                        rule: rule.id.clone(),
+                        severity: rule.severity,
+                        file: file.to_path_buf(),
+                        path: if path.is_empty() { "<root>".to_string() } else { path.clone() },
+                        message,
+                    });
+                }
+            }
+        }
+
+        for fix in crate::autofix::detect(self, file, config_type, config) {
+            if let Some(reason) = self.exemption_reason(&annotations, fix.rule, file) {
+                report.exemptions.push(AppliedExemption {
+                    rule: fix.rule.to_string(),
+                    file: file.to_path_buf(),
+                    reason,
+                });
+                continue;
+            }
+            report.violations.push(PolicyViolation {
+                rule: fix.rule.to_string(),
+                severity: Severity::Warning,
+                file: file.to_path_buf(),
+                path: fix.path.to_string(),
+                message: format!("{} (fixable with --fix)", fix.message()),
+            });
+        }
+
+        if let Some(budgets) = &self.budgets {
+            for (rule, path, message) in check_budgets(budgets, config, source) {
+                if let Some(reason) = self.exemption_reason(&annotations, rule, file) {
+                    report.exemptions.push(AppliedExemption {
+                        rule: rule.to_string(),
+                        file: file.to_path_buf(),
+                        reason,
+                    });
+                    continue;
+                }
+                report.violations.push(PolicyViolation {
+                    rule: rule.to_string(),
+                    severity: budgets.severity,
+                    file: file.to_path_buf(),
+                    path,
+                    message,
+                });
+            }
+        }
+
+        report
+    }
+
+    /// Problems in a config that `spklr lint --fix` can correct, leaving out exempt rules
+    pub fn fixes(
+        &self,
+        file: &Path,
+        config_type: Option<MoonConfig>,
+        config: &Value,
+        source: &str,
+    ) -> Vec<crate::autofix::Fix> {
+        let (annotations, _) = parse_exemption_annotations(source);
+        crate::autofix::detect(self, file, config_type, config)
+            .into_iter()
+            .filter(|fix| self.exemption_reason(&annotations, fix.rule, file).is_none())
+            .collect()
+    }
+
+    /// Reason a rule is waived for a file, from an annotation or a policy exemption
+    fn exemption_reason(
+        &self,
+        annotations: &BTreeMap<String, String>,
+        rule: &str,
+        file: &Path,
+    ) -> Option<String> {
+        annotations.get(rule).cloned().or_else(|| {
+            self.exemptions
+                .iter()
+                .find(|ex| ex.rule == rule && file.to_string_lossy().contains(&ex.file))
+                .map(|ex| ex.reason.clone())
+        })
+    }
+}
+
+/// Check a config against the budgets, returning `(rule, path, message)` for each one exceeded
+fn check_budgets(budgets: &Budgets, config: &Value, source: &str) -> Vec<(&'static str, String, String)> {
+    let mut exceeded = Vec::new();
+
+    if let Some(max_tasks) = budgets.max_tasks {
+        let count = config.get("tasks").and_then(Value::as_object).map_or(0, |tasks| tasks.len());
+        if count > max_tasks {
+            exceeded.push((
+                BUDGET_MAX_TASKS,
+                "tasks".to_string(),
+                format!(
+                    "{} tasks exceeds the budget of {}; move shared tasks into .moon/tasks files",
+                    count, max_tasks
+                ),
+            ));
+        }
+    }
+
+    if let Some(max_file_size) = budgets.max_file_size {
+        let size = source.len() as u64;
+        if size > max_file_size.bytes() {
+            exceeded.push((
+                BUDGET_MAX_FILE_SIZE,
+                "<root>".to_string(),
+                format!("file is {}, over the budget of {}", max_file_size.format(size), max_file_size),
+            ));
+        }
+    }
+
+    if let Some(max_depth) = budgets.max_depth {
+        let depth = nesting_depth(config);
+        if depth > max_depth {
+            exceeded.push((
+                BUDGET_MAX_DEPTH,
+                "<root>".to_string(),
+                format!("nesting depth {} exceeds the budget of {}", depth, max_depth),
+            ));
+        }
+    }
+
+    exceeded
+}
+
+/// Nesting depth of objects and lists below the top level; flat configs are depth 0
+pub fn nesting_depth(value: &Value) -> usize {
+    container_depth(value).saturating_sub(1)
+}
+
+fn container_depth(value: &Value) -> usize {
+    match value {
+        Value::Object(map) => 1 + map.values().map(container_depth).max().unwrap_or(0),
+        Value::Array(items) => 1 + items.iter().map(container_depth).max().unwrap_or(0),
+        _ => 0,
+    }
+}
+
+/// Guess the Moon config type from its file name
+pub fn infer_config_type(path: &Path) -> Option<MoonConfig> {
+    let stem = path.file_stem()?.to_str()?;
+    match stem {
+        "moon" => Some(MoonConfig::Project),
+        "workspace" => Some(MoonConfig::Workspace),
+        "toolchain" => Some(MoonConfig::Toolchain),
+        "template" => Some(MoonConfig::Template),
+        "tasks" => Some(MoonConfig::Task),
+        _ if path.parent().is_some_and(|p| p.ends_with(".moon/tasks")) => Some(MoonConfig::Task),
+        _ => None,
+    }
+}
+
+fn rule_applies_to(rule: &PolicyRule, config_type: Option<MoonConfig>) -> bool {
+    if rule.applies_to.is_empty() {
+        return true;
+    }
+    match config_type {
+        Some(config_type) => rule
+            .applies_to
+            .iter()
+            .any(|name| name.eq_ignore_ascii_case(&config_type.to_string())),
+        None => false,
+    }
+}
+
+/// Run a rule's checks against one selected value
+fn check_rule(rule: &PolicyRule, value: &Value) -> Vec<String> {
+    let mut messages = Vec::new();
+    let describe = |fallback: String| rule.description.clone().unwrap_or(fallback);
+
+    if let Some(required) = &rule.require
+        && select_values(value, required).iter().all(|(_, v)| v.is_null())
+    {
+        messages.push(describe(format!("`{}` must be set", required)));
+    }
+
+    if let Some(forbidden) = &rule.forbid
+        && select_values(value, forbidden).iter().any(|(_, v)| !v.is_null())
+    {
+        messages.push(describe(format!("`{}` is not allowed", forbidden)));
+    }
+
+    if let Some(allowed) = &rule.allowed
+        && !value.is_null()
+        && !allowed.contains(value)
+    {
+        messages.push(describe(format!(
+            "value {} is not one of the allowed values {}",
+            value,
+            Value::Array(allowed.clone())
+        )));
+    }
+
+    if let Some(denied) = &rule.denied
+        && denied.contains(value)
+    {
+        messages.push(describe(format!("value {} is denied", value)));
+    }
+
+    if let (Some(pattern), Some(text)) = (&rule.pattern, value.as_str()) {
+        // Patterns are validated when the policy is loaded
+        if Regex::new(pattern).is_ok_and(|re| !re.is_match(text)) {
+            messages.push(describe(format!("value \"{}\" does not match /{}/", text, pattern)));
+        }
+    }
+
+    messages
+}
+
+/// Select all values matching a dot-separated selector, returning `(path, value)` pairs
+///
+/// Selectors are [`ConfigPath`](crate::config_path::ConfigPath) patterns.
+pub fn select_values<'a>(root: &'a Value, selector: &str) -> Vec<(String, &'a Value)> {
+    crate::config_path::ConfigPath::new(selector)
+        .select(root)
+        .into_iter()
+        .map(|(path, value)| (path.to_string(), value))
+        .collect()
+}
+
+/// Find `spklr:allow(rule-id) reason` annotations in `#` or `//` comments
+///
+/// Returns the reason for each exempt rule, and the rules annotated without a reason.
+fn parse_exemption_annotations(source: &str) -> (BTreeMap<String, String>, Vec<String>) {
+    static ALLOW: OnceLock<Regex> = OnceLock::new();
+    let allow = ALLOW.get_or_init(|| {
+        Regex::new(r"(?:#|//)\s*spklr:allow\((?P<rules>[^)]+)\)\s*(?P<reason>.*)$").unwrap()
+    });
+
+    let mut annotations = BTreeMap::new();
+    let mut unexplained = Vec::new();
+    for line in source.lines() {
+        if let Some(caps) = allow.captures(line) {
+            let reason = caps["reason"].trim();
+            for rule in caps["rules"].split(',') {
+                let rule = rule.trim().to_string();
+                if reason.is_empty() {
+                    unexplained.push(rule);
+                } else {
+                    annotations.insert(rule, reason.to_string());
+                }
+            }
+        }
+    }
+    (annotations, unexplained)
+}
@@ -0,0 +1,30 @@
+//! Prelude Module for Space Pklr
+//!
+//! The types and functions most library users need, in one import:
+//!
+//! ```
+//! use space_pklr::prelude::*;
+//! use serde_json::json;
+//!
+//! # fn main() -> Result<(), CliError> {
+//! let schema = json!({
+//!     "title": "Deploy",
+//!     "type": "object",
+//!     "properties": { "region": { "type": "string" } }
+//! });
+//! let module: PklModule = SchemaGenerator::new(GeneratorConfig::default()).generate_from_json_value(&schema, "Deploy")?;
+//! assert!(module.render().contains("region: String?"));
+//!
+//! let converted = convert_str("language: rust\n", SchemaFormat::Yaml, SchemaFormat::Pkl, MoonConfig::Project)?;
+//! assert_eq!(converted.output, "language = \"rust\"\n");
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Everything here is also available from its own module; the prelude only re-exports.
+
+pub use crate::config_processor::{load_config_value, parse_config_str, render_config_value, write_config_value};
+pub use crate::convert::{ConfigConverter, ConvertOutput, convert_str};
+pub use crate::pkl_schema::{GeneratorConfig, PklClass, PklModule, PklProperty, PklType, PklTypeAlias, SchemaGenerator};
+pub use crate::schema_diff::{Change, ChangeKind, classify_change};
+pub use crate::types::{CliError, LoadedConfig, MoonConfig, SchemaFormat};
@@ -0,0 +1,31 @@
+//! Read-Only Module for Space Pklr
+//!
+//! `--read-only` guarantees a run touches neither the disk nor the network, for hermetic
+//! sandboxes like Bazel actions. Every write path checks the gate before acting, and so
+//! does creating an HTTP client ([`crate::network::RetryPolicy::client`]); results come
+//! back only through stdout and the exit code.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::types::CliError;
+
+static READ_ONLY: AtomicBool = AtomicBool::new(false);
+
+/// Forbid disk writes and network access for the rest of the run
+pub fn enable() {
+    READ_ONLY.store(true, Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+    READ_ONLY.load(Ordering::Relaxed)
+}
+
+/// Fail with a read-only diagnostic if `action` isn't allowed
+pub fn ensure_allowed(action: impl Into<String>) -> Result<(), CliError> {
+    if is_enabled() {
+        return Err(CliError::ReadOnly {
+            action: action.into(),
+        });
+    }
+    Ok(())
+}
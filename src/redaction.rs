@@ -0,0 +1,119 @@
+//! Redaction Module for Space Pklr
+//!
+//! Internal config types can carry defaults that shouldn't be published, such as
+//! hostnames of private registries. `[redaction]` in `spklr.toml` lists property-path
+//! globs whose defaults and examples are replaced with a placeholder in generated JSON
+//! Schemas and docs:
+//!
+//! ```toml
+//! [redaction]
+//! paths = ["RegistryConfig.host", "**.token", "InternalConfig.*.url"]
+//! placeholder = "<internal>"
+//! ```
+//!
+//! Paths are `Type.property` (nested inline objects add segments), matched as
+//! [`ConfigPath`] patterns: `*` matches one segment and `**` any number of segments.
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::config_path::ConfigPath;
+#[cfg(feature = "docgen")]
+use crate::docgen::DocEntry;
+use crate::types::CliError;
+
+/// Placeholder used when `[redaction]` doesn't set one
+pub const DEFAULT_PLACEHOLDER: &str = "<redacted>";
+
+/// `[redaction]` settings
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct RedactionConfig {
+    /// Property-path globs to redact
+    pub paths: Vec<String>,
+    pub placeholder: Option<String>,
+}
+
+impl RedactionConfig {
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+
+    pub fn placeholder(&self) -> &str {
+        self.placeholder.as_deref().unwrap_or(DEFAULT_PLACEHOLDER)
+    }
+
+    /// Whether a property path matches any redaction glob
+    pub fn matches(&self, path: &str) -> bool {
+        let path = ConfigPath::from_segments(path.split('.'));
+        self.paths.iter().any(|glob| ConfigPath::new(glob).matches(&path))
+    }
+
+    /// Redact defaults and examples of matching properties in a JSON Schema value
+    ///
+    /// Returns the number of properties redacted.
+    pub fn redact_schema(&self, schema: &mut Value) -> usize {
+        if self.is_empty() {
+            return 0;
+        }
+        let mut redacted = 0;
+        if let Some(title) = schema.get("title").and_then(Value::as_str).map(str::to_string) {
+            redacted += self.redact_properties(&title, schema);
+        }
+        if let Some(Value::Object(definitions)) = schema.get_mut("definitions") {
+            for (name, definition) in definitions.iter_mut() {
+                redacted += self.redact_properties(name, definition);
+            }
+        }
+        redacted
+    }
+
+    fn redact_properties(&self, path: &str, schema: &mut Value) -> usize {
+        let Some(Value::Object(properties)) = schema.get_mut("properties") else {
+            return 0;
+        };
+        let mut redacted = 0;
+        for (property, property_schema) in properties.iter_mut() {
+            let property_path = format!("{}.{}", path, property);
+            if self.matches(&property_path) {
+                if let Value::Object(property_schema) = property_schema {
+                    if property_schema.contains_key("default") {
+                        property_schema.insert("default".to_string(), Value::from(self.placeholder()));
+                    }
+                    if property_schema.contains_key("examples") {
+                        property_schema.insert("examples".to_string(), Value::from(vec![self.placeholder()]));
+                    }
+                }
+                redacted += 1;
+            }
+            redacted += self.redact_properties(&property_path, property_schema);
+        }
+        redacted
+    }
+
+    /// Redact generated JSON Schema content, leaving other content untouched
+    pub fn redact_json_schema(&self, content: &str) -> Result<String, CliError> {
+        if self.is_empty() {
+            return Ok(content.to_string());
+        }
+        let mut schema: Value = serde_json::from_str(content).map_err(|e| CliError::ValidationError {
+            source: Box::new(e),
+        })?;
+        if self.redact_schema(&mut schema) == 0 {
+            return Ok(content.to_string());
+        }
+        serde_json::to_string_pretty(&schema).map_err(|e| CliError::ValidationError {
+            source: Box::new(e),
+        })
+    }
+
+    /// Replace the examples of matching documentation entries
+    #[cfg(feature = "docgen")]
+    pub fn redact_entries(&self, entries: &mut [DocEntry]) {
+        for entry in entries {
+            if !entry.examples.is_empty() && self.matches(&entry.path) {
+                entry.examples = vec![Value::from(self.placeholder())];
+            }
+        }
+    }
+}
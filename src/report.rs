@@ -0,0 +1,249 @@
+//! Report Module for Space Pklr
+//!
+//! The structured result of one spklr invocation, written as JSON with the global
+//! `--report <path>` flag so CI tooling can consume results without scraping output,
+//! or rendered as Markdown with `--summary <path>` for job summaries and PR comments.
+//! Commands record what they did (conversions, findings, generated files) through a
+//! process-wide collector, like timings.
+
+use serde::Serialize;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::ci::annotations::Finding;
+use crate::timings::TimingsReport;
+use crate::types::CliError;
+
+/// A file converted by `spklr convert`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConvertedFile {
+    pub input: PathBuf,
+    /// `None` when the result went to stdout
+    pub output: Option<PathBuf>,
+    pub from: String,
+    pub to: String,
+}
+
+/// How a generated file compares to what was on disk before
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FileChange {
+    Added,
+    Modified,
+    Unchanged,
+}
+
+/// A file written by `spklr generate`, with a line-level diff summary
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GeneratedFile {
+    pub path: PathBuf,
+    pub change: FileChange,
+    pub lines_added: usize,
+    pub lines_removed: usize,
+}
+
+impl GeneratedFile {
+    /// Compare new contents against the previous file, if there was one
+    pub fn compare(path: impl Into<PathBuf>, previous: Option<&str>, contents: &str) -> Self {
+        let (change, lines_added, lines_removed) = match previous {
+            None => (FileChange::Added, contents.lines().count(), 0),
+            Some(previous) if previous == contents => (FileChange::Unchanged, 0, 0),
+            Some(previous) => {
+                let (added, removed) = line_delta(previous, contents);
+                (FileChange::Modified, added, removed)
+            }
+        };
+        Self {
+            path: path.into(),
+            change,
+            lines_added,
+            lines_removed,
+        }
+    }
+}
+
+/// Lines added and removed, ignoring moves
+fn line_delta(previous: &str, contents: &str) -> (usize, usize) {
+    let mut counts: std::collections::HashMap<&str, isize> = std::collections::HashMap::new();
+    for line in contents.lines() {
+        *counts.entry(line).or_default() += 1;
+    }
+    for line in previous.lines() {
+        *counts.entry(line).or_default() -= 1;
+    }
+    counts.values().fold((0, 0), |(added, removed), &n| {
+        if n > 0 {
+            (added + n as usize, removed)
+        } else {
+            (added, removed + n.unsigned_abs())
+        }
+    })
+}
+
+#[derive(Default)]
+struct RecordedDetails {
+    conversions: Vec<ConvertedFile>,
+    findings: Vec<Finding>,
+    generated: Vec<GeneratedFile>,
+}
+
+static RECORDED: Mutex<RecordedDetails> = Mutex::new(RecordedDetails {
+    conversions: Vec::new(),
+    findings: Vec::new(),
+    generated: Vec::new(),
+});
+
+/// Record a finished conversion
+pub fn record_conversion(conversion: ConvertedFile) {
+    if let Ok(mut recorded) = RECORDED.lock() {
+        recorded.conversions.push(conversion);
+    }
+}
+
+/// Record validation, policy or drift findings
+pub fn record_findings(findings: &[Finding]) {
+    if let Ok(mut recorded) = RECORDED.lock() {
+        recorded.findings.extend_from_slice(findings);
+    }
+}
+
+/// Record a generated file
+pub fn record_generated(file: GeneratedFile) {
+    if let Ok(mut recorded) = RECORDED.lock() {
+        recorded.generated.push(file);
+    }
+}
+
+/// Machine-readable summary of a run
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunReport {
+    pub spklr_version: &'static str,
+    pub command: String,
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timings: Option<TimingsReport>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub conversions: Vec<ConvertedFile>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub findings: Vec<Finding>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub generated: Vec<GeneratedFile>,
+}
+
+impl RunReport {
+    pub fn new(command: impl Into<String>) -> Self {
+        Self {
+            spklr_version: env!("CARGO_PKG_VERSION"),
+            command: command.into(),
+            success: true,
+            error: None,
+            timings: None,
+            conversions: Vec::new(),
+            findings: Vec::new(),
+            generated: Vec::new(),
+        }
+    }
+
+    /// Take everything commands recorded during the run
+    pub fn collect_details(&mut self) {
+        if let Ok(mut recorded) = RECORDED.lock() {
+            self.conversions.append(&mut recorded.conversions);
+            self.findings.append(&mut recorded.findings);
+            self.generated.append(&mut recorded.generated);
+        }
+    }
+
+    /// Write the report as pretty JSON
+    pub fn write(&self, path: &Path) -> Result<(), CliError> {
+        // Read-only runs report through stdout instead
+        if crate::read_only::is_enabled() {
+            let json = serde_json::to_string_pretty(self)
+                .map_err(|e| CliError::Generic(format!("Failed to serialize run report: {}", e)))?;
+            println!("{}", json);
+            return Ok(());
+        }
+
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| CliError::Generic(format!("Failed to serialize run report: {}", e)))?;
+        crate::atomic_write::write_atomic_sync(path, json)
+    }
+
+    /// Render the report as a Markdown job summary
+    pub fn render_summary(&self) -> String {
+        let status = if self.success { "✅ succeeded" } else { "❌ failed" };
+        let mut out = format!("## spklr {} {}\n\n", self.command, status);
+        if let Some(error) = &self.error {
+            let _ = writeln!(out, "> {}\n", error.replace('\n', " "));
+        }
+
+        if !self.conversions.is_empty() {
+            out.push_str("### Converted files\n\n| Input | Output | From | To |\n| --- | --- | --- | --- |\n");
+            for conversion in &self.conversions {
+                let output = conversion
+                    .output
+                    .as_ref()
+                    .map(|path| format!("`{}`", path.display()))
+                    .unwrap_or_else(|| "stdout".to_string());
+                let _ = writeln!(
+                    out,
+                    "| `{}` | {} | {} | {} |",
+                    conversion.input.display(),
+                    output,
+                    conversion.from,
+                    conversion.to
+                );
+            }
+            out.push('\n');
+        }
+
+        if !self.findings.is_empty() {
+            out.push_str("### Validation failures\n\n");
+            out.push_str(&crate::ci::annotations::render_findings_table(&self.findings));
+            out.push('\n');
+        }
+
+        if !self.generated.is_empty() {
+            out.push_str("### Schema changes\n\n| File | Change | Lines |\n| --- | --- | --- |\n");
+            for file in &self.generated {
+                let change = match file.change {
+                    FileChange::Added => "added",
+                    FileChange::Modified => "modified",
+                    FileChange::Unchanged => "unchanged",
+                };
+                let _ = writeln!(
+                    out,
+                    "| `{}` | {} | +{} −{} |",
+                    file.path.display(),
+                    change,
+                    file.lines_added,
+                    file.lines_removed
+                );
+            }
+            out.push('\n');
+        }
+
+        if let Some(timings) = &self.timings {
+            let _ = writeln!(out, "<sub>Finished in {:.0}ms with spklr {}</sub>", timings.total_ms, self.spklr_version);
+        } else {
+            let _ = writeln!(out, "<sub>spklr {}</sub>", self.spklr_version);
+        }
+        out
+    }
+
+    /// Write the Markdown job summary
+    pub fn write_summary(&self, path: &Path) -> Result<(), CliError> {
+        let summary = self.render_summary();
+        // Read-only runs report through stdout instead
+        if crate::read_only::is_enabled() {
+            println!("{}", summary);
+            return Ok(());
+        }
+        crate::atomic_write::write_atomic_sync(path, summary)
+    }
+}
@@ -0,0 +1,270 @@
+//! Schema Diff Module for Space Pklr
+//!
+//! Compares two sets of generated modules by structure rather than text, so a review
+//! sees "`Project.TaskConfig.cache` changed type `Boolean?` → `Boolean`" instead of a
+//! reflowed file. Modules are matched by name; within them, classes, typealiases, and
+//! properties are matched by name and compared by type, constraints, defaults, and
+//! `fixed`. Doc comments are left out.
+//!
+//! Every change is also classified for compatibility: it's breaking when a config that
+//! was valid before can fail against the new schema. That covers removed definitions,
+//! new required properties, narrowed types, added constraints, and newly `fixed`
+//! properties. Widened types, loosened constraints, and changed defaults are not.
+//!
+//! [`classify_change`] is the same analysis for one module, for registries and release
+//! automation that hold modules in memory:
+//!
+//! ```
+//! use space_pklr::pkl_schema::{PklModule, PklProperty, PklType};
+//! use space_pklr::schema_diff::classify_change;
+//!
+//! let old = PklModule {
+//!     name: "Project".to_string(),
+//!     properties: vec![PklProperty::new("owner", None, PklType::String.nullable())],
+//!     ..Default::default()
+//! };
+//! let mut new = old.clone();
+//! new.properties[0].ty = PklType::String;
+//!
+//! let changes = classify_change(&old, &new);
+//! assert_eq!(changes[0].path, "Project.owner");
+//! assert!(changes[0].breaking);
+//! ```
+//!
+//! Sets are loaded from files or directories of JSON Schema documents (`*.json`) or
+//! saved modules (`*.ir.json`, see [`crate::pkl_schema::ir`]).
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::path::Path;
+
+use crate::pkl_schema::{PklModule, PklProperty, PklType, SchemaGenerator, pkl_literal};
+use crate::types::CliError;
+
+/// What happened to a definition
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// One difference between two schema sets
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SchemaChange {
+    pub kind: ChangeKind,
+    /// `Module`, `Module.Class`, or `Module.Class.property`
+    pub path: String,
+    /// What changed, for [`ChangeKind::Changed`]
+    pub detail: Option<String>,
+    /// Whether a config valid against the old schema can fail against the new one
+    pub breaking: bool,
+}
+
+impl Display for SchemaChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let sign = match self.kind {
+            ChangeKind::Added => '+',
+            ChangeKind::Removed => '-',
+            ChangeKind::Changed => '~',
+        };
+        match &self.detail {
+            Some(detail) => write!(f, "{} {}: {}", sign, self.path, detail),
+            None => write!(f, "{} {}", sign, self.path),
+        }
+    }
+}
+
+/// A classified schema change, as [`classify_change`] returns it
+pub type Change = SchemaChange;
+
+/// Differences from `before` to `after`, sorted by path
+pub fn diff_modules(before: &[PklModule], after: &[PklModule]) -> Vec<SchemaChange> {
+    let mut changes = Vec::new();
+    let before: BTreeMap<&str, &PklModule> = before.iter().map(|module| (module.name.as_str(), module)).collect();
+    let after: BTreeMap<&str, &PklModule> = after.iter().map(|module| (module.name.as_str(), module)).collect();
+    compare(&before, &after, "", &mut changes, |_| false, diff_module);
+    sort(changes)
+}
+
+/// Classified differences from one version of a module to the next, sorted by path
+///
+/// The modules are compared even if they're named differently; paths start with the
+/// new module's name.
+pub fn classify_change(old: &PklModule, new: &PklModule) -> Vec<Change> {
+    let mut changes = Vec::new();
+    diff_module(old, new, &new.name, &mut changes);
+    sort(changes)
+}
+
+fn diff_module(old: &PklModule, new: &PklModule, path: &str, changes: &mut Vec<SchemaChange>) {
+    diff_properties(&old.properties, &new.properties, path, changes);
+
+    let old_classes = by_name(&old.classes, |class| &class.name);
+    let new_classes = by_name(&new.classes, |class| &class.name);
+    compare(&old_classes, &new_classes, path, changes, |_| false, |old, new, path, changes| {
+        diff_properties(&old.properties, &new.properties, path, changes);
+    });
+
+    let old_aliases = by_name(&old.typealiases, |alias| &alias.name);
+    let new_aliases = by_name(&new.typealiases, |alias| &alias.name);
+    compare(&old_aliases, &new_aliases, path, changes, |_| false, |old, new, path, changes| {
+        // Docs of a documented union's values aren't part of its type
+        if old.ty.render() != new.ty.render() {
+            let detail = format!("type {} → {}", old.ty.render(), new.ty.render());
+            changes.push(changed(path, detail, !accepts(&new.ty, &old.ty)));
+        }
+    });
+}
+
+fn sort(mut changes: Vec<SchemaChange>) -> Vec<SchemaChange> {
+    changes.sort_by(|a, b| a.path.cmp(&b.path).then(a.kind.cmp(&b.kind)));
+    changes
+}
+
+fn diff_properties(before: &[PklProperty], after: &[PklProperty], parent: &str, changes: &mut Vec<SchemaChange>) {
+    let before = by_name(before, |property| &property.name);
+    let after = by_name(after, |property| &property.name);
+    // A new property breaks existing configs only if they now have to set it
+    let required = |property: &PklProperty| !matches!(property.ty, PklType::Nullable(_) | PklType::Any) && property.default.is_none();
+    compare(&before, &after, parent, changes, required, |old, new, path, changes| {
+        if old.ty.render() != new.ty.render() {
+            let detail = format!("type {} → {}", old.ty.render(), new.ty.render());
+            changes.push(changed(path, detail, !accepts(&new.ty, &old.ty)));
+        }
+        if old.constraints != new.constraints {
+            let show = |property: &PklProperty| {
+                let expressions: Vec<&str> = property.constraints.iter().map(|c| c.expression.as_str()).collect();
+                if expressions.is_empty() { "none".to_string() } else { expressions.join(", ") }
+            };
+            // Any constraint that wasn't there before can reject a value that passed
+            let tightened = new.constraints.iter().any(|constraint| !old.constraints.contains(constraint));
+            changes.push(changed(path, format!("constraints {} → {}", show(old), show(new)), tightened));
+        }
+        if old.default != new.default {
+            let show = |property: &PklProperty| {
+                property
+                    .default
+                    .as_ref()
+                    .map_or_else(|| "none".to_string(), |default| pkl_literal(default, &property.ty))
+            };
+            changes.push(changed(path, format!("default {} → {}", show(old), show(new)), false));
+        }
+        if old.fixed != new.fixed {
+            let detail = if new.fixed { "now fixed" } else { "no longer fixed" };
+            changes.push(changed(path, detail.to_string(), new.fixed));
+        }
+    });
+}
+
+fn by_name<T>(items: &[T], name: impl Fn(&T) -> &String) -> BTreeMap<&str, &T> {
+    items.iter().map(|item| (name(item).as_str(), item)).collect()
+}
+
+/// Report additions and removals between two maps, and compare the items in both
+///
+/// Removals are always breaking; additions are when `breaking_if_added` says so.
+fn compare<T>(
+    before: &BTreeMap<&str, &T>,
+    after: &BTreeMap<&str, &T>,
+    parent: &str,
+    changes: &mut Vec<SchemaChange>,
+    breaking_if_added: impl Fn(&T) -> bool,
+    mut both: impl FnMut(&T, &T, &str, &mut Vec<SchemaChange>),
+) {
+    let path = |name: &str| if parent.is_empty() { name.to_string() } else { format!("{}.{}", parent, name) };
+    for (name, old) in before {
+        match after.get(name) {
+            Some(new) => both(old, new, &path(name), changes),
+            None => changes.push(SchemaChange {
+                kind: ChangeKind::Removed,
+                path: path(name),
+                detail: None,
+                breaking: true,
+            }),
+        }
+    }
+    for (name, new) in after.iter().filter(|(name, _)| !before.contains_key(*name)) {
+        changes.push(SchemaChange {
+            kind: ChangeKind::Added,
+            path: path(name),
+            detail: None,
+            breaking: breaking_if_added(new),
+        });
+    }
+}
+
+fn changed(path: &str, detail: String, breaking: bool) -> SchemaChange {
+    SchemaChange {
+        kind: ChangeKind::Changed,
+        path: path.to_string(),
+        detail: Some(detail),
+        breaking,
+    }
+}
+
+/// Whether every value of type `old` is also a value of type `new`
+///
+/// Named types are compared by name; changes inside them are reported on their own.
+pub fn accepts(new: &PklType, old: &PklType) -> bool {
+    match (new, old) {
+        _ if new == old => true,
+        (PklType::Any, _) => true,
+        (PklType::Nullable(new), PklType::Nullable(old)) => accepts(new, old),
+        (PklType::Nullable(new), old) => accepts(new, old),
+        (_, PklType::Nullable(_)) => false,
+        (PklType::Variants(_), _) => new.union_members().is_some_and(|members| accepts(&PklType::Union(members), old)),
+        (_, PklType::Variants(_)) => old.union_members().is_some_and(|members| accepts(new, &PklType::Union(members))),
+        (_, PklType::Union(members)) => members.iter().all(|member| accepts(new, member)),
+        (PklType::Union(members), old) => members.iter().any(|member| accepts(member, old)),
+        (PklType::Number, PklType::Int) => true,
+        (PklType::String, PklType::StringLiteral(_)) => true,
+        (PklType::Listing(new), PklType::Listing(old)) => accepts(new, old),
+        (PklType::Mapping(new_key, new_value), PklType::Mapping(old_key, old_value)) => {
+            accepts(new_key, old_key) && accepts(new_value, old_value)
+        }
+        _ => false,
+    }
+}
+
+/// Load the modules in a file, or in every `*.json` file of a directory
+pub fn load_modules(path: &Path) -> Result<Vec<PklModule>, CliError> {
+    if !path.is_dir() {
+        return load_file(path);
+    }
+    let entries = std::fs::read_dir(path).map_err(|e| CliError::IoError {
+        context: format!("Reading schema directory: {}", path.display()),
+        source: e,
+    })?;
+    let mut files: Vec<_> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|file| file.extension().is_some_and(|extension| extension == "json"))
+        .collect();
+    files.sort();
+
+    let mut modules = Vec::new();
+    for file in files {
+        modules.extend(load_file(&file)?);
+    }
+    Ok(modules)
+}
+
+fn load_file(path: &Path) -> Result<Vec<PklModule>, CliError> {
+    let name = path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+    if name.ends_with(".ir.json") {
+        let content = std::fs::read_to_string(path).map_err(|e| CliError::IoError {
+            context: format!("Reading saved modules: {}", path.display()),
+            source: e,
+        })?;
+        crate::pkl_schema::ir::from_str(&content)
+    } else if name.ends_with(".json") {
+        Ok(vec![SchemaGenerator::from_json_schema(path)?])
+    } else {
+        Err(CliError::Generic(format!(
+            "Can't compare {}: use JSON Schema documents or modules saved with `--format ir`",
+            path.display()
+        )))
+    }
+}
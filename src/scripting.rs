@@ -0,0 +1,86 @@
+//! Scripting Module for Space Pklr
+//!
+//! Runs user-supplied [Rhai](https://rhai.rs) scripts over a config between parse and
+//! render during conversion. The parsed config is available to the script as the
+//! `config` object map, and whatever `config` holds when the script ends is the result.
+//! The script's own value is ignored: Rhai statements keep their value even after a `;`,
+//! so a script ending in `config.remove("docker");` would otherwise evaluate to the
+//! removed section.
+//!
+//! ```rhai
+//! // rename a key
+//! config.dependsOn = config.remove("deps");
+//! // drop a section
+//! config.remove("docker");
+//! // compute a value
+//! config.tags = (config.tags ?? []) + ["migrated"];
+//! ```
+//!
+//! To build a new config instead, define `transform`; it's called with `config` after
+//! the script's statements run, and returns the result:
+//!
+//! ```rhai
+//! fn transform(config) {
+//!     #{ language: config.language, tasks: config.tasks }
+//! }
+//! ```
+//!
+//! `print()` and `debug()` in scripts go to the tracing log. Scripting is behind the
+//! `scripting` cargo feature.
+
+use serde_json::Value;
+use std::path::Path;
+
+use crate::types::CliError;
+
+/// Run a transform script over a config value and return the transformed value
+#[cfg(feature = "scripting")]
+pub fn run_transform_script(script: &Path, config: Value) -> Result<Value, CliError> {
+    use rhai::{CallFnOptions, Dynamic, Engine, Scope};
+
+    let script_error = |message: String| CliError::ScriptError {
+        script: script.to_path_buf(),
+        message,
+    };
+
+    let mut engine = Engine::new();
+    engine.on_print(|text| tracing::info!("[script] {}", text));
+    engine.on_debug(|text, _source, pos| tracing::debug!("[script] {} @ {}", text, pos));
+
+    let ast = engine
+        .compile_file(script.to_path_buf())
+        .map_err(|e| script_error(e.to_string()))?;
+
+    let mut scope = Scope::new();
+    let input = rhai::serde::to_dynamic(&config).map_err(|e| script_error(e.to_string()))?;
+    scope.push_dynamic("config", input);
+
+    engine
+        .run_ast_with_scope(&mut scope, &ast)
+        .map_err(|e| script_error(e.to_string()))?;
+    let config = scope
+        .get_value::<Dynamic>("config")
+        .ok_or_else(|| script_error("script removed the `config` variable".to_string()))?;
+
+    let has_transform = ast
+        .iter_functions()
+        .any(|function| function.name == "transform" && function.params.len() == 1);
+    let output = if has_transform {
+        engine
+            .call_fn_with_options::<Dynamic>(CallFnOptions::new().eval_ast(false), &mut scope, &ast, "transform", (config,))
+            .map_err(|e| script_error(e.to_string()))?
+    } else {
+        config
+    };
+
+    rhai::serde::from_dynamic(&output).map_err(|e| script_error(e.to_string()))
+}
+
+/// Run a transform script over a config value and return the transformed value
+#[cfg(not(feature = "scripting"))]
+pub fn run_transform_script(script: &Path, _config: Value) -> Result<Value, CliError> {
+    Err(CliError::ScriptError {
+        script: script.to_path_buf(),
+        message: "spklr was built without the `scripting` feature".to_string(),
+    })
+}
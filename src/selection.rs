@@ -0,0 +1,277 @@
+//! Selection Module for Space Pklr
+//!
+//! `spklr generate schema --types ProjectConfig,TaskConfig` keeps only the named types
+//! and the types they reference, directly or transitively. Generated files that define
+//! none of the named types are skipped entirely.
+//!
+//! - JSON Schema: unreferenced `definitions` are dropped; the root schema is kept only
+//!   if its `title` was selected
+//! - TypeScript: unreferenced top-level declarations (with their doc comments) are dropped
+//! - Pkl modules: unreferenced classes and typealiases are dropped; module properties are
+//!   kept only if the module itself was selected
+
+use serde_json::{Map, Value};
+use std::collections::{BTreeSet, VecDeque};
+
+use crate::pkl_schema::{PklModule, PklType};
+use crate::types::CliError;
+
+const DEFINITIONS_PREFIX: &str = "#/definitions/";
+
+/// Generated content narrowed down to the selected types
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Selection {
+    pub content: String,
+    /// Requested types this content defines
+    pub matched: Vec<String>,
+}
+
+/// Parse a `--types` list entry, trimming whitespace
+pub fn parse_type_name(arg: &str) -> Result<String, String> {
+    let name = arg.trim();
+    if name.is_empty() {
+        return Err("type names can't be empty".to_string());
+    }
+    Ok(name.to_string())
+}
+
+/// Narrow generated `content` in `format` (`json-schema`/`json` or `typescript`/`ts`)
+///
+/// Returns `None` if the content defines none of `types`.
+pub fn select(content: &str, format: &str, types: &[String]) -> Result<Option<Selection>, CliError> {
+    match format {
+        "json-schema" | "json" => select_json_schema(content, types),
+        "typescript" | "ts" => Ok(select_typescript(content, types)),
+        _ => Err(CliError::UnsupportedFormat {
+            format: format.to_string(),
+            available: vec!["json-schema", "typescript"],
+        }),
+    }
+}
+
+/// Keep the selected JSON Schema definitions (and root) and what they reference
+pub fn select_json_schema(content: &str, types: &[String]) -> Result<Option<Selection>, CliError> {
+    let schema: Value = serde_json::from_str(content).map_err(|e| CliError::ValidationError {
+        source: Box::new(e),
+    })?;
+    let Value::Object(root) = &schema else {
+        return Ok(None);
+    };
+    let empty = Map::new();
+    let definitions = root.get("definitions").and_then(Value::as_object).unwrap_or(&empty);
+    let title = root.get("title").and_then(Value::as_str);
+
+    let root_selected = title.is_some_and(|title| types.iter().any(|t| t == title));
+    let matched: Vec<String> = types
+        .iter()
+        .filter(|t| definitions.contains_key(t.as_str()) || Some(t.as_str()) == title)
+        .cloned()
+        .collect();
+    if matched.is_empty() {
+        return Ok(None);
+    }
+
+    let mut queue: VecDeque<String> = matched.iter().filter(|t| definitions.contains_key(t.as_str())).cloned().collect();
+    if root_selected {
+        let root_only: Map<String, Value> = root
+            .iter()
+            .filter(|(key, _)| key.as_str() != "definitions")
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+        queue.extend(json_references(&Value::Object(root_only)));
+    }
+    let reachable = reachable(queue, |name| {
+        definitions.get(name).map(json_references).unwrap_or_default()
+    });
+
+    let mut selected = if root_selected {
+        root.clone()
+    } else {
+        root.iter()
+            .filter(|(key, _)| key.as_str() == "$schema")
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect()
+    };
+    let kept: Map<String, Value> = definitions
+        .iter()
+        .filter(|(name, _)| reachable.contains(name.as_str()))
+        .map(|(name, definition)| (name.clone(), definition.clone()))
+        .collect();
+    selected.remove("definitions");
+    if !kept.is_empty() {
+        selected.insert("definitions".to_string(), Value::Object(kept));
+    }
+
+    let content = serde_json::to_string_pretty(&Value::Object(selected))
+        .map_err(|e| CliError::Generic(format!("Failed to serialize selected schema: {}", e)))?;
+    Ok(Some(Selection { content, matched }))
+}
+
+fn json_references(value: &Value) -> Vec<String> {
+    match value {
+        Value::Object(object) => object
+            .iter()
+            .flat_map(|(key, value)| match (key.as_str(), value) {
+                ("$ref", Value::String(reference)) => reference
+                    .strip_prefix(DEFINITIONS_PREFIX)
+                    .map(|name| vec![name.to_string()])
+                    .unwrap_or_default(),
+                _ => json_references(value),
+            })
+            .collect(),
+        Value::Array(items) => items.iter().flat_map(json_references).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// A top-level TypeScript declaration and the doc comment above it
+struct Declaration<'a> {
+    name: Option<&'a str>,
+    lines: Vec<&'a str>,
+}
+
+/// Keep the selected TypeScript declarations and the declarations they reference
+pub fn select_typescript(content: &str, types: &[String]) -> Option<Selection> {
+    let mut preamble = Vec::new();
+    let mut declarations: Vec<Declaration> = Vec::new();
+    // Inside a top-level doc comment, or just past one that the next `export` belongs to
+    let mut in_doc = false;
+    let mut documented = false;
+    for line in content.lines() {
+        let export = line.strip_prefix("export ");
+        if line.starts_with("/**") || (export.is_some() && !in_doc && !documented) {
+            declarations.push(Declaration { name: None, lines: Vec::new() });
+        }
+        if line.starts_with("/**") {
+            in_doc = true;
+        }
+        if in_doc && line.contains("*/") {
+            in_doc = false;
+            documented = true;
+        }
+        if let Some(rest) = export
+            && let Some(declaration) = declarations.last_mut()
+        {
+            declaration.name = declared_name(rest);
+            documented = false;
+        }
+        match declarations.last_mut() {
+            Some(declaration) => declaration.lines.push(line),
+            None => preamble.push(line),
+        }
+    }
+
+    let names: BTreeSet<&str> = declarations.iter().filter_map(|d| d.name).collect();
+    let matched: Vec<String> = types.iter().filter(|t| names.contains(t.as_str())).cloned().collect();
+    if matched.is_empty() {
+        return None;
+    }
+    let reachable = reachable(matched.iter().cloned().collect(), |name| {
+        declarations
+            .iter()
+            .filter(|d| d.name == Some(name))
+            .flat_map(|d| d.lines.iter().flat_map(|line| identifiers(line)))
+            .filter(|identifier| *identifier != name && names.contains(identifier))
+            .map(str::to_string)
+            .collect()
+    });
+
+    let mut lines = preamble;
+    for declaration in &declarations {
+        if declaration.name.is_some_and(|name| reachable.contains(name)) {
+            lines.extend(&declaration.lines);
+        }
+    }
+    let mut content = lines.join("\n").trim_end().to_string();
+    content.push('\n');
+    Some(Selection { content, matched })
+}
+
+fn declared_name(rest: &str) -> Option<&str> {
+    let rest = rest.strip_prefix("declare ").unwrap_or(rest);
+    let (keyword, rest) = rest.split_once(' ')?;
+    if !["interface", "type", "enum", "const", "class"].contains(&keyword) {
+        return None;
+    }
+    identifiers(rest).next()
+}
+
+fn identifiers(line: &str) -> impl Iterator<Item = &str> {
+    line.split(|c: char| !c.is_alphanumeric() && c != '_' && c != '$')
+        .filter(|word| !word.is_empty())
+}
+
+/// Keep the selected classes and typealiases of a Pkl module and what they reference
+///
+/// Returns `None` if the module defines none of `types`.
+pub fn select_module(module: &PklModule, types: &[String]) -> Option<(PklModule, Vec<String>)> {
+    let module_selected = types.contains(&module.name);
+    let defined = |name: &str| {
+        module.classes.iter().any(|class| class.name == name)
+            || module.typealiases.iter().any(|typealias| typealias.name == name)
+    };
+    let matched: Vec<String> = types
+        .iter()
+        .filter(|t| **t == module.name || defined(t.as_str()))
+        .cloned()
+        .collect();
+    if matched.is_empty() {
+        return None;
+    }
+
+    let mut queue: VecDeque<String> = matched.iter().filter(|t| defined(t.as_str())).cloned().collect();
+    if module_selected {
+        queue.extend(module.properties.iter().flat_map(|property| named_types(&property.ty)));
+    }
+    let reachable = reachable(queue, |name| {
+        let class_refs = module
+            .classes
+            .iter()
+            .filter(|class| class.name == name)
+            .flat_map(|class| {
+                let properties = class.properties.iter().flat_map(|property| named_types(&property.ty));
+                let subclasses = module.subclasses(&class.name).map(|subclass| subclass.name.clone());
+                properties.chain(class.extends.clone()).chain(subclasses)
+            });
+        let alias_refs = module
+            .typealiases
+            .iter()
+            .filter(|typealias| typealias.name == name)
+            .flat_map(|typealias| named_types(&typealias.ty));
+        class_refs.chain(alias_refs).collect()
+    });
+
+    let selected = PklModule {
+        properties: if module_selected { module.properties.clone() } else { Vec::new() },
+        classes: module.classes.iter().filter(|class| reachable.contains(&class.name)).cloned().collect(),
+        typealiases: module
+            .typealiases
+            .iter()
+            .filter(|typealias| reachable.contains(&typealias.name))
+            .cloned()
+            .collect(),
+        ..module.clone()
+    };
+    Some((selected, matched))
+}
+
+fn named_types(ty: &PklType) -> Vec<String> {
+    match ty {
+        PklType::Named(name) => vec![name.clone()],
+        PklType::Listing(inner) | PklType::Nullable(inner) => named_types(inner),
+        PklType::Mapping(key, value) => named_types(key).into_iter().chain(named_types(value)).collect(),
+        PklType::Union(members) => members.iter().flat_map(named_types).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Every name reachable from `queue` through `references`
+fn reachable(mut queue: VecDeque<String>, references: impl Fn(&str) -> Vec<String>) -> BTreeSet<String> {
+    let mut seen = BTreeSet::new();
+    while let Some(name) = queue.pop_front() {
+        if seen.insert(name.clone()) {
+            queue.extend(references(&name));
+        }
+    }
+    seen
+}
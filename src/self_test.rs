@@ -0,0 +1,149 @@
+//! Self Test Module for Space Pklr
+//!
+//! Golden fixtures embedded in the binary, and the renderings they were captured from.
+//! `spklr self test` renders every fixture again and compares it byte for byte with the
+//! golden copy, so a platform that renders differently (CRLF line endings, a locale
+//! that changes number formatting, a mangled encoding) shows up before anyone trusts a
+//! migration run on it.
+//!
+//! The fixtures live in `src/self_test/`: a small JSON Schema generated into a Pkl
+//! module, and a small project config converted to Pkl, JSON, and TOML. When a
+//! rendering change is intended, regenerate the golden files in the same commit.
+
+use serde_json::Value;
+
+use crate::config_processor::{parse_config_str, render_config_value};
+use crate::pkl_schema::SchemaGenerator;
+use crate::types::{CliError, SchemaFormat};
+
+const SCHEMA: &str = include_str!("self_test/schema.json");
+const PROJECT: &str = include_str!("self_test/project.yml");
+
+/// An embedded golden fixture
+#[derive(Debug, Clone, Copy)]
+pub struct Fixture {
+    pub name: &'static str,
+    /// The golden rendering
+    pub expected: &'static str,
+    render: fn() -> Result<String, CliError>,
+}
+
+impl Fixture {
+    /// Render the fixture again in this environment
+    pub fn render(&self) -> Result<String, CliError> {
+        (self.render)()
+    }
+
+    /// Render the fixture and compare it with the golden copy
+    pub fn check(&self) -> FixtureOutcome {
+        let result = match self.render() {
+            Ok(actual) => match first_difference(self.expected, &actual) {
+                None => Ok(()),
+                Some(difference) => Err(difference.to_string()),
+            },
+            Err(e) => Err(e.to_string()),
+        };
+        FixtureOutcome {
+            name: self.name,
+            result,
+        }
+    }
+}
+
+/// Result of checking one fixture
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FixtureOutcome {
+    pub name: &'static str,
+    /// Why the rendering doesn't match, if it doesn't
+    pub result: Result<(), String>,
+}
+
+impl FixtureOutcome {
+    pub fn passed(&self) -> bool {
+        self.result.is_ok()
+    }
+}
+
+/// Every embedded fixture
+pub fn fixtures() -> Vec<Fixture> {
+    vec![
+        Fixture {
+            name: "schema.pkl",
+            expected: include_str!("self_test/schema.pkl"),
+            render: render_schema,
+        },
+        Fixture {
+            name: "project.pkl",
+            expected: include_str!("self_test/project.pkl"),
+            render: || render_project(SchemaFormat::Pkl),
+        },
+        Fixture {
+            name: "project.json",
+            expected: include_str!("self_test/project.json"),
+            render: || render_project(SchemaFormat::Json),
+        },
+        Fixture {
+            name: "project.toml",
+            expected: include_str!("self_test/project.toml"),
+            render: || render_project(SchemaFormat::Toml),
+        },
+    ]
+}
+
+/// Check every fixture
+pub fn run() -> Vec<FixtureOutcome> {
+    fixtures().iter().map(Fixture::check).collect()
+}
+
+fn render_schema() -> Result<String, CliError> {
+    let schema: Value = serde_json::from_str(SCHEMA).map_err(|e| CliError::ValidationError { source: Box::new(e) })?;
+    Ok(SchemaGenerator::default().generate_from_json_value(&schema, "Fixture")?.render())
+}
+
+fn render_project(format: SchemaFormat) -> Result<String, CliError> {
+    let value = parse_config_str(PROJECT, &SchemaFormat::Yaml)?;
+    render_config_value(&value, &format)
+}
+
+/// Where a rendering first departs from its golden copy
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Difference {
+    /// 1-based line number
+    pub line: usize,
+    pub expected: Option<String>,
+    pub actual: Option<String>,
+}
+
+impl std::fmt::Display for Difference {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let show = |line: &Option<String>| match line {
+            Some(line) => format!("{:?}", line),
+            None => "end of output".to_string(),
+        };
+        write!(f, "line {}: expected {}, got {}", self.line, show(&self.expected), show(&self.actual))
+    }
+}
+
+/// The first line where `actual` differs from `expected`, byte for byte
+///
+/// Lines keep their terminators, so a `\r\n` where `\n` was expected is a difference.
+pub fn first_difference(expected: &str, actual: &str) -> Option<Difference> {
+    if expected == actual {
+        return None;
+    }
+    let mut expected_lines = expected.split_inclusive('\n');
+    let mut actual_lines = actual.split_inclusive('\n');
+    let mut line = 1;
+    loop {
+        match (expected_lines.next(), actual_lines.next()) {
+            (Some(e), Some(a)) if e == a => line += 1,
+            (e, a) => {
+                return Some(Difference {
+                    line,
+                    expected: e.map(str::to_string),
+                    actual: a.map(str::to_string),
+                });
+            }
+        }
+    }
+}
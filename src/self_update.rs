@@ -0,0 +1,265 @@
+//! Self Update Module for Space Pklr
+//!
+//! `spklr self-update` replaces the running binary with a GitHub release, for teammates
+//! who don't have cargo:
+//!
+//! 1. the release (the latest, or `--version`) is looked up with the GitHub API; set
+//!    `GITHUB_TOKEN` to lift its rate limit
+//! 2. the asset for this platform (`space-pklr-<os>-<arch>`, see [`release_asset_name`])
+//!    is downloaded next to the executable, and must match the `<asset>.sha256`
+//!    published with it; releases without checksums aren't installed
+//! 3. the download has to run and report the release's version
+//! 4. it's renamed over the executable, which is atomic on one filesystem. Windows
+//!    can't replace a running executable, so the old one is moved aside to
+//!    `spklr.old.exe` first and removed on the next update
+//!
+//! Downloads retry and use mirrors per the `download` network settings (see
+//! [`crate::network`]). Read-only and hermetic runs refuse to update.
+
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+use crate::compatibility::compare_versions;
+use crate::types::CliError;
+
+/// GitHub `owner/repo` releases are published to
+pub fn release_repo() -> &'static str {
+    env!("CARGO_PKG_REPOSITORY").trim_start_matches("https://github.com/").trim_end_matches('/')
+}
+
+/// Release asset built for a Rust `(OS, ARCH)` pair, as named by the release workflow
+pub fn release_asset_name(os: &str, arch: &str, musl: bool) -> Option<&'static str> {
+    match (os, arch, musl) {
+        ("linux", "x86_64", true) => Some("space-pklr-linux-musl"),
+        ("linux", "x86_64", false) => Some("space-pklr-linux-amd64"),
+        ("linux", "aarch64", false) => Some("space-pklr-linux-aarch64"),
+        ("macos", "x86_64", _) => Some("space-pklr-macos-amd64"),
+        ("macos", "aarch64", _) => Some("space-pklr-macos-aarch64"),
+        ("windows", "x86_64", _) => Some("space-pklr-windows-amd64.exe"),
+        _ => None,
+    }
+}
+
+/// A published release
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Release {
+    /// Version without the tag's `v`
+    pub version: String,
+    /// Asset names and download URLs
+    pub assets: Vec<(String, String)>,
+}
+
+impl Release {
+    /// Read a release from the GitHub API's JSON
+    pub fn from_json(release: &Value) -> Option<Self> {
+        let version = release["tag_name"].as_str()?.trim_start_matches('v').to_string();
+        let assets = release["assets"]
+            .as_array()?
+            .iter()
+            .filter_map(|asset| Some((asset["name"].as_str()?.to_string(), asset["browser_download_url"].as_str()?.to_string())))
+            .collect();
+        Some(Self { version, assets })
+    }
+
+    pub fn asset_url(&self, name: &str) -> Option<&str> {
+        self.assets.iter().find(|(asset, _)| asset == name).map(|(_, url)| url.as_str())
+    }
+}
+
+/// Whether to install `release` over the running `current` version
+///
+/// Without `force`, only newer releases are installed, unless one was asked for by version.
+pub fn should_install(current: &str, release: &str, requested: bool, force: bool) -> bool {
+    match compare_versions(release, current) {
+        std::cmp::Ordering::Equal => force,
+        std::cmp::Ordering::Greater => true,
+        std::cmp::Ordering::Less => force || requested,
+    }
+}
+
+/// What `spklr self-update` did
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UpdateOutcome {
+    UpToDate { version: String },
+    /// `--check` found a release to install
+    Available { current: String, version: String },
+    Updated { from: String, to: String, path: PathBuf },
+}
+
+/// Options for [`self_update`]
+#[derive(Debug, Clone, Default)]
+pub struct UpdateOptions {
+    /// Release to install instead of the latest
+    pub version: Option<String>,
+    /// Only report whether an update is available
+    pub check: bool,
+    /// Reinstall or downgrade
+    pub force: bool,
+}
+
+fn failed(reason: impl Into<String>) -> CliError {
+    CliError::SelfUpdateFailed { reason: reason.into() }
+}
+
+/// Look up a release and install it over the running executable
+pub async fn self_update(options: &UpdateOptions) -> Result<UpdateOutcome, CliError> {
+    if !options.check {
+        if crate::hermetic::is_enabled() {
+            return Err(failed("hermetic runs never modify the installed spklr"));
+        }
+        crate::read_only::ensure_allowed("replace the spklr executable")?;
+    }
+    let current = env!("CARGO_PKG_VERSION").to_string();
+    let tool_config = crate::tool_config::ToolConfig::discover()?;
+    let release = fetch_release(options.version.as_deref(), &tool_config).await?;
+
+    if !should_install(&current, &release.version, options.version.is_some(), options.force) {
+        return Ok(UpdateOutcome::UpToDate { version: current });
+    }
+    if options.check {
+        return Ok(UpdateOutcome::Available {
+            current,
+            version: release.version,
+        });
+    }
+
+    let asset = release_asset_name(std::env::consts::OS, std::env::consts::ARCH, cfg!(target_env = "musl")).ok_or_else(|| {
+        failed(format!(
+            "no release binary is published for {}-{}",
+            std::env::consts::OS,
+            std::env::consts::ARCH
+        ))
+    })?;
+    let url = release
+        .asset_url(asset)
+        .ok_or_else(|| failed(format!("release {} has no {} asset", release.version, asset)))?;
+    let urls = vec![url.to_string()];
+    let checksum_urls: Vec<String> = release
+        .asset_url(&format!("{}.sha256", asset))
+        .map(|checksum| checksum.trim_end_matches(".sha256").to_string())
+        .into_iter()
+        .collect();
+    let expected_sha256 = crate::download::fetch_published_sha256(
+        &checksum_urls,
+        &tool_config.retry_policy(crate::network::Operation::Checksum),
+    )
+    .await
+    .ok_or_else(|| failed(format!("release {} publishes no checksum for {}; refusing to install it unverified", release.version, asset)))?;
+
+    let executable = std::env::current_exe()
+        .and_then(|path| path.canonicalize())
+        .map_err(|e| CliError::IoError {
+            context: "Locating the spklr executable".to_string(),
+            source: e,
+        })?;
+    let staged = executable.with_file_name(format!(".spklr-{}.download", release.version));
+    crate::download::download_resumable(
+        &urls,
+        &staged,
+        Some(&expected_sha256),
+        &tool_config.retry_policy(crate::network::Operation::Download),
+    )
+    .await?;
+
+    if let Err(error) = verify_staged(&staged, &release.version).await {
+        let _ = std::fs::remove_file(&staged);
+        return Err(error);
+    }
+    replace_executable(&staged, &executable)?;
+    Ok(UpdateOutcome::Updated {
+        from: current,
+        to: release.version,
+        path: executable,
+    })
+}
+
+/// The release named `version`, or the latest
+#[cfg(feature = "network")]
+async fn fetch_release(version: Option<&str>, tool_config: &crate::tool_config::ToolConfig) -> Result<Release, CliError> {
+    use crate::network::Failure;
+
+    let endpoint = match version {
+        Some(version) => format!("tags/v{}", version.trim_start_matches('v')),
+        None => "latest".to_string(),
+    };
+    let url = format!("{}/repos/{}/releases/{}", crate::ci::comment::DEFAULT_API_URL, release_repo(), endpoint);
+    let policy = tool_config.retry_policy(crate::network::Operation::Github);
+    let client = policy.client()?;
+    let token = std::env::var("GITHUB_TOKEN").ok().filter(|token| !token.is_empty());
+
+    let release: Value = crate::network::retry(&policy, &url, || async {
+        let mut request = client.get(&url).header(reqwest::header::ACCEPT, "application/vnd.github+json");
+        if let Some(token) = &token {
+            request = request.bearer_auth(token);
+        }
+        let response = request.send().await.map_err(|e| policy.request_failure(e))?;
+        match crate::network::status_failure(&response) {
+            None => response.json().await.map_err(|e| Failure::Fatal(e.to_string())),
+            Some(Failure::Fatal(_)) if response.status() == reqwest::StatusCode::NOT_FOUND => {
+                Err(Failure::Fatal(format!("no release {} in {}", endpoint, release_repo())))
+            }
+            Some(failure) => Err(failure),
+        }
+    })
+    .await
+    .map_err(CliError::NetworkError)?;
+    Release::from_json(&release).ok_or_else(|| failed(format!("GitHub returned an unreadable release from {}", url)))
+}
+
+#[cfg(not(feature = "network"))]
+async fn fetch_release(_version: Option<&str>, _tool_config: &crate::tool_config::ToolConfig) -> Result<Release, CliError> {
+    Err(CliError::NetworkError(
+        "spklr was built without the `network` feature; cannot look up releases".to_string(),
+    ))
+}
+
+/// Make the download executable and check that it runs and is the expected release
+async fn verify_staged(staged: &Path, version: &str) -> Result<(), CliError> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(staged, std::fs::Permissions::from_mode(0o755)).map_err(|e| CliError::IoError {
+            context: format!("Making {} executable", staged.display()),
+            source: e,
+        })?;
+    }
+    let output = tokio::process::Command::new(staged)
+        .arg("--version")
+        .output()
+        .await
+        .map_err(|e| failed(format!("the downloaded binary doesn't run here: {}", e)))?;
+    let reported = String::from_utf8_lossy(&output.stdout);
+    if !output.status.success() || !reported.split_whitespace().any(|word| word.trim_start_matches('v') == version) {
+        return Err(failed(format!(
+            "the downloaded binary reports `{}`, not version {}",
+            reported.trim(),
+            version
+        )));
+    }
+    Ok(())
+}
+
+/// Move `staged` over `executable`
+///
+/// Both must be on one filesystem (the download is staged next to the executable), so
+/// the rename is atomic: the executable is either the old binary or the new one.
+pub fn replace_executable(staged: &Path, executable: &Path) -> Result<(), CliError> {
+    let rename = |from: &Path, to: &Path| {
+        std::fs::rename(from, to).map_err(|e| CliError::IoError {
+            context: format!("Moving {} to {}", from.display(), to.display()),
+            source: e,
+        })
+    };
+    if cfg!(windows) {
+        // A running executable can be renamed but not replaced or deleted
+        let old = executable.with_extension("old.exe");
+        let _ = std::fs::remove_file(&old);
+        rename(executable, &old)?;
+        if let Err(error) = rename(staged, executable) {
+            let _ = rename(&old, executable);
+            return Err(error);
+        }
+        return Ok(());
+    }
+    rename(staged, executable)
+}
@@ -0,0 +1,410 @@
+//! Task Graph Module for Space Pklr
+//!
+//! Builds Moon's task dependency graph from parsed project and inherited task
+//! configs, without running Moon itself. Task `deps` are resolved with Moon's
+//! target scopes (`project:task`, `~:task`, `^:task`, `#tag:task`), with `^:`
+//! following each project's `dependsOn` list. The graph can be exported as
+//! Graphviz DOT or JSON so migration results can be sanity-checked visually.
+
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Display;
+use std::str::FromStr;
+
+use crate::types::CliError;
+
+/// Export format for dependency graphs
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum GraphFormat {
+    /// Graphviz DOT
+    #[default]
+    Dot,
+    /// Plain JSON (nodes, edges, and unresolved deps)
+    Json,
+}
+
+impl FromStr for GraphFormat {
+    type Err = CliError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "dot" | "graphviz" | "gv" => Ok(GraphFormat::Dot),
+            "json" | "j" => Ok(GraphFormat::Json),
+            _ => Err(CliError::UnsupportedFormat {
+                format: s.to_string(),
+                available: vec!["dot", "json"],
+            }),
+        }
+    }
+}
+
+impl Display for GraphFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GraphFormat::Dot => write!(f, "dot"),
+            GraphFormat::Json => write!(f, "json"),
+        }
+    }
+}
+
+/// A single task in the graph, addressed by its `project:task` target
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TaskNode {
+    pub target: String,
+    pub project: String,
+    pub task: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    /// Whether the task came from an inherited tasks config rather than the project itself
+    pub inherited: bool,
+}
+
+/// A task dependency that couldn't be matched to a task in the graph
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct UnresolvedDep {
+    pub from: String,
+    pub dep: String,
+    pub reason: String,
+}
+
+/// Resolved task dependency graph
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TaskGraph {
+    pub nodes: BTreeMap<String, TaskNode>,
+    /// Edges point from a task to the task it depends on
+    pub edges: BTreeSet<(String, String)>,
+    pub unresolved: BTreeSet<UnresolvedDep>,
+}
+
+/// A project as seen by the graph builder
+#[derive(Debug, Clone, Default)]
+struct ProjectEntry {
+    depends_on: Vec<String>,
+    tags: Vec<String>,
+    tasks: BTreeMap<String, (Value, bool)>,
+    inherit_include: Option<BTreeSet<String>>,
+    inherit_exclude: BTreeSet<String>,
+}
+
+/// Collects project and inherited task configs, then resolves them into a [`TaskGraph`]
+#[derive(Debug, Default)]
+pub struct TaskGraphBuilder {
+    projects: BTreeMap<String, ProjectEntry>,
+    inherited_tasks: BTreeMap<String, Value>,
+    implicit_deps: Vec<String>,
+}
+
+impl TaskGraphBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an inherited tasks config (`.moon/tasks.yml` or `.moon/tasks/*.yml`)
+    ///
+    /// Later files win when two define a task with the same name, mirroring Moon's merge order.
+    pub fn add_inherited_tasks(&mut self, config: &Value) -> &mut Self {
+        if let Some(tasks) = config.get("tasks").and_then(Value::as_object) {
+            for (name, task) in tasks {
+                self.inherited_tasks.insert(name.clone(), task.clone());
+            }
+        }
+        if let Some(deps) = config.get("implicitDeps").and_then(Value::as_array) {
+            self.implicit_deps.extend(deps.iter().filter_map(dep_target));
+        }
+        self
+    }
+
+    /// Add a project config (`moon.yml`)
+    ///
+    /// `fallback_id` is used when the config doesn't set `id`, normally the project's directory name.
+    pub fn add_project(&mut self, fallback_id: &str, config: &Value) -> &mut Self {
+        let id = config
+            .get("id")
+            .and_then(Value::as_str)
+            .unwrap_or(fallback_id)
+            .to_string();
+
+        let depends_on = config
+            .get("dependsOn")
+            .and_then(Value::as_array)
+            .map(|deps| {
+                deps.iter()
+                    .filter_map(|dep| match dep {
+                        Value::String(id) => Some(id.clone()),
+                        Value::Object(obj) => obj.get("id").and_then(Value::as_str).map(str::to_string),
+                        _ => None,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let tags = config
+            .get("tags")
+            .and_then(Value::as_array)
+            .map(|tags| tags.iter().filter_map(Value::as_str).map(str::to_string).collect())
+            .unwrap_or_default();
+
+        let tasks = config
+            .get("tasks")
+            .and_then(Value::as_object)
+            .map(|tasks| {
+                tasks
+                    .iter()
+                    .map(|(name, task)| (name.clone(), (task.clone(), false)))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let (inherit_include, inherit_exclude) = inherited_filters(config);
+        self.projects.insert(
+            id,
+            ProjectEntry {
+                depends_on,
+                tags,
+                tasks,
+                inherit_include,
+                inherit_exclude,
+            },
+        );
+        self
+    }
+
+    /// Resolve all task deps into graph edges
+    pub fn build(&self) -> TaskGraph {
+        let mut graph = TaskGraph::default();
+        let projects = self.with_inherited_tasks();
+
+        for (project, entry) in &projects {
+            for (task, (config, inherited)) in &entry.tasks {
+                let target = format!("{}:{}", project, task);
+                graph.nodes.insert(
+                    target.clone(),
+                    TaskNode {
+                        target,
+                        project: project.clone(),
+                        task: task.clone(),
+                        command: task_command(config),
+                        inherited: *inherited,
+                    },
+                );
+            }
+        }
+
+        for (project, entry) in &projects {
+            for (task, (config, _)) in &entry.tasks {
+                let from = format!("{}:{}", project, task);
+                let deps = config
+                    .get("deps")
+                    .and_then(Value::as_array)
+                    .map(|deps| deps.iter().filter_map(dep_target).collect::<Vec<_>>())
+                    .unwrap_or_default();
+
+                for dep in deps.iter().chain(self.implicit_deps.iter()) {
+                    match self.resolve_dep(project, entry, dep, &graph) {
+                        Ok(targets) => {
+                            for to in targets.into_iter().filter(|to| *to != from) {
+                                graph.edges.insert((from.clone(), to));
+                            }
+                        }
+                        Err(reason) => {
+                            graph.unresolved.insert(UnresolvedDep {
+                                from: from.clone(),
+                                dep: dep.clone(),
+                                reason,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+
+        graph
+    }
+
+    /// Merge inherited tasks into each project; project-defined tasks take precedence
+    fn with_inherited_tasks(&self) -> BTreeMap<String, ProjectEntry> {
+        let mut projects = self.projects.clone();
+        for entry in projects.values_mut() {
+            for (name, task) in &self.inherited_tasks {
+                let included = entry
+                    .inherit_include
+                    .as_ref()
+                    .is_none_or(|names| names.contains(name));
+                if included && !entry.inherit_exclude.contains(name) && !entry.tasks.contains_key(name) {
+                    entry.tasks.insert(name.clone(), (task.clone(), true));
+                }
+            }
+        }
+        projects
+    }
+
+    /// Resolve a single dep target to the task targets it refers to
+    fn resolve_dep(
+        &self,
+        project: &str,
+        entry: &ProjectEntry,
+        dep: &str,
+        graph: &TaskGraph,
+    ) -> Result<Vec<String>, String> {
+        let (scope, task) = match dep.split_once(':') {
+            Some((scope, task)) => (scope, task),
+            None => ("~", dep),
+        };
+
+        match scope {
+            "~" => {
+                let target = format!("{}:{}", project, task);
+                if graph.nodes.contains_key(&target) {
+                    Ok(vec![target])
+                } else {
+                    Err(format!("task '{}' not found in project '{}'", task, project))
+                }
+            }
+            // Moon skips `^:` deps on projects that don't define the task
+            "^" => Ok(entry
+                .depends_on
+                .iter()
+                .map(|dep_project| format!("{}:{}", dep_project, task))
+                .filter(|target| graph.nodes.contains_key(target))
+                .collect()),
+            "" => Err("the all-projects scope `:task` is not allowed in task deps".to_string()),
+            tag if tag.starts_with('#') => Ok(self
+                .projects
+                .iter()
+                .filter(|(_, other)| other.tags.iter().any(|t| t == &tag[1..]))
+                .map(|(other, _)| format!("{}:{}", other, task))
+                .filter(|target| graph.nodes.contains_key(target))
+                .collect()),
+            other_project => {
+                let target = format!("{}:{}", other_project, task);
+                if graph.nodes.contains_key(&target) {
+                    Ok(vec![target])
+                } else if self.projects.contains_key(other_project) {
+                    Err(format!("task '{}' not found in project '{}'", task, other_project))
+                } else {
+                    Err(format!("project '{}' not found", other_project))
+                }
+            }
+        }
+    }
+}
+
+impl TaskGraph {
+    /// Render the graph as Graphviz DOT, grouping tasks by project
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph tasks {\n  rankdir=LR;\n  node [shape=box];\n");
+
+        let mut by_project: BTreeMap<&str, Vec<&TaskNode>> = BTreeMap::new();
+        for node in self.nodes.values() {
+            by_project.entry(node.project.as_str()).or_default().push(node);
+        }
+
+        for (index, (project, nodes)) in by_project.iter().enumerate() {
+            out.push_str(&format!("  subgraph cluster_{} {{\n", index));
+            out.push_str(&format!("    label={};\n", dot_quote(project)));
+            for node in nodes {
+                let style = if node.inherited { ", style=dashed" } else { "" };
+                out.push_str(&format!(
+                    "    {} [label={}{}];\n",
+                    dot_quote(&node.target),
+                    dot_quote(&node.task),
+                    style
+                ));
+            }
+            out.push_str("  }\n");
+        }
+
+        for (from, to) in &self.edges {
+            out.push_str(&format!("  {} -> {};\n", dot_quote(from), dot_quote(to)));
+        }
+
+        for dep in &self.unresolved {
+            let missing = format!("missing:{}", dep.dep);
+            out.push_str(&format!(
+                "  {} [label={}, color=red, fontcolor=red];\n",
+                dot_quote(&missing),
+                dot_quote(&dep.dep)
+            ));
+            out.push_str(&format!(
+                "  {} -> {} [color=red, style=dotted];\n",
+                dot_quote(&dep.from),
+                dot_quote(&missing)
+            ));
+        }
+
+        out.push_str("}\n");
+        out
+    }
+
+    /// Render the graph as pretty-printed JSON
+    pub fn to_json(&self) -> Result<String, CliError> {
+        let edges: Vec<_> = self
+            .edges
+            .iter()
+            .map(|(from, to)| serde_json::json!({ "from": from, "to": to }))
+            .collect();
+
+        serde_json::to_string_pretty(&serde_json::json!({
+            "nodes": self.nodes.values().collect::<Vec<_>>(),
+            "edges": edges,
+            "unresolved": self.unresolved,
+        }))
+        .map_err(|e| CliError::Generic(format!("Failed to serialize task graph: {}", e)))
+    }
+
+    /// Render the graph in the requested format
+    pub fn render(&self, format: GraphFormat) -> Result<String, CliError> {
+        match format {
+            GraphFormat::Dot => Ok(self.to_dot()),
+            GraphFormat::Json => self.to_json(),
+        }
+    }
+}
+
+/// Extract the target string from a task dep (`"build"` or `{ target: "build" }`)
+fn dep_target(dep: &Value) -> Option<String> {
+    match dep {
+        Value::String(target) => Some(target.clone()),
+        Value::Object(obj) => obj.get("target").and_then(Value::as_str).map(str::to_string),
+        _ => None,
+    }
+}
+
+/// Extract a printable command from a task config (`command` may be a string or a list)
+fn task_command(task: &Value) -> Option<String> {
+    match task.get("command")? {
+        Value::String(command) => Some(command.clone()),
+        Value::Array(parts) => Some(
+            parts
+                .iter()
+                .filter_map(Value::as_str)
+                .collect::<Vec<_>>()
+                .join(" "),
+        ),
+        _ => None,
+    }
+}
+
+/// Read `workspace.inheritedTasks.include`/`exclude` from a project config
+fn inherited_filters(config: &Value) -> (Option<BTreeSet<String>>, BTreeSet<String>) {
+    let inherited = config.pointer("/workspace/inheritedTasks");
+    let names = |key: &str| {
+        inherited
+            .and_then(|it| it.get(key))
+            .and_then(Value::as_array)
+            .map(|names| {
+                names
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .map(str::to_string)
+                    .collect::<BTreeSet<_>>()
+            })
+    };
+    (names("include"), names("exclude").unwrap_or_default())
+}
+
+/// Quote an identifier for DOT output
+fn dot_quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
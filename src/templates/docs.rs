@@ -0,0 +1,292 @@
+//! Markdown docs for generated schemas
+//!
+//! `spklr generate schema --docs markdown` renders each generated [`PklModule`] as a
+//! small doc site instead of Pkl: `<Module>.md` for the module's own properties and an
+//! index of its types, plus `<Module>.<Type>.md` for every class and typealias.
+//! Property tables list types, defaults, and the first line of each doc comment; the
+//! sections below a table hold the full docs, constraints, examples, and deprecations.
+//! Types defined in the set link to their pages, including ones imported from another
+//! module.
+
+use std::collections::BTreeMap;
+
+use crate::pkl_schema::{PklClass, PklModule, PklProperty, PklType, PklTypeAlias, pkl_literal};
+
+/// Markdown pages for `modules`, as `(file name, content)` pairs
+pub fn markdown_pages(modules: &[PklModule]) -> Vec<(String, String)> {
+    let mut pages = Vec::new();
+    for module in modules {
+        let links = Links::new(module, modules);
+        pages.push((format!("{}.md", module.name), module_page(module, &links)));
+        for class in &module.classes {
+            pages.push((type_page_name(&module.name, &class.name), class_page(module, class, &links)));
+        }
+        for typealias in &module.typealiases {
+            pages.push((type_page_name(&module.name, &typealias.name), typealias_page(module, typealias, &links)));
+        }
+    }
+    pages
+}
+
+/// File name of the page for `ty` in `module`
+pub fn type_page_name(module: &str, ty: &str) -> String {
+    format!("{}.{}.md", module, ty)
+}
+
+/// Where the type names used in one module point
+struct Links<'a> {
+    module: &'a PklModule,
+    /// Import alias to the module it imports
+    imports: BTreeMap<&'a str, &'a PklModule>,
+}
+
+impl<'a> Links<'a> {
+    fn new(module: &'a PklModule, modules: &'a [PklModule]) -> Self {
+        let imports = module
+            .imports
+            .iter()
+            .filter_map(|import| {
+                let alias = import.alias.as_deref()?;
+                let file = import.uri.rsplit('/').next().unwrap_or(&import.uri);
+                let name = file.strip_suffix(".pkl").unwrap_or(file);
+                let imported = modules.iter().find(|candidate| candidate.name == name)?;
+                Some((alias, imported))
+            })
+            .collect();
+        Self { module, imports }
+    }
+
+    /// Page for a type name as written in the module, if the set defines it
+    fn page(&self, name: &str) -> Option<String> {
+        let (owner, ty) = match name.split_once('.') {
+            Some((alias, ty)) => (*self.imports.get(alias)?, ty),
+            None => (self.module, name),
+        };
+        defines(owner, ty).then(|| type_page_name(&owner.name, ty))
+    }
+
+    /// Markdown for a type, with links to the pages of the types it names
+    fn render(&self, ty: &PklType) -> String {
+        let mut pieces = Vec::new();
+        self.pieces(ty, &mut pieces);
+        // Runs of plain source share one code span, so `Int?` doesn't become `Int``?`
+        let mut markdown = String::new();
+        let mut code = String::new();
+        for piece in pieces {
+            match piece {
+                Piece::Code(source) => code.push_str(&source),
+                Piece::Link(name, page) => {
+                    flush_code(&mut markdown, &mut code);
+                    markdown.push_str(&format!("[`{}`]({})", name, page));
+                }
+            }
+        }
+        flush_code(&mut markdown, &mut code);
+        markdown
+    }
+
+    fn pieces(&self, ty: &PklType, pieces: &mut Vec<Piece>) {
+        match ty {
+            PklType::Named(name) => match self.page(name) {
+                Some(page) => pieces.push(Piece::Link(name.clone(), page)),
+                None => pieces.push(Piece::Code(name.clone())),
+            },
+            PklType::Listing(item) => {
+                pieces.push(Piece::Code("Listing<".to_string()));
+                self.pieces(item, pieces);
+                pieces.push(Piece::Code(">".to_string()));
+            }
+            PklType::Mapping(key, value) => {
+                pieces.push(Piece::Code("Mapping<".to_string()));
+                self.pieces(key, pieces);
+                pieces.push(Piece::Code(", ".to_string()));
+                self.pieces(value, pieces);
+                pieces.push(Piece::Code(">".to_string()));
+            }
+            PklType::Union(members) => {
+                for (i, member) in members.iter().enumerate() {
+                    if i > 0 {
+                        pieces.push(Piece::Code("|".to_string()));
+                    }
+                    self.pieces(member, pieces);
+                }
+            }
+            PklType::Nullable(inner) => match inner.as_ref() {
+                PklType::Union(_) | PklType::Variants(_) => {
+                    pieces.push(Piece::Code("(".to_string()));
+                    self.pieces(inner, pieces);
+                    pieces.push(Piece::Code(")?".to_string()));
+                }
+                _ => {
+                    self.pieces(inner, pieces);
+                    pieces.push(Piece::Code("?".to_string()));
+                }
+            },
+            other => pieces.push(Piece::Code(other.render())),
+        }
+    }
+}
+
+/// Part of a rendered type: Pkl source, or a type name linking to its page
+enum Piece {
+    Code(String),
+    Link(String, String),
+}
+
+fn flush_code(markdown: &mut String, code: &mut String) {
+    if !code.is_empty() {
+        markdown.push_str(&format!("`{}`", code));
+        code.clear();
+    }
+}
+
+fn defines(module: &PklModule, ty: &str) -> bool {
+    module.classes.iter().any(|class| class.name == ty) || module.typealiases.iter().any(|typealias| typealias.name == ty)
+}
+
+fn module_page(module: &PklModule, links: &Links) -> String {
+    let mut page = format!("# {}\n\n", module.name);
+    push_doc(&mut page, module.doc.as_deref());
+    if !module.properties.is_empty() {
+        page.push_str("## Properties\n\n");
+        push_properties(&mut page, &module.properties, links);
+    }
+    if !module.classes.is_empty() || !module.typealiases.is_empty() {
+        page.push_str("## Types\n\n");
+        let types = module
+            .classes
+            .iter()
+            .map(|class| (&class.name, &class.doc))
+            .chain(module.typealiases.iter().map(|typealias| (&typealias.name, &typealias.doc)));
+        for (name, doc) in types {
+            page.push_str(&format!("- [`{}`]({})", name, type_page_name(&module.name, name)));
+            if let Some(summary) = summary(doc.as_deref()) {
+                page.push_str(&format!(": {}", summary));
+            }
+            page.push('\n');
+        }
+    }
+    page
+}
+
+fn class_page(module: &PklModule, class: &PklClass, links: &Links) -> String {
+    let kind = if class.is_abstract { "Abstract class" } else { "Class" };
+    let mut page = format!("# {}\n\n{} in [`{}`]({}.md)\n\n", class.name, kind, module.name, module.name);
+    if let Some(parent) = &class.extends {
+        page.push_str(&format!("**Extends:** {}\n\n", links.render(&PklType::Named(parent.clone()))));
+    }
+    let subclasses: Vec<String> = module
+        .subclasses(&class.name)
+        .map(|subclass| links.render(&PklType::Named(subclass.name.clone())))
+        .collect();
+    if !subclasses.is_empty() {
+        page.push_str(&format!("**Subclasses:** {}\n\n", subclasses.join(", ")));
+    }
+    push_doc(&mut page, class.doc.as_deref());
+    if !class.properties.is_empty() {
+        page.push_str("## Properties\n\n");
+        push_properties(&mut page, &class.properties, links);
+    }
+    page
+}
+
+fn typealias_page(module: &PklModule, typealias: &PklTypeAlias, links: &Links) -> String {
+    let mut page = format!("# {}\n\nTypealias in [`{}`]({}.md)\n\n", typealias.name, module.name, module.name);
+    push_doc(&mut page, typealias.doc.as_deref());
+    page.push_str(&format!("**Type:** {}\n", links.render(&typealias.ty)));
+    if let PklType::Variants(variants) = &typealias.ty {
+        page.push_str("\n## Values\n\n| Value | Description |\n|---|---|\n");
+        for variant in variants {
+            let mut description = variant.doc.as_deref().map(cell).unwrap_or_default();
+            if let Some(message) = &variant.deprecated {
+                let deprecation = match message.as_str() {
+                    "" => "**Deprecated.**".to_string(),
+                    message => format!("**Deprecated:** {}", cell(message)),
+                };
+                description = format!("{} {}", deprecation, description).trim_end().to_string();
+            }
+            page.push_str(&format!("| `{}` | {} |\n", cell(&PklType::StringLiteral(variant.value.clone()).render()), description));
+        }
+    }
+    page
+}
+
+fn push_doc(page: &mut String, doc: Option<&str>) {
+    if let Some(doc) = doc.filter(|doc| !doc.trim().is_empty()) {
+        page.push_str(doc.trim());
+        page.push_str("\n\n");
+    }
+}
+
+/// A property table, followed by a section for each property
+fn push_properties(page: &mut String, properties: &[PklProperty], links: &Links) {
+    page.push_str("| Property | Type | Default | Description |\n|---|---|---|---|\n");
+    for property in properties {
+        let default = property
+            .default
+            .as_ref()
+            .map(|default| format!("`{}`", cell(&pkl_literal(default, &property.ty))))
+            .unwrap_or_default();
+        let mut description = summary(property.doc.as_deref()).map(cell).unwrap_or_default();
+        if property.deprecated.is_some() {
+            description = format!("**Deprecated.** {}", description).trim_end().to_string();
+        }
+        page.push_str(&format!(
+            "| [`{}`](#{}) | {} | {} | {} |\n",
+            property.name,
+            anchor(&property.name),
+            cell(&links.render(&property.ty)),
+            default,
+            description
+        ));
+    }
+    for property in properties {
+        page.push_str(&format!("\n### `{}`\n\n", property.name));
+        if let Some(message) = &property.deprecated {
+            match message.as_str() {
+                "" => page.push_str("> **Deprecated.**\n\n"),
+                message => page.push_str(&format!("> **Deprecated:** {}\n\n", message)),
+            }
+        }
+        push_doc(page, property.doc.as_deref());
+        page.push_str(&format!("**Type:** {}\n", links.render(&property.ty)));
+        if let Some(default) = &property.default {
+            let qualifier = if property.fixed { " (fixed)" } else { "" };
+            page.push_str(&format!("\n**Default{}:** `{}`\n", qualifier, pkl_literal(default, &property.ty)));
+        }
+        if !property.constraints.is_empty() {
+            page.push_str("\n**Constraints:**\n\n");
+            for constraint in &property.constraints {
+                page.push_str(&format!("- `{}`", constraint.expression));
+                if let Some(provenance) = &constraint.provenance {
+                    page.push_str(&format!(" ({})", provenance));
+                }
+                page.push('\n');
+            }
+        }
+        if !property.examples.is_empty() {
+            page.push_str("\n**Examples:**\n\n");
+            for example in &property.examples {
+                page.push_str(&format!("```pkl\n{} = {}\n```\n", property.name, pkl_literal(example, &property.ty)));
+            }
+        }
+    }
+}
+
+/// First line of a doc comment
+fn summary(doc: Option<&str>) -> Option<&str> {
+    doc.and_then(|doc| doc.trim().lines().next()).filter(|line| !line.is_empty())
+}
+
+/// Text safe to put in a table cell
+fn cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+/// Heading anchor GitHub generates for a property section
+fn anchor(name: &str) -> String {
+    name.to_lowercase()
+        .chars()
+        .filter(|c| c.is_alphanumeric() || *c == '-' || *c == '_')
+        .collect()
+}
@@ -0,0 +1,183 @@
+//! Template engines for header and footer templates
+//!
+//! The built-in engine substitutes `{{ path }}` placeholders and nothing else.
+//! MiniJinja adds Jinja syntax for teams that want filters, conditionals, and inline
+//! expressions; it's behind the `jinja_templates` cargo feature.
+
+use serde::Deserialize;
+use std::fmt::Display;
+
+use super::{Lookup, TemplateContext, VAR_ENV_PREFIX};
+use crate::types::CliError;
+
+/// Renders a template against a context
+pub trait TemplateEngine {
+    /// Engine name, as used in `spklr.toml`
+    fn name(&self) -> &'static str;
+
+    /// Render `template` (named `name` in errors) against `context`
+    ///
+    /// With `strict`, references that don't resolve are errors.
+    fn render(&self, name: &str, template: &str, context: &TemplateContext, strict: bool) -> Result<String, CliError>;
+}
+
+/// Engine selected by `engine` in `[templates]`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EngineKind {
+    /// `{{ path }}` placeholders
+    #[default]
+    Builtin,
+    /// Jinja syntax via MiniJinja
+    #[serde(alias = "jinja")]
+    MiniJinja,
+}
+
+impl Display for EngineKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EngineKind::Builtin => write!(f, "builtin"),
+            EngineKind::MiniJinja => write!(f, "minijinja"),
+        }
+    }
+}
+
+/// The engine for `kind`
+pub fn engine(kind: EngineKind) -> Box<dyn TemplateEngine> {
+    match kind {
+        EngineKind::Builtin => Box::new(BuiltinEngine),
+        EngineKind::MiniJinja => Box::new(MiniJinjaEngine),
+    }
+}
+
+/// `{{ path }}` placeholder substitution
+///
+/// Undefined `vars.*` references are always errors; other references that don't
+/// resolve render empty unless `strict` is set.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BuiltinEngine;
+
+impl TemplateEngine for BuiltinEngine {
+    fn name(&self) -> &'static str {
+        "builtin"
+    }
+
+    fn render(&self, name: &str, template: &str, context: &TemplateContext, strict: bool) -> Result<String, CliError> {
+        let template_error = |offset: usize, message: String| {
+            let (line, column) = position(template, offset);
+            CliError::TemplateError {
+                template: name.to_string(),
+                message: format!("{} (line {}, column {})", message, line, column),
+            }
+        };
+
+        let mut output = String::new();
+        let mut rest = template;
+        while let Some(start) = rest.find("{{") {
+            let offset = template.len() - rest.len() + start;
+            output.push_str(&rest[..start]);
+            let after = &rest[start + 2..];
+            let Some(end) = after.find("}}") else {
+                return Err(template_error(offset, "unclosed `{{`".to_string()));
+            };
+            let reference = after[..end].trim();
+            match context.lookup(reference) {
+                Lookup::Found(value) => output.push_str(&value),
+                Lookup::Missing if strict => {
+                    return Err(template_error(
+                        offset,
+                        format!("`{}` is not defined; available: spklr.version, output.file, vars.*, module.*", reference),
+                    ));
+                }
+                Lookup::Missing => {}
+                Lookup::UndefinedVariable => {
+                    return Err(template_error(offset, undefined_variable(&reference["vars.".len()..])));
+                }
+            }
+            rest = &after[end + 2..];
+        }
+        output.push_str(rest);
+        Ok(output)
+    }
+}
+
+/// Jinja templates rendered with MiniJinja
+///
+/// Templates see the same `spklr`, `output`, and `vars` values. As with the built-in
+/// engine, undefined `vars.*` references are always errors. Strict mode maps to
+/// MiniJinja's strict undefined handling; otherwise other undefined values render empty.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MiniJinjaEngine;
+
+impl TemplateEngine for MiniJinjaEngine {
+    fn name(&self) -> &'static str {
+        "minijinja"
+    }
+
+    #[cfg(feature = "jinja_templates")]
+    fn render(&self, name: &str, template: &str, context: &TemplateContext, strict: bool) -> Result<String, CliError> {
+        use minijinja::{Environment, UndefinedBehavior};
+
+        let template_error = |e: minijinja::Error| CliError::TemplateError {
+            template: name.to_string(),
+            message: match e.line() {
+                Some(line) => format!("{} (line {})", e, line),
+                None => e.to_string(),
+            },
+        };
+
+        let mut environment = Environment::new();
+        environment.set_undefined_behavior(if strict {
+            UndefinedBehavior::Strict
+        } else {
+            UndefinedBehavior::Chainable
+        });
+        environment.add_template(name, template).map_err(template_error)?;
+        let template = environment.get_template(name).map_err(template_error)?;
+
+        let mut undefined: Vec<String> = template
+            .undeclared_variables(true)
+            .into_iter()
+            .filter_map(|reference| {
+                let variable = reference.strip_prefix("vars.")?.split('.').next()?.to_string();
+                matches!(context.lookup(&format!("vars.{}", variable)), Lookup::UndefinedVariable).then_some(variable)
+            })
+            .collect();
+        undefined.sort();
+        if let Some(variable) = undefined.first() {
+            return Err(CliError::TemplateError {
+                template: name.to_string(),
+                message: undefined_variable(variable),
+            });
+        }
+
+        template.render(context.resolved()).map_err(template_error)
+    }
+
+    #[cfg(not(feature = "jinja_templates"))]
+    fn render(&self, name: &str, _template: &str, _context: &TemplateContext, _strict: bool) -> Result<String, CliError> {
+        Err(CliError::TemplateError {
+            template: name.to_string(),
+            message: "spklr was built without the `jinja_templates` feature".to_string(),
+        })
+    }
+}
+
+/// Error message for a `vars.<variable>` reference with no value
+fn undefined_variable(variable: &str) -> String {
+    format!(
+        "undefined variable `vars.{}`; pass --var {}=... or set {}{}",
+        variable,
+        variable,
+        VAR_ENV_PREFIX,
+        variable.to_uppercase()
+    )
+}
+
+/// 1-based line and column of a byte offset
+fn position(text: &str, offset: usize) -> (usize, usize) {
+    let before = &text[..offset];
+    let line = before.matches('\n').count() + 1;
+    let column = before.rsplit('\n').next().map_or(0, |l| l.chars().count()) + 1;
+    (line, column)
+}
@@ -0,0 +1,487 @@
+//! Templates Module for Space Pklr
+//!
+//! Header and footer templates from `[templates]` in `spklr.toml` are rendered and
+//! wrapped around generated schemas, config templates, and docs, as comments in each
+//! output's syntax. JSON has no comments, so JSON outputs are left as they are.
+//!
+//! ```toml
+//! [templates]
+//! header = "Generated by spklr {{ spklr.version }} for {{ vars.team }}. Do not edit."
+//! footer = "Source: {{ output.file }}"
+//! ```
+//!
+//! Templates see `spklr.version`, `output.file`, and `vars.<name>`. Variables come from
+//! `SPKLR_VAR_<NAME>` environment variables, overridden by `--var name=value`; names
+//! are lowercased. Referencing an undefined `vars.*` variable is an error, so a
+//! pipeline that forgets to pass one fails instead of publishing a blank. With
+//! `strict = true`, any reference that doesn't resolve is an error too, instead of
+//! rendering as an empty string.
+//!
+//! Templates use the built-in `{{ path.to.value }}` placeholders unless
+//! `engine = "minijinja"` selects Jinja syntax (filters, conditionals, inline
+//! expressions); that engine is behind the `jinja_templates` cargo feature.
+//!
+//! Trailers are appended verbatim rather than as comments, for epilogues in the
+//! output's own syntax such as Pkl `output` blocks or helper functions. `trailer`
+//! applies to every output; `[templates.modules.<Module>]` adds one for the outputs of
+//! a single config type, ahead of the global one. Outputs generated for a config type
+//! also see `module.name`, `module.config_type`, `module.types`, and
+//! `module.properties` (see [`module`]).
+//!
+//! ```toml
+//! [templates]
+//! trailer = "output { renderer = new YamlRenderer {} }"
+//!
+//! [templates.modules.Workspace]
+//! trailer = "// {{ module.name }} defines {{ module.types }}"
+//! ```
+//!
+//! `template_dir` (or `--template-dir`) replaces how generated Pkl modules are written,
+//! per module, class, or property; see [`overrides`].
+//!
+//! `config_header` replaces the `amends "<schema>"` line `spklr convert --pkl-header`
+//! starts Pkl configs with; it sees `schema.keyword`, `schema.path`, and `schema.module`.
+//!
+//! `theme = "<name>"` (or `--theme`) starts from a preset instead of writing templates:
+//! `documented`, `compact`, `minimal-comments`, or `annotations-heavy`. See [`themes`].
+//!
+//! `--debug-templates <DIR>` writes the resolved context (`context.json`) and every
+//! render with its output and timing (`renders.json`) to `DIR` when the run ends.
+//!
+//! `spklr generate schema --docs markdown` renders generated modules as Markdown pages
+//! instead of Pkl; see [`docs`]. Headers and footers wrap those pages too.
+
+pub mod docs;
+pub mod engine;
+pub mod module;
+pub mod overrides;
+pub mod themes;
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Instant;
+
+use crate::types::CliError;
+
+pub use engine::{BuiltinEngine, EngineKind, TemplateEngine};
+pub use module::ModuleInfo;
+pub use overrides::{TemplateOverrides, set_cli_template_dir};
+pub use themes::{Theme, set_cli_theme};
+
+/// Prefix of environment variables ingested as template variables
+pub const VAR_ENV_PREFIX: &str = "SPKLR_VAR_";
+
+static CLI_VARIABLES: Mutex<BTreeMap<String, String>> = Mutex::new(BTreeMap::new());
+static DEBUG_DIR: Mutex<Option<PathBuf>> = Mutex::new(None);
+static RENDERS: Mutex<Vec<RenderTrace>> = Mutex::new(Vec::new());
+
+/// One template render, recorded for `--debug-templates`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RenderTrace {
+    pub template: String,
+    pub engine: String,
+    /// Output the template was rendered for
+    pub file: Option<String>,
+    pub duration_micros: u64,
+    pub output: String,
+}
+
+/// Set the `--var` variables for the rest of the run
+pub fn set_cli_variables(variables: Vec<(String, String)>) {
+    if let Ok(mut cli_variables) = CLI_VARIABLES.lock() {
+        cli_variables.extend(variables);
+    }
+}
+
+/// Record renders and dump them to `dir` at the end of the run
+pub fn enable_debug(dir: PathBuf) {
+    if let Ok(mut debug_dir) = DEBUG_DIR.lock() {
+        *debug_dir = Some(dir);
+    }
+}
+
+fn debug_dir() -> Option<PathBuf> {
+    DEBUG_DIR.lock().ok().and_then(|dir| dir.clone())
+}
+
+/// Write `context.json` and `renders.json` to the debug directory, if enabled
+pub fn write_debug_dump() -> Result<Option<PathBuf>, CliError> {
+    let Some(dir) = debug_dir() else {
+        return Ok(None);
+    };
+    crate::read_only::ensure_allowed(format!("write {}", dir.display()))?;
+    std::fs::create_dir_all(&dir).map_err(|e| CliError::IoError {
+        context: format!("Creating template debug directory: {}", dir.display()),
+        source: e,
+    })?;
+
+    let renders = RENDERS.lock().map(|renders| renders.clone()).unwrap_or_default();
+    write_json(&dir.join("context.json"), &TemplateContext::current().resolved())?;
+    write_json(&dir.join("renders.json"), &serde_json::to_value(renders).unwrap_or_default())?;
+    Ok(Some(dir))
+}
+
+fn write_json(path: &Path, value: &serde_json::Value) -> Result<(), CliError> {
+    let content = serde_json::to_string_pretty(value).map_err(|e| CliError::ValidationError {
+        source: Box::new(e),
+    })?;
+    std::fs::write(path, content).map_err(|e| CliError::IoError {
+        context: format!("Writing {}", path.display()),
+        source: e,
+    })
+}
+
+/// Parse a `--var key=value` argument
+pub fn parse_var(arg: &str) -> Result<(String, String), String> {
+    match arg.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() => Ok((key.trim().to_lowercase(), value.to_string())),
+        _ => Err(format!("expected KEY=VALUE, got '{}'", arg)),
+    }
+}
+
+/// `[templates]` settings
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct TemplateConfig {
+    /// Template rendered as a comment before each generated output
+    pub header: Option<String>,
+    /// Template rendered as a comment after each generated output
+    pub footer: Option<String>,
+    /// Fail on references that don't resolve instead of rendering them empty
+    pub strict: bool,
+    /// Template syntax: `builtin` placeholders (default) or `minijinja`
+    pub engine: EngineKind,
+    /// Preset supplying `header` and `footer` where they aren't set
+    pub theme: Option<Theme>,
+    /// Template appended verbatim to each generated output
+    pub trailer: Option<String>,
+    /// Per-module templates, keyed by module name (e.g. `Workspace`)
+    pub modules: BTreeMap<String, ModuleTemplates>,
+    /// Directory of `module`/`class`/`property` templates for generated Pkl source
+    pub template_dir: Option<PathBuf>,
+    /// Template for the `amends`/`extends` line of converted Pkl configs
+    pub config_header: Option<String>,
+}
+
+/// `[templates.modules.<Module>]` settings
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ModuleTemplates {
+    /// Template appended verbatim to the module's outputs, before the global trailer
+    pub trailer: Option<String>,
+}
+
+impl TemplateConfig {
+    pub fn is_empty(&self) -> bool {
+        self.header.is_none()
+            && self.footer.is_none()
+            && self.trailer.is_none()
+            && self.modules.values().all(|module| module.trailer.is_none())
+    }
+
+    /// Override templates from `--template-dir` or `template_dir`, if either is set
+    pub fn overrides(&self) -> Result<TemplateOverrides, CliError> {
+        match overrides::cli_template_dir().or_else(|| self.template_dir.clone()) {
+            Some(dir) => TemplateOverrides::load(&dir, self.engine, self.strict),
+            None => Ok(TemplateOverrides::default()),
+        }
+    }
+
+    /// Trailer for `module`, if one is configured
+    pub fn module_trailer(&self, module: &str) -> Option<&str> {
+        self.modules
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(module))
+            .and_then(|(_, templates)| templates.trailer.as_deref())
+    }
+
+    /// This config with the `--theme` or configured theme filled in
+    pub fn themed(&self) -> Self {
+        let Some(theme) = themes::cli_theme().or(self.theme) else {
+            return self.clone();
+        };
+        let preset = theme.config();
+        Self {
+            header: self.header.clone().or(preset.header),
+            footer: self.footer.clone().or(preset.footer),
+            theme: Some(theme),
+            ..self.clone()
+        }
+    }
+}
+
+/// Data available to templates
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TemplateContext {
+    /// `vars.<name>` values
+    pub variables: BTreeMap<String, String>,
+    /// `output.file`: name of the output being rendered
+    pub file: Option<String>,
+    /// `module.*`: the module the output was generated for
+    pub module: Option<ModuleInfo>,
+    /// Values for template overrides (`property`, `class`, `module`), taking precedence
+    pub values: serde_json::Map<String, serde_json::Value>,
+}
+
+impl TemplateContext {
+    /// Variables from `SPKLR_VAR_*` environment variables, overridden by `--var`
+    pub fn current() -> Self {
+        let mut variables: BTreeMap<String, String> = std::env::vars()
+            .filter_map(|(key, value)| {
+                let name = key.strip_prefix(VAR_ENV_PREFIX)?;
+                (!name.is_empty()).then(|| (name.to_lowercase(), value))
+            })
+            .collect();
+        if let Ok(cli_variables) = CLI_VARIABLES.lock() {
+            variables.extend(cli_variables.clone());
+        }
+        Self {
+            variables,
+            ..Default::default()
+        }
+    }
+
+    /// This context for rendering around `file`
+    pub fn for_file(&self, file: &str) -> Self {
+        Self {
+            file: Some(file.to_string()),
+            ..self.clone()
+        }
+    }
+
+    /// This context for rendering an output of `module`
+    pub fn for_module(&self, module: ModuleInfo) -> Self {
+        Self {
+            module: Some(module),
+            ..self.clone()
+        }
+    }
+
+    /// This context with `value` available as `name`
+    pub fn with_value(&self, name: &str, value: serde_json::Value) -> Self {
+        let mut context = self.clone();
+        context.values.insert(name.to_string(), value);
+        context
+    }
+
+    /// Everything templates can reference, as it resolves for this context
+    pub fn resolved(&self) -> serde_json::Value {
+        let mut resolved = json!({
+            "spklr": { "version": env!("CARGO_PKG_VERSION") },
+            "output": { "file": self.file },
+            "vars": self.variables,
+            "module": self.module,
+        });
+        if let Some(resolved) = resolved.as_object_mut() {
+            resolved.extend(self.values.clone());
+        }
+        resolved
+    }
+
+    /// Resolve a dotted reference like `vars.team`
+    fn lookup(&self, reference: &str) -> Lookup {
+        let (root, path) = reference.split_once('.').unwrap_or((reference, ""));
+        if let Some(value) = self.values.get(root) {
+            let value = crate::config_path::ConfigPath::new(path).get(value);
+            return match value {
+                Some(serde_json::Value::String(text)) => Lookup::Found(text.clone()),
+                Some(serde_json::Value::Null) | None => Lookup::Missing,
+                Some(serde_json::Value::Array(items)) => Lookup::Found(
+                    items
+                        .iter()
+                        .map(|item| item.as_str().map_or_else(|| item.to_string(), str::to_string))
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                ),
+                Some(other) => Lookup::Found(other.to_string()),
+            };
+        }
+        if let Some(name) = reference.strip_prefix("vars.") {
+            return match self.variables.get(name) {
+                Some(value) => Lookup::Found(value.clone()),
+                None => Lookup::UndefinedVariable,
+            };
+        }
+        if let Some(field) = reference.strip_prefix("module.") {
+            return self
+                .module
+                .as_ref()
+                .and_then(|module| module.lookup(field))
+                .map_or(Lookup::Missing, Lookup::Found);
+        }
+        match reference {
+            "spklr.version" => Lookup::Found(env!("CARGO_PKG_VERSION").to_string()),
+            "output.file" => self.file.clone().map_or(Lookup::Missing, Lookup::Found),
+            _ => Lookup::Missing,
+        }
+    }
+}
+
+enum Lookup {
+    Found(String),
+    Missing,
+    UndefinedVariable,
+}
+
+/// Render a template with the built-in engine
+pub fn render(name: &str, template: &str, context: &TemplateContext, strict: bool) -> Result<String, CliError> {
+    render_with(&BuiltinEngine, name, template, context, strict)
+}
+
+/// Render a template with `engine`, recording it for `--debug-templates`
+pub fn render_with(
+    engine: &dyn TemplateEngine,
+    name: &str,
+    template: &str,
+    context: &TemplateContext,
+    strict: bool,
+) -> Result<String, CliError> {
+    let started = Instant::now();
+    let output = engine.render(name, template, context, strict)?;
+
+    if debug_dir().is_some() {
+        if let Ok(mut renders) = RENDERS.lock() {
+            renders.push(RenderTrace {
+                template: name.to_string(),
+                engine: engine.name().to_string(),
+                file: context.file.clone(),
+                duration_micros: started.elapsed().as_micros() as u64,
+                output: output.clone(),
+            });
+        }
+    }
+    Ok(output)
+}
+
+/// Comment line prefix for an output file, or `None` if it can't carry comments
+fn comment_prefix(file: &str) -> Option<&'static str> {
+    match file.rsplit('.').next()? {
+        "ts" | "js" | "pkl" => Some("// "),
+        "yml" | "yaml" | "toml" => Some("# "),
+        "md" => Some(""),
+        _ => None,
+    }
+}
+
+fn as_comment(prefix: &str, text: &str) -> String {
+    text.lines()
+        .map(|line| format!("{}{}", prefix, line).trim_end().to_string())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Writer that remembers whether what went through it so far ends with a newline
+pub(crate) struct LineEndWriter<'a> {
+    inner: &'a mut dyn Write,
+    pub ends_with_newline: bool,
+}
+
+impl<'a> LineEndWriter<'a> {
+    pub fn new(inner: &'a mut dyn Write) -> Self {
+        Self {
+            inner,
+            ends_with_newline: false,
+        }
+    }
+}
+
+impl Write for LineEndWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        if written > 0 {
+            self.ends_with_newline = buf[written - 1] == b'\n';
+        }
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl TemplateConfig {
+    /// Wrap generated `content` for `file` in the rendered header and footer
+    pub fn wrap(&self, context: &TemplateContext, file: &str, content: &str) -> Result<String, CliError> {
+        let mut output = Vec::new();
+        self.wrap_with(context, file, &mut output, |out| {
+            out.write_all(content.as_bytes()).map_err(|e| CliError::IoError {
+                context: format!("Wrapping {}", file),
+                source: e,
+            })
+        })?;
+        // Only strings were written
+        Ok(String::from_utf8_lossy(&output).into_owned())
+    }
+
+    /// Stream the header into `out`, then whatever `body` writes, then the trailers and footer
+    pub fn wrap_with(
+        &self,
+        context: &TemplateContext,
+        file: &str,
+        out: &mut dyn Write,
+        body: impl FnOnce(&mut dyn Write) -> Result<(), CliError>,
+    ) -> Result<(), CliError> {
+        let config = self.themed();
+        let Some(prefix) = comment_prefix(file).filter(|_| !config.is_empty()) else {
+            return body(out);
+        };
+        let context = context.for_file(file);
+        let engine = engine::engine(config.engine);
+        let io_err = |e| CliError::IoError {
+            context: format!("Writing {}", file),
+            source: e,
+        };
+
+        let mut out = LineEndWriter::new(out);
+        if let Some(header) = &config.header {
+            let header = as_comment(prefix, &render_with(engine.as_ref(), "header", header, &context, config.strict)?);
+            write!(out, "{}\n\n", header).map_err(io_err)?;
+        }
+        body(&mut out)?;
+        let module_trailer = context.module.as_ref().and_then(|module| config.module_trailer(&module.name));
+        let trailers = [("module trailer", module_trailer), ("trailer", config.trailer.as_deref())];
+        for (name, trailer) in trailers.into_iter().filter_map(|(name, trailer)| Some((name, trailer?))) {
+            let trailer = render_with(engine.as_ref(), name, trailer, &context, config.strict)?;
+            let separator = if out.ends_with_newline { "\n" } else { "\n\n" };
+            write!(out, "{}{}\n", separator, trailer.trim_end()).map_err(io_err)?;
+        }
+        if let Some(footer) = &config.footer {
+            let footer = as_comment(prefix, &render_with(engine.as_ref(), "footer", footer, &context, config.strict)?);
+            let separator = if out.ends_with_newline { "\n" } else { "\n\n" };
+            write!(out, "{}{}\n", separator, footer).map_err(io_err)?;
+        }
+        Ok(())
+    }
+}
+
+/// Wrap a generated output using `spklr.toml` templates and the current variables
+///
+/// Outputs named after a config type also get its `module.*` data.
+pub fn wrap_generated(file: &str, content: &str) -> Result<String, CliError> {
+    let config = crate::tool_config::ToolConfig::discover()?.templates;
+    config.wrap(&generated_context(&config, file)?, file, content)
+}
+
+/// Like [`wrap_generated`], streaming the output into `out` with `body` writing the content
+pub fn wrap_generated_with(
+    file: &str,
+    out: &mut dyn Write,
+    body: impl FnOnce(&mut dyn Write) -> Result<(), CliError>,
+) -> Result<(), CliError> {
+    let config = crate::tool_config::ToolConfig::discover()?.templates;
+    config.wrap_with(&generated_context(&config, file)?, file, out, body)
+}
+
+/// Template context for the generated output `file`
+fn generated_context(config: &TemplateConfig, file: &str) -> Result<TemplateContext, CliError> {
+    let context = TemplateContext::current();
+    match module::config_type_for_file(file).filter(|_| !config.themed().is_empty()) {
+        Some(config_type) => Ok(context.for_module(ModuleInfo::for_config(config_type)?)),
+        None => Ok(context),
+    }
+}
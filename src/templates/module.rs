@@ -0,0 +1,91 @@
+//! Module data for templates
+//!
+//! Outputs generated for a Moon config type expose that module to templates as
+//! `module.*`: its name, the types it defines, and its top-level properties, read from
+//! the type's generated JSON Schema.
+
+use serde::Serialize;
+use serde_json::Value;
+use std::str::FromStr;
+
+use crate::types::{CliError, MoonConfig};
+
+/// `module.*` values for an output generated from one config type
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ModuleInfo {
+    /// Module name, e.g. `Workspace`
+    pub name: String,
+    /// Config type the module was generated from, e.g. `workspace`
+    pub config_type: String,
+    /// The root type followed by every definition
+    pub types: Vec<String>,
+    /// Top-level properties of the root type
+    pub properties: Vec<String>,
+}
+
+impl ModuleInfo {
+    /// Module data for `config_type`, from a JSON Schema generated for it
+    pub fn from_json_schema(config_type: MoonConfig, schema: &Value) -> Self {
+        let mut types: Vec<String> = schema
+            .get("title")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .into_iter()
+            .collect();
+        if let Some(definitions) = schema.get("definitions").and_then(Value::as_object) {
+            types.extend(definitions.keys().cloned());
+        }
+        let properties = schema
+            .get("properties")
+            .and_then(Value::as_object)
+            .map(|properties| properties.keys().cloned().collect())
+            .unwrap_or_default();
+
+        Self {
+            name: module_name(config_type),
+            config_type: config_type.to_string(),
+            types,
+            properties,
+        }
+    }
+
+    /// Module data for `config_type`, generating its JSON Schema
+    pub fn for_config(config_type: MoonConfig) -> Result<Self, CliError> {
+        let content = crate::moon_schema::generate_schema(config_type, "json-schema")?;
+        let schema: Value = serde_json::from_str(&content).map_err(|e| CliError::ValidationError {
+            source: Box::new(e),
+        })?;
+        Ok(Self::from_json_schema(config_type, &schema))
+    }
+
+    /// Resolve `module.<field>`; lists are comma-separated
+    pub(super) fn lookup(&self, field: &str) -> Option<String> {
+        match field {
+            "name" => Some(self.name.clone()),
+            "config_type" => Some(self.config_type.clone()),
+            "types" => Some(self.types.join(", ")),
+            "properties" => Some(self.properties.join(", ")),
+            _ => None,
+        }
+    }
+}
+
+/// PascalCase module name for a config type
+pub fn module_name(config_type: MoonConfig) -> String {
+    let name = config_type.to_string();
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => name,
+    }
+}
+
+/// Config type an output was generated from, going by its file name
+/// (`project_schema.ts`, `workspace.pkl`, ...)
+pub fn config_type_for_file(file: &str) -> Option<MoonConfig> {
+    let name = std::path::Path::new(file).file_stem()?.to_str()?;
+    let name = name.strip_suffix("_schema").unwrap_or(name);
+    MoonConfig::from_str(name)
+        .ok()
+        .filter(|config_type| *config_type != MoonConfig::All)
+}
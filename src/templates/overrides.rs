@@ -0,0 +1,95 @@
+//! Template overrides for generated Pkl source
+//!
+//! `template_dir` in `[templates]` (or `--template-dir`) points at a directory of
+//! templates that replace how generated Pkl modules are written. Each is optional; a
+//! missing one falls back to the built-in rendering:
+//!
+//! - `property.tmpl`: one property, with `property.name`, `property.identifier`,
+//!   `property.type`, `property.doc`, `property.doc_comment`, `property.optional`,
+//!   `property.default` (a Pkl expression, if any), `property.fixed`, and
+//!   `property.constraints` (already part of `property.type`)
+//! - `class.tmpl`: one class, with `class.name`, `class.open`, `class.abstract`,
+//!   `class.extends` (the superclass, if any), `class.doc`, `class.doc_comment`, and
+//!   `class.properties` (the rendered, indented properties)
+//! - `module.tmpl`: the whole module, with `module.name`, `module.open`, `module.doc`,
+//!   `module.doc_comment`, and the rendered `module.imports`, `module.properties`,
+//!   `module.classes`, and `module.typealiases`
+//!
+//! Templates use the engine selected by `engine` and honour `strict`.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use super::{EngineKind, TemplateContext, engine, render_with};
+use crate::types::CliError;
+
+/// Kinds of template that can be overridden, by file stem
+pub const OVERRIDE_KINDS: [&str; 3] = ["module", "class", "property"];
+
+/// File extension of override templates
+pub const OVERRIDE_EXTENSION: &str = "tmpl";
+
+static CLI_TEMPLATE_DIR: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+/// Set the `--template-dir` for the rest of the run; it overrides `template_dir`
+pub fn set_cli_template_dir(dir: Option<PathBuf>) {
+    if let Ok(mut template_dir) = CLI_TEMPLATE_DIR.lock() {
+        *template_dir = dir;
+    }
+}
+
+pub(super) fn cli_template_dir() -> Option<PathBuf> {
+    CLI_TEMPLATE_DIR.lock().ok().and_then(|dir| dir.clone())
+}
+
+/// Override templates loaded from a template directory
+#[derive(Debug, Clone, Default)]
+pub struct TemplateOverrides {
+    templates: BTreeMap<String, String>,
+    engine: EngineKind,
+    strict: bool,
+}
+
+impl TemplateOverrides {
+    /// Load whichever override templates exist in `dir`
+    pub fn load(dir: &Path, engine: EngineKind, strict: bool) -> Result<Self, CliError> {
+        if !dir.is_dir() {
+            return Err(CliError::FileNotFound { path: dir.to_path_buf() });
+        }
+        let mut templates = BTreeMap::new();
+        for kind in OVERRIDE_KINDS {
+            let path = dir.join(format!("{}.{}", kind, OVERRIDE_EXTENSION));
+            if !path.is_file() {
+                continue;
+            }
+            let template = std::fs::read_to_string(&path).map_err(|e| CliError::IoError {
+                context: format!("Reading template override: {}", path.display()),
+                source: e,
+            })?;
+            templates.insert(kind.to_string(), template);
+        }
+        Ok(Self { templates, engine, strict })
+    }
+
+    /// Whether `kind` has an override
+    pub fn has(&self, kind: &str) -> bool {
+        self.templates.contains_key(kind)
+    }
+
+    /// Render the `kind` override against `context`, or `builtin` if there isn't one
+    pub fn render(
+        &self,
+        kind: &str,
+        context: &TemplateContext,
+        builtin: impl FnOnce() -> String,
+    ) -> Result<String, CliError> {
+        match self.templates.get(kind) {
+            Some(template) => {
+                let engine = engine::engine(self.engine);
+                render_with(engine.as_ref(), &format!("{}.{}", kind, OVERRIDE_EXTENSION), template, context, self.strict)
+            }
+            None => Ok(builtin()),
+        }
+    }
+}
@@ -0,0 +1,107 @@
+//! Theme presets for generated output
+//!
+//! A theme is a curated header/footer bundle, selected with `--theme` or `theme` in
+//! `[templates]`. `header` and `footer` set in `spklr.toml` take precedence over the
+//! theme's, so a theme can be used as a starting point and adjusted piecemeal.
+
+use serde::Deserialize;
+use std::fmt::Display;
+use std::str::FromStr;
+use std::sync::Mutex;
+
+use super::TemplateConfig;
+use crate::types::CliError;
+
+static CLI_THEME: Mutex<Option<Theme>> = Mutex::new(None);
+
+/// Named output style
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Theme {
+    /// Explains where the file came from and how to regenerate it
+    Documented,
+    /// A single-line provenance header
+    Compact,
+    /// No header or footer
+    MinimalComments,
+    /// `@generated`-style annotations that tools and reviewers key off
+    AnnotationsHeavy,
+}
+
+impl Theme {
+    pub fn all() -> &'static [Theme] {
+        &[
+            Theme::Documented,
+            Theme::Compact,
+            Theme::MinimalComments,
+            Theme::AnnotationsHeavy,
+        ]
+    }
+
+    /// The templates this theme stands for
+    pub fn config(self) -> TemplateConfig {
+        let (header, footer) = match self {
+            Theme::Documented => (
+                Some(
+                    "Generated by spklr {{ spklr.version }} from Moon's configuration schema.\n\
+                     Do not edit by hand: changes are overwritten when this file is regenerated.\n\
+                     Regenerate with `spklr generate`, or see https://moonrepo.dev/docs/config for the options."
+                ),
+                Some("End of {{ output.file }}"),
+            ),
+            Theme::Compact => (Some("Generated by spklr {{ spklr.version }}. Do not edit."), None),
+            Theme::MinimalComments => (None, None),
+            Theme::AnnotationsHeavy => (
+                Some(
+                    "@generated by spklr {{ spklr.version }}\n\
+                     @file {{ output.file }}\n\
+                     @see https://moonrepo.dev/docs/config\n\
+                     @readonly Do not edit by hand; regenerate with `spklr generate`."
+                ),
+                Some("@end {{ output.file }}"),
+            ),
+        };
+        TemplateConfig {
+            header: header.map(str::to_string),
+            footer: footer.map(str::to_string),
+            ..Default::default()
+        }
+    }
+}
+
+impl Display for Theme {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Theme::Documented => write!(f, "documented"),
+            Theme::Compact => write!(f, "compact"),
+            Theme::MinimalComments => write!(f, "minimal-comments"),
+            Theme::AnnotationsHeavy => write!(f, "annotations-heavy"),
+        }
+    }
+}
+
+impl FromStr for Theme {
+    type Err = CliError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Theme::all()
+            .iter()
+            .copied()
+            .find(|theme| theme.to_string().eq_ignore_ascii_case(s))
+            .ok_or_else(|| CliError::UnsupportedFormat {
+                format: s.to_string(),
+                available: vec!["documented", "compact", "minimal-comments", "annotations-heavy"],
+            })
+    }
+}
+
+/// Set the `--theme` for the rest of the run; it overrides `theme` in `spklr.toml`
+pub fn set_cli_theme(theme: Option<Theme>) {
+    if let Ok(mut cli_theme) = CLI_THEME.lock() {
+        *cli_theme = theme;
+    }
+}
+
+pub(super) fn cli_theme() -> Option<Theme> {
+    CLI_THEME.lock().ok().and_then(|theme| *theme)
+}
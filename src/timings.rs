@@ -0,0 +1,178 @@
+//! Timings Module for Space Pklr
+//!
+//! Collects per-phase durations for `--timings`, so performance reports come with
+//! real numbers. Phases are recorded from wherever the work happens through a
+//! process-wide collector; nothing is recorded unless timings were enabled.
+
+use serde::Serialize;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+/// A measured phase of a command
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Phase {
+    /// Reading Moon config types into schemas
+    Introspection,
+    /// Loading and parsing input configs
+    Conversion,
+    /// Rendering output text
+    Render,
+    /// Writing files
+    Write,
+    /// Running the Pkl CLI
+    PklEval,
+}
+
+impl Phase {
+    pub fn all() -> [Phase; 5] {
+        [Phase::Introspection, Phase::Conversion, Phase::Render, Phase::Write, Phase::PklEval]
+    }
+}
+
+impl std::fmt::Display for Phase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Phase::Introspection => write!(f, "introspection"),
+            Phase::Conversion => write!(f, "conversion"),
+            Phase::Render => write!(f, "render"),
+            Phase::Write => write!(f, "write"),
+            Phase::PklEval => write!(f, "pkl eval"),
+        }
+    }
+}
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static RECORDED: Mutex<Vec<(Phase, Duration)>> = Mutex::new(Vec::new());
+
+/// Start collecting timings
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Record a finished phase
+pub fn record(phase: Phase, elapsed: Duration) {
+    if !is_enabled() {
+        return;
+    }
+    if let Ok(mut recorded) = RECORDED.lock() {
+        recorded.push((phase, elapsed));
+    }
+}
+
+/// Time a synchronous block
+pub fn time<T>(phase: Phase, f: impl FnOnce() -> T) -> T {
+    let _timer = Timer::start(phase);
+    f()
+}
+
+/// Records its phase when dropped; use for async or early-returning code
+pub struct Timer {
+    phase: Phase,
+    started: Instant,
+}
+
+impl Timer {
+    pub fn start(phase: Phase) -> Self {
+        Self {
+            phase,
+            started: Instant::now(),
+        }
+    }
+}
+
+impl Drop for Timer {
+    fn drop(&mut self) {
+        record(self.phase, self.started.elapsed());
+    }
+}
+
+/// Total for one phase
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PhaseTiming {
+    pub phase: Phase,
+    pub calls: usize,
+    pub duration_ms: f64,
+}
+
+/// Everything `--timings` reports
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimingsReport {
+    pub phases: Vec<PhaseTiming>,
+    pub total_ms: f64,
+    /// Peak resident set size, where the platform exposes it
+    pub peak_rss_bytes: Option<u64>,
+}
+
+impl TimingsReport {
+    /// Summarize what's been recorded, with `total` as the wall time of the whole command
+    pub fn collect(total: Duration) -> Self {
+        let recorded = RECORDED.lock().map(|r| r.clone()).unwrap_or_default();
+        let phases = Phase::all()
+            .into_iter()
+            .filter_map(|phase| {
+                let durations: Vec<Duration> = recorded
+                    .iter()
+                    .filter(|(p, _)| *p == phase)
+                    .map(|(_, d)| *d)
+                    .collect();
+                (!durations.is_empty()).then(|| PhaseTiming {
+                    phase,
+                    calls: durations.len(),
+                    duration_ms: millis(durations.iter().sum()),
+                })
+            })
+            .collect();
+
+        Self {
+            phases,
+            total_ms: millis(total),
+            peak_rss_bytes: peak_rss_bytes(),
+        }
+    }
+
+    /// Render the breakdown table printed by `--timings`
+    pub fn render_table(&self) -> String {
+        let mut out = String::from("⏱️  Timings\n");
+        out.push_str(&format!("  {:<15} {:>6} {:>12}\n", "phase", "calls", "duration"));
+        for timing in &self.phases {
+            out.push_str(&format!(
+                "  {:<15} {:>6} {:>10.1}ms\n",
+                timing.phase.to_string(),
+                timing.calls,
+                timing.duration_ms
+            ));
+        }
+        out.push_str(&format!("  {:<15} {:>6} {:>10.1}ms\n", "total", "", self.total_ms));
+        match self.peak_rss_bytes {
+            Some(bytes) => out.push_str(&format!("  peak RSS: {:.1} MiB\n", bytes as f64 / (1024.0 * 1024.0))),
+            None => out.push_str("  peak RSS: n/a\n"),
+        }
+        out
+    }
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+/// Peak RSS from `/proc/self/status` (`VmHWM`)
+#[cfg(target_os = "linux")]
+fn peak_rss_bytes() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmHWM:"))?;
+    let kib: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kib * 1024)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn peak_rss_bytes() -> Option<u64> {
+    None
+}
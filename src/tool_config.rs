@@ -0,0 +1,232 @@
+//! Tool Config Module for Space Pklr
+//!
+//! Loads `spklr.toml`, the configuration for spklr itself (as opposed to the Moon
+//! configs it processes). Relative paths in the file are resolved against the
+//! directory that contains it.
+//!
+//! ```toml
+//! [[plugins]]
+//! name = "hcl"
+//! path = "plugins/hcl.wasm"
+//! kind = "renderer"
+//!
+//! [download]
+//! mirrors = ["https://artifacts.example.com/pkl"]
+//!
+//! [network]
+//! max_attempts = 4
+//! timeout_secs = 30
+//!
+//! [network.operations.link-check]
+//! max_attempts = 1
+//!
+//! [redaction]
+//! paths = ["RegistryConfig.host"]
+//!
+//! [templates]
+//! header = "Generated by spklr {{ spklr.version }}. Do not edit."
+//!
+//! [generator]
+//! type_prefix = "Moon"
+//!
+//! [[toolchain_plugins]]
+//! id = "deno"
+//! schema = "plugins/deno.schema.json"
+//!
+//! [cache]
+//! max_size = "128MiB"
+//! ```
+//!
+//! With `[env] interpolate = true`, string values can read environment variables as
+//! `${env:NAME}`; see [`crate::interpolation`].
+
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+use crate::network::{NetworkConfig, Operation, RetryPolicy};
+use crate::types::CliError;
+
+/// File name of the tool config
+pub const TOOL_CONFIG_FILE: &str = "spklr.toml";
+
+/// Kind of capability a WASM plugin provides
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PluginKind {
+    /// Renders a config value into a custom output format
+    Renderer,
+    /// Decodes a custom input format into a config value
+    Codec,
+}
+
+/// A WASM plugin declared in `spklr.toml`
+#[derive(Debug, Clone, Deserialize)]
+pub struct PluginConfig {
+    /// Format name used to select the plugin (e.g. `--to-plugin hcl`)
+    pub name: String,
+    /// Path to the `.wasm` module
+    pub path: PathBuf,
+    pub kind: PluginKind,
+}
+
+/// `[download]` settings for fetching the Pkl CLI
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct DownloadConfig {
+    /// Base URLs mirroring the Pkl GitHub release layout (`<mirror>/<version>/<archive>`)
+    pub mirrors: Vec<String>,
+    /// Attempts per URL before falling back to the next mirror
+    ///
+    /// Superseded by `[network.operations.download]`, which wins when both are set.
+    pub max_attempts: Option<u32>,
+    /// Initial retry delay in seconds, doubled after each failure
+    ///
+    /// Superseded by `[network.operations.download]`, which wins when both are set.
+    pub backoff_secs: Option<u64>,
+}
+
+impl DownloadConfig {
+    /// Configured mirrors followed by any from `SPKLR_PKL_MIRRORS` (comma-separated)
+    pub fn all_mirrors(&self) -> Vec<String> {
+        let mut mirrors = self.mirrors.clone();
+        if let Ok(env_mirrors) = std::env::var("SPKLR_PKL_MIRRORS") {
+            mirrors.extend(
+                env_mirrors
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|m| !m.is_empty())
+                    .map(str::to_string),
+            );
+        }
+        mirrors
+    }
+
+    /// `policy` with the legacy `[download]` retry settings applied
+    pub fn apply(&self, mut policy: RetryPolicy) -> RetryPolicy {
+        if let Some(max_attempts) = self.max_attempts {
+            policy.max_attempts = max_attempts.max(1);
+        }
+        if let Some(backoff_secs) = self.backoff_secs {
+            policy.initial_backoff = std::time::Duration::from_secs(backoff_secs);
+        }
+        policy
+    }
+}
+
+/// `[ci]` settings for the `spklr ci` commands
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct CiConfig {
+    /// Token for `spklr ci comment`, below `--github-token` and above `GITHUB_TOKEN`;
+    /// best given as `${env:NAME}` rather than committed
+    pub github_token: Option<String>,
+}
+
+/// Parsed `spklr.toml`
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ToolConfig {
+    pub env: crate::interpolation::EnvConfig,
+    pub plugins: Vec<PluginConfig>,
+    pub download: DownloadConfig,
+    pub network: NetworkConfig,
+    pub redaction: crate::redaction::RedactionConfig,
+    pub templates: crate::templates::TemplateConfig,
+    pub generator: crate::pkl_schema::GeneratorConfig,
+    pub toolchain_plugins: Vec<crate::pkl_schema::toolchain_plugins::ToolchainPluginConfig>,
+    pub ci: CiConfig,
+    pub cache: crate::eval_cache::CacheConfig,
+}
+
+impl ToolConfig {
+    /// Load a tool config file, interpolating environment variables if it opts in
+    pub fn load(path: &Path) -> Result<Self, CliError> {
+        Self::load_with_env(path, &|name| std::env::var(name).ok())
+    }
+
+    /// Load a tool config file, looking up `${env:...}` references with `lookup`
+    pub fn load_with_env(path: &Path, lookup: &impl Fn(&str) -> Option<String>) -> Result<Self, CliError> {
+        let content = std::fs::read_to_string(path).map_err(|e| CliError::IoError {
+            context: format!("Reading tool config: {}", path.display()),
+            source: e,
+        })?;
+
+        let invalid = |e: Box<dyn std::error::Error + Send + Sync>| CliError::ValidationError { source: e };
+        let mut value: toml::Value = toml::from_str(&content).map_err(|e| invalid(Box::new(e)))?;
+        let env: crate::interpolation::EnvConfig = match value.get("env") {
+            Some(env) => env.clone().try_into().map_err(|e| invalid(Box::new(e)))?,
+            None => Default::default(),
+        };
+        if env.interpolate {
+            crate::interpolation::interpolate(&mut value, &env, lookup)
+                .map_err(|e| invalid(format!("{}: {}", path.display(), e).into()))?;
+        } else if crate::interpolation::has_references(&value) {
+            tracing::warn!(
+                "{}: ${{env:...}} references are used as written; set `interpolate = true` under [env] to expand them",
+                path.display()
+            );
+        }
+        let mut config: ToolConfig = value.try_into().map_err(|e| invalid(Box::new(e)))?;
+
+        let base = path.parent().unwrap_or(Path::new("."));
+        for plugin in &mut config.plugins {
+            if plugin.path.is_relative() {
+                plugin.path = base.join(&plugin.path);
+            }
+        }
+        for plugin in &mut config.toolchain_plugins {
+            if let Some(schema) = plugin.schema.as_mut().filter(|schema| schema.is_relative()) {
+                *schema = base.join(&*schema);
+            }
+        }
+        if let Some(dir) = config.templates.template_dir.as_mut().filter(|dir| dir.is_relative()) {
+            *dir = base.join(&*dir);
+        }
+        if let Some(dir) = config.cache.dir.as_mut().filter(|dir| dir.is_relative()) {
+            *dir = base.join(&*dir);
+        }
+        for name in config.network.unknown_operations() {
+            tracing::warn!("{}: unknown network operation '{}' is ignored", path.display(), name);
+        }
+
+        Ok(config)
+    }
+
+    /// Load `spklr.toml` from the current directory, or the defaults if there isn't one
+    ///
+    /// Hermetic runs never read it.
+    pub fn discover() -> Result<Self, CliError> {
+        if crate::hermetic::is_enabled() {
+            return Ok(Self::default());
+        }
+        let path = PathBuf::from(TOOL_CONFIG_FILE);
+        if path.exists() {
+            Self::load(&path)
+        } else {
+            Ok(Self::default())
+        }
+    }
+
+    /// Find a plugin by name and kind
+    pub fn plugin(&self, name: &str, kind: PluginKind) -> Option<&PluginConfig> {
+        self.plugins
+            .iter()
+            .find(|plugin| plugin.kind == kind && plugin.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Retry policy for `operation`, with `[network]` (and for downloads, `[download]`) and
+    /// `--timeout` applied
+    pub fn retry_policy(&self, operation: Operation) -> RetryPolicy {
+        let mut policy = self.network.defaults.apply(operation.defaults());
+        if operation == Operation::Download {
+            policy = self.download.apply(policy);
+        }
+        if let Some(settings) = self.network.operations.get(operation.name()) {
+            policy = settings.apply(policy);
+        }
+        if let Some(timeout) = crate::network::cli_timeout() {
+            policy.timeout = Some(timeout.as_duration()).filter(|timeout| !timeout.is_zero());
+        }
+        policy
+    }
+}
@@ -32,3 +32,179 @@ impl std::fmt::Display for CliFlag {
         }
     }
 }
+
+/// Units a [`HumanDuration`] can be written in, in milliseconds
+const DURATION_UNITS: [(&str, u64); 4] = [("ms", 1), ("s", 1_000), ("m", 60_000), ("h", 3_600_000)];
+
+/// Units a [`ByteSize`] can be written in, in bytes
+const SIZE_UNITS: [(&str, u64); 7] = [
+    ("B", 1),
+    ("KB", 1_000),
+    ("MB", 1_000_000),
+    ("GB", 1_000_000_000),
+    ("KiB", 1 << 10),
+    ("MiB", 1 << 20),
+    ("GiB", 1 << 30),
+];
+
+/// A duration flag like `90s`, `500ms`, `2m`, or `1h`; a bare number is seconds
+///
+/// Displays in the unit it was given in, so errors and reports echo the user's value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HumanDuration {
+    millis: u64,
+    unit: usize,
+}
+
+impl HumanDuration {
+    pub fn as_duration(&self) -> std::time::Duration {
+        std::time::Duration::from_millis(self.millis)
+    }
+
+    /// `duration` written in this value's unit, e.g. a measured time next to a limit
+    pub fn format(&self, duration: std::time::Duration) -> String {
+        format_quantity(duration.as_millis() as u64, DURATION_UNITS[self.unit])
+    }
+}
+
+impl From<std::time::Duration> for HumanDuration {
+    /// In the largest unit that divides it evenly
+    fn from(duration: std::time::Duration) -> Self {
+        let millis = duration.as_millis() as u64;
+        Self {
+            millis,
+            unit: exact_unit(millis, &DURATION_UNITS),
+        }
+    }
+}
+
+impl FromStr for HumanDuration {
+    type Err = CliError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let (millis, unit) = parse_quantity(s, &DURATION_UNITS, 1, "duration", "90s, 500ms, 2m, 1h")?;
+        Ok(Self { millis, unit })
+    }
+}
+
+impl std::fmt::Display for HumanDuration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&format_quantity(self.millis, DURATION_UNITS[self.unit]))
+    }
+}
+
+/// A size flag like `2MB`, `512KiB`, or `16384`; a bare number is bytes
+///
+/// `KB`/`MB`/`GB` are powers of 1000 and `KiB`/`MiB`/`GiB` powers of 1024; units are
+/// case-insensitive. Displays in the unit it was given in. Config files may give a size
+/// as a number of bytes or as a string in the same syntax.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ByteSize {
+    bytes: u64,
+    unit: usize,
+}
+
+impl ByteSize {
+    pub fn bytes(&self) -> u64 {
+        self.bytes
+    }
+
+    /// `bytes` written in this value's unit, e.g. a file's size next to a limit
+    pub fn format(&self, bytes: u64) -> String {
+        format_quantity(bytes, SIZE_UNITS[self.unit])
+    }
+}
+
+impl From<u64> for ByteSize {
+    /// In the largest unit that divides it evenly
+    fn from(bytes: u64) -> Self {
+        Self {
+            bytes,
+            unit: exact_unit(bytes, &SIZE_UNITS[..4]),
+        }
+    }
+}
+
+impl FromStr for ByteSize {
+    type Err = CliError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let (bytes, unit) = parse_quantity(s, &SIZE_UNITS, 0, "size", "2MB, 512KiB, 16384")?;
+        Ok(Self { bytes, unit })
+    }
+}
+
+impl std::fmt::Display for ByteSize {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&format_quantity(self.bytes, SIZE_UNITS[self.unit]))
+    }
+}
+
+impl serde::Serialize for ByteSize {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for ByteSize {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        #[derive(serde::Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Bytes(u64),
+            Text(String),
+        }
+        match Raw::deserialize(deserializer)? {
+            Raw::Bytes(bytes) => Ok(ByteSize::from(bytes)),
+            Raw::Text(text) => text.parse().map_err(serde::de::Error::custom),
+        }
+    }
+}
+
+/// Parse `<number><unit>` into base units and the index of the unit used
+///
+/// Numbers may have a fraction (`1.5MB`) as long as the result is a whole base unit.
+fn parse_quantity(
+    s: &str,
+    units: &[(&str, u64)],
+    default_unit: usize,
+    what: &str,
+    examples: &str,
+) -> std::result::Result<(u64, usize), CliError> {
+    let invalid = || CliError::Generic(format!("Invalid {} '{}'; expected e.g. {}", what, s, examples));
+    let trimmed = s.trim();
+    let split = trimmed
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(trimmed.len());
+    let (number, unit) = trimmed.split_at(split);
+    let unit = match unit.trim() {
+        "" => default_unit,
+        unit => units
+            .iter()
+            .position(|(name, _)| name.eq_ignore_ascii_case(unit))
+            .ok_or_else(invalid)?,
+    };
+    let number: f64 = number.parse().map_err(|_| invalid())?;
+    let value = number * units[unit].1 as f64;
+    if !value.is_finite() || value > u64::MAX as f64 || value.fract() != 0.0 {
+        return Err(invalid());
+    }
+    Ok((value as u64, unit))
+}
+
+/// `value` (in base units) in `unit`, with at most two decimals
+fn format_quantity(value: u64, (name, factor): (&str, u64)) -> String {
+    if value.is_multiple_of(factor) {
+        return format!("{}{}", value / factor, name);
+    }
+    let scaled = format!("{:.2}", value as f64 / factor as f64);
+    format!("{}{}", scaled.trim_end_matches('0').trim_end_matches('.'), name)
+}
+
+/// Index of the largest unit that divides `value` evenly
+fn exact_unit(value: u64, units: &[(&str, u64)]) -> usize {
+    units
+        .iter()
+        .rposition(|(_, factor)| value > 0 && value.is_multiple_of(*factor))
+        .unwrap_or(0)
+}
@@ -112,12 +112,209 @@ pub enum CliError {
         source: Box<dyn std::error::Error + Send + Sync>,
     },
 
+    /// Lint found policy violations at error severity
+    #[error("{count} policy violation(s) found")]
+    #[diagnostic(
+        code(cli::policy_violations),
+        help("Fix the reported configs, or exempt them with a `# spklr:allow(<rule>) <reason>` comment")
+    )]
+    PolicyViolations { count: usize },
+
+    /// Unknown subcommand with no matching `spklr-<name>` executable
+    #[error("No such command: {name}")]
+    #[diagnostic(
+        code(cli::unknown_command),
+        help("{help}")
+    )]
+    ExternalCommandNotFound { name: String, help: String },
+
+    /// A conversion transform script failed to compile or run
+    #[error("Transform script failed: {script}")]
+    #[diagnostic(
+        code(cli::script_error),
+        help("{message}")
+    )]
+    ScriptError { script: PathBuf, message: String },
+
+    /// A Pkl config the native parser read needs the Pkl CLI to evaluate
+    #[error("{construct} at line {line}, column {column} needs the Pkl CLI to evaluate")]
+    #[diagnostic(
+        code(cli::pkl_needs_evaluation),
+        help("Without the Pkl CLI only literal values can be read; install it with: spklr pkl-me pkl")
+    )]
+    PklNeedsEvaluation { construct: String, line: usize, column: usize },
+
+    /// Generated files no longer match their recorded checksums
+    #[error("{count} generated file(s) changed since generation")]
+    #[diagnostic(
+        code(cli::checksum_mismatch),
+        help("Generated files must not be edited by hand; re-run `spklr generate` to refresh them and SHA256SUMS")
+    )]
+    ChecksumMismatch { count: usize },
+
+    /// `spklr pkl-me --report` found incompatible versions
+    #[error("{count} compatibility check(s) failed")]
+    #[diagnostic(
+        code(cli::compatibility_errors),
+        help("Follow the steps under \"To fix\" in the report")
+    )]
+    CompatibilityErrors { count: usize },
+
+    /// `spklr self-update` couldn't install a release
+    #[error("Self update failed: {reason}")]
+    #[diagnostic(
+        code(cli::self_update_failed),
+        help("Download spklr from https://github.com/knitli/space-pklr/releases, or run `cargo install space-pklr`")
+    )]
+    SelfUpdateFailed { reason: String },
+
+    /// `spklr doctor` found tools that don't match the project's pins
+    #[error("{count} tool(s) don't match the project's pinned versions")]
+    #[diagnostic(
+        code(cli::tool_pin_mismatch),
+        help("Run `mise install` (or `asdf install`) so the active shims match .mise.toml/.tool-versions")
+    )]
+    ToolPinMismatch { count: usize },
+
+    /// `spklr diff --exit-code` found schema changes
+    #[error("{count} schema change(s) found")]
+    #[diagnostic(
+        code(cli::schema_changes),
+        help("Review the changes above; drop --exit-code to report them without failing")
+    )]
+    SchemaChanges { count: usize },
+
+    /// `spklr diff --check-compat` found changes that can break existing configs
+    #[error("{count} breaking schema change(s) found")]
+    #[diagnostic(
+        code(cli::breaking_schema_changes),
+        help("Configs valid against the old schema can fail against the new one; release these changes in a new major version")
+    )]
+    BreakingSchemaChanges { count: usize },
+
+    /// `spklr self test` renderings differ from the embedded golden fixtures
+    #[error("{count} self-test fixture(s) rendered differently")]
+    #[diagnostic(
+        code(cli::self_test_failed),
+        help("This environment renders output differently from the build's golden copies; check line-ending, locale, and encoding settings before trusting converted configs")
+    )]
+    SelfTestFailed { count: usize },
+
+    /// `spklr corpus run` found configs that failed to convert or validate
+    #[error("{failed} corpus config(s) failed and {unavailable} repositories couldn't be checked")]
+    #[diagnostic(
+        code(cli::corpus_failures),
+        help("Check the errors listed above; a config that passed in the previous release points at a converter regression")
+    )]
+    CorpusFailures { failed: usize, unavailable: usize },
+
+    /// `spklr convert FILES...` couldn't convert some of the files
+    #[error("{failed} of {total} file(s) failed to convert")]
+    #[diagnostic(
+        code(cli::batch_conversion_failures),
+        help("Fix the files marked ❌ above and run again; files that passed were converted")
+    )]
+    BatchConversionFailures { failed: usize, total: usize },
+
+    /// `spklr docgen` checks found problems in the documentation strings
+    #[error("{count} documentation issue(s) found")]
+    #[diagnostic(
+        code(cli::doc_issues),
+        help("Fix the upstream doc comments (typos, dead links), or accept project terms in spklr-dictionary.txt")
+    )]
+    DocIssues { count: usize },
+
+    /// Generated schemas are documented below the `--doc-coverage` threshold
+    #[error("{count} config type(s) below {threshold}% documentation coverage")]
+    #[diagnostic(
+        code(cli::doc_coverage),
+        help("Document the listed properties upstream in moon_config, or lower --doc-coverage")
+    )]
+    DocCoverageBelowThreshold { count: usize, threshold: f64 },
+
+    /// A header or footer template failed to render
+    #[error("Template '{template}' failed to render")]
+    #[diagnostic(
+        code(cli::template_error),
+        help("{message}")
+    )]
+    TemplateError { template: String, message: String },
+
+    /// Converting the output back to the source format didn't reproduce the input
+    #[error("Round trip changed {count} value(s)")]
+    #[diagnostic(
+        code(cli::roundtrip_mismatch),
+        help("{differences}")
+    )]
+    RoundTripMismatch { count: usize, differences: String },
+
+    /// `--env-policy error` found an environment variable token in a converted value
+    #[error("{path} uses environment variable {token}")]
+    #[diagnostic(
+        code(cli::env_token),
+        help("Use --env-policy preserve to keep the token for Moon to substitute, or read-env to read it when the Pkl is evaluated")
+    )]
+    EnvTokenFound { path: String, token: String },
+
+    /// A write or network access was attempted under --read-only
+    #[error("Refusing to {action} in read-only mode")]
+    #[diagnostic(
+        code(cli::read_only),
+        help("--read-only forbids disk writes and network access; omit --output to print results to stdout")
+    )]
+    ReadOnly { action: String },
+
+    /// Another spklr process holds the lock on an output or cache path
+    #[error("Another spklr process holds the lock on {path} ({holder})")]
+    #[diagnostic(
+        code(cli::lock_held),
+        help("Wait for the other run to finish, or pass --wait to block until the lock is released")
+    )]
+    LockHeld { path: PathBuf, holder: String },
+
+    /// A WASM renderer or codec plugin failed to load or run
+    #[error("Plugin failed: {plugin}")]
+    #[diagnostic(
+        code(cli::plugin_error),
+        help("{message}")
+    )]
+    PluginError { plugin: PathBuf, message: String },
+
+    /// A hand-written Pkl fragment can't be appended to its generated module
+    #[error("Invalid fragment: {fragment}")]
+    #[diagnostic(
+        code(cli::fragment_error),
+        help("{message}")
+    )]
+    FragmentError { fragment: PathBuf, message: String },
+
+    /// Config doesn't conform to its Pkl schema
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    SchemaViolation(Box<SchemaViolation>),
+
     /// Generic error wrapper
     #[error("Error: {0}")]
     #[diagnostic(code(cli::generic_error))]
     Generic(String),
 }
 
+/// A config value rejected by its Pkl schema, with the config source for the label
+#[derive(Error, Diagnostic, Debug)]
+#[error("{} doesn't match its schema: {message}", .path.display())]
+#[diagnostic(
+    code(cli::schema_violation),
+    help("Fix the highlighted value, or regenerate the schema if the config is newer than it")
+)]
+pub struct SchemaViolation {
+    pub path: PathBuf,
+    pub message: String,
+    #[source_code]
+    pub source_code: miette::NamedSource<String>,
+    #[label("{message}")]
+    pub span: Option<miette::SourceSpan>,
+}
+
 /// Result type alias for CLI operations
 pub type Result<T> = miette::Result<T, CliError>;
 
@@ -153,7 +350,12 @@ pub fn ensure_file_exists(path: &PathBuf) -> Result<()> {
 }
 
 /// Helper function to check if output file can be written
+///
+/// `-` is stdout, which can always be written.
 pub fn ensure_output_writable(path: &PathBuf, force: bool) -> Result<()> {
+    if path.as_os_str() == "-" {
+        return Ok(());
+    }
     if path.exists() && !force {
         return Err(CliError::OutputFileExists { path: path.clone() });
     }
@@ -161,6 +363,7 @@ pub fn ensure_output_writable(path: &PathBuf, force: bool) -> Result<()> {
 }
 
 /// Convert from reqwest::Error
+#[cfg(feature = "network")]
 impl From<reqwest::Error> for CliError {
     fn from(err: reqwest::Error) -> Self {
         CliError::NetworkError(err.to_string())
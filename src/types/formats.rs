@@ -77,12 +77,16 @@ impl FromStr for TemplateFormat {
 pub enum SchemaFormat {
     Pkl,
     Json,
+    /// JSON with comments and trailing commas (see [`crate::jsonc`])
+    Jsonc,
+    Yaml,
+    Toml,
     Typescript,
 }
 
 impl SchemaFormat {
     pub fn all_supported_extensions() -> Vec<&'static str> {
-        vec!["pkl", "json", "ts"]
+        vec!["pkl", "json", "jsonc", "json5", "yml", "yaml", "toml", "ts"]
     }
 
     pub fn is_supported_extension(&self, ext: &str) -> bool {
@@ -92,7 +96,9 @@ impl SchemaFormat {
     pub fn to_schematic(&self) -> Format {
         match self {
             SchemaFormat::Pkl => Format::Pkl,
-            SchemaFormat::Json => Format::Json,
+            SchemaFormat::Json | SchemaFormat::Jsonc => Format::Json,
+            SchemaFormat::Yaml => Format::Yaml,
+            SchemaFormat::Toml => Format::Toml,
             SchemaFormat::Typescript => Format::None,
         }
     }
@@ -102,7 +108,10 @@ impl Display for SchemaFormat {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             SchemaFormat::Json => write!(f, "json"),
+            SchemaFormat::Jsonc => write!(f, "jsonc"),
             SchemaFormat::Pkl => write!(f, "pkl"),
+            SchemaFormat::Yaml => write!(f, "yaml"),
+            SchemaFormat::Toml => write!(f, "toml"),
             SchemaFormat::Typescript => write!(f, "typescript"),
         }
     }
@@ -114,11 +123,14 @@ impl FromStr for SchemaFormat {
     fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
         match s.to_lowercase().as_str() {
             "json" | "jsonschema" | "json-schema" | "json_schema" => Ok(SchemaFormat::Json),
+            "jsonc" | "json5" => Ok(SchemaFormat::Jsonc),
             "pkl" | "pklr" | "pcf" => Ok(SchemaFormat::Pkl),
+            "yaml" | "yml" => Ok(SchemaFormat::Yaml),
+            "toml" => Ok(SchemaFormat::Toml),
             "typescript" | "ts" => Ok(SchemaFormat::Typescript),
             _ => Err(CliError::UnsupportedFormat {
                 format: s.to_string(),
-                available: vec!["json", "pkl", "typescript"],
+                available: vec!["json", "jsonc", "pkl", "yaml", "toml", "typescript"],
             }),
         }
     }
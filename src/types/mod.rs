@@ -4,10 +4,10 @@ pub mod formats;
 pub mod moon;
 pub mod pkl;
 
-pub use cli::CliFlag;
-pub use error::{CliError, InternalError, Result, ensure_file_exists, ensure_output_writable, pkl_execution_error};
+pub use cli::{ByteSize, CliFlag, HumanDuration};
+pub use error::{CliError, InternalError, Result, SchemaViolation, ensure_file_exists, ensure_output_writable, pkl_execution_error};
 pub use formats::{SchemaFormat};
-pub use moon::{LoadedConfig, MoonConfig};
+pub use moon::{ConfigValues, LoadedConfig, MoonConfig};
 pub use pkl::{
-    ConfigTranslation, EnumTranslation, OpenStructs, OptionalFormat, PropertyDefault, TypeMap,
+    ConfigHeader, ConfigTranslation, EnumTranslation, OpenStructs, OptionalFormat, PropertyDefault, TypeMap,
 };
@@ -2,7 +2,7 @@ use crate::types::{CliError, InternalError, SchemaFormat, TypeMap};
 use moon_config::{ProjectConfig, TaskConfig, TemplateConfig, ToolchainConfig, WorkspaceConfig};
 use schematic_types::SchemaType;
 use serde_json::Value;
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::str::FromStr;
 
 /// Represents supported Moon config formats.
@@ -42,6 +42,10 @@ pub struct UnknownConfig {
     /// Optional type hint if we can guess what kind of config this might be
     #[serde(skip)]
     pub type_hint: Option<String>,
+
+    /// Source comments by dotted property path, for formats that have them (JSONC)
+    #[serde(skip)]
+    pub comments: BTreeMap<String, String>,
 }
 
 impl Default for UnknownConfig {
@@ -51,6 +55,7 @@ impl Default for UnknownConfig {
             original_format: None,
             type_hint: None,
             name: None,
+            comments: BTreeMap::new(),
         }
     }
 }
@@ -63,6 +68,7 @@ impl UnknownConfig {
             original_format: None,
             type_hint: None,
             name: None,
+            comments: BTreeMap::new(),
         }
     }
 
@@ -73,6 +79,7 @@ impl UnknownConfig {
             original_format: Some(format),
             type_hint: None,
             name: None,
+            comments: BTreeMap::new(),
         }
     }
 }
@@ -221,6 +228,72 @@ impl LoadedConfig {
         }
     }
 
+    /// The project config, if this is one
+    pub fn as_project(&self) -> Option<&ProjectConfig> {
+        match self {
+            LoadedConfig::Project(config) => Some(config),
+            _ => None,
+        }
+    }
+
+    /// The workspace config, if this is one
+    pub fn as_workspace(&self) -> Option<&WorkspaceConfig> {
+        match self {
+            LoadedConfig::Workspace(config) => Some(config),
+            _ => None,
+        }
+    }
+
+    /// The template config, if this is one
+    pub fn as_template(&self) -> Option<&TemplateConfig> {
+        match self {
+            LoadedConfig::Template(config) => Some(config),
+            _ => None,
+        }
+    }
+
+    /// The toolchain config, if this is one
+    pub fn as_toolchain(&self) -> Option<&ToolchainConfig> {
+        match self {
+            LoadedConfig::Toolchain(config) => Some(config),
+            _ => None,
+        }
+    }
+
+    /// The task config, if this is one
+    pub fn as_task(&self) -> Option<&TaskConfig> {
+        match self {
+            LoadedConfig::Task(config) => Some(config),
+            _ => None,
+        }
+    }
+
+    /// The untyped content of a config that didn't load as a Moon type
+    pub fn as_unknown(&self) -> Option<&UnknownConfig> {
+        match self {
+            LoadedConfig::Unknown(config) => Some(config),
+            _ => None,
+        }
+    }
+
+    /// The config as a `serde_json::Value`, for tooling that works on any config type
+    pub fn to_value(&self) -> Result<Value, CliError> {
+        let serialized = match self {
+            LoadedConfig::Project(config) => serde_json::to_value(config),
+            LoadedConfig::Workspace(config) => serde_json::to_value(config),
+            LoadedConfig::Template(config) => serde_json::to_value(config),
+            LoadedConfig::Toolchain(config) => serde_json::to_value(config),
+            LoadedConfig::Task(config) => serde_json::to_value(config),
+            LoadedConfig::Unknown(config) => Ok(config.content.clone()),
+        };
+        serialized.map_err(|e| CliError::ValidationError { source: Box::new(e) })
+    }
+
+    /// Every value in the config with its dotted path (`tasks.build.deps.0`), parents first
+    pub fn iter_values(&self) -> Result<ConfigValues, CliError> {
+        Ok(ConfigValues::new(self.to_value()?))
+    }
+
     /// Get the underlying config value
     pub fn get_config(&self) -> Result<ConfigValue, InternalError> {
         match self {
@@ -237,6 +310,50 @@ impl LoadedConfig {
     }
 }
 
+/// Iterator over the values of a config, from [`LoadedConfig::iter_values`]
+///
+/// Yields `(path, value)` depth first, each object or array before its children. List
+/// items are addressed by index; the root itself isn't yielded.
+#[derive(Debug, Clone)]
+pub struct ConfigValues {
+    stack: Vec<(String, Value)>,
+}
+
+impl ConfigValues {
+    fn new(root: Value) -> Self {
+        let mut values = Self { stack: Vec::new() };
+        values.push_children("", root);
+        values
+    }
+
+    fn push_children(&mut self, path: &str, value: Value) {
+        let child = |key: &str| if path.is_empty() { key.to_string() } else { format!("{}.{}", path, key) };
+        let children: Vec<(String, Value)> = match value {
+            Value::Object(map) => map.into_iter().map(|(key, value)| (child(&key), value)).collect(),
+            Value::Array(items) => items
+                .into_iter()
+                .enumerate()
+                .map(|(index, value)| (child(&index.to_string()), value))
+                .collect(),
+            _ => return,
+        };
+        // Reversed so they pop in document order
+        self.stack.extend(children.into_iter().rev());
+    }
+}
+
+impl Iterator for ConfigValues {
+    type Item = (String, Value);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (path, value) = self.stack.pop()?;
+        if value.is_object() || value.is_array() {
+            self.push_children(&path, value.clone());
+        }
+        Some((path, value))
+    }
+}
+
 impl MoonConfigFormat {
     /// Get supported moon config formats for variants
     fn supported_extensions(&self) -> Vec<&'static str> {
@@ -161,6 +161,57 @@ impl ConfigTranslation {
     }
 }
 
+/// Header pointing a Pkl config converted from YAML/JSON at its generated schema module.
+///
+/// With `Amends` or `Extends`, the output starts with e.g. `amends "../schemas/Project.pkl"`, so Pkl validates it against the schema as soon as it's evaluated. `None` (default) leaves the config standalone.
+#[derive(Default, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ConfigHeader {
+    /// No header; the config is a standalone module.
+    #[default]
+    None,
+    /// `amends "<schema>"`: the config fills in the schema's properties.
+    Amends,
+    /// `extends "<schema>"`: the config may also add properties of its own.
+    Extends,
+}
+
+impl FromStr for ConfigHeader {
+    type Err = CliError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "none" | "no" | "0" | "n" => Ok(ConfigHeader::None),
+            "amends" | "amend" | "a" | "1" => Ok(ConfigHeader::Amends),
+            "extends" | "extend" | "e" | "2" => Ok(ConfigHeader::Extends),
+            _ => Err(CliError::UnsupportedFormat {
+                format: s.to_string(),
+                available: vec!["none", "amends", "extends"],
+            }),
+        }
+    }
+}
+
+impl Display for ConfigHeader {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigHeader::None => write!(f, "none"),
+            ConfigHeader::Amends => write!(f, "amends"),
+            ConfigHeader::Extends => write!(f, "extends"),
+        }
+    }
+}
+
+impl ConfigHeader {
+    /// The Pkl keyword for the header, if there is one.
+    pub fn keyword(&self) -> Option<&'static str> {
+        match self {
+            ConfigHeader::None => None,
+            ConfigHeader::Amends => Some("amends"),
+            ConfigHeader::Extends => Some("extends"),
+        }
+    }
+}
+
 /// Clarifies how a type annotation will be rendered when optional in Pkl
 ///
 /// The choices are `Optional` and `OptionalExplicitNothing`. The default is `Optional`, which is the more idiomatic, but you may want to be explicit.
@@ -0,0 +1,178 @@
+//! Upgrade Module for Space Pklr
+//!
+//! Config migrations between Moon releases, kept as data. Each [`Migration`] records
+//! the release that renamed or moved a setting, the config type it's in, and where it
+//! went. `spklr upgrade-config --to-moon 1.39` plans every migration up to that release
+//! and applies them with the line edits `spklr lint --fix` uses (see [`crate::autofix`]),
+//! so YAML and Pkl configs keep their comments and formatting.
+//!
+//! When a Moon release renames or restructures a setting, add it to [`MIGRATIONS`] in
+//! release order. A `to` without dots renames the last key in place; a dotted `to` is
+//! the full new path.
+
+use serde_json::Value;
+use std::fmt::Display;
+use std::str::FromStr;
+
+use crate::autofix::{Fix, FixKind};
+use crate::config_path::ConfigPath;
+use crate::types::{CliError, MoonConfig};
+
+/// Rule id reported for upgrade edits
+pub const MOON_UPGRADE: &str = "moon-upgrade";
+
+/// A renamed or moved setting
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Migration {
+    /// Moon release that made the change, as `(major, minor)`
+    pub since: (u64, u64),
+    pub config_type: MoonConfig,
+    /// Old path; `*` matches any key
+    pub from: &'static str,
+    /// New key name, or the full new path when it contains a dot
+    pub to: &'static str,
+    pub reason: &'static str,
+}
+
+/// Known migrations, in release order
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        since: (1, 30),
+        config_type: MoonConfig::Workspace,
+        from: "runner",
+        to: "pipeline",
+        reason: "`runner` was renamed to `pipeline`",
+    },
+    Migration {
+        since: (1, 31),
+        config_type: MoonConfig::Project,
+        from: "platform",
+        to: "toolchain.default",
+        reason: "the project platform is now the default toolchain",
+    },
+    Migration {
+        since: (1, 31),
+        config_type: MoonConfig::Project,
+        from: "tasks.*.platform",
+        to: "toolchain",
+        reason: "task `platform` was replaced by `toolchain`",
+    },
+    Migration {
+        since: (1, 31),
+        config_type: MoonConfig::Task,
+        from: "tasks.*.platform",
+        to: "toolchain",
+        reason: "task `platform` was replaced by `toolchain`",
+    },
+    Migration {
+        since: (1, 39),
+        config_type: MoonConfig::Project,
+        from: "type",
+        to: "layer",
+        reason: "project `type` was renamed to `layer`",
+    },
+    Migration {
+        since: (1, 39),
+        config_type: MoonConfig::Workspace,
+        from: "constraints.enforceProjectTypeRelationships",
+        to: "enforceLayerRelationships",
+        reason: "project types are now layers",
+    },
+];
+
+/// A Moon release to upgrade to: `1.39`, or `1.x` for the latest known 1.x
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MoonVersion {
+    pub major: u64,
+    /// `None` for every minor release
+    pub minor: Option<u64>,
+}
+
+impl MoonVersion {
+    /// Whether a change made in `release` is part of this version
+    pub fn includes(&self, release: (u64, u64)) -> bool {
+        match self.minor {
+            Some(minor) => release <= (self.major, minor),
+            None => release.0 <= self.major,
+        }
+    }
+}
+
+impl FromStr for MoonVersion {
+    type Err = CliError;
+
+    /// `1`, `1.x`, `1.39`, or `v1.39.2` (the patch release is ignored)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || CliError::Generic(format!("Invalid moon version '{}': expected e.g. 1.39 or 1.x", s));
+        let mut parts = s.trim().trim_start_matches('v').split('.');
+        let major = parts.next().and_then(|major| major.parse().ok()).ok_or_else(invalid)?;
+        let minor = match parts.next() {
+            None | Some("x" | "*") => None,
+            Some(minor) => Some(minor.parse().map_err(|_| invalid())?),
+        };
+        Ok(Self { major, minor })
+    }
+}
+
+impl Display for MoonVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.minor {
+            Some(minor) => write!(f, "{}.{}", self.major, minor),
+            None => write!(f, "{}.x", self.major),
+        }
+    }
+}
+
+/// One planned edit, and the migration behind it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Step {
+    pub migration: &'static Migration,
+    pub fix: Fix,
+}
+
+impl Display for Step {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (major, minor) = self.migration.since;
+        let to = match &self.fix.kind {
+            FixKind::MoveKey { to } => to.to_string(),
+            FixKind::RenameKey { to } => {
+                let mut segments = self.fix.path.segments().to_vec();
+                if let Some(last) = segments.last_mut() {
+                    *last = to.clone();
+                }
+                segments.join(".")
+            }
+            FixKind::InsertSchema { .. } => String::new(),
+        };
+        write!(f, "moon {}.{}: {} → {} ({})", major, minor, self.fix.path, to, self.migration.reason)
+    }
+}
+
+/// The edits that bring a config of `config_type` up to `target`
+pub fn plan(config_type: MoonConfig, config: &Value, target: &MoonVersion) -> Vec<Step> {
+    let mut steps = Vec::new();
+    for migration in MIGRATIONS {
+        if migration.config_type != config_type || !target.includes(migration.since) {
+            continue;
+        }
+        for (path, value) in ConfigPath::new(migration.from).select(config) {
+            if value.is_null() || path.is_root() {
+                continue;
+            }
+            let kind = if migration.to.contains('.') {
+                FixKind::MoveKey { to: ConfigPath::new(migration.to) }
+            } else {
+                FixKind::RenameKey { to: migration.to.to_string() }
+            };
+            steps.push(Step {
+                migration,
+                fix: Fix {
+                    rule: MOON_UPGRADE,
+                    path,
+                    kind,
+                },
+            });
+        }
+    }
+    steps
+}
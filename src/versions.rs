@@ -0,0 +1,105 @@
+//! Version Manifest Module for Space Pklr
+//!
+//! `spklr-versions.json` records the toolchain behind a project's generated schemas in
+//! one machine-readable place, so Renovate or Dependabot regex managers can bump the
+//! pins. spklr rewrites the tool-owned fields whenever it generates into a project that
+//! has the file; the `pkl` pin is the project's and is only read (it's the default for
+//! `spklr pkl-me install`). The format is documented in `docs/spklr-versions.md`.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::types::CliError;
+
+pub const VERSIONS_FILE: &str = "spklr-versions.json";
+
+/// Current manifest format
+pub const MANIFEST_VERSION: u32 = 1;
+
+/// moon_config release the schemas are generated from (keep in sync with Cargo.lock)
+pub const MOON_CONFIG_CRATE_VERSION: &str = "0.1.5";
+
+/// Contents of `spklr-versions.json`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VersionManifest {
+    pub manifest_version: u32,
+    /// spklr release that last generated the schemas
+    pub spklr: String,
+    /// Pinned Pkl CLI version
+    pub pkl: String,
+    /// moon_config release the schemas describe
+    pub moon_config: String,
+    /// Version of the generated schema package: `<spklr>+moon-config.<moon_config>`
+    pub schema_package: String,
+}
+
+impl VersionManifest {
+    /// Manifest for the running spklr, pinning `pkl`
+    pub fn current(pkl: impl Into<String>) -> Self {
+        let spklr = env!("CARGO_PKG_VERSION").to_string();
+        Self {
+            manifest_version: MANIFEST_VERSION,
+            schema_package: format!("{}+moon-config.{}", spklr, MOON_CONFIG_CRATE_VERSION),
+            spklr,
+            pkl: pkl.into(),
+            moon_config: MOON_CONFIG_CRATE_VERSION.to_string(),
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self, CliError> {
+        let content = std::fs::read_to_string(path).map_err(|e| CliError::IoError {
+            context: format!("Reading version manifest: {}", path.display()),
+            source: e,
+        })?;
+        serde_json::from_str(&content).map_err(|e| CliError::ValidationError {
+            source: Box::new(e),
+        })
+    }
+
+    /// Load `spklr-versions.json` from the current directory, if there is one
+    ///
+    /// Hermetic runs never read it.
+    pub fn discover() -> Result<Option<Self>, CliError> {
+        let path = PathBuf::from(VERSIONS_FILE);
+        if crate::hermetic::is_enabled() || !path.exists() {
+            return Ok(None);
+        }
+        Self::load(&path).map(Some)
+    }
+
+    /// The same pins with the tool-owned fields brought up to the running spklr
+    pub fn refreshed(&self) -> Self {
+        Self::current(self.pkl.clone())
+    }
+
+    pub fn render(&self) -> Result<String, CliError> {
+        serde_json::to_string_pretty(self)
+            .map(|json| json + "\n")
+            .map_err(|e| CliError::Generic(format!("Failed to serialize version manifest: {}", e)))
+    }
+
+    pub fn write(&self, path: &Path) -> Result<(), CliError> {
+        crate::atomic_write::write_atomic_sync(path, self.render()?)
+    }
+}
+
+/// Pkl version pinned in `spklr-versions.json`, if the project has one
+pub fn pinned_pkl_version() -> Option<String> {
+    VersionManifest::discover().ok().flatten().map(|manifest| manifest.pkl)
+}
+
+/// Bring an existing `spklr-versions.json` in the current directory up to date
+///
+/// Returns whether the file changed. Projects without the file are left alone.
+pub fn refresh_existing() -> Result<bool, CliError> {
+    let Some(manifest) = VersionManifest::discover()? else {
+        return Ok(false);
+    };
+    let refreshed = manifest.refreshed();
+    if refreshed == manifest || crate::read_only::is_enabled() {
+        return Ok(false);
+    }
+    refreshed.write(Path::new(VERSIONS_FILE))?;
+    Ok(true)
+}
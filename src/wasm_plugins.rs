@@ -0,0 +1,165 @@
+//! WASM Plugin Host for Space Pklr
+//!
+//! Custom output renderers and input codecs can ship as portable `.wasm` modules
+//! declared in `spklr.toml`, so proprietary formats don't require recompiling spklr.
+//! Plugins run in [wasmtime](https://wasmtime.dev) with no imports: no filesystem,
+//! network, or clock access. Everything they need comes in through their input buffer.
+//!
+//! # Plugin ABI (version 1)
+//!
+//! A plugin module must export:
+//!
+//! | Export | Signature | Purpose |
+//! |---|---|---|
+//! | `memory` | memory | Linear memory shared with the host |
+//! | `spklr_abi_version` | `() -> i32` | Must return `1` |
+//! | `spklr_alloc` | `(len: i32) -> i32` | Allocate `len` bytes, return a pointer |
+//! | `spklr_dealloc` | `(ptr: i32, len: i32)` | Free a buffer returned by `spklr_alloc` or a call below |
+//! | `spklr_render` | `(ptr: i32, len: i32) -> i64` | Renderers only |
+//! | `spklr_decode` | `(ptr: i32, len: i32) -> i64` | Codecs only |
+//!
+//! The host writes the input into a buffer from `spklr_alloc` and calls the entry point.
+//! The `i64` result packs the output buffer as `(ptr << 32) | len`. Input and output are
+//! UTF-8 JSON:
+//!
+//! - `spklr_render` receives the config value and returns `{"output": "<text>"}`
+//! - `spklr_decode` receives `{"input": "<text>"}` and returns `{"value": <config>}`
+//! - either may return `{"error": "<message>"}` instead
+//!
+//! Each call runs on a budget: [`PLUGIN_FUEL`] units of fuel (roughly one per wasm
+//! instruction) and [`PLUGIN_MEMORY_LIMIT`] bytes of linear memory. A plugin that loops
+//! forever or keeps growing its memory fails with a plugin error instead of hanging or
+//! exhausting spklr.
+//!
+//! The plugin host is behind the `wasm_plugins` cargo feature.
+
+use serde_json::Value;
+use std::path::Path;
+
+use crate::types::CliError;
+
+/// ABI version this host implements
+pub const PLUGIN_ABI_VERSION: i32 = 1;
+
+/// Fuel one plugin call may use, roughly the number of wasm instructions it may execute
+pub const PLUGIN_FUEL: u64 = 1_000_000_000;
+
+/// Linear memory one plugin instance may grow to, in bytes
+pub const PLUGIN_MEMORY_LIMIT: usize = 256 * 1024 * 1024;
+
+/// Render a config value with a renderer plugin
+pub fn render_with_plugin(module: &Path, value: &Value) -> Result<String, CliError> {
+    let input = serde_json::to_vec(value)
+        .map_err(|e| plugin_error(module, format!("serializing input: {}", e)))?;
+    let response = call_plugin(module, "spklr_render", &input)?;
+
+    response
+        .get("output")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .ok_or_else(|| plugin_error(module, "response is missing `output`".to_string()))
+}
+
+/// Decode custom input text into a config value with a codec plugin
+pub fn decode_with_plugin(module: &Path, input: &str) -> Result<Value, CliError> {
+    let request = serde_json::to_vec(&serde_json::json!({ "input": input }))
+        .map_err(|e| plugin_error(module, format!("serializing input: {}", e)))?;
+    let mut response = call_plugin(module, "spklr_decode", &request)?;
+
+    response
+        .get_mut("value")
+        .map(Value::take)
+        .ok_or_else(|| plugin_error(module, "response is missing `value`".to_string()))
+}
+
+fn plugin_error(module: &Path, message: String) -> CliError {
+    CliError::PluginError {
+        plugin: module.to_path_buf(),
+        message,
+    }
+}
+
+/// Instantiate a plugin, call one entry point with `input`, and parse its JSON response
+#[cfg(feature = "wasm_plugins")]
+fn call_plugin(module_path: &Path, entry_point: &str, input: &[u8]) -> Result<Value, CliError> {
+    use wasmtime::{Config, Engine, Instance, Module, Store, StoreLimitsBuilder, Trap};
+
+    let err = |message: String| plugin_error(module_path, message);
+    // Running out of fuel or memory is reported as the budget it exceeded
+    let trap_err = |e: wasmtime::Error| match e.downcast_ref::<Trap>() {
+        Some(Trap::OutOfFuel) => err(format!("plugin exceeded its budget of {} fuel units", PLUGIN_FUEL)),
+        _ => err(format!("{:#}", e)),
+    };
+
+    let mut config = Config::new();
+    config.consume_fuel(true);
+    let engine = Engine::new(&config).map_err(|e| err(e.to_string()))?;
+    let module = Module::from_file(&engine, module_path).map_err(|e| err(e.to_string()))?;
+    let limits = StoreLimitsBuilder::new()
+        .memory_size(PLUGIN_MEMORY_LIMIT)
+        .trap_on_grow_failure(true)
+        .build();
+    let mut store = Store::new(&engine, limits);
+    store.limiter(|limits| limits);
+    store.set_fuel(PLUGIN_FUEL).map_err(|e| err(e.to_string()))?;
+    let instance = Instance::new(&mut store, &module, &[]).map_err(trap_err)?;
+
+    let abi_version = instance
+        .get_typed_func::<(), i32>(&mut store, "spklr_abi_version")
+        .map_err(|e| err(format!("missing `spklr_abi_version`: {}", e)))?
+        .call(&mut store, ())
+        .map_err(trap_err)?;
+    if abi_version != PLUGIN_ABI_VERSION {
+        return Err(err(format!(
+            "plugin ABI version {} is not supported (expected {})",
+            abi_version, PLUGIN_ABI_VERSION
+        )));
+    }
+
+    let memory = instance
+        .get_memory(&mut store, "memory")
+        .ok_or_else(|| err("plugin does not export `memory`".to_string()))?;
+    let alloc = instance
+        .get_typed_func::<i32, i32>(&mut store, "spklr_alloc")
+        .map_err(|e| err(e.to_string()))?;
+    let dealloc = instance
+        .get_typed_func::<(i32, i32), ()>(&mut store, "spklr_dealloc")
+        .map_err(|e| err(e.to_string()))?;
+    let entry = instance
+        .get_typed_func::<(i32, i32), i64>(&mut store, entry_point)
+        .map_err(|e| err(format!("plugin does not export `{}`: {}", entry_point, e)))?;
+
+    let input_len = i32::try_from(input.len()).map_err(|_| err("input too large".to_string()))?;
+    let input_ptr = alloc.call(&mut store, input_len).map_err(trap_err)?;
+    memory
+        .write(&mut store, input_ptr as usize, input)
+        .map_err(|e| err(e.to_string()))?;
+
+    let packed = entry.call(&mut store, (input_ptr, input_len)).map_err(trap_err)?;
+    let _ = dealloc.call(&mut store, (input_ptr, input_len));
+
+    let output_ptr = (packed as u64 >> 32) as usize;
+    let output_len = (packed as u64 & 0xffff_ffff) as usize;
+    let mut output = vec![0u8; output_len];
+    memory
+        .read(&store, output_ptr, &mut output)
+        .map_err(|e| err(e.to_string()))?;
+    let _ = dealloc.call(&mut store, (output_ptr as i32, output_len as i32));
+
+    let response: Value = serde_json::from_slice(&output)
+        .map_err(|e| err(format!("plugin returned invalid JSON: {}", e)))?;
+    if let Some(message) = response.get("error").and_then(Value::as_str) {
+        return Err(err(message.to_string()));
+    }
+
+    Ok(response)
+}
+
+/// Instantiate a plugin, call one entry point with `input`, and parse its JSON response
+#[cfg(not(feature = "wasm_plugins"))]
+fn call_plugin(module_path: &Path, _entry_point: &str, _input: &[u8]) -> Result<Value, CliError> {
+    Err(plugin_error(
+        module_path,
+        "spklr was built without the `wasm_plugins` feature".to_string(),
+    ))
+}
@@ -0,0 +1,235 @@
+use space_pklr::atomic_write::{write_atomic, write_atomic_streamed, write_atomic_sync, write_atomic_with};
+
+#[test]
+fn test_write_atomic_sync_replaces_file_without_leftovers() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("schema.json");
+
+    write_atomic_sync(&path, "{}").unwrap();
+    write_atomic_sync(&path, "{\"v\": 2}").unwrap();
+
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "{\"v\": 2}");
+    let entries: Vec<_> = std::fs::read_dir(dir.path()).unwrap().flatten().collect();
+    assert_eq!(entries.len(), 1, "temporary files should be renamed away");
+}
+
+#[test]
+fn test_concurrent_writes_to_one_path_use_separate_temp_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("schema.json");
+
+    std::thread::scope(|scope| {
+        for n in 0..8 {
+            let path = &path;
+            scope.spawn(move || {
+                for _ in 0..20 {
+                    write_atomic_sync(path, format!("{{\"writer\": {}}}", n)).unwrap();
+                }
+            });
+        }
+    });
+
+    let written: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    assert!(written["writer"].is_u64());
+    let entries: Vec<_> = std::fs::read_dir(dir.path()).unwrap().flatten().collect();
+    assert_eq!(entries.len(), 1, "temporary files should be renamed away");
+}
+
+#[test]
+fn test_write_atomic_sync_fails_for_missing_directory() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("missing").join("schema.json");
+
+    assert!(write_atomic_sync(&path, "{}").is_err());
+    assert!(!path.exists());
+}
+
+#[tokio::test]
+async fn test_write_atomic_async() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("moon.pkl");
+
+    write_atomic(&path, "language = \"rust\"\n").await.unwrap();
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "language = \"rust\"\n");
+}
+
+#[tokio::test]
+async fn test_write_atomic_streamed_keeps_old_file_on_failure() {
+    use space_pklr::types::CliError;
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("project.yml");
+    write_atomic_sync(&path, "language: rust\n").unwrap();
+
+    let result = write_atomic_with(&path, |out| {
+        out.write_all(b"language: ").unwrap();
+        Err(CliError::Generic("render failed".to_string()))
+    });
+    assert!(result.is_err());
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "language: rust\n");
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+
+    write_atomic_streamed(&path, |out| {
+        for chunk in ["language: ", "go\n"] {
+            out.write_all(chunk.as_bytes()).unwrap();
+        }
+        Ok(())
+    })
+    .await
+    .unwrap();
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "language: go\n");
+}
+
+#[test]
+fn test_staged_dir_commit_swaps_in_all_files() {
+    use space_pklr::atomic_write::StagedDir;
+
+    let root = tempfile::tempdir().unwrap();
+    let target = root.path().join("schemas");
+    std::fs::create_dir_all(&target).unwrap();
+    std::fs::write(target.join("project.json"), "old").unwrap();
+    std::fs::write(target.join("README.md"), "keep me").unwrap();
+
+    let staged = StagedDir::begin(&target).unwrap();
+    staged.write("project.json", "new").unwrap();
+    staged.write("workspace.json", "new").unwrap();
+
+    // Nothing is visible before commit
+    assert_eq!(std::fs::read_to_string(target.join("project.json")).unwrap(), "old");
+    assert!(!target.join("workspace.json").exists());
+
+    staged.commit().unwrap();
+
+    assert_eq!(std::fs::read_to_string(target.join("project.json")).unwrap(), "new");
+    assert_eq!(std::fs::read_to_string(target.join("workspace.json")).unwrap(), "new");
+    assert_eq!(std::fs::read_to_string(target.join("README.md")).unwrap(), "keep me");
+
+    // Only the target remains, and staging and backup directories are gone from it
+    let entries: Vec<_> = std::fs::read_dir(root.path()).unwrap().flatten().collect();
+    assert_eq!(entries.len(), 1);
+    let mut names: Vec<_> = std::fs::read_dir(&target)
+        .unwrap()
+        .flatten()
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .collect();
+    names.sort();
+    assert_eq!(names, ["README.md", "project.json", "workspace.json"]);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_staged_dir_leaves_unrelated_entries_and_symlinks_alone() {
+    use space_pklr::atomic_write::StagedDir;
+    use std::os::unix::fs::symlink;
+
+    let root = tempfile::tempdir().unwrap();
+    let target = root.path().join("repo");
+    let outside = root.path().join("outside");
+    std::fs::create_dir_all(target.join(".git")).unwrap();
+    std::fs::create_dir_all(&outside).unwrap();
+    std::fs::write(target.join(".git/HEAD"), "ref: refs/heads/main").unwrap();
+    std::fs::write(outside.join("shared.json"), "shared").unwrap();
+    symlink(&outside, target.join("linked-dir")).unwrap();
+    symlink(outside.join("shared.json"), target.join("project.json")).unwrap();
+
+    let staged = StagedDir::begin(&target).unwrap();
+    staged.write("project.json", "new").unwrap();
+    staged.commit().unwrap();
+
+    // The symlink itself is replaced; the file it pointed to is untouched
+    assert!(!target.join("project.json").symlink_metadata().unwrap().file_type().is_symlink());
+    assert_eq!(std::fs::read_to_string(target.join("project.json")).unwrap(), "new");
+    assert_eq!(std::fs::read_to_string(outside.join("shared.json")).unwrap(), "shared");
+
+    // Unrelated entries, including directory symlinks, stay exactly as they were
+    assert!(target.join("linked-dir").symlink_metadata().unwrap().file_type().is_symlink());
+    assert_eq!(std::fs::read_to_string(target.join(".git/HEAD")).unwrap(), "ref: refs/heads/main");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_staged_dir_refuses_writes_through_symlinked_directories() {
+    use space_pklr::atomic_write::StagedDir;
+    use std::os::unix::fs::symlink;
+
+    let root = tempfile::tempdir().unwrap();
+    let target = root.path().join("schemas");
+    let outside = root.path().join("outside");
+    std::fs::create_dir_all(&target).unwrap();
+    std::fs::create_dir_all(&outside).unwrap();
+    symlink(&outside, target.join("types")).unwrap();
+
+    let staged = StagedDir::begin(&target).unwrap();
+    staged.write("project.json", "new").unwrap();
+    staged.write("types/Task.pkl", "new").unwrap();
+    assert!(staged.commit().is_err());
+
+    // Nothing was moved into place
+    assert!(!target.join("project.json").exists());
+    assert_eq!(std::fs::read_dir(&outside).unwrap().count(), 0);
+    assert_eq!(std::fs::read_dir(&target).unwrap().count(), 1);
+}
+
+#[test]
+fn test_staged_dir_dropped_without_commit_rolls_back() {
+    use space_pklr::atomic_write::StagedDir;
+
+    let root = tempfile::tempdir().unwrap();
+    let target = root.path().join("schemas");
+
+    {
+        let staged = StagedDir::begin(&target).unwrap();
+        staged.write("project.json", "new").unwrap();
+    }
+
+    assert!(!target.exists());
+    assert_eq!(std::fs::read_dir(root.path()).unwrap().count(), 0);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_sigint_mid_write_cleans_up_and_exits_130() {
+    use space_pklr::atomic_write::{EXIT_INTERRUPTED, install_interrupt_handler};
+    use std::io::{BufRead, BufReader};
+    use std::process::{Command, Stdio};
+
+    if let Ok(dir) = std::env::var("SPKLR_TEST_SIGINT_DIR") {
+        // Child: block inside a write until the parent interrupts it
+        install_interrupt_handler().unwrap();
+        let path = std::path::Path::new(&dir).join("schema.json");
+        let _ = write_atomic_with(&path, |out| {
+            out.write_all(b"{\"partial\": ").unwrap();
+            out.flush().unwrap();
+            println!("spklr-test: writing");
+            std::thread::sleep(std::time::Duration::from_secs(60));
+            Ok(())
+        });
+        std::process::exit(0);
+    }
+
+    let dir = tempfile::tempdir().unwrap();
+    let mut child = Command::new(std::env::current_exe().unwrap())
+        .args(["test_sigint_mid_write_cleans_up_and_exits_130", "--exact", "--nocapture"])
+        .env("SPKLR_TEST_SIGINT_DIR", dir.path())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    let stdout = BufReader::new(child.stdout.take().unwrap());
+    for line in stdout.lines() {
+        if line.unwrap().contains("spklr-test: writing") {
+            break;
+        }
+    }
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1, "the temporary file should exist mid-write");
+
+    let killed = Command::new("kill")
+        .args(["-INT", &child.id().to_string()])
+        .status()
+        .unwrap();
+    assert!(killed.success());
+
+    let status = child.wait().unwrap();
+    assert_eq!(status.code(), Some(EXIT_INTERRUPTED));
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0, "no partial output should remain");
+}
@@ -0,0 +1,110 @@
+use serde_json::json;
+use space_pklr::autofix::{DEPRECATED_RENAME, KEY_WHITESPACE, MISSING_SCHEMA, apply, detect};
+use space_pklr::config_processor::parse_config_str;
+use space_pklr::policy::Policy;
+use space_pklr::types::{MoonConfig, SchemaFormat};
+use std::path::Path;
+
+fn policy() -> Policy {
+    Policy::from_value(json!({
+        "keyWhitespace": true,
+        "requireSchema": true,
+        "renames": [{ "from": "tasks.*.local", "to": "preset" }]
+    }))
+    .unwrap()
+}
+
+const PROJECT: &str = "# Project settings
+language: rust
+tasks:
+  build:
+    command: cargo build
+    local: true # keep this comment
+  \"lint \":
+    command: cargo clippy
+    deps:
+      - target: ~:build
+        local: false
+";
+
+#[test]
+fn test_detect_and_fix_yaml() {
+    let file = Path::new("moon.yml");
+    let value = parse_config_str(PROJECT, &SchemaFormat::Yaml).unwrap();
+    let fixes = detect(&policy(), file, Some(MoonConfig::Project), &value);
+    let rules: Vec<&str> = fixes.iter().map(|fix| fix.rule).collect();
+    assert_eq!(rules, [MISSING_SCHEMA, KEY_WHITESPACE, DEPRECATED_RENAME]);
+
+    let outcome = apply(file, PROJECT, &fixes);
+    assert!(outcome.skipped.is_empty(), "{:?}", outcome.skipped);
+    assert_eq!(
+        outcome.content,
+        "# Project settings
+$schema: 'https://moonrepo.dev/schemas/project.json'
+language: rust
+tasks:
+  build:
+    command: cargo build
+    preset: true # keep this comment
+  \"lint\":
+    command: cargo clippy
+    deps:
+      - target: ~:build
+        local: false
+"
+    );
+
+    let preview = outcome.preview(file);
+    assert!(preview.contains("@@ line 7 @@ [deprecated-rename] tasks.build.local\n-    local: true # keep this comment\n+    preset: true # keep this comment\n"));
+    assert!(preview.contains("@@ line 2 @@ [missing-schema] $schema\n+$schema:"));
+}
+
+#[test]
+fn test_fix_keys_in_list_items_json_and_pkl() {
+    let value = parse_config_str(PROJECT, &SchemaFormat::Yaml).unwrap();
+    let renames = Policy::from_value(json!({ "renames": [{ "from": "tasks.*.deps.*.local", "to": "optional" }] })).unwrap();
+    let fixes = detect(&renames, Path::new("moon.yml"), None, &value);
+    let outcome = apply(Path::new("moon.yml"), PROJECT, &fixes);
+    assert!(outcome.content.contains("      - target: ~:build\n        optional: false\n"));
+
+    let json_source = "{\n  \"tasks\": {\n    \"build\": {\n      \"local\": true\n    }\n  }\n}\n";
+    let json_value: serde_json::Value = serde_json::from_str(json_source).unwrap();
+    let fixes = detect(&policy(), Path::new("moon.json"), Some(MoonConfig::Project), &json_value);
+    let outcome = apply(Path::new("moon.json"), json_source, &fixes);
+    assert_eq!(
+        outcome.content,
+        "{\n  \"$schema\": \"https://moonrepo.dev/schemas/project.json\",\n  \"tasks\": {\n    \"build\": {\n      \"preset\": true\n    }\n  }\n}\n"
+    );
+
+    let pkl_source = "amends \"Project.pkl\"\n\ntasks {\n  [\"build\"] {\n    local = true // why\n  }\n  [\"test \"] {\n    command = \"cargo test\"\n  }\n}\n";
+    let pkl_value = json!({ "tasks": { "build": { "local": true }, "test ": { "command": "cargo test" } } });
+    let fixes = detect(&policy(), Path::new("moon.pkl"), Some(MoonConfig::Project), &pkl_value);
+    let outcome = apply(Path::new("moon.pkl"), pkl_source, &fixes);
+    assert!(outcome.skipped.is_empty(), "{:?}", outcome.skipped);
+    assert!(outcome.content.contains("    preset = true // why\n"));
+    assert!(outcome.content.contains("  [\"test\"] {\n"));
+}
+
+#[test]
+fn test_conflicting_rename_is_skipped() {
+    let source = "tasks:\n  build:\n    local: true\n    preset: false\n";
+    let value = parse_config_str(source, &SchemaFormat::Yaml).unwrap();
+    let fixes = detect(&policy(), Path::new("moon.yml"), None, &value);
+    let outcome = apply(Path::new("moon.yml"), source, &fixes);
+    assert_eq!(outcome.content, source);
+    assert_eq!(outcome.skipped[0].1, "`preset` is already set");
+}
+
+#[test]
+fn test_fixable_violations_and_exemptions() {
+    let source = "# spklr:allow(missing-schema) generated by a template\nlanguage: rust\ntasks:\n  build:\n    local: true\n";
+    let value = parse_config_str(source, &SchemaFormat::Yaml).unwrap();
+    let file = Path::new("moon.yml");
+    let report = policy().evaluate(file, Some(MoonConfig::Project), &value, source);
+
+    assert_eq!(report.violations.len(), 1);
+    assert_eq!(report.violations[0].rule, DEPRECATED_RENAME);
+    assert!(report.violations[0].message.ends_with("(fixable with --fix)"));
+    assert_eq!(report.exemptions[0].rule, MISSING_SCHEMA);
+    assert_eq!(policy().fixes(file, Some(MoonConfig::Project), &value, source).len(), 1);
+}
@@ -0,0 +1,117 @@
+use space_pklr::batch::{convert_files, expand, matches_segment, output_path};
+use space_pklr::types::SchemaFormat;
+use std::path::{Path, PathBuf};
+
+#[test]
+fn test_matches_segment() {
+    assert!(matches_segment("moon.yml", "moon.yml"));
+    assert!(matches_segment("*.yml", "moon.yml"));
+    assert!(matches_segment("moon.y?l", "moon.yml"));
+    assert!(matches_segment("*", ""));
+    assert!(!matches_segment("*.yml", "moon.yaml"));
+    assert!(!matches_segment("moon.?", "moon.yml"));
+}
+
+#[test]
+fn test_output_path() {
+    let out_dir = Path::new("converted");
+    assert_eq!(
+        output_path(Path::new("./apps/web/moon.yml"), out_dir, &SchemaFormat::Pkl),
+        PathBuf::from("converted/apps/web/moon.pkl")
+    );
+    assert_eq!(
+        output_path(Path::new("../shared/moon.yml"), out_dir, &SchemaFormat::Json),
+        PathBuf::from("converted/shared/moon.json")
+    );
+}
+
+#[tokio::test]
+async fn test_convert_files_collects_failures() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    for (file, content) in [
+        ("apps/web/moon.yml", "language: typescript\n"),
+        ("apps/api/moon.yml", "tasks: [unclosed\n"),
+        ("apps/api/node_modules/dep/moon.yml", "language: javascript\n"),
+        ("apps/notes.yml", "language: rust\n"),
+    ] {
+        let path = root.join(file);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, content).unwrap();
+    }
+
+    let files = expand(&[format!("{}/**/moon.yml", root.display())]).unwrap();
+    assert_eq!(files, vec![root.join("apps/api/moon.yml"), root.join("apps/web/moon.yml")]);
+    assert!(expand(&[format!("{}/**/missing.yml", root.display())]).is_err());
+
+    let files = vec![root.join("apps/api/moon.yml"), root.join("apps/web/moon.yml"), root.join("apps/notes.yml")];
+    let out_dir = root.join("converted");
+    let outcomes = convert_files(&files, None, &SchemaFormat::Pkl, Some(&out_dir), false, Some(2))
+        .await
+        .unwrap();
+    assert_eq!(outcomes.len(), 3);
+
+    // A parse error doesn't stop the other files
+    assert!(!outcomes[0].passed());
+    assert_eq!(outcomes[0].output, None);
+    assert!(outcomes[1].passed());
+    let output = outcomes[1].output.clone().unwrap();
+    assert!(output.starts_with(&out_dir));
+    assert!(output.ends_with("apps/web/moon.pkl"));
+    assert!(std::fs::read_to_string(&output).unwrap().contains("language = \"typescript\""));
+
+    // No config type can be inferred from `notes.yml`
+    assert_eq!(outcomes[2].config_type, None);
+    assert!(outcomes[2].errors[0].contains("--config-type"));
+}
+
+#[tokio::test]
+async fn test_convert_files_refuses_existing_outputs_without_force() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("moon.yml");
+    std::fs::write(&input, "language: rust\n").unwrap();
+    let out_dir = dir.path().join("converted");
+    let output = output_path(&input, &out_dir, &SchemaFormat::Json);
+    std::fs::create_dir_all(output.parent().unwrap()).unwrap();
+    std::fs::write(&output, "keep me").unwrap();
+
+    let files = vec![input];
+    let outcomes = convert_files(&files, None, &SchemaFormat::Json, Some(&out_dir), false, None)
+        .await
+        .unwrap();
+    assert!(!outcomes[0].passed());
+    assert_eq!(outcomes[0].output, None);
+    assert_eq!(std::fs::read_to_string(&output).unwrap(), "keep me");
+
+    let outcomes = convert_files(&files, None, &SchemaFormat::Json, Some(&out_dir), true, None)
+        .await
+        .unwrap();
+    assert!(outcomes[0].passed(), "{:?}", outcomes[0].errors);
+    let converted: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&output).unwrap()).unwrap();
+    assert_eq!(converted["language"], "rust");
+}
+
+#[tokio::test]
+async fn test_convert_files_evaluates_pkl_inputs() {
+    // Pkl inputs need the Pkl CLI, or the native parser for literal-only configs
+    let has_pkl = matches!(space_pklr::pkl_tooling::find_pkl_executable().await, Ok(Some(_)));
+    if !has_pkl && !cfg!(feature = "native_pkl") {
+        return;
+    }
+
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("apps/web/moon.pkl");
+    std::fs::create_dir_all(input.parent().unwrap()).unwrap();
+    std::fs::write(&input, "language = \"typescript\"\ntags {\n  \"web\"\n}\n").unwrap();
+    let out_dir = dir.path().join("converted");
+
+    let outcomes = convert_files(&[input], None, &SchemaFormat::Yaml, Some(&out_dir), false, None)
+        .await
+        .unwrap();
+    assert!(outcomes[0].passed(), "{:?}", outcomes[0].errors);
+    let output = outcomes[0].output.clone().unwrap();
+    assert!(output.ends_with("apps/web/moon.yml"));
+    let converted = std::fs::read_to_string(&output).unwrap();
+    assert!(converted.contains("language: typescript"), "{}", converted);
+    assert!(converted.contains("- web"), "{}", converted);
+}
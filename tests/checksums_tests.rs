@@ -0,0 +1,62 @@
+use serde_json::json;
+use space_pklr::checksums::{ATTESTATION_FILE, CHECKSUM_FILE, ChecksumManifest, record_generation};
+
+#[test]
+fn test_manifest_round_trip() {
+    let content = "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08  project_schema.json\n\
+                   60303AE22B998861BCE3B28F33EEC1BE758A213C86C93C076DBE9F558C11C752 *types.ts\n";
+    let manifest = ChecksumManifest::parse(content);
+
+    assert_eq!(manifest.entries.len(), 2);
+    assert_eq!(
+        manifest.entries["types.ts"],
+        "60303ae22b998861bce3b28f33eec1be758a213c86c93c076dbe9f558c11c752"
+    );
+    assert_eq!(ChecksumManifest::parse(&manifest.render()), manifest);
+}
+
+#[test]
+fn test_record_and_verify_detects_hand_edits() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("a.json"), "{}").unwrap();
+    std::fs::write(dir.path().join("b.ts"), "export {}").unwrap();
+
+    record_generation(
+        dir.path(),
+        &["a.json".to_string(), "b.ts".to_string()],
+        true,
+        json!({ "command": "generate schema" }),
+    )
+    .unwrap();
+    assert!(dir.path().join(CHECKSUM_FILE).exists());
+
+    let attestation: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(dir.path().join(ATTESTATION_FILE)).unwrap()).unwrap();
+    assert_eq!(attestation["_type"], "https://in-toto.io/Statement/v1");
+    assert_eq!(attestation["subject"].as_array().unwrap().len(), 2);
+
+    let manifest = ChecksumManifest::load(dir.path()).unwrap();
+    assert!(manifest.verify(dir.path()).is_ok());
+
+    std::fs::write(dir.path().join("a.json"), "{\"edited\": true}").unwrap();
+    std::fs::remove_file(dir.path().join("b.ts")).unwrap();
+
+    let report = manifest.verify(dir.path());
+    assert_eq!(report.modified, vec!["a.json"]);
+    assert_eq!(report.missing, vec!["b.ts"]);
+    assert_eq!(report.failure_count(), 2);
+}
+
+#[test]
+fn test_record_keeps_entries_from_earlier_runs() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("a.json"), "{}").unwrap();
+    std::fs::write(dir.path().join("b.json"), "[]").unwrap();
+
+    record_generation(dir.path(), &["a.json".to_string()], false, json!({})).unwrap();
+    record_generation(dir.path(), &["b.json".to_string()], false, json!({})).unwrap();
+
+    let manifest = ChecksumManifest::load(dir.path()).unwrap();
+    assert_eq!(manifest.entries.len(), 2);
+    assert!(!dir.path().join(ATTESTATION_FILE).exists());
+}
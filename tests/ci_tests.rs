@@ -0,0 +1,260 @@
+use space_pklr::ci::bazel::{BazelRuleOptions, render_rules};
+
+#[test]
+fn test_bazel_rules_run_hermetically() {
+    let rules = render_rules(&BazelRuleOptions::default());
+
+    assert!(rules.contains("arguments = [\"--hermetic\"]"));
+    assert!(rules.contains("\"SPKLR_PKL_PATH\": pkl.path"));
+    assert!(rules.contains("spklr_schema = rule("));
+    assert!(rules.contains("spklr_convert = rule("));
+}
+
+#[test]
+fn test_bazel_rules_use_custom_labels() {
+    let rules = render_rules(&BazelRuleOptions {
+        spklr_label: "//tools:spklr".to_string(),
+        pkl_label: "//third_party/pkl".to_string(),
+    });
+
+    assert!(rules.contains("default = \"//tools:spklr\""));
+    assert!(rules.contains("default = \"//third_party/pkl\""));
+    assert!(!rules.contains("@pkl//:pkl"));
+}
+
+#[test]
+fn test_sri_from_hex() {
+    use space_pklr::ci::nix::sri_from_hex;
+
+    assert_eq!(
+        sri_from_hex("e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855").as_deref(),
+        Some("sha256-47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU=")
+    );
+    assert_eq!(sri_from_hex("not-a-digest"), None);
+}
+
+#[test]
+fn test_nix_flake_pins_executables() {
+    use space_pklr::ci::nix::{NIX_SYSTEMS, NixFlakeOptions, PklExecutablePin, render_flake};
+    use space_pklr::pkl_tooling::pkl_release_url;
+
+    let (system, executable) = NIX_SYSTEMS[0];
+    let flake = render_flake(&NixFlakeOptions {
+        spklr_version: "0.1.0".to_string(),
+        pkl_version: "0.28.0".to_string(),
+        pkl_executables: vec![
+            PklExecutablePin {
+                system: system.to_string(),
+                url: pkl_release_url("0.28.0", executable),
+                hash: Some("sha256-47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU=".to_string()),
+            },
+            PklExecutablePin {
+                system: "aarch64-darwin".to_string(),
+                url: "https://example.com/pkl-macos-aarch64".to_string(),
+                hash: None,
+            },
+        ],
+    });
+
+    assert!(flake.contains("pklVersion = \"0.28.0\";"));
+    assert!(flake.contains("inputs.spklr.url = \"github:knitli/space-pklr/v0.1.0\";"));
+    // Pkl releases are bare executables, installed without unpacking
+    assert!(flake.contains(
+        "x86_64-linux = { url = \"https://github.com/apple/pkl/releases/download/0.28.0/pkl-linux-amd64\"; hash = \"sha256-47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU=\"; };"
+    ));
+    assert!(flake.contains("dontUnpack = true;"));
+    assert!(flake.contains("installPhase = \"install -Dm755 $src $out/bin/pkl\";"));
+    assert!(!flake.contains("sourceRoot"));
+    assert!(flake.contains("hash = nixpkgs.lib.fakeHash;"));
+    assert!(flake.contains("SPKLR_PKL_PATH = \"${self.packages.${system}.pkl}/bin/pkl\";"));
+}
+
+#[test]
+fn test_mise_pins_round_trip() {
+    use space_pklr::ci::mise::{parse_mise_toml, parse_tool_versions, render_mise_toml, render_tool_versions};
+
+    let pins = parse_mise_toml(&render_mise_toml("0.1.0", "0.28.0")).unwrap();
+    assert_eq!(pins.spklr.as_deref(), Some("0.1.0"));
+    assert_eq!(pins.pkl.as_deref(), Some("0.28.0"));
+
+    let tool_versions = render_tool_versions("0.1.0", "0.28.0");
+    assert!(tool_versions.contains("\nubi:knitli/space-pklr 0.1.0\n"), "{}", tool_versions);
+    assert!(!tool_versions.contains("asdf plugin add"));
+    let pins = parse_tool_versions(&tool_versions);
+    assert_eq!(pins.spklr.as_deref(), Some("0.1.0"));
+    assert_eq!(pins.pkl.as_deref(), Some("0.28.0"));
+}
+
+#[test]
+fn test_mise_pin_forms() {
+    use space_pklr::ci::mise::parse_mise_toml;
+
+    let pins = parse_mise_toml(
+        r#"
+[tools]
+node = "20"
+"ubi:knitli/space-pklr" = ["0.1.0", "0.0.9"]
+pkl = { version = "0.28.1" }
+"#,
+    )
+    .unwrap();
+    assert_eq!(pins.spklr.as_deref(), Some("0.1.0"));
+    assert_eq!(pins.pkl.as_deref(), Some("0.28.1"));
+}
+
+#[cfg(feature = "cli")]
+#[test]
+fn test_doctor_pin_matching() {
+    use space_pklr::commands::doctor::pin_matches;
+
+    assert!(pin_matches("0.28.0", "0.28.0"));
+    assert!(pin_matches("0.28", "0.28.2"));
+    assert!(pin_matches("latest", "0.29.0"));
+    assert!(pin_matches("v0.1.0", "0.1.0"));
+    assert!(!pin_matches("0.2", "0.28.0"));
+    assert!(!pin_matches("0.28.0", "0.28.1"));
+}
+
+#[test]
+fn test_version_manifest_round_trip() {
+    use space_pklr::versions::{MANIFEST_VERSION, MOON_CONFIG_CRATE_VERSION, VersionManifest};
+
+    let manifest = VersionManifest::current("0.28.0");
+    assert_eq!(manifest.manifest_version, MANIFEST_VERSION);
+    assert_eq!(manifest.moon_config, MOON_CONFIG_CRATE_VERSION);
+    assert_eq!(
+        manifest.schema_package,
+        format!("{}+moon-config.{}", env!("CARGO_PKG_VERSION"), MOON_CONFIG_CRATE_VERSION)
+    );
+
+    let rendered = manifest.render().unwrap();
+    assert!(rendered.contains("\"moonConfig\""));
+    assert!(rendered.contains("\"schemaPackage\""));
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("spklr-versions.json");
+    manifest.write(&path).unwrap();
+    assert_eq!(VersionManifest::load(&path).unwrap(), manifest);
+}
+
+#[test]
+fn test_version_manifest_refresh_keeps_pkl_pin() {
+    use space_pklr::versions::VersionManifest;
+
+    let mut stale = VersionManifest::current("0.27.2");
+    stale.spklr = "0.0.1".to_string();
+    stale.schema_package = "0.0.1+moon-config.0.1.0".to_string();
+
+    let refreshed = stale.refreshed();
+    assert_eq!(refreshed.pkl, "0.27.2");
+    assert_eq!(refreshed.spklr, env!("CARGO_PKG_VERSION"));
+    assert_eq!(refreshed, VersionManifest::current("0.27.2"));
+}
+
+fn sample_findings() -> Vec<space_pklr::ci::annotations::Finding> {
+    use space_pklr::ci::annotations::Finding;
+    use space_pklr::policy::Severity;
+
+    vec![
+        Finding {
+            file: "apps/web/moon.yml".into(),
+            severity: Severity::Error,
+            rule: "tasks-cache".to_string(),
+            message: "tasks.build: missing options.cache".to_string(),
+        },
+        Finding {
+            file: "apps/api/moon.yml".into(),
+            severity: Severity::Warning,
+            rule: "languages".to_string(),
+            message: "language: go | not allowed".to_string(),
+        },
+    ]
+}
+
+#[test]
+fn test_github_annotations() {
+    use space_pklr::ci::annotations::render_github;
+
+    let out = render_github(&sample_findings());
+    let lines: Vec<_> = out.lines().collect();
+    assert_eq!(
+        lines[0],
+        "::error file=apps/web/moon.yml,title=spklr tasks-cache::tasks.build: missing options.cache"
+    );
+    assert!(lines[1].starts_with("::warning file=apps/api/moon.yml,"));
+}
+
+#[test]
+fn test_buildkite_annotation_markdown() {
+    use space_pklr::ci::annotations::render_buildkite;
+
+    let body = render_buildkite("lint", &sample_findings());
+    assert!(body.starts_with("**spklr lint**: 1 error(s), 1 warning(s)"));
+    assert!(body.contains("| ❌ | `apps/web/moon.yml` | `tasks-cache` |"));
+    assert!(body.contains("language: go \\| not allowed"));
+
+    assert_eq!(render_buildkite("check", &[]), "**spklr check**: all checks passed\n");
+}
+
+#[test]
+fn test_gitlab_code_quality_report() {
+    use space_pklr::ci::annotations::render_gitlab;
+
+    let report: serde_json::Value = serde_json::from_str(&render_gitlab(&sample_findings()).unwrap()).unwrap();
+    let issues = report.as_array().unwrap();
+    assert_eq!(issues.len(), 2);
+    assert_eq!(issues[0]["check_name"], "tasks-cache");
+    assert_eq!(issues[0]["severity"], "major");
+    assert_eq!(issues[0]["location"]["path"], "apps/web/moon.yml");
+    assert_eq!(issues[0]["location"]["lines"]["begin"], 1);
+    assert_eq!(issues[1]["severity"], "minor");
+    assert_ne!(issues[0]["fingerprint"], issues[1]["fingerprint"]);
+}
+
+#[test]
+fn test_output_format_parsing() {
+    use space_pklr::ci::annotations::OutputFormat;
+
+    assert_eq!("gitlab".parse::<OutputFormat>().unwrap(), OutputFormat::Gitlab);
+    assert_eq!("Buildkite".parse::<OutputFormat>().unwrap(), OutputFormat::Buildkite);
+    assert!("jenkins".parse::<OutputFormat>().is_err());
+}
+
+#[test]
+fn test_sticky_comment_body_and_lookup() {
+    use serde_json::json;
+    use space_pklr::ci::comment::{find_sticky_comment, render_comment_body, sticky_marker};
+
+    let marker = sticky_marker("schemas");
+    assert_eq!(marker, "<!-- spklr:sticky-comment:schemas -->");
+
+    let body = render_comment_body(&marker, &["## spklr lint ✅ succeeded\n\n".to_string(), "## spklr check ❌ failed\n".to_string()]);
+    assert_eq!(
+        body,
+        "<!-- spklr:sticky-comment:schemas -->\n## spklr lint ✅ succeeded\n\n## spklr check ❌ failed\n"
+    );
+
+    let bot = json!({ "login": "github-actions[bot]", "type": "Bot" });
+    let reviewer = json!({ "login": "octocat", "type": "User" });
+    let comments = vec![
+        json!({ "id": 1, "body": "LGTM", "user": reviewer }),
+        json!({ "id": 2, "body": format!("{}\nold results", sticky_marker("spklr")), "user": bot }),
+        // A reviewer quoting the bot's comment
+        json!({ "id": 3, "body": format!("> {}\n> old results\n\nWhy?", marker), "user": reviewer }),
+        json!({ "id": 4, "body": format!("{}\nold results", marker), "user": reviewer }),
+        json!({ "id": 5, "body": format!("{}\nold results", marker), "user": bot }),
+    ];
+    assert_eq!(find_sticky_comment(&comments, &marker, None), Some(5));
+    assert_eq!(find_sticky_comment(&comments, &marker, Some("github-actions[bot]")), Some(5));
+    assert_eq!(find_sticky_comment(&comments, &marker, Some("octocat")), Some(4));
+    assert_eq!(find_sticky_comment(&comments[..4], &marker, None), None);
+}
+
+#[test]
+fn test_parse_pr_number() {
+    use space_pklr::ci::comment::parse_pr_number;
+
+    assert_eq!(parse_pr_number("refs/pull/42/merge"), Some(42));
+    assert_eq!(parse_pr_number("refs/pull/7/head"), Some(7));
+    assert_eq!(parse_pr_number("refs/heads/main"), None);
+}
@@ -0,0 +1,78 @@
+use space_pklr::cli_defaults::{CliDefaults, LoadedDefaults, PROJECT_DEFAULTS_FILE, discover_from, find_project_file};
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+#[test]
+fn test_load_resolves_relative_paths() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join(PROJECT_DEFAULTS_FILE);
+    std::fs::write(
+        &path,
+        r#"
+output_dir = "schemas"
+template_dir = "/abs/templates"
+module_name = "Deploy"
+include_properties = ["tasks.*"]
+
+[type_mappings]
+TaskConfig = "MoonTask"
+"#,
+    )
+    .unwrap();
+
+    let defaults = CliDefaults::load(&path).unwrap();
+    assert_eq!(defaults.output_dir, Some(dir.path().join("schemas")));
+    assert_eq!(defaults.template_dir, Some(PathBuf::from("/abs/templates")));
+    assert_eq!(defaults.module_name.as_deref(), Some("Deploy"));
+    assert_eq!(defaults.include_properties, vec!["tasks.*"]);
+    assert_eq!(defaults.type_mappings["TaskConfig"], "MoonTask");
+
+    // Typos are reported rather than ignored
+    std::fs::write(&path, "output-dir = \"schemas\"\n").unwrap();
+    assert!(CliDefaults::load(&path).is_err());
+}
+
+#[test]
+fn test_project_overrides_user_and_flags_override_both() {
+    let root = TempDir::new().unwrap();
+    let nested = root.path().join("apps").join("web");
+    std::fs::create_dir_all(&nested).unwrap();
+    std::fs::write(
+        root.path().join(PROJECT_DEFAULTS_FILE),
+        "pkl_version = \"0.28.1\"\nexclude_properties = [\"docker\"]\n[type_mappings]\nTier = \"ServiceTier\"\n",
+    )
+    .unwrap();
+    let user = root.path().join("user.toml");
+    std::fs::write(
+        &user,
+        "pkl_version = \"0.27.0\"\nmodule_name = \"Mine\"\n[type_mappings]\nTier = \"UserTier\"\nTask = \"UserTask\"\n",
+    )
+    .unwrap();
+
+    assert_eq!(find_project_file(&nested), Some(root.path().join(PROJECT_DEFAULTS_FILE)));
+    let loaded = discover_from(&nested, Some(&user)).unwrap();
+    assert_eq!(loaded.sources, vec![user.clone(), root.path().join(PROJECT_DEFAULTS_FILE)]);
+    assert_eq!(loaded.defaults.pkl_version.as_deref(), Some("0.28.1"));
+    assert_eq!(loaded.defaults.module_name.as_deref(), Some("Mine"));
+    assert_eq!(loaded.defaults.exclude_properties, vec!["docker"]);
+    assert_eq!(loaded.defaults.type_mappings["Tier"], "ServiceTier");
+    assert_eq!(loaded.defaults.type_mappings["Task"], "UserTask");
+
+    let flagged = loaded.with_flags(None, Some("0.29.0".to_string()));
+    assert_eq!(flagged.defaults.pkl_version.as_deref(), Some("0.29.0"));
+    assert_eq!(flagged.defaults.template_dir, None);
+}
+
+#[test]
+fn test_unset_defaults_are_left_out_of_toml() {
+    let dir = TempDir::new().unwrap();
+    let loaded = discover_from(dir.path(), Some(&dir.path().join("missing.toml"))).unwrap();
+    assert!(!loaded.sources.contains(&dir.path().join("missing.toml")));
+
+    let defaults = CliDefaults {
+        pkl_version: Some("0.28.1".to_string()),
+        ..Default::default()
+    };
+    assert_eq!(toml::to_string_pretty(&defaults).unwrap(), "pkl_version = \"0.28.1\"\n");
+    assert_eq!(LoadedDefaults::default().with_flags(None, None).defaults, CliDefaults::default());
+}
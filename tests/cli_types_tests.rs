@@ -0,0 +1,53 @@
+use space_pklr::types::{ByteSize, HumanDuration};
+use std::time::Duration;
+
+#[test]
+fn test_parse_durations() {
+    let timeout: HumanDuration = "90s".parse().unwrap();
+    assert_eq!(timeout.as_duration(), Duration::from_secs(90));
+    assert_eq!(timeout.to_string(), "90s");
+
+    assert_eq!("500ms".parse::<HumanDuration>().unwrap().as_duration(), Duration::from_millis(500));
+    assert_eq!("1.5m".parse::<HumanDuration>().unwrap().as_duration(), Duration::from_secs(90));
+    assert_eq!("2H".parse::<HumanDuration>().unwrap().to_string(), "2h");
+    // Bare numbers are seconds
+    assert_eq!("30".parse::<HumanDuration>().unwrap().to_string(), "30s");
+
+    for invalid in ["", "s", "90x", "-5s", "1.0001ms"] {
+        assert!(invalid.parse::<HumanDuration>().is_err(), "{} should be rejected", invalid);
+    }
+}
+
+#[test]
+fn test_parse_sizes() {
+    let size: ByteSize = "2MB".parse().unwrap();
+    assert_eq!(size.bytes(), 2_000_000);
+    assert_eq!(size.to_string(), "2MB");
+
+    assert_eq!("512kib".parse::<ByteSize>().unwrap().bytes(), 512 * 1024);
+    assert_eq!("16384".parse::<ByteSize>().unwrap().to_string(), "16384B");
+    assert!("2XB".parse::<ByteSize>().is_err());
+}
+
+#[test]
+fn test_values_print_in_the_given_unit() {
+    let size: ByteSize = "2MB".parse().unwrap();
+    assert_eq!(size.format(2_500_000), "2.5MB");
+    assert_eq!(size.format(3_141_593), "3.14MB");
+
+    let timeout: HumanDuration = "2m".parse().unwrap();
+    assert_eq!(timeout.format(Duration::from_secs(150)), "2.5m");
+
+    // Values that weren't typed pick the largest unit that fits exactly
+    assert_eq!(HumanDuration::from(Duration::from_secs(120)).to_string(), "2m");
+    assert_eq!(HumanDuration::from(Duration::from_millis(1500)).to_string(), "1500ms");
+    assert_eq!(ByteSize::from(16_000).to_string(), "16KB");
+}
+
+#[test]
+fn test_sizes_deserialize_from_numbers_and_strings() {
+    let sizes: Vec<ByteSize> = serde_json::from_str(r#"[1024, "16KiB"]"#).unwrap();
+    assert_eq!(sizes[0].bytes(), 1024);
+    assert_eq!(sizes[1].bytes(), 16 * 1024);
+    assert_eq!(serde_json::to_string(&sizes[1]).unwrap(), r#""16KiB""#);
+}
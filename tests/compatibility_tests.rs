@@ -0,0 +1,73 @@
+use space_pklr::compatibility::{
+    CheckStatus, check_generated_schemas, check_moon_config, check_pkl_version, check_platform, compare_versions, render_matrix,
+    schema_markers,
+};
+use space_pklr::pkl_tooling::get_recommended_pkl_version;
+use space_pklr::versions::{MOON_CONFIG_CRATE_VERSION, VersionManifest};
+use std::cmp::Ordering;
+
+#[test]
+fn test_compare_versions() {
+    assert_eq!(compare_versions("0.28.10", "0.28.2"), Ordering::Greater);
+    assert_eq!(compare_versions("v0.28.0", "0.28.0"), Ordering::Equal);
+    assert_eq!(compare_versions("0.28.0-rc.1", "0.28.0"), Ordering::Less);
+    assert_eq!(compare_versions("0.9.0", "0.25.0"), Ordering::Less);
+}
+
+#[test]
+fn test_check_pkl_version() {
+    let recommended = get_recommended_pkl_version();
+    assert_eq!(check_pkl_version(Some(recommended), None).status, CheckStatus::Ok);
+
+    let missing = check_pkl_version(None, Some("0.28.1"));
+    assert_eq!(missing.status, CheckStatus::Error);
+    assert!(missing.remediation.unwrap().contains("--version 0.28.1"));
+
+    assert_eq!(check_pkl_version(Some("0.24.0"), None).status, CheckStatus::Error);
+    assert_eq!(check_pkl_version(Some("99.0.0"), None).status, CheckStatus::Warning);
+
+    // The project's pin wins over the tested versions
+    let pinned = check_pkl_version(Some(recommended), Some("0.27"));
+    assert_eq!(pinned.status, CheckStatus::Warning);
+    assert_eq!(pinned.expected.as_deref(), Some("0.27"));
+}
+
+#[test]
+fn test_check_schemas_and_moon_config() {
+    let dir = tempfile::tempdir().unwrap();
+    assert_eq!(check_generated_schemas(dir.path(), None).status, CheckStatus::Unknown);
+    assert_eq!(check_moon_config(None).status, CheckStatus::Unknown);
+
+    let running = env!("CARGO_PKG_VERSION");
+    std::fs::write(dir.path().join("Project.pkl"), format!("/// Generated by spklr {}. Do not edit.\nmodule Project\n", running)).unwrap();
+    assert_eq!(schema_markers(dir.path()).into_iter().collect::<Vec<_>>(), vec![running.to_string()]);
+    assert_eq!(check_generated_schemas(dir.path(), None).status, CheckStatus::Ok);
+
+    // Schemas from a newer spklr can't be trusted with this one
+    std::fs::write(dir.path().join("Task.pkl"), "// @generated by spklr 999.0.0\n").unwrap();
+    let newer = check_generated_schemas(dir.path(), None);
+    assert_eq!(newer.status, CheckStatus::Error);
+    assert_eq!(newer.found.as_deref(), Some(format!("{}, 999.0.0", running).as_str()));
+
+    let mut manifest = VersionManifest::current("0.28.0");
+    assert_eq!(check_moon_config(Some(&manifest)).status, CheckStatus::Ok);
+    manifest.moon_config = "0.0.1".to_string();
+    let stale = check_moon_config(Some(&manifest));
+    assert_eq!(stale.status, CheckStatus::Warning);
+    assert_eq!(stale.found.as_deref(), Some(MOON_CONFIG_CRATE_VERSION));
+}
+
+#[test]
+fn test_render_matrix() {
+    let checks = vec![check_platform("linux", "x86_64"), check_platform("freebsd", "x86_64"), check_pkl_version(None, None)];
+    assert_eq!(checks[0].status, CheckStatus::Ok);
+    assert_eq!(checks[1].status, CheckStatus::Warning);
+
+    let matrix = render_matrix(&checks);
+    let lines: Vec<&str> = matrix.lines().collect();
+    assert!(lines[0].starts_with("Component"));
+    assert!(lines[1].contains("pkl-cli-linux-amd64.tar.gz"));
+    assert!(lines[3].contains("error"));
+    assert!(matrix.contains("To fix:\n  - Platform: "));
+    assert!(matrix.contains("  - Pkl CLI: Install it: spklr pkl-me pkl"));
+}
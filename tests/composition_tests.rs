@@ -0,0 +1,93 @@
+use serde_json::json;
+use space_pklr::composition::{CompositionManifest, SourceKind, compose};
+use space_pklr::pkl_schema::json_schema::to_module;
+use space_pklr::types::MoonConfig;
+use std::path::PathBuf;
+
+#[test]
+fn test_parse_manifest() {
+    let manifest = CompositionManifest::parse(
+        r#"
+[package]
+name = "acme-moon"
+version = "1.2.0"
+base_uri = "package://pkg.acme.dev/acme-moon"
+
+[[sources]]
+moon = "project"
+
+[[sources]]
+json_schema = "schemas/deploy.schema.json"
+name = "Deploy"
+"#,
+    )
+    .unwrap();
+    assert_eq!(manifest.package.common, "Common");
+    assert_eq!(manifest.sources[0].kind().unwrap(), SourceKind::Moon(MoonConfig::Project));
+    assert_eq!(
+        manifest.sources[1].kind().unwrap(),
+        SourceKind::JsonSchema(PathBuf::from("schemas/deploy.schema.json"))
+    );
+    assert_eq!(
+        manifest.pkl_project(),
+        "amends \"pkl:Project\"\n\npackage {\n  name = \"acme-moon\"\n  baseUri = \"package://pkg.acme.dev/acme-moon\"\n  version = \"1.2.0\"\n}\n"
+    );
+
+    let package = "[package]\nname = \"x\"\n";
+    assert!(CompositionManifest::parse(package).is_err());
+    assert!(CompositionManifest::parse(&format!("{}[[sources]]\nmoon = \"all\"\n", package)).is_err());
+    assert!(CompositionManifest::parse(&format!("{}[[sources]]\nmoon = \"project\"\njson_schema = \"a.json\"\n", package)).is_err());
+}
+
+#[test]
+fn test_moon_package() {
+    let manifest = CompositionManifest::moon_package("moon");
+
+    assert_eq!(manifest.package.name, "moon");
+    assert_eq!(manifest.sources.len(), MoonConfig::all_types().len());
+    assert_eq!(manifest.sources[0].kind().unwrap(), SourceKind::Moon(MoonConfig::Project));
+    assert_eq!(manifest.sources[0].name.as_deref(), Some("Project"));
+    assert!(manifest.sources.iter().any(|source| source.name.as_deref() == Some("Workspace")));
+}
+
+#[test]
+fn test_compose_moves_shared_types_to_common() {
+    let owner = json!({ "type": "object", "properties": { "team": { "type": "string" } } });
+    let project = json!({
+        "title": "Project",
+        "type": "object",
+        "properties": {
+            "owner": { "$ref": "#/definitions/Owner" },
+            "tier": { "$ref": "#/definitions/Tier" }
+        },
+        "definitions": { "Owner": owner, "Tier": { "enum": ["a", "b"] } }
+    });
+    let deploy = json!({
+        "title": "Deploy",
+        "type": "object",
+        "properties": {
+            "owner": { "$ref": "#/definitions/Owner" },
+            "tier": { "$ref": "#/definitions/Tier" }
+        },
+        "definitions": { "Owner": owner, "Tier": { "enum": ["x"] } }
+    });
+    let mut modules = vec![to_module(&project, "project").unwrap(), to_module(&deploy, "deploy").unwrap()];
+
+    compose(&mut modules, "Common");
+    let names: Vec<&str> = modules.iter().map(|module| module.name.as_str()).collect();
+    assert_eq!(names, vec!["Common", "Project", "Deploy"]);
+    assert_eq!(modules[0].classes[0].name, "Owner");
+    // Differing definitions of Tier stay with their modules
+    assert!(modules[0].typealiases.is_empty());
+    assert_eq!(modules[2].typealiases[0].name, "Tier");
+
+    let rendered = modules[2].render();
+    assert!(rendered.contains("import \"Common.pkl\" as common\n"));
+    assert!(rendered.contains("owner: common.Owner?\n"));
+    assert!(rendered.contains("tier: Tier?\n"));
+
+    // Nothing shared, no common module
+    let mut single = vec![to_module(&project, "project").unwrap()];
+    compose(&mut single, "Common");
+    assert_eq!(single.len(), 1);
+}
@@ -0,0 +1,24 @@
+use space_pklr::concurrency::{available, jobs, set_cli_jobs};
+use space_pklr::pkl_schema::GeneratorConfig;
+
+#[test]
+fn test_jobs_precedence() {
+    assert_eq!(jobs(None), available());
+    assert_eq!(jobs(Some(0)), available());
+    assert_eq!(jobs(Some(3)), 3);
+
+    set_cli_jobs(Some(2));
+    assert_eq!(jobs(Some(3)), 2);
+    set_cli_jobs(Some(0));
+    assert_eq!(jobs(Some(3)), available());
+    set_cli_jobs(None);
+    assert_eq!(jobs(Some(3)), 3);
+}
+
+#[test]
+fn test_generator_concurrency_setting() {
+    let config: GeneratorConfig = toml::from_str("concurrency = 2").unwrap();
+    assert_eq!(config.concurrency, Some(2));
+    let config: GeneratorConfig = toml::from_str("parallelism = 4").unwrap();
+    assert_eq!(config.concurrency, Some(4));
+}
@@ -0,0 +1,42 @@
+use serde_json::json;
+use space_pklr::config_path::ConfigPath;
+
+#[test]
+fn test_get_and_set() {
+    let mut config = json!({ "tasks": { "build": { "deps": ["lint", "test"] } }, "language": "rust" });
+
+    let path: ConfigPath = "tasks.build.deps.1".parse().unwrap();
+    assert_eq!(path.to_string(), "tasks.build.deps.1");
+    assert_eq!(path.get(&config), Some(&json!("test")));
+    assert_eq!(ConfigPath::new("tasks.missing").get(&config), None);
+
+    let cache = ConfigPath::new("tasks.build.options.cache");
+    assert_eq!(cache.set(&mut config, json!(false)).unwrap(), None);
+    assert_eq!(config["tasks"]["build"]["options"], json!({ "cache": false }));
+    assert_eq!(path.set(&mut config, json!("check")).unwrap(), Some(json!("test")));
+
+    *ConfigPath::new("language").get_mut(&mut config).unwrap() = json!("go");
+    assert_eq!(config["language"], json!("go"));
+
+    assert!(ConfigPath::new("language.version").set(&mut config, json!(1)).is_err());
+    assert!(ConfigPath::new("tasks.build.deps.5").set(&mut config, json!("x")).is_err());
+    assert!(ConfigPath::new("tasks.*").set(&mut config, json!("x")).is_err());
+}
+
+#[test]
+fn test_patterns() {
+    let pattern = ConfigPath::new("toolchain.*.version");
+    assert!(pattern.is_pattern());
+    assert!(pattern.matches(&ConfigPath::new("toolchain.node.version")));
+    assert!(!pattern.matches(&ConfigPath::new("toolchain.node")));
+    assert!(pattern.matches_prefix(&ConfigPath::new("toolchain.node")));
+    assert!(ConfigPath::new("**.token").matches(&ConfigPath::new("token")));
+
+    let config = json!({ "tasks": { "a": { "cache": true }, "b": {} } });
+    let selected: Vec<String> = ConfigPath::new("tasks.*.cache")
+        .select(&config)
+        .into_iter()
+        .map(|(path, value)| format!("{}={}", path, value))
+        .collect();
+    assert_eq!(selected, vec!["tasks.a.cache=true", "tasks.b.cache=null"]);
+}
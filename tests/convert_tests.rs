@@ -0,0 +1,242 @@
+use serde_json::json;
+use space_pklr::convert::{ConfigConverter, convert_str, semantic_diff};
+use space_pklr::types::moon::UnknownConfig;
+use space_pklr::types::{LoadedConfig, MoonConfig, SchemaFormat};
+
+fn converter(to: SchemaFormat) -> ConfigConverter {
+    let config = UnknownConfig::new(json!({
+        "language": "rust",
+        "tasks": { "build": { "command": "cargo build" } }
+    }));
+    ConfigConverter::new(LoadedConfig::Unknown(config), SchemaFormat::Yaml, to)
+}
+
+#[test]
+fn test_convert_loaded_config() {
+    let converted = converter(SchemaFormat::Json).convert().unwrap();
+    let parsed: serde_json::Value = serde_json::from_str(&converted).unwrap();
+    assert_eq!(parsed["tasks"]["build"]["command"], "cargo build");
+
+    let pkl = converter(SchemaFormat::Pkl).convert().unwrap();
+    assert!(pkl.contains("language = \"rust\""));
+    assert!(pkl.contains("[\"build\"] {"));
+
+    assert!(converter(SchemaFormat::Typescript).convert().is_err());
+}
+
+#[test]
+fn test_converter_accessors() {
+    let converter = converter(SchemaFormat::Pkl);
+    assert_eq!(converter.source_format(), &SchemaFormat::Yaml);
+    assert_eq!(converter.target_format(), &SchemaFormat::Pkl);
+    assert_eq!(converter.config().struct_name(), "UnknownConfig");
+}
+
+#[test]
+fn test_semantic_diff() {
+    let before = json!({ "a": 1, "b": [1, 2], "c": { "d": "x" }, "e": null });
+    let after = json!({ "a": 1.0, "b": [1], "c": { "d": "y", "f": true } });
+
+    assert_eq!(
+        semantic_diff(&before, &after),
+        vec!["b: 2 item(s) became 1", "c.d: \"x\" became \"y\"", "c.f: added (true)"]
+    );
+    assert!(semantic_diff(&before, &before).is_empty());
+}
+
+#[test]
+fn test_convert_with_roundtrip_verification() {
+    let converted = converter(SchemaFormat::Json).verify_roundtrip(true).convert().unwrap();
+    assert!(converted.contains("cargo build"));
+}
+
+#[test]
+fn test_schema_header() {
+    use space_pklr::convert::{relative_path, schema_header};
+    use space_pklr::templates::TemplateConfig;
+    use space_pklr::types::{ConfigHeader, MoonConfig};
+    use std::path::{Path, PathBuf};
+
+    assert_eq!(
+        relative_path(Path::new("/repo/apps/web"), Path::new("/repo/schemas/Project.pkl")),
+        PathBuf::from("../../schemas/Project.pkl")
+    );
+    assert_eq!(relative_path(Path::new("/repo/./x/.."), Path::new("/repo/Project.pkl")), PathBuf::from("Project.pkl"));
+
+    let templates = TemplateConfig::default();
+    let header = |kind: ConfigHeader, templates: &TemplateConfig| {
+        schema_header(&kind, MoonConfig::Project, Path::new("/repo/schemas"), Path::new("/repo/apps/web"), templates).unwrap()
+    };
+    assert_eq!(header(ConfigHeader::None, &templates), None);
+    assert_eq!(header(ConfigHeader::Amends, &templates).unwrap(), "amends \"../../schemas/Project.pkl\"");
+    assert_eq!(header(ConfigHeader::Extends, &templates).unwrap(), "extends \"../../schemas/Project.pkl\"");
+
+    let templated = TemplateConfig {
+        config_header: Some("{{ schema.keyword }} \"@moon/{{ schema.module }}.pkl\"".to_string()),
+        ..Default::default()
+    };
+    assert_eq!(header(ConfigHeader::Amends, &templated).unwrap(), "amends \"@moon/Project.pkl\"");
+
+    assert!("extends".parse::<ConfigHeader>().unwrap() == ConfigHeader::Extends);
+    assert!(schema_header(&ConfigHeader::Amends, MoonConfig::All, Path::new("."), Path::new("."), &templates).is_err());
+}
+
+#[test]
+fn test_convert_str() {
+    let converted = convert_str("language: rust\n", SchemaFormat::Yaml, SchemaFormat::Json, MoonConfig::Project).unwrap();
+    assert_eq!(serde_json::from_str::<serde_json::Value>(&converted.output).unwrap(), json!({ "language": "rust" }));
+    assert!(converted.diagnostics.is_empty());
+
+    // Invalid configs are still converted, with an error
+    let converted = convert_str("type: nonsense\n", SchemaFormat::Yaml, SchemaFormat::Json, MoonConfig::Project).unwrap();
+    assert!(converted.output.contains("nonsense"));
+    assert!(converted.has_errors());
+
+    assert!(convert_str("language = \"rust\"", SchemaFormat::Pkl, SchemaFormat::Yaml, MoonConfig::Project).is_err());
+    assert!(convert_str("{}", SchemaFormat::Json, SchemaFormat::Yaml, MoonConfig::All).is_err());
+}
+
+#[test]
+fn test_toml_conversion() {
+    let toml = r#"
+language = "rust"
+tags = ["app"]
+
+[tasks.build]
+command = "cargo build"
+deps = ["^:build"]
+
+[tasks.build.options]
+cache = false
+"#;
+    let converted = convert_str(toml, SchemaFormat::Toml, SchemaFormat::Json, MoonConfig::Project).unwrap();
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(&converted.output).unwrap(),
+        json!({
+            "language": "rust",
+            "tags": ["app"],
+            "tasks": { "build": { "command": "cargo build", "deps": ["^:build"], "options": { "cache": false } } }
+        })
+    );
+
+    let yaml = "language: rust\nowners:\n  defaultOwner: null\ntasks:\n  build:\n    command: cargo build\n";
+    let converted = convert_str(yaml, SchemaFormat::Yaml, SchemaFormat::Toml, MoonConfig::Project).unwrap();
+    assert_eq!(converted.output, "language = \"rust\"\n\n[owners]\n\n[tasks.build]\ncommand = \"cargo build\"\n");
+    assert!(converted.diagnostics.is_empty());
+
+    assert_eq!("toml".parse::<SchemaFormat>().unwrap(), SchemaFormat::Toml);
+}
+
+#[test]
+fn test_convert_with_property_case() {
+    use space_pklr::pkl_schema::casing::Case;
+    use space_pklr::pkl_schema::json_schema::to_module;
+    use space_pklr::pkl_schema::GeneratorConfig;
+
+    let schema = to_module(
+        &json!({
+            "title": "Project",
+            "type": "object",
+            "properties": {
+                "dependsOn": { "type": "array", "items": { "type": "string" } },
+                "tasks": { "type": "object", "additionalProperties": { "type": "object", "properties": { "runInCI": { "type": "boolean" } } } }
+            }
+        }),
+        "project",
+    )
+    .unwrap();
+    let config = GeneratorConfig {
+        property_case: Case::Snake,
+        ..Default::default()
+    };
+    let value = json!({ "dependsOn": ["core"], "tasks": { "buildApp": { "runInCI": true } } });
+    let converter = ConfigConverter::new(LoadedConfig::Unknown(UnknownConfig::new(value)), SchemaFormat::Yaml, SchemaFormat::Json)
+        .property_case(schema, config);
+    let output: serde_json::Value = serde_json::from_str(&converter.convert().unwrap()).unwrap();
+    assert_eq!(output, json!({ "depends_on": ["core"], "tasks": { "buildApp": { "run_in_ci": true } } }));
+}
+
+#[tokio::test]
+async fn test_stdio_inputs_and_outputs() {
+    use space_pklr::config_processor::{is_stdio, load_config_source, load_config_value};
+    use std::path::{Path, PathBuf};
+
+    assert!(is_stdio(Path::new("-")));
+    assert!(!is_stdio(Path::new("./-")));
+
+    let value = load_config_source("language: rust\n", &SchemaFormat::Yaml).await.unwrap();
+    assert_eq!(value, json!({ "language": "rust" }));
+    assert_eq!(load_config_source("  \n", &SchemaFormat::Toml).await.unwrap(), json!({}));
+
+    // stdin has no extension to detect the format from
+    assert!(load_config_value(Path::new("-"), None).await.is_err());
+
+    // stdout can always be written, even without --force
+    assert!(space_pklr::types::ensure_output_writable(&PathBuf::from("-"), false).is_ok());
+}
+
+/// `convert --input -` reads its config with `read_input`; the test runs itself again with
+/// a config piped to stdin
+#[tokio::test]
+async fn test_convert_reads_stdin() {
+    use space_pklr::config_processor::read_input;
+    use std::io::Write;
+    use std::path::Path;
+    use std::process::{Command, Stdio};
+
+    if std::env::var_os("SPKLR_TEST_STDIN").is_some() {
+        let content = read_input(Path::new("-")).await.unwrap();
+        let converted = convert_str(&content, SchemaFormat::Yaml, SchemaFormat::Json, MoonConfig::Project).unwrap();
+        println!("converted: {}", converted.output.replace('\n', ""));
+        return;
+    }
+
+    let mut child = Command::new(std::env::current_exe().unwrap())
+        .args(["test_convert_reads_stdin", "--exact", "--nocapture"])
+        .env("SPKLR_TEST_STDIN", "1")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"language: rust\ntags: [web]\n")
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let converted = stdout
+        .lines()
+        .find_map(|line| Some(line.split_once("converted: ")?.1))
+        .unwrap_or_else(|| panic!("no converted config in {}", stdout));
+    let converted: serde_json::Value = serde_json::from_str(converted).unwrap();
+    assert_eq!(converted["language"], "rust");
+    assert_eq!(converted["tags"], json!(["web"]));
+}
+
+#[test]
+fn test_pkl_identifiers_quote_keywords() {
+    use space_pklr::config_processor::pkl_identifier;
+
+    assert_eq!(pkl_identifier("language"), "language");
+    assert_eq!(pkl_identifier("extends"), "`extends`");
+    for keyword in ["import", "class", "module", "function", "new", "as", "hidden", "when"] {
+        assert_eq!(pkl_identifier(keyword), format!("`{}`", keyword));
+    }
+    assert_eq!(pkl_identifier("extendsFrom"), "extendsFrom");
+    assert_eq!(pkl_identifier("dev-server"), "`dev-server`");
+}
+
+#[test]
+fn test_pkl_escape_control_characters() {
+    use space_pklr::config_processor::pkl_escape;
+
+    assert_eq!(pkl_escape("say \"hi\"\\\n"), "say \\\"hi\\\"\\\\\\n");
+    assert_eq!(pkl_escape("bell\u{7}"), "bell\\u{7}");
+    assert_eq!(pkl_escape("del\u{7f}"), "del\\u{7f}");
+    assert_eq!(pkl_escape("esc\u{1b}[0m"), "esc\\u{1b}[0m");
+    assert_eq!(pkl_escape("naïve ✓"), "naïve ✓");
+}
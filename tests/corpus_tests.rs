@@ -0,0 +1,88 @@
+use space_pklr::corpus::{ConfigOutcome, CorpusManifest, RepoReport, check_checkout, summarize};
+use std::path::PathBuf;
+
+#[test]
+fn test_corpus_manifest() {
+    let manifest = CorpusManifest::from_toml(
+        r#"
+[[repos]]
+url = "https://github.com/moonrepo/moon.git"
+rev = "v1.30.0"
+
+[[repos]]
+name = "examples"
+url = "git@github.com:moonrepo/examples"
+"#,
+    )
+    .unwrap();
+    assert_eq!(manifest.repos.len(), 2);
+    assert_eq!(manifest.repos[0].name(), "moon");
+    assert_eq!(manifest.repos[0].rev.as_deref(), Some("v1.30.0"));
+    assert_eq!(manifest.repos[1].name(), "examples");
+    assert!(CorpusManifest::from_toml("[[repos]]\nname = \"no-url\"\n").is_err());
+}
+
+#[test]
+fn test_check_checkout() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    for (file, content) in [
+        (".moon/workspace.yml", "projects:\n  - 'apps/*'\n"),
+        ("apps/web/moon.yml", "language: typescript\n"),
+        ("apps/api/moon.yml", "tasks: [unclosed\n"),
+    ] {
+        let path = root.join(file);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, content).unwrap();
+    }
+
+    let outcomes = check_checkout(root);
+    let paths: Vec<PathBuf> = outcomes.iter().map(|outcome| outcome.path.clone()).collect();
+    assert_eq!(
+        paths,
+        vec![PathBuf::from(".moon/workspace.yml"), PathBuf::from("apps/api/moon.yml"), PathBuf::from("apps/web/moon.yml")]
+    );
+    assert!(outcomes[0].passed());
+    assert!(!outcomes[1].passed());
+    assert_eq!(outcomes[1].config_type, "project");
+    assert!(outcomes[2].passed());
+}
+
+#[test]
+fn test_summarize_corpus() {
+    let config = |path: &str, errors: &[&str]| ConfigOutcome {
+        path: PathBuf::from(path),
+        config_type: "project".to_string(),
+        errors: errors.iter().map(|error| error.to_string()).collect(),
+        warnings: 1,
+    };
+    let reports = vec![
+        RepoReport {
+            name: "a".to_string(),
+            url: "https://example.com/a".to_string(),
+            error: None,
+            configs: vec![
+                config("moon.yml", &[]),
+                config("apps/x/moon.yml", &["unknown field `foo`\n  at line 3", "unknown field `foo`\n  at line 9"]),
+                config("apps/y/moon.yml", &["unknown field `foo`", "invalid type: integer"]),
+            ],
+        },
+        RepoReport {
+            name: "b".to_string(),
+            url: "https://example.com/b".to_string(),
+            error: Some("git fetch failed".to_string()),
+            configs: Vec::new(),
+        },
+    ];
+    assert_eq!(reports[0].passed(), 1);
+    assert_eq!(reports[0].failed(), 2);
+
+    let summary = summarize(&reports);
+    assert_eq!((summary.repos, summary.unavailable), (2, 1));
+    assert_eq!((summary.configs, summary.passed, summary.failed, summary.warnings), (3, 1, 2, 3));
+    assert_eq!(
+        summary.top_errors,
+        vec![("unknown field `foo`".to_string(), 2), ("invalid type: integer".to_string(), 1)]
+    );
+    assert!((summary.pass_rate() - 100.0 / 3.0).abs() < 1e-9);
+}
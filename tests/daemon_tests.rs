@@ -0,0 +1,145 @@
+use serde_json::{Value, json};
+use space_pklr::daemon::DaemonState;
+
+async fn request(state: &DaemonState, request: Value) -> Value {
+    let (response, _) = state.handle_line(&request.to_string()).await;
+    serde_json::from_str(&response.expect("expected a response")).unwrap()
+}
+
+#[tokio::test]
+async fn test_ping() {
+    let state = DaemonState::new();
+    let response = request(&state, json!({ "jsonrpc": "2.0", "id": 1, "method": "ping" })).await;
+    assert_eq!(response["id"], 1);
+    assert_eq!(response["result"], "pong");
+}
+
+#[tokio::test]
+async fn test_convert_inline_content() {
+    let state = DaemonState::new();
+    let response = request(
+        &state,
+        json!({
+            "jsonrpc": "2.0",
+            "id": "a",
+            "method": "convert",
+            "params": { "content": "language: rust\n", "from": "yaml", "to": "json" }
+        }),
+    )
+    .await;
+
+    let converted: Value = serde_json::from_str(response["result"].as_str().unwrap()).unwrap();
+    assert_eq!(converted, json!({ "language": "rust" }));
+}
+
+#[tokio::test]
+async fn test_generate_json_schema() {
+    let state = DaemonState::new();
+    let generate = json!({
+        "jsonrpc": "2.0",
+        "id": 5,
+        "method": "generate",
+        "params": { "configType": "project", "format": "json-schema" }
+    });
+
+    let response = request(&state, generate.clone()).await;
+    let schema: Value = serde_json::from_str(response["result"].as_str().unwrap()).unwrap();
+    assert!(schema["properties"]["tasks"].is_object());
+
+    // Served from the warm cache the second time
+    assert_eq!(request(&state, generate).await["result"], response["result"]);
+}
+
+#[tokio::test]
+async fn test_query_selector() {
+    let state = DaemonState::new();
+    let response = request(
+        &state,
+        json!({
+            "jsonrpc": "2.0",
+            "id": 2,
+            "method": "query",
+            "params": {
+                "content": "tasks:\n  build:\n    command: cargo build\n  test:\n    command: cargo test\n",
+                "selector": "tasks.*.command"
+            }
+        }),
+    )
+    .await;
+
+    let commands: Vec<&str> = response["result"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|m| m["value"].as_str().unwrap())
+        .collect();
+    assert_eq!(commands, vec!["cargo build", "cargo test"]);
+}
+
+#[tokio::test]
+async fn test_errors() {
+    let state = DaemonState::new();
+
+    let response = request(&state, json!({ "jsonrpc": "2.0", "id": 3, "method": "nope" })).await;
+    assert_eq!(response["error"]["code"], -32601);
+
+    let response = request(
+        &state,
+        json!({ "jsonrpc": "2.0", "id": 4, "method": "convert", "params": { "content": "a: 1" } }),
+    )
+    .await;
+    assert_eq!(response["error"]["code"], -32602);
+
+    let (response, _) = state.handle_line("{not json").await;
+    let response: Value = serde_json::from_str(&response.unwrap()).unwrap();
+    assert_eq!(response["error"]["code"], -32700);
+}
+
+#[tokio::test]
+async fn test_notifications_and_shutdown() {
+    let state = DaemonState::new();
+
+    let (response, shutdown) = state.handle_line(r#"{"jsonrpc":"2.0","method":"ping"}"#).await;
+    assert!(response.is_none());
+    assert!(!shutdown);
+
+    let (response, shutdown) = state
+        .handle_line(r#"{"jsonrpc":"2.0","id":9,"method":"shutdown"}"#)
+        .await;
+    assert!(response.is_some());
+    assert!(shutdown);
+}
+
+#[test]
+fn test_http_helpers() {
+    use space_pklr::http_server::{check_input_format, moon_version_matches, normalize_listen_addr};
+    use space_pklr::versions::MOON_CONFIG_CRATE_VERSION;
+
+    assert_eq!(normalize_listen_addr(":8080"), "127.0.0.1:8080");
+    assert_eq!(normalize_listen_addr("0.0.0.0:9000"), "0.0.0.0:9000");
+
+    assert!(moon_version_matches(MOON_CONFIG_CRATE_VERSION));
+    assert!(moon_version_matches(&format!("v{}", MOON_CONFIG_CRATE_VERSION)));
+    let minor = MOON_CONFIG_CRATE_VERSION.rsplit_once('.').unwrap().0;
+    assert!(moon_version_matches(minor));
+    assert!(!moon_version_matches(&format!("{}.99", MOON_CONFIG_CRATE_VERSION)));
+    assert!(!moon_version_matches("99.0"));
+
+    // Posted Pkl could read the server's files, so it's refused
+    assert!(check_input_format(None).is_ok());
+    assert!(check_input_format(Some("yaml")).is_ok());
+    assert!(check_input_format(Some("pkl")).is_err());
+    assert!(check_input_format(Some("PCF")).is_err());
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_serve_socket_leaves_other_files_alone() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("notes.txt");
+    std::fs::write(&path, "keep me").unwrap();
+
+    let error = space_pklr::daemon::serve_socket(&path).await.unwrap_err();
+    assert!(error.to_string().contains("not a socket"), "{}", error);
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "keep me");
+}
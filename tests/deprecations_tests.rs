@@ -0,0 +1,74 @@
+use serde_json::json;
+use space_pklr::deprecations::{collect, next_major, plan};
+
+fn previous() -> serde_json::Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "platform": { "type": "string", "deprecated": true, "deprecationMessage": "Use toolchain instead" },
+            "owner": { "type": "string", "deprecated": true },
+            "language": { "type": "string" }
+        },
+        "definitions": {
+            "TaskConfig": {
+                "type": "object",
+                "properties": {
+                    "local": { "type": "boolean", "deprecated": true, "description": "Use preset" }
+                }
+            }
+        }
+    })
+}
+
+fn current() -> serde_json::Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "platform": { "type": "string", "deprecated": true, "deprecationMessage": "Use toolchain instead" },
+            "owner": { "type": "string" },
+            "language": { "type": "string", "deprecated": true },
+            "tags": { "type": "array", "items": { "type": "object", "properties": { "id": { "deprecated": true } } } }
+        },
+        "definitions": {
+            "TaskConfig": { "type": "object", "properties": {} }
+        }
+    })
+}
+
+#[test]
+fn test_collect_deprecations() {
+    let found = collect(&current());
+    let paths: Vec<&str> = found.iter().map(|deprecation| deprecation.path.as_str()).collect();
+    assert_eq!(paths, ["language", "platform", "tags.*.id"]);
+    assert_eq!(found[1].message.as_deref(), Some("Use toolchain instead"));
+
+    let earlier = collect(&previous());
+    assert_eq!(earlier[0].path, "TaskConfig.local");
+    assert_eq!(earlier[0].message.as_deref(), Some("Use preset"));
+}
+
+#[test]
+fn test_removal_plan() {
+    let current_schema = current();
+    let removal = plan(&collect(&previous()), &collect(&current_schema), &current_schema, Some("1.4.2"));
+    let paths = |deprecations: &[space_pklr::deprecations::Deprecation]| {
+        deprecations.iter().map(|deprecation| deprecation.path.clone()).collect::<Vec<_>>()
+    };
+
+    assert_eq!(removal.next_major.as_deref(), Some("2.0.0"));
+    assert_eq!(paths(&removal.removable), ["platform"]);
+    assert_eq!(paths(&removal.newly_deprecated), ["language", "tags.*.id"]);
+    assert_eq!(paths(&removal.removed), ["TaskConfig.local"]);
+    assert_eq!(paths(&removal.undeprecated), ["owner"]);
+
+    let rendered = removal.to_string();
+    assert!(rendered.starts_with("Deprecation removal plan for 2.0.0\n"));
+    assert!(rendered.contains("  - platform: Use toolchain instead\n"));
+}
+
+#[test]
+fn test_next_major() {
+    assert_eq!(next_major("v0.9.1").as_deref(), Some("1.0.0"));
+    assert_eq!(next_major("0.1.0+moon-config.0.1.5").as_deref(), Some("1.0.0"));
+    assert_eq!(next_major("latest"), None);
+}
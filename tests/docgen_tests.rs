@@ -0,0 +1,141 @@
+#![cfg(feature = "docgen")]
+
+use serde_json::json;
+use space_pklr::docgen::examples::{ExampleOverrides, extract_examples};
+use space_pklr::docgen::links;
+use space_pklr::docgen::spellcheck::{self, Dictionary};
+use space_pklr::docgen::{DocKind, collect_docs, coverage, render_markdown};
+
+fn test_schema() -> serde_json::Value {
+    json!({
+        "title": "ProjectConfig",
+        "description": "Configures a project.",
+        "properties": {
+            "language": { "description": "The primary Typescript or Rust langauge." },
+            "owners": { "$ref": "#/definitions/OwnersConfig" }
+        },
+        "definitions": {
+            "OwnersConfig": {
+                "description": "Owners recieve review requests. See `recieve` and https://moonrepo.dev/teh-docs",
+                "properties": { "defaultOwner": { "description": "" } }
+            }
+        }
+    })
+}
+
+#[test]
+fn test_collect_docs_walks_root_and_definitions() {
+    let entries = collect_docs(&test_schema());
+    let paths: Vec<_> = entries.iter().map(|e| e.path.as_str()).collect();
+    assert_eq!(
+        paths,
+        vec![
+            "ProjectConfig",
+            "ProjectConfig.language",
+            "ProjectConfig.owners",
+            "OwnersConfig",
+            "OwnersConfig.defaultOwner"
+        ]
+    );
+    assert_eq!(entries[1].kind, DocKind::Property);
+    assert_eq!(entries[2].text, None);
+    assert_eq!(entries[4].text, None);
+
+    let page = render_markdown("project configuration", &entries);
+    assert!(page.starts_with("# project configuration\n"));
+    assert!(page.contains("\n### `ProjectConfig.language`\n\nThe primary"));
+}
+
+#[test]
+fn test_spellcheck_flags_misspellings_and_terminology() {
+    let entries = collect_docs(&test_schema());
+    let issues = spellcheck::check(&entries, &Dictionary::default());
+
+    let flagged: Vec<_> = issues
+        .iter()
+        .map(|i| (i.path.as_str(), i.word.as_str(), i.suggestion.as_str()))
+        .collect();
+    // Code spans and URLs are skipped; "langauge" isn't in the built-in list
+    assert_eq!(
+        flagged,
+        vec![
+            ("ProjectConfig.language", "Typescript", "TypeScript"),
+            ("OwnersConfig", "recieve", "receive"),
+        ]
+    );
+}
+
+#[test]
+fn test_spellcheck_project_dictionary() {
+    let dictionary = Dictionary::parse("# project words\ntypescript\nlangauge => language\n");
+    let issues = spellcheck::check(&collect_docs(&test_schema()), &dictionary);
+
+    let words: Vec<_> = issues.iter().map(|i| i.word.as_str()).collect();
+    assert_eq!(words, vec!["langauge", "recieve"]);
+    assert_eq!(issues[0].suggestion, "language");
+}
+
+#[test]
+fn test_extract_links() {
+    let schema = json!({
+        "title": "WorkspaceConfig",
+        "properties": {
+            "vcs": { "description": "See [the docs](https://moonrepo.dev/docs/config/workspace#vcs). Also https://git-scm.com, or `http://localhost`." }
+        }
+    });
+
+    let links = links::extract_links(&collect_docs(&schema));
+    let urls: Vec<_> = links.iter().map(|l| l.url.as_str()).collect();
+    assert_eq!(
+        urls,
+        vec![
+            "https://moonrepo.dev/docs/config/workspace#vcs",
+            "https://git-scm.com"
+        ]
+    );
+    assert_eq!(links[0].path, "WorkspaceConfig.vcs");
+}
+
+#[test]
+fn test_doc_coverage() {
+    let coverage = coverage(&collect_docs(&test_schema()));
+
+    assert_eq!(coverage.total, 5);
+    assert_eq!(coverage.documented, 3);
+    assert_eq!(coverage.undocumented, vec!["ProjectConfig.owners", "OwnersConfig.defaultOwner"]);
+    assert!((coverage.percent() - 60.0).abs() < f64::EPSILON);
+
+    assert_eq!(space_pklr::docgen::coverage(&[]).percent(), 100.0);
+}
+
+#[test]
+fn test_examples_heuristics_and_overrides() {
+    assert_eq!(extract_examples(&json!({ "type": "string", "default": "auto" })), vec![json!("auto")]);
+    assert_eq!(
+        extract_examples(&json!({ "enum": ["bun", "deno", "node", "rust"] })),
+        vec![json!("bun"), json!("deno"), json!("node")]
+    );
+    assert_eq!(extract_examples(&json!({ "type": ["string", "null"] })), vec![json!("example")]);
+
+    let schema = json!({
+        "title": "ProjectConfig",
+        "properties": {
+            "language": { "type": "string" },
+            "tags": { "type": "array" }
+        }
+    });
+    let mut entries = collect_docs(&schema);
+    assert_eq!(entries[1].examples, vec![json!("example")]);
+    assert!(entries[2].examples.is_empty());
+
+    let overrides = ExampleOverrides::parse(
+        "\"ProjectConfig.tags\" = [\"frontend\"]\n\n[ProjectConfig]\nlanguage = [\"typescript\", \"rust\"]\n\n[TaskConfig]\ncommand = \"cargo build\"\n",
+    )
+    .unwrap();
+    overrides.apply(&mut entries);
+
+    assert_eq!(entries[1].examples, vec![json!("typescript"), json!("rust")]);
+    assert_eq!(entries[2].examples, vec![json!("frontend")]);
+    assert_eq!(overrides.unmatched(&entries), vec!["TaskConfig.command"]);
+    assert!(render_markdown("project", &entries).contains("Examples: `\"typescript\"`, `\"rust\"`"));
+}
@@ -0,0 +1,39 @@
+use space_pklr::download::{RetryPolicy, parse_sha256_file, sha256_hex};
+use std::time::Duration;
+
+#[test]
+fn test_backoff_doubles_and_caps() {
+    let policy = RetryPolicy {
+        max_attempts: 6,
+        initial_backoff: Duration::from_secs(1),
+        max_backoff: Duration::from_secs(5),
+        ..Default::default()
+    };
+
+    assert_eq!(policy.backoff(1), Duration::from_secs(1));
+    assert_eq!(policy.backoff(2), Duration::from_secs(2));
+    assert_eq!(policy.backoff(3), Duration::from_secs(4));
+    assert_eq!(policy.backoff(4), Duration::from_secs(5));
+    assert_eq!(policy.backoff(40), Duration::from_secs(5));
+}
+
+#[test]
+fn test_parse_sha256_file() {
+    let digest = "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08";
+
+    assert_eq!(parse_sha256_file(digest).as_deref(), Some(digest));
+    assert_eq!(
+        parse_sha256_file(&format!("{}  pkl-cli-linux-amd64.tar.gz\n", digest.to_uppercase())).as_deref(),
+        Some(digest)
+    );
+    assert_eq!(parse_sha256_file("not-a-digest"), None);
+    assert_eq!(parse_sha256_file(""), None);
+}
+
+#[test]
+fn test_sha256_hex() {
+    assert_eq!(
+        sha256_hex(b"test"),
+        "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08"
+    );
+}
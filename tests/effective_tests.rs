@@ -0,0 +1,105 @@
+use serde_json::json;
+use space_pklr::effective::{compose_project, find_workspace_root, inherited_task_files};
+use tempfile::TempDir;
+
+#[test]
+fn test_inherited_task_files_follow_moon_scope_order() {
+    let workspace = TempDir::new().unwrap();
+    let tasks_dir = workspace.path().join(".moon/tasks");
+    std::fs::create_dir_all(&tasks_dir).unwrap();
+    for file in ["../tasks.yml", "node.yml", "typescript.yml", "typescript-library.yml", "tag-release.yml", "rust.yml"] {
+        std::fs::write(tasks_dir.join(file), "tasks: {}\n").unwrap();
+    }
+
+    let project = json!({
+        "language": "typescript",
+        "stack": "frontend",
+        "type": "library",
+        "tags": ["release"]
+    });
+    let names: Vec<String> = inherited_task_files(workspace.path(), &project)
+        .iter()
+        .map(|p| p.strip_prefix(workspace.path()).unwrap().display().to_string())
+        .collect();
+
+    assert_eq!(
+        names,
+        vec![
+            ".moon/tasks.yml",
+            ".moon/tasks/typescript.yml",
+            ".moon/tasks/typescript-library.yml",
+            ".moon/tasks/tag-release.yml",
+        ]
+    );
+}
+
+#[test]
+fn test_find_workspace_root_searches_upwards() {
+    let workspace = TempDir::new().unwrap();
+    std::fs::create_dir_all(workspace.path().join(".moon")).unwrap();
+    let project = workspace.path().join("apps/web");
+    std::fs::create_dir_all(&project).unwrap();
+
+    let root = find_workspace_root(&project).unwrap();
+    assert_eq!(root, std::fs::canonicalize(workspace.path()).unwrap());
+}
+
+#[test]
+fn test_compose_project_applies_filters_and_merges_tasks() {
+    let global = json!({
+        "fileGroups": { "sources": ["src/**/*"], "tests": ["tests/**/*"] },
+        "implicitInputs": ["package.json"],
+        "taskOptions": { "cache": false },
+        "tasks": {
+            "build": { "command": "tsc", "args": ["--build"], "options": { "cache": true } },
+            "lint": { "command": "eslint" },
+            "format": { "command": "prettier" }
+        }
+    });
+    let project = json!({
+        "fileGroups": { "sources": ["lib/**/*"] },
+        "workspace": {
+            "inheritedTasks": { "exclude": ["format"], "rename": { "lint": "check" } }
+        },
+        "tasks": {
+            "build": { "args": ["--verbose"] },
+            "serve": { "command": "vite" }
+        }
+    });
+
+    let effective = compose_project(&[global], &project, "web");
+
+    assert_eq!(effective["id"], "web");
+    assert_eq!(effective["fileGroups"]["sources"], json!(["lib/**/*"]));
+    assert_eq!(effective["fileGroups"]["tests"], json!(["tests/**/*"]));
+
+    let tasks = effective["tasks"].as_object().unwrap();
+    assert!(!tasks.contains_key("format"));
+    assert!(!tasks.contains_key("lint"));
+    assert_eq!(tasks["check"]["options"]["cache"], false);
+    assert_eq!(tasks["build"]["args"], json!(["--build", "--verbose"]));
+    assert_eq!(tasks["build"]["options"]["cache"], true);
+    assert_eq!(tasks["serve"]["inputs"], json!(["package.json"]));
+}
+
+#[test]
+fn test_compose_project_keeps_task_options_for_overrides_without_options() {
+    let global = json!({
+        "taskOptions": { "mergeArgs": "replace", "cache": false },
+        "tasks": {
+            "test": { "command": "jest", "args": ["--ci"] }
+        }
+    });
+    let project = json!({
+        "tasks": {
+            "test": { "args": ["--watch"] }
+        }
+    });
+
+    let effective = compose_project(&[global], &project, "web");
+
+    let test = &effective["tasks"]["test"];
+    assert_eq!(test["command"], "jest");
+    assert_eq!(test["args"], json!(["--watch"]));
+    assert_eq!(test["options"], json!({ "mergeArgs": "replace", "cache": false }));
+}
@@ -0,0 +1,82 @@
+use serde_json::json;
+use space_pklr::config_processor::write_pkl_with_env_policy;
+use space_pklr::convert::ConfigConverter;
+use space_pklr::env_tokens::{EnvTokenPolicy, Segment, check, first_token, pkl_value, segments};
+use space_pklr::types::{MoonConfig, SchemaFormat};
+use std::collections::BTreeMap;
+
+fn render(value: &serde_json::Value, policy: EnvTokenPolicy) -> String {
+    let mut out = Vec::new();
+    write_pkl_with_env_policy(value, &BTreeMap::new(), policy, &mut out).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn test_segments() {
+    assert_eq!(
+        segments("$HOME/bin:${PATH}"),
+        vec![
+            Segment::Var { name: "HOME", token: "$HOME" },
+            Segment::Text("/bin:"),
+            Segment::Var { name: "PATH", token: "${PATH}" },
+        ]
+    );
+    // Moon's own tokens, lone and unterminated `$`s, and `${...}` with modifiers are text
+    for text in ["$projectRoot/dist", "cost: $5", "${HOME", "${NODE_ENV:-dev}", "$"] {
+        assert_eq!(segments(text), vec![Segment::Text(text)], "{}", text);
+    }
+    assert_eq!(
+        segments("$CI_JOBs"),
+        vec![Segment::Var { name: "CI_JOB", token: "$CI_JOB" }, Segment::Text("s")]
+    );
+}
+
+#[test]
+fn test_preserve_keeps_tokens_verbatim() {
+    let value = json!({
+        "command": "echo \"${GREETING}\" $USER \\(not pkl)",
+        "env": { "NODE_ENV": "$NODE_ENV" },
+    });
+    let pkl = render(&value, EnvTokenPolicy::Preserve);
+    assert!(pkl.contains(r#"command = "echo \"${GREETING}\" $USER \\(not pkl)""#), "{}", pkl);
+    assert!(pkl.contains(r#"["NODE_ENV"] = "$NODE_ENV""#), "{}", pkl);
+}
+
+#[test]
+fn test_read_env_reads_variables_when_evaluated() {
+    assert_eq!(pkl_value("${HOME}", EnvTokenPolicy::ReadEnv), r#"read("env:HOME")"#);
+    assert_eq!(
+        pkl_value("$HOME/\"bin\"", EnvTokenPolicy::ReadEnv),
+        r#""\(read("env:HOME"))/\"bin\"""#
+    );
+    assert_eq!(pkl_value("$projectRoot", EnvTokenPolicy::ReadEnv), r#""$projectRoot""#);
+
+    let pkl = render(&json!({ "args": ["--token", "$NPM_TOKEN"] }), EnvTokenPolicy::ReadEnv);
+    assert!(pkl.contains("  \"--token\"\n  read(\"env:NPM_TOKEN\")\n"), "{}", pkl);
+}
+
+#[test]
+fn test_error_policy_names_the_first_token() {
+    let value = json!({ "tasks": { "build": { "args": ["--out", "$projectRoot", "${OUT_DIR}"] } } });
+    let (path, token) = first_token(&value).unwrap();
+    assert_eq!(path.to_string(), "tasks.build.args.2");
+    assert_eq!(token, "${OUT_DIR}");
+
+    assert!(check(&value, EnvTokenPolicy::Preserve).is_ok());
+    let error = check(&value, EnvTokenPolicy::Error).unwrap_err();
+    assert_eq!(error.to_string(), "tasks.build.args.2 uses environment variable ${OUT_DIR}");
+    assert!(check(&json!({ "language": "rust" }), EnvTokenPolicy::Error).is_ok());
+}
+
+#[test]
+fn test_converter_env_policy() {
+    let converter = |policy: &str| {
+        ConfigConverter::from_content("language: rust\nenv:\n  TARGET: ${CARGO_TARGET}\n", MoonConfig::Project, SchemaFormat::Yaml, SchemaFormat::Pkl)
+            .unwrap()
+            .env_policy(policy.parse().unwrap())
+    };
+    assert!(converter("preserve").convert().unwrap().contains(r#"["TARGET"] = "${CARGO_TARGET}""#));
+    assert!(converter("read-env").convert().unwrap().contains(r#"["TARGET"] = read("env:CARGO_TARGET")"#));
+    assert!(converter("error").convert().is_err());
+    assert!("evaluate".parse::<EnvTokenPolicy>().is_err());
+}
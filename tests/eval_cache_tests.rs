@@ -0,0 +1,52 @@
+use space_pklr::eval_cache::{CacheConfig, EvalCache, cache_key, dependencies};
+
+#[test]
+fn test_cache_key_follows_local_dependencies() {
+    let dir = tempfile::tempdir().unwrap();
+    let base = dir.path().join("base.pkl");
+    let config = dir.path().join("moon.pkl");
+    std::fs::write(&base, "language: String = \"rust\"\n").unwrap();
+    std::fs::write(&config, "amends \"base.pkl\"\nimport \"package://pkg.pkl-lang.org/x@1.0.0#/y.pkl\"\n").unwrap();
+    assert_eq!(
+        dependencies(&std::fs::read_to_string(&config).unwrap()),
+        vec!["base.pkl", "package://pkg.pkl-lang.org/x@1.0.0#/y.pkl"]
+    );
+
+    let args = vec!["eval".to_string(), "-f".to_string(), "json".to_string()];
+    let key = cache_key(&config, "0.28.2", &args).unwrap();
+    assert_eq!(cache_key(&config, "0.28.2", &args), Some(key.clone()));
+    assert_ne!(cache_key(&config, "0.29.0", &args), Some(key.clone()));
+
+    // Changing an amended module changes the key of every module built on it
+    std::fs::write(&base, "language: String = \"go\"\n").unwrap();
+    assert_ne!(cache_key(&config, "0.28.2", &args), Some(key));
+
+    // Resource reads can change without the sources changing
+    std::fs::write(&config, "token = read(\"env:TOKEN\")\n").unwrap();
+    assert_eq!(cache_key(&config, "0.28.2", &args), None);
+}
+
+#[test]
+fn test_cache_entries_and_size_limit() {
+    let dir = tempfile::tempdir().unwrap();
+    let cache = EvalCache::new(dir.path().join("eval"), 10);
+    assert_eq!(cache.get("a"), None);
+
+    cache.put("a", "{\"n\":1}");
+    assert_eq!(cache.get("a").as_deref(), Some("{\"n\":1}"));
+    assert_eq!(cache.stats().entries, 1);
+
+    // Past the limit, the least recently used entries go first
+    cache.put("b", "{\"n\":2}");
+    assert_eq!(cache.get("a"), None);
+    assert_eq!(cache.get("b").as_deref(), Some("{\"n\":2}"));
+
+    let cleared = cache.clear().unwrap();
+    assert_eq!(cleared.entries, 1);
+    assert_eq!(cache.stats().entries, 0);
+
+    let disabled: CacheConfig = toml::from_str("enabled = false").unwrap();
+    assert!(EvalCache::from_config(&disabled).is_none());
+    let sized: CacheConfig = toml::from_str("max_size = \"1MiB\"\ndir = \"cache\"").unwrap();
+    assert_eq!(EvalCache::from_config(&sized).unwrap().dir(), std::path::Path::new("cache"));
+}
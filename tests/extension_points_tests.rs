@@ -0,0 +1,56 @@
+use serde_json::json;
+use space_pklr::extension_points::{ExtensionKind, find, gap};
+use space_pklr::pkl_schema::PklType;
+use space_pklr::pkl_schema::json_schema::to_module;
+
+#[test]
+fn test_find_extension_points() {
+    let toolchain = to_module(
+        &json!({
+            "title": "Toolchain",
+            "type": "object",
+            "properties": {
+                "deno": { "type": "object", "additionalProperties": true },
+                "node": { "$ref": "#/definitions/NodeConfig" }
+            },
+            "definitions": {
+                "NodeConfig": {
+                    "type": "object",
+                    "properties": {
+                        "version": { "type": "string" },
+                        "env": { "type": "object", "additionalProperties": {} },
+                        "args": { "type": "array" },
+                        "extra": {}
+                    }
+                }
+            }
+        }),
+        "toolchain",
+    )
+    .unwrap();
+
+    let points = find(&[toolchain]);
+    let paths: Vec<(&str, ExtensionKind)> = points.iter().map(|point| (point.path.as_str(), point.kind)).collect();
+    assert_eq!(
+        paths,
+        vec![
+            ("Toolchain.NodeConfig.args", ExtensionKind::UntypedListing),
+            ("Toolchain.NodeConfig.env", ExtensionKind::OpenMapping),
+            ("Toolchain.NodeConfig.extra", ExtensionKind::Untyped),
+            ("Toolchain.deno", ExtensionKind::OpenMapping),
+        ]
+    );
+    assert_eq!(points[3].ty, "Mapping<String, Any>?");
+    assert!(points[3].guidance.contains("[[toolchain_plugins]] with id = \"deno\""));
+    assert!(points[1].guidance.contains("composition manifest"));
+}
+
+#[test]
+fn test_gap_takes_the_widest() {
+    let ty = PklType::Union(vec![
+        PklType::Listing(Box::new(PklType::Any)),
+        PklType::Mapping(Box::new(PklType::String), Box::new(PklType::Any)),
+    ]);
+    assert_eq!(gap(&ty), Some(ExtensionKind::OpenMapping));
+    assert_eq!(gap(&PklType::Mapping(Box::new(PklType::String), Box::new(PklType::String))), None);
+}
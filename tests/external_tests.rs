@@ -0,0 +1,56 @@
+#![cfg(all(feature = "cli", unix))]
+
+use space_pklr::commands::external::{handle_external, list_external_commands};
+use std::os::unix::fs::PermissionsExt;
+use std::process::Command;
+
+// PATH is process-wide, so the dispatch runs in a child test process with its own PATH
+#[tokio::test]
+async fn test_external_command_dispatch() {
+    if std::env::var_os("SPKLR_TEST_EXTERNAL").is_some() {
+        println!("external commands: {}", list_external_commands().join(","));
+        let args = ["foo", "--flag"].map(str::to_string).to_vec();
+        println!("external exit: {}", handle_external(args).await.unwrap());
+        return;
+    }
+
+    let dir = tempfile::tempdir().unwrap();
+    let context = dir.path().join("context.json");
+    let arguments = dir.path().join("args.txt");
+    let script = dir.path().join("spklr-foo");
+    std::fs::write(
+        &script,
+        format!("#!/bin/sh\ncat > '{}'\necho \"$@\" > '{}'\nexit 3\n", context.display(), arguments.display()),
+    )
+    .unwrap();
+    std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+    // Not executable, so not a command
+    std::fs::write(dir.path().join("spklr-notes"), "").unwrap();
+
+    let path = std::env::join_paths(
+        std::iter::once(dir.path().to_path_buf()).chain(std::env::split_paths(&std::env::var_os("PATH").unwrap_or_default())),
+    )
+    .unwrap();
+    let output = Command::new(std::env::current_exe().unwrap())
+        .args(["test_external_command_dispatch", "--exact", "--nocapture"])
+        .env("SPKLR_TEST_EXTERNAL", "1")
+        .env("PATH", path)
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(output.status.success(), "{}", stdout);
+
+    let listed = stdout
+        .lines()
+        .find_map(|line| line.strip_prefix("external commands: "))
+        .unwrap_or_else(|| panic!("no command list in {}", stdout));
+    assert!(listed.split(',').any(|name| name == "foo"), "{}", listed);
+    assert!(!listed.split(',').any(|name| name == "notes"), "{}", listed);
+    assert!(stdout.contains("external exit: 3"), "{}", stdout);
+
+    // The context document arrives on stdin, the arguments on the command line
+    let json: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&context).unwrap()).unwrap();
+    assert_eq!(json["command"], "foo");
+    assert_eq!(json["args"], serde_json::json!(["--flag"]));
+    assert_eq!(std::fs::read_to_string(&arguments).unwrap(), "--flag\n");
+}
@@ -0,0 +1,49 @@
+use space_pklr::fragments::{append_fragment, check_syntax, find_fragment};
+use tempfile::TempDir;
+
+#[test]
+fn test_fragment_appended_to_matching_module() {
+    let dir = TempDir::new().unwrap();
+    std::fs::write(
+        dir.path().join("Workspace.append.pkl"),
+        "/// Path of a project\nfunction projectPath(name: String): String = \"apps/\\(name)\"\n",
+    )
+    .unwrap();
+
+    assert!(find_fragment(dir.path(), "workspace.pkl").is_some());
+    assert!(find_fragment(dir.path(), "workspace.yml").is_none());
+    assert!(find_fragment(dir.path(), "project.pkl").is_none());
+
+    let output = append_fragment(dir.path(), "out/workspace.pkl", "module Workspace\n\nprojects: Listing<String>\n").unwrap();
+    assert_eq!(
+        output,
+        "module Workspace\n\nprojects: Listing<String>\n\n/// Path of a project\nfunction projectPath(name: String): String = \"apps/\\(name)\"\n"
+    );
+    assert_eq!(append_fragment(dir.path(), "project.pkl", "module Project\n").unwrap(), "module Project\n");
+}
+
+#[test]
+fn test_invalid_fragment_is_rejected() {
+    let dir = TempDir::new().unwrap();
+    std::fs::write(dir.path().join("Project.append.pkl"), "function f() = List(1, 2\n").unwrap();
+
+    let error = append_fragment(dir.path(), "project.pkl", "module Project\n").unwrap_err();
+    match error {
+        space_pklr::types::CliError::FragmentError { message, .. } => assert_eq!(message, "unclosed `(` (line 1)"),
+        other => panic!("unexpected error: {}", other),
+    }
+}
+
+#[test]
+fn test_fragment_syntax_check() {
+    assert!(check_syntax("local x = \"}\" // {\n/* ( */ y = List(x)\n").is_ok());
+    assert!(check_syntax("text = \"\"\"\n  { unbalanced\n  \"\"\"\n").is_ok());
+    assert!(check_syntax("x = \"open\n").is_err());
+    assert_eq!(check_syntax("a {\n  b = 1)\n").unwrap_err(), "`)` on line 2 closes `{` from line 1");
+    assert_eq!(check_syntax("x = 1\n}\n").unwrap_err(), "unmatched `}` (line 2)");
+    assert_eq!(
+        check_syntax("import \"pkl:json\"\n").unwrap_err(),
+        "`import` declarations can't be appended to a module (line 1)"
+    );
+    assert!(check_syntax("/* never closed\n").is_err());
+}
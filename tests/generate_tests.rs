@@ -0,0 +1,68 @@
+#![cfg(feature = "cli")]
+
+use clap::Parser;
+use space_pklr::cli_app::{Cli, Commands};
+use space_pklr::commands::generate::{GenerateCommands, SchemaArgs, handle_schema_generation};
+
+fn schema_args(args: &[&str]) -> SchemaArgs {
+    let cli = Cli::try_parse_from(["spklr", "generate", "schema"].iter().chain(args)).unwrap();
+    match cli.command {
+        Commands::Generate(GenerateCommands::Schema(args)) => *args,
+        _ => unreachable!(),
+    }
+}
+
+#[tokio::test]
+async fn test_pkl_schema_with_types() {
+    let dir = tempfile::tempdir().unwrap();
+    let output = dir.path().to_str().unwrap();
+
+    let args = schema_args(&["--config-type", "project", "--format", "pkl", "--types", "OwnersConfig", "--output", output]);
+    handle_schema_generation(args).await.unwrap();
+
+    let module = std::fs::read_to_string(dir.path().join("Project.pkl")).unwrap();
+    assert!(module.contains("class OwnersConfig {"), "{}", module);
+    // Referenced types come along, everything else is left out
+    assert!(module.contains("typealias OwnersPaths = "), "{}", module);
+    assert!(!module.contains("class TaskConfig"), "{}", module);
+    assert!(dir.path().join("PklProject").exists());
+
+    let args = schema_args(&["--config-type", "project", "--format", "pkl", "--types", "NoSuchConfig", "--output", output]);
+    let error = handle_schema_generation(args).await.unwrap_err();
+    assert!(error.to_string().contains("NoSuchConfig"), "{}", error);
+}
+
+#[tokio::test]
+async fn test_pkl_schema_refuses_partials() {
+    let args = schema_args(&["--config-type", "project", "--format", "pkl", "--partials"]);
+    let error = handle_schema_generation(args).await.unwrap_err();
+    assert!(error.to_string().contains("--partials"), "{}", error);
+}
+
+#[tokio::test]
+async fn test_pkl_schema_with_policy() {
+    let dir = tempfile::tempdir().unwrap();
+    let policy = dir.path().join("policy.json");
+    std::fs::write(
+        &policy,
+        r#"{ "overrides": [{ "path": "owners.defaultOwner", "constraints": ["startsWith(\"@\")"], "reason": "Owners are handles" }] }"#,
+    )
+    .unwrap();
+    let output = dir.path().join("out");
+
+    let args = schema_args(&[
+        "--config-type",
+        "project",
+        "--format",
+        "pkl",
+        "--policy",
+        policy.to_str().unwrap(),
+        "--output",
+        output.to_str().unwrap(),
+    ]);
+    handle_schema_generation(args).await.unwrap();
+
+    let module = std::fs::read_to_string(output.join("Project.pkl")).unwrap();
+    assert!(module.contains("// Org policy: Owners are handles\n"), "{}", module);
+    assert!(module.contains("defaultOwner: String(startsWith(\"@\"))?\n"), "{}", module);
+}
@@ -0,0 +1,73 @@
+use space_pklr::init::{has_project_config, pkl_path, starter_config, starter_path, yaml_configs};
+use space_pklr::types::MoonConfig;
+use std::path::{Path, PathBuf};
+
+#[test]
+fn test_yaml_configs() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    for file in [
+        ".moon/workspace.yml",
+        ".moon/toolchain.yml",
+        ".moon/tasks.yml",
+        ".moon/tasks/node.yml",
+        "apps/web/moon.yml",
+        "packages/lib/moon.yaml",
+        "templates/app/template.yml",
+        "apps/web/node_modules/dep/moon.yml",
+        ".git/moon.yml",
+        "apps/web/README.md",
+    ] {
+        let path = root.join(file);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, "").unwrap();
+    }
+
+    let found: Vec<(MoonConfig, PathBuf)> = yaml_configs(root)
+        .into_iter()
+        .map(|(config_type, path)| (config_type, path.strip_prefix(root).unwrap().to_path_buf()))
+        .collect();
+    assert_eq!(
+        found,
+        vec![
+            (MoonConfig::Task, PathBuf::from(".moon/tasks/node.yml")),
+            (MoonConfig::Task, PathBuf::from(".moon/tasks.yml")),
+            (MoonConfig::Toolchain, PathBuf::from(".moon/toolchain.yml")),
+            (MoonConfig::Workspace, PathBuf::from(".moon/workspace.yml")),
+            (MoonConfig::Project, PathBuf::from("apps/web/moon.yml")),
+            (MoonConfig::Project, PathBuf::from("packages/lib/moon.yaml")),
+            (MoonConfig::Template, PathBuf::from("templates/app/template.yml")),
+        ]
+    );
+    assert_eq!(pkl_path(Path::new("apps/web/moon.yml")), PathBuf::from("apps/web/moon.pkl"));
+}
+
+#[test]
+fn test_has_project_config() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir_all(dir.path().join(".moon")).unwrap();
+    std::fs::write(dir.path().join(".moon/workspace.yml"), "").unwrap();
+    assert!(!has_project_config(dir.path()));
+
+    std::fs::create_dir_all(dir.path().join("apps/web")).unwrap();
+    std::fs::write(dir.path().join("apps/web/moon.pkl"), "").unwrap();
+    assert!(has_project_config(dir.path()));
+}
+
+#[test]
+fn test_starter_configs() {
+    let root = Path::new("/repo");
+    assert_eq!(starter_path(root, MoonConfig::Workspace), Some(PathBuf::from("/repo/.moon/workspace.pkl")));
+    assert_eq!(starter_path(root, MoonConfig::Project), Some(PathBuf::from("/repo/moon.pkl")));
+    assert_eq!(starter_path(root, MoonConfig::Toolchain), None);
+
+    let workspace = starter_config(MoonConfig::Workspace, "amends \"pkl/Workspace.pkl\"");
+    assert!(workspace.starts_with("amends \"pkl/Workspace.pkl\"\n\n"));
+    // Everything after the header is commented out, so the starter evaluates as is
+    assert!(workspace.lines().skip(1).all(|line| line.is_empty() || line.starts_with("//")));
+    assert!(workspace.contains("projects {"));
+
+    let project = starter_config(MoonConfig::Project, "amends \".moon/pkl/Project.pkl\"");
+    assert!(project.lines().skip(1).all(|line| line.is_empty() || line.starts_with("//")));
+    assert!(project.contains("tasks {"));
+}
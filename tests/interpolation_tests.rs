@@ -0,0 +1,78 @@
+use space_pklr::interpolation::{EnvConfig, REDACTED, interpolate_str, redact};
+use space_pklr::tool_config::ToolConfig;
+
+fn lookup(name: &str) -> Option<String> {
+    match name {
+        "PKL_MIRROR" => Some("https://mirror.internal/pkl".to_string()),
+        "CI_BOT_TOKEN" => Some("bot-token-5f2a9c".to_string()),
+        "SHORT" => Some("on".to_string()),
+        _ => None,
+    }
+}
+
+fn enabled() -> EnvConfig {
+    EnvConfig { interpolate: true, allow: Vec::new() }
+}
+
+fn load(content: &str) -> Result<ToolConfig, String> {
+    let dir = tempfile::tempdir().unwrap();
+    let config_path = dir.path().join("spklr.toml");
+    std::fs::write(&config_path, content).unwrap();
+    ToolConfig::load_with_env(&config_path, &lookup).map_err(|e| {
+        use std::error::Error;
+        e.source().map(|source| source.to_string()).unwrap_or_else(|| e.to_string())
+    })
+}
+
+#[test]
+fn test_interpolate_references() {
+    let config = enabled();
+    assert_eq!(interpolate_str("${env:PKL_MIRROR}/v1", &config, &lookup).unwrap(), "https://mirror.internal/pkl/v1");
+    assert_eq!(interpolate_str("${env:MISSING:-fallback}", &config, &lookup).unwrap(), "fallback");
+    assert_eq!(interpolate_str("cost: $5, $${env:PKL_MIRROR}", &config, &lookup).unwrap(), "cost: $5, ${env:PKL_MIRROR}");
+
+    assert!(interpolate_str("${env:MISSING}", &config, &lookup).unwrap_err().contains("MISSING is not set"));
+    assert!(interpolate_str("${env:PKL_MIRROR", &config, &lookup).unwrap_err().contains("unterminated"));
+    assert!(interpolate_str("${env:BAD-NAME}", &config, &lookup).unwrap_err().contains("invalid variable name"));
+
+    let allowlisted = EnvConfig { interpolate: true, allow: vec!["CI_*".to_string()] };
+    assert!(interpolate_str("${env:CI_BOT_TOKEN}", &allowlisted, &lookup).is_ok());
+    assert!(interpolate_str("${env:PKL_MIRROR}", &allowlisted, &lookup).unwrap_err().contains("allow"));
+}
+
+#[test]
+fn test_tool_config_interpolates_when_enabled() {
+    let content = r#"
+[env]
+interpolate = true
+
+[download]
+mirrors = ["${env:PKL_MIRROR}", "${env:BACKUP_MIRROR:-https://backup.example.com/pkl}"]
+
+[ci]
+github_token = "${env:CI_BOT_TOKEN}"
+"#;
+    let config = load(content).unwrap();
+    assert_eq!(config.download.mirrors, vec!["https://mirror.internal/pkl", "https://backup.example.com/pkl"]);
+    assert_eq!(config.ci.github_token.as_deref(), Some("bot-token-5f2a9c"));
+
+    // Without the opt-in, references are plain text
+    let config = load(&content.replace("interpolate = true", "interpolate = false")).unwrap();
+    assert_eq!(config.download.mirrors[0], "${env:PKL_MIRROR}");
+}
+
+#[test]
+fn test_tool_config_errors_name_the_key() {
+    let error = load("[env]\ninterpolate = true\n\n[download]\nmirrors = [\"${env:MISSING}\"]\n").unwrap_err();
+    assert!(error.contains("download.mirrors[0]: environment variable MISSING is not set"), "{}", error);
+}
+
+#[test]
+fn test_substituted_values_are_redacted() {
+    let config = enabled();
+    interpolate_str("${env:CI_BOT_TOKEN} ${env:SHORT}", &config, &lookup).unwrap();
+
+    assert_eq!(redact("Authorization: bot-token-5f2a9c"), format!("Authorization: {}", REDACTED));
+    // Masking values this short would garble unrelated output
+    assert_eq!(redact("retries on"), "retries on");
+}
@@ -0,0 +1,74 @@
+use serde_json::json;
+use space_pklr::config_processor::parse_config_str;
+use space_pklr::convert::convert_str;
+use space_pklr::jsonc::{comments, strip};
+use space_pklr::types::{MoonConfig, SchemaFormat};
+use std::str::FromStr;
+
+const PROJECT: &str = r#"// Project config for the CLI
+{
+  // Rust, of course
+  "language": "rust",
+  "tags": [
+    /* first */ "cli",
+    "app", // the binary
+  ],
+  "tasks": {
+    "build": {
+      "command": "cargo build", // no --release
+      /*
+       * Upstream builds
+       */
+      "deps": ["^:build"],
+    },
+  },
+  "url": "https://example.com/*not-a-comment*/",
+}
+"#;
+
+#[test]
+fn test_strip_keeps_strings_and_positions() {
+    let stripped = strip(PROJECT);
+    assert_eq!(stripped.lines().count(), PROJECT.lines().count());
+    assert!(!stripped.contains("Rust, of course"));
+
+    let value: serde_json::Value = serde_json::from_str(&stripped).unwrap();
+    assert_eq!(value["url"], "https://example.com/*not-a-comment*/");
+    assert_eq!(value["tags"], json!(["cli", "app"]));
+    assert_eq!(value["tasks"]["build"]["deps"], json!(["^:build"]));
+}
+
+#[test]
+fn test_comments_by_path() {
+    let found = comments(PROJECT);
+    assert_eq!(found.get("").map(String::as_str), Some("Project config for the CLI"));
+    assert_eq!(found.get("language").map(String::as_str), Some("Rust, of course"));
+    assert_eq!(found.get("tags.0").map(String::as_str), Some("first"));
+    assert_eq!(found.get("tags.1").map(String::as_str), Some("the binary"));
+    assert_eq!(found.get("tasks.build.command").map(String::as_str), Some("no --release"));
+    assert_eq!(found.get("tasks.build.deps").map(String::as_str), Some("Upstream builds"));
+    assert!(!found.contains_key("url"));
+}
+
+#[test]
+fn test_parse_jsonc_format() {
+    assert_eq!(SchemaFormat::from_str("json5").unwrap(), SchemaFormat::Jsonc);
+    assert_eq!(SchemaFormat::Jsonc.to_string(), "jsonc");
+
+    let value = parse_config_str(PROJECT, &SchemaFormat::Jsonc).unwrap();
+    assert_eq!(value["language"], "rust");
+    assert!(parse_config_str(PROJECT, &SchemaFormat::Json).is_err());
+}
+
+#[test]
+fn test_convert_jsonc_comments_to_pkl() {
+    let converted = convert_str(PROJECT, SchemaFormat::Jsonc, SchemaFormat::Pkl, MoonConfig::Project).unwrap();
+    let pkl = converted.output;
+    assert!(pkl.starts_with("// Project config for the CLI\n\n"));
+    assert!(pkl.contains("/// Rust, of course\nlanguage = \"rust\""));
+    assert!(pkl.contains("  // first\n  \"cli\""));
+    assert!(pkl.contains("    /// no --release\n    command = \"cargo build\""));
+
+    let json = convert_str(PROJECT, SchemaFormat::Jsonc, SchemaFormat::Json, MoonConfig::Project).unwrap();
+    assert!(!json.output.contains("the binary"));
+}
@@ -0,0 +1,63 @@
+use space_pklr::CliError;
+use space_pklr::lock::PathLock;
+
+#[test]
+fn test_second_lock_fails_fast_until_released() {
+    let root = tempfile::tempdir().unwrap();
+    let target = root.path().join("schemas");
+
+    let first = PathLock::acquire_with(&target, false).unwrap();
+    assert!(first.path().exists());
+
+    match PathLock::acquire_with(&target, false) {
+        Err(CliError::LockHeld { holder, .. }) => {
+            assert_eq!(holder, format!("pid {}", std::process::id()));
+        }
+        other => panic!("expected LockHeld, got {:?}", other),
+    }
+
+    drop(first);
+    assert!(PathLock::acquire_with(&target, false).is_ok());
+}
+
+#[test]
+fn test_waiting_lock_acquires_after_release() {
+    let root = tempfile::tempdir().unwrap();
+    let target = root.path().join("out.json");
+
+    let first = PathLock::acquire_with(&target, false).unwrap();
+    let waiter = {
+        let target = target.clone();
+        std::thread::spawn(move || PathLock::acquire_with(&target, true).map(|_| ()))
+    };
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+    drop(first);
+    assert!(waiter.join().unwrap().is_ok());
+}
+
+#[test]
+fn test_locks_leave_no_files_next_to_outputs() {
+    let root = tempfile::tempdir().unwrap();
+    let output = root.path().join("moon.pkl");
+
+    {
+        let lock = PathLock::acquire_with(&output, false).unwrap();
+        assert!(!lock.path().starts_with(root.path()));
+        space_pklr::atomic_write::write_atomic_sync(&output, "language = \"rust\"\n").unwrap();
+    }
+
+    let entries: Vec<_> = std::fs::read_dir(root.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name())
+        .collect();
+    assert_eq!(entries, vec![std::ffi::OsString::from("moon.pkl")]);
+
+    // The same path, relative or not yet existing, maps to the same lock
+    let lock = PathLock::acquire_with(&output, false).unwrap();
+    assert!(matches!(
+        PathLock::acquire_with(&root.path().join(".").join("moon.pkl"), false),
+        Err(CliError::LockHeld { .. })
+    ));
+    drop(lock);
+}
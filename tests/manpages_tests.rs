@@ -0,0 +1,44 @@
+#![cfg(feature = "cli")]
+
+use space_pklr::commands::manpages::{EXAMPLES, command, examples, help_all, write_manpages};
+
+#[test]
+fn test_examples_name_real_commands() {
+    let root = command();
+    for (path, _) in EXAMPLES {
+        let mut current = &root;
+        for name in path.split_whitespace() {
+            current = current
+                .find_subcommand(name)
+                .unwrap_or_else(|| panic!("examples for unknown command `{}`", path));
+        }
+    }
+    assert!(examples("convert").iter().all(|example| example.starts_with("spklr convert")));
+    assert!(examples("nonexistent").is_empty());
+}
+
+#[test]
+fn test_help_all() {
+    let help = help_all();
+    assert!(help.starts_with("spklr\n=====\n"));
+    assert!(help.contains("\nspklr generate schema\n"));
+    assert!(help.contains("  $ spklr pkl-me --report\n"));
+    // Hidden commands stay out of the help
+    assert!(!help.contains("spklr manpages"));
+}
+
+#[test]
+fn test_write_manpages() {
+    let dir = tempfile::tempdir().unwrap();
+    let written = write_manpages(dir.path()).unwrap();
+    assert!(written.contains(&dir.path().join("spklr.1")));
+    assert!(!dir.path().join("spklr-manpages.1").exists());
+
+    let convert = std::fs::read_to_string(dir.path().join("spklr-convert.1")).unwrap();
+    assert!(convert.starts_with(".ie"));
+    assert!(convert.contains(".SH EXAMPLES\n"));
+    assert!(convert.contains("spklr convert \\-\\-input moon.yml"));
+
+    let nested = std::fs::read_to_string(dir.path().join("spklr-generate-schema.1")).unwrap();
+    assert!(nested.contains("\\-\\-from\\-json\\-schema"));
+}
@@ -0,0 +1,95 @@
+use serde_json::json;
+use space_pklr::merge::{MergeStrategy, merge_configs, merge_field};
+
+#[test]
+fn test_merge_configs_deep_merges_objects_and_replaces_arrays() {
+    let base = json!({
+        "language": "typescript",
+        "tags": ["app"],
+        "project": { "name": "web", "owner": "frontend" }
+    });
+    let overlay = json!({
+        "tags": ["frontend"],
+        "project": { "owner": "platform" }
+    });
+
+    assert_eq!(
+        merge_configs(&base, &overlay),
+        json!({
+            "language": "typescript",
+            "tags": ["frontend"],
+            "project": { "name": "web", "owner": "platform" }
+        })
+    );
+}
+
+#[test]
+fn test_merge_tasks_use_task_strategies() {
+    let base = json!({
+        "tasks": {
+            "build": {
+                "command": "vite build",
+                "args": ["--mode", "production"],
+                "deps": ["^:build"],
+                "env": { "NODE_ENV": "production", "CI": "true" },
+                "inputs": ["src/**/*"]
+            },
+            "lint": { "command": "eslint" }
+        }
+    });
+    let overlay = json!({
+        "tasks": {
+            "build": {
+                "args": "--emptyOutDir",
+                "deps": ["codegen"],
+                "env": { "NODE_ENV": "staging" },
+                "inputs": ["vite.config.ts"],
+                "options": { "mergeDeps": "prepend", "mergeInputs": "replace", "mergeEnv": "preserve" }
+            },
+            "test": { "command": "vitest" }
+        }
+    });
+
+    let merged = merge_configs(&base, &overlay);
+    let build = &merged["tasks"]["build"];
+    assert_eq!(build["command"], "vite build");
+    assert_eq!(build["args"], json!(["--mode", "production", "--emptyOutDir"]));
+    assert_eq!(build["deps"], json!(["codegen", "^:build"]));
+    assert_eq!(build["env"], json!({ "NODE_ENV": "production", "CI": "true" }));
+    assert_eq!(build["inputs"], json!(["vite.config.ts"]));
+    assert_eq!(merged["tasks"]["lint"]["command"], "eslint");
+    assert_eq!(merged["tasks"]["test"]["command"], "vitest");
+}
+
+#[test]
+fn test_merge_field_strategies() {
+    let base = json!({ "A": "1", "B": "1" });
+    let overlay = json!({ "B": "2" });
+
+    assert_eq!(merge_field(&base, &overlay, MergeStrategy::Append), json!({ "A": "1", "B": "2" }));
+    assert_eq!(merge_field(&base, &overlay, MergeStrategy::Prepend), json!({ "A": "1", "B": "1" }));
+    assert_eq!(merge_field(&base, &overlay, MergeStrategy::Replace), overlay);
+    assert_eq!(merge_field(&base, &overlay, MergeStrategy::Preserve), base);
+    assert_eq!("PREPEND".parse::<MergeStrategy>().unwrap(), MergeStrategy::Prepend);
+}
+
+#[test]
+fn test_merge_task_keeps_base_options_when_overlay_has_none() {
+    let base = json!({
+        "tasks": {
+            "test": {
+                "command": "jest",
+                "args": ["--ci"],
+                "deps": ["^:build"],
+                "options": { "merge": "replace", "mergeArgs": "append" }
+            }
+        }
+    });
+    let overlay = json!({ "tasks": { "test": { "args": ["--coverage"], "deps": ["codegen"] } } });
+
+    let merged = merge_configs(&base, &overlay);
+    let test = &merged["tasks"]["test"];
+    assert_eq!(test["args"], json!(["--ci", "--coverage"]));
+    assert_eq!(test["deps"], json!(["codegen"]));
+    assert_eq!(test["options"], json!({ "merge": "replace", "mergeArgs": "append" }));
+}
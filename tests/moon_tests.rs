@@ -0,0 +1,32 @@
+use serde_json::json;
+use space_pklr::types::LoadedConfig;
+use space_pklr::types::moon::UnknownConfig;
+
+#[test]
+fn test_typed_accessors() {
+    let project = LoadedConfig::Project(Default::default());
+    assert!(project.as_project().is_some());
+    assert!(project.as_workspace().is_none());
+    assert!(project.as_unknown().is_none());
+    assert!(project.to_value().unwrap().is_object());
+
+    let unknown = LoadedConfig::Unknown(UnknownConfig::new(json!({ "name": "x" })));
+    assert!(unknown.as_project().is_none());
+    assert_eq!(unknown.as_unknown().unwrap().content, json!({ "name": "x" }));
+}
+
+#[test]
+fn test_iter_values() {
+    let config = LoadedConfig::Unknown(UnknownConfig::new(json!({
+        "language": "rust",
+        "tasks": { "build": { "deps": ["lint", "test"] } }
+    })));
+    let paths: Vec<String> = config.iter_values().unwrap().map(|(path, _)| path).collect();
+    assert_eq!(
+        paths,
+        vec!["language", "tasks", "tasks.build", "tasks.build.deps", "tasks.build.deps.0", "tasks.build.deps.1"]
+    );
+
+    let leaf = config.iter_values().unwrap().find(|(path, _)| path == "tasks.build.deps.1");
+    assert_eq!(leaf.unwrap().1, json!("test"));
+}
@@ -0,0 +1,112 @@
+#![cfg(feature = "native_pkl")]
+
+use serde_json::json;
+use space_pklr::native_pkl::parse;
+use space_pklr::types::CliError;
+
+#[test]
+fn test_parse_literal_config() {
+    let config = parse(
+        r##"
+/// Web app
+amends "package://github.com/moonrepo/moon/releases/download/pkl-v1/moon@1#/Project.pkl"
+
+language = "typescript"
+tags { "frontend"; "vite" }
+env = new Mapping { ["NODE_ENV"] = "production" }
+tasks {
+  ["build"] {
+    command = "vite build --out \"dist\""
+    deps = new Listing<String> { "^:build" }
+    options { retryCount = 2; cache = true; timeout = -1.5e2 }
+  }
+  ["build"] { inputs = List("src/**/*", "package.json") }
+}
+owners = Map("defaultOwner", null, "count", 0x1F)
+notes = """
+    First line
+      indented \t tab
+    """
+pattern = #"C:\dir\#n"#
+"##,
+    )
+    .unwrap();
+
+    assert_eq!(
+        config,
+        json!({
+            "language": "typescript",
+            "tags": ["frontend", "vite"],
+            "env": { "NODE_ENV": "production" },
+            "tasks": {
+                "build": {
+                    "command": "vite build --out \"dist\"",
+                    "deps": ["^:build"],
+                    "options": { "retryCount": 2, "cache": true, "timeout": -150.0 },
+                    "inputs": ["src/**/*", "package.json"],
+                }
+            },
+            "owners": { "defaultOwner": null, "count": 31 },
+            "notes": "First line\n  indented \t tab",
+            "pattern": "C:\\dir\n",
+        })
+    );
+}
+
+#[test]
+fn test_expressions_need_evaluation() {
+    for (source, construct, line) in [
+        ("timeout = 5.min", "unit", 1),
+        ("name = \"web\"\nid = \"\\(name)-app\"", "String interpolation", 2),
+        ("a = 1\nb = a", "reference to `a`", 2),
+        ("count = 1 + 2", "`+` operator", 1),
+        ("import \"base.pkl\"\nx = 1", "`import`", 1),
+        ("tags {\n  for (t in List(1)) { t }\n}", "`for` generator", 2),
+        ("x = read(\"env:HOME\")", "`read`", 1),
+    ] {
+        match parse(source) {
+            Err(CliError::PklNeedsEvaluation { construct: found, line: found_line, .. }) => {
+                assert!(found.contains(construct), "{}: {}", source, found);
+                assert_eq!(found_line, line, "{}", source);
+            }
+            other => panic!("{} parsed as {:?}", source, other),
+        }
+    }
+
+    // Syntax errors are reported as such, not as needing evaluation
+    assert!(matches!(parse("tasks {"), Err(CliError::Generic(_))));
+    assert!(matches!(parse("name = \"unterminated"), Err(CliError::Generic(_))));
+}
+
+#[test]
+fn test_empty_bodies_take_the_amended_module_type() {
+    let amends = "amends \"package://github.com/moonrepo/moon/releases/download/pkl-v1/moon@1#/Project.pkl\"\n";
+    let config = parse(&format!(
+        "{}tags {{}}\nenv {{}}\ntasks {{\n  [\"build\"] {{\n    deps {{}}\n    options {{}}\n  }}\n}}\n",
+        amends
+    ))
+    .unwrap();
+    assert_eq!(
+        config,
+        json!({
+            "tags": [],
+            "env": {},
+            "tasks": { "build": { "deps": [], "options": {} } },
+        })
+    );
+
+    // Explicit types don't need the schema
+    assert_eq!(
+        parse("tags = new Listing {}\nenv = new Mapping {}").unwrap(),
+        json!({ "tags": [], "env": {} })
+    );
+
+    // Without a known amended module, an empty body could be either
+    match parse("tags {}") {
+        Err(CliError::PklNeedsEvaluation { construct, line, .. }) => {
+            assert!(construct.contains("empty body"), "{}", construct);
+            assert_eq!(line, 1);
+        }
+        other => panic!("parsed as {:?}", other),
+    }
+}
@@ -0,0 +1,99 @@
+use space_pklr::network::{Operation, RetryPolicy};
+use space_pklr::tool_config::ToolConfig;
+use std::time::Duration;
+
+fn load(content: &str) -> ToolConfig {
+    let dir = tempfile::tempdir().unwrap();
+    let config_path = dir.path().join("spklr.toml");
+    std::fs::write(&config_path, content).unwrap();
+    ToolConfig::load(&config_path).unwrap()
+}
+
+#[test]
+fn test_operation_defaults() {
+    let config = ToolConfig::default();
+
+    let download = config.retry_policy(Operation::Download);
+    assert_eq!(download.max_attempts, 4);
+    assert_eq!(download.timeout, None);
+
+    let link_check = config.retry_policy(Operation::LinkCheck);
+    assert_eq!(link_check.max_attempts, 2);
+    assert_eq!(link_check.timeout, Some(Duration::from_secs(10)));
+
+    assert_eq!(config.retry_policy(Operation::Github), RetryPolicy::default());
+}
+
+#[test]
+fn test_network_settings_layer() {
+    let config = load(
+        r#"
+[download]
+max_attempts = 3
+backoff_secs = 5
+
+[network]
+max_attempts = 2
+initial_backoff_ms = 250
+timeout_secs = 60
+jitter = 0
+
+[network.operations.download]
+max_attempts = 8
+
+[network.operations.link-check]
+timeout_secs = 0
+"#,
+    );
+
+    // [network] applies everywhere, over each operation's defaults
+    let github = config.retry_policy(Operation::Github);
+    assert_eq!(github.max_attempts, 2);
+    assert_eq!(github.initial_backoff, Duration::from_millis(250));
+    assert_eq!(github.timeout, Some(Duration::from_secs(60)));
+    assert_eq!(github.jitter, 0.0);
+
+    // The legacy [download] keys beat [network]; the operation table beats both
+    let download = config.retry_policy(Operation::Download);
+    assert_eq!(download.max_attempts, 8);
+    assert_eq!(download.initial_backoff, Duration::from_secs(5));
+
+    // 0 removes the limit
+    assert_eq!(config.retry_policy(Operation::LinkCheck).timeout, None);
+
+    assert!(config.network.unknown_operations().is_empty());
+}
+
+#[test]
+fn test_unknown_operations() {
+    let config = load("[network.operations.downlaod]\nmax_attempts = 1\n");
+
+    assert_eq!(config.network.unknown_operations(), vec!["downlaod"]);
+    assert_eq!(config.retry_policy(Operation::Download).max_attempts, 4);
+}
+
+#[test]
+fn test_jitter_stays_within_bounds() {
+    let policy = RetryPolicy {
+        jitter: 0.5,
+        max_backoff: Duration::from_secs(10),
+        ..Default::default()
+    };
+
+    let delay = Duration::from_secs(4);
+    assert_eq!(policy.jittered(delay, 0.0), Duration::from_secs(2));
+    assert_eq!(policy.jittered(delay, 0.5), Duration::from_secs(4));
+    assert_eq!(policy.jittered(Duration::from_secs(8), 0.99), Duration::from_secs(10));
+
+    for attempt in 1..6 {
+        let delay = policy.delay(attempt, None);
+        assert!(delay >= policy.backoff(attempt) / 2 && delay <= policy.max_backoff);
+    }
+
+    // Retry-After is honored as given, up to max_backoff
+    assert_eq!(policy.delay(1, Some(Duration::from_secs(3))), Duration::from_secs(3));
+    assert_eq!(policy.delay(1, Some(Duration::from_secs(300))), Duration::from_secs(10));
+
+    let steady = RetryPolicy { jitter: 0.0, ..Default::default() };
+    assert_eq!(steady.delay(2, None), Duration::from_secs(2));
+}
@@ -0,0 +1,60 @@
+use serde_json::{Value, json};
+use space_pklr::partials::{json_schema_partials, typescript_partials};
+
+#[test]
+fn test_json_schema_partials() {
+    let schema = json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": "ProjectConfig",
+        "type": "object",
+        "required": ["language"],
+        "properties": {
+            "language": { "type": "string" },
+            "owners": { "$ref": "#/definitions/OwnersConfig" }
+        },
+        "definitions": {
+            "OwnersConfig": {
+                "type": "object",
+                "required": ["paths"],
+                "properties": {
+                    "paths": { "type": "array", "items": { "type": "string" } },
+                    "defaultOwner": { "anyOf": [{ "$ref": "#/definitions/Owner" }, { "type": "null" }] }
+                }
+            },
+            "Owner": { "type": "string" }
+        }
+    });
+
+    let rendered = json_schema_partials(&schema.to_string()).unwrap();
+    let partial: Value = serde_json::from_str(&rendered).unwrap();
+    let definitions = &partial["definitions"];
+
+    // Full definitions are untouched
+    assert_eq!(definitions["OwnersConfig"]["required"], json!(["paths"]));
+    assert_eq!(partial["required"], json!(["language"]));
+
+    let owners = &definitions["OwnersConfigPartial"];
+    assert!(owners.get("required").is_none());
+    assert_eq!(
+        owners["properties"]["defaultOwner"]["anyOf"][0]["$ref"],
+        "#/definitions/OwnerPartial"
+    );
+
+    let root = &definitions["ProjectConfigPartial"];
+    assert_eq!(root["title"], "ProjectConfigPartial");
+    assert!(root.get("required").is_none());
+    assert_eq!(root["properties"]["owners"]["$ref"], "#/definitions/OwnersConfigPartial");
+}
+
+#[test]
+fn test_typescript_partials() {
+    let ts = "export interface ProjectConfig {\n\tlanguage: string;\n}\n\nexport interface TaskConfig {\n}\n";
+    let rendered = typescript_partials(ts);
+
+    assert!(rendered.starts_with(ts.trim_end()));
+    assert!(rendered.contains("export type DeepPartial<T>"));
+    assert!(rendered.contains("export type ProjectConfigPartial = DeepPartial<ProjectConfig>;"));
+    assert!(rendered.contains("export type TaskConfigPartial = DeepPartial<TaskConfig>;"));
+
+    assert_eq!(typescript_partials("export type Id = string;\n"), "export type Id = string;\n");
+}
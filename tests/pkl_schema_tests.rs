@@ -0,0 +1,954 @@
+use serde_json::json;
+use space_pklr::pkl_schema::json_schema::{pascal_case, to_module, type_schema};
+use space_pklr::pkl_schema::extensions::{ensure_stub, ext_path};
+use space_pklr::pkl_schema::imports::split_types;
+use space_pklr::pkl_schema::{GeneratorConfig, PklType, SchemaGenerator, identifier};
+use tempfile::TempDir;
+
+fn service_schema() -> serde_json::Value {
+    json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": "service-config",
+        "description": "Service settings",
+        "type": "object",
+        "required": ["name", "ports"],
+        "properties": {
+            "name": { "type": "string", "description": "Service name" },
+            "ports": { "type": "array", "items": { "type": "integer" } },
+            "tier": { "$ref": "#/definitions/Tier" },
+            "labels": { "type": "object", "additionalProperties": { "type": "string" } },
+            "health-check": {
+                "type": "object",
+                "required": ["path"],
+                "properties": {
+                    "path": { "type": "string" },
+                    "interval": { "type": ["number", "null"] }
+                }
+            },
+            "owner": { "anyOf": [{ "$ref": "#/$defs/owner" }, { "type": "null" }] }
+        },
+        "definitions": {
+            "Tier": { "enum": ["frontend", "backend"], "description": "Service tier" }
+        },
+        "$defs": {}
+    })
+}
+
+#[test]
+fn test_json_schema_to_module() {
+    let module = to_module(&service_schema(), "fallback").unwrap();
+    assert_eq!(module.name, "ServiceConfig");
+    assert_eq!(module.classes.len(), 1);
+    assert_eq!(module.classes[0].name, "ServiceConfigHealthCheck");
+    assert_eq!(module.typealiases[0].name, "Tier");
+
+    let rendered = module.render();
+    assert_eq!(
+        rendered,
+        r#"/// Service settings
+open module ServiceConfig
+
+/// Service name
+name: String
+ports: Listing<Int>
+tier: Tier?
+labels: Mapping<String, String>?
+`health-check`: ServiceConfigHealthCheck?
+owner: Owner?
+
+open class ServiceConfigHealthCheck {
+  path: String
+  interval: Number?
+}
+
+/// Service tier
+typealias Tier = "frontend"|"backend"
+"#
+    );
+}
+
+#[test]
+fn test_json_schema_types() {
+    assert_eq!(PklType::Union(vec![PklType::String, PklType::Int]).nullable().render(), "(String|Int)?");
+    assert_eq!(PklType::String.nullable().nullable().render(), "String?");
+    assert_eq!(identifier("fileGroups"), "fileGroups");
+    assert_eq!(identifier("file-groups"), "`file-groups`");
+    assert_eq!(identifier("extends"), "`extends`");
+    assert_eq!(pascal_case("vcs_config"), "VcsConfig");
+
+    let schema = json!({ "properties": { "remote": { "$ref": "https://example.com/remote.json" } } });
+    assert!(to_module(&schema, "remote").is_err());
+}
+
+#[test]
+fn test_schema_generator_from_json_schema_file() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("deploy.schema.json");
+    std::fs::write(&path, json!({ "properties": { "region": { "type": "string" } } }).to_string()).unwrap();
+
+    let module = SchemaGenerator::from_json_schema(&path).unwrap();
+    assert_eq!(module.name, "Deploy");
+    assert_eq!(module.render(), "open module Deploy\n\nregion: String?\n");
+}
+
+#[test]
+fn test_module_to_json_schema() {
+    let module = to_module(&service_schema(), "fallback").unwrap();
+    let exported = module.to_json_schema();
+
+    assert_eq!(exported["title"], "ServiceConfig");
+    assert_eq!(exported["required"], json!(["name", "ports"]));
+    assert_eq!(exported["properties"]["ports"], json!({ "type": "array", "items": { "type": "integer" } }));
+    assert_eq!(
+        exported["properties"]["tier"],
+        json!({ "anyOf": [{ "$ref": "#/definitions/Tier" }, { "type": "null" }] })
+    );
+    assert_eq!(exported["definitions"]["Tier"], json!({ "enum": ["frontend", "backend"], "description": "Service tier" }));
+    assert_eq!(
+        exported["definitions"]["ServiceConfigHealthCheck"]["properties"]["interval"],
+        json!({ "type": ["number", "null"] })
+    );
+
+    // Importing the export reproduces the module
+    assert_eq!(to_module(&exported, "fallback").unwrap(), module);
+}
+
+#[test]
+fn test_nullable_literal_union_schema() {
+    let ty = PklType::Union(vec![PklType::StringLiteral("a".to_string()), PklType::StringLiteral("b".to_string())]).nullable();
+    assert_eq!(type_schema(&ty), json!({ "enum": ["a", "b", null] }));
+}
+
+#[test]
+fn test_extension_stub_created_once() {
+    let dir = TempDir::new().unwrap();
+    let module_path = dir.path().join("service-config.pkl");
+    let module = to_module(&service_schema(), "fallback").unwrap();
+
+    assert_eq!(ext_path(&module_path), dir.path().join("service-config.ext.pkl"));
+    let stub = ensure_stub(&module_path, &module).unwrap().unwrap();
+    let content = std::fs::read_to_string(&stub).unwrap();
+    assert!(content.contains("open module ServiceConfigExt\n\nextends \"service-config.pkl\"\n"));
+    assert!(content.ends_with("open class ServiceConfigHealthCheckExt extends ServiceConfigHealthCheck {\n}\n"));
+
+    // User edits survive regeneration
+    std::fs::write(&stub, "extends \"service-config.pkl\"\n\nteam: String\n").unwrap();
+    assert!(ensure_stub(&module_path, &module).unwrap().is_none());
+    assert_eq!(std::fs::read_to_string(&stub).unwrap(), "extends \"service-config.pkl\"\n\nteam: String\n");
+
+    let closed = space_pklr::pkl_schema::PklModule { open: false, ..module };
+    assert!(ensure_stub(&dir.path().join("closed.pkl"), &closed).unwrap().is_none());
+}
+
+#[test]
+fn test_type_prefix_and_suffix_rewrite_references() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("service.json");
+    std::fs::write(&path, service_schema().to_string()).unwrap();
+
+    let config = GeneratorConfig {
+        type_prefix: Some("Acme".to_string()),
+        type_suffix: Some("V1".to_string()),
+        ..Default::default()
+    };
+    let module = SchemaGenerator::new(config).generate_from_json_schema(&path).unwrap();
+    assert_eq!(module.name, "ServiceConfig");
+
+    let rendered = module.render();
+    assert!(rendered.contains("tier: AcmeTierV1?\n"));
+    assert!(rendered.contains("`health-check`: AcmeServiceConfigHealthCheckV1?\n"));
+    assert!(rendered.contains("open class AcmeServiceConfigHealthCheckV1 {\n"));
+    assert!(rendered.contains("typealias AcmeTierV1 = \"frontend\"|\"backend\"\n"));
+    assert_eq!(
+        module.to_json_schema()["properties"]["tier"]["anyOf"][0]["$ref"],
+        "#/definitions/AcmeTierV1"
+    );
+}
+
+#[test]
+fn test_type_mappings_replace_names() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("service.json");
+    std::fs::write(&path, service_schema().to_string()).unwrap();
+
+    let config = GeneratorConfig {
+        type_prefix: Some("Acme".to_string()),
+        type_mappings: [("Tier".to_string(), "ServiceTier".to_string())].into_iter().collect(),
+        ..Default::default()
+    };
+    let rendered = SchemaGenerator::new(config).generate_from_json_schema(&path).unwrap().render();
+    // Mapped names skip the prefix; the rest keep it
+    assert!(rendered.contains("tier: ServiceTier?\n"));
+    assert!(rendered.contains("typealias ServiceTier = \"frontend\"|\"backend\"\n"));
+    assert!(rendered.contains("open class AcmeServiceConfigHealthCheck {\n"));
+}
+
+#[test]
+fn test_deprecations_and_examples_round_trip() {
+    let schema = json!({
+        "title": "Service",
+        "type": "object",
+        "properties": {
+            "port": { "type": "integer", "examples": [80, 443] },
+            "host": { "type": "string", "deprecated": true, "deprecationMessage": "Use address" },
+            "legacy": { "type": "string", "deprecated": true }
+        }
+    });
+    let module = to_module(&schema, "service").unwrap();
+    assert_eq!(module.properties[0].examples, vec![json!(80), json!(443)]);
+    assert_eq!(module.properties[1].deprecated.as_deref(), Some("Use address"));
+    assert_eq!(module.properties[2].deprecated.as_deref(), Some(""));
+    // Neither changes the Pkl source
+    assert!(module.render().contains("port: Int?\nhost: String?\nlegacy: String?\n"));
+
+    let exported = module.to_json_schema();
+    assert_eq!(exported["properties"]["port"]["examples"], json!([80, 443]));
+    assert_eq!(exported["properties"]["host"]["deprecationMessage"], "Use address");
+    assert_eq!(exported["properties"]["legacy"]["deprecated"], true);
+    assert!(exported["properties"]["legacy"].get("deprecationMessage").is_none());
+}
+
+#[test]
+fn test_split_types_imports_shared_types() {
+    let owner = json!({ "type": "object", "properties": { "team": { "type": "string" } } });
+    let tasks = json!({
+        "title": "Tasks",
+        "type": "object",
+        "properties": { "owner": { "$ref": "#/definitions/Owner" } },
+        "definitions": { "Owner": owner }
+    });
+    let project = json!({
+        "title": "Project",
+        "type": "object",
+        "properties": {
+            "owner": { "$ref": "#/definitions/Owner" },
+            "backup": { "type": "array", "items": { "$ref": "#/definitions/Owner" } }
+        },
+        "definitions": { "Owner": owner }
+    });
+    let mut modules = vec![to_module(&tasks, "tasks").unwrap(), to_module(&project, "project").unwrap()];
+
+    split_types(&mut modules);
+    let (tasks, project) = (&modules[0], &modules[1]);
+    assert_eq!(tasks.classes.len(), 1);
+    assert!(tasks.imports.is_empty());
+    assert!(project.classes.is_empty());
+    assert_eq!(project.imports.len(), 1);
+
+    let rendered = project.render();
+    assert!(rendered.starts_with("open module Project\n\nimport \"Tasks.pkl\" as tasks\n\n"));
+    assert!(rendered.contains("owner: tasks.Owner?\n"));
+    assert!(rendered.contains("backup: Listing<tasks.Owner>?\n"));
+    assert_eq!(
+        project.to_json_schema()["properties"]["owner"]["anyOf"][0]["$ref"],
+        "Tasks.json#/definitions/Owner"
+    );
+}
+
+#[test]
+fn test_split_types_keeps_conflicting_definitions() {
+    let tasks = json!({
+        "title": "Tasks",
+        "type": "object",
+        "properties": { "build": { "$ref": "#/definitions/TaskConfig" } },
+        "definitions": { "TaskConfig": { "type": "object", "properties": { "command": { "type": "string" } } } }
+    });
+    let project = json!({
+        "title": "Project",
+        "type": "object",
+        "properties": {
+            "tasks": { "type": "object", "additionalProperties": { "$ref": "#/definitions/TaskConfig" } }
+        },
+        "definitions": { "TaskConfig": { "type": "object", "properties": { "script": { "type": "string" } } } }
+    });
+    let mut modules = vec![to_module(&tasks, "tasks").unwrap(), to_module(&project, "project").unwrap()];
+
+    let conflicts = split_types(&mut modules);
+    assert_eq!(conflicts, vec!["TaskConfig".to_string()]);
+    let (tasks, project) = (&modules[0], &modules[1]);
+    assert_eq!(tasks.classes[0].properties[0].name, "command");
+    assert_eq!(project.classes[0].properties[0].name, "script");
+    assert!(project.imports.is_empty());
+    assert!(project.render().contains("tasks: Mapping<String, TaskConfig>?\n"));
+}
+
+#[test]
+fn test_split_types_alias_avoids_property_names() {
+    let owner = json!({ "type": "object", "properties": { "team": { "type": "string" } } });
+    let tasks = json!({
+        "title": "Tasks",
+        "type": "object",
+        "properties": { "owner": { "$ref": "#/definitions/Owner" } },
+        "definitions": { "Owner": owner }
+    });
+    let project = json!({
+        "title": "Project",
+        "type": "object",
+        "properties": {
+            "tasks": { "type": "array", "items": { "type": "string" } },
+            "owner": { "$ref": "#/definitions/Owner" }
+        },
+        "definitions": { "Owner": owner }
+    });
+    let mut modules = vec![to_module(&tasks, "tasks").unwrap(), to_module(&project, "project").unwrap()];
+
+    assert!(split_types(&mut modules).is_empty());
+    let rendered = modules[1].render();
+    assert!(rendered.contains("import \"Tasks.pkl\" as tasksModule\n"), "{}", rendered);
+    assert!(rendered.contains("owner: tasksModule.Owner?\n"), "{}", rendered);
+}
+
+#[test]
+fn test_property_filters() {
+    use space_pklr::pkl_schema::filters::PropertyFilter;
+
+    let schema = json!({
+        "title": "Workspace",
+        "type": "object",
+        "properties": {
+            "name": { "type": "string" },
+            "toolchain": {
+                "type": "object",
+                "properties": {
+                    "node": { "$ref": "#/definitions/Tool" },
+                    "rust": { "$ref": "#/definitions/Tool" }
+                }
+            },
+            "runner": { "$ref": "#/definitions/Runner" }
+        },
+        "definitions": {
+            "Tool": {
+                "type": "object",
+                "properties": { "version": { "type": "string" }, "plugins": { "type": "array", "items": { "type": "string" } } }
+            },
+            "Runner": { "type": "object", "properties": { "cache": { "type": "boolean" } } }
+        }
+    });
+    let module = to_module(&schema, "workspace").unwrap();
+    let names = |module: &space_pklr::pkl_schema::PklModule, class: &str| -> Vec<String> {
+        module
+            .classes
+            .iter()
+            .find(|c| c.name == class)
+            .map(|c| c.properties.iter().map(|p| p.name.clone()).collect())
+            .unwrap_or_default()
+    };
+
+    let mut excluded = module.clone();
+    PropertyFilter::new(&[], &["toolchain.*.version".to_string(), "runner".to_string()]).apply(&mut excluded);
+    assert_eq!(names(&excluded, "Tool"), vec!["plugins"]);
+    assert!(excluded.properties.iter().all(|p| p.name != "runner"));
+    // Runner was only used by the excluded property
+    assert!(excluded.classes.iter().all(|c| c.name != "Runner"));
+
+    let mut included = module.clone();
+    PropertyFilter::new(&["toolchain.node.version".to_string()], &[]).apply(&mut included);
+    let top: Vec<&str> = included.properties.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(top, vec!["toolchain"]);
+    assert_eq!(names(&included, "WorkspaceToolchain"), vec!["node"]);
+    assert_eq!(names(&included, "Tool"), vec!["version"]);
+
+    let mut unchanged = module.clone();
+    PropertyFilter::default().apply(&mut unchanged);
+    assert_eq!(unchanged, module);
+}
+
+#[test]
+fn test_policy_overrides() {
+    use space_pklr::pkl_schema::overrides::apply_overrides;
+    use space_pklr::policy::Policy;
+
+    let schema = json!({
+        "title": "Workspace",
+        "type": "object",
+        "properties": {
+            "name": { "type": "string" },
+            "node": { "$ref": "#/definitions/Tool" },
+            "tags": { "type": "array", "items": { "type": "string" } }
+        },
+        "definitions": {
+            "Tool": { "type": "object", "properties": { "version": { "type": "string" } } }
+        }
+    });
+    let mut module = to_module(&schema, "workspace").unwrap();
+    let policy = Policy::from_value(json!({
+        "overrides": [
+            { "path": "name", "required": true },
+            { "path": "node.version", "default": "20.11.0", "fixed": true },
+            { "path": "tags", "default": ["ci"] },
+            { "path": "missing", "required": true },
+            { "path": "name", "appliesTo": ["project"], "required": false }
+        ]
+    }))
+    .unwrap();
+
+    let matched = apply_overrides(&mut module, &policy.overrides);
+    assert_eq!(matched, vec!["name", "node.version", "tags"]);
+    let rendered = module.render();
+    assert!(rendered.contains("\nname: String\n"));
+    assert!(rendered.contains("fixed version: String? = \"20.11.0\""));
+    assert!(rendered.contains("tags: Listing<String>? = new Listing { \"ci\" }"));
+
+    let exported = module.to_json_schema();
+    assert_eq!(exported["required"], json!(["name"]));
+    assert_eq!(exported["definitions"]["Tool"]["properties"]["version"]["const"], json!("20.11.0"));
+
+    assert!(Policy::from_value(json!({ "overrides": [{ "path": "name", "fixed": true }] })).is_err());
+}
+
+#[test]
+fn test_policy_constraints() {
+    use space_pklr::pkl_schema::overrides::apply_overrides;
+    use space_pklr::policy::Policy;
+
+    let schema = json!({
+        "title": "Toolchain",
+        "type": "object",
+        "properties": {
+            "version": { "type": "string" },
+            "channel": { "type": "string" }
+        },
+        "required": ["channel"]
+    });
+    let mut module = to_module(&schema, "toolchain").unwrap();
+    let policy = Policy::from_value(json!({
+        "overrides": [
+            { "path": "version", "constraints": ["startsWith(\"20.\")"], "reason": "Node 20 only" },
+            { "path": "version", "constraints": ["startsWith(\"20.\")", "length < 10"] },
+            { "path": "channel", "constraints": ["this != \"nightly\""] }
+        ]
+    }))
+    .unwrap();
+    apply_overrides(&mut module, &policy.overrides);
+
+    let rendered = module.render();
+    assert!(rendered.contains(
+        "// Org policy: Node 20 only\n// Org policy: override of `version`\nversion: String(startsWith(\"20.\"), length < 10)?\n"
+    ));
+    assert!(rendered.contains("// Org policy: override of `channel`\nchannel: String(this != \"nightly\")\n"));
+    assert_eq!(
+        module.to_json_schema()["properties"]["version"]["$comment"],
+        json!("Pkl constraints: startsWith(\"20.\"), length < 10")
+    );
+
+    assert!(Policy::from_value(json!({ "overrides": [{ "path": "name", "constraints": [" "] }] })).is_err());
+}
+
+#[test]
+fn test_parallel_generation_keeps_order() {
+    use space_pklr::pkl_schema::parallel;
+
+    let dir = TempDir::new().unwrap();
+    let paths: Vec<_> = (0..9)
+        .map(|index| {
+            let path = dir.path().join(format!("module{}.schema.json", index));
+            std::fs::write(&path, json!({ "properties": { "value": { "type": "string" } } }).to_string()).unwrap();
+            path
+        })
+        .collect();
+
+    for concurrency in [None, Some(1), Some(4)] {
+        let generator = SchemaGenerator::new(GeneratorConfig { concurrency, ..Default::default() });
+        let names: Vec<String> = generator
+            .generate_all_from_json_schema(&paths)
+            .unwrap()
+            .into_iter()
+            .map(|module| module.name)
+            .collect();
+        let expected: Vec<String> = (0..9).map(|index| format!("Module{}", index)).collect();
+        assert_eq!(names, expected);
+    }
+
+    let mut missing = paths.clone();
+    missing.push(dir.path().join("missing.schema.json"));
+    assert!(SchemaGenerator::default().generate_all_from_json_schema(&missing).is_err());
+
+    assert_eq!(parallel::threads(Some(8), 3), 3);
+    assert_eq!(parallel::threads(Some(2), 0), 1);
+    assert_eq!(parallel::map(&[1, 2, 3, 4, 5], Some(2), |n| n * 10), vec![10, 20, 30, 40, 50]);
+}
+
+#[test]
+fn test_write_with_streams_rendered_module() {
+    use space_pklr::templates::{TemplateContext, TemplateOverrides};
+
+    let module = to_module(&service_schema(), "fallback").unwrap();
+    let mut streamed = Vec::new();
+    module
+        .write_with(&mut streamed, &TemplateOverrides::default(), &TemplateContext::default())
+        .unwrap();
+    assert_eq!(String::from_utf8(streamed).unwrap(), module.render());
+}
+
+#[test]
+fn test_ir_roundtrip_and_upgrade() {
+    use space_pklr::pkl_schema::ir;
+
+    let module = to_module(&service_schema(), "fallback").unwrap();
+    let document = ir::to_json(std::slice::from_ref(&module));
+    assert_eq!(document["schema_version"], ir::SCHEMA_VERSION);
+    assert_eq!(ir::from_json(document).unwrap(), vec![module.clone()]);
+
+    // Unversioned documents are a bare module or a list of them
+    let bare = serde_json::to_value(&module).unwrap();
+    assert_eq!(ir::from_json(bare.clone()).unwrap(), vec![module.clone()]);
+    assert_eq!(ir::from_json(json!([bare])).unwrap(), vec![module]);
+
+    let sparse = json!({ "name": "Tiny", "properties": [{ "name": "port", "ty": "int" }] });
+    let tiny = &ir::from_json(sparse).unwrap()[0];
+    assert_eq!(tiny.properties[0].ty, PklType::Int);
+    assert!(!tiny.properties[0].fixed);
+
+    let newer = json!({ "schema_version": ir::SCHEMA_VERSION + 1, "modules": [] });
+    assert!(ir::from_json(newer).unwrap_err().to_string().contains("upgrade spklr"));
+    assert!(ir::from_str("\"module\"").is_err());
+}
+
+#[test]
+fn test_example_document() {
+    use space_pklr::pkl_schema::examples::{example_document, example_file_name, schema_examples};
+
+    let schema = json!({
+        "title": "Project",
+        "type": "object",
+        "required": ["language"],
+        "properties": {
+            "language": { "description": "The primary language\nMore detail", "type": "string", "examples": ["rust"] },
+            "kind": { "$ref": "#/definitions/Kind" },
+            "owners": { "type": "array", "items": { "type": "string" }, "default": ["@infra"] },
+            "options": { "anyOf": [{ "$ref": "#/definitions/Options" }, { "type": "null" }] },
+            "tasks": { "type": "object", "additionalProperties": { "$ref": "#/definitions/Task" } },
+            "extra": {}
+        },
+        "definitions": {
+            "Kind": { "enum": ["library", "application"] },
+            "Options": { "type": "object", "properties": { "cache": { "type": "boolean", "default": false } } },
+            "Task": {
+                "type": "object",
+                "properties": {
+                    "command": { "type": "string", "examples": ["cargo build"] },
+                    "deps": { "type": "array", "items": { "$ref": "#/definitions/Task" } }
+                }
+            }
+        }
+    });
+    let examples = schema_examples(&schema);
+    assert_eq!(examples["kind"], json!("library"));
+    assert_eq!(examples["tasks.*.command"], json!("cargo build"));
+
+    let module = SchemaGenerator::default().generate_from_json_value(&schema, "Project").unwrap();
+    assert_eq!(example_file_name(&module), "Project.example.pkl");
+    let document = example_document(&module, &[], &examples, "../Project.pkl");
+    assert_eq!(
+        document,
+        r#"amends "../Project.pkl"
+
+/// The primary language
+language = "rust"
+kind = "library"
+owners {
+  "@infra"
+}
+options {
+  cache = false
+}
+tasks {
+  ["example"] {
+    command = "cargo build"
+  }
+}
+"#
+    );
+}
+
+#[test]
+fn test_toolchain_plugin_schema_merged_as_classes() {
+    use space_pklr::pkl_schema::toolchain_plugins::{manifest_schema, merge_plugin};
+
+    let toolchain_schema = json!({
+        "title": "Toolchain",
+        "type": "object",
+        "properties": {
+            "deno": { "type": "object", "additionalProperties": true },
+            "node": { "$ref": "#/definitions/NodeConfig" }
+        },
+        "definitions": {
+            "NodeConfig": { "type": "object", "properties": { "version": { "type": "string" } } }
+        }
+    });
+    let mut toolchain = to_module(&toolchain_schema, "toolchain").unwrap();
+    let manifest = json!({
+        "id": "deno",
+        "schema": {
+            "title": "DenoSettings",
+            "description": "Deno toolchain settings",
+            "type": "object",
+            "properties": {
+                "permissions": { "type": "array", "items": { "$ref": "#/definitions/Permission" } },
+                "install": { "type": "object", "properties": { "lockfile": { "type": "boolean" } } }
+            },
+            "definitions": {
+                "Permission": { "enum": ["net", "read"] }
+            }
+        }
+    });
+    merge_plugin(&mut toolchain, "deno", &manifest_schema(manifest)).unwrap();
+
+    let rendered = toolchain.render();
+    assert!(rendered.contains("/// Deno toolchain settings\ndeno: DenoPluginConfig?\n"));
+    assert!(rendered.contains(
+        "/// Deno toolchain settings\nopen class DenoPluginConfig {\n  permissions: Listing<DenoPluginPermission>?\n  install: DenoPluginInstall?\n}\n"
+    ));
+    assert!(rendered.contains("open class DenoPluginInstall {\n  lockfile: Boolean?\n}\n"));
+    assert!(rendered.contains("typealias DenoPluginPermission = \"net\"|\"read\"\n"));
+
+    // A second plugin is added as a new property, and type clashes are refused
+    merge_plugin(&mut toolchain, "zig", &json!({ "type": "object", "properties": {} })).unwrap();
+    assert!(toolchain.render().contains("/// Settings for the `zig` toolchain plugin\nzig: ZigPluginConfig?\n"));
+    assert!(merge_plugin(&mut toolchain, "zig", &json!({ "type": "object", "properties": {} })).is_err());
+}
+
+#[test]
+fn test_property_case() {
+    use space_pklr::pkl_schema::casing::{Case, recase_value};
+
+    assert_eq!(Case::Camel.apply("cache_dir"), "cacheDir");
+    assert_eq!(Case::Snake.apply("HTTPServerURL"), "http_server_url");
+    assert_eq!(Case::Snake.apply("inheritedTasks"), "inherited_tasks");
+    assert_eq!(Case::Kebab.apply("vcs_config"), "vcs-config");
+    assert_eq!(Case::Pascal.apply("health-check"), "HealthCheck");
+    assert_eq!(Case::Snake.apply("$schema"), "$schema");
+    assert_eq!(Case::Preserve.apply("cache_dir"), "cache_dir");
+
+    let schema = to_module(&service_schema(), "fallback").unwrap();
+    let mut config = GeneratorConfig {
+        property_case: Case::Snake,
+        ..Default::default()
+    };
+    config
+        .property_case_overrides
+        .insert("ServiceConfigHealthCheck".to_string(), Case::Camel);
+    let module = SchemaGenerator::new(config.clone()).generate_from_json_value(&service_schema(), "fallback").unwrap();
+    let rendered = module.render();
+    assert!(rendered.contains("\nhealth_check: ServiceConfigHealthCheck?\n"));
+    assert!(rendered.contains("open class ServiceConfigHealthCheck {\n  path: String\n"));
+
+    // Converted configs get the same names, but mapping keys are kept
+    let value = json!({
+        "name": "api",
+        "health-check": { "path": "/healthz" },
+        "labels": { "team-name": "core" }
+    });
+    assert_eq!(
+        recase_value(&value, &schema, &config),
+        json!({
+            "name": "api",
+            "health_check": { "path": "/healthz" },
+            "labels": { "team-name": "core" }
+        })
+    );
+}
+
+#[test]
+fn test_constraint_providers() {
+    use space_pklr::pkl_schema::PklConstraint;
+    use space_pklr::pkl_schema::constraints::{ConstraintProvider, ConstraintRule, Field};
+
+    struct PositiveNumbers;
+
+    impl ConstraintProvider for PositiveNumbers {
+        fn constraints(&self, field: &Field<'_>) -> Vec<PklConstraint> {
+            match field.property.ty {
+                PklType::Nullable(ref inner) if **inner == PklType::Number => vec![PklConstraint {
+                    expression: "this > 0".to_string(),
+                    provenance: None,
+                }],
+                _ => Vec::new(),
+            }
+        }
+    }
+
+    let config = GeneratorConfig {
+        constraints: vec![
+            ConstraintRule {
+                property: "ports".to_string(),
+                expression: "every((port) -> port > 1024)".to_string(),
+                reason: Some("Ports below 1024 need root".to_string()),
+            },
+            ConstraintRule {
+                property: "ServiceConfigHealthCheck.path".to_string(),
+                expression: "startsWith(\"/\")".to_string(),
+                reason: None,
+            },
+            ConstraintRule {
+                property: "Other.name".to_string(),
+                expression: "!isEmpty".to_string(),
+                reason: None,
+            },
+        ],
+        ..Default::default()
+    };
+    let module = SchemaGenerator::new(config)
+        .with_constraint_provider(PositiveNumbers)
+        .generate_from_json_value(&service_schema(), "fallback")
+        .unwrap();
+    let rendered = module.render();
+    assert!(rendered.contains("// Org rule: Ports below 1024 need root\nports: Listing<Int>(every((port) -> port > 1024))\n"));
+    assert!(rendered.contains("  // Org rule: constraint on `ServiceConfigHealthCheck.path`\n  path: String(startsWith(\"/\"))\n"));
+    assert!(rendered.contains("  interval: Number(this > 0)?\n"));
+    assert!(rendered.contains("\nname: String\n"));
+}
+
+#[test]
+fn test_documented_enum_variants() {
+    let schema = json!({
+        "title": "Project",
+        "type": "object",
+        "properties": {
+            "kind": { "$ref": "#/definitions/Kind" },
+            "stack": { "$ref": "#/definitions/Stack" }
+        },
+        "definitions": {
+            "Kind": {
+                "description": "Kind of project",
+                "oneOf": [
+                    { "const": "library", "description": "A reusable package" },
+                    { "const": "application", "description": "A deployable app\nwith its own entry point" },
+                    { "const": "package", "deprecated": true, "deprecationMessage": "Use \"library\"" }
+                ]
+            },
+            "Stack": {
+                "type": "string",
+                "enum": ["frontend", "backend"],
+                "enumDescriptions": ["Runs in the browser", ""]
+            }
+        }
+    });
+    let module = to_module(&schema, "fallback").unwrap();
+    let rendered = module.render();
+    assert!(rendered.contains(
+        "/// Kind of project\ntypealias Kind =\n  // A reusable package\n  \"library\"\n  // A deployable app\n  // with its own entry point\n  |\"application\"\n  // Deprecated: Use \"library\"\n  |\"package\"\n"
+    ));
+    assert!(rendered.contains("typealias Stack =\n  // Runs in the browser\n  \"frontend\"\n  |\"backend\"\n"));
+    // Used inline, the type is the plain union
+    assert_eq!(module.typealiases[1].ty.render(), "\"frontend\"|\"backend\"");
+
+    // The docs survive a JSON Schema round trip
+    let exported = module.to_json_schema();
+    assert_eq!(exported["definitions"]["Kind"]["oneOf"][2], json!({ "const": "package", "deprecated": true, "deprecationMessage": "Use \"library\"" }));
+    assert_eq!(to_module(&exported, "fallback").unwrap().typealiases, module.typealiases);
+
+    // Plain enums still collapse to a literal union
+    let plain = to_module(&json!({ "type": "object", "properties": { "tier": { "enum": ["a", "b"] } } }), "Plain").unwrap();
+    assert_eq!(plain.properties[0].ty.render(), "(\"a\"|\"b\")?");
+}
+
+#[test]
+fn test_examples_meet_constraints() {
+    use space_pklr::pkl_schema::examples::{extract_examples, violation};
+
+    // Placeholders are moved inside the bounds
+    assert_eq!(extract_examples(&json!({ "type": "integer", "minimum": 100 })), vec![json!(100)]);
+    assert_eq!(extract_examples(&json!({ "type": "integer", "exclusiveMaximum": -5 })), vec![json!(-6)]);
+    assert_eq!(extract_examples(&json!({ "type": "integer", "minimum": 1, "multipleOf": 4 })), vec![json!(4)]);
+    assert_eq!(
+        extract_examples(&json!({ "type": "number", "exclusiveMinimum": 0, "exclusiveMaximum": 1 })),
+        vec![json!(0.5)]
+    );
+    assert_eq!(extract_examples(&json!({ "type": "string", "maxLength": 3 })), vec![json!("exa")]);
+    assert_eq!(extract_examples(&json!({ "type": "string", "minLength": 9 })), vec![json!("exampleex")]);
+    // No placeholder fits a pattern, so there's no example rather than a wrong one
+    assert_eq!(extract_examples(&json!({ "type": "string", "pattern": "^\\d+\\.\\d+$" })), Vec::<serde_json::Value>::new());
+
+    // Declared examples and defaults that break the constraints are skipped
+    assert_eq!(
+        extract_examples(&json!({ "type": "integer", "minimum": 100, "examples": [42, 8080] })),
+        vec![json!(8080)]
+    );
+    assert_eq!(extract_examples(&json!({ "type": "integer", "minimum": 100, "default": 42 })), vec![json!(100)]);
+    assert_eq!(
+        extract_examples(&json!({ "type": "string", "pattern": "^v", "default": "1.0", "examples": ["v1.0"] })),
+        vec![json!("v1.0")]
+    );
+
+    assert_eq!(violation(&json!(42), &json!({ "minimum": 100 })).as_deref(), Some("below the minimum 100"));
+    assert_eq!(violation(&json!(0), &json!({ "minimum": 0, "exclusiveMinimum": true })).as_deref(), Some("not above the minimum 0"));
+    assert_eq!(violation(&json!("c"), &json!({ "enum": ["a", "b"] })).as_deref(), Some("not one of [\"a\",\"b\"]"));
+    assert_eq!(violation(&json!("v1"), &json!({ "pattern": "^v\\d" })), None);
+}
+
+#[test]
+fn test_tagged_union_classes() {
+    let schema = json!({
+        "title": "Task",
+        "type": "object",
+        "required": ["deps"],
+        "properties": {
+            "deps": { "type": "array", "items": { "$ref": "#/definitions/TaskDependency" } },
+            "target": {
+                "oneOf": [
+                    { "type": "object", "required": ["kind", "id"], "properties": { "kind": { "const": "project" }, "id": { "type": "string" } } },
+                    { "type": "object", "required": ["kind"], "properties": { "kind": { "enum": ["all"] } } },
+                    { "type": "null" }
+                ]
+            }
+        },
+        "definitions": {
+            "TaskDependency": {
+                "description": "A task this one depends on",
+                "anyOf": [{ "$ref": "#/definitions/TaskDependencyTag" }, { "$ref": "#/definitions/TaskDependencyProject" }]
+            },
+            "TaskDependencyProject": {
+                "type": "object",
+                "required": ["type", "project"],
+                "properties": {
+                    "type": { "const": "project", "description": "Depend on a project's task" },
+                    "project": { "type": "string" }
+                }
+            },
+            "TaskDependencyTag": {
+                "type": "object",
+                "required": ["type", "tag"],
+                "properties": { "type": { "const": "tag" }, "tag": { "type": "string" } }
+            }
+        }
+    });
+    let module = to_module(&schema, "fallback").unwrap();
+    assert!(module.typealiases.is_empty());
+    assert_eq!(module.properties[0].ty.render(), "Listing<TaskDependency>");
+    assert_eq!(module.properties[1].ty.render(), "TaskTarget?");
+
+    let rendered = module.render();
+    assert!(rendered.contains("/// A task this one depends on\nabstract class TaskDependency {\n  type: \"tag\"|\"project\"\n}\n"));
+    assert!(rendered.contains(
+        "open class TaskDependencyProject extends TaskDependency {\n  /// Depend on a project's task\n  fixed type: \"project\"\n  project: String\n}\n"
+    ));
+    assert!(rendered.contains("open class TaskDependencyTag extends TaskDependency {\n  fixed type: \"tag\"\n  tag: String\n}\n"));
+    assert!(rendered.contains("abstract class TaskTarget {\n  kind: \"project\"|\"all\"\n}\n"));
+    assert!(rendered.contains("open class TaskTargetProject extends TaskTarget {\n  fixed kind: \"project\"\n  id: String\n}\n"));
+    assert!(rendered.contains("open class TaskTargetAll extends TaskTarget {\n  fixed kind: \"all\"\n}\n"));
+
+    // The export is a union of the members again
+    let exported = module.to_json_schema();
+    assert_eq!(
+        exported["definitions"]["TaskDependency"]["oneOf"],
+        json!([{ "$ref": "#/definitions/TaskDependencyProject" }, { "$ref": "#/definitions/TaskDependencyTag" }])
+    );
+    assert_eq!(exported["definitions"]["TaskDependencyTag"]["properties"]["type"], json!({ "const": "tag" }));
+
+    // Objects without a shared tag stay a plain union
+    let untagged = json!({
+        "type": "object",
+        "properties": {
+            "value": { "anyOf": [
+                { "type": "object", "properties": { "a": { "type": "string" } } },
+                { "type": "object", "properties": { "b": { "type": "string" } } }
+            ] }
+        }
+    });
+    let module = to_module(&untagged, "Untagged").unwrap();
+    assert_eq!(module.properties[0].ty.render(), "(UntaggedValue1|UntaggedValue2)?");
+}
+
+#[test]
+fn test_sort_modes() {
+    use space_pklr::pkl_schema::ordering::{SortMode, dependency_order, sort_module};
+
+    let schema = json!({
+        "title": "Workspace",
+        "type": "object",
+        "properties": {
+            "vcs": { "$ref": "#/definitions/Vcs" },
+            "projects": { "type": "array", "items": { "$ref": "#/definitions/Project" } },
+            "kind": { "$ref": "#/definitions/Kind" }
+        },
+        "definitions": {
+            "Vcs": { "type": "object", "properties": { "provider": { "type": "string" } } },
+            "Project": {
+                "type": "object",
+                "properties": { "owner": { "$ref": "#/definitions/Owner" }, "kind": { "$ref": "#/definitions/Kind" } }
+            },
+            "Owner": { "type": "object", "properties": { "team": { "type": "string" } } },
+            "Kind": { "enum": ["app", "lib"] }
+        }
+    });
+    let module = to_module(&schema, "Workspace").unwrap();
+    assert_eq!(dependency_order(&module), vec!["Kind", "Owner", "Project", "Vcs"]);
+
+    let mut dependency = module.clone();
+    sort_module(&mut dependency, SortMode::Dependency);
+    let classes: Vec<&str> = dependency.classes.iter().map(|class| class.name.as_str()).collect();
+    assert_eq!(classes, vec!["Owner", "Project", "Vcs"]);
+    let properties: Vec<&str> = dependency.properties.iter().map(|property| property.name.as_str()).collect();
+    assert_eq!(properties, vec!["kind", "projects", "vcs"]);
+
+    // Sorting is stable however the source orders its definitions
+    let mut reordered = module.clone();
+    reordered.classes.reverse();
+    reordered.properties.reverse();
+    sort_module(&mut reordered, SortMode::Dependency);
+    assert_eq!(reordered.render(), dependency.render());
+
+    let mut source = module.clone();
+    sort_module(&mut source, SortMode::Source);
+    assert_eq!(source, module);
+
+    let config: GeneratorConfig = toml::from_str("sort_mode = \"alphabetical\"").unwrap();
+    assert_eq!(config.sort_mode, SortMode::Alphabetical);
+}
+
+#[test]
+fn test_one_of_unions() {
+    use space_pklr::pkl_schema::unions::{one_of_type, variants_are_disjoint};
+
+    let variants = |types: &[&str]| types.iter().map(|ty| ty.to_string()).collect::<Vec<_>>();
+
+    // Distinct base types can't both match, so `|` is already exact
+    let disjoint = variants(&["String", "Int", "Listing<String>"]);
+    assert!(variants_are_disjoint(&disjoint));
+    assert_eq!(one_of_type(&disjoint), None);
+
+    // Classes, literals and constrained types may overlap
+    assert_eq!(
+        one_of_type(&variants(&["Project", "Tag"])).as_deref(),
+        Some("(Project|Tag)((if (this is Project) 1 else 0) + (if (this is Tag) 1 else 0) == 1)")
+    );
+    assert_eq!(
+        one_of_type(&variants(&["*String", "String(length > 2)"])).as_deref(),
+        Some("(*String|String(length > 2))((if (this is String) 1 else 0) + (if (this is String(length > 2)) 1 else 0) == 1)")
+    );
+    assert!(!variants_are_disjoint(&variants(&["String", "String"])));
+
+    // A nested union counts as its members, and is parenthesized in its check
+    assert_eq!(one_of_type(&variants(&["String|Int", "Boolean"])), None);
+    assert_eq!(
+        one_of_type(&variants(&["String|Int", "Int(isPositive)"])).as_deref(),
+        Some("(String|Int|Int(isPositive))((if (this is (String|Int)) 1 else 0) + (if (this is Int(isPositive)) 1 else 0) == 1)")
+    );
+    assert_eq!(
+        one_of_type(&variants(&["\"a|b\"", "String"])).as_deref(),
+        Some("(\"a|b\"|String)((if (this is \"a|b\") 1 else 0) + (if (this is String) 1 else 0) == 1)")
+    );
+}
+
+#[test]
+fn test_sorted_generation_is_byte_identical() {
+    use space_pklr::pkl_schema::ordering::SortMode;
+
+    let generator = SchemaGenerator::new(GeneratorConfig {
+        sort_mode: SortMode::Dependency,
+        ..Default::default()
+    });
+    let render = |schema: &serde_json::Value| generator.generate_from_json_value(schema, "Service").unwrap().render();
+
+    let first = render(&service_schema());
+    assert_eq!(render(&service_schema()), first);
+
+    // Discovery order in the source doesn't leak into the output
+    let mut module = generator.generate_from_json_value(&service_schema(), "Service").unwrap();
+    module.classes.reverse();
+    module.typealiases.reverse();
+    module.properties.reverse();
+    space_pklr::pkl_schema::ordering::sort_module(&mut module, SortMode::Dependency);
+    assert_eq!(module.render(), first);
+}
@@ -0,0 +1,59 @@
+use space_pklr::msgpack::{MsgValue, decode, encode};
+use space_pklr::pkl_tooling::{PklCli, PklEvaluator, PklSource};
+use std::path::PathBuf;
+
+#[test]
+fn test_msgpack_roundtrip() {
+    let long = "x".repeat(300);
+    let value = MsgValue::Array(vec![
+        MsgValue::from(0x23),
+        MsgValue::map([
+            ("requestId", MsgValue::from(70_000)),
+            ("evaluatorId", MsgValue::from(-5_000_000_000)),
+            ("moduleUri", MsgValue::from("file:///repo/moon.pkl")),
+            ("expr", MsgValue::from(long.as_str())),
+            ("negative", MsgValue::from(-3)),
+            ("flags", MsgValue::Array(vec![MsgValue::Nil, MsgValue::Bool(true), MsgValue::Float(1.5)])),
+            ("result", MsgValue::Bin(vec![0xa2, b'{', b'}'])),
+            ("duration", MsgValue::Ext(1, vec![0; 4])),
+        ]),
+    ]);
+    let mut bytes = Vec::new();
+    encode(&value, &mut bytes);
+
+    let (decoded, used) = decode(&bytes).unwrap().unwrap();
+    assert_eq!(decoded, value);
+    assert_eq!(used, bytes.len());
+    assert_eq!(decoded.as_int(), None);
+    let MsgValue::Array(parts) = &decoded else { unreachable!() };
+    assert_eq!(parts[1].get("requestId").and_then(MsgValue::as_int), Some(70_000));
+    assert_eq!(parts[1].get("expr").and_then(MsgValue::as_str), Some(long.as_str()));
+}
+
+#[test]
+fn test_msgpack_decodes_streams() {
+    let mut bytes = Vec::new();
+    encode(&MsgValue::from("first"), &mut bytes);
+    let first = bytes.len();
+    encode(&MsgValue::map([("requestId", MsgValue::from(2))]), &mut bytes);
+
+    // Values are read one at a time, and a value cut short waits for more bytes
+    assert_eq!(decode(&bytes).unwrap(), Some((MsgValue::from("first"), first)));
+    for end in first..bytes.len() {
+        assert_eq!(decode(&bytes[first..end]).unwrap(), None);
+    }
+    assert!(decode(&bytes[first..]).unwrap().is_some());
+    assert!(decode(&[0xc1]).is_err());
+}
+
+#[tokio::test]
+async fn test_evaluator_falls_back_without_server() {
+    let pkl_cli = PklCli {
+        path: PathBuf::from("/nonexistent/spklr-test/pkl"),
+        source: PklSource::SystemPath,
+        version: None,
+    };
+    let evaluator = PklEvaluator::start(pkl_cli.clone()).await;
+    assert!(matches!(evaluator, PklEvaluator::Cli(_)));
+    assert_eq!(evaluator.pkl_cli().path, pkl_cli.path);
+}
@@ -0,0 +1,69 @@
+use space_pklr::download::sha256_hex;
+use space_pklr::pkl_tooling::{pkldoc_jar_url, pkldoc_module_source, pkldoc_package_info, sort_versions, verify_archive};
+use tempfile::TempDir;
+
+#[tokio::test]
+async fn test_verify_archive() {
+    let dir = TempDir::new().unwrap();
+    let archive = dir.path().join("pkl-cli-linux-amd64.tar.gz");
+    std::fs::write(&archive, b"archive bytes").unwrap();
+    let digest = sha256_hex(b"archive bytes");
+
+    assert_eq!(verify_archive(&archive, Some(&digest)).await.unwrap(), digest);
+    assert!(verify_archive(&archive, Some(&"0".repeat(64))).await.is_err());
+    assert!(verify_archive(&archive, Some("not-a-digest")).await.is_err());
+
+    // Without --sha256 the digest must be recorded next to the archive
+    assert!(verify_archive(&archive, None).await.is_err());
+    std::fs::write(dir.path().join("pkl-cli-linux-amd64.tar.gz.sha256"), format!("{}  pkl-cli-linux-amd64.tar.gz\n", digest.to_uppercase())).unwrap();
+    assert_eq!(verify_archive(&archive, None).await.unwrap(), digest);
+}
+
+#[test]
+fn test_sort_versions_newest_first() {
+    let mut versions: Vec<String> = ["0.9.0", "0.27.1", "0.28.0", "0.27.10", "0.28.0-rc.1"]
+        .iter()
+        .map(|v| v.to_string())
+        .collect();
+    sort_versions(&mut versions);
+    assert_eq!(versions, vec!["0.28.0", "0.28.0-rc.1", "0.27.10", "0.27.1", "0.9.0"]);
+}
+
+#[test]
+fn test_pkldoc_module_source_prefixes_package() {
+    let named = "/// Project config\nopen module Project\n\nname: String\n";
+    assert_eq!(
+        pkldoc_module_source(named, "moon", "ignored"),
+        "/// Project config\nopen module moon.Project\n\nname: String\n"
+    );
+
+    // Without a module clause, one goes after the doc comment and annotations
+    let unnamed = "/// Shared types\n@ModuleInfo { minPklVersion = \"0.28.0\" }\n\ntypealias Id = String\n";
+    assert_eq!(
+        pkldoc_module_source(unnamed, "moon", "Common"),
+        "/// Shared types\n@ModuleInfo { minPklVersion = \"0.28.0\" }\nmodule moon.Common\n\ntypealias Id = String\n"
+    );
+}
+
+#[test]
+fn test_pkldoc_package_info() {
+    let info = pkldoc_package_info("moon", "1.2.0", "package://example.com/moon@1.2.0#/", None, Some("Moon schemas."));
+    assert!(info.starts_with("/// Moon schemas.\namends \"pkl:DocPackageInfo\"\n"));
+    assert!(info.contains("name = \"moon\"\n"));
+    assert!(info.contains("version = \"1.2.0\"\n"));
+    assert!(info.contains("importUri = \"package://example.com/moon@1.2.0#/\"\n"));
+    assert!(info.contains("sourceCodeUrlScheme = \"package://example.com/moon@1.2.0#%{path}\"\n"));
+    assert!(info.contains("authors {}"));
+
+    let linked = pkldoc_package_info("moon", "1.2.0", "file:///schemas/", Some("https://github.com/acme/repo/blob/main/pkl/"), None);
+    assert!(linked.starts_with("amends"));
+    assert!(linked.contains("sourceCodeUrlScheme = \"https://github.com/acme/repo/blob/main/pkl%{path}\"\n"));
+}
+
+#[test]
+fn test_pkldoc_jar_url() {
+    assert_eq!(
+        pkldoc_jar_url("0.28.2"),
+        "https://repo1.maven.org/maven2/org/pkl-lang/pkl-doc/0.28.2/pkl-doc-0.28.2.jar"
+    );
+}
@@ -0,0 +1,129 @@
+use serde_json::json;
+use space_pklr::policy::{
+    BUDGET_MAX_DEPTH, BUDGET_MAX_FILE_SIZE, BUDGET_MAX_TASKS, EXEMPTION_WITHOUT_REASON, Policy, Severity, nesting_depth, select_values,
+};
+use space_pklr::types::MoonConfig;
+use std::path::Path;
+
+fn test_policy() -> Policy {
+    Policy::from_value(json!({
+        "rules": [
+            {
+                "id": "tasks-cache",
+                "appliesTo": ["project"],
+                "select": "tasks.*",
+                "require": "options.cache"
+            },
+            {
+                "id": "no-docker",
+                "appliesTo": ["toolchain"],
+                "forbid": "docker"
+            },
+            {
+                "id": "languages",
+                "select": "language",
+                "allowed": ["rust", "typescript"],
+                "severity": "warning"
+            }
+        ]
+    }))
+    .unwrap()
+}
+
+#[test]
+fn test_policy_reports_violations() {
+    let config = json!({
+        "language": "python",
+        "tasks": {
+            "build": { "options": { "cache": true } },
+            "test": { "command": "pytest" }
+        }
+    });
+
+    let report = test_policy().evaluate(Path::new("moon.yml"), Some(MoonConfig::Project), &config, "");
+
+    assert_eq!(report.violations.len(), 2);
+    assert_eq!(report.error_count(), 1);
+
+    let cache = report.violations.iter().find(|v| v.rule == "tasks-cache").unwrap();
+    assert_eq!(cache.path, "tasks.test");
+    let language = report.violations.iter().find(|v| v.rule == "languages").unwrap();
+    assert_eq!(language.severity, Severity::Warning);
+}
+
+#[test]
+fn test_policy_respects_applies_to_and_annotations() {
+    let config = json!({ "docker": { "version": "24" } });
+
+    let report = test_policy().evaluate(Path::new("toolchain.yml"), Some(MoonConfig::Toolchain), &config, "");
+    assert_eq!(report.error_count(), 1);
+
+    let source = "# spklr:allow(no-docker) legacy image builds\ndocker:\n  version: '24'\n";
+    let report = test_policy().evaluate(Path::new("toolchain.yml"), Some(MoonConfig::Toolchain), &config, source);
+    assert_eq!(report.error_count(), 0);
+    assert_eq!(report.exemptions[0].reason, "legacy image builds");
+
+    // An annotation without a reason exempts nothing
+    let source = "# spklr:allow(no-docker)\ndocker:\n  version: '24'\n";
+    let report = test_policy().evaluate(Path::new("toolchain.yml"), Some(MoonConfig::Toolchain), &config, source);
+    assert!(report.exemptions.is_empty());
+    let rules: Vec<_> = report.violations.iter().map(|v| v.rule.as_str()).collect();
+    assert_eq!(rules, vec![EXEMPTION_WITHOUT_REASON, "no-docker"]);
+    assert_eq!(report.error_count(), 2);
+}
+
+#[test]
+fn test_policy_selectors() {
+    let config = json!({ "a": { "b": [{ "c": 1 }, { "c": 2 }] } });
+
+    let values: Vec<_> = select_values(&config, "a.b.*.c").into_iter().map(|(p, _)| p).collect();
+    assert_eq!(values, vec!["a.b.0.c", "a.b.1.c"]);
+
+    assert_eq!(select_values(&config, "**.c").len(), 2);
+    assert!(select_values(&config, "a.missing")[0].1.is_null());
+}
+
+#[test]
+fn test_policy_rejects_invalid_rules() {
+    assert!(Policy::from_value(json!({ "rules": [{ "forbid": "docker" }] })).is_err());
+    assert!(Policy::from_value(json!({ "rules": [{ "id": "bad", "pattern": "(" }] })).is_err());
+}
+
+#[test]
+fn test_policy_budgets() {
+    let policy = Policy::from_value(json!({
+        "budgets": { "maxTasks": 1, "maxFileSize": 1024, "maxDepth": 3, "severity": "warning" }
+    }))
+    .unwrap();
+    let config = json!({
+        "tasks": {
+            "build": { "options": { "cache": true } },
+            "test": { "command": "pytest" }
+        }
+    });
+    assert_eq!(nesting_depth(&config), 3);
+
+    let report = policy.evaluate(Path::new("moon.yml"), Some(MoonConfig::Project), &config, "");
+    let rules: Vec<_> = report.violations.iter().map(|v| v.rule.as_str()).collect();
+    assert_eq!(rules, vec![BUDGET_MAX_TASKS]);
+    assert_eq!(report.error_count(), 0);
+
+    let deep = json!({ "tasks": { "build": { "options": { "env": { "A": "1" } } } } });
+    let report = policy.evaluate(Path::new("moon.yml"), Some(MoonConfig::Project), &deep, "");
+    assert_eq!(report.violations[0].rule, BUDGET_MAX_DEPTH);
+
+    let source = "# spklr:allow(budget-max-depth) generated from upstream\n";
+    let report = policy.evaluate(Path::new("moon.yml"), Some(MoonConfig::Project), &deep, source);
+    assert!(report.violations.is_empty());
+    assert_eq!(report.exemptions[0].rule, BUDGET_MAX_DEPTH);
+}
+
+#[test]
+fn test_file_size_budget_reports_in_its_unit() {
+    let policy = Policy::from_value(json!({ "budgets": { "maxFileSize": "1KB" } })).unwrap();
+    let source = "a".repeat(1500);
+
+    let report = policy.evaluate(Path::new("moon.yml"), Some(MoonConfig::Project), &json!({}), &source);
+    assert_eq!(report.violations[0].rule, BUDGET_MAX_FILE_SIZE);
+    assert_eq!(report.violations[0].message, "file is 1.5KB, over the budget of 1KB");
+}
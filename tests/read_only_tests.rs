@@ -0,0 +1,71 @@
+use space_pklr::CliError;
+
+// Read-only mode is process-wide, so everything that depends on it lives in one test
+#[test]
+fn test_read_only_blocks_writes_and_locks() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("schema.json");
+
+    space_pklr::atomic_write::write_atomic_sync(&path, "{}").unwrap();
+
+    space_pklr::read_only::enable();
+    assert!(space_pklr::read_only::is_enabled());
+
+    assert!(matches!(
+        space_pklr::atomic_write::write_atomic_sync(&path, "{\"changed\": true}"),
+        Err(CliError::ReadOnly { .. })
+    ));
+    assert!(matches!(
+        space_pklr::lock::PathLock::acquire_with(&path, false),
+        Err(CliError::ReadOnly { .. })
+    ));
+    assert!(matches!(
+        space_pklr::atomic_write::StagedDir::begin(dir.path().join("out")),
+        Err(CliError::ReadOnly { .. })
+    ));
+
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "{}");
+    assert!(!dir.path().join("out").exists());
+
+    // No HTTP client can be created, so no fetch can slip past the gate
+    #[cfg(feature = "network")]
+    assert!(matches!(
+        space_pklr::network::RetryPolicy::default().client(),
+        Err(CliError::ReadOnly { .. })
+    ));
+
+    // Pkl evaluations can't reach the network, from `pkl eval` or the server
+    use space_pklr::msgpack::MsgValue;
+    use space_pklr::pkl_tooling::{PklCli, PklSource, pkl_command};
+
+    let pkl_cli = PklCli {
+        path: std::path::PathBuf::from("pkl"),
+        source: PklSource::SystemPath,
+        version: None,
+    };
+    let command = pkl_command(&pkl_cli, &["eval".to_string(), "-f".to_string(), "json".to_string(), "moon.pkl".to_string()]);
+    let args: Vec<String> = command.get_args().map(|arg| arg.to_string_lossy().to_string()).collect();
+    assert_eq!(
+        args,
+        [
+            "eval",
+            "--no-cache",
+            "--allowed-modules",
+            "pkl:,repl:,file:,modulepath:",
+            "--allowed-resources",
+            "env:,prop:,file:,modulepath:",
+            "-f",
+            "json",
+            "moon.pkl",
+        ]
+    );
+
+    let settings = MsgValue::map(space_pklr::pkl_server::evaluator_settings());
+    for (key, expected) in [
+        ("allowedModules", ["pkl:", "repl:", "file:", "modulepath:"]),
+        ("allowedResources", ["env:", "prop:", "file:", "modulepath:"]),
+    ] {
+        let expected = MsgValue::Array(expected.into_iter().map(MsgValue::from).collect());
+        assert_eq!(settings.get(key), Some(&expected));
+    }
+}
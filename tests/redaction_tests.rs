@@ -0,0 +1,62 @@
+use serde_json::json;
+use space_pklr::redaction::{DEFAULT_PLACEHOLDER, RedactionConfig};
+
+fn config(paths: &[&str]) -> RedactionConfig {
+    RedactionConfig {
+        paths: paths.iter().map(|p| p.to_string()).collect(),
+        placeholder: None,
+    }
+}
+
+#[test]
+fn test_redaction_globs() {
+    let redaction = config(&["RegistryConfig.host", "**.token", "InternalConfig.*.url"]);
+
+    assert!(redaction.matches("RegistryConfig.host"));
+    assert!(!redaction.matches("RegistryConfig.port"));
+    assert!(redaction.matches("token"));
+    assert!(redaction.matches("A.b.c.token"));
+    assert!(redaction.matches("InternalConfig.mirror.url"));
+    assert!(!redaction.matches("InternalConfig.url"));
+}
+
+#[test]
+fn test_redact_schema_defaults_and_examples() {
+    let mut schema = json!({
+        "title": "InternalConfig",
+        "properties": {
+            "host": { "type": "string", "default": "registry.corp.internal" },
+            "mirror": {
+                "type": "object",
+                "properties": { "url": { "type": "string", "examples": ["https://mirror.corp.internal"] } }
+            }
+        },
+        "definitions": {
+            "RegistryConfig": { "properties": { "host": { "default": "npm.corp.internal" }, "port": { "default": 443 } } }
+        }
+    });
+
+    let redaction = RedactionConfig {
+        placeholder: Some("<internal>".to_string()),
+        ..config(&["*.host", "InternalConfig.mirror.url"])
+    };
+    assert_eq!(redaction.redact_schema(&mut schema), 3);
+
+    assert_eq!(schema["properties"]["host"]["default"], "<internal>");
+    assert_eq!(schema["properties"]["mirror"]["properties"]["url"]["examples"], json!(["<internal>"]));
+    assert_eq!(schema["definitions"]["RegistryConfig"]["properties"]["host"]["default"], "<internal>");
+    assert_eq!(schema["definitions"]["RegistryConfig"]["properties"]["port"]["default"], 443);
+}
+
+#[cfg(feature = "docgen")]
+#[test]
+fn test_redact_doc_entries() {
+    let schema = json!({
+        "title": "InternalConfig",
+        "properties": { "host": { "type": "string", "default": "registry.corp.internal" } }
+    });
+    let mut entries = space_pklr::docgen::collect_docs(&schema);
+    config(&["**.host"]).redact_entries(&mut entries);
+
+    assert_eq!(entries[1].examples, vec![json!(DEFAULT_PLACEHOLDER)]);
+}
@@ -0,0 +1,49 @@
+use space_pklr::ci::annotations::Finding;
+use space_pklr::policy::Severity;
+use space_pklr::report::{self, ConvertedFile, FileChange, GeneratedFile, RunReport};
+
+#[test]
+fn test_generated_file_compare() {
+    let added = GeneratedFile::compare("a.json", None, "one\ntwo\n");
+    assert_eq!(added.change, FileChange::Added);
+    assert_eq!((added.lines_added, added.lines_removed), (2, 0));
+
+    let unchanged = GeneratedFile::compare("a.json", Some("one\n"), "one\n");
+    assert_eq!(unchanged.change, FileChange::Unchanged);
+
+    let modified = GeneratedFile::compare("a.json", Some("one\ntwo\nthree\n"), "one\n2\nthree\nfour\n");
+    assert_eq!(modified.change, FileChange::Modified);
+    assert_eq!((modified.lines_added, modified.lines_removed), (2, 1));
+}
+
+#[test]
+fn test_summary_renders_recorded_details() {
+    report::record_conversion(ConvertedFile {
+        input: "moon.yml".into(),
+        output: Some("moon.pkl".into()),
+        from: "yaml".to_string(),
+        to: "pkl".to_string(),
+    });
+    report::record_findings(&[Finding {
+        file: "moon.yml".into(),
+        severity: Severity::Error,
+        rule: "tasks-cache".to_string(),
+        message: "tasks.build: missing options.cache".to_string(),
+    }]);
+    report::record_generated(GeneratedFile::compare("schemas/project.json", Some("a\n"), "b\n"));
+
+    let mut run = RunReport::new("lint");
+    run.collect_details();
+    run.success = false;
+    let summary = run.render_summary();
+
+    assert!(summary.starts_with("## spklr lint ❌ failed"));
+    assert!(summary.contains("| `moon.yml` | `moon.pkl` | yaml | pkl |"));
+    assert!(summary.contains("### Validation failures"));
+    assert!(summary.contains("`tasks-cache`"));
+    assert!(summary.contains("| `schemas/project.json` | modified | +1 −1 |"));
+
+    let json = serde_json::to_value(&run).unwrap();
+    assert_eq!(json["conversions"][0]["to"], "pkl");
+    assert_eq!(json["generated"][0]["change"], "modified");
+}
@@ -0,0 +1,150 @@
+use serde_json::json;
+use space_pklr::pkl_schema::{PklConstraint, PklType, SchemaGenerator, ir};
+use space_pklr::schema_diff::{ChangeKind, accepts, diff_modules, load_modules};
+use tempfile::TempDir;
+
+fn schema() -> serde_json::Value {
+    json!({
+        "title": "Project",
+        "type": "object",
+        "properties": {
+            "language": { "type": "string" },
+            "owner": { "type": "string" },
+            "options": { "$ref": "#/definitions/Options" }
+        },
+        "definitions": {
+            "Options": {
+                "type": "object",
+                "properties": { "cache": { "type": "boolean" }, "retries": { "type": "integer" } }
+            }
+        }
+    })
+}
+
+#[test]
+fn test_diff_modules() {
+    let generator = SchemaGenerator::default();
+    let before = generator.generate_from_json_value(&schema(), "Project").unwrap();
+    let mut after = before.clone();
+    after.properties.retain(|property| property.name != "owner");
+    let options = after.classes.iter_mut().find(|class| class.name == "Options").unwrap();
+    options.properties[0].ty = PklType::Boolean;
+    options.properties[1].default = Some(json!(3));
+    options.properties[1].constraints.push(PklConstraint {
+        expression: "this <= 5".to_string(),
+        provenance: None,
+    });
+    after.typealiases.push(space_pklr::pkl_schema::PklTypeAlias {
+        name: "Kind".to_string(),
+        doc: None,
+        ty: PklType::String,
+    });
+
+    let changes = diff_modules(std::slice::from_ref(&before), std::slice::from_ref(&after));
+    let rendered: Vec<String> = changes.iter().map(ToString::to_string).collect();
+    assert_eq!(
+        rendered,
+        [
+            "+ Project.Kind",
+            "~ Project.Options.cache: type Boolean? → Boolean",
+            "~ Project.Options.retries: constraints none → this <= 5",
+            "~ Project.Options.retries: default none → 3",
+            "- Project.owner",
+        ]
+    );
+    let breaking: Vec<bool> = changes.iter().map(|change| change.breaking).collect();
+    assert_eq!(breaking, [false, true, true, false, true]);
+    assert!(diff_modules(std::slice::from_ref(&before), std::slice::from_ref(&before)).is_empty());
+
+    let removed = diff_modules(&[before], &[]);
+    assert_eq!(removed[0].kind, ChangeKind::Removed);
+    assert_eq!(removed[0].path, "Project");
+}
+
+#[test]
+fn test_compatibility() {
+    let generator = SchemaGenerator::default();
+    let before = generator.generate_from_json_value(&schema(), "Project").unwrap();
+
+    // The reverse of every breaking change is safe
+    let mut after = before.clone();
+    let options = after.classes.iter_mut().find(|class| class.name == "Options").unwrap();
+    options.properties[1].ty = PklType::Number.nullable();
+    options.properties[1].fixed = true;
+    let changes = diff_modules(std::slice::from_ref(&before), std::slice::from_ref(&after));
+    let breaking: Vec<(&str, bool)> =
+        changes.iter().map(|change| (change.detail.as_deref().unwrap(), change.breaking)).collect();
+    assert_eq!(breaking, [("type Int? → Number?", false), ("now fixed", true)]);
+
+    let restored = diff_modules(std::slice::from_ref(&after), std::slice::from_ref(&before));
+    assert!(restored.iter().any(|change| change.breaking));
+    assert!(restored.iter().any(|change| change.detail.as_deref() == Some("no longer fixed") && !change.breaking));
+
+    // New properties break configs only when they're required
+    let mut after = before.clone();
+    let mut optional = before.properties[0].clone();
+    optional.name = "team".to_string();
+    let mut required = optional.clone();
+    required.name = "tier".to_string();
+    required.ty = PklType::String;
+    after.properties.extend([optional, required]);
+    let changes = diff_modules(std::slice::from_ref(&before), std::slice::from_ref(&after));
+    let added: Vec<(&str, bool)> = changes.iter().map(|change| (change.path.as_str(), change.breaking)).collect();
+    assert_eq!(added, [("Project.team", false), ("Project.tier", true)]);
+}
+
+#[test]
+fn test_accepts() {
+    let literal = |value: &str| PklType::StringLiteral(value.to_string());
+    let union = PklType::Union(vec![literal("a"), literal("b")]);
+
+    assert!(accepts(&PklType::String.nullable(), &PklType::String));
+    assert!(!accepts(&PklType::String, &PklType::String.nullable()));
+    assert!(accepts(&PklType::Number, &PklType::Int));
+    assert!(!accepts(&PklType::Int, &PklType::Number));
+    assert!(accepts(&PklType::Any, &PklType::Boolean));
+    assert!(!accepts(&PklType::Boolean, &PklType::Any));
+    assert!(accepts(&PklType::String, &union));
+    assert!(accepts(&PklType::Union(vec![literal("a"), literal("b"), literal("c")]), &union));
+    assert!(!accepts(&PklType::Union(vec![literal("a")]), &union));
+    assert!(accepts(&PklType::Listing(Box::new(PklType::Number)), &PklType::Listing(Box::new(PklType::Int))));
+    assert!(!accepts(&PklType::Named("A".to_string()), &PklType::Named("B".to_string())));
+}
+
+#[test]
+fn test_load_modules_from_directory() {
+    let dir = TempDir::new().unwrap();
+    std::fs::write(dir.path().join("project.json"), schema().to_string()).unwrap();
+    let module = SchemaGenerator::default().generate_from_json_value(&schema(), "Project").unwrap();
+    let mut saved = module.clone();
+    saved.name = "Saved".to_string();
+    std::fs::write(dir.path().join("Saved.ir.json"), ir::to_string(&[saved]).unwrap()).unwrap();
+    std::fs::write(dir.path().join("Project.pkl"), "ignored").unwrap();
+
+    let modules = load_modules(dir.path()).unwrap();
+    let names: Vec<&str> = modules.iter().map(|module| module.name.as_str()).collect();
+    assert_eq!(names, ["Saved", "Project"]);
+    assert!(load_modules(&dir.path().join("Project.pkl")).is_err());
+}
+
+#[test]
+fn test_classify_change() {
+    use space_pklr::schema_diff::classify_change;
+
+    let old = SchemaGenerator::default().generate_from_json_value(&schema(), "Project").unwrap();
+    assert!(classify_change(&old, &old).is_empty());
+
+    // Renamed modules are still compared member by member
+    let mut new = old.clone();
+    new.name = "ProjectV2".to_string();
+    new.properties.retain(|property| property.name != "owner");
+    let changes = classify_change(&old, &new);
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].kind, ChangeKind::Removed);
+    assert_eq!(changes[0].path, "ProjectV2.owner");
+    assert!(changes[0].breaking);
+    assert_eq!(
+        serde_json::to_value(&changes[0]).unwrap(),
+        json!({ "kind": "removed", "path": "ProjectV2.owner", "detail": null, "breaking": true })
+    );
+}
@@ -0,0 +1,52 @@
+#![cfg(feature = "scripting")]
+
+use serde_json::{Value, json};
+use space_pklr::scripting::run_transform_script;
+use space_pklr::types::CliError;
+
+fn run(script: &str, config: Value) -> Result<Value, CliError> {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("transform.rhai");
+    std::fs::write(&path, script).unwrap();
+    run_transform_script(&path, config)
+}
+
+fn config() -> Value {
+    json!({ "language": "rust", "deps": ["core"], "docker": { "version": "24" } })
+}
+
+#[test]
+fn test_script_mutates_config() {
+    let output = run(
+        "config.dependsOn = config.remove(\"deps\");\nconfig.tags = (config.tags ?? []) + [\"migrated\"];\n",
+        config(),
+    )
+    .unwrap();
+    assert_eq!(
+        output,
+        json!({ "language": "rust", "dependsOn": ["core"], "docker": { "version": "24" }, "tags": ["migrated"] })
+    );
+}
+
+#[test]
+fn test_trailing_statement_value_is_ignored() {
+    // `config.remove(...)` evaluates to the removed section, even with the `;`
+    for script in ["config.remove(\"docker\");", "config.remove(\"docker\")", "config.remove(\"docker\");\n42"] {
+        assert_eq!(run(script, config()).unwrap(), json!({ "language": "rust", "deps": ["core"] }), "{}", script);
+    }
+}
+
+#[test]
+fn test_transform_function_returns_new_config() {
+    let script = "config.language = \"typescript\";\n\nfn transform(config) {\n    #{ language: config.language, tags: [\"web\"] }\n}\n";
+    assert_eq!(run(script, config()).unwrap(), json!({ "language": "typescript", "tags": ["web"] }));
+}
+
+#[test]
+fn test_script_errors_name_the_script() {
+    match run("config.tags = ;", config()) {
+        Err(CliError::ScriptError { script, .. }) => assert!(script.ends_with("transform.rhai")),
+        other => panic!("expected a script error, got {:?}", other),
+    }
+    assert!(matches!(run("throw \"nope\";", config()), Err(CliError::ScriptError { .. })));
+}
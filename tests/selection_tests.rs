@@ -0,0 +1,115 @@
+use serde_json::{Value, json};
+use space_pklr::pkl_schema::{PklClass, PklModule, PklProperty, PklType};
+use space_pklr::selection::{select_json_schema, select_module, select_typescript};
+
+fn project_schema() -> String {
+    json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": "ProjectConfig",
+        "type": "object",
+        "properties": {
+            "owners": { "$ref": "#/definitions/OwnersConfig" }
+        },
+        "definitions": {
+            "OwnersConfig": {
+                "type": "object",
+                "properties": {
+                    "defaultOwner": { "anyOf": [{ "$ref": "#/definitions/Owner" }, { "type": "null" }] }
+                }
+            },
+            "Owner": { "type": "string" },
+            "TaskConfig": {
+                "type": "object",
+                "properties": { "command": { "type": "string" } }
+            },
+            "Unused": { "type": "string" }
+        }
+    })
+    .to_string()
+}
+
+#[test]
+fn test_select_json_schema() {
+    let schema = project_schema();
+
+    let selection = select_json_schema(&schema, &["ProjectConfig".to_string()]).unwrap().unwrap();
+    let selected: Value = serde_json::from_str(&selection.content).unwrap();
+    assert_eq!(selection.matched, vec!["ProjectConfig"]);
+    assert_eq!(selected["title"], "ProjectConfig");
+    let mut kept: Vec<&String> = selected["definitions"].as_object().unwrap().keys().collect();
+    kept.sort();
+    assert_eq!(kept, vec!["Owner", "OwnersConfig"]);
+
+    // Selecting a definition drops the root schema
+    let selection = select_json_schema(&schema, &["TaskConfig".to_string()]).unwrap().unwrap();
+    let selected: Value = serde_json::from_str(&selection.content).unwrap();
+    assert!(selected.get("title").is_none());
+    assert!(selected.get("properties").is_none());
+    assert_eq!(selected["$schema"], "http://json-schema.org/draft-07/schema#");
+    assert_eq!(selected["definitions"].as_object().unwrap().len(), 1);
+
+    assert!(select_json_schema(&schema, &["WorkspaceConfig".to_string()]).unwrap().is_none());
+}
+
+#[test]
+fn test_select_typescript() {
+    let ts = "// Automatically generated by schematic.\n\n\
+              /** Who owns a project. */\n\
+              export interface OwnersConfig {\n\
+              \t/** Default owner. */\n\
+              \tdefaultOwner: Owner | null;\n\
+              }\n\n\
+              export type Owner = string;\n\n\
+              export interface ProjectConfig {\n\
+              \towners: OwnersConfig;\n\
+              }\n\n\
+              export interface TaskConfig {\n\
+              \tcommand: string;\n\
+              }\n";
+
+    let selection = select_typescript(ts, &["ProjectConfig".to_string()]).unwrap();
+    assert_eq!(selection.matched, vec!["ProjectConfig"]);
+    assert!(selection.content.starts_with("// Automatically generated by schematic."));
+    assert!(selection.content.contains("/** Who owns a project. */\nexport interface OwnersConfig {"));
+    assert!(selection.content.contains("export type Owner = string;"));
+    assert!(selection.content.contains("export interface ProjectConfig {"));
+    assert!(!selection.content.contains("TaskConfig"));
+
+    let selection = select_typescript(ts, &["TaskConfig".to_string()]).unwrap();
+    assert!(!selection.content.contains("OwnersConfig"));
+    assert!(selection.content.ends_with("\tcommand: string;\n}\n"));
+
+    assert!(select_typescript(ts, &["WorkspaceConfig".to_string()]).is_none());
+}
+
+#[test]
+fn test_select_module() {
+    let class = |name: &str, ty: PklType| PklClass::new(name, None, vec![PklProperty::new("value", None, ty)]);
+    let module = PklModule {
+        name: "Project".to_string(),
+        open: true,
+        properties: vec![PklProperty::new(
+            "owners",
+            None,
+            PklType::Named("OwnersConfig".to_string()).nullable(),
+        )],
+        classes: vec![
+            class("OwnersConfig", PklType::Listing(Box::new(PklType::Named("Owner".to_string())))),
+            class("Owner", PklType::String),
+            class("TaskConfig", PklType::String),
+        ],
+        ..Default::default()
+    };
+
+    let (selected, matched) = select_module(&module, &["OwnersConfig".to_string()]).unwrap();
+    assert_eq!(matched, vec!["OwnersConfig"]);
+    assert!(selected.properties.is_empty());
+    let names: Vec<&str> = selected.classes.iter().map(|class| class.name.as_str()).collect();
+    assert_eq!(names, vec!["OwnersConfig", "Owner"]);
+
+    let (selected, _) = select_module(&module, &["Project".to_string()]).unwrap();
+    assert_eq!(selected.properties.len(), 1);
+    assert_eq!(selected.classes.len(), 2);
+
+    assert!(select_module(&module, &["Workspace".to_string()]).is_none());
+}
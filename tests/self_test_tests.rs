@@ -0,0 +1,28 @@
+use space_pklr::self_test::{Difference, first_difference, fixtures, run};
+
+#[test]
+fn test_fixtures_match_golden_copies() {
+    let outcomes = run();
+    assert_eq!(outcomes.len(), fixtures().len());
+    for outcome in outcomes {
+        assert!(outcome.passed(), "{}: {:?}", outcome.name, outcome.result);
+    }
+}
+
+#[test]
+fn test_first_difference() {
+    assert_eq!(first_difference("a\nb\n", "a\nb\n"), None);
+    assert_eq!(
+        first_difference("a\nb\n", "a\r\nb\r\n"),
+        Some(Difference {
+            line: 1,
+            expected: Some("a\n".to_string()),
+            actual: Some("a\r\n".to_string()),
+        })
+    );
+
+    let missing = first_difference("a\nb\n", "a\n").unwrap();
+    assert_eq!(missing.line, 2);
+    assert_eq!(missing.actual, None);
+    assert_eq!(missing.to_string(), "line 2: expected \"b\\n\", got end of output");
+}
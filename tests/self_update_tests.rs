@@ -0,0 +1,55 @@
+use space_pklr::self_update::{Release, release_asset_name, release_repo, replace_executable, should_install};
+
+#[test]
+fn test_release_asset_name() {
+    assert_eq!(release_asset_name("linux", "x86_64", false), Some("space-pklr-linux-amd64"));
+    assert_eq!(release_asset_name("linux", "x86_64", true), Some("space-pklr-linux-musl"));
+    assert_eq!(release_asset_name("macos", "aarch64", false), Some("space-pklr-macos-aarch64"));
+    assert_eq!(release_asset_name("windows", "x86_64", false), Some("space-pklr-windows-amd64.exe"));
+    assert_eq!(release_asset_name("linux", "aarch64", true), None);
+    assert_eq!(release_asset_name("freebsd", "x86_64", false), None);
+    assert_eq!(release_repo(), "knitli/space-pklr");
+}
+
+#[test]
+fn test_release_from_json() {
+    let release = Release::from_json(&serde_json::json!({
+        "tag_name": "v0.2.0",
+        "assets": [
+            { "name": "space-pklr-linux-amd64", "browser_download_url": "https://example.com/space-pklr-linux-amd64" },
+            { "name": "space-pklr-linux-amd64.sha256", "browser_download_url": "https://example.com/space-pklr-linux-amd64.sha256" },
+        ],
+    }))
+    .unwrap();
+    assert_eq!(release.version, "0.2.0");
+    assert_eq!(release.asset_url("space-pklr-linux-amd64"), Some("https://example.com/space-pklr-linux-amd64"));
+    assert_eq!(release.asset_url("space-pklr-macos-amd64"), None);
+    assert!(Release::from_json(&serde_json::json!({ "message": "Not Found" })).is_none());
+}
+
+#[test]
+fn test_should_install() {
+    assert!(should_install("0.1.0", "0.2.0", false, false));
+    assert!(!should_install("0.2.0", "0.2.0", false, false));
+    assert!(should_install("0.2.0", "0.2.0", false, true));
+    // Downgrades only when asked for
+    assert!(!should_install("0.2.0", "0.1.0", false, false));
+    assert!(should_install("0.2.0", "0.1.0", true, false));
+}
+
+#[test]
+fn test_replace_executable() {
+    let dir = tempfile::tempdir().unwrap();
+    let executable = dir.path().join("spklr");
+    let staged = dir.path().join(".spklr-0.2.0.download");
+    std::fs::write(&executable, "old").unwrap();
+    std::fs::write(&staged, "new").unwrap();
+
+    replace_executable(&staged, &executable).unwrap();
+    assert_eq!(std::fs::read_to_string(&executable).unwrap(), "new");
+    assert!(!staged.exists());
+
+    // A missing download leaves the installed binary alone
+    assert!(replace_executable(&staged, &executable).is_err());
+    assert_eq!(std::fs::read_to_string(&executable).unwrap(), "new");
+}
@@ -0,0 +1,82 @@
+use serde_json::json;
+use space_pklr::task_graph::{GraphFormat, TaskGraphBuilder};
+use std::str::FromStr;
+
+#[test]
+fn test_task_graph_resolves_dep_scopes() {
+    let mut builder = TaskGraphBuilder::new();
+    builder.add_inherited_tasks(&json!({
+        "tasks": {
+            "lint": { "command": "eslint ." }
+        }
+    }));
+    builder.add_project(
+        "shared",
+        &json!({
+            "tasks": { "build": { "command": "tsc" } }
+        }),
+    );
+    builder.add_project(
+        "app-dir",
+        &json!({
+            "id": "app",
+            "dependsOn": ["shared"],
+            "tasks": {
+                "build": { "command": ["vite", "build"], "deps": ["^:build", "lint"] },
+                "test": { "command": "vitest", "deps": [{ "target": "~:build" }, "missing:task"] }
+            }
+        }),
+    );
+
+    let graph = builder.build();
+
+    assert!(graph.nodes.contains_key("app:build"));
+    assert!(graph.nodes.contains_key("app:lint"));
+    assert!(graph.nodes["app:lint"].inherited);
+    assert_eq!(graph.nodes["app:build"].command.as_deref(), Some("vite build"));
+
+    assert!(graph.edges.contains(&("app:build".to_string(), "shared:build".to_string())));
+    assert!(graph.edges.contains(&("app:build".to_string(), "app:lint".to_string())));
+    assert!(graph.edges.contains(&("app:test".to_string(), "app:build".to_string())));
+
+    assert_eq!(graph.unresolved.len(), 1);
+    let unresolved = graph.unresolved.iter().next().unwrap();
+    assert_eq!(unresolved.dep, "missing:task");
+}
+
+#[test]
+fn test_task_graph_respects_inherited_exclude() {
+    let mut builder = TaskGraphBuilder::new();
+    builder.add_project(
+        "app",
+        &json!({ "workspace": { "inheritedTasks": { "exclude": ["lint"] } } }),
+    );
+    builder.add_inherited_tasks(&json!({ "tasks": { "lint": {}, "format": {} } }));
+
+    let graph = builder.build();
+
+    assert!(graph.nodes.contains_key("app:format"));
+    assert!(!graph.nodes.contains_key("app:lint"));
+}
+
+#[test]
+fn test_task_graph_exports() {
+    let mut builder = TaskGraphBuilder::new();
+    builder.add_project(
+        "app",
+        &json!({ "tasks": { "build": {}, "test": { "deps": ["build"] } } }),
+    );
+    let graph = builder.build();
+
+    let dot = graph.render(GraphFormat::Dot).unwrap();
+    assert!(dot.starts_with("digraph tasks {"));
+    assert!(dot.contains("\"app:test\" -> \"app:build\";"));
+
+    let json: serde_json::Value =
+        serde_json::from_str(&graph.render(GraphFormat::Json).unwrap()).unwrap();
+    assert_eq!(json["edges"][0]["from"], "app:test");
+    assert_eq!(json["nodes"].as_array().unwrap().len(), 2);
+
+    assert_eq!(GraphFormat::from_str("graphviz").unwrap(), GraphFormat::Dot);
+    assert!(GraphFormat::from_str("svg").is_err());
+}
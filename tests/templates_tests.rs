@@ -0,0 +1,316 @@
+use space_pklr::templates::module::config_type_for_file;
+use space_pklr::templates::{
+    EngineKind, ModuleInfo, ModuleTemplates, TemplateConfig, TemplateContext, TemplateOverrides, Theme, parse_var,
+    render,
+};
+use space_pklr::types::MoonConfig;
+use std::collections::BTreeMap;
+
+fn context() -> TemplateContext {
+    let mut context = TemplateContext::default();
+    context.variables.insert("team".to_string(), "platform".to_string());
+    context
+}
+
+#[test]
+fn test_parse_var() {
+    assert_eq!(parse_var("Team=platform=infra"), Ok(("team".to_string(), "platform=infra".to_string())));
+    assert!(parse_var("team").is_err());
+    assert!(parse_var("=value").is_err());
+}
+
+#[test]
+fn test_render_variables_and_builtins() {
+    let context = context().for_file("project_schema.ts");
+    let rendered = render("header", "Owned by {{ vars.team }} ({{output.file}}){{ unknown }}", &context, false).unwrap();
+    assert_eq!(rendered, "Owned by platform (project_schema.ts)");
+
+    assert!(render("header", "{{ vars.missing }}", &context, false).is_err());
+    assert!(render("header", "{{ vars.team", &context, false).is_err());
+}
+
+#[test]
+fn test_wrap_uses_output_comment_syntax() {
+    let config = TemplateConfig {
+        header: Some("Generated for {{ vars.team }}\nDo not edit".to_string()),
+        footer: Some("end".to_string()),
+        ..Default::default()
+    };
+
+    let wrapped = config.wrap(&context(), "moon.yml", "language: rust\n").unwrap();
+    assert_eq!(wrapped, "# Generated for platform\n# Do not edit\n\nlanguage: rust\n\n# end\n");
+
+    let wrapped = config.wrap(&context(), "types.ts", "export {};").unwrap();
+    assert!(wrapped.starts_with("// Generated for platform\n"));
+
+    // JSON can't carry comments
+    assert_eq!(config.wrap(&context(), "schema.json", "{}").unwrap(), "{}");
+}
+
+#[test]
+fn test_strict_mode_reports_template_and_position() {
+    let template = "Owned by {{ vars.team }}\nBuilt {{ build.date }}";
+
+    assert_eq!(render("footer", template, &context(), false).unwrap(), "Owned by platform\nBuilt ");
+
+    let error = render("footer", template, &context(), true).unwrap_err();
+    match error {
+        space_pklr::types::CliError::TemplateError { template, message } => {
+            assert_eq!(template, "footer");
+            assert!(message.contains("`build.date` is not defined"));
+            assert!(message.contains("line 2, column 7"));
+        }
+        other => panic!("unexpected error: {}", other),
+    }
+}
+
+#[test]
+fn test_debug_dump_writes_context_and_renders() {
+    let dir = tempfile::tempdir().unwrap();
+    let debug_dir = dir.path().join("debug");
+    space_pklr::templates::enable_debug(debug_dir.clone());
+
+    render("header", "Owned by {{ vars.team }}", &context().for_file("moon.yml"), false).unwrap();
+    assert_eq!(space_pklr::templates::write_debug_dump().unwrap(), Some(debug_dir.clone()));
+
+    let context: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(debug_dir.join("context.json")).unwrap()).unwrap();
+    assert!(context["spklr"]["version"].is_string());
+
+    let renders: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(debug_dir.join("renders.json")).unwrap()).unwrap();
+    let render = renders
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|r| r["output"] == "Owned by platform")
+        .unwrap();
+    assert_eq!(render["template"], "header");
+    assert_eq!(render["file"], "moon.yml");
+    assert!(render["durationMicros"].is_u64());
+}
+
+#[test]
+fn test_engine_selection_from_tool_config() {
+    let dir = tempfile::tempdir().unwrap();
+    let config_path = dir.path().join("spklr.toml");
+    std::fs::write(&config_path, "[templates]\nengine = \"jinja\"\nheader = \"{{ vars.team | upper }}\"\n").unwrap();
+
+    let config = space_pklr::tool_config::ToolConfig::load(&config_path).unwrap();
+    assert_eq!(config.templates.engine, EngineKind::MiniJinja);
+    assert_eq!(space_pklr::templates::engine::engine(config.templates.engine).name(), "minijinja");
+
+    let wrapped = config.templates.wrap(&context(), "moon.yml", "language: rust\n");
+    if cfg!(feature = "jinja_templates") {
+        assert_eq!(wrapped.unwrap(), "# PLATFORM\n\nlanguage: rust\n");
+    } else {
+        assert!(wrapped.is_err());
+    }
+}
+
+#[cfg(feature = "jinja_templates")]
+#[test]
+fn test_minijinja_strict_mode() {
+    use space_pklr::templates::engine::MiniJinjaEngine;
+    use space_pklr::templates::TemplateEngine;
+
+    let template = "{% if vars.team %}Owned by {{ vars.team }}{% endif %}{{ build.date }}";
+    assert_eq!(MiniJinjaEngine.render("header", template, &context(), false).unwrap(), "Owned by platform");
+    assert!(MiniJinjaEngine.render("header", template, &context(), true).is_err());
+}
+
+#[cfg(feature = "jinja_templates")]
+#[test]
+fn test_engines_agree_on_variables() {
+    use space_pklr::templates::TemplateEngine;
+    use space_pklr::templates::engine::{BuiltinEngine, MiniJinjaEngine};
+    use space_pklr::types::CliError;
+
+    let engines: [&dyn TemplateEngine; 2] = [&BuiltinEngine, &MiniJinjaEngine];
+    for engine in engines {
+        for strict in [false, true] {
+            let rendered = engine.render("header", "Owned by {{ vars.team }}", &context(), strict);
+            assert_eq!(rendered.unwrap(), "Owned by platform", "{}", engine.name());
+
+            let error = engine.render("header", "Owned by {{ vars.team }}{{ vars.missing }}", &context(), strict);
+            let Err(CliError::TemplateError { message, .. }) = error else {
+                panic!("{} rendered an undefined variable: {:?}", engine.name(), error);
+            };
+            assert!(message.starts_with("undefined variable `vars.missing`"), "{}", message);
+        }
+    }
+}
+
+#[test]
+fn test_theme_presets() {
+    assert_eq!("Annotations-Heavy".parse::<Theme>().unwrap(), Theme::AnnotationsHeavy);
+    assert!("loud".parse::<Theme>().is_err());
+    for theme in Theme::all() {
+        assert_eq!(theme.to_string().parse::<Theme>().unwrap(), *theme);
+    }
+
+    let compact = TemplateConfig { theme: Some(Theme::Compact), ..Default::default() };
+    let wrapped = compact.wrap(&context(), "moon.yml", "language: rust\n").unwrap();
+    assert!(wrapped.starts_with("# Generated by spklr "));
+    assert!(wrapped.ends_with("language: rust\n"));
+
+    let minimal = TemplateConfig { theme: Some(Theme::MinimalComments), ..Default::default() };
+    assert_eq!(minimal.wrap(&context(), "moon.yml", "language: rust\n").unwrap(), "language: rust\n");
+
+    // Configured templates take precedence over the theme's
+    let overridden = TemplateConfig {
+        header: Some("Owned by {{ vars.team }}".to_string()),
+        theme: Some(Theme::AnnotationsHeavy),
+        ..Default::default()
+    };
+    let wrapped = overridden.wrap(&context(), "types.ts", "export {};").unwrap();
+    assert!(wrapped.starts_with("// Owned by platform\n"));
+    assert!(wrapped.ends_with("// @end types.ts\n"));
+}
+
+#[test]
+fn test_module_info_from_json_schema() {
+    let schema = serde_json::json!({
+        "title": "WorkspaceConfig",
+        "properties": { "projects": {}, "vcs": {} },
+        "definitions": { "VcsConfig": {} }
+    });
+    let module = ModuleInfo::from_json_schema(MoonConfig::Workspace, &schema);
+    assert_eq!(module.name, "Workspace");
+    assert_eq!(module.types, vec!["WorkspaceConfig", "VcsConfig"]);
+    assert_eq!(module.properties, vec!["projects", "vcs"]);
+
+    assert_eq!(config_type_for_file("out/project_schema.ts"), Some(MoonConfig::Project));
+    assert_eq!(config_type_for_file("workspace.pkl"), Some(MoonConfig::Workspace));
+    assert_eq!(config_type_for_file("all.pkl"), None);
+    assert_eq!(config_type_for_file("README.md"), None);
+}
+
+#[test]
+fn test_trailers_append_verbatim_with_module_data() {
+    let mut modules = BTreeMap::new();
+    modules.insert(
+        "workspace".to_string(),
+        ModuleTemplates { trailer: Some("// {{ module.name }}: {{ module.types }}".to_string()) },
+    );
+    let config = TemplateConfig {
+        trailer: Some("output { renderer = new YamlRenderer {} }\n".to_string()),
+        footer: Some("end".to_string()),
+        modules,
+        ..Default::default()
+    };
+    let module = ModuleInfo {
+        name: "Workspace".to_string(),
+        types: vec!["WorkspaceConfig".to_string(), "VcsConfig".to_string()],
+        ..Default::default()
+    };
+
+    let wrapped = config.wrap(&context().for_module(module), "workspace.pkl", "vcs {}").unwrap();
+    assert_eq!(
+        wrapped,
+        "vcs {}\n\n// Workspace: WorkspaceConfig, VcsConfig\n\noutput { renderer = new YamlRenderer {} }\n\n// end\n"
+    );
+
+    // Other modules only get the global trailer
+    let wrapped = config.wrap(&context(), "project.pkl", "id = \"web\"\n").unwrap();
+    assert_eq!(wrapped, "id = \"web\"\n\noutput { renderer = new YamlRenderer {} }\n\n// end\n");
+}
+
+#[test]
+fn test_wrap_with_streams_the_body() {
+    use std::io::Write;
+
+    let config = TemplateConfig {
+        header: Some("Generated".to_string()),
+        trailer: Some("output {}".to_string()),
+        ..Default::default()
+    };
+    let mut out = Vec::new();
+    config
+        .wrap_with(&context(), "project.pkl", &mut out, |out| {
+            write!(out, "id = ").unwrap();
+            write!(out, "\"web\"").unwrap();
+            Ok(())
+        })
+        .unwrap();
+    let streamed = String::from_utf8(out).unwrap();
+    assert_eq!(streamed, "// Generated\n\nid = \"web\"\n\noutput {}\n");
+    assert_eq!(streamed, config.wrap(&context(), "project.pkl", "id = \"web\"").unwrap());
+}
+
+#[test]
+fn test_template_overrides_fall_back_to_builtins() {
+    let dir = tempfile::TempDir::new().unwrap();
+    std::fs::write(
+        dir.path().join("property.tmpl"),
+        "{{ property.doc_comment }}{{ property.identifier }}: {{ property.type }} // owned by {{ vars.team }}",
+    )
+    .unwrap();
+    std::fs::write(dir.path().join("module.tmpl"), "module {{ module.name }}\n\n{{ module.properties }}").unwrap();
+
+    let overrides = TemplateOverrides::load(dir.path(), EngineKind::Builtin, true).unwrap();
+    assert!(overrides.has("property"));
+    assert!(!overrides.has("class"));
+
+    let schema = serde_json::json!({
+        "title": "Deploy",
+        "required": ["region"],
+        "properties": { "region": { "type": "string", "description": "Cloud region" } }
+    });
+    let module = space_pklr::pkl_schema::json_schema::to_module(&schema, "deploy").unwrap();
+    let rendered = module.render_with(&overrides, &context()).unwrap();
+    assert_eq!(rendered, "module Deploy\n\n/// Cloud region\nregion: String // owned by platform\n");
+
+    // Without overrides the built-in rendering is used
+    assert!(module.render().starts_with("open module Deploy\n"));
+    assert!(TemplateOverrides::load(&dir.path().join("missing"), EngineKind::Builtin, false).is_err());
+}
+
+#[test]
+fn test_markdown_docs_pages() {
+    use space_pklr::pkl_schema::imports::split_types;
+    use space_pklr::pkl_schema::json_schema::to_module;
+    use space_pklr::templates::docs::markdown_pages;
+
+    let owner = serde_json::json!({ "type": "object", "description": "Who owns it", "properties": { "team": { "type": "string" } } });
+    let tasks = serde_json::json!({
+        "title": "Tasks",
+        "type": "object",
+        "properties": { "owner": { "$ref": "#/definitions/Owner" } },
+        "definitions": { "Owner": owner }
+    });
+    let project = serde_json::json!({
+        "title": "Project",
+        "description": "A project",
+        "type": "object",
+        "required": ["name"],
+        "properties": {
+            "name": { "type": "string", "description": "Project name\n\nShown in the UI", "examples": ["web"] },
+            "port": { "type": "integer", "default": 8080 },
+            "legacy": { "type": "string", "deprecated": true, "deprecationMessage": "Use name" },
+            "owner": { "$ref": "#/definitions/Owner" }
+        },
+        "definitions": { "Owner": owner }
+    });
+    let mut modules = vec![to_module(&tasks, "tasks").unwrap(), to_module(&project, "project").unwrap()];
+    split_types(&mut modules);
+    modules[1].properties[1].default = Some(serde_json::json!(8080));
+
+    let pages = markdown_pages(&modules);
+    let names: Vec<&str> = pages.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(names, vec!["Tasks.md", "Tasks.Owner.md", "Project.md"]);
+
+    let tasks_page = &pages[0].1;
+    assert!(tasks_page.contains("- [`Owner`](Tasks.Owner.md): Who owns it\n"));
+
+    let project_page = &pages[2].1;
+    assert!(project_page.starts_with("# Project\n\nA project\n\n## Properties\n\n| Property | Type | Default | Description |\n"));
+    assert!(project_page.contains("| [`name`](#name) | `String` |  | Project name |\n"));
+    assert!(project_page.contains("| [`port`](#port) | `Int?` | `8080` |  |\n"));
+    assert!(project_page.contains("| [`legacy`](#legacy) | `String?` |  | **Deprecated.** |\n"));
+    // Imported types link to the page of the module that defines them
+    assert!(project_page.contains("| [`owner`](#owner) | [`tasks.Owner`](Tasks.Owner.md)`?` |"));
+    assert!(project_page.contains("### `name`\n\nProject name\n\nShown in the UI\n\n**Type:** `String`\n\n**Examples:**\n\n```pkl\nname = \"web\"\n```\n"));
+    assert!(project_page.contains("### `port`\n\n**Type:** `Int?`\n\n**Default:** `8080`\n"));
+    assert!(project_page.contains("### `legacy`\n\n> **Deprecated:** Use name\n\n"));
+}
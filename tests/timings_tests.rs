@@ -0,0 +1,29 @@
+use space_pklr::timings::{self, Phase, TimingsReport};
+use std::time::Duration;
+
+#[test]
+fn test_timings_report_aggregates_phases() {
+    timings::enable();
+    timings::record(Phase::Render, Duration::from_millis(5));
+    timings::record(Phase::Render, Duration::from_millis(7));
+    timings::record(Phase::Write, Duration::from_millis(2));
+    let value = timings::time(Phase::PklEval, || 42);
+    assert_eq!(value, 42);
+
+    let report = TimingsReport::collect(Duration::from_millis(20));
+
+    let render = report.phases.iter().find(|t| t.phase == Phase::Render).unwrap();
+    assert_eq!(render.calls, 2);
+    assert!((render.duration_ms - 12.0).abs() < 0.001);
+    assert!(report.phases.iter().any(|t| t.phase == Phase::PklEval));
+    assert!(report.phases.iter().all(|t| t.phase != Phase::Introspection));
+    assert!((report.total_ms - 20.0).abs() < 0.001);
+
+    let table = report.render_table();
+    assert!(table.contains("render"));
+    assert!(table.contains("pkl eval"));
+    assert!(table.contains("peak RSS"));
+
+    let json = serde_json::to_value(&report).unwrap();
+    assert_eq!(json["phases"][0]["phase"], "render");
+}
@@ -0,0 +1,56 @@
+use space_pklr::tool_config::{PluginKind, ToolConfig};
+
+#[test]
+fn test_plugins_resolve_relative_to_config_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let config_path = dir.path().join("spklr.toml");
+    std::fs::write(
+        &config_path,
+        r#"
+[[plugins]]
+name = "hcl"
+path = "plugins/hcl.wasm"
+kind = "renderer"
+
+[[plugins]]
+name = "ini"
+path = "/opt/spklr/ini.wasm"
+kind = "codec"
+"#,
+    )
+    .unwrap();
+
+    let config = ToolConfig::load(&config_path).unwrap();
+
+    let hcl = config.plugin("HCL", PluginKind::Renderer).unwrap();
+    assert_eq!(hcl.path, dir.path().join("plugins/hcl.wasm"));
+
+    let ini = config.plugin("ini", PluginKind::Codec).unwrap();
+    assert_eq!(ini.path, std::path::PathBuf::from("/opt/spklr/ini.wasm"));
+
+    // Kind must match
+    assert!(config.plugin("hcl", PluginKind::Codec).is_none());
+}
+
+#[test]
+fn test_empty_tool_config() {
+    let dir = tempfile::tempdir().unwrap();
+    let config_path = dir.path().join("spklr.toml");
+    std::fs::write(&config_path, "").unwrap();
+
+    let config = ToolConfig::load(&config_path).unwrap();
+    assert!(config.plugins.is_empty());
+}
+
+#[test]
+fn test_invalid_plugin_kind_is_rejected() {
+    let dir = tempfile::tempdir().unwrap();
+    let config_path = dir.path().join("spklr.toml");
+    std::fs::write(
+        &config_path,
+        "[[plugins]]\nname = \"x\"\npath = \"x.wasm\"\nkind = \"linter\"\n",
+    )
+    .unwrap();
+
+    assert!(ToolConfig::load(&config_path).is_err());
+}
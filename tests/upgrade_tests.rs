@@ -0,0 +1,131 @@
+use space_pklr::autofix::{Fix, FixKind, apply};
+use space_pklr::config_path::ConfigPath;
+use space_pklr::config_processor::parse_config_str;
+use space_pklr::types::{MoonConfig, SchemaFormat};
+use space_pklr::upgrade::{MOON_UPGRADE, MoonVersion, plan};
+use std::path::Path;
+
+const PROJECT: &str = "# Project settings
+type: library
+platform: node # runs on node
+tasks:
+  build:
+    command: npm run build
+    platform: node
+";
+
+#[test]
+fn test_moon_version() {
+    let version: MoonVersion = "v1.31.2".parse().unwrap();
+    assert_eq!(version, MoonVersion { major: 1, minor: Some(31) });
+    assert!(version.includes((1, 31)));
+    assert!(!version.includes((1, 39)));
+
+    let latest: MoonVersion = "1.x".parse().unwrap();
+    assert_eq!(latest.to_string(), "1.x");
+    assert!(latest.includes((1, 39)));
+    assert!(!latest.includes((2, 0)));
+    assert!("latest".parse::<MoonVersion>().is_err());
+}
+
+#[test]
+fn test_plan_and_apply_yaml() {
+    let file = Path::new("moon.yml");
+    let value = parse_config_str(PROJECT, &SchemaFormat::Yaml).unwrap();
+
+    let steps = plan(MoonConfig::Project, &value, &"1.31".parse().unwrap());
+    let planned: Vec<String> = steps.iter().map(|step| step.to_string()).collect();
+    assert_eq!(
+        planned,
+        [
+            "moon 1.31: platform → toolchain.default (the project platform is now the default toolchain)",
+            "moon 1.31: tasks.build.platform → tasks.build.toolchain (task `platform` was replaced by `toolchain`)",
+        ]
+    );
+    assert!(plan(MoonConfig::Workspace, &value, &"1.x".parse().unwrap()).is_empty());
+
+    let steps = plan(MoonConfig::Project, &value, &"1.x".parse().unwrap());
+    let fixes: Vec<Fix> = steps.into_iter().map(|step| step.fix).collect();
+    let outcome = apply(file, PROJECT, &fixes);
+    assert!(outcome.skipped.is_empty(), "{:?}", outcome.skipped);
+    assert_eq!(
+        outcome.content,
+        "# Project settings
+layer: library
+toolchain:
+  default: node # runs on node
+tasks:
+  build:
+    command: npm run build
+    toolchain: node
+"
+    );
+    assert!(outcome.preview(file).contains("+toolchain:\n+  default: node # runs on node\n"));
+
+    // Running the plan again finds nothing left to do
+    let upgraded = parse_config_str(&outcome.content, &SchemaFormat::Yaml).unwrap();
+    assert!(plan(MoonConfig::Project, &upgraded, &"1.x".parse().unwrap()).is_empty());
+}
+
+#[test]
+fn test_move_into_existing_parent() {
+    let file = Path::new("moon.yml");
+    let source = "platform: node\ntoolchain:\n  typescript:\n    routeOutDirToCache: true\n";
+    let fix = Fix {
+        rule: MOON_UPGRADE,
+        path: ConfigPath::new("platform"),
+        kind: FixKind::MoveKey { to: ConfigPath::new("toolchain.default") },
+    };
+    let outcome = apply(file, source, std::slice::from_ref(&fix));
+    assert_eq!(outcome.content, "toolchain:\n  default: node\n  typescript:\n    routeOutDirToCache: true\n");
+
+    let conflict = apply(file, "platform: node\ntoolchain:\n  default: bun\n", std::slice::from_ref(&fix));
+    assert!(!conflict.changed());
+    assert_eq!(conflict.skipped[0].1, "`toolchain.default` is already set");
+
+    let nested = apply(file, "platform:\n  name: node\n", &[fix]);
+    assert_eq!(nested.skipped[0].1, "only members on a single line can be moved");
+}
+
+#[test]
+fn test_apply_pkl() {
+    let file = Path::new("moon.pkl");
+    let source = r#"amends "Project.pkl"
+
+type = "library"
+platform = "node"
+tasks {
+  ["build"] {
+    command = "npm run build"
+    platform = "node"
+  }
+}
+"#;
+    let value = serde_json::json!({
+        "type": "library",
+        "platform": "node",
+        "tasks": { "build": { "command": "npm run build", "platform": "node" } }
+    });
+    let fixes: Vec<Fix> = plan(MoonConfig::Project, &value, &"1.x".parse().unwrap())
+        .into_iter()
+        .map(|step| step.fix)
+        .collect();
+    let outcome = apply(file, source, &fixes);
+    assert!(outcome.skipped.is_empty(), "{:?}", outcome.skipped);
+    assert_eq!(
+        outcome.content,
+        r#"amends "Project.pkl"
+
+layer = "library"
+toolchain {
+  default = "node"
+}
+tasks {
+  ["build"] {
+    command = "npm run build"
+    toolchain = "node"
+  }
+}
+"#
+    );
+}
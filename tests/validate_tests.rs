@@ -0,0 +1,64 @@
+#![cfg(feature = "cli")]
+
+use space_pklr::commands::validate::{amending_module, line_span, parse_pkl_error, property_span};
+use space_pklr::types::SchemaFormat;
+use std::path::Path;
+
+const PKL_ERROR: &str = "–– Pkl Error ––
+Type constraint `length > 3` violated.
+Value: \"ab\"
+
+4 | name: String(length > 3)
+                 ^^^^^^^^^^
+at Project#name (file:///schemas/Project.pkl, line 4)
+
+3 | name = \"ab\"
+           ^^^^
+at spklr-validate-x1#owners.name (file:///tmp/spklr-validate-x1.pkl, line 3)
+
+106 | text = renderer.renderDocument(value)
+      ^^^^
+at pkl.base#Module.output.text (pkl:base)
+";
+
+#[test]
+fn test_parse_pkl_error() {
+    let violation = parse_pkl_error(PKL_ERROR, "spklr-validate-x1.pkl");
+    assert_eq!(violation.message, "Type constraint `length > 3` violated.\nValue: \"ab\"");
+    assert_eq!(violation.property.as_deref(), Some("owners.name"));
+    assert_eq!(violation.line, Some(3));
+
+    // Frames outside the evaluated module aren't used
+    let violation = parse_pkl_error(PKL_ERROR, "other.pkl");
+    assert_eq!(violation.property, None);
+    assert_eq!(violation.line, None);
+
+    let violation = parse_pkl_error("Cannot find module `missing.pkl`.\n", "x.pkl");
+    assert_eq!(violation.message, "Cannot find module `missing.pkl`.");
+}
+
+#[test]
+fn test_amending_module() {
+    let schema = Path::new("/schemas/Project.pkl");
+
+    let pkl = amending_module("amends \"old.pkl\"\n\nname = \"ab\"\n", &SchemaFormat::Pkl, schema).unwrap();
+    assert_eq!(pkl, "amends \"file:///schemas/Project.pkl\"\n\n\nname = \"ab\"\n");
+
+    let yaml = amending_module("name: ab\n", &SchemaFormat::Yaml, schema).unwrap();
+    assert!(yaml.starts_with("amends \"file:///schemas/Project.pkl\"\n"));
+    assert!(yaml.contains("name = \"ab\""));
+}
+
+#[test]
+fn test_source_spans() {
+    let yaml = "name: top\nowners:\n  defaultOwner: me\n  name: ab\n";
+
+    let span = property_span(yaml, "owners.name").unwrap();
+    assert_eq!(&yaml[span.offset()..span.offset() + span.len()], "name: ab");
+
+    let span = line_span(yaml, 1).unwrap();
+    assert_eq!(&yaml[span.offset()..span.offset() + span.len()], "name: top");
+
+    assert!(property_span(yaml, "owners.missing").is_none());
+    assert!(line_span(yaml, 99).is_none());
+}
@@ -0,0 +1,87 @@
+#![cfg(feature = "wasm_plugins")]
+
+use space_pklr::CliError;
+use space_pklr::wasm_plugins::{PLUGIN_FUEL, render_with_plugin};
+use std::path::PathBuf;
+
+/// Exports every renderer needs except `spklr_render`: memory, the ABI version, and a
+/// bump allocator
+const PRELUDE: &str = r#"
+    (memory (export "memory") 1)
+    (global $next (mut i32) (i32.const 4096))
+    (func (export "spklr_abi_version") (result i32) (i32.const 1))
+    (func (export "spklr_alloc") (param $len i32) (result i32)
+        (local $ptr i32)
+        (local.set $ptr (global.get $next))
+        (global.set $next (i32.add (global.get $next) (local.get $len)))
+        (local.get $ptr))
+    (func (export "spklr_dealloc") (param i32 i32))
+"#;
+
+/// Compile a plugin module from `body` (after the prelude) into a temporary `.wasm` file
+fn plugin(dir: &tempfile::TempDir, name: &str, body: &str) -> PathBuf {
+    let wasm = wat::parse_str(format!("(module {} {})", PRELUDE, body)).unwrap();
+    let path = dir.path().join(format!("{}.wasm", name));
+    std::fs::write(&path, wasm).unwrap();
+    path
+}
+
+fn plugin_message(result: Result<String, CliError>) -> String {
+    match result {
+        Err(CliError::PluginError { message, .. }) => message,
+        other => panic!("expected a plugin error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_renderer_plugin_returns_output() {
+    let dir = tempfile::tempdir().unwrap();
+    // `{"output":"hello"}` (18 bytes) at offset 1024, returned as (1024 << 32) | 18
+    let module = plugin(
+        &dir,
+        "hello",
+        r#"
+        (data (i32.const 1024) "{\"output\":\"hello\"}")
+        (func (export "spklr_render") (param i32 i32) (result i64)
+            (i64.or (i64.shl (i64.const 1024) (i64.const 32)) (i64.const 18)))
+        "#,
+    );
+
+    let output = render_with_plugin(&module, &serde_json::json!({ "language": "rust" })).unwrap();
+    assert_eq!(output, "hello");
+}
+
+#[test]
+fn test_plugin_without_entry_point_is_refused() {
+    let dir = tempfile::tempdir().unwrap();
+    let module = plugin(&dir, "codec-only", r#"(func (export "spklr_decode") (param i32 i32) (result i64) (i64.const 0))"#);
+
+    let message = plugin_message(render_with_plugin(&module, &serde_json::json!({})));
+    assert!(message.contains("spklr_render"), "{}", message);
+}
+
+#[test]
+fn test_runaway_plugin_runs_out_of_fuel() {
+    let dir = tempfile::tempdir().unwrap();
+    let module = plugin(
+        &dir,
+        "spin",
+        r#"(func (export "spklr_render") (param i32 i32) (result i64) (loop $forever (br $forever)) (unreachable))"#,
+    );
+
+    let message = plugin_message(render_with_plugin(&module, &serde_json::json!({})));
+    assert!(message.contains(&PLUGIN_FUEL.to_string()), "{}", message);
+}
+
+#[test]
+fn test_memory_hungry_plugin_hits_the_limit() {
+    let dir = tempfile::tempdir().unwrap();
+    // 8192 pages of 64 KiB is 512 MiB, over the limit
+    let module = plugin(
+        &dir,
+        "hungry",
+        r#"(func (export "spklr_render") (param i32 i32) (result i64) (drop (memory.grow (i32.const 8192))) (i64.const 0))"#,
+    );
+
+    plugin_message(render_with_plugin(&module, &serde_json::json!({})));
+}